@@ -184,9 +184,9 @@ impl<S: ScoringMatrix> NeedlemanWunsch<S> {
         let mut best_j = ref_len;
 
         // Check last row (query fully aligned)
-        for j in 0..=ref_len {
-            if score_matrix[query_len][j] > max_score {
-                max_score = score_matrix[query_len][j];
+        for (j, &score) in score_matrix[query_len].iter().enumerate().take(ref_len + 1) {
+            if score > max_score {
+                max_score = score;
                 best_i = query_len;
                 best_j = j;
             }
@@ -155,7 +155,7 @@ impl TaxonomyCoverage {
         }
 
         // Sort children by cumulative count (descending)
-        children.sort_by(|a, b| b.cumulative_count.cmp(&a.cumulative_count));
+        children.sort_by_key(|b| std::cmp::Reverse(b.cumulative_count));
 
         TaxonNode {
             taxon_id,
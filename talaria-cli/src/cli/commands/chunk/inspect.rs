@@ -68,7 +68,7 @@ impl ManifestInspector for TemporalManifest {
             .collect();
 
         // Sort by sequence count (descending)
-        all_groups.sort_by(|a, b| b.2.cmp(&a.2));
+        all_groups.sort_by_key(|b| std::cmp::Reverse(b.2));
 
         // Categorize taxa
         let mut model_organisms = Vec::new();
@@ -565,7 +565,7 @@ impl ManifestInspector for talaria_herald::ReductionManifest {
             .collect();
 
         // Sort by sequence count (descending)
-        all_groups.sort_by(|a, b| b.2.cmp(&a.2));
+        all_groups.sort_by_key(|b| std::cmp::Reverse(b.2));
 
         // Categorize taxa
         let mut model_organisms = Vec::new();
@@ -853,7 +853,7 @@ impl ManifestInspector for talaria_herald::ReductionManifest {
 
         // Sort reference chunks by sequence count
         let mut sorted_refs = self.reference_chunks.clone();
-        sorted_refs.sort_by(|a, b| b.sequence_count.cmp(&a.sequence_count));
+        sorted_refs.sort_by_key(|b| std::cmp::Reverse(b.sequence_count));
 
         for (i, chunk) in sorted_refs.iter().take(5).enumerate() {
             output.push_str(&format!(
@@ -1665,7 +1665,7 @@ fn calculate_estimated_centrality(chunk: &ManifestMetadata, avg_sequences: f64)
 }
 
 /// Load full taxonomy database from NCBI dump files
-fn load_taxonomy_db() -> Result<TaxonomyDB> {
+pub(crate) fn load_taxonomy_db() -> Result<TaxonomyDB> {
     // Use the standard taxonomy location
     let taxonomy_dir = talaria_core::system::paths::talaria_databases_dir()
         .join("taxonomy")
@@ -1687,7 +1687,7 @@ fn load_taxonomy_db() -> Result<TaxonomyDB> {
 }
 
 /// Get taxonomy name for a taxon ID
-fn get_taxonomy_name(taxon_id: TaxonId, taxonomy_db: &Option<TaxonomyDB>) -> String {
+pub(crate) fn get_taxonomy_name(taxon_id: TaxonId, taxonomy_db: &Option<TaxonomyDB>) -> String {
     if let Some(db) = taxonomy_db {
         if let Some(taxon_info) = db.get_taxon(taxon_id.0) {
             return taxon_info.scientific_name.clone();
@@ -1,8 +1,11 @@
+use crate::cli::commands::load_filter_taxonomy;
 use crate::cli::formatting::output::*;
 use anyhow::{Context, Result};
 use clap::Args;
 use std::path::PathBuf;
-use talaria_herald::SHA256Hash;
+use talaria_herald::taxonomy::filter::{FilterContext, TaxonomyFilter};
+use talaria_herald::taxonomy::TaxonomyManager;
+use talaria_herald::{SHA256Hash, TaxonId};
 
 /// Trait for different lookup strategies
 pub trait ChunkLookupStrategy {
@@ -43,6 +46,10 @@ pub struct LookupArgs {
     #[arg(long, value_name = "NAME")]
     pub organism: Option<String>,
 
+    /// Taxonomy filter expression (e.g., "rank:genus AND descendants_of(1224)")
+    #[arg(long, value_name = "EXPR")]
+    pub filter: Option<String>,
+
     /// Database to search in (e.g., "uniprot/swissprot")
     #[arg(long, value_name = "DATABASE")]
     pub database: Option<String>,
@@ -119,6 +126,10 @@ struct TaxidLookup(u32);
 struct AccessionLookup(String);
 struct OrganismLookup(String);
 struct DatabaseLookup(String);
+struct FilterLookup {
+    filter: TaxonomyFilter,
+    taxonomy: Option<TaxonomyManager>,
+}
 
 impl ChunkLookupStrategy for HashLookup {
     fn lookup(&self, index: &ChunkIndex) -> Result<Vec<ChunkMatch>> {
@@ -163,7 +174,7 @@ impl ChunkLookupStrategy for TaxidLookup {
         }
 
         // Sort by relevance
-        matches.sort_by(|a, b| b.relevance_score.partial_cmp(&a.relevance_score).unwrap());
+        matches.sort_by(|a, b| b.relevance_score.total_cmp(&a.relevance_score));
         Ok(matches)
     }
 
@@ -211,7 +222,7 @@ impl ChunkLookupStrategy for OrganismLookup {
         }
 
         // Sort by relevance
-        matches.sort_by(|a, b| b.relevance_score.partial_cmp(&a.relevance_score).unwrap());
+        matches.sort_by(|a, b| b.relevance_score.total_cmp(&a.relevance_score));
         Ok(matches)
     }
 
@@ -244,6 +255,44 @@ impl ChunkLookupStrategy for DatabaseLookup {
     }
 }
 
+impl ChunkLookupStrategy for FilterLookup {
+    fn lookup(&self, index: &ChunkIndex) -> Result<Vec<ChunkMatch>> {
+        let mut ctx = FilterContext::new();
+        if let Some(taxonomy) = &self.taxonomy {
+            ctx = ctx.with_resolver(taxonomy);
+        }
+
+        let mut matches = Vec::new();
+        for chunk in index.by_hash.values() {
+            // Relevance is the share of the chunk's taxa that satisfy the filter
+            let matching: f32 = chunk
+                .taxonomy
+                .iter()
+                .filter(|t| self.filter.matches_with(&[TaxonId(t.taxid)], &ctx))
+                .map(|t| t.percentage)
+                .sum();
+
+            if matching > 0.0 {
+                matches.push(ChunkMatch {
+                    chunk: chunk.clone(),
+                    match_reason: format!("Matches filter {}", self.filter),
+                    relevance_score: matching / 100.0,
+                });
+            }
+        }
+
+        matches.sort_by(|a, b| b.relevance_score.total_cmp(&a.relevance_score));
+        Ok(matches)
+    }
+
+    fn description(&self) -> String {
+        format!(
+            "Looking up chunks matching taxonomy filter: {}",
+            self.filter
+        )
+    }
+}
+
 impl ChunkDisplay for ChunkDisplayInfo {
     fn display(&self, detailed: bool) {
         section_header("Chunk Information");
@@ -380,10 +429,15 @@ pub fn run(args: LookupArgs) -> Result<()> {
         Box::new(AccessionLookup(accession.clone()))
     } else if let Some(organism) = &args.organism {
         Box::new(OrganismLookup(organism.clone()))
+    } else if let Some(filter) = &args.filter {
+        Box::new(FilterLookup {
+            filter: TaxonomyFilter::parse(filter)?,
+            taxonomy: load_filter_taxonomy(),
+        })
     } else if let Some(db_filter) = database_filter {
         Box::new(DatabaseLookup(db_filter))
     } else {
-        anyhow::bail!("Please specify a lookup criterion (--hash, --taxid, --accession, --organism, or --database)");
+        anyhow::bail!("Please specify a lookup criterion (--hash, --taxid, --accession, --organism, --filter, or --database)");
     };
 
     // Perform lookup
@@ -662,7 +716,7 @@ fn extract_taxonomy_info(chunk_meta: &talaria_herald::ManifestMetadata) -> Vec<T
         });
     }
 
-    taxonomy.sort_by(|a, b| b.count.cmp(&a.count));
+    taxonomy.sort_by_key(|b| std::cmp::Reverse(b.count));
     taxonomy
 }
 
@@ -0,0 +1,173 @@
+/// `talaria database accessions`: history of retired accessions
+use anyhow::{Context, Result};
+use clap::{Args, Subcommand};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+use talaria_herald::database::DatabaseManager;
+use talaria_herald::storage::accession_history::{
+    parse_ncbi_dead_accessions, parse_ncbi_replaced, parse_uniprot_delac, parse_uniprot_sec_ac,
+    HistoryFile,
+};
+use talaria_herald::storage::ResolutionOutcome;
+
+#[derive(Args)]
+pub struct AccessionsArgs {
+    #[command(subcommand)]
+    pub command: AccessionsCommand,
+}
+
+#[derive(Subcommand)]
+pub enum AccessionsCommand {
+    /// Import merged, deleted and replaced accessions from release files
+    Import(ImportAccessionsArgs),
+
+    /// Follow accessions through their history to the current sequence
+    Resolve(ResolveAccessionsArgs),
+}
+
+#[derive(Args)]
+pub struct ImportAccessionsArgs {
+    /// UniProt sec_ac.txt (secondary accessions from merges and demerges)
+    #[arg(long)]
+    pub sec_ac: Option<PathBuf>,
+
+    /// UniProt delac_sp.txt or delac_tr.txt (deleted accessions)
+    #[arg(long)]
+    pub delac: Option<PathBuf>,
+
+    /// NCBI dead accession table in accession2taxid layout
+    /// (e.g. dead_prot.accession2taxid)
+    #[arg(long)]
+    pub ncbi_dead: Option<PathBuf>,
+
+    /// Replaced accessions, one "old<TAB>new" pair per line
+    #[arg(long)]
+    pub ncbi_replaced: Option<PathBuf>,
+
+    /// Release the files belong to (default: the release named in UniProt
+    /// headers, otherwise today's date)
+    #[arg(long)]
+    pub release: Option<String>,
+}
+
+#[derive(Args)]
+pub struct ResolveAccessionsArgs {
+    /// Accessions to resolve (versions and "gi|N" are accepted)
+    #[arg(required = true)]
+    pub accessions: Vec<String>,
+}
+
+pub fn run(args: AccessionsArgs) -> Result<()> {
+    match args.command {
+        AccessionsCommand::Import(args) => import(args),
+        AccessionsCommand::Resolve(args) => resolve(args),
+    }
+}
+
+type Parser = fn(BufReader<File>) -> Result<HistoryFile>;
+
+fn import(args: ImportAccessionsArgs) -> Result<()> {
+    use crate::cli::formatting::output::*;
+
+    let inputs: Vec<(&Path, Parser, &str)> = [
+        (
+            args.sec_ac.as_deref(),
+            parse_uniprot_sec_ac as Parser,
+            "uniprot",
+        ),
+        (
+            args.delac.as_deref(),
+            parse_uniprot_delac as Parser,
+            "uniprot",
+        ),
+        (
+            args.ncbi_dead.as_deref(),
+            parse_ncbi_dead_accessions as Parser,
+            "ncbi",
+        ),
+        (
+            args.ncbi_replaced.as_deref(),
+            parse_ncbi_replaced as Parser,
+            "ncbi",
+        ),
+    ]
+    .into_iter()
+    .filter_map(|(path, parser, source)| path.map(|path| (path, parser, source)))
+    .collect();
+
+    if inputs.is_empty() {
+        anyhow::bail!("Nothing to import; pass --sec-ac, --delac, --ncbi-dead or --ncbi-replaced");
+    }
+
+    let manager = DatabaseManager::new(None)?;
+    let history = manager
+        .get_repository()
+        .storage
+        .sequence_storage
+        .accession_history();
+
+    section_header("Importing accession history");
+    for (i, (path, parser, source)) in inputs.iter().enumerate() {
+        let file =
+            File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+        let parsed = parser(BufReader::new(file))
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        let release = args
+            .release
+            .clone()
+            .or_else(|| parsed.release.clone())
+            .unwrap_or_else(|| chrono::Utc::now().format("%Y-%m-%d").to_string());
+        let stats = history.import(&parsed, &release, source)?;
+
+        let mut items = vec![
+            ("Release", release),
+            ("New events", stats.recorded.to_string()),
+            ("Already known", stats.unchanged.to_string()),
+        ];
+        if stats.gi_numbers > 0 {
+            items.push(("GI numbers", stats.gi_numbers.to_string()));
+        }
+        tree_section(&path.display().to_string(), items, i == inputs.len() - 1);
+    }
+
+    success("Accession history updated");
+    Ok(())
+}
+
+fn resolve(args: ResolveAccessionsArgs) -> Result<()> {
+    use crate::cli::formatting::output::*;
+
+    let manager = DatabaseManager::new(None)?;
+    let storage = &manager.get_repository().storage.sequence_storage;
+
+    for (i, query) in args.accessions.iter().enumerate() {
+        let resolution = storage.resolve_accession(query)?;
+        let mut items: Vec<(&str, String)> = resolution
+            .steps
+            .iter()
+            .map(|step| ("History", format!("{} was {}", step.accession, step.entry)))
+            .collect();
+        match &resolution.outcome {
+            ResolutionOutcome::Current { accession, hash } => {
+                items.push(("Current", accession.clone()));
+                items.push(("Sequence", hash.to_hex()));
+            }
+            ResolutionOutcome::Deleted { release, .. } => {
+                items.push(("Status", format!("deleted in {}", release)));
+            }
+            ResolutionOutcome::Ambiguous { candidates, .. } => {
+                items.push(("Candidates", candidates.join(", ")));
+            }
+            ResolutionOutcome::NotStored { accession } => {
+                items.push(("Status", format!("{} is not stored locally", accession)));
+            }
+            ResolutionOutcome::Unknown => {
+                items.push(("Status", "not found".to_string()));
+            }
+        }
+        tree_section(query, items, i == args.accessions.len() - 1);
+    }
+    Ok(())
+}
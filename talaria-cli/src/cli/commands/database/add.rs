@@ -1,6 +1,6 @@
 #![allow(dead_code)]
 
-/// Add a custom database from a FASTA file
+/// Add a custom database from one or more FASTA files
 use clap::Args;
 use std::collections::HashMap;
 use std::path::PathBuf;
@@ -10,12 +10,13 @@ const TALARIA_MAGIC: &[u8] = b"TAL\x01";
 
 #[derive(Args)]
 pub struct AddArgs {
-    /// Path to the FASTA file to add as a custom database
-    #[arg(short, long, value_name = "FILE")]
-    pub input: PathBuf,
+    /// FASTA files, directories or glob patterns (e.g. "proteomes/*.faa")
+    /// to add as a custom database
+    #[arg(short, long, value_name = "PATH", num_args = 1.., required = true)]
+    pub input: Vec<PathBuf>,
 
     /// Name for the custom database (e.g., "team-proteins")
-    /// If not specified, uses the filename without extension
+    /// If not specified, uses the file or directory name of a single input
     #[arg(short, long)]
     pub name: Option<String>,
 
@@ -37,9 +38,14 @@ pub struct AddArgs {
     pub version: Option<String>,
 
     /// Replace existing database if it exists
-    #[arg(long)]
+    #[arg(long, conflicts_with = "append")]
     pub replace: bool,
 
+    /// Add the sequences to a new version of an existing database, keeping
+    /// everything in its current version
+    #[arg(long)]
+    pub append: bool,
+
     /// Copy file instead of moving (keeps original in place)
     #[arg(long)]
     pub copy: bool,
@@ -51,6 +57,16 @@ pub struct AddArgs {
     /// Show deduplication statistics
     #[arg(long)]
     pub show_dedup_stats: bool,
+
+    /// Processing pipeline (TOML or YAML) to run on the sequences before storing
+    #[arg(long, value_name = "FILE")]
+    pub pipeline: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub chunking: super::chunking::ChunkingArgs,
+
+    #[command(flatten)]
+    pub ingest: super::ingest::IngestArgs,
 }
 
 pub fn run(args: AddArgs) -> anyhow::Result<()> {
@@ -59,7 +75,7 @@ pub fn run(args: AddArgs) -> anyhow::Result<()> {
     use chrono::Utc;
     use std::sync::Arc;
     use talaria_bio::parse_fasta;
-    use talaria_herald::chunker::{ChunkingStrategy, TaxonomicChunker};
+    use talaria_herald::chunker::TaxonomicChunker;
     use talaria_herald::database::DatabaseManager;
     use talaria_herald::MerkleDAG;
     use talaria_herald::{
@@ -67,29 +83,38 @@ pub fn run(args: AddArgs) -> anyhow::Result<()> {
         SHA256HashExt, SerializedMerkleTree, TemporalManifest, UniProtDatabase,
     };
 
-    // Validate input file
-    if !args.input.exists() {
-        anyhow::bail!("Input file does not exist: {:?}", args.input);
-    }
+    // Expand directories and patterns into the files to read
+    let inputs = super::ingest::discover_inputs(&args.input)?;
+    let mut assigner = super::ingest::TaxonomyAssigner::from_args(&args.ingest)?;
 
-    // Determine database name
-    let db_name = args
-        .name
-        .clone()
-        .or_else(|| {
-            args.input
-                .file_stem()
-                .and_then(|s| s.to_str())
-                .map(|s| s.to_string())
-        })
-        .ok_or_else(|| anyhow::anyhow!("Could not determine database name"))?;
+    let _metrics = crate::cli::metrics::start("add")?;
 
-    let dataset = args.dataset.clone().unwrap_or_else(|| db_name.clone());
-
-    // Initialize paths
     use talaria_core::system::paths;
     let base_path = paths::talaria_databases_dir();
 
+    let pipeline = args
+        .pipeline
+        .as_deref()
+        .map(|path| super::pipeline::LoadedPipeline::load(path, &base_path))
+        .transpose()?;
+
+    // Determine database name
+    let db_name = match (&args.name, args.input.as_slice()) {
+        (Some(name), _) => Some(name.clone()),
+        (None, [input]) if input.is_dir() => input
+            .file_name()
+            .and_then(|s| s.to_str())
+            .map(|s| s.to_string()),
+        (None, [_]) if inputs.len() == 1 => inputs[0]
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .map(|s| s.to_string()),
+        (None, _) => anyhow::bail!("Use --name when adding several inputs"),
+    }
+    .ok_or_else(|| anyhow::anyhow!("Could not determine database name"))?;
+
+    let dataset = args.dataset.clone().unwrap_or_else(|| db_name.clone());
+
     // Create DatabaseManager first (it will initialize HeraldStorage which creates a SequenceStorage)
     let manager = DatabaseManager::new(Some(base_path.to_string_lossy().to_string()))?;
 
@@ -110,14 +135,34 @@ pub fn run(args: AddArgs) -> anyhow::Result<()> {
     let db_base = base_path.join("versions").join(&args.source).join(&dataset);
     let db_path = db_base.join(&version);
 
-    if db_base.exists() && !args.replace {
+    if db_base.exists() && !args.replace && !args.append {
         anyhow::bail!(
-            "Database already exists: {}/{}. Use --replace to overwrite.",
+            "Database already exists: {}/{}. Use --replace to overwrite or --append to add to it.",
             args.source,
             dataset
         );
     }
 
+    // Appending keeps the current version's chunks and skips sequences it already holds
+    let previous = if args.append {
+        let manifest = manager
+            .get_version_manifest(&args.source, &dataset, "current")
+            .map_err(|_| {
+                anyhow::anyhow!(
+                    "Nothing to append to: {}/{} does not exist",
+                    args.source,
+                    dataset
+                )
+            })?;
+        let mut hashes = std::collections::HashSet::new();
+        for chunk in &manifest.chunk_index {
+            hashes.extend(manager.load_manifest(&chunk.hash)?.sequence_refs);
+        }
+        Some((manifest, hashes))
+    } else {
+        None
+    };
+
     info(&format!("Adding database: {}/{}", args.source, dataset));
     println!();
 
@@ -152,6 +197,13 @@ pub fn run(args: AddArgs) -> anyhow::Result<()> {
         DatabaseSource::Custom(format!("{}/{}", args.source, dataset))
     };
 
+    // Resolve chunking; flags become this database's stored override
+    let mut strategy = manager.chunking_strategy(&database_source_enum)?;
+    if args.chunking.is_set() {
+        args.chunking.apply(&mut strategy)?;
+        manager.set_chunking_strategy(&database_source_enum, &strategy)?;
+    }
+
     // Check taxonomy prerequisites
     use talaria_herald::taxonomy::TaxonomyPrerequisites;
     let prereqs = TaxonomyPrerequisites::new();
@@ -161,10 +213,32 @@ pub fn run(args: AddArgs) -> anyhow::Result<()> {
         prereqs.ensure_prerequisites(true)?;
     }
 
-    // Check file size to determine processing path
-    let file_size = std::fs::metadata(&args.input)?.len();
+    // Check the (uncompressed) input size to determine processing path
+    let mut file_size = 0;
+    for input in &inputs {
+        file_size += super::ingest::estimated_input_size(input)?;
+    }
     const STREAMING_THRESHOLD: u64 = 1_000_000_000; // 1GB
 
+    let in_memory_reason = if pipeline.is_some() {
+        Some("--pipeline")
+    } else if args.append {
+        Some("--append")
+    } else if args.ingest.is_set() {
+        Some("Taxonomy assignment")
+    } else if inputs.len() > 1 {
+        Some("Adding several files")
+    } else {
+        None
+    };
+    if let (true, Some(reason)) = (file_size > STREAMING_THRESHOLD, in_memory_reason) {
+        anyhow::bail!(
+            "{} needs the whole input in memory and is not supported above {:.0} GB",
+            reason,
+            STREAMING_THRESHOLD as f64 / 1e9
+        );
+    }
+
     if file_size > STREAMING_THRESHOLD {
         // LARGE FILE PATH: Use streaming mode to avoid OOM
         action(&format!(
@@ -183,7 +257,7 @@ pub fn run(args: AddArgs) -> anyhow::Result<()> {
         // 2. Automatic deduplication
         // 3. Manifest storage
         let mut manager_mut = manager;
-        manager_mut.chunk_database(&args.input, &database_source_enum, Some(&progress_callback))?;
+        manager_mut.chunk_database(&inputs[0], &database_source_enum, Some(&progress_callback))?;
 
         // Flush storage
         manager_mut
@@ -203,24 +277,138 @@ pub fn run(args: AddArgs) -> anyhow::Result<()> {
     }
 
     // SMALL FILE PATH: Use original in-memory path with detailed stats
-    // Read FASTA file
-    action(&format!("Reading FASTA file: {:?}", args.input));
-    let sequences = parse_fasta(&args.input)?;
-    let sequence_count = sequences.len();
+    // Read FASTA files, assigning taxonomy per file
+    let mut sequences = Vec::new();
+    for input in &inputs {
+        action(&format!("Reading FASTA file: {:?}", input));
+        let mut file_sequences = parse_fasta(input)?;
+        if args.ingest.is_set() {
+            assigner.assign(&mut file_sequences, assigner.file_default(input));
+        }
+        sequences.extend(file_sequences);
+    }
 
-    // Check for empty file
-    if sequence_count == 0 {
-        anyhow::bail!("Input file contains no sequences. Please provide a valid FASTA file.");
+    // Check for empty input
+    if sequences.is_empty() {
+        anyhow::bail!("Input contains no sequences. Please provide a valid FASTA file.");
     }
 
+    if inputs.len() > 1 {
+        tree_item(false, "Files read", Some(&format_number(inputs.len())));
+    }
     tree_item(
         false,
         "Sequences read",
-        Some(&format_number(sequence_count)),
+        Some(&format_number(sequences.len())),
     );
 
+    if args.ingest.is_set() {
+        let stats = &assigner.stats;
+        println!();
+        subsection_header("Taxonomy Assignment");
+        if args.ingest.taxid_map.is_some() {
+            tree_item(
+                false,
+                "Mapping entries",
+                Some(&format_number(assigner.mapping_size())),
+            );
+        }
+        tree_item(false, "From mapping", Some(&format_number(stats.from_map)));
+        tree_item(
+            false,
+            "From headers",
+            Some(&format_number(stats.from_header)),
+        );
+        tree_item(
+            false,
+            "From file defaults",
+            Some(&format_number(stats.from_default)),
+        );
+        tree_item(true, "Unassigned", Some(&format_number(stats.unassigned)));
+        if stats.conflicts > 0 {
+            warning(&format!(
+                "{} sequences have a header taxid that differs from the mapping; the mapping was used",
+                format_number(stats.conflicts)
+            ));
+        }
+        if stats.regex_misses > 0 {
+            warning(&format!(
+                "--id-regex did not match {} headers",
+                format_number(stats.regex_misses)
+            ));
+        }
+        for file in assigner.unmatched_files(&inputs) {
+            warning(&format!(
+                "No input file matches '{}' from the sample sheet",
+                file
+            ));
+        }
+    }
+
+    if let Some(pipeline) = &pipeline {
+        sequences = pipeline.apply(sequences)?;
+        println!();
+    }
+
+    if let Some((manifest, hashes)) = &previous {
+        // Sequences the version already holds still record their new
+        // accessions, headers and taxa; only their chunk entries are skipped
+        let (present, new): (Vec<_>, Vec<_>) = sequences
+            .into_iter()
+            .partition(|seq| hashes.contains(&SHA256Hash::compute(&seq.sequence)));
+        sequences = new;
+        tree_item(
+            false,
+            &format!("Already in version {}", manifest.version),
+            Some(&format_number(present.len())),
+        );
+
+        if !present.is_empty() {
+            let records: Vec<(String, String, Option<u32>)> = present
+                .iter()
+                .map(|seq| {
+                    let header = match &seq.description {
+                        Some(description) => format!(">{} {}", seq.id, description),
+                        None => format!(">{}", seq.id),
+                    };
+                    (
+                        String::from_utf8_lossy(&seq.sequence).into_owned(),
+                        header,
+                        seq.taxon_id,
+                    )
+                })
+                .collect();
+            sequence_storage.store_sequences_batch_with_taxa(
+                records
+                    .iter()
+                    .map(|(sequence, header, taxon_id)| {
+                        (
+                            sequence.as_str(),
+                            header.as_str(),
+                            database_source_enum.clone(),
+                            taxon_id.map(talaria_herald::TaxonId),
+                        )
+                    })
+                    .collect(),
+            )?;
+        }
+
+        if sequences.is_empty() {
+            sequence_storage.save_indices()?;
+            sequence_storage.flush()?;
+            println!();
+            info(&format!(
+                "Nothing new to append; {}/{} keeps its chunks, {} representations recorded",
+                args.source,
+                dataset,
+                format_number(present.len())
+            ));
+            return Ok(());
+        }
+    }
+    let sequence_count = sequences.len();
+
     // Create chunker with sequence storage
-    let strategy = ChunkingStrategy::default();
     let mut chunker =
         TaxonomicChunker::new(strategy, sequence_storage, database_source_enum.clone());
 
@@ -268,7 +456,7 @@ pub fn run(args: AddArgs) -> anyhow::Result<()> {
         subsection_header("Deduplication Statistics");
         tree_item(
             false,
-            "Total sequences in file",
+            "Total sequences read",
             Some(&format_number(sequence_count)),
         );
         tree_item(
@@ -306,7 +494,10 @@ pub fn run(args: AddArgs) -> anyhow::Result<()> {
 
     // Convert ChunkManifests to ManifestMetadata for compatibility
     let pb = create_progress_bar(chunk_manifests.len() as u64, "Storing manifests");
-    let mut chunk_infos = Vec::new();
+    let mut chunk_infos = match &previous {
+        Some((manifest, _)) => manifest.chunk_index.clone(),
+        None => Vec::new(),
+    };
 
     for manifest in &chunk_manifests {
         pb.inc(1);
@@ -364,7 +555,9 @@ pub fn run(args: AddArgs) -> anyhow::Result<()> {
         chunk_index: chunk_infos.clone(),
         discrepancies: Vec::new(),
         etag: format!("custom-{}-{}", dataset, version),
-        previous_version: None,
+        previous_version: previous
+            .as_ref()
+            .map(|(manifest, _)| manifest.version.clone()),
     };
 
     // Save manifest to RocksDB using shared function (NO filesystem writes)
@@ -385,6 +578,12 @@ pub fn run(args: AddArgs) -> anyhow::Result<()> {
         sequence_count,
         total_size,
     )?;
+    if let Some(pipeline) = &pipeline {
+        manager.set_version_pipeline(&args.source, &dataset, &version, &pipeline.record)?;
+        if let Some(report) = pipeline.screening_report() {
+            manager.set_version_screening(&args.source, &dataset, &version, &report)?;
+        }
+    }
 
     // Flush RocksDB to ensure data is persisted
     manager
@@ -431,6 +630,9 @@ pub fn run(args: AddArgs) -> anyhow::Result<()> {
         "Chunk manifests",
         Some(&format_number(chunk_infos.len())),
     );
+    if let Some(pipeline) = &pipeline {
+        tree_item(false, "Pipeline", Some(&pipeline.record.name));
+    }
     tree_item(true, "Location", Some(&db_path.display().to_string()));
 
     // Show global repository stats
@@ -1,8 +1,10 @@
 use anyhow::Result;
 use clap::{Args, Subcommand};
 use colored::Colorize;
+use std::path::PathBuf;
 
-use talaria_herald::backup::BackupManager;
+use talaria_core::config::{load_user_config, BackupConfig};
+use talaria_herald::backup::{BackupManager, OffsiteBackup, RestoreDrillReport, RetentionPolicy};
 use talaria_herald::database::DatabaseManager;
 
 #[derive(Debug, Args)]
@@ -20,6 +22,12 @@ pub enum BackupSubcommand {
 
         #[arg(long, help = "Description of why this backup was created")]
         description: Option<String>,
+
+        #[arg(long, help = "Push the backup off-site once it is created")]
+        push: bool,
+
+        #[arg(long, help = "Off-site target (default: [backup] offsite in config)")]
+        target: Option<String>,
     },
 
     /// Restore database from a backup
@@ -32,12 +40,65 @@ pub enum BackupSubcommand {
     List {
         #[arg(long, short = 'd', help = "Show detailed information")]
         detailed: bool,
+
+        #[arg(long, help = "List backups on the off-site target")]
+        offsite: bool,
+
+        #[arg(long, help = "Off-site target (default: [backup] offsite in config)")]
+        target: Option<String>,
     },
 
     /// Verify a backup's integrity
     Verify {
         /// Name of backup to verify
         name: String,
+
+        #[arg(
+            long,
+            value_name = "DIR",
+            help = "Restore into DIR and check every chunk and manifest"
+        )]
+        restore_to: Option<PathBuf>,
+
+        #[arg(
+            long,
+            requires = "restore_to",
+            help = "Download the backup from the off-site target first"
+        )]
+        offsite: bool,
+
+        #[arg(long, help = "Off-site target (default: [backup] offsite in config)")]
+        target: Option<String>,
+    },
+
+    /// Upload a backup off-site, sending only files the target lacks
+    Push {
+        /// Name of backup to push
+        name: String,
+
+        #[arg(long, help = "Off-site target (default: [backup] offsite in config)")]
+        target: Option<String>,
+
+        #[arg(long, help = "Skip applying the retention policy after the push")]
+        no_prune: bool,
+    },
+
+    /// Download a backup from the off-site target
+    Pull {
+        /// Name of backup to pull
+        name: String,
+
+        #[arg(long, help = "Off-site target (default: [backup] offsite in config)")]
+        target: Option<String>,
+    },
+
+    /// Apply the daily/weekly/monthly retention policy off-site
+    Prune {
+        #[arg(long, help = "Off-site target (default: [backup] offsite in config)")]
+        target: Option<String>,
+
+        #[arg(long, help = "Show what would be removed without deleting")]
+        dry_run: bool,
     },
 
     /// Delete a backup
@@ -58,16 +119,18 @@ pub fn execute(cmd: &BackupCommand) -> Result<()> {
     let manager = BackupManager::new()?;
 
     match &cmd.command {
-        BackupSubcommand::Create { name, description } => {
-            // Get RocksDB backend from DatabaseManager
+        BackupSubcommand::Create {
+            name,
+            description,
+            push,
+            target,
+        } => {
             let db_manager = DatabaseManager::new(None)?;
-            let rocksdb = db_manager
-                .get_repository()
-                .storage
-                .sequence_storage
-                .get_rocksdb();
-
-            let metadata = manager.create_backup(&rocksdb, name, description.clone())?;
+            let metadata = manager.create_backup(
+                &db_manager.get_repository().storage,
+                name,
+                description.clone(),
+            )?;
             println!();
             println!("{} Backup created successfully", "✓".green().bold());
             println!("  Name: {}", metadata.name.cyan());
@@ -79,6 +142,10 @@ pub fn execute(cmd: &BackupCommand) -> Result<()> {
                 "  Created: {}",
                 metadata.created_at.format("%Y-%m-%d %H:%M:%S UTC")
             );
+
+            if *push {
+                push_offsite(&manager, name, target.as_deref(), true)?;
+            }
         }
 
         BackupSubcommand::Restore { name } => {
@@ -92,7 +159,15 @@ pub fn execute(cmd: &BackupCommand) -> Result<()> {
             manager.restore_backup(name)?;
         }
 
-        BackupSubcommand::List { detailed } => {
+        BackupSubcommand::List {
+            offsite: true,
+            target,
+            ..
+        } => {
+            list_offsite(&manager, target.as_deref())?;
+        }
+
+        BackupSubcommand::List { detailed, .. } => {
             let backups = manager.list_backups()?;
 
             if backups.is_empty() {
@@ -148,10 +223,62 @@ pub fn execute(cmd: &BackupCommand) -> Result<()> {
             }
         }
 
-        BackupSubcommand::Verify { name } => {
+        BackupSubcommand::Verify {
+            name,
+            restore_to: None,
+            ..
+        } => {
             manager.verify_backup(name)?;
         }
 
+        BackupSubcommand::Verify {
+            name,
+            restore_to: Some(restore_to),
+            offsite,
+            target,
+        } => {
+            let report = if *offsite {
+                // Pull into a scratch backups directory, never the local one
+                let (runtime, offsite, _) = open_offsite(&manager, target.as_deref())?;
+                let scratch = BackupManager::with_dir(restore_to.join("backups"))?;
+                println!("Downloading backup '{}'...", name);
+                runtime.block_on(offsite.pull(name, &scratch))?;
+                scratch.restore_drill(name, &restore_to.join("restore"))?
+            } else {
+                manager.restore_drill(name, restore_to)?
+            };
+            print_drill_report(&report)?;
+        }
+
+        BackupSubcommand::Push {
+            name,
+            target,
+            no_prune,
+        } => {
+            push_offsite(&manager, name, target.as_deref(), !*no_prune)?;
+        }
+
+        BackupSubcommand::Pull { name, target } => {
+            let (runtime, offsite, _) = open_offsite(&manager, target.as_deref())?;
+            let metadata = runtime.block_on(offsite.pull(name, &manager))?;
+            println!();
+            println!("{} Backup pulled", "✓".green().bold());
+            println!("  Name: {}", metadata.name.cyan());
+            println!(
+                "  Created: {}",
+                metadata.created_at.format("%Y-%m-%d %H:%M:%S UTC")
+            );
+            println!(
+                "Restore with: {}",
+                format!("talaria database backup restore {}", name).cyan()
+            );
+        }
+
+        BackupSubcommand::Prune { target, dry_run } => {
+            let (runtime, offsite, config) = open_offsite(&manager, target.as_deref())?;
+            prune_offsite(&runtime, &offsite, &config, *dry_run)?;
+        }
+
         BackupSubcommand::Delete { name } => {
             // Confirm deletion
             use dialoguer::Confirm;
@@ -191,3 +318,136 @@ pub fn execute(cmd: &BackupCommand) -> Result<()> {
 
     Ok(())
 }
+
+/// Connect to the off-site target from `--target` or the `[backup]` config
+fn open_offsite(
+    manager: &BackupManager,
+    target: Option<&str>,
+) -> Result<(tokio::runtime::Runtime, OffsiteBackup, BackupConfig)> {
+    let config = load_user_config()?.backup;
+    let Some(url) = target
+        .map(str::to_string)
+        .or_else(|| config.offsite.clone())
+    else {
+        anyhow::bail!("No off-site target; pass --target or set [backup] offsite in config");
+    };
+
+    let runtime = tokio::runtime::Runtime::new()?;
+    // The S3 client is built on the current runtime
+    let offsite = {
+        let _guard = runtime.enter();
+        OffsiteBackup::from_url(
+            &url,
+            config.region.as_deref(),
+            config.endpoint.as_deref(),
+            manager.backups_dir().join(".offsite"),
+        )?
+    };
+    Ok((runtime, offsite, config))
+}
+
+fn push_offsite(
+    manager: &BackupManager,
+    name: &str,
+    target: Option<&str>,
+    prune: bool,
+) -> Result<()> {
+    let (runtime, offsite, config) = open_offsite(manager, target)?;
+
+    println!();
+    println!("Pushing backup '{}' off-site...", name);
+    let report = runtime.block_on(offsite.push(manager, name))?;
+    println!("{} Backup pushed", "✓".green().bold());
+    println!("  Uploaded: {} files", report.uploaded);
+    println!("  Already off-site: {} files", report.skipped);
+    println!(
+        "  Transferred: {:.2} MB",
+        report.bytes_uploaded as f64 / 1_048_576.0
+    );
+
+    if prune {
+        prune_offsite(&runtime, &offsite, &config, false)?;
+    }
+    Ok(())
+}
+
+fn prune_offsite(
+    runtime: &tokio::runtime::Runtime,
+    offsite: &OffsiteBackup,
+    config: &BackupConfig,
+    dry_run: bool,
+) -> Result<()> {
+    let policy = RetentionPolicy::from_config(config);
+    let report = runtime.block_on(offsite.prune(&policy, dry_run))?;
+
+    println!();
+    println!(
+        "Retention (daily {}, weekly {}, monthly {}): keeping {} backups",
+        policy.daily,
+        policy.weekly,
+        policy.monthly,
+        report.kept.len()
+    );
+    let verb = if dry_run { "Would remove" } else { "Removed" };
+    for name in &report.removed {
+        println!("  {} {}", verb, name.yellow());
+    }
+    if report.deleted_files > 0 {
+        println!(
+            "  {} {} files no retained backup uses",
+            verb, report.deleted_files
+        );
+    }
+    Ok(())
+}
+
+fn list_offsite(manager: &BackupManager, target: Option<&str>) -> Result<()> {
+    let (runtime, offsite, _) = open_offsite(manager, target)?;
+    let entries = runtime.block_on(offsite.list())?;
+
+    println!();
+    if entries.is_empty() {
+        println!("{}", "No off-site backups found.".dimmed());
+        return Ok(());
+    }
+    println!("{} {}", "●".cyan().bold(), "Off-site Backups".bold());
+    println!();
+    for entry in &entries {
+        println!(
+            "  {} {} ({}, pushed {}, {} files)",
+            "●".dimmed(),
+            entry.metadata.name.cyan().bold(),
+            entry.metadata.created_at.format("%Y-%m-%d"),
+            entry.pushed_at.format("%Y-%m-%d %H:%M"),
+            entry.files.len()
+        );
+    }
+    Ok(())
+}
+
+fn print_drill_report(report: &RestoreDrillReport) -> Result<()> {
+    println!();
+    println!("{} {}", "●".cyan().bold(), "Restore Drill".bold());
+    println!("  Backup:            {}", report.name.cyan());
+    println!("  Restored to:       {}", report.restored_to.display());
+    println!("  Chunks verified:   {}", report.chunks_verified);
+    println!("  Manifests checked: {}", report.manifests_checked);
+
+    if !report.passed() {
+        for (manifest, chunk) in report.missing_chunks.iter().take(10) {
+            println!("  {} {} lacks chunk {}", "✗".red(), manifest, chunk);
+        }
+        anyhow::bail!(
+            "Restore drill failed: {} chunks referenced by manifests are missing",
+            report.missing_chunks.len()
+        );
+    }
+
+    println!();
+    println!("{} Restore drill passed", "✓".green().bold());
+    println!(
+        "  The restored copy is left in {} for inspection",
+        report.restored_to.display()
+    );
+    Ok(())
+}
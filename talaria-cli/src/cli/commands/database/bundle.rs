@@ -0,0 +1,256 @@
+use anyhow::Result;
+use clap::{Args, Subcommand};
+use std::path::{Path, PathBuf};
+use talaria_herald::database::bundle::{self, BUNDLE_EXTENSION};
+use talaria_herald::database::{BundleHeader, BundleImportReport, BundleOptions, DatabaseManager};
+use talaria_herald::SHA256Hash;
+
+#[derive(Args)]
+pub struct BundleCommand {
+    #[command(subcommand)]
+    pub command: BundleSubcommand,
+}
+
+#[derive(Subcommand)]
+pub enum BundleSubcommand {
+    /// Pack a database version into a single verifiable archive
+    Create {
+        /// Database reference (e.g., "uniprot/swissprot@2024_04:blast-30")
+        reference: String,
+
+        /// Bundle file to write (default: named after the reference, .tar.zst)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Leave out the taxonomy tree
+        #[arg(long)]
+        no_taxonomy: bool,
+
+        /// Database repository path (default: ${TALARIA_HOME}/databases)
+        #[arg(long)]
+        db_path: Option<PathBuf>,
+    },
+
+    /// Verify a bundle and merge it into the repository
+    Import {
+        /// Bundle file to import
+        file: PathBuf,
+
+        /// Chunk root the bundle must have, as printed by `bundle create`
+        #[arg(long, value_name = "HASH")]
+        expect_root: Option<String>,
+
+        /// Database repository path (default: ${TALARIA_HOME}/databases)
+        #[arg(long)]
+        db_path: Option<PathBuf>,
+    },
+
+    /// Verify a bundle without importing it
+    Verify {
+        /// Bundle file to verify
+        file: PathBuf,
+
+        /// Chunk root the bundle must have, as printed by `bundle create`
+        #[arg(long, value_name = "HASH")]
+        expect_root: Option<String>,
+    },
+
+    /// Show what a bundle contains
+    Info {
+        /// Bundle file to inspect
+        file: PathBuf,
+    },
+}
+
+pub fn run(cmd: BundleCommand) -> Result<()> {
+    match cmd.command {
+        BundleSubcommand::Create {
+            reference,
+            output,
+            no_taxonomy,
+            db_path,
+        } => run_create(&reference, output, no_taxonomy, db_path),
+        BundleSubcommand::Import {
+            file,
+            expect_root,
+            db_path,
+        } => run_import(&file, expect_root.as_deref(), db_path),
+        BundleSubcommand::Verify { file, expect_root } => run_verify(&file, expect_root.as_deref()),
+        BundleSubcommand::Info { file } => run_info(&file),
+    }
+}
+
+fn open_manager(db_path: Option<PathBuf>) -> Result<DatabaseManager> {
+    DatabaseManager::new(db_path.map(|p| p.to_string_lossy().to_string()))
+}
+
+fn parse_root(root: Option<&str>) -> Result<Option<SHA256Hash>> {
+    root.map(|hex| {
+        SHA256Hash::from_hex(hex).map_err(|_| anyhow::anyhow!("Invalid chunk root '{}'", hex))
+    })
+    .transpose()
+}
+
+fn run_create(
+    reference: &str,
+    output: Option<PathBuf>,
+    no_taxonomy: bool,
+    db_path: Option<PathBuf>,
+) -> Result<()> {
+    use crate::cli::formatting::output::*;
+
+    let manager = open_manager(db_path)?;
+    let output = output.unwrap_or_else(|| default_bundle_name(reference));
+    let options = BundleOptions {
+        include_taxonomy: !no_taxonomy,
+    };
+
+    action(&format!("Bundling {}...", reference));
+    let header = bundle::create_bundle(&manager, reference, &output, &options)?;
+
+    print_header(&header);
+    if header.taxonomy_version.is_none() && !no_taxonomy {
+        warning("No taxonomy installed; the bundle carries none");
+    }
+    success(&format!("Wrote {}", output.display()));
+    info(&format!(
+        "Import with: talaria database bundle import {} --expect-root {}",
+        output.display(),
+        header.chunk_root
+    ));
+
+    Ok(())
+}
+
+fn run_import(file: &Path, expect_root: Option<&str>, db_path: Option<PathBuf>) -> Result<()> {
+    use crate::cli::formatting::output::*;
+
+    let expected = parse_root(expect_root)?;
+    if expected.is_none() {
+        warning("No --expect-root given; only the bundle's own consistency is checked");
+    }
+    let manager = open_manager(db_path)?;
+
+    action(&format!("Importing {}...", file.display()));
+    let report = bundle::import_bundle(&manager, file, expected.as_ref())?;
+
+    print_report(&report, true);
+    success(&format!("Imported {}", report.header.label()));
+    if !report.became_current {
+        info(&format!(
+            "Existing current version kept; switch with: talaria database versions set-current {}/{} {}",
+            report.header.source, report.header.dataset, report.header.version
+        ));
+    }
+
+    Ok(())
+}
+
+fn run_verify(file: &Path, expect_root: Option<&str>) -> Result<()> {
+    use crate::cli::formatting::output::*;
+
+    let expected = parse_root(expect_root)?;
+    action(&format!("Verifying {}...", file.display()));
+    let report = bundle::verify_bundle(file, expected.as_ref())?;
+
+    print_report(&report, false);
+    success(&format!(
+        "Bundle is intact ({} chunks, {} sequences)",
+        format_number(report.chunks_imported),
+        format_number(report.sequences_imported)
+    ));
+    if expected.is_none() {
+        warning("No --expect-root given; compare the chunk root with the source");
+    }
+
+    Ok(())
+}
+
+fn run_info(file: &Path) -> Result<()> {
+    let header = bundle::read_bundle_header(file)?;
+    print_header(&header);
+    Ok(())
+}
+
+fn print_header(header: &BundleHeader) {
+    use crate::cli::formatting::output::*;
+
+    section_header("Bundle");
+    let mut items = vec![
+        ("Version", header.version.clone()),
+        ("Chunk root", header.chunk_root.clone()),
+        ("Chunks", format_number(header.chunk_count)),
+        ("Sequences", format_number(header.sequence_count)),
+        (
+            "Created",
+            header
+                .created_at
+                .format("%Y-%m-%d %H:%M:%S UTC")
+                .to_string(),
+        ),
+    ];
+    if let Some(profile) = &header.profile {
+        items.push(("Profile", profile.clone()));
+    }
+    if let Some(taxonomy) = &header.taxonomy_version {
+        items.push(("Taxonomy", taxonomy.clone()));
+    }
+    tree_section(&header.label(), items, true);
+}
+
+fn print_report(report: &BundleImportReport, imported: bool) {
+    use crate::cli::formatting::output::*;
+
+    print_header(&report.header);
+    let mut items = vec![
+        ("Chunks", format_number(report.chunks_imported)),
+        ("Sequences", format_number(report.sequences_imported)),
+    ];
+    if imported {
+        items.push((
+            "Chunks already present",
+            format_number(report.chunks_skipped),
+        ));
+        items.push((
+            "Sequences already present",
+            format_number(report.sequences_skipped),
+        ));
+    }
+    if report.delta_chunks > 0 {
+        items.push(("Delta chunks", format_number(report.delta_chunks)));
+    }
+    if report.taxonomy_installed {
+        items.push(("Taxonomy", "installed".to_string()));
+    }
+    let title = if imported { "Imported" } else { "Verified" };
+    tree_section(title, items, true);
+}
+
+/// `<source>_<dataset>_<version>[_<profile>].tar.zst` in the working directory
+fn default_bundle_name(reference: &str) -> PathBuf {
+    let name: String = reference
+        .chars()
+        .map(|c| match c {
+            '/' | '@' | ':' => '_',
+            c => c,
+        })
+        .collect();
+    PathBuf::from(format!("{}.{}", name, BUNDLE_EXTENSION))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_bundle_name() {
+        assert_eq!(
+            default_bundle_name("uniprot/swissprot@2024_04:blast-30"),
+            PathBuf::from("uniprot_swissprot_2024_04_blast-30.tar.zst")
+        );
+        assert_eq!(
+            default_bundle_name("custom/proteins"),
+            PathBuf::from("custom_proteins.tar.zst")
+        );
+    }
+}
@@ -0,0 +1,129 @@
+/// Chunking flags shared by `database add` and `database download`
+use clap::Args;
+use talaria_herald::chunker::{ChunkBoundaries, ChunkingStrategy, TaxonomicRank};
+use talaria_herald::types::{ChunkStrategy, SpecialTaxon};
+use talaria_herald::TaxonId;
+
+use crate::cli::commands::herald::remote::parse_size;
+
+/// Any flag given here is saved as the database's chunking override and
+/// used for every later import and update of it
+#[derive(Args, Clone, Default)]
+pub struct ChunkingArgs {
+    /// Target chunk size (e.g. 10MB)
+    #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+    pub chunk_size: Option<u64>,
+
+    /// Maximum chunk size (e.g. 64MB for object-store friendly parts)
+    #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+    pub max_chunk_size: Option<u64>,
+
+    /// How chunk boundaries are placed: packed, content-defined
+    #[arg(long, value_name = "POLICY")]
+    pub chunk_boundaries: Option<ChunkBoundaries>,
+
+    /// Keep a taxon and its descendants in their own chunks (repeatable)
+    #[arg(long = "isolate-taxon", value_name = "TAXID")]
+    pub isolate_taxa: Vec<u32>,
+
+    /// Group everything under a taxon at a rank, e.g. 10239:family (repeatable)
+    #[arg(long = "group-taxon", value_name = "TAXID:RANK", value_parser = parse_group_taxon)]
+    pub group_taxa: Vec<(u32, TaxonomicRank)>,
+}
+
+impl ChunkingArgs {
+    /// Whether any chunking flag was given
+    pub fn is_set(&self) -> bool {
+        self.chunk_size.is_some()
+            || self.max_chunk_size.is_some()
+            || self.chunk_boundaries.is_some()
+            || !self.isolate_taxa.is_empty()
+            || !self.group_taxa.is_empty()
+    }
+
+    /// Apply the flags on top of a resolved strategy
+    pub fn apply(&self, strategy: &mut ChunkingStrategy) -> anyhow::Result<()> {
+        if let Some(size) = self.chunk_size {
+            strategy.target_chunk_size = size as usize;
+        }
+        if let Some(size) = self.max_chunk_size {
+            strategy.max_chunk_size = size as usize;
+            // A cap below the target also lowers the target
+            strategy.target_chunk_size = strategy.target_chunk_size.min(strategy.max_chunk_size);
+        }
+        if let Some(boundaries) = self.chunk_boundaries {
+            strategy.boundaries = boundaries;
+        }
+        for &taxon_id in &self.isolate_taxa {
+            strategy.set_special_taxon(SpecialTaxon {
+                taxon_id: TaxonId(taxon_id),
+                name: String::new(),
+                strategy: ChunkStrategy::OwnChunks,
+            });
+        }
+        for &(taxon_id, rank) in &self.group_taxa {
+            strategy.set_special_taxon(SpecialTaxon {
+                taxon_id: TaxonId(taxon_id),
+                name: String::new(),
+                strategy: ChunkStrategy::group_at(rank),
+            });
+        }
+        strategy.validate()
+    }
+}
+
+/// Parse `TAXID:RANK`
+fn parse_group_taxon(s: &str) -> Result<(u32, TaxonomicRank), String> {
+    let (taxon, rank) = s
+        .split_once(':')
+        .ok_or_else(|| format!("expected TAXID:RANK, got '{}'", s))?;
+    let taxon = taxon
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid taxon ID '{}'", taxon))?;
+    let rank =
+        TaxonomicRank::parse(rank.trim()).ok_or_else(|| format!("unknown rank '{}'", rank))?;
+    Ok((taxon, rank))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_group_taxon() {
+        assert_eq!(
+            parse_group_taxon("10239:family").unwrap(),
+            (10239, TaxonomicRank::Family)
+        );
+        assert!(parse_group_taxon("10239").is_err());
+        assert!(parse_group_taxon("viruses:family").is_err());
+        assert!(parse_group_taxon("10239:clade").is_err());
+    }
+
+    #[test]
+    fn test_apply_chunking_args() {
+        let args = ChunkingArgs {
+            max_chunk_size: Some(4 << 20),
+            isolate_taxa: vec![9606],
+            group_taxa: vec![(10239, TaxonomicRank::Family)],
+            ..Default::default()
+        };
+        assert!(args.is_set());
+
+        let mut strategy = ChunkingStrategy::default();
+        args.apply(&mut strategy).unwrap();
+
+        assert_eq!(strategy.max_chunk_size, 4 << 20);
+        assert_eq!(strategy.target_chunk_size, 4 << 20);
+        assert_eq!(
+            strategy.special_taxon(TaxonId(9606)).unwrap().strategy,
+            ChunkStrategy::OwnChunks
+        );
+        assert_eq!(
+            strategy.special_taxon(TaxonId(10239)).unwrap().strategy,
+            ChunkStrategy::group_at(TaxonomicRank::Family)
+        );
+        assert!(!ChunkingArgs::default().is_set());
+    }
+}
@@ -0,0 +1,144 @@
+/// `talaria database compose`: virtual databases defined by set algebra
+use anyhow::Result;
+use clap::Args;
+use talaria_herald::database::{compose, ComposeExpr, DatabaseManager};
+use talaria_herald::taxonomy::filter::TaxonomyResolver;
+use talaria_utils::display::format::format_bytes;
+
+#[derive(Args)]
+pub struct ComposeArgs {
+    /// Virtual database to create a version of ("source/dataset", or a dataset
+    /// name under "virtual")
+    pub name: String,
+
+    /// Set expression, e.g.
+    /// "uniprot/swissprot ∪ custom/lab-proteins − ncbi/refseq-protein ∩ taxon:Bacteria".
+    /// Omit to re-evaluate the current version's expression against the
+    /// current operand versions.
+    pub expression: Option<String>,
+
+    /// Version name (default: UTC timestamp)
+    #[arg(long)]
+    pub version: Option<String>,
+
+    /// Only parse the expression and show how it is grouped
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+pub fn run(args: ComposeArgs) -> Result<()> {
+    use crate::cli::formatting::output::*;
+
+    let (source, dataset) = match args.name.split_once('/') {
+        Some((source, dataset)) => (source.to_string(), dataset.to_string()),
+        None => ("virtual".to_string(), args.name.clone()),
+    };
+    if source.is_empty() || dataset.is_empty() || dataset.contains('/') {
+        anyhow::bail!("Invalid database name '{}'", args.name);
+    }
+
+    let manager = DatabaseManager::new(None)?;
+    let current = manager
+        .resolve_version_reference(&source, &dataset, "current")
+        .ok();
+    let previous = match &current {
+        Some(version) => match manager.version_composition(&source, &dataset, version)? {
+            Some(record) => Some(record),
+            None => anyhow::bail!(
+                "{}/{} is a regular database; pick another name for the virtual database",
+                source,
+                dataset
+            ),
+        },
+        None => None,
+    };
+
+    let text = match (&args.expression, &previous) {
+        (Some(text), _) => text.clone(),
+        (None, Some(record)) => record.expression.clone(),
+        (None, None) => anyhow::bail!(
+            "{}/{} does not exist yet; give an expression to compose it from",
+            source,
+            dataset
+        ),
+    };
+    let expr = ComposeExpr::parse(&text)?;
+
+    section_header(&format!("Composing {}/{}", source, dataset));
+    tree_item(false, "Expression", Some(&expr.to_string()));
+    tree_item(
+        true,
+        "Operands",
+        Some(
+            &expr
+                .databases()
+                .iter()
+                .map(|reference| reference.to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+        ),
+    );
+    if args.dry_run {
+        return Ok(());
+    }
+
+    let taxonomy = if expr.uses_taxonomy() {
+        action("Loading taxonomy...");
+        crate::cli::commands::load_filter_taxonomy()
+    } else {
+        None
+    };
+    let version = args
+        .version
+        .clone()
+        .unwrap_or_else(talaria_core::system::paths::generate_utc_timestamp);
+
+    action("Evaluating expression...");
+    let report = compose::compose_database(
+        &manager,
+        &source,
+        &dataset,
+        &version,
+        &expr,
+        taxonomy.as_ref().map(|t| t as &dyn TaxonomyResolver),
+    )?;
+
+    println!();
+    subsection_header("Operands");
+    for (index, operand) in report.record.operands.iter().enumerate() {
+        tree_item(
+            index + 1 == report.record.operands.len(),
+            &format!("{}@{}", operand.database, operand.requested),
+            Some(&format!(
+                "{} ({} sequences)",
+                operand.version,
+                format_number(operand.sequences)
+            )),
+        );
+    }
+
+    subsection_header("Result");
+    tree_item(false, "Version", Some(&report.version));
+    tree_item(false, "Sequences", Some(&format_number(report.sequences)));
+    tree_item(
+        false,
+        "Chunks",
+        Some(&format!(
+            "{} ({} shared, {} new manifests)",
+            format_number(report.chunks),
+            format_number(report.chunks_shared),
+            format_number(report.chunks_created)
+        )),
+    );
+    tree_item(true, "Size", Some(&format_bytes(report.total_size as u64)));
+
+    success(&format!(
+        "Composed {}/{} without copying sequence data",
+        source, dataset
+    ));
+    info(&format!(
+        "Re-evaluate against newer operand versions with: talaria database compose {}/{}",
+        source, dataset
+    ));
+    Ok(())
+}
@@ -677,6 +677,22 @@ fn generate_report(
             100.0 - comparison.chunk_analysis.shared_percentage_b
         )),
     ]);
+    if let Some(reuse) = comparison.chunk_analysis.reuse_percentage_b() {
+        chunk_table.add_row(vec![
+            Cell::new("Chunk data reused"),
+            Cell::new("-"),
+            Cell::new(format!(
+                "{} ({:.1}%)",
+                format_bytes(comparison.chunk_analysis.reused_bytes_b as usize),
+                reuse
+            )),
+        ]);
+        chunk_table.add_row(vec![
+            Cell::new("Chunk data new"),
+            Cell::new("-"),
+            Cell::new(format_bytes(comparison.chunk_analysis.new_bytes_b as usize)),
+        ]);
+    }
     report = report.section(Section::table("Chunk-Level Analysis", chunk_table));
 
     // Sequence analysis table
@@ -787,6 +803,24 @@ fn display_chunk_analysis(analysis: &talaria_herald::ChunkAnalysis) -> anyhow::R
         ),
     ]);
 
+    // How much of the second version a sync could skip
+    if let Some(reuse) = analysis.reuse_percentage_b() {
+        table.add_row(vec![
+            "Chunk data reused",
+            "-",
+            &format!(
+                "{} ({:.1}%)",
+                format_bytes(analysis.reused_bytes_b as usize),
+                reuse
+            ),
+        ]);
+        table.add_row(vec![
+            "Chunk data new",
+            "-",
+            &format_bytes(analysis.new_bytes_b as usize),
+        ]);
+    }
+
     println!("{}", table);
     Ok(())
 }
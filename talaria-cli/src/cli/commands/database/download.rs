@@ -138,6 +138,9 @@ pub struct DownloadArgs {
     /// Show available versions for the database
     #[arg(long)]
     pub show_versions: bool,
+
+    #[command(flatten)]
+    pub chunking: super::chunking::ChunkingArgs,
 }
 
 impl DownloadArgs {
@@ -176,6 +179,7 @@ impl DownloadArgs {
             mirror: false,
             output_document: None,
             show_versions: false,
+            chunking: Default::default(),
         }
     }
 }
@@ -201,6 +205,8 @@ pub fn run(args: DownloadArgs) -> anyhow::Result<()> {
         return run_complete_taxonomy_download(args);
     }
 
+    let _metrics = crate::cli::metrics::start("download")?;
+
     // Handle --dry-run flag (check for updates without downloading)
     if args.dry_run {
         if let Some(ref database) = args.database {
@@ -210,74 +216,75 @@ pub fn run(args: DownloadArgs) -> anyhow::Result<()> {
         }
     }
 
-    if args.interactive || args.database.is_none() {
-        run_interactive_download(args)
-    } else {
-        // Parse and validate the database reference
-        use talaria_utils::database::database_ref::parse_database_ref;
-        let (source, dataset) = parse_database_ref(args.database.as_ref().unwrap())?;
-
-        // Print header and HERALD info (unless quiet mode)
-        if !args.quiet {
-            use crate::cli::formatting::info_box;
-            use crate::cli::formatting::output::section_header;
-            use colored::Colorize;
-
-            // Format the display name nicely
-            let source_name = match source.as_str() {
-                "uniprot" => "UniProt",
-                "ncbi" => "NCBI",
-                _ => &source,
-            };
-            let dataset_name = match dataset.as_str() {
-                "swissprot" => "SwissProt",
-                "trembl" => "TrEMBL",
-                "uniref50" => "UniRef50",
-                "uniref90" => "UniRef90",
-                "uniref100" => "UniRef100",
-                "idmapping" => "IdMapping",
-                "nr" => "NR",
-                "nt" => "NT",
-                "refseq-protein" => "RefSeq Proteins",
-                "refseq-genomic" => "RefSeq Genomes",
-                "taxonomy" => "Taxonomy",
-                "prot-accession2taxid" => "Protein Accession2TaxId",
-                "nucl-accession2taxid" => "Nucleotide Accession2TaxId",
-                _ => &dataset,
-            };
-
-            println!();
-            section_header(&format!(
-                "▶ Database Download: {}: {}",
-                source_name, dataset_name
-            ));
-            println!("{}", "═".repeat(80).dimmed());
-            println!();
-
-            info_box(
-                "Content-Addressed Storage (HERALD)",
-                &[
-                    "Automatic deduplication",
-                    "Incremental updates",
-                    "Cryptographic verification",
-                    "Bandwidth-efficient downloads",
-                ],
-            );
-            println!();
-        }
+    match args.database.as_deref().filter(|_| !args.interactive) {
+        None => run_interactive_download(args),
+        Some(database) => {
+            // Parse and validate the database reference
+            use talaria_utils::database::database_ref::parse_database_ref;
+            let (source, dataset) = parse_database_ref(database)?;
+
+            // Print header and HERALD info (unless quiet mode)
+            if !args.quiet {
+                use crate::cli::formatting::info_box;
+                use crate::cli::formatting::output::section_header;
+                use colored::Colorize;
+
+                // Format the display name nicely
+                let source_name = match source.as_str() {
+                    "uniprot" => "UniProt",
+                    "ncbi" => "NCBI",
+                    _ => &source,
+                };
+                let dataset_name = match dataset.as_str() {
+                    "swissprot" => "SwissProt",
+                    "trembl" => "TrEMBL",
+                    "uniref50" => "UniRef50",
+                    "uniref90" => "UniRef90",
+                    "uniref100" => "UniRef100",
+                    "idmapping" => "IdMapping",
+                    "nr" => "NR",
+                    "nt" => "NT",
+                    "refseq-protein" => "RefSeq Proteins",
+                    "refseq-genomic" => "RefSeq Genomes",
+                    "taxonomy" => "Taxonomy",
+                    "prot-accession2taxid" => "Protein Accession2TaxId",
+                    "nucl-accession2taxid" => "Nucleotide Accession2TaxId",
+                    _ => &dataset,
+                };
 
-        // Handle custom databases (with taxids) vs regular databases
-        if source == "custom" {
-            run_custom_download(args, dataset)
-        } else {
-            // Use HERALD for regular database downloads
-            use super::download_impl::run_database_download;
-
-            let database_source = talaria_herald::download::parse_database_source(&format!(
-                "{}/{}",
-                source, dataset
-            ))?;
-            run_database_download(args, database_source)
+                println!();
+                section_header(&format!(
+                    "▶ Database Download: {}: {}",
+                    source_name, dataset_name
+                ));
+                println!("{}", "═".repeat(80).dimmed());
+                println!();
+
+                info_box(
+                    "Content-Addressed Storage (HERALD)",
+                    &[
+                        "Automatic deduplication",
+                        "Incremental updates",
+                        "Cryptographic verification",
+                        "Bandwidth-efficient downloads",
+                    ],
+                );
+                println!();
+            }
+
+            // Handle custom databases (with taxids) vs regular databases
+            if source == "custom" {
+                run_custom_download(args, dataset)
+            } else {
+                // Use HERALD for regular database downloads
+                use super::download_impl::run_database_download;
+
+                let database_source = talaria_herald::download::parse_database_source(&format!(
+                    "{}/{}",
+                    source, dataset
+                ))?;
+                run_database_download(args, database_source)
+            }
         }
     }
 }
@@ -348,6 +355,7 @@ fn run_complete_taxonomy_download(args: DownloadArgs) -> anyhow::Result<()> {
                     mirror: args.mirror,
                     output_document: args.output_document.clone(),
                     show_versions: args.show_versions,
+                    chunking: Default::default(), // Taxonomy components are not chunked
                 };
 
                 match run_database_download(component_args, database_source) {
@@ -796,7 +804,7 @@ fn download_uniprot_interactive(output_dir: &PathBuf) -> anyhow::Result<()> {
     use crate::cli::interactive::{show_info, show_success};
     use dialoguer::{theme::ColorfulTheme, Confirm, Select};
 
-    let datasets = vec![
+    let datasets = [
         (
             "swissprot",
             "SwissProt",
@@ -877,7 +885,7 @@ fn download_ncbi_interactive(output_dir: &PathBuf) -> anyhow::Result<()> {
     use crate::cli::interactive::{show_info, show_success};
     use dialoguer::{theme::ColorfulTheme, Select};
 
-    let datasets = vec![
+    let datasets = [
         ("nr", "NR", "Non-redundant protein sequences (~90GB)"),
         ("nt", "NT", "Nucleotide sequences (~70GB)"),
         (
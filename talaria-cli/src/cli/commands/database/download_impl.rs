@@ -78,6 +78,13 @@ pub fn run_database_download(args: DownloadArgs, database_source: DatabaseSource
     tracing::debug!("Ensuring version integrity for {}", database_source);
     manager.ensure_version_integrity(&database_source)?;
 
+    // Chunking flags become this database's stored override
+    if args.chunking.is_set() && !args.dry_run {
+        let mut strategy = manager.chunking_strategy(&database_source)?;
+        args.chunking.apply(&mut strategy)?;
+        manager.set_chunking_strategy(&database_source, &strategy)?;
+    }
+
     // Check for resumable operations BEFORE creating the task list UI
     // Declare workspace_state outside the blocks so it can be used later
     use talaria_herald::download::find_existing_workspace_for_source;
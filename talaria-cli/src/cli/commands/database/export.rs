@@ -9,6 +9,7 @@ use clap::Args;
 use std::io::{BufWriter, Write};
 use std::path::{Path, PathBuf};
 
+use crate::cli::commands::load_filter_taxonomy;
 use crate::cli::formatting::output::{info as print_info, success as print_success};
 use crate::cli::progress::create_spinner;
 use talaria_bio::taxonomy::{StandardTaxonomyFormatter, TaxonomyFormatter};
@@ -16,6 +17,9 @@ use talaria_core::system::paths;
 use talaria_herald::database::DatabaseManager;
 use talaria_herald::manifest::Manifest;
 use talaria_herald::operations::FastaAssembler;
+use talaria_herald::taxonomy::filter::{FilterContext, TaxonomyFilter};
+use talaria_herald::taxonomy::TaxonomyManager;
+use talaria_herald::TaxonId;
 use talaria_utils::database::database_ref::{parse_database_reference, DatabaseReference};
 
 #[derive(Args)]
@@ -73,7 +77,9 @@ pub struct ExportArgs {
     #[arg(long)]
     pub taxonomy_date: Option<String>,
 
-    /// Filter by taxonomy expression (e.g., "Bacteria AND NOT Escherichia")
+    /// Filter by taxonomy expression (e.g., "Bacteria AND NOT Escherichia",
+    /// "rank:genus", "lineage:Bacteria/Proteobacteria/*", "descendants_of(1224)",
+    /// "depth<=5", "length>=100")
     #[arg(long)]
     pub taxonomy_filter: Option<String>,
 
@@ -197,9 +203,25 @@ fn perform_export(
     db_ref: &DatabaseReference,
     output_path: &Path,
 ) -> Result<ExportStats> {
+    // The taxonomy is loaded once and shared by chunk and sequence filtering
+    let taxonomy_filter = args
+        .taxonomy_filter
+        .as_deref()
+        .map(TaxonomyFilter::parse)
+        .transpose()?;
+    let taxonomy = taxonomy_filter
+        .as_ref()
+        .and_then(|_| load_filter_taxonomy());
+
     // Check if we need bi-temporal export
     if args.sequence_date.is_some() || args.taxonomy_date.is_some() {
-        return perform_bitemporal_export(args, db_ref, output_path);
+        return perform_bitemporal_export(
+            args,
+            db_ref,
+            output_path,
+            taxonomy_filter.as_ref(),
+            taxonomy.as_ref(),
+        );
     }
 
     // Initialize database manager
@@ -288,6 +310,30 @@ fn perform_export(
         manifest_data
     };
 
+    // Restrict to chunks where at least one taxon can match; mixed chunks are
+    // refined sequence by sequence during export
+    let filtered_manifest_owned;
+    let final_manifest_data = if let Some(filter) = &taxonomy_filter {
+        let mut filtered = final_manifest_data.clone();
+        filtered
+            .chunk_index
+            .retain(|chunk| matches_taxonomy_filter(chunk, filter, taxonomy.as_ref()));
+
+        if !args.quiet {
+            print_info(&format!(
+                "Taxonomy filter {} selected {}/{} chunks",
+                filter,
+                filtered.chunk_index.len(),
+                final_manifest_data.chunk_index.len()
+            ));
+        }
+
+        filtered_manifest_owned = filtered;
+        &filtered_manifest_owned
+    } else {
+        final_manifest_data
+    };
+
     // Create assembler using the HERALD storage (use open to rebuild index)
     let herald_storage = talaria_herald::HeraldStorage::open(&base_path)?;
     let assembler = FastaAssembler::new(&herald_storage);
@@ -301,7 +347,8 @@ fn perform_export(
             &args.format,
             args.compress,
             args.with_taxonomy,
-            args,
+            taxonomy_filter.as_ref(),
+            taxonomy.as_ref(),
         )?
     } else {
         export_full(
@@ -312,6 +359,8 @@ fn perform_export(
             args.compress,
             args.with_taxonomy,
             args,
+            taxonomy_filter.as_ref(),
+            taxonomy.as_ref(),
         )?
     };
 
@@ -425,7 +474,8 @@ fn export_streamed(
     format: &ExportFormat,
     compress: bool,
     _with_taxonomy: bool,
-    _args: &ExportArgs,
+    filter: Option<&TaxonomyFilter>,
+    taxonomy: Option<&TaxonomyManager>,
 ) -> Result<usize> {
     use flate2::write::GzEncoder;
     use flate2::Compression;
@@ -447,12 +497,30 @@ fn export_streamed(
         .map(|c| c.hash.clone())
         .collect();
 
-    // Stream assembly directly to writer
-    let total_sequences = match format {
-        ExportFormat::Fasta => assembler.stream_assembly(&chunk_hashes, &mut writer)?,
-        _ => {
-            anyhow::bail!("Streaming export only supports FASTA format currently");
+    if !matches!(format, ExportFormat::Fasta) {
+        anyhow::bail!("Streaming export only supports FASTA format currently");
+    }
+
+    // Stream assembly directly to writer; with a taxonomy filter, chunks are
+    // assembled one at a time so mixed chunks can be filtered per sequence
+    let total_sequences = if let Some(filter) = filter {
+        let mut total_sequences = 0;
+        for hash in &chunk_hashes {
+            for seq in assembler.assemble_from_chunks(std::slice::from_ref(hash))? {
+                if !sequence_matches(&seq, filter, taxonomy) {
+                    continue;
+                }
+                match &seq.description {
+                    Some(desc) => writeln!(writer, ">{} {}", seq.id, desc)?,
+                    None => writeln!(writer, ">{}", seq.id)?,
+                }
+                writeln!(writer, "{}", String::from_utf8_lossy(&seq.sequence))?;
+                total_sequences += 1;
+            }
         }
+        total_sequences
+    } else {
+        assembler.stream_assembly(&chunk_hashes, &mut writer)?
     };
 
     writer.flush()?;
@@ -467,6 +535,8 @@ fn export_full(
     compress: bool,
     with_taxonomy: bool,
     args: &ExportArgs,
+    filter: Option<&TaxonomyFilter>,
+    taxonomy: Option<&TaxonomyManager>,
 ) -> Result<usize> {
     use flate2::write::GzEncoder;
     use flate2::Compression;
@@ -481,6 +551,11 @@ fn export_full(
     // Assemble all sequences
     let mut sequences = assembler.assemble_from_chunks(&chunk_hashes)?;
 
+    // Chunks were pre-filtered; refine per sequence where taxon and length are known
+    if let Some(filter) = filter {
+        sequences.retain(|seq| sequence_matches(seq, filter, taxonomy));
+    }
+
     // Apply redundancy reduction if requested
     if let Some(redundancy) = args.redundancy {
         sequences = apply_redundancy_reduction(sequences, redundancy)?;
@@ -576,6 +651,8 @@ fn perform_bitemporal_export(
     args: &ExportArgs,
     db_ref: &DatabaseReference,
     output_path: &Path,
+    taxonomy_filter: Option<&TaxonomyFilter>,
+    taxonomy: Option<&TaxonomyManager>,
 ) -> Result<ExportStats> {
     use chrono::Utc;
     use std::sync::Arc;
@@ -685,9 +762,9 @@ fn perform_bitemporal_export(
     let mut sequence_count = 0;
 
     for chunk_meta in snapshot.chunks() {
-        // Apply taxonomy filter if specified
-        if let Some(filter) = &args.taxonomy_filter {
-            if !matches_taxonomy_filter(&chunk_meta, filter)? {
+        // Skip chunks no taxon of which can match; the rest are refined per sequence
+        if let Some(filter) = taxonomy_filter {
+            if !matches_taxonomy_filter(&chunk_meta, filter, taxonomy) {
                 continue;
             }
         }
@@ -702,13 +779,25 @@ fn perform_bitemporal_export(
                     // Load actual sequences from canonical storage
                     for seq_hash in &manifest.sequence_refs {
                         if let Ok(canonical) = storage.sequence_storage.load_canonical(seq_hash) {
+                            let taxon_id = storage
+                                .sequence_storage
+                                .load_representations(seq_hash)
+                                .ok()
+                                .and_then(|reps| {
+                                    reps.representations.iter().find_map(|rep| rep.taxon_id)
+                                });
                             let seq = talaria_bio::sequence::Sequence {
                                 id: seq_hash.to_hex(),
                                 description: None,
                                 sequence: canonical.sequence.clone(),
-                                taxon_id: None,
+                                taxon_id: taxon_id.map(|t| t.0),
                                 taxonomy_sources: Default::default(),
                             };
+                            if let Some(filter) = taxonomy_filter {
+                                if !sequence_matches(&seq, filter, taxonomy) {
+                                    continue;
+                                }
+                            }
 
                             match args.format {
                                 ExportFormat::Fasta => {
@@ -745,6 +834,11 @@ fn perform_bitemporal_export(
                     let sequences = talaria_bio::parse_fasta_from_bytes(&chunk_data)?;
 
                     for seq in sequences {
+                        if let Some(filter) = taxonomy_filter {
+                            if !sequence_matches(&seq, filter, taxonomy) {
+                                continue;
+                            }
+                        }
                         match args.format {
                             ExportFormat::Fasta => {
                                 writeln!(writer, ">{}", seq.id)?;
@@ -822,12 +916,35 @@ fn parse_time_input(input: &str) -> Result<chrono::DateTime<chrono::Utc>> {
     ))
 }
 
-fn matches_taxonomy_filter(chunk: &talaria_herald::ManifestMetadata, filter: &str) -> Result<bool> {
-    // Use the new taxonomy filter with boolean expression support
-    use talaria_herald::taxonomy::filter::TaxonomyFilter;
+/// Per-sequence taxonomy filter
+///
+/// A sequence without a taxon matches no taxon predicate, so it is kept only
+/// when the filter holds for no taxa at all, as exclude-only filters do.
+fn sequence_matches(
+    seq: &talaria_bio::sequence::Sequence,
+    filter: &TaxonomyFilter,
+    taxonomy: Option<&TaxonomyManager>,
+) -> bool {
+    let mut ctx = FilterContext::new().with_sequence_length(seq.sequence.len());
+    if let Some(taxonomy) = taxonomy {
+        ctx = ctx.with_resolver(taxonomy);
+    }
+    match seq.taxon_id {
+        Some(taxon_id) => filter.matches_with(&[TaxonId(taxon_id)], &ctx),
+        None => filter.evaluate_with(&[], &ctx) == Some(true),
+    }
+}
 
-    let filter = TaxonomyFilter::parse(filter)?;
-    Ok(filter.matches(&chunk.taxon_ids))
+fn matches_taxonomy_filter(
+    chunk: &talaria_herald::ManifestMetadata,
+    filter: &TaxonomyFilter,
+    taxonomy: Option<&TaxonomyManager>,
+) -> bool {
+    let mut ctx = FilterContext::new();
+    if let Some(taxonomy) = taxonomy {
+        ctx = ctx.with_resolver(taxonomy);
+    }
+    filter.may_match_any(&chunk.taxon_ids, &ctx)
 }
 
 /// Apply redundancy reduction using simple sequence clustering
@@ -926,3 +1043,33 @@ fn calculate_sequence_similarity(seq1: &[u8], seq2: &[u8]) -> f32 {
         intersection as f32 / union as f32
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sequence(taxon_id: Option<u32>) -> talaria_bio::sequence::Sequence {
+        talaria_bio::sequence::Sequence {
+            id: "P12345".to_string(),
+            description: None,
+            sequence: b"MKTAYIAKQR".to_vec(),
+            taxon_id,
+            taxonomy_sources: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_sequence_without_taxon_only_passes_exclude_filters() {
+        let include = TaxonomyFilter::parse("562 OR 9606").unwrap();
+        assert!(sequence_matches(&sequence(Some(562)), &include, None));
+        assert!(!sequence_matches(&sequence(None), &include, None));
+
+        let exclude = TaxonomyFilter::parse("NOT 562").unwrap();
+        assert!(!sequence_matches(&sequence(Some(562)), &exclude, None));
+        assert!(sequence_matches(&sequence(None), &exclude, None));
+
+        // Length alone decides for untaxed sequences too
+        let length = TaxonomyFilter::parse("length > 5").unwrap();
+        assert!(sequence_matches(&sequence(None), &length, None));
+    }
+}
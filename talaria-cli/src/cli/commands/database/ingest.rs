@@ -0,0 +1,519 @@
+/// Input discovery and taxonomy assignment for `database add`
+use anyhow::{Context, Result};
+use clap::Args;
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use talaria_bio::sequence::Sequence;
+
+/// Extensions picked up when an input is a directory
+const FASTA_EXTENSIONS: &[&str] = &["fa", "fasta", "faa", "fna", "fas", "ffn", "frn", "mpfa"];
+
+#[derive(Args, Debug, Clone, Default)]
+pub struct IngestArgs {
+    /// Accession to taxid table (tab-separated, or comma-separated for .csv)
+    #[arg(long, value_name = "FILE")]
+    pub taxid_map: Option<PathBuf>,
+
+    /// Columns of --taxid-map holding the accession and the taxid, by header
+    /// name or 1-based position (default: "1,2")
+    #[arg(long, value_name = "ACCESSION,TAXID", requires = "taxid_map")]
+    pub map_columns: Option<String>,
+
+    /// Regex extracting the accession from each header line (without '>');
+    /// the first capture group is used if there is one
+    #[arg(long, value_name = "REGEX")]
+    pub id_regex: Option<String>,
+
+    /// Taxid for sequences that neither the map nor the header assigns
+    #[arg(long, value_name = "TAXID")]
+    pub default_taxid: Option<u32>,
+
+    /// Default taxid for one input file, matched by file name (repeatable)
+    #[arg(long = "file-taxid", value_name = "FILE=TAXID")]
+    pub file_taxids: Vec<String>,
+
+    /// Sample sheet giving a default taxid per input file: file name in the
+    /// first column, taxid in the second (tab-separated, or .csv)
+    #[arg(long, value_name = "FILE")]
+    pub sample_sheet: Option<PathBuf>,
+}
+
+impl IngestArgs {
+    /// Whether any taxonomy option was given
+    pub fn is_set(&self) -> bool {
+        self.taxid_map.is_some()
+            || self.id_regex.is_some()
+            || self.default_taxid.is_some()
+            || !self.file_taxids.is_empty()
+            || self.sample_sheet.is_some()
+    }
+}
+
+/// Expand files, directories and glob patterns into FASTA files
+///
+/// Directories contribute their FASTA files (not recursively); patterns use
+/// shell glob syntax in any path component (`dir/*/x.fa`, `**/*.faa`). Order
+/// follows the inputs, with each directory or pattern sorted by name.
+pub fn discover_inputs(inputs: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for input in inputs {
+        let text = input.to_string_lossy();
+        let mut found = if text.contains(['*', '?', '[']) {
+            let mut matched = Vec::new();
+            for entry in glob::glob(&text)
+                .with_context(|| format!("Invalid input pattern {}", input.display()))?
+            {
+                let path = entry?;
+                if path.is_file() {
+                    matched.push(path);
+                }
+            }
+            matched
+        } else if input.is_dir() {
+            list_dir(input, is_fasta_name)?
+        } else if input.exists() {
+            vec![input.clone()]
+        } else {
+            anyhow::bail!("Input does not exist: {}", input.display());
+        };
+        if found.is_empty() {
+            anyhow::bail!("No FASTA files match {}", input.display());
+        }
+        found.sort();
+        files.extend(found);
+    }
+
+    let mut seen = HashSet::new();
+    files.retain(|file| seen.insert(file.clone()));
+    Ok(files)
+}
+
+/// Typical expansion of gzip-compressed FASTA
+const GZIP_EXPANSION: u64 = 4;
+
+/// Uncompressed size of an input, estimated for `.gz` files
+///
+/// The gzip trailer only holds the size modulo 4 GiB, which is useless for
+/// exactly the inputs where size matters, so a fixed ratio is applied.
+pub fn estimated_input_size(path: &Path) -> Result<u64> {
+    let size = std::fs::metadata(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?
+        .len();
+    let compressed = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("gz"));
+    Ok(if compressed {
+        size.saturating_mul(GZIP_EXPANSION)
+    } else {
+        size
+    })
+}
+
+fn list_dir(dir: &Path, keep: impl Fn(&str) -> bool) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir)
+        .with_context(|| format!("Failed to read directory {}", dir.display()))?
+    {
+        let path = entry?.path();
+        let keep_file = path.is_file()
+            && path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(&keep);
+        if keep_file {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+fn is_fasta_name(name: &str) -> bool {
+    let name = name.strip_suffix(".gz").unwrap_or(name);
+    name.rsplit_once('.')
+        .is_some_and(|(_, ext)| FASTA_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+}
+
+/// A table column, by header name or 0-based position
+#[derive(Debug, Clone, PartialEq)]
+enum Column {
+    Index(usize),
+    Name(String),
+}
+
+impl Column {
+    fn parse(spec: &str) -> Result<Self> {
+        let spec = spec.trim();
+        match spec.parse::<usize>() {
+            Ok(0) => anyhow::bail!("Column positions start at 1"),
+            Ok(position) => Ok(Column::Index(position - 1)),
+            Err(_) if !spec.is_empty() => Ok(Column::Name(spec.to_string())),
+            Err(_) => anyhow::bail!("Empty column name"),
+        }
+    }
+}
+
+fn parse_columns(spec: &str) -> Result<(Column, Column)> {
+    match spec.split_once(',') {
+        Some((key, value)) => Ok((Column::parse(key)?, Column::parse(value)?)),
+        None => anyhow::bail!("Expected two columns such as \"1,2\" or \"accession,taxid\""),
+    }
+}
+
+/// Read a key-to-taxid table
+///
+/// Named columns are looked up in the first line. With positional columns a
+/// first line whose taxid cell is not a number is taken as a header.
+fn read_taxid_table(path: &Path, key: &Column, taxid: &Column) -> Result<HashMap<String, u32>> {
+    let delimiter = match path.extension().and_then(|ext| ext.to_str()) {
+        Some(ext) if ext.eq_ignore_ascii_case("csv") => ',',
+        _ => '\t',
+    };
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.starts_with('#'))
+        .peekable();
+
+    let split = |line: &str| -> Vec<String> {
+        line.split(delimiter)
+            .map(|cell| cell.trim().trim_matches('"').to_string())
+            .collect()
+    };
+
+    let (key, taxid) = match (key, taxid) {
+        (Column::Index(key), Column::Index(taxid)) => {
+            let has_header = lines.peek().is_some_and(|(_, line)| {
+                split(line)
+                    .get(*taxid)
+                    .is_some_and(|cell| cell.parse::<u32>().is_err())
+            });
+            if has_header {
+                lines.next();
+            }
+            (*key, *taxid)
+        }
+        _ => {
+            let header = lines
+                .next()
+                .map(|(_, line)| split(line))
+                .unwrap_or_default();
+            let position = |column: &Column| match column {
+                Column::Index(index) => Ok(*index),
+                Column::Name(name) => header
+                    .iter()
+                    .position(|cell| cell.eq_ignore_ascii_case(name))
+                    .with_context(|| format!("{} has no column named '{}'", path.display(), name)),
+            };
+            (position(key)?, position(taxid)?)
+        }
+    };
+
+    let mut table = HashMap::new();
+    for (number, line) in lines {
+        let cells = split(line);
+        let (Some(name), Some(value)) = (cells.get(key), cells.get(taxid)) else {
+            anyhow::bail!("{}:{}: missing columns", path.display(), number + 1);
+        };
+        let value: u32 = value.parse().with_context(|| {
+            format!(
+                "{}:{}: invalid taxid '{}'",
+                path.display(),
+                number + 1,
+                value
+            )
+        })?;
+        if !name.is_empty() && value > 0 {
+            table.insert(name.clone(), value);
+        }
+    }
+    Ok(table)
+}
+
+/// How taxids were assigned across the ingested files
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AssignmentStats {
+    pub from_map: usize,
+    pub from_header: usize,
+    pub from_default: usize,
+    pub unassigned: usize,
+    /// Map and header disagreed; the map won
+    pub conflicts: usize,
+    /// Headers the id regex did not match
+    pub regex_misses: usize,
+}
+
+/// Assigns taxids from a mapping table, headers and per-file defaults
+///
+/// Precedence is mapping, then header (`OX=`, `TaxID=`), then the file's
+/// default, then the global default.
+pub struct TaxonomyAssigner {
+    map: HashMap<String, u32>,
+    id_regex: Option<Regex>,
+    default_taxid: Option<u32>,
+    file_taxids: HashMap<String, u32>,
+    pub stats: AssignmentStats,
+}
+
+impl TaxonomyAssigner {
+    pub fn from_args(args: &IngestArgs) -> Result<Self> {
+        let map = match &args.taxid_map {
+            Some(path) => {
+                let (key, taxid) = parse_columns(args.map_columns.as_deref().unwrap_or("1,2"))?;
+                read_taxid_table(path, &key, &taxid)?
+            }
+            None => HashMap::new(),
+        };
+
+        let mut file_taxids = match &args.sample_sheet {
+            Some(path) => read_taxid_table(path, &Column::Index(0), &Column::Index(1))?,
+            None => HashMap::new(),
+        };
+        for entry in &args.file_taxids {
+            let (file, taxid) = entry
+                .rsplit_once('=')
+                .with_context(|| format!("Expected FILE=TAXID, got '{}'", entry))?;
+            let taxid = taxid
+                .trim()
+                .parse()
+                .with_context(|| format!("Invalid taxid in '{}'", entry))?;
+            file_taxids.insert(file.trim().to_string(), taxid);
+        }
+
+        let id_regex = args
+            .id_regex
+            .as_deref()
+            .map(Regex::new)
+            .transpose()
+            .context("Invalid --id-regex")?;
+
+        Ok(Self {
+            map,
+            id_regex,
+            default_taxid: args.default_taxid,
+            file_taxids,
+            stats: AssignmentStats::default(),
+        })
+    }
+
+    pub fn mapping_size(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Per-file entries that match none of the given files
+    pub fn unmatched_files(&self, files: &[PathBuf]) -> Vec<String> {
+        let mut unmatched: Vec<String> = self
+            .file_taxids
+            .keys()
+            .filter(|key| !files.iter().any(|file| file_keys(file).contains(key)))
+            .cloned()
+            .collect();
+        unmatched.sort();
+        unmatched
+    }
+
+    /// Default taxid for sequences from a file
+    pub fn file_default(&self, file: &Path) -> Option<u32> {
+        file_keys(file)
+            .iter()
+            .find_map(|key| self.file_taxids.get(key).copied())
+            .or(self.default_taxid)
+    }
+
+    /// Assign taxids to sequences read from one file
+    pub fn assign(&mut self, sequences: &mut [Sequence], file_default: Option<u32>) {
+        for seq in sequences {
+            let accession = self.accession(seq);
+            let sources = &mut seq.taxonomy_sources;
+            sources.header_parsed = seq.taxon_id;
+            sources.mapping_lookup = accession.and_then(|accession| {
+                self.map.get(&accession).copied().or_else(|| {
+                    let (base, version) = accession.rsplit_once('.')?;
+                    version
+                        .bytes()
+                        .all(|b| b.is_ascii_digit())
+                        .then(|| self.map.get(base).copied())
+                        .flatten()
+                })
+            });
+            sources.chunk_context = file_default;
+
+            match (sources.mapping_lookup, sources.header_parsed) {
+                (Some(mapped), header) => {
+                    self.stats.from_map += 1;
+                    if header.is_some_and(|header| header != mapped) {
+                        self.stats.conflicts += 1;
+                    }
+                }
+                (None, Some(_)) => self.stats.from_header += 1,
+                (None, None) if file_default.is_some() => self.stats.from_default += 1,
+                (None, None) => self.stats.unassigned += 1,
+            }
+            seq.taxon_id = sources.resolve_with_priority();
+        }
+    }
+
+    /// Key used for the mapping table
+    fn accession(&mut self, seq: &Sequence) -> Option<String> {
+        let Some(regex) = &self.id_regex else {
+            return Some(seq.id.clone());
+        };
+        let header = match &seq.description {
+            Some(description) => format!("{} {}", seq.id, description),
+            None => seq.id.clone(),
+        };
+        let found = regex.captures(&header).and_then(|captures| {
+            captures
+                .get(1)
+                .or_else(|| captures.get(0))
+                .map(|m| m.as_str().to_string())
+        });
+        if found.is_none() {
+            self.stats.regex_misses += 1;
+        }
+        found
+    }
+}
+
+/// Names a file can be referred to by in a sample sheet
+fn file_keys(file: &Path) -> Vec<String> {
+    let mut keys = vec![file.to_string_lossy().into_owned()];
+    if let Some(name) = file.file_name().and_then(|name| name.to_str()) {
+        keys.push(name.to_string());
+        let stem = name.strip_suffix(".gz").unwrap_or(name);
+        if let Some((stem, _)) = stem.rsplit_once('.') {
+            keys.push(stem.to_string());
+        }
+    }
+    keys
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn sequence(id: &str, description: Option<&str>, taxon: Option<u32>) -> Sequence {
+        let mut seq = Sequence::new(id.to_string(), b"MKV".to_vec());
+        seq.description = description.map(str::to_string);
+        seq.taxon_id = taxon;
+        seq
+    }
+
+    #[test]
+    fn test_discover_inputs() {
+        let dir = TempDir::new().unwrap();
+        for name in ["b.faa", "a.fasta.gz", "notes.txt", "c.fa"] {
+            std::fs::write(dir.path().join(name), ">x\nMKV\n").unwrap();
+        }
+
+        let files = discover_inputs(&[dir.path().to_path_buf()]).unwrap();
+        let names: Vec<_> = files
+            .iter()
+            .map(|f| f.file_name().unwrap().to_str().unwrap())
+            .collect();
+        assert_eq!(names, vec!["a.fasta.gz", "b.faa", "c.fa"]);
+
+        let files = discover_inputs(&[dir.path().join("*.fa*"), dir.path().join("c.fa")]).unwrap();
+        assert_eq!(files.len(), 3);
+        assert!(discover_inputs(&[dir.path().join("*.gbk")]).is_err());
+
+        // Wildcards in directory components
+        for sample in ["s1", "s2"] {
+            std::fs::create_dir(dir.path().join(sample)).unwrap();
+            std::fs::write(dir.path().join(sample).join("x.fa"), ">x\nMKV\n").unwrap();
+        }
+        let files = discover_inputs(&[dir.path().join("*/x.fa")]).unwrap();
+        assert_eq!(
+            files,
+            vec![dir.path().join("s1/x.fa"), dir.path().join("s2/x.fa")]
+        );
+    }
+
+    #[test]
+    fn test_estimated_input_size() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("plain.fa"), vec![b'A'; 100]).unwrap();
+        std::fs::write(dir.path().join("packed.fa.gz"), vec![0u8; 100]).unwrap();
+        assert_eq!(
+            estimated_input_size(&dir.path().join("plain.fa")).unwrap(),
+            100
+        );
+        assert_eq!(
+            estimated_input_size(&dir.path().join("packed.fa.gz")).unwrap(),
+            100 * GZIP_EXPANSION
+        );
+    }
+
+    #[test]
+    fn test_read_taxid_table_columns() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("samples.csv");
+        std::fs::write(
+            &path,
+            "sample,organism,taxid\nP1,E. coli,562\nP2,human,9606\n",
+        )
+        .unwrap();
+
+        let (key, taxid) = parse_columns("sample,taxid").unwrap();
+        let table = read_taxid_table(&path, &key, &taxid).unwrap();
+        assert_eq!(table.get("P2"), Some(&9606));
+
+        let (key, taxid) = parse_columns("1,3").unwrap();
+        let table = read_taxid_table(&path, &key, &taxid).unwrap();
+        assert_eq!(table.len(), 2);
+
+        let (key, taxid) = parse_columns("sample,species").unwrap();
+        assert!(read_taxid_table(&path, &key, &taxid).is_err());
+        assert!(parse_columns("0,1").is_err());
+    }
+
+    #[test]
+    fn test_assignment_precedence() {
+        let dir = TempDir::new().unwrap();
+        let map = dir.path().join("map.tsv");
+        std::fs::write(&map, "accession\ttaxid\nPROT1\t562\nPROT2\t1280\n").unwrap();
+
+        let args = IngestArgs {
+            taxid_map: Some(map),
+            id_regex: Some(r"^lab\|([^|]+)\|".to_string()),
+            default_taxid: Some(1),
+            file_taxids: vec!["liver.faa=10090".to_string()],
+            ..Default::default()
+        };
+        let mut assigner = TaxonomyAssigner::from_args(&args).unwrap();
+        assert_eq!(assigner.mapping_size(), 2);
+        assert_eq!(
+            assigner.file_default(Path::new("in/liver.faa")),
+            Some(10090)
+        );
+        assert_eq!(assigner.file_default(Path::new("in/other.faa")), Some(1));
+        assert_eq!(
+            assigner.unmatched_files(&[PathBuf::from("in/other.faa")]),
+            vec!["liver.faa".to_string()]
+        );
+
+        let mut sequences = vec![
+            sequence("lab|PROT1.2|x", Some("OX=9606"), Some(9606)),
+            sequence("lab|PROT3|x", Some("OX=9606"), Some(9606)),
+            sequence("lab|PROT4|x", None, None),
+            sequence("unlabelled", None, None),
+        ];
+        assigner.assign(&mut sequences, Some(10090));
+        let taxa: Vec<_> = sequences.iter().map(|s| s.taxon_id).collect();
+        assert_eq!(taxa, vec![Some(562), Some(9606), Some(10090), Some(10090)]);
+        assert_eq!(
+            assigner.stats,
+            AssignmentStats {
+                from_map: 1,
+                from_header: 1,
+                from_default: 2,
+                unassigned: 0,
+                conflicts: 1,
+                regex_misses: 1,
+            }
+        );
+    }
+}
@@ -220,7 +220,7 @@ fn run_all_versions_list(
 
         // Sort versions by date (newest first)
         let mut sorted_versions = versions.clone();
-        sorted_versions.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        sorted_versions.sort_by_key(|b| std::cmp::Reverse(b.created_at));
 
         // Show each version
         for (v_idx, version) in sorted_versions.iter().enumerate() {
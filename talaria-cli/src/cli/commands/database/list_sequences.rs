@@ -245,8 +245,8 @@ pub fn run(args: ListSequencesArgs) -> anyhow::Result<()> {
 
     writer.flush()?;
 
-    if args.output.is_some() {
-        eprintln!("\u{2713} Output written to {:?}", args.output.unwrap());
+    if let Some(output) = &args.output {
+        eprintln!("\u{2713} Output written to {:?}", output);
     }
 
     Ok(())
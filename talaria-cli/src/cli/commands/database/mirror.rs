@@ -333,6 +333,7 @@ impl SyncCmd {
     async fn run(&self) -> Result<()> {
         use std::time::Instant;
         let start_time = Instant::now();
+        let _metrics = crate::cli::metrics::start("mirror_sync")?;
 
         println!("🔄 Syncing database: {}", self.database);
 
@@ -1,19 +1,25 @@
 #![allow(dead_code)]
 
+pub mod accessions; // Merged, deleted and replaced accession history
 pub mod add; // Canonical sequence-based add (the ONLY add)
 pub mod backup;
+pub mod bundle; // Portable bundles for air-gapped transfer
 pub mod check_discrepancies;
+pub mod chunking; // Chunking flags shared by add and download
 pub mod clean; // Database cleaning (removes unreferenced data)
+pub mod compose; // Virtual databases from set algebra
 pub mod delete;
 pub mod diff;
 pub mod download;
 pub mod download_impl;
 pub mod export;
 pub mod info;
+pub mod ingest; // Input discovery and taxonomy assignment for add
 pub mod list;
 pub mod list_sequences;
 pub mod mirror; // Database mirroring
 pub mod optimize; // Database optimization
+pub mod pipeline; // Processing pipelines shared by add and reduce
 pub mod taxa_coverage;
 pub mod update;
 pub mod update_taxonomy;
@@ -40,6 +46,9 @@ pub enum DatabaseCommands {
     /// Add a custom database from a local FASTA file
     Add(add::AddArgs),
 
+    /// Define a virtual database as set algebra over existing ones
+    Compose(compose::ComposeArgs),
+
     /// Update existing databases (check for new versions)
     Update(update::UpdateArgs),
 
@@ -56,6 +65,9 @@ pub enum DatabaseCommands {
     /// List sequences in a database
     ListSequences(list_sequences::ListSequencesArgs),
 
+    /// Import and query the history of merged, deleted and replaced accessions
+    Accessions(accessions::AccessionsArgs),
+
     // === Version Management ===
     /// Manage database versions
     Versions(versions::VersionsArgs),
@@ -64,6 +76,9 @@ pub enum DatabaseCommands {
     /// Manage database backups
     Backup(backup::BackupCommand),
 
+    /// Create, verify and import portable database bundles
+    Bundle(bundle::BundleCommand),
+
     // === Export & Integration ===
     /// Export database from HERALD to FASTA format
     Export(export::ExportArgs),
@@ -105,10 +120,12 @@ pub fn run(args: DatabaseArgs) -> anyhow::Result<()> {
         DatabaseCommands::Download(args) => download::run(args),
         DatabaseCommands::Update(args) => update::run(args),
         DatabaseCommands::Add(args) => add::run(args),
+        DatabaseCommands::Compose(args) => compose::run(args),
         DatabaseCommands::Export(args) => export::run(args),
         DatabaseCommands::Versions(args) => versions::run(args),
         DatabaseCommands::Stats => run_stats(),
         DatabaseCommands::ListSequences(args) => list_sequences::run(args),
+        DatabaseCommands::Accessions(args) => accessions::run(args),
         DatabaseCommands::TaxaCoverage(args) => taxa_coverage::run(args),
         DatabaseCommands::UpdateTaxonomy(args) => update_taxonomy::run(args),
         DatabaseCommands::Check(args) => check_discrepancies::run(args),
@@ -121,6 +138,7 @@ pub fn run(args: DatabaseArgs) -> anyhow::Result<()> {
         }
         DatabaseCommands::Diff(args) => diff::run(args),
         DatabaseCommands::Backup(args) => backup::execute(&args),
+        DatabaseCommands::Bundle(args) => bundle::run(args),
         DatabaseCommands::Optimize(args) => {
             let runtime = tokio::runtime::Runtime::new()?;
             runtime.block_on(args.run())
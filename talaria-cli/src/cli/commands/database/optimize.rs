@@ -5,11 +5,14 @@
 /// - Rebuilding indices for faster queries
 /// - Removing obsolete temporal versions
 /// - Consolidating small chunks
+/// - Rebasing deep delta chains
 use anyhow::{anyhow, Result};
 use clap::Args;
 use indicatif::{ProgressBar, ProgressStyle};
 use std::path::PathBuf;
 use talaria_core::system::paths::talaria_databases_dir;
+use talaria_herald::delta::ReconstructorConfig;
+use talaria_herald::storage::HeraldStorage;
 use talaria_herald::HeraldRepository;
 
 #[derive(Debug, Args)]
@@ -30,6 +33,14 @@ pub struct OptimizeCmd {
     #[arg(long)]
     compact: bool,
 
+    /// Re-encode delta chunks whose chain is deeper than --max-chain-depth
+    #[arg(long)]
+    rebase_deltas: bool,
+
+    /// Maximum delta chain depth kept by --rebase-deltas
+    #[arg(long, value_name = "DEPTH", default_value_t = ReconstructorConfig::default().max_chain_depth)]
+    max_chain_depth: usize,
+
     /// Remove temporal versions older than N days
     #[arg(long, value_name = "DAYS")]
     prune_temporal: Option<u32>,
@@ -108,14 +119,21 @@ impl OptimizeCmd {
             pb.set_position(50);
         }
 
-        // Step 3: Rebuild indices if requested
+        // Step 3: Rebase deep delta chains if requested
+        if self.rebase_deltas {
+            pb.set_message("Rebasing delta chains...");
+            self.rebase_deltas(&repository.storage, self.dry_run)?;
+            pb.set_position(60);
+        }
+
+        // Step 4: Rebuild indices if requested
         if self.rebuild_indices {
             pb.set_message("Rebuilding indices...");
             self.rebuild_indices(&mut repository, self.dry_run)?;
             pb.set_position(75);
         }
 
-        // Step 4: Prune temporal versions if requested
+        // Step 5: Prune temporal versions if requested
         if let Some(days) = self.prune_temporal {
             pb.set_message("Pruning old temporal versions...");
             let pruned = self.prune_temporal(&mut repository, days, self.dry_run)?;
@@ -258,6 +276,12 @@ impl OptimizeCmd {
             }
         }
 
+        // Rebase delta chains (global operation, delta chunks live in shared storage)
+        if self.rebase_deltas {
+            println!();
+            self.rebase_deltas(&manager.get_repository().storage, self.dry_run)?;
+        }
+
         // Repack chunks (per-database operation, only applies to completed databases)
         if self.repack {
             println!();
@@ -406,6 +430,56 @@ impl OptimizeCmd {
         Ok(())
     }
 
+    fn rebase_deltas(&self, storage: &HeraldStorage, dry_run: bool) -> Result<()> {
+        use talaria_herald::delta::DeltaRebaser;
+
+        println!(
+            "\n🔗 Rebasing delta chains deeper than {}...",
+            self.max_chain_depth
+        );
+
+        let report = DeltaRebaser::new(storage, self.max_chain_depth)
+            .with_dry_run(dry_run)
+            .run()?;
+
+        if report.depth_before.is_empty() {
+            println!("  No delta chunks found");
+            return Ok(());
+        }
+
+        println!("  Chain depth    Before     After");
+        let depths = report.depth_before.keys().chain(report.depth_after.keys());
+        let max_depth = depths.copied().max().unwrap_or(0);
+        for depth in 1..=max_depth {
+            let before = report.depth_before.get(&depth).copied().unwrap_or(0);
+            let after = report.depth_after.get(&depth).copied().unwrap_or(0);
+            if before + after > 0 {
+                println!("  {:>11} {:>9} {:>9}", depth, before, after);
+            }
+        }
+
+        let verb = if dry_run { "Would rebase" } else { "Rebased" };
+        println!(
+            "  {} {} chunks ({} sequences re-encoded), max depth {} → {}",
+            verb,
+            report.chunks_rebased,
+            report.sequences_reencoded,
+            report.max_depth_before(),
+            report.max_depth_after()
+        );
+        if report.chunks_removed > 0 {
+            println!("  Removed {} replaced chunks", report.chunks_removed);
+        }
+        if report.chunks_skipped > 0 {
+            println!(
+                "  ⚠️  {} chunks could not be re-encoded and were left unchanged",
+                report.chunks_skipped
+            );
+        }
+
+        Ok(())
+    }
+
     fn rebuild_indices(&self, repository: &mut HeraldRepository, dry_run: bool) -> Result<()> {
         println!("\n🔍 Rebuilding indices for faster queries...");
 
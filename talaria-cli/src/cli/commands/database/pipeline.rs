@@ -0,0 +1,111 @@
+/// `--pipeline` support shared by `database add` and `reduce`
+use anyhow::{Context, Result};
+use std::path::Path;
+use talaria_bio::sequence::Sequence;
+use talaria_herald::database::DatabaseManager;
+use talaria_herald::processing::{
+    ContaminationReport, PipelineDefinition, PipelineRecord, ScreenAction,
+    StandardProcessingPipeline,
+};
+use talaria_utils::database::database_ref::parse_database_reference;
+
+/// A pipeline definition, validated and ready to run
+pub struct LoadedPipeline {
+    /// What gets stored with the version or reduction
+    pub record: PipelineRecord,
+    pipeline: StandardProcessingPipeline,
+}
+
+impl LoadedPipeline {
+    /// Panels stored as databases are read from the repository at
+    /// `databases_dir`. Must be called before the caller opens its own
+    /// `DatabaseManager` there, since panels are read through a temporary one
+    pub fn load(path: &Path, databases_dir: &Path) -> Result<Self> {
+        let definition = PipelineDefinition::from_file(path)?;
+        Ok(Self {
+            pipeline: definition
+                .build_with_panels(&|panel: &str| load_panel(panel, databases_dir))?,
+            record: definition.record()?,
+        })
+    }
+
+    /// Findings of the pipeline's screening steps, once it has run
+    pub fn screening_report(&self) -> Option<ContaminationReport> {
+        self.pipeline.screening_report()
+    }
+
+    /// Run every step and print what each one did
+    pub fn apply(&self, sequences: Vec<Sequence>) -> Result<Vec<Sequence>> {
+        use crate::cli::formatting::output::*;
+
+        let input_count = sequences.len();
+        action(&format!(
+            "Running pipeline '{}' on {} sequences...",
+            self.record.name,
+            format_number(input_count)
+        ));
+        let (sequences, result) = self.pipeline.run(sequences)?;
+
+        for (index, stage) in result.stage_results.iter().enumerate() {
+            tree_item(
+                index + 1 == result.stage_results.len(),
+                &stage.stage_name,
+                Some(&format!(
+                    "{} dropped, {} changed",
+                    format_number(stage.sequences_filtered),
+                    format_number(stage.sequences_modified)
+                )),
+            );
+        }
+
+        if sequences.is_empty() {
+            anyhow::bail!(
+                "Pipeline '{}' dropped all {} sequences",
+                self.record.name,
+                input_count
+            );
+        }
+        info(&format!(
+            "{} of {} sequences kept",
+            format_number(sequences.len()),
+            format_number(input_count)
+        ));
+        if let Some(report) = self.screening_report() {
+            if !report.findings.is_empty() {
+                warning(&format!(
+                    "Contaminant screening: {} flagged, {} trimmed, {} excluded",
+                    format_number(report.count(ScreenAction::Flag)),
+                    format_number(report.count(ScreenAction::Trim)),
+                    format_number(report.count(ScreenAction::Exclude))
+                ));
+            }
+        }
+        Ok(sequences)
+    }
+}
+
+/// Panel sequences from a FASTA file or a stored database ("source/dataset[@version]")
+fn load_panel(panel: &str, databases_dir: &Path) -> Result<Vec<Sequence>> {
+    if Path::new(panel).is_file() {
+        return Ok(talaria_bio::parse_fasta(panel)?);
+    }
+
+    let db_ref = parse_database_reference(panel)
+        .with_context(|| format!("Panel '{}' is neither a FASTA file nor a database", panel))?;
+    let manager = DatabaseManager::new(Some(databases_dir.to_string_lossy().to_string()))?;
+    let manifest = manager.get_version_manifest(
+        &db_ref.source,
+        &db_ref.dataset,
+        db_ref.version.as_deref().unwrap_or("current"),
+    )?;
+    let storage = &manager.get_repository().storage.sequence_storage;
+
+    let mut sequences = Vec::new();
+    for chunk in &manifest.chunk_index {
+        for hash in &manager.load_manifest(&chunk.hash)?.sequence_refs {
+            let fasta = storage.get_sequence_as_fasta(hash, None)?;
+            sequences.extend(talaria_bio::parse_fasta_from_bytes(fasta.as_bytes())?);
+        }
+    }
+    Ok(sequences)
+}
@@ -112,7 +112,7 @@ pub fn run(args: TaxaCoverageArgs) -> Result<()> {
             .iter()
             .map(|(taxid, count)| (format!("taxid:{}", taxid), *count))
             .collect();
-        taxa_with_counts.sort_by(|a, b| b.1.cmp(&a.1));
+        taxa_with_counts.sort_by_key(|b| std::cmp::Reverse(b.1));
         let most_common_taxa = taxa_with_counts.iter().take(20).cloned().collect();
 
         // Get rare taxa (less than 5 sequences)
@@ -122,6 +122,8 @@ pub fn run(args: TaxaCoverageArgs) -> Result<()> {
             .cloned()
             .collect();
 
+        let lineage_counts = lineage_counts(&primary_coverage, &taxonomy_db);
+
         // Build comparison if available
         let comparison_data = comparison.as_ref().map(|comp| {
             let shared = primary_coverage
@@ -155,6 +157,7 @@ pub fn run(args: TaxaCoverageArgs) -> Result<()> {
             coverage_by_rank,
             most_common_taxa,
             rare_taxa,
+            lineage_counts,
             comparison: comparison_data,
             duration: Duration::from_secs(0), // TODO: Track actual duration
         };
@@ -278,6 +281,38 @@ fn analyze_database(path: &PathBuf, taxonomy_db: &TaxonomyDB) -> Result<Taxonomy
     Ok(coverage)
 }
 
+/// Named lineage of every observed taxon with its sequence count
+fn lineage_counts(
+    coverage: &TaxonomyCoverage,
+    taxonomy_db: &TaxonomyDB,
+) -> Vec<(Vec<String>, usize)> {
+    // root and "cellular organisms" would only add rings every lineage shares
+    const SHARED_ANCESTORS: [u32; 2] = [1, 131567];
+
+    coverage
+        .taxon_counts
+        .iter()
+        .map(|(&taxid, &count)| {
+            let lineage = taxonomy_db
+                .get_lineage(taxid)
+                .into_iter()
+                .filter(|id| !SHARED_ANCESTORS.contains(id))
+                .map(|id| {
+                    taxonomy_db
+                        .get_taxon(id)
+                        .map(|t| t.scientific_name.clone())
+                        .unwrap_or_else(|| format!("taxid:{}", id))
+                })
+                .collect::<Vec<_>>();
+            if lineage.is_empty() {
+                (vec!["Unclassified".to_string()], count)
+            } else {
+                (lineage, count)
+            }
+        })
+        .collect()
+}
+
 fn extract_taxon_id(header: &str) -> Option<u32> {
     // Look for patterns like "TaxID=12345" or "tax_id:12345" or "[taxid:12345]"
     let patterns = vec![
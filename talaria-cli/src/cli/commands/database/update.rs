@@ -79,6 +79,7 @@ pub fn run(args: UpdateArgs) -> Result<()> {
     let mut updates_available = Vec::new();
     let mut up_to_date = Vec::new();
     let mut errors = Vec::new();
+    let mut update_reports = Vec::new();
 
     // Check each database
     for db_name in &databases_to_check {
@@ -136,9 +137,12 @@ pub fn run(args: UpdateArgs) -> Result<()> {
                 let update_spinner = create_spinner(&format!("Updating {}...", db));
 
                 // Perform actual update
-                match perform_update(db) {
-                    Ok(()) => {
+                match perform_update(&mut manager, db, &update_spinner) {
+                    Ok(report) => {
                         update_spinner.finish_with_message(format!("{} {}", "✓".green(), db));
+                        if let Some(report) = report {
+                            update_reports.push((db.clone(), report));
+                        }
                     }
                     Err(e) => {
                         update_spinner.finish_with_message(format!("{} {} - {}", "✗".red(), db, e));
@@ -146,6 +150,21 @@ pub fn run(args: UpdateArgs) -> Result<()> {
                     }
                 }
             }
+
+            for (db, report) in &update_reports {
+                println!();
+                let items = vec![
+                    ("Version", report.version.clone()),
+                    ("Previous", report.previous_version.clone()),
+                    ("Added", format_number(report.sequences_added)),
+                    ("Removed", format_number(report.sequences_removed)),
+                    ("Modified", format_number(report.sequences_modified)),
+                    ("Unchanged", format_number(report.sequences_unchanged)),
+                    ("Chunks reused", format_number(report.chunks_reused)),
+                    ("Chunks created", format_number(report.chunks_created)),
+                ];
+                tree_section(db, items, true);
+            }
         } else {
             println!();
             info(&format!(
@@ -195,6 +214,8 @@ pub fn run(args: UpdateArgs) -> Result<()> {
                 unique_to_b: Vec::new(),
                 shared_percentage_a: 0.0,
                 shared_percentage_b: 0.0,
+                reused_bytes_b: 0,
+                new_bytes_b: 0,
             },
             sequence_analysis: SequenceAnalysis {
                 total_sequences_a: 0,
@@ -254,31 +275,32 @@ enum UpdateStatus {
     NotFound,
 }
 
-fn check_database_update(
-    manager: &mut talaria_herald::database::DatabaseManager,
-    database: &str,
-    force: bool,
-) -> Result<UpdateStatus> {
+/// Map a database reference to the source it is updated from
+fn update_source(database: &str) -> Result<Option<talaria_herald::download::DatabaseSource>> {
     use talaria_herald::download::{DatabaseSource, NCBIDatabase, UniProtDatabase};
     use talaria_utils::database::database_ref::parse_database_ref;
 
-    // Parse database reference to get source
     let (source_str, dataset) = parse_database_ref(database)?;
 
-    // Map to DatabaseSource enum
-    let source = match source_str.as_str() {
-        "uniprot" => match dataset.as_str() {
-            "swissprot" => DatabaseSource::UniProt(UniProtDatabase::SwissProt),
-            "trembl" => DatabaseSource::UniProt(UniProtDatabase::TrEMBL),
-            _ => return Ok(UpdateStatus::NotFound),
-        },
-        "ncbi" => match dataset.as_str() {
-            "nr" => DatabaseSource::NCBI(NCBIDatabase::NR),
-            "nt" => DatabaseSource::NCBI(NCBIDatabase::NT),
-            "taxonomy" => DatabaseSource::NCBI(NCBIDatabase::Taxonomy),
-            _ => return Ok(UpdateStatus::NotFound),
-        },
-        _ => return Ok(UpdateStatus::NotFound),
+    let source = match (source_str.as_str(), dataset.as_str()) {
+        ("uniprot", "swissprot") => DatabaseSource::UniProt(UniProtDatabase::SwissProt),
+        ("uniprot", "trembl") => DatabaseSource::UniProt(UniProtDatabase::TrEMBL),
+        ("ncbi", "nr") => DatabaseSource::NCBI(NCBIDatabase::NR),
+        ("ncbi", "nt") => DatabaseSource::NCBI(NCBIDatabase::NT),
+        ("ncbi", "taxonomy") => DatabaseSource::NCBI(NCBIDatabase::Taxonomy),
+        _ => return Ok(None),
+    };
+
+    Ok(Some(source))
+}
+
+fn check_database_update(
+    manager: &mut talaria_herald::database::DatabaseManager,
+    database: &str,
+    force: bool,
+) -> Result<UpdateStatus> {
+    let Some(source) = update_source(database)? else {
+        return Ok(UpdateStatus::NotFound);
     };
 
     // Use async runtime to check for updates
@@ -360,18 +382,29 @@ fn check_taxonomy_update(
     }
 }
 
-fn perform_update(database: &str) -> Result<()> {
-    // Use the download command with appropriate flags
-    use crate::cli::commands::database::download::DownloadArgs;
-    use talaria_utils::database::database_ref::parse_database_ref;
-
-    // Parse database reference
-    let (_source, _dataset) = parse_database_ref(database)?;
-
-    // Create download args for update (not dry-run, not force)
-    let mut download_args = DownloadArgs::default_with_database(database.to_string());
-    download_args.resume = true;
+fn perform_update(
+    manager: &mut talaria_herald::database::DatabaseManager,
+    database: &str,
+    spinner: &indicatif::ProgressBar,
+) -> Result<Option<talaria_herald::database::IncrementalUpdateReport>> {
+    use talaria_herald::download::{DatabaseSource, NCBIDatabase};
+
+    let source = match update_source(database)? {
+        // Taxonomy is not a sequence release, so there is nothing to diff
+        Some(DatabaseSource::NCBI(NCBIDatabase::Taxonomy)) | None => {
+            use crate::cli::commands::database::download::DownloadArgs;
+
+            let mut download_args = DownloadArgs::default_with_database(database.to_string());
+            download_args.resume = true;
+            crate::cli::commands::database::download::run(download_args)?;
+            return Ok(None);
+        }
+        Some(source) => source,
+    };
 
-    // Run download which will handle update if needed
-    crate::cli::commands::database::download::run(download_args)
+    // Stream the new release against the current version
+    let runtime = tokio::runtime::Runtime::new()?;
+    runtime.block_on(manager.update_incremental(&source, |msg: &str| {
+        spinner.set_message(format!("{}: {}", database, msg));
+    }))
 }
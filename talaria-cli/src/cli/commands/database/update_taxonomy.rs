@@ -17,6 +17,18 @@ pub struct UpdateTaxonomyArgs {
     /// Database repository path (default: ${TALARIA_HOME}/databases)
     #[arg(long)]
     pub db_path: Option<std::path::PathBuf>,
+
+    /// Reclassify existing databases against the new taxonomy (no re-chunking)
+    #[arg(long)]
+    pub reclassify: bool,
+
+    /// Taxonomy version to diff against (default: the one recorded in each database version)
+    #[arg(long, value_name = "VERSION")]
+    pub from: Option<String>,
+
+    /// Report taxon transitions affecting at least this many sequences
+    #[arg(long, default_value = "100")]
+    pub mass_threshold: usize,
 }
 
 pub fn run(args: UpdateTaxonomyArgs) -> Result<()> {
@@ -53,7 +65,6 @@ pub fn run(args: UpdateTaxonomyArgs) -> Result<()> {
                     "  {} Taxonomy is up-to-date, but force flag was set",
                     "ℹ".blue()
                 );
-                println!("  Force update not yet implemented");
             } else {
                 println!("{} Taxonomy is already up-to-date", "✓".green().bold());
             }
@@ -73,5 +84,108 @@ pub fn run(args: UpdateTaxonomyArgs) -> Result<()> {
         }
     }
 
+    // Reclassification only ever runs when asked for
+    if args.reclassify {
+        reclassify_databases(&mut manager, args.from.as_deref(), args.mass_threshold)?;
+    }
+
+    Ok(())
+}
+
+/// Apply the current taxonomy to every installed sequence database
+fn reclassify_databases(
+    manager: &mut talaria_herald::database::DatabaseManager,
+    from: Option<&str>,
+    mass_threshold: usize,
+) -> Result<()> {
+    use talaria_herald::database::DatabaseManager;
+    use talaria_herald::download::parse_database_source;
+
+    let previous_tree = from.map(|version| {
+        talaria_core::system::paths::talaria_taxonomy_version_dir(version).join("tree")
+    });
+
+    println!(
+        "{} Reclassifying databases against taxonomy {}...",
+        "►".cyan().bold(),
+        DatabaseManager::current_taxonomy_version().yellow()
+    );
+
+    let mut failed = Vec::new();
+    for db in manager.list_databases()? {
+        let source = match parse_database_source(&db.name) {
+            Ok(source) => source,
+            Err(e) => {
+                println!("  {} {}: {}", "✗".red(), db.name, e);
+                failed.push(db.name);
+                continue;
+            }
+        };
+        // Taxonomy data itself has nothing to reclassify
+        if DatabaseManager::is_taxonomy_database(&source) {
+            continue;
+        }
+
+        let report = match manager.reclassify_with_taxonomy(
+            &source,
+            previous_tree.as_deref(),
+            mass_threshold,
+            &|msg| println!("  {}", msg.dimmed()),
+        ) {
+            Ok(report) => report,
+            Err(e) => {
+                println!("  {} {}: {:#}", "✗".red(), db.name, e);
+                failed.push(db.name);
+                continue;
+            }
+        };
+
+        if !report.has_changes() {
+            println!(
+                "  {} {}: {} taxa unchanged",
+                "✓".green(),
+                db.name,
+                report.taxa_checked
+            );
+            continue;
+        }
+
+        println!(
+            "  {} {}: version {} (from {})",
+            "✓".green().bold(),
+            db.name,
+            report.version,
+            report.previous_version
+        );
+        println!(
+            "    Taxa: {} checked, {} merged, {} deleted, {} moved",
+            report.taxa_checked, report.taxa_merged, report.taxa_deleted, report.taxa_moved
+        );
+        println!(
+            "    Sequences reclassified: {} ({} chunks re-indexed)",
+            report.sequences_reclassified, report.chunks_reindexed
+        );
+        for event in &report.mass_reclassifications {
+            let taxon = |t: Option<talaria_core::types::TaxonId>| {
+                t.map_or_else(|| "none".to_string(), |t| t.0.to_string())
+            };
+            println!(
+                "    {} {} → {}: {} sequences",
+                "⚠".yellow(),
+                taxon(event.old_taxon),
+                taxon(event.new_taxon),
+                event.affected_sequences.len()
+            );
+        }
+    }
+
+    if !failed.is_empty() {
+        anyhow::bail!(
+            "Reclassification failed for {} database(s): {}",
+            failed.len(),
+            failed.join(", ")
+        );
+    }
+
     Ok(())
 }
@@ -449,8 +449,66 @@ fn show_info(args: InfoVersionArgs) -> Result<()> {
     info.push(("Sequence Version", manifest.sequence_version.clone()));
     info.push(("Taxonomy Version", manifest.taxonomy_version.clone()));
 
+    let pipeline = manager.version_pipeline(&db_ref.source, &db_ref.dataset, &timestamp)?;
+    if let Some(ref pipeline) = pipeline {
+        info.push((
+            "Pipeline",
+            format!("{} (sha256 {})", pipeline.name, &pipeline.sha256[..12]),
+        ));
+    }
+
+    let composition = manager.version_composition(&db_ref.source, &db_ref.dataset, &timestamp)?;
+    if let Some(ref composition) = composition {
+        info.push(("Composed from", composition.expression.clone()));
+    }
+
+    let screening = manager.version_screening(&db_ref.source, &db_ref.dataset, &timestamp)?;
+    if let Some(ref report) = screening {
+        let panels: Vec<&str> = report.panels.iter().map(|p| p.name.as_str()).collect();
+        info.push((
+            "Screening",
+            format!("{} ({} findings)", panels.join(", "), report.findings.len()),
+        ));
+    }
+
     tree_section("Details", info, false);
 
+    if let Some(composition) = composition {
+        println!("\n{}", "Operand versions:".bold());
+        for operand in &composition.operands {
+            println!(
+                "  {}@{} -> {} ({} sequences)",
+                operand.database, operand.requested, operand.version, operand.sequences
+            );
+        }
+    }
+
+    if let Some(pipeline) = pipeline {
+        println!("\n{}", "Pipeline definition:".bold());
+        for line in pipeline.definition.lines() {
+            println!("  {}", line);
+        }
+    }
+
+    if let Some(report) = screening.filter(|r| !r.findings.is_empty()) {
+        const SHOWN: usize = 10;
+        println!("\n{}", "Contamination findings:".bold());
+        for finding in report.findings.iter().take(SHOWN) {
+            println!(
+                "  {} {} ({} in {}, {} k-mers, confidence {:.2})",
+                finding.sequence_id,
+                finding.action,
+                finding.contaminant,
+                finding.panel,
+                finding.matched_kmers,
+                finding.confidence
+            );
+        }
+        if report.findings.len() > SHOWN {
+            println!("  ... and {} more", report.findings.len() - SHOWN);
+        }
+    }
+
     println!("\n{} Manifest stored in RocksDB", "✓".green().bold());
 
     Ok(())
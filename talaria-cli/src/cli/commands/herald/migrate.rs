@@ -0,0 +1,128 @@
+use anyhow::Result;
+use clap::Args;
+use colored::*;
+use std::path::PathBuf;
+use talaria_herald::operations::{FormatMigrator, MigrationOptions};
+use talaria_herald::storage::CURRENT_FORMAT_VERSION;
+use talaria_herald::HeraldStorage;
+
+#[derive(Args)]
+pub struct MigrateArgs {
+    /// Path to HERALD repository
+    #[arg(short, long)]
+    pub path: Option<PathBuf>,
+
+    /// Show the migration plan without changing anything
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Undo an interrupted migration
+    #[arg(long, conflicts_with = "dry_run")]
+    pub rollback: bool,
+}
+
+pub fn run(args: MigrateArgs) -> Result<()> {
+    let base_path = if let Some(p) = args.path {
+        p
+    } else {
+        use talaria_core::system::paths;
+        paths::talaria_databases_dir()
+    };
+
+    if !base_path.exists() {
+        anyhow::bail!("HERALD repository not found at {}", base_path.display());
+    }
+
+    // Opening checked would refuse the formats this command migrates
+    let storage = HeraldStorage::open_unchecked(&base_path)?;
+    let migrator = FormatMigrator::new(&storage);
+
+    if args.rollback {
+        println!(
+            "{} Rolling back migration of {}...",
+            "►".cyan().bold(),
+            base_path.display()
+        );
+        migrator.rollback()?;
+        println!(
+            "{} Repository restored to format {}",
+            "✓".green().bold(),
+            migrator.current_version()?
+        );
+        return Ok(());
+    }
+
+    let pending = migrator.pending_checkpoint()?;
+    let plan = match (&pending, migrator.plan()?) {
+        (Some(checkpoint), _) => checkpoint.plan.clone(),
+        (None, Some(plan)) => plan,
+        (None, None) => {
+            println!(
+                "{} Repository already uses format {}",
+                "✓".green().bold(),
+                CURRENT_FORMAT_VERSION
+            );
+            return Ok(());
+        }
+    };
+
+    println!(
+        "{} Migrating {} from format {} to {}",
+        "►".cyan().bold(),
+        base_path.display(),
+        plan.from_version,
+        plan.to_version
+    );
+    if let Some(checkpoint) = &pending {
+        println!(
+            "  Resuming migration started {} ({} of {} steps done)",
+            checkpoint.started_at.format("%Y-%m-%d %H:%M:%S UTC"),
+            checkpoint.completed_steps.len(),
+            plan.steps.len()
+        );
+    }
+    println!();
+    for (i, step) in plan.steps.iter().enumerate() {
+        let done = pending
+            .as_ref()
+            .is_some_and(|c| c.completed_steps.contains(&step.name));
+        let marker = if done { "✓".green() } else { "•".normal() };
+        println!("  {} {}. {}", marker, i + 1, step.name.bold());
+        println!("       {}", step.description.dimmed());
+    }
+    println!();
+
+    if args.dry_run {
+        println!(
+            "{} Dry run, nothing changed. Run without --dry-run to migrate.",
+            "⚠".yellow().bold()
+        );
+        return Ok(());
+    }
+
+    let options = MigrationOptions {
+        create_backup: true,
+        verify_after: true,
+        ..Default::default()
+    };
+    let result = migrator.execute(&plan, &options, &|message: &str| {
+        println!("  {}", message);
+    })?;
+
+    println!();
+    if result.success {
+        println!(
+            "{} Repository migrated to format {} in {}s",
+            "✓".green().bold(),
+            plan.to_version,
+            result.duration_seconds
+        );
+        Ok(())
+    } else {
+        anyhow::bail!(
+            "Migration step '{}' failed and was rolled back: {}",
+            result.failed_steps.join(", "),
+            result.error.unwrap_or_default()
+        )
+    }
+}
@@ -1,6 +1,8 @@
 #![allow(dead_code)]
 
 pub mod history;
+pub mod migrate;
+pub mod remote;
 pub mod sync;
 pub mod time_travel;
 pub mod verify_storage;
@@ -24,6 +26,12 @@ pub enum HeraldCommands {
     /// Initialize a new HERALD repository
     Init(InitArgs),
 
+    /// Upgrade the repository's on-disk format
+    Migrate(migrate::MigrateArgs),
+
+    /// Fetch missing chunks on demand from a remote (thin clone)
+    Remote(remote::RemoteArgs),
+
     /// Show HERALD repository statistics
     Stats(StatsArgs),
 
@@ -39,6 +47,14 @@ pub struct InitArgs {
     /// Path to initialize HERALD repository
     #[arg(short, long)]
     pub path: Option<std::path::PathBuf>,
+
+    /// Create a thin clone that fetches chunks on demand from this remote
+    #[arg(long)]
+    pub remote: Option<String>,
+
+    /// Maximum size of locally cached chunks for a thin clone (e.g. 20G)
+    #[arg(long, requires = "remote", value_parser = remote::parse_size)]
+    pub cache_size: Option<u64>,
 }
 
 #[derive(Args)]
@@ -53,6 +69,8 @@ pub fn run(args: HeraldArgs) -> anyhow::Result<()> {
         HeraldCommands::Sync(args) => sync::run(args),
         HeraldCommands::History(args) => history::run(args),
         HeraldCommands::Init(args) => run_init(args),
+        HeraldCommands::Migrate(args) => migrate::run(args),
+        HeraldCommands::Remote(args) => remote::run(args),
         HeraldCommands::Stats(args) => run_stats(args),
         HeraldCommands::TimeTravel(args) => time_travel::run(args),
         HeraldCommands::VerifyStorage(args) => verify_storage::run(args),
@@ -83,12 +101,22 @@ fn run_init(args: InitArgs) -> anyhow::Result<()> {
 
     std::fs::create_dir_all(&path)?;
     HeraldRepository::init(&path)?;
+    let mut bootstrapped = 0;
+    if let Some(url) = &args.remote {
+        let cache_size = args.cache_size.unwrap_or(remote::DEFAULT_CACHE_SIZE);
+        remote::configure(&path, url, cache_size)?;
+        bootstrapped = talaria_herald::HeraldStorage::open(&path)?.bootstrap_from_remote()?;
+    }
 
     println!(
         "{} HERALD repository initialized successfully!",
         "✓".green().bold()
     );
     println!("  Path: {}", path.display());
+    if let Some(url) = &args.remote {
+        println!("  Remote: {} (chunks fetched on demand)", url);
+        println!("  Manifest entries copied: {}", bootstrapped);
+    }
 
     Ok(())
 }
@@ -0,0 +1,149 @@
+use anyhow::Result;
+use clap::Args;
+use colored::*;
+use std::path::{Path, PathBuf};
+use talaria_herald::storage::ThinCloneConfig;
+use talaria_herald::HeraldStorage;
+use talaria_utils::display::format::format_bytes;
+
+/// Default cache budget of a thin clone
+pub const DEFAULT_CACHE_SIZE: u64 = 20 * 1024 * 1024 * 1024;
+
+#[derive(Args)]
+pub struct RemoteArgs {
+    /// Path to HERALD repository
+    #[arg(short, long)]
+    pub path: Option<PathBuf>,
+
+    /// Remote to fetch missing chunks from (s3://, gs://, https://, file://)
+    pub url: Option<String>,
+
+    /// Maximum size of locally cached chunks (e.g. 500M, 20G)
+    #[arg(long, value_parser = parse_size)]
+    pub cache_size: Option<u64>,
+
+    /// Stop fetching from the remote; chunks already cached are kept
+    #[arg(long, conflicts_with_all = ["url", "cache_size"])]
+    pub disable: bool,
+}
+
+/// Parse a byte count with an optional K/M/G/T suffix (powers of 1024)
+pub fn parse_size(s: &str) -> Result<u64, String> {
+    let s = s.trim();
+    let split = s
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("invalid size '{}'", s))?;
+    let multiplier: u64 = match unit.trim().to_ascii_uppercase().trim_end_matches('B') {
+        "" => 1,
+        "K" | "KI" => 1 << 10,
+        "M" | "MI" => 1 << 20,
+        "G" | "GI" => 1 << 30,
+        "T" | "TI" => 1 << 40,
+        _ => return Err(format!("unknown size unit in '{}'", s)),
+    };
+    Ok((number * multiplier as f64) as u64)
+}
+
+/// Make `path` a thin clone of `url`
+pub fn configure(path: &Path, url: &str, cache_size: u64) -> Result<()> {
+    ThinCloneConfig::new(url, cache_size).save(path)
+}
+
+pub fn run(args: RemoteArgs) -> Result<()> {
+    let base_path = if let Some(p) = args.path {
+        p
+    } else {
+        use talaria_core::system::paths;
+        paths::talaria_databases_dir()
+    };
+
+    if !base_path.exists() {
+        anyhow::bail!("HERALD repository not found at {}", base_path.display());
+    }
+
+    let existing = ThinCloneConfig::load(&base_path)?;
+
+    if args.disable {
+        if existing.is_none() {
+            println!("{} Repository is not a thin clone", "⚠".yellow().bold());
+            return Ok(());
+        }
+        ThinCloneConfig::remove(&base_path)?;
+        println!(
+            "{} Remote disabled; cached chunks are kept as local data",
+            "✓".green().bold()
+        );
+        return Ok(());
+    }
+
+    if args.url.is_some() || args.cache_size.is_some() {
+        let url = match (args.url, &existing) {
+            (Some(url), _) => url,
+            (None, Some(config)) => config.remote_url.clone(),
+            (None, None) => anyhow::bail!("A remote URL is required to enable a thin clone"),
+        };
+        let cache_size = args
+            .cache_size
+            .or(existing.as_ref().map(|c| c.cache_budget))
+            .unwrap_or(DEFAULT_CACHE_SIZE);
+        configure(&base_path, &url, cache_size)?;
+
+        // Apply a lowered budget right away
+        let storage = HeraldStorage::open(&base_path)?;
+        let evicted = storage.prune_remote_cache(cache_size)?;
+
+        println!(
+            "{} Fetching missing chunks from {}",
+            "✓".green().bold(),
+            url.bold()
+        );
+        println!("  Cache budget: {}", format_bytes(cache_size));
+        if evicted > 0 {
+            println!("  Evicted {} cached chunks", evicted);
+        }
+        return Ok(());
+    }
+
+    let storage = HeraldStorage::open(&base_path)?;
+    match storage.remote_source() {
+        Some(remote) => {
+            let (chunks, bytes) = remote.cached();
+            println!(
+                "{} Thin clone of {}",
+                "►".cyan().bold(),
+                remote.config().remote_url.bold()
+            );
+            println!(
+                "  Cached: {} chunks, {} of {}",
+                chunks,
+                format_bytes(bytes),
+                format_bytes(remote.config().cache_budget)
+            );
+        }
+        None => println!(
+            "{} Full repository; all chunks are stored locally",
+            "►".cyan().bold()
+        ),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("1024").unwrap(), 1024);
+        assert_eq!(parse_size("20G").unwrap(), 20 << 30);
+        assert_eq!(parse_size("1.5k").unwrap(), 1536);
+        assert_eq!(parse_size("500MB").unwrap(), 500 << 20);
+        assert!(parse_size("12X").is_err());
+        assert!(parse_size("G").is_err());
+    }
+}
@@ -62,6 +62,11 @@ pub struct SyncArgs {
     /// Show sync status without performing sync
     #[arg(long)]
     pub status: bool,
+
+    /// Upload the layout thin clones read (`herald init --remote`) instead of
+    /// the repository files; implies `--direction upload`
+    #[arg(long, conflicts_with = "status")]
+    pub publish: bool,
 }
 
 pub fn run(args: SyncArgs) -> Result<()> {
@@ -115,8 +120,24 @@ pub fn run(args: SyncArgs) -> Result<()> {
     // Create cloud storage
     let storage = tokio::runtime::Runtime::new()?.block_on(async { create_storage(&config) })?;
 
+    // Thin clones read a published layout rather than the repository files
+    let sync_path = if args.publish {
+        use talaria_core::system::paths;
+        let staging = paths::talaria_cache_dir().join("publish");
+        println!("{} Publishing chunks and manifests...", "►".cyan().bold());
+        let herald = talaria_herald::HeraldStorage::open(&local_path)?;
+        let report = talaria_herald::storage::thin_clone::publish(&herald, &staging)?;
+        println!(
+            "  {} chunks written, {} unchanged, {} manifest entries",
+            report.chunks_written, report.chunks_unchanged, report.manifest_entries
+        );
+        staging
+    } else {
+        local_path.clone()
+    };
+
     // Create sync manager
-    let sync_manager = CloudSyncManager::new(storage, local_path.clone(), args.prefix.clone());
+    let sync_manager = CloudSyncManager::new(storage, sync_path, args.prefix.clone());
 
     // Show status if requested
     if args.status {
@@ -151,6 +172,7 @@ pub fn run(args: SyncArgs) -> Result<()> {
 
     // Parse sync direction
     let direction = match args.direction.to_lowercase().as_str() {
+        _ if args.publish => SyncDirection::Upload,
         "upload" | "push" => SyncDirection::Upload,
         "download" | "pull" => SyncDirection::Download,
         "bidirectional" | "both" | "sync" => SyncDirection::Bidirectional,
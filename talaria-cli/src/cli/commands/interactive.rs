@@ -101,11 +101,15 @@ pub fn run(_args: InteractiveArgs) -> anyhow::Result<()> {
 
 fn run_app<B: ratatui::backend::Backend>(terminal: &mut Terminal<B>) -> anyhow::Result<()> {
     let mut selected_index = 0;
-    let menu_items = vec![
+    let menu_items = [
         (
             "▼ Download databases",
             "Download and manage biological databases",
         ),
+        (
+            "▣ Browse repository",
+            "Explore stored databases, versions, taxa and sequences",
+        ),
         (
             "▶ Reduce a FASTA file",
             "Intelligently reduce a FASTA file for indexing",
@@ -258,16 +262,21 @@ fn run_app<B: ratatui::backend::Backend>(terminal: &mut Terminal<B>) -> anyhow::
                             crate::cli::interactive::download::run_download_wizard(terminal)?;
                         }
                         1 => {
+                            // Browse repository
+                            terminal.clear()?;
+                            crate::cli::interactive::browser::run_repository_browser(terminal)?;
+                        }
+                        2 => {
                             // Reduce FASTA
                             terminal.clear()?;
                             crate::cli::interactive::reduce::run_reduce_wizard(terminal)?;
                         }
-                        2 => {
+                        3 => {
                             // View statistics
                             terminal.clear()?;
                             crate::cli::interactive::stats::run_stats_viewer(terminal)?;
                         }
-                        3 => {
+                        4 => {
                             // Setup wizard - need to temporarily exit raw mode for dialoguer
                             terminal.clear()?;
 
@@ -283,17 +292,17 @@ fn run_app<B: ratatui::backend::Backend>(terminal: &mut Terminal<B>) -> anyhow::
                             terminal.hide_cursor()?;
                             terminal.clear()?;
                         }
-                        4 => {
+                        5 => {
                             // Configure settings
                             terminal.clear()?;
                             crate::cli::interactive::config_editor::run_config_editor(terminal)?;
                         }
-                        5 => {
+                        6 => {
                             // View documentation
                             terminal.clear()?;
                             crate::cli::interactive::docs_viewer::run_docs_viewer(terminal)?;
                         }
-                        6 => return Ok(()), // Exit
+                        7 => return Ok(()), // Exit
                         _ => {}
                     }
                 }
@@ -323,9 +332,8 @@ fn save_wizard_config(
     }
 
     // Get save path
-    let default_path = dirs::config_dir()
-        .map(|p| p.join("talaria").join("config.toml"))
-        .unwrap_or_else(|| PathBuf::from("config.toml"));
+    let default_path =
+        talaria_core::config::user_config_path().unwrap_or_else(|| PathBuf::from("config.toml"));
 
     let path_str: String = Input::with_theme(&ColorfulTheme::default())
         .with_prompt("Configuration file path")
@@ -369,6 +377,8 @@ fn save_wizard_config(
             auto_update_check: false,
             preferred_mirror: Some("ebi".to_string()),
         },
+        chunking: Default::default(),
+        backup: Default::default(),
     };
 
     // Save config
@@ -0,0 +1,167 @@
+use anyhow::Result;
+use clap::{Args, Subcommand};
+use std::path::PathBuf;
+use talaria_herald::database::lockfile::LOCKFILE_NAME;
+
+#[derive(Args)]
+#[command(args_conflicts_with_subcommands = true)]
+pub struct LockArgs {
+    #[command(subcommand)]
+    pub command: Option<LockCommands>,
+
+    #[command(flatten)]
+    pub create: CreateLockArgs,
+}
+
+#[derive(Subcommand)]
+pub enum LockCommands {
+    /// Check that the local state matches a lockfile
+    Verify(LockFileArgs),
+
+    /// Switch databases, taxonomy and tools back to the locked versions,
+    /// fetching missing chunks from TALARIA_REMOTE_REPO or TALARIA_CHUNK_SERVER
+    Restore(LockFileArgs),
+}
+
+#[derive(Args)]
+pub struct CreateLockArgs {
+    /// Database references to pin (e.g., "uniprot/swissprot@stable:blast-30")
+    pub databases: Vec<String>,
+
+    /// Lockfile to write
+    #[arg(short, long, default_value = LOCKFILE_NAME)]
+    pub output: PathBuf,
+
+    /// Database repository path (default: ${TALARIA_HOME}/databases)
+    #[arg(long)]
+    pub db_path: Option<PathBuf>,
+}
+
+#[derive(Args)]
+pub struct LockFileArgs {
+    /// Lockfile to read
+    #[arg(default_value = LOCKFILE_NAME)]
+    pub lockfile: PathBuf,
+
+    /// Database repository path (default: ${TALARIA_HOME}/databases)
+    #[arg(long)]
+    pub db_path: Option<PathBuf>,
+}
+
+pub fn run(args: LockArgs) -> Result<()> {
+    match args.command {
+        Some(LockCommands::Verify(args)) => run_verify(args),
+        Some(LockCommands::Restore(args)) => run_restore(args),
+        None => run_create(args.create),
+    }
+}
+
+fn open_manager(
+    db_path: Option<PathBuf>,
+) -> Result<(
+    talaria_herald::database::DatabaseManager,
+    talaria_tools::ToolManager,
+)> {
+    let manager = talaria_herald::database::DatabaseManager::new(
+        db_path.map(|p| p.to_string_lossy().to_string()),
+    )?;
+    let tools = talaria_tools::ToolManager::new()?;
+    Ok((manager, tools))
+}
+
+fn run_create(args: CreateLockArgs) -> Result<()> {
+    use crate::cli::formatting::output::*;
+    use talaria_herald::database::AnalysisLock;
+
+    if args.databases.is_empty() {
+        anyhow::bail!("No databases given. Usage: talaria lock <DATABASE>...");
+    }
+
+    let (manager, tools) = open_manager(args.db_path)?;
+    let lock = AnalysisLock::resolve(&manager, &args.databases, &tools)?;
+    lock.save(&args.output)?;
+
+    section_header("Lockfile");
+    for (i, db) in lock.databases.iter().enumerate() {
+        let mut items = vec![
+            ("Version", db.version.clone()),
+            ("Sequence root", db.sequence_root.clone()),
+            ("Taxonomy root", db.taxonomy_root.clone()),
+            ("Chunks", format_number(db.chunk_count)),
+            ("Sequences", format_number(db.sequence_count)),
+        ];
+        if let Some(profile) = &db.profile {
+            items.push(("Profile", profile.name.clone()));
+        }
+        tree_section(&db.reference, items, i + 1 == lock.databases.len());
+    }
+    if let Some(taxonomy) = &lock.taxonomy_version {
+        info(&format!("Taxonomy: {}", taxonomy));
+    }
+    for tool in &lock.tools {
+        info(&format!("Tool: {} {}", tool.name, tool.version));
+    }
+    success(&format!("Wrote {}", args.output.display()));
+
+    Ok(())
+}
+
+fn run_verify(args: LockFileArgs) -> Result<()> {
+    use crate::cli::formatting::output::*;
+    use talaria_herald::database::AnalysisLock;
+
+    let lock = AnalysisLock::load(&args.lockfile)?;
+    let (manager, tools) = open_manager(args.db_path)?;
+
+    action(&format!("Verifying {}...", args.lockfile.display()));
+    let mismatches = lock.verify(&manager, &tools);
+    if mismatches.is_empty() {
+        success(&format!(
+            "Local state matches the lockfile ({} databases, {} tools)",
+            lock.databases.len(),
+            lock.tools.len()
+        ));
+        return Ok(());
+    }
+
+    for mismatch in &mismatches {
+        error(&mismatch.to_string());
+    }
+    anyhow::bail!(
+        "{} difference(s) from {}; run 'talaria lock restore' to switch back",
+        mismatches.len(),
+        args.lockfile.display()
+    )
+}
+
+fn run_restore(args: LockFileArgs) -> Result<()> {
+    use crate::cli::formatting::output::*;
+    use talaria_herald::database::AnalysisLock;
+
+    let lock = AnalysisLock::load(&args.lockfile)?;
+    let (manager, tools) = open_manager(args.db_path)?;
+
+    action(&format!("Restoring {}...", args.lockfile.display()));
+    let report = lock.restore(&manager, &tools)?;
+    if report.chunks_fetched > 0 {
+        info(&format!(
+            "Fetched {} missing chunks from the remote",
+            format_number(report.chunks_fetched)
+        ));
+    }
+    for restored in &report.restored {
+        success(restored);
+    }
+    for missing in &report.missing {
+        warning(&format!("{} is not available locally", missing));
+    }
+
+    if !report.missing.is_empty() {
+        anyhow::bail!(
+            "{} locked item(s) missing; download the database versions or install the tools first",
+            report.missing.len()
+        );
+    }
+
+    Ok(())
+}
@@ -2,8 +2,10 @@ pub mod chunk;
 pub mod database;
 pub mod herald;
 pub mod interactive;
+pub mod lock;
 pub mod reconstruct;
 pub mod reduce;
+pub mod serve;
 pub mod stats;
 pub mod temporal;
 pub mod tools;
@@ -33,3 +35,27 @@ pub fn save_report<T: Reportable>(result: &T, format: &str, output_path: &Path)
     std::fs::write(output_path, content)?;
     Ok(())
 }
+
+/// Load the current taxonomy for evaluating taxonomy filter expressions
+///
+/// Names resolve through the taxon name index built from `names.dmp`.
+/// Returns `None` when no taxonomy has been downloaded; filters then fall
+/// back to taxon IDs and a small table of well-known names.
+pub fn load_filter_taxonomy() -> Option<talaria_herald::taxonomy::TaxonomyManager> {
+    use talaria_herald::taxonomy::TaxonomyManager;
+    use talaria_utils::taxonomy::{get_taxonomy_tree_path, has_taxonomy};
+
+    if !has_taxonomy() {
+        return None;
+    }
+
+    let tree_path = get_taxonomy_tree_path();
+    let mut manager = TaxonomyManager::load(&tree_path).ok()?;
+    if !manager.has_taxonomy() {
+        manager.load_ncbi_taxonomy_quiet(&tree_path).ok()?;
+    }
+    if let Err(e) = manager.load_name_index(&tree_path) {
+        tracing::debug!("Taxon name index unavailable: {}", e);
+    }
+    Some(manager)
+}
@@ -34,6 +34,13 @@ pub struct ReconstructArgs {
     #[arg(long)]
     pub sequences: Vec<String>,
 
+    /// File of sequence IDs to reconstruct, one per line ('#' starts a comment)
+    ///
+    /// Profiles with an accession index rebuild only these sequences instead
+    /// of walking every chunk
+    #[arg(long, value_name = "FILE")]
+    pub accession_file: Option<PathBuf>,
+
     /// List available sequences without reconstructing (dry run)
     #[arg(long)]
     pub list_only: bool,
@@ -64,7 +71,7 @@ pub struct ReconstructArgs {
     pub report_format: String,
 }
 
-pub fn run(args: ReconstructArgs) -> anyhow::Result<()> {
+pub fn run(mut args: ReconstructArgs) -> anyhow::Result<()> {
     use indicatif::{ProgressBar, ProgressStyle};
     use talaria_utils::display::format::{format_bytes, get_file_size};
 
@@ -73,6 +80,15 @@ pub fn run(args: ReconstructArgs) -> anyhow::Result<()> {
         return show_version_history(&args);
     }
 
+    // IDs from --accession-file add to those given with --sequences
+    if let Some(path) = &args.accession_file {
+        let accessions = read_accession_file(path)?;
+        if accessions.is_empty() {
+            anyhow::bail!("No sequence IDs found in {}", path.display());
+        }
+        args.sequences.extend(accessions);
+    }
+
     // Create progress bar
     let pb = ProgressBar::new_spinner();
     pb.set_style(
@@ -349,6 +365,79 @@ pub fn run(args: ReconstructArgs) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Read sequence IDs from a file, one per line
+///
+/// Blank lines and `#` comments are skipped. Only the first word of a line is
+/// used and a leading `>` is dropped, so FASTA header lines work as-is.
+fn read_accession_file(path: &std::path::Path) -> anyhow::Result<Vec<String>> {
+    use anyhow::Context;
+
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read accession file {}", path.display()))?;
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.trim_start_matches('>').split_whitespace().next())
+        .map(String::from)
+        .collect())
+}
+
+/// Rebuild only the requested sequences through the profile's accession index
+fn reconstruct_by_accession(
+    storage: &talaria_herald::HeraldStorage,
+    index: &talaria_herald::operations::StoredAccessionIndex,
+    accessions: &[String],
+    output_path: &PathBuf,
+    pb: indicatif::ProgressBar,
+) -> anyhow::Result<()> {
+    use talaria_herald::operations::AccessionReconstructor;
+
+    pb.set_message(format!(
+        "Reconstructing {} sequences by accession...",
+        format_number(accessions.len())
+    ));
+    let result = AccessionReconstructor::new(storage, index).reconstruct(accessions)?;
+    talaria_bio::write_fasta(output_path, &result.sequences)?;
+    pb.finish_and_clear();
+
+    subsection_header("Reconstruction Summary");
+    tree_item(false, "Profile", Some(index.profile()));
+    tree_item(
+        false,
+        "Reconstructed",
+        Some(&format_number(result.sequences.len())),
+    );
+    tree_item(
+        false,
+        "Not in profile",
+        Some(&format_number(result.missing.len())),
+    );
+    tree_item(
+        true,
+        "Output file",
+        Some(&output_path.display().to_string()),
+    );
+
+    if !result.missing.is_empty() {
+        let shown: Vec<&str> = result.missing.iter().take(10).map(String::as_str).collect();
+        warning(&format!(
+            "{} requested sequences are not in profile '{}': {}{}",
+            format_number(result.missing.len()),
+            index.profile,
+            shown.join(", "),
+            if result.missing.len() > shown.len() {
+                ", ..."
+            } else {
+                ""
+            }
+        ));
+    }
+
+    success("Reconstruction complete!");
+    Ok(())
+}
+
 /// Parse a database reference that may include a reduction profile
 /// Format: `source/dataset[\:profile][@version]`
 /// Returns: `(base_reference, Option<String>)` where the Option contains the profile name
@@ -450,6 +539,17 @@ fn reconstruct_from_herald(
             )
         })?;
 
+    if !sequence_filter.is_empty() {
+        if let Some(index) =
+            storage.get_accession_index(source, dataset, &db_info.version, profile_name)?
+        {
+            return reconstruct_by_accession(&storage, &index, &sequence_filter, output_path, pb);
+        }
+        warning(
+            "Profile has no accession index (reduced by an older version); scanning all chunks",
+        );
+    }
+
     pb.set_message(format!(
         "Found profile '{}' with {} reference chunks and {} delta chunks",
         profile,
@@ -550,23 +650,31 @@ fn reconstruct_from_herald_database(
     pb: indicatif::ProgressBar,
 ) -> anyhow::Result<()> {
     use std::collections::HashSet;
+    use talaria_herald::database::DatabaseManager;
     use talaria_herald::ReductionManifest;
-    use talaria_herald::{DeltaReconstructor, FastaAssembler, HeraldStorage};
+    use talaria_herald::{DeltaReconstructor, FastaAssembler};
 
     let herald_path = herald_path.clone().unwrap_or_else(|| {
         use talaria_core::system::paths;
         paths::talaria_databases_dir()
     });
 
-    // Open HERALD storage
-    let storage = HeraldStorage::new(&herald_path)?;
+    // Open HERALD storage through the database manager, which resolves
+    // aliases such as "current" to the version the profile was stored under
+    let manager = DatabaseManager::new(Some(herald_path.to_string_lossy().to_string()))?;
+    let storage = &manager.get_repository().storage;
+    let version = manager.resolve_version_reference(
+        &db_ref.source,
+        &db_ref.dataset,
+        db_ref.version_or_default(),
+    )?;
 
     // Load reduction manifest from version-specific location
     let manifest_path = herald_path
         .join("versions")
         .join(&db_ref.source)
         .join(&db_ref.dataset)
-        .join(db_ref.version_or_default())
+        .join(&version)
         .join("profiles");
 
     // Try .tal format first
@@ -593,6 +701,17 @@ fn reconstruct_from_herald_database(
         );
     };
 
+    if !sequence_filter.is_empty() {
+        if let Some(index) =
+            storage.get_accession_index(&db_ref.source, &db_ref.dataset, &version, profile)?
+        {
+            return reconstruct_by_accession(storage, &index, &sequence_filter, output_path, pb);
+        }
+        warning(
+            "Profile has no accession index (reduced by an older version); scanning all chunks",
+        );
+    }
+
     pb.set_message(format!(
         "Found profile '{}' with {} reference chunks and {} delta chunks",
         profile,
@@ -610,7 +729,7 @@ fn reconstruct_from_herald_database(
 
     // Reconstruct reference sequences using the assembler
     pb.set_message("Loading reference sequences from chunks...");
-    let assembler = FastaAssembler::new(&storage);
+    let assembler = FastaAssembler::new(storage);
     let reference_hashes: Vec<_> = manifest
         .reference_chunks
         .iter()
@@ -666,7 +785,7 @@ fn reconstruct_from_herald_database(
     println!("\nReconstruction Statistics:");
     println!("  Database: {}", db_ref.base_ref());
     println!("  Profile: {}", profile);
-    println!("  Version: {}", db_ref.version_or_default());
+    println!("  Version: {}", version);
     println!(
         "  Total sequences: {}",
         manifest.statistics.original_sequences
@@ -763,6 +882,7 @@ mod tests {
             herald_profile: None,
             herald_path: None,
             sequences: vec![],
+            accession_file: None,
             list_only: false,
             at_time: None,
             sequence_version: None,
@@ -903,6 +1023,7 @@ mod tests {
             herald_profile: None,
             herald_path: None,
             sequences: vec![],
+            accession_file: None,
             list_only: false,
             at_time: None,
             sequence_version: None,
@@ -920,6 +1041,7 @@ mod tests {
             herald_profile: Some("blast-30".to_string()),
             herald_path: None,
             sequences: vec![],
+            accession_file: None,
             list_only: false,
             at_time: None,
             sequence_version: None,
@@ -960,6 +1082,7 @@ mod tests {
             herald_profile: None,
             herald_path: None,
             sequences: vec![],
+            accession_file: None,
             list_only: true,
             at_time: None,
             sequence_version: None,
@@ -977,6 +1100,7 @@ mod tests {
             herald_profile: Some("blast-30".to_string()),
             herald_path: None,
             sequences: vec![],
+            accession_file: None,
             list_only: false,
             at_time: None,
             sequence_version: None,
@@ -1016,5 +1140,22 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_read_accession_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("hits.txt");
+        std::fs::write(
+            &path,
+            "# search hits\nP12345\n\n  Q9XYZ1  \n>sp|A0A001 Some protein\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            read_accession_file(&path).unwrap(),
+            vec!["P12345", "Q9XYZ1", "sp|A0A001"]
+        );
+        assert!(read_accession_file(&dir.path().join("missing.txt")).is_err());
+    }
+
     // Test removed - verbose field no longer exists in ReconstructArgs
 }
@@ -67,6 +67,10 @@ pub struct ReduceArgs {
     #[arg(long)]
     pub low_complexity_filter: bool,
 
+    /// Processing pipeline (TOML or YAML) to run on the input before reduction
+    #[arg(long, value_name = "FILE")]
+    pub pipeline: Option<PathBuf>,
+
     /// Use alignment-based selection instead of simple greedy
     #[arg(long)]
     pub align_select: bool,
@@ -418,6 +422,19 @@ pub fn run(mut args: ReduceArgs) -> anyhow::Result<()> {
     // Initialize formatter
     crate::cli::formatting::formatter::init();
 
+    let _metrics = crate::cli::metrics::start("reduce")?;
+
+    let pipeline = args
+        .pipeline
+        .as_deref()
+        .map(|path| {
+            super::database::pipeline::LoadedPipeline::load(
+                path,
+                &talaria_core::system::paths::talaria_databases_dir(),
+            )
+        })
+        .transpose()?;
+
     // Initialize HERALD workspace manager
     let mut herald_manager = HeraldWorkspaceManager::new()?;
 
@@ -671,8 +688,8 @@ pub fn run(mut args: ReduceArgs) -> anyhow::Result<()> {
     // Generate output database name for reduced version
     let _profile_or_ratio = if let Some(profile) = &args.profile {
         profile.clone()
-    } else if args.reduction_ratio.is_some() && args.reduction_ratio.unwrap() > 0.0 {
-        format!("{}pct", (args.reduction_ratio.unwrap() * 100.0) as u32)
+    } else if let Some(ratio) = args.reduction_ratio.filter(|&r| r > 0.0) {
+        format!("{}pct", (ratio * 100.0) as u32)
     } else {
         "auto".to_string()
     };
@@ -795,6 +812,13 @@ pub fn run(mut args: ReduceArgs) -> anyhow::Result<()> {
     const CHUNK_THRESHOLD: usize = 20_000_000;
     const CHUNK_SIZE: usize = 10_000_000;
 
+    if sequence_count > CHUNK_THRESHOLD && pipeline.is_some() {
+        anyhow::bail!(
+            "--pipeline needs all sequences in memory and is not supported above {} sequences",
+            format_number(CHUNK_THRESHOLD)
+        );
+    }
+
     if sequence_count > CHUNK_THRESHOLD {
         println!();
         info(&format!(
@@ -901,6 +925,9 @@ pub fn run(mut args: ReduceArgs) -> anyhow::Result<()> {
             }
         }
 
+        // Filters mark dropped sequences by clearing them
+        sequences.retain(|s| !s.sequence.is_empty());
+
         // Log if sequences were filtered
         let filtered_count = initial_count - sequences.len();
         if filtered_count > 0 {
@@ -912,6 +939,14 @@ pub fn run(mut args: ReduceArgs) -> anyhow::Result<()> {
         }
     }
 
+    if let Some(pipeline) = &pipeline {
+        task_list.set_task_message(
+            load_task,
+            &format!("Running pipeline '{}'...", pipeline.record.name),
+        );
+        sequences = pipeline.apply(sequences)?;
+    }
+
     // Keep a copy for the HTML report if needed
     let _original_sequences = if args.html_report.is_some() {
         sequences.clone()
@@ -1077,6 +1112,7 @@ pub fn run(mut args: ReduceArgs) -> anyhow::Result<()> {
             &source,
             &dataset,
             &db_version,
+            pipeline.as_ref(),
         )?;
 
         task_list.update_task(write_task, TaskStatus::Complete);
@@ -1196,9 +1232,10 @@ fn store_reduction_in_herald(
     source: &str,
     dataset: &str,
     version: &str,
+    pipeline: Option<&super::database::pipeline::LoadedPipeline>,
 ) -> anyhow::Result<u64> {
     // use talaria_herald::chunker::TaxonomicChunker; // Disabled until reduce is updated
-    use std::collections::HashMap;
+    use std::collections::{HashMap, HashSet};
     use std::time::Instant;
     use talaria_herald::SHA256Hash;
     use talaria_herald::{
@@ -1206,6 +1243,7 @@ fn store_reduction_in_herald(
         operations::reduction::{
             DeltaChunkRef, ReductionManifest, ReductionParameters, ReferenceChunk,
         },
+        operations::AccessionIndex,
         DeltaGenerator,
     };
 
@@ -1291,13 +1329,14 @@ fn store_reduction_in_herald(
         source_database.clone(),
         parameters,
     );
+    manifest.pipeline = pipeline.map(|p| p.record.clone());
+    manifest.screening = pipeline.and_then(|p| p.screening_report());
 
     // Chunk and store reference sequences using canonical storage
     action("Chunking reference sequences...");
 
     use std::sync::Arc;
     use talaria_herald::chunker::TaxonomicChunker;
-    use talaria_herald::ChunkingStrategy;
 
     // Reuse the existing SequenceStorage from HERALD repository
     // This avoids double-initialization of RocksDB
@@ -1324,9 +1363,10 @@ fn store_reduction_in_herald(
         _ => talaria_core::DatabaseSource::Custom(format!("{}/{}", source, dataset)),
     };
 
-    // Create chunker with canonical storage
-    let mut chunker =
-        TaxonomicChunker::new(ChunkingStrategy::default(), sequence_storage, db_source);
+    // Create chunker with canonical storage, honouring any chunking
+    // override saved for the source database
+    let strategy = db_manager.chunking_strategy(&db_source)?;
+    let mut chunker = TaxonomicChunker::new(strategy, sequence_storage, db_source);
 
     // Create progress bar for sequence processing
     let ref_count = references.len();
@@ -1364,6 +1404,8 @@ fn store_reduction_in_herald(
 
     let mut reference_chunk_refs = Vec::new();
     let mut ref_chunk_map = HashMap::new();
+    let mut accession_index = AccessionIndex::new(profile_name.clone());
+    let mut reference_hashes = HashMap::new();
 
     // Parallelize chunk manifest storage for better performance
     use rayon::prelude::*;
@@ -1382,10 +1424,12 @@ fn store_reduction_in_herald(
 
             // Get sequence IDs from the manifest's sequence_refs
             let mut sequence_ids = Vec::new();
+            let mut sequence_hashes = Vec::new();
             for seq_hash in &manifest.sequence_refs {
                 // Get sequence ID from the canonical storage
                 if let Ok(seq_info) = chunker.sequence_storage.get_sequence_info(seq_hash) {
                     sequence_ids.push(seq_info.id);
+                    sequence_hashes.push(*seq_hash);
                 }
             }
 
@@ -1405,17 +1449,19 @@ fn store_reduction_in_herald(
                 pb_clone.set_position(count as u64);
             }
 
-            Ok((ref_chunk, chunk_hash, sequence_ids))
+            Ok((ref_chunk, chunk_hash, sequence_ids, sequence_hashes))
         })
         .collect::<anyhow::Result<Vec<_>>>()?;
 
-    // Build reference_chunk_refs and ref_chunk_map from results
-    for (ref_chunk, chunk_hash, sequence_ids) in results {
+    // Build reference_chunk_refs, ref_chunk_map and the accession index from results
+    for (ref_chunk, chunk_hash, sequence_ids, sequence_hashes) in results {
         reference_chunk_refs.push(ref_chunk);
 
         // Map sequence IDs to chunk hash for delta processing
-        for seq_id in sequence_ids {
-            ref_chunk_map.insert(seq_id, chunk_hash.clone());
+        for (seq_id, seq_hash) in sequence_ids.into_iter().zip(sequence_hashes) {
+            ref_chunk_map.insert(seq_id.clone(), chunk_hash.clone());
+            accession_index.add_reference(seq_id.clone(), seq_hash);
+            reference_hashes.insert(seq_id, seq_hash);
         }
     }
 
@@ -1467,24 +1513,39 @@ fn store_reduction_in_herald(
             target_sequences_per_chunk: 1000,
             max_delta_ops_threshold: 100,
         };
-        let mut delta_generator = DeltaGenerator::new(delta_config);
-
-        // Convert delta records to sequences for delta generation
-        let all_child_sequences: Vec<talaria_bio::sequence::Sequence> = deltas
-            .iter()
-            .map(|d| talaria_bio::sequence::Sequence {
-                id: d.child_id.clone(),
-                description: None,
-                sequence: Vec::new(), // Will be filled by delta generator
-                taxon_id: d.taxon_id,
-                taxonomy_sources: Default::default(),
-            })
-            .collect();
-
-        let all_ref_sequences: Vec<talaria_bio::sequence::Sequence> = references.to_vec();
+        let delta_generator = DeltaGenerator::new(delta_config);
+
+        // The reducer already encoded each child against its reference, so keep
+        // those records and remember which reference each child needs
+        let mut child_references = HashMap::new();
+        let mut delta_records = Vec::with_capacity(deltas.len());
+        let mut unknown_references = HashSet::new();
+        for delta in deltas {
+            match reference_hashes.get(&delta.reference_id) {
+                Some(reference_hash) => {
+                    child_references.insert(delta.child_id.clone(), *reference_hash);
+                    delta_records.push(delta.clone());
+                }
+                None => {
+                    unknown_references.insert(delta.reference_id.as_str());
+                }
+            }
+        }
+        // A child whose reference was not stored could never be rebuilt
+        if !unknown_references.is_empty() {
+            let dropped = deltas.len() - delta_records.len();
+            let mut shown: Vec<&str> = unknown_references.iter().copied().take(5).collect();
+            shown.sort_unstable();
+            anyhow::bail!(
+                "{} delta records point at {} references that were not stored (e.g. {})",
+                format_number(dropped),
+                format_number(unknown_references.len()),
+                shown.join(", ")
+            );
+        }
 
         // Generate delta chunks using the new system
-        if !all_child_sequences.is_empty() && !all_ref_sequences.is_empty() {
+        if !delta_records.is_empty() {
             // Get the first reference chunk hash as the base
             let base_ref_hash = ref_chunk_map
                 .values()
@@ -1492,11 +1553,11 @@ fn store_reduction_in_herald(
                 .ok_or_else(|| anyhow::anyhow!("No reference chunks available"))?;
 
             info("Generating delta chunks...");
-            let delta_chunks = delta_generator.generate_delta_chunks(
-                &all_child_sequences,
-                &all_ref_sequences,
-                base_ref_hash.clone(),
-            )?;
+            let delta_chunks =
+                delta_generator.chunk_delta_records(delta_records, base_ref_hash.clone())?;
+            for delta_chunk in &delta_chunks {
+                accession_index.add_delta_chunk(delta_chunk, &child_references);
+            }
 
             // Add progress bar for delta chunk storage
             let delta_progress = ProgressBar::new(delta_chunks.len() as u64);
@@ -1567,6 +1628,9 @@ fn store_reduction_in_herald(
     let manifest_hash = herald
         .storage
         .store_database_reduction_manifest(&manifest, source, dataset, version)?;
+    herald
+        .storage
+        .store_accession_index(&accession_index, source, dataset, version)?;
 
     // Update metadata cache with the new reduction profile
     // This ensures it shows up immediately in database list/info
@@ -1595,6 +1659,10 @@ fn store_reduction_in_herald(
             format!("{} chunks", format_number(manifest.delta_chunks.len())),
         ));
     }
+    details.push((
+        "Accession index",
+        format!("{} sequences", format_number(accession_index.len())),
+    ));
     details.push(("Merkle root", manifest.reduction_merkle_root.to_string()));
     details.push((
         "Deduplication",
@@ -1899,6 +1967,7 @@ fn reduce_in_chunks(
         &source,
         &dataset,
         &db_version,
+        None,
     )?;
 
     task_list.update_task(write_task, TaskStatus::Complete);
@@ -0,0 +1,79 @@
+//! Read-only HTTP query API over a HERALD repository
+//!
+//! `talaria serve` exposes JSON endpoints for databases, versions, reference
+//! resolution, sequence lookup and Merkle proofs, plus streaming FASTA for
+//! taxonomic subsets and bi-temporal queries. Nothing in the API writes to
+//! the repository.
+
+mod routes;
+
+use anyhow::{Context, Result};
+use axum::routing::get;
+use axum::Router;
+use clap::Args;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use talaria_herald::database::DatabaseManager;
+
+#[derive(Args)]
+pub struct ServeArgs {
+    /// Address to listen on
+    #[arg(short, long, default_value = "127.0.0.1:8080")]
+    pub bind: SocketAddr,
+
+    /// Database repository path (default: ${TALARIA_HOME}/databases)
+    #[arg(long)]
+    pub db_path: Option<PathBuf>,
+}
+
+/// Shared state of all request handlers
+#[derive(Clone)]
+pub struct ServerState {
+    manager: Arc<DatabaseManager>,
+}
+
+/// Routes of the query API
+pub fn router(manager: Arc<DatabaseManager>) -> Router {
+    let state = ServerState { manager };
+
+    Router::new()
+        .route("/api/v1/health", get(routes::health))
+        .route("/api/v1/databases", get(routes::list_databases))
+        .route(
+            "/api/v1/databases/:source/:dataset/versions",
+            get(routes::list_versions),
+        )
+        .route("/api/v1/resolve", get(routes::resolve))
+        .route("/api/v1/sequences/:accession", get(routes::get_sequence))
+        .route("/api/v1/subset", get(routes::taxonomic_subset))
+        .route("/api/v1/temporal", get(routes::temporal_query))
+        .route("/api/v1/proofs/:chunk_hash", get(routes::chunk_proof))
+        .route("/metrics", get(routes::metrics))
+        .with_state(state)
+}
+
+pub fn run(args: ServeArgs) -> Result<()> {
+    use crate::cli::formatting::output::*;
+
+    let _metrics = crate::cli::metrics::start("serve")?;
+    let manager = DatabaseManager::new(args.db_path.map(|p| p.to_string_lossy().to_string()))?;
+    let app = router(Arc::new(manager));
+
+    let runtime = tokio::runtime::Runtime::new()?;
+    runtime.block_on(async {
+        let server = axum::Server::try_bind(&args.bind)
+            .with_context(|| format!("Failed to listen on {}", args.bind))?
+            .serve(app.into_make_service());
+
+        success(&format!("Serving HERALD query API on http://{}", args.bind));
+        info("Press Ctrl+C to stop");
+
+        server
+            .with_graceful_shutdown(async {
+                let _ = tokio::signal::ctrl_c().await;
+            })
+            .await?;
+        Ok(())
+    })
+}
@@ -0,0 +1,566 @@
+use super::ServerState;
+use axum::body::{Bytes, StreamBody};
+use axum::extract::{Path, Query, State};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::io::{self, Write};
+use talaria_bio::sequence::Sequence;
+use talaria_herald::database::{DatabaseManager, LockedDatabase};
+use talaria_herald::operations::FastaAssembler;
+use talaria_herald::storage::ResolutionOutcome;
+use talaria_herald::{MerkleDAG, MerkleProof, SHA256Hash, TaxonId, Verifier};
+
+/// Chunks loaded per step while streaming a subset or temporal query
+const SUBSET_BATCH_CHUNKS: usize = 64;
+
+/// Bytes buffered before a streamed block is handed to the client
+const STREAM_BLOCK_SIZE: usize = 64 * 1024;
+
+const FASTA_CONTENT_TYPE: &str = "text/x-fasta; charset=utf-8";
+
+/// Error returned to API clients as `{"error": "..."}`
+pub enum ApiError {
+    BadRequest(String),
+    NotFound(String),
+    Internal(anyhow::Error),
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, message) = match self {
+            ApiError::BadRequest(message) => (StatusCode::BAD_REQUEST, message),
+            ApiError::NotFound(message) => (StatusCode::NOT_FOUND, message),
+            ApiError::Internal(e) => {
+                tracing::error!("Request failed: {:#}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}", e))
+            }
+        };
+        (status, Json(serde_json::json!({ "error": message }))).into_response()
+    }
+}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(e: anyhow::Error) -> Self {
+        ApiError::Internal(e)
+    }
+}
+
+type ApiResult<T> = Result<T, ApiError>;
+
+/// Run repository work off the async executor
+async fn blocking<T, F>(state: &ServerState, f: F) -> ApiResult<T>
+where
+    T: Send + 'static,
+    F: FnOnce(&DatabaseManager) -> ApiResult<T> + Send + 'static,
+{
+    let manager = state.manager.clone();
+    tokio::task::spawn_blocking(move || f(&manager))
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?
+}
+
+/// Stream FASTA produced by `produce` on a blocking thread
+///
+/// Production stops with a broken pipe as soon as the client disconnects.
+fn stream_fasta<F>(state: &ServerState, produce: F) -> Response
+where
+    F: FnOnce(&DatabaseManager, &mut dyn Write) -> anyhow::Result<usize> + Send + 'static,
+{
+    let (tx, rx) = tokio::sync::mpsc::channel::<io::Result<Bytes>>(16);
+    let manager = state.manager.clone();
+
+    tokio::task::spawn_blocking(move || {
+        let mut writer =
+            io::BufWriter::with_capacity(STREAM_BLOCK_SIZE, ChannelWriter { tx: tx.clone() });
+        let result = produce(&manager, &mut writer).and_then(|count| {
+            writer.flush()?;
+            Ok(count)
+        });
+        match result {
+            Ok(count) => tracing::debug!("Streamed {} sequences", count),
+            Err(e) => {
+                tracing::warn!("FASTA stream aborted: {:#}", e);
+                let _ = tx.blocking_send(Err(io::Error::other(e.to_string())));
+            }
+        }
+    });
+
+    let body = futures::stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|block| (block, rx))
+    });
+    (
+        [(header::CONTENT_TYPE, FASTA_CONTENT_TYPE)],
+        StreamBody::new(body),
+    )
+        .into_response()
+}
+
+/// `Write` end of a streamed response body
+struct ChannelWriter {
+    tx: tokio::sync::mpsc::Sender<io::Result<Bytes>>,
+}
+
+impl Write for ChannelWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.tx
+            .blocking_send(Ok(Bytes::copy_from_slice(buf)))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "client disconnected"))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn write_fasta(writer: &mut dyn Write, sequences: &[Sequence]) -> io::Result<()> {
+    for sequence in sequences {
+        writeln!(writer, "{}", sequence.header())?;
+        writer.write_all(&sequence.sequence)?;
+        writer.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+/// Parse a comma-separated list of taxon IDs
+fn parse_taxa(taxa: &str) -> ApiResult<Vec<u32>> {
+    taxa.split(',')
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(|t| {
+            t.parse()
+                .map_err(|_| ApiError::BadRequest(format!("Invalid taxon ID '{}'", t)))
+        })
+        .collect()
+}
+
+fn resolve_reference(manager: &DatabaseManager, reference: &str) -> ApiResult<LockedDatabase> {
+    LockedDatabase::resolve(manager, reference).map_err(|e| ApiError::NotFound(format!("{:#}", e)))
+}
+
+pub async fn health() -> Json<serde_json::Value> {
+    Json(serde_json::json!({
+        "status": "ok",
+        "version": env!("CARGO_PKG_VERSION"),
+    }))
+}
+
+/// Prometheus metrics, when enabled with `--metrics-listen` or `--metrics-textfile`
+pub async fn metrics() -> ApiResult<Response> {
+    use talaria_herald::performance::metrics;
+
+    let registry = metrics::registry()
+        .ok_or_else(|| ApiError::NotFound("Metrics are not enabled".to_string()))?;
+    Ok((
+        [(header::CONTENT_TYPE, metrics::CONTENT_TYPE)],
+        registry.render(),
+    )
+        .into_response())
+}
+
+pub async fn list_databases(State(state): State<ServerState>) -> ApiResult<impl IntoResponse> {
+    let databases = blocking(&state, |manager| Ok(manager.list_databases()?)).await?;
+    Ok(Json(databases))
+}
+
+pub async fn list_versions(
+    State(state): State<ServerState>,
+    Path((source, dataset)): Path<(String, String)>,
+) -> ApiResult<impl IntoResponse> {
+    let versions = blocking(&state, move |manager| {
+        let versions = manager.list_database_versions(&source, &dataset)?;
+        if versions.is_empty() {
+            return Err(ApiError::NotFound(format!(
+                "No versions of {}/{}",
+                source, dataset
+            )));
+        }
+        Ok(versions)
+    })
+    .await?;
+    Ok(Json(versions))
+}
+
+#[derive(Deserialize)]
+pub struct ReferenceQuery {
+    /// `source/dataset[@version][:profile]`
+    #[serde(rename = "ref")]
+    reference: String,
+}
+
+pub async fn resolve(
+    State(state): State<ServerState>,
+    Query(query): Query<ReferenceQuery>,
+) -> ApiResult<impl IntoResponse> {
+    let resolved = blocking(&state, move |manager| {
+        resolve_reference(manager, &query.reference)
+    })
+    .await?;
+    Ok(Json(resolved))
+}
+
+#[derive(Deserialize)]
+pub struct SequenceQuery {
+    /// `json` (default) or `fasta`
+    #[serde(default)]
+    format: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct SequenceResponse {
+    accession: String,
+    hash: String,
+    length: usize,
+    header: String,
+    sequence: String,
+}
+
+pub async fn get_sequence(
+    State(state): State<ServerState>,
+    Path(accession): Path<String>,
+    Query(query): Query<SequenceQuery>,
+) -> ApiResult<Response> {
+    let as_fasta = match query.format.as_deref() {
+        None | Some("json") => false,
+        Some("fasta") => true,
+        Some(other) => {
+            return Err(ApiError::BadRequest(format!(
+                "Unknown format '{}'. Use: json, fasta",
+                other
+            )))
+        }
+    };
+
+    let found = blocking(&state, move |manager| {
+        let storage = &manager.get_storage().sequence_storage;
+        let resolution = storage.resolve_accession(&accession)?;
+        let ResolutionOutcome::Current {
+            accession: current,
+            hash,
+        } = &resolution.outcome
+        else {
+            return Err(ApiError::NotFound(resolution.describe()));
+        };
+        // Identical sequences share one entry; use the header of the one asked for
+        let fasta = storage.get_sequence_as_fasta_for_accession(hash, current)?;
+        let (header, sequence) = fasta.split_once('\n').unwrap_or((fasta.as_str(), ""));
+        Ok(SequenceResponse {
+            length: sequence.len(),
+            header: header.trim_start_matches('>').to_string(),
+            sequence: sequence.to_string(),
+            hash: hash.to_hex(),
+            accession,
+        })
+    })
+    .await?;
+
+    if as_fasta {
+        let body = format!(">{}\n{}\n", found.header, found.sequence);
+        Ok(([(header::CONTENT_TYPE, FASTA_CONTENT_TYPE)], body).into_response())
+    } else {
+        Ok(Json(found).into_response())
+    }
+}
+
+#[derive(Deserialize)]
+pub struct SubsetQuery {
+    #[serde(rename = "ref")]
+    reference: String,
+    /// Comma-separated taxon IDs
+    taxa: String,
+}
+
+pub async fn taxonomic_subset(
+    State(state): State<ServerState>,
+    Query(query): Query<SubsetQuery>,
+) -> ApiResult<Response> {
+    let taxa: Vec<TaxonId> = parse_taxa(&query.taxa)?.into_iter().map(TaxonId).collect();
+    if taxa.is_empty() {
+        return Err(ApiError::BadRequest("No taxon IDs given".to_string()));
+    }
+
+    // Resolve before streaming so a bad reference is reported with a status
+    let reference = query.reference;
+    let (manifest, relevant) = blocking(&state, move |manager| {
+        let resolved = resolve_reference(manager, &reference)?;
+        if resolved.profile.is_some() {
+            return Err(ApiError::BadRequest(
+                "Reduction profiles cannot be streamed as subsets".to_string(),
+            ));
+        }
+        let manifest = manager.get_manifest(&format!(
+            "{}/{}@{}",
+            resolved.source, resolved.dataset, resolved.version
+        ))?;
+        let relevant: Vec<_> = manifest
+            .chunk_index
+            .iter()
+            .filter(|chunk| chunk.taxon_ids.iter().any(|tid| taxa.contains(tid)))
+            .cloned()
+            .collect();
+        Ok((manifest, (relevant, taxa)))
+    })
+    .await?;
+
+    Ok(stream_fasta(&state, move |manager, writer| {
+        let (relevant, taxa) = relevant;
+        let assembler = FastaAssembler::new(manager.get_storage());
+
+        // Assemble a few chunks at a time so memory stays bounded
+        let mut batch_manifest = manifest;
+        let mut count = 0;
+        for batch in relevant.chunks(SUBSET_BATCH_CHUNKS) {
+            batch_manifest.chunk_index = batch.to_vec();
+            let sequences = assembler.assemble_taxonomic_subset(&taxa, &batch_manifest)?;
+            write_fasta(writer, &sequences)?;
+            count += sequences.len();
+        }
+        Ok(count)
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct TemporalQuery {
+    /// RFC 3339 time of the sequence data
+    sequence_time: DateTime<Utc>,
+    /// RFC 3339 time of the taxonomy; defaults to `sequence_time`
+    taxonomy_time: Option<DateTime<Utc>>,
+    /// Comma-separated taxon IDs
+    taxa: Option<String>,
+}
+
+pub async fn temporal_query(
+    State(state): State<ServerState>,
+    Query(query): Query<TemporalQuery>,
+) -> ApiResult<Response> {
+    let taxa = query.taxa.as_deref().map(parse_taxa).transpose()?;
+    let taxonomy_time = query.taxonomy_time.unwrap_or(query.sequence_time);
+
+    // Resolve before streaming so a bad coordinate is reported with a status
+    let chunk_hashes = blocking(&state, move |manager| {
+        Ok(manager.chunks_at_time(query.sequence_time, taxonomy_time, taxa)?)
+    })
+    .await?;
+
+    Ok(stream_fasta(&state, move |manager, writer| {
+        // Load a few chunks at a time so memory stays bounded
+        let mut count = 0;
+        for batch in chunk_hashes.chunks(SUBSET_BATCH_CHUNKS) {
+            let sequences = manager.get_repository().load_sequences_from_chunks(batch)?;
+            write_fasta(writer, &sequences)?;
+            count += sequences.len();
+        }
+        Ok(count)
+    }))
+}
+
+#[derive(Serialize)]
+pub struct ProofResponse {
+    reference: String,
+    version: String,
+    chunk_hash: String,
+    /// Chunk Merkle root stored with the version's manifest
+    manifest_root: Option<String>,
+    /// Whether the stored chunk bytes hash to `chunk_hash`
+    content_verified: bool,
+    /// Whether the proof leads to `manifest_root` and the content matches
+    verified: bool,
+    proof: MerkleProof,
+}
+
+pub async fn chunk_proof(
+    State(state): State<ServerState>,
+    Path(chunk_hash): Path<String>,
+    Query(query): Query<ReferenceQuery>,
+) -> ApiResult<impl IntoResponse> {
+    let hash = SHA256Hash::from_hex(&chunk_hash)
+        .map_err(|_| ApiError::BadRequest(format!("Invalid chunk hash '{}'", chunk_hash)))?;
+
+    let response = blocking(&state, move |manager| {
+        let resolved = resolve_reference(manager, &query.reference)?;
+        let manifest = manager.get_manifest(&format!(
+            "{}/{}@{}",
+            resolved.source, resolved.dataset, resolved.version
+        ))?;
+
+        let dag = MerkleDAG::build_from_items(manifest.chunk_index.clone())?;
+        let proof = dag.generate_proof_by_hash(&hash).map_err(|_| {
+            ApiError::NotFound(format!(
+                "Chunk {} is not part of {}",
+                chunk_hash,
+                resolved.label()
+            ))
+        })?;
+
+        // The proof is built from the same index, so it only means something
+        // against the root recorded when the version was created
+        let manifest_root = manifest
+            .chunk_merkle_tree
+            .as_ref()
+            .map(|tree| tree.root_hash);
+        let storage = manager.get_storage();
+        let content_verified = storage.has_chunk(&hash)
+            && Verifier::new(storage, &manifest)
+                .verify_chunk(&hash)
+                .is_ok();
+        let verified = content_verified
+            && manifest_root == Some(proof.root_hash)
+            && MerkleDAG::verify_proof(&proof, &[]);
+
+        Ok(ProofResponse {
+            reference: query.reference,
+            version: resolved.version,
+            chunk_hash,
+            manifest_root: manifest_root.map(|root| root.to_hex()),
+            content_verified,
+            verified,
+            proof,
+        })
+    })
+    .await?;
+    Ok(Json(response))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    /// Repository under `dir` whose current version of custom/test_serve holds `sequences`
+    fn repository_with(dir: &std::path::Path, sequences: &[(&str, &str)]) -> Arc<DatabaseManager> {
+        let mut manager =
+            DatabaseManager::new(Some(dir.join("repo").to_string_lossy().into_owned())).unwrap();
+        let sequences = sequences
+            .iter()
+            .map(|(id, seq)| Sequence {
+                id: id.to_string(),
+                description: Some("serve".to_string()),
+                sequence: seq.as_bytes().to_vec(),
+                taxon_id: Some(562),
+                taxonomy_sources: Default::default(),
+            })
+            .collect();
+        manager
+            .chunk_sequences_direct_with_progress_final(
+                sequences,
+                &talaria_core::DatabaseSource::Custom("test_serve".to_string()),
+                None,
+                true,
+            )
+            .unwrap();
+        Arc::new(manager)
+    }
+
+    /// Status and JSON body of a GET request
+    async fn get_json(
+        manager: &Arc<DatabaseManager>,
+        uri: &str,
+    ) -> (StatusCode, serde_json::Value) {
+        use axum::body::{Body, HttpBody};
+        use tower::ServiceExt;
+
+        let request = axum::http::Request::get(uri).body(Body::empty()).unwrap();
+        let response = super::super::router(manager.clone())
+            .oneshot(request)
+            .await
+            .unwrap();
+        let status = response.status();
+        let mut body = response.into_body();
+        let mut data = Vec::new();
+        while let Some(block) = body.data().await {
+            data.extend_from_slice(&block.unwrap());
+        }
+        (status, serde_json::from_slice(&data).unwrap())
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_sequence_route_uses_header_of_requested_accession() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::env::set_var("TALARIA_HOME", dir.path());
+        // Identical sequences are stored once under both accessions
+        let manager = repository_with(
+            dir.path(),
+            &[("SEQ_A", "ACGTACGTACGTAAAA"), ("SEQ_B", "ACGTACGTACGTAAAA")],
+        );
+
+        for accession in ["SEQ_A", "SEQ_B"] {
+            let (status, body) =
+                get_json(&manager, &format!("/api/v1/sequences/{}", accession)).await;
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(body["accession"], accession);
+            assert!(body["header"].as_str().unwrap().starts_with(accession));
+            assert_eq!(body["sequence"], "ACGTACGTACGTAAAA");
+        }
+
+        let (status, _) = get_json(&manager, "/api/v1/sequences/SEQ_MISSING").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        std::env::remove_var("TALARIA_HOME");
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_chunk_proof_route_checks_root_and_content() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::env::set_var("TALARIA_HOME", dir.path());
+        let manager = repository_with(
+            dir.path(),
+            &[
+                ("SEQ_001", "ACGTACGTACGTAAAA"),
+                ("SEQ_002", "GGGGCCCCGGGGCCCC"),
+            ],
+        );
+        let manifest = manager.get_manifest("custom/test_serve").unwrap();
+        let root = manifest.chunk_merkle_tree.as_ref().unwrap().root_hash;
+        let hash = manifest.chunk_index[0].hash;
+        let uri = format!("/api/v1/proofs/{}?ref=custom/test_serve", hash.to_hex());
+
+        let (status, body) = get_json(&manager, &uri).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["manifest_root"], root.to_hex());
+        assert_eq!(body["content_verified"], true);
+        assert_eq!(body["verified"], true);
+
+        // A proof for a chunk whose stored bytes changed must not verify
+        let chunks = manager.get_repository().storage.chunk_storage();
+        let original = chunks.load_chunk(&hash).unwrap();
+        chunks.store_chunk(&hash, b"tampered").unwrap();
+        let (status, body) = get_json(&manager, &uri).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["content_verified"], false);
+        assert_eq!(body["verified"], false);
+        chunks.store_chunk(&hash, &original).unwrap();
+
+        let unknown = SHA256Hash::compute(b"not a chunk").to_hex();
+        let (status, _) = get_json(
+            &manager,
+            &format!("/api/v1/proofs/{}?ref=custom/test_serve", unknown),
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, _) = get_json(&manager, "/api/v1/proofs/xyz?ref=custom/test_serve").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        std::env::remove_var("TALARIA_HOME");
+    }
+
+    #[test]
+    fn test_parse_taxa() {
+        assert_eq!(parse_taxa("9606, 10090,").ok(), Some(vec![9606, 10090]));
+        assert!(parse_taxa("9606,human").is_err());
+    }
+
+    #[test]
+    fn test_channel_writer_reports_disconnect() {
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        let mut writer = ChannelWriter { tx };
+        writer.write_all(b">seq\n").unwrap();
+        drop(rx);
+        let err = writer.write_all(b"ACGT\n").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::BrokenPipe);
+    }
+}
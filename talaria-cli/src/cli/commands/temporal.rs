@@ -41,7 +41,7 @@ pub struct ReproduceArgs {
     #[arg(long)]
     pub date: String,
 
-    /// Specific taxon to extract (name or ID)
+    /// Taxa to extract (ID, name or taxonomy filter expression)
     #[arg(long)]
     pub taxon: Option<String>,
 
@@ -68,7 +68,7 @@ pub struct RetroactiveArgs {
     #[arg(long)]
     pub taxonomy: String,
 
-    /// Filter by taxon IDs
+    /// Filter by taxon IDs, names or a taxonomy filter expression
     #[arg(long)]
     pub taxon_ids: Option<String>,
 
@@ -455,7 +455,9 @@ fn parse_date(date_str: &str) -> Result<DateTime<Utc>> {
 }
 
 fn parse_taxon_filter(taxon_str: &str) -> Result<Vec<TaxonId>> {
-    // Try to parse as comma-separated IDs first
+    use talaria_herald::taxonomy::filter::{FilterContext, TaxonomyFilter};
+
+    // Comma-separated IDs need no taxonomy
     if let Ok(ids) = taxon_str
         .split(',')
         .map(|s| s.trim().parse::<u32>().map(TaxonId))
@@ -464,14 +466,80 @@ fn parse_taxon_filter(taxon_str: &str) -> Result<Vec<TaxonId>> {
         return Ok(ids);
     }
 
-    // Otherwise treat as taxon name and look up
-    // For now, return empty vec - would need taxonomy lookup
-    use crate::cli::formatting::output::warning;
-    warning(&format!(
-        "Taxon name lookup not yet implemented for '{}'",
-        taxon_str
-    ));
-    Ok(vec![])
+    let filter = TaxonomyFilter::parse(taxon_str)?;
+    let taxa = match super::load_filter_taxonomy() {
+        Some(taxonomy) if taxonomy.name_index().is_some() => match &filter {
+            TaxonomyFilter::Name(name) => vec![resolve_taxon_name(&taxonomy, name)?],
+            _ => taxonomy.select_taxa(&filter)?,
+        },
+        Some(taxonomy) => taxonomy.select_taxa(&filter)?,
+        None => filter
+            .identity_taxa(&FilterContext::new())
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Filter '{}' needs taxonomy data. Download with: talaria database download ncbi/taxonomy",
+                    taxon_str
+                )
+            })?,
+    };
+
+    if taxa.is_empty() {
+        anyhow::bail!("No taxa match '{}'", taxon_str);
+    }
+
+    Ok(taxa)
+}
+
+/// Resolve a single taxon name, explaining homonyms and likely typos
+fn resolve_taxon_name(
+    taxonomy: &talaria_herald::taxonomy::TaxonomyManager,
+    name: &str,
+) -> Result<TaxonId> {
+    use talaria_herald::taxonomy::NameResolution;
+
+    match taxonomy.resolve_taxon_name(name)? {
+        NameResolution::Unique(taxon_id) => Ok(taxon_id),
+        NameResolution::Ambiguous(matches) => {
+            let candidates: Vec<String> = matches
+                .iter()
+                .map(|m| {
+                    let label = m.entry.unique_name.as_deref().unwrap_or(&m.entry.name);
+                    let kingdom: Vec<String> = taxonomy
+                        .get_lineage(&m.entry.taxon_id)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .filter(|n| {
+                            matches!(n.rank.as_str(), "superkingdom" | "domain" | "kingdom")
+                        })
+                        .map(|n| n.name)
+                        .collect();
+                    format!(
+                        "  {} - {} ({})",
+                        m.entry.taxon_id.0,
+                        label,
+                        kingdom.join(" > ")
+                    )
+                })
+                .collect();
+            anyhow::bail!(
+                "Taxon name '{}' is ambiguous, use a taxon ID or unique name:\n{}",
+                name,
+                candidates.join("\n")
+            )
+        }
+        NameResolution::NotFound { suggestions } if !suggestions.is_empty() => {
+            let candidates: Vec<String> = suggestions
+                .iter()
+                .map(|m| format!("  {} ({})", m.entry.name, m.entry.taxon_id.0))
+                .collect();
+            anyhow::bail!(
+                "Unknown taxon name '{}'. Did you mean:\n{}",
+                name,
+                candidates.join("\n")
+            )
+        }
+        NameResolution::NotFound { .. } => anyhow::bail!("Unknown taxon name '{}'", name),
+    }
 }
 
 fn write_sequences(sequences: &[talaria_bio::sequence::Sequence], path: &PathBuf) -> Result<()> {
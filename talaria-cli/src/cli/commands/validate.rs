@@ -40,6 +40,30 @@ pub struct ValidateArgs {
     /// Report output format (text, html, json, csv)
     #[arg(long = "report-format", value_name = "FORMAT", default_value = "text")]
     pub report_format: String,
+
+    /// Search queries against the original and reduced databases and report recall
+    #[arg(long)]
+    pub recall: bool,
+
+    /// Held-out query FASTA for --recall (default: sample from the original)
+    #[arg(long, value_name = "FILE", requires = "recall")]
+    pub queries: Option<PathBuf>,
+
+    /// Number of original sequences to sample as queries for --recall
+    #[arg(long, default_value = "1000")]
+    pub sample: usize,
+
+    /// Random seed for query sampling
+    #[arg(long, default_value = "42")]
+    pub seed: u64,
+
+    /// Aligner used for --recall
+    #[arg(long, default_value = "lambda")]
+    pub aligner: String,
+
+    /// NCBI taxonomy dump for per-rank agreement (default: current taxonomy)
+    #[arg(long, value_name = "DIR")]
+    pub taxonomy_dir: Option<PathBuf>,
 }
 
 /// Validate database reduction from HERALD system
@@ -238,6 +262,22 @@ pub fn run(args: ValidateArgs) -> anyhow::Result<()> {
             "Reduction profile required for validation. Use format: 'database:profile' (e.g., 'uniprot/swissprot:blast-30')"
         ))?;
 
+        if args.recall {
+            use talaria_herald::database::DatabaseManager;
+            use talaria_herald::operations::recall::load_profile_sequences;
+
+            pb.set_message("Loading reduction profile...");
+            let reference = talaria_core::types::DatabaseReference::parse(db_ref_str)?;
+            let profile = load_profile_sequences(&DatabaseManager::new(None)?, &reference)?;
+            pb.finish_and_clear();
+            return run_recall(
+                &args,
+                &profile.original,
+                &profile.references,
+                &profile.deltas,
+            );
+        }
+
         // Implement database validation for HERALD
         validate_from_herald(db_ref_str, _profile.to_string())?;
         return Ok(());
@@ -245,12 +285,15 @@ pub fn run(args: ValidateArgs) -> anyhow::Result<()> {
         // Traditional file-based usage
         let original = args
             .original
+            .clone()
             .ok_or_else(|| anyhow::anyhow!("Original file (-o) is required"))?;
         let reduced = args
             .reduced
+            .clone()
             .ok_or_else(|| anyhow::anyhow!("Reduced file (-r) is required"))?;
         let deltas = args
             .deltas
+            .clone()
             .ok_or_else(|| anyhow::anyhow!("Delta file (-d) is required"))?;
 
         if !original.exists() {
@@ -291,6 +334,11 @@ pub fn run(args: ValidateArgs) -> anyhow::Result<()> {
     let deltas = talaria_storage::io::metadata::load_metadata(&deltas_path)?;
     pb.set_message(format!("Loaded {} delta records", deltas.len()));
 
+    if args.recall {
+        pb.finish_and_clear();
+        return run_recall(&args, &original_seqs, &reduced_seqs, &deltas);
+    }
+
     // Calculate coverage metrics
     pb.set_message("Calculating validation metrics...");
     let validator = talaria_herald::operations::validator::ValidatorImpl::new();
@@ -303,7 +351,8 @@ pub fn run(args: ValidateArgs) -> anyhow::Result<()> {
     )?;
 
     // Compare alignment results if provided
-    if let (Some(orig_results), Some(red_results)) = (args.original_results, args.reduced_results) {
+    if let (Some(orig_results), Some(red_results)) = (&args.original_results, &args.reduced_results)
+    {
         pb.set_message("Comparing alignment results...");
         let alignment_metrics = validator.compare_alignments(&orig_results, &red_results)?;
         println!(
@@ -367,7 +416,7 @@ pub fn run(args: ValidateArgs) -> anyhow::Result<()> {
     }
 
     // TODO: Re-implement report generation using new generic framework
-    if let Some(_report_path) = args.report {
+    if let Some(_report_path) = &args.report {
         eprintln!(
             "Warning: Report generation is temporarily disabled pending migration to new framework"
         );
@@ -376,6 +425,130 @@ pub fn run(args: ValidateArgs) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Search-recall validation: run the aligner against both databases
+fn run_recall(
+    args: &ValidateArgs,
+    original: &[talaria_bio::sequence::Sequence],
+    references: &[talaria_bio::sequence::Sequence],
+    deltas: &[talaria_bio::compression::DeltaRecord],
+) -> Result<()> {
+    use crate::cli::formatting::output::*;
+    use crate::cli::progress::create_spinner;
+    use talaria_herald::operations::recall::{sample_queries, RecallValidator};
+    use talaria_tools::{Aligner, Tool, ToolManager};
+
+    let queries = match &args.queries {
+        Some(path) => talaria_bio::parse_fasta(path)?,
+        None => sample_queries(original, args.sample, args.seed),
+    };
+    if queries.is_empty() {
+        anyhow::bail!("No queries to search");
+    }
+
+    let tool: Tool = args.aligner.parse()?;
+    let mut aligner: Box<dyn Aligner> = match tool {
+        Tool::Lambda => {
+            let path = ToolManager::new()?.get_current_tool_path(Tool::Lambda)?;
+            Box::new(talaria_tools::LambdaAligner::new(path)?)
+        }
+        other => anyhow::bail!(
+            "{} is not supported for recall validation yet; use lambda",
+            other
+        ),
+    };
+
+    let taxonomy = load_recall_taxonomy(args.taxonomy_dir.as_deref())?;
+    if taxonomy.is_none() {
+        warning("No taxonomy available; only exact taxon agreement will be reported");
+    }
+
+    let pb = create_spinner(&format!(
+        "Searching {} queries with {}...",
+        format_number(queries.len()),
+        tool
+    ));
+    let mut validator = RecallValidator::new(aligner.as_mut());
+    if let Some(db) = &taxonomy {
+        validator = validator.with_taxonomy(db);
+    }
+    let result = validator.run(&queries, original, references, deltas)?;
+    pb.finish_and_clear();
+
+    subsection_header("Search Recall");
+    tree_section(
+        "Hits",
+        vec![
+            ("Queries", format_number(result.queries)),
+            (
+                "With original hits",
+                format_number(result.queries_with_hits),
+            ),
+            ("Original hits", format_number(result.original_hits)),
+            ("Recovered hits", format_number(result.recovered_hits)),
+            ("Sensitivity", format!("{:.2}%", result.sensitivity * 100.0)),
+            (
+                "Top-hit concordance",
+                format!("{:.2}%", result.top_hit_concordance * 100.0),
+            ),
+        ],
+        false,
+    );
+    tree_section(
+        "Taxonomic Agreement",
+        result
+            .rank_agreement
+            .iter()
+            .map(|r| {
+                (
+                    r.rank.as_str(),
+                    format!("{:.2}% ({}/{})", r.agreement * 100.0, r.agreed, r.compared),
+                )
+            })
+            .collect(),
+        false,
+    );
+    tree_section(
+        "E-value Shift (log10)",
+        vec![
+            ("Mean", format!("{:+.2}", result.evalue_shift.mean_log10)),
+            (
+                "Median",
+                format!("{:+.2}", result.evalue_shift.median_log10),
+            ),
+            ("Largest", format!("{:+.2}", result.evalue_shift.max_log10)),
+            ("Worse", format_number(result.evalue_shift.worse)),
+        ],
+        true,
+    );
+
+    if let Some(path) = args.report_output.as_ref().or(args.report.as_ref()) {
+        crate::cli::commands::save_report(&result, &args.report_format, path)?;
+        success(&format!("Report saved to {}", path.display()));
+    }
+
+    Ok(())
+}
+
+/// Load the NCBI taxonomy dump used for per-rank agreement, if any
+fn load_recall_taxonomy(dir: Option<&std::path::Path>) -> Result<Option<talaria_bio::TaxonomyDB>> {
+    use talaria_bio::taxonomy::core::ncbi;
+
+    let dir = match dir {
+        Some(dir) => dir.to_path_buf(),
+        None if talaria_utils::taxonomy::has_taxonomy() => {
+            talaria_utils::taxonomy::get_taxonomy_tree_path()
+        }
+        None => return Ok(None),
+    };
+
+    let names = dir.join("names.dmp");
+    let nodes = dir.join("nodes.dmp");
+    if !names.exists() || !nodes.exists() {
+        anyhow::bail!("No names.dmp/nodes.dmp in {}", dir.display());
+    }
+    Ok(Some(ncbi::build_taxonomy_db(&names, &nodes)?))
+}
+
 /// Parse a database reference that must include a reduction profile
 /// Format: `source/dataset[\:profile][@version]`
 /// Returns: `(base_reference, Option<String>)` where the Option contains the profile name
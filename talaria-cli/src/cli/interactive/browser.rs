@@ -0,0 +1,1470 @@
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ratatui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap},
+    Frame, Terminal,
+};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::io;
+use std::path::PathBuf;
+use talaria_bio::taxonomy::TaxonomyDB;
+use talaria_core::types::DatabaseVersionInfo;
+use talaria_herald::database::manager::DatabaseInfo;
+use talaria_herald::database::DatabaseManager;
+use talaria_herald::{
+    CanonicalSequence, ManifestMetadata, SHA256Hash, SequenceRepresentation, TaxonId,
+    TemporalManifest,
+};
+use talaria_utils::display::format::format_bytes;
+
+use crate::cli::commands::chunk::inspect::{get_taxonomy_name, load_taxonomy_db};
+use crate::cli::formatting::format_number;
+
+/// Sequences listed per chunk; larger chunks show a count of the rest
+const SEQUENCE_LIMIT: usize = 2000;
+/// Residues shown in the sequence detail pane
+const PREVIEW_LENGTH: usize = 600;
+/// Rows moved by PageUp/PageDown
+const PAGE: usize = 20;
+
+/// Taxa of a version's chunks, arranged along their lineages
+///
+/// Chunks count towards their primary taxon and every ancestor of it, as in
+/// `chunk inspect`, so each node carries the totals of its subtree.
+pub struct TaxonTree {
+    nodes: HashMap<TaxonId, TaxonNode>,
+    roots: Vec<TaxonId>,
+    expanded: HashSet<TaxonId>,
+}
+
+pub struct TaxonNode {
+    pub name: String,
+    pub rank: String,
+    pub parent: Option<TaxonId>,
+    pub children: Vec<TaxonId>,
+    pub chunks: usize,
+    pub sequences: usize,
+}
+
+impl TaxonTree {
+    /// `lineage` returns `(taxon, name, rank)` from the root down to the taxon
+    pub fn build<F>(chunks: &[ManifestMetadata], lineage: F) -> Self
+    where
+        F: Fn(TaxonId) -> Vec<(TaxonId, String, String)>,
+    {
+        let mut tree = Self {
+            nodes: HashMap::new(),
+            roots: Vec::new(),
+            expanded: HashSet::new(),
+        };
+        let mut lineages: HashMap<TaxonId, Vec<(TaxonId, String, String)>> = HashMap::new();
+
+        for chunk in chunks {
+            let primary = primary_taxon(chunk);
+            let path = lineages.entry(primary).or_insert_with(|| {
+                let path = lineage(primary);
+                if path.is_empty() {
+                    vec![(primary, format!("TaxID {}", primary.0), "no rank".into())]
+                } else {
+                    path
+                }
+            });
+
+            let mut parent = None;
+            for (taxon, name, rank) in path.iter() {
+                let mut is_new = false;
+                let node = tree.nodes.entry(*taxon).or_insert_with(|| {
+                    is_new = true;
+                    TaxonNode {
+                        name: name.clone(),
+                        rank: rank.clone(),
+                        parent,
+                        children: Vec::new(),
+                        chunks: 0,
+                        sequences: 0,
+                    }
+                });
+                node.chunks += 1;
+                node.sequences += chunk.sequence_count;
+                if is_new {
+                    match parent.and_then(|parent| tree.nodes.get_mut(&parent)) {
+                        Some(parent) => parent.children.push(*taxon),
+                        None => tree.roots.push(*taxon),
+                    }
+                }
+                parent = Some(*taxon);
+            }
+        }
+
+        let sequences: HashMap<TaxonId, usize> = tree
+            .nodes
+            .iter()
+            .map(|(taxon, node)| (*taxon, node.sequences))
+            .collect();
+        let by_size = |a: &TaxonId, b: &TaxonId| sequences[b].cmp(&sequences[a]).then(a.cmp(b));
+        for node in tree.nodes.values_mut() {
+            node.children.sort_by(by_size);
+        }
+        tree.roots.sort_by(by_size);
+        tree.expanded.extend(tree.roots.iter().copied());
+        tree
+    }
+
+    pub fn get(&self, taxon: TaxonId) -> Option<&TaxonNode> {
+        self.nodes.get(&taxon)
+    }
+
+    pub fn is_expanded(&self, taxon: TaxonId) -> bool {
+        self.expanded.contains(&taxon)
+    }
+
+    pub fn toggle(&mut self, taxon: TaxonId) {
+        if !self.expanded.remove(&taxon) {
+            self.expanded.insert(taxon);
+        }
+    }
+
+    pub fn expand(&mut self, taxon: TaxonId) {
+        self.expanded.insert(taxon);
+    }
+
+    pub fn collapse(&mut self, taxon: TaxonId) {
+        self.expanded.remove(&taxon);
+    }
+
+    /// Rows currently shown, as `(depth, taxon)` in display order
+    pub fn visible(&self) -> Vec<(usize, TaxonId)> {
+        let mut rows = Vec::new();
+        let mut stack: Vec<(usize, TaxonId)> =
+            self.roots.iter().rev().map(|taxon| (0, *taxon)).collect();
+        while let Some((depth, taxon)) = stack.pop() {
+            rows.push((depth, taxon));
+            if self.is_expanded(taxon) {
+                if let Some(node) = self.nodes.get(&taxon) {
+                    stack.extend(node.children.iter().rev().map(|child| (depth + 1, *child)));
+                }
+            }
+        }
+        rows
+    }
+
+    /// Whether `taxon` is `ancestor` or lies below it
+    pub fn is_within(&self, taxon: TaxonId, ancestor: TaxonId) -> bool {
+        let mut current = Some(taxon);
+        while let Some(id) = current {
+            if id == ancestor {
+                return true;
+            }
+            current = self.nodes.get(&id).and_then(|node| node.parent);
+        }
+        false
+    }
+
+    /// Chunks whose primary taxon lies within `ancestor`
+    pub fn chunks_within(
+        &self,
+        chunks: &[ManifestMetadata],
+        ancestor: TaxonId,
+    ) -> Vec<ManifestMetadata> {
+        chunks
+            .iter()
+            .filter(|chunk| self.is_within(primary_taxon(chunk), ancestor))
+            .cloned()
+            .collect()
+    }
+}
+
+fn primary_taxon(chunk: &ManifestMetadata) -> TaxonId {
+    chunk.taxon_ids.first().copied().unwrap_or(TaxonId(0))
+}
+
+/// What changed between a version and the one before it
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct VersionDiff {
+    pub chunks_added: usize,
+    pub chunks_removed: usize,
+    pub chunks_shared: usize,
+    pub sequences_before: usize,
+    pub sequences_after: usize,
+    pub taxa_added: Vec<TaxonId>,
+    pub taxa_removed: Vec<TaxonId>,
+}
+
+impl VersionDiff {
+    pub fn between(old: &[ManifestMetadata], new: &[ManifestMetadata]) -> Self {
+        let old_chunks: HashSet<SHA256Hash> = old.iter().map(|c| c.hash).collect();
+        let new_chunks: HashSet<SHA256Hash> = new.iter().map(|c| c.hash).collect();
+        let old_taxa: BTreeSet<TaxonId> = old
+            .iter()
+            .flat_map(|c| c.taxon_ids.iter().copied())
+            .collect();
+        let new_taxa: BTreeSet<TaxonId> = new
+            .iter()
+            .flat_map(|c| c.taxon_ids.iter().copied())
+            .collect();
+
+        Self {
+            chunks_added: new_chunks.difference(&old_chunks).count(),
+            chunks_removed: old_chunks.difference(&new_chunks).count(),
+            chunks_shared: new_chunks.intersection(&old_chunks).count(),
+            sequences_before: old.iter().map(|c| c.sequence_count).sum(),
+            sequences_after: new.iter().map(|c| c.sequence_count).sum(),
+            taxa_added: new_taxa.difference(&old_taxa).copied().collect(),
+            taxa_removed: old_taxa.difference(&new_taxa).copied().collect(),
+        }
+    }
+}
+
+struct SequenceRow {
+    hash: SHA256Hash,
+    id: String,
+    length: usize,
+    header: String,
+}
+
+/// The version being browsed below the version list
+struct OpenVersion {
+    source: String,
+    dataset: String,
+    timestamp: String,
+    manifest: TemporalManifest,
+}
+
+enum View {
+    Databases,
+    Versions {
+        source: String,
+        dataset: String,
+        versions: Vec<DatabaseVersionInfo>,
+    },
+    Taxonomy {
+        tree: TaxonTree,
+    },
+    Chunks {
+        title: String,
+        chunks: Vec<ManifestMetadata>,
+    },
+    Sequences {
+        chunk: ManifestMetadata,
+        rows: Vec<SequenceRow>,
+        total: usize,
+    },
+    Sequence {
+        id: String,
+        canonical: Option<CanonicalSequence>,
+        representations: Vec<SequenceRepresentation>,
+        scroll: u16,
+    },
+}
+
+struct Level {
+    view: View,
+    state: ListState,
+}
+
+/// Selection to export: versions and taxa go through `database export`
+/// outside the TUI, chunks and sequences are written to the working directory
+enum ExportRequest {
+    Version {
+        reference: String,
+        taxon: Option<TaxonId>,
+        output: Option<PathBuf>,
+    },
+    Chunk(ManifestMetadata),
+    Sequence(SHA256Hash, String),
+}
+
+pub struct RepositoryBrowser {
+    manager: DatabaseManager,
+    databases: Vec<DatabaseInfo>,
+    levels: Vec<Level>,
+    version: Option<OpenVersion>,
+    taxonomy: Option<Option<TaxonomyDB>>,
+    diffs: HashMap<(String, String, String), Option<VersionDiff>>,
+    status: String,
+}
+
+impl RepositoryBrowser {
+    pub fn new() -> anyhow::Result<Self> {
+        let manager = DatabaseManager::new(None)?;
+        let mut databases = manager.list_databases()?;
+        databases.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut browser = Self {
+            manager,
+            databases,
+            levels: Vec::new(),
+            version: None,
+            taxonomy: None,
+            diffs: HashMap::new(),
+            status: String::new(),
+        };
+        browser.push(View::Databases);
+        if browser.databases.is_empty() {
+            browser.status =
+                "No databases yet. Download or add one first, e.g. talaria database download uniprot/swissprot".into();
+        }
+        Ok(browser)
+    }
+
+    fn push(&mut self, view: View) {
+        let mut state = ListState::default();
+        state.select(Some(0));
+        self.levels.push(Level { view, state });
+    }
+
+    fn level(&self) -> &Level {
+        self.levels.last().expect("browser always has a level")
+    }
+
+    fn level_mut(&mut self) -> &mut Level {
+        self.levels.last_mut().expect("browser always has a level")
+    }
+
+    fn selected(&self) -> usize {
+        self.level().state.selected().unwrap_or(0)
+    }
+
+    fn row_count(&self) -> usize {
+        match &self.level().view {
+            View::Databases => self.databases.len(),
+            View::Versions { versions, .. } => versions.len(),
+            View::Taxonomy { tree } => tree.visible().len(),
+            View::Chunks { chunks, .. } => chunks.len(),
+            View::Sequences { rows, .. } => rows.len(),
+            View::Sequence { .. } => 0,
+        }
+    }
+
+    fn move_by(&mut self, delta: isize) {
+        if let View::Sequence { scroll, .. } = &mut self.level_mut().view {
+            *scroll = (*scroll as isize + delta).max(0) as u16;
+            return;
+        }
+        let count = self.row_count();
+        if count == 0 {
+            return;
+        }
+        let current = self.selected() as isize;
+        let next = (current + delta).clamp(0, count as isize - 1) as usize;
+        self.level_mut().state.select(Some(next));
+    }
+
+    fn back(&mut self) -> bool {
+        if self.levels.len() == 1 {
+            return false;
+        }
+        let level = self.levels.pop().expect("checked above");
+        if matches!(level.view, View::Taxonomy { .. }) {
+            self.version = None;
+        }
+        self.status.clear();
+        true
+    }
+
+    fn database_parts(&self, index: usize) -> Option<(String, String)> {
+        self.databases.get(index).and_then(|db| {
+            db.name
+                .split_once('/')
+                .map(|(source, dataset)| (source.to_string(), dataset.to_string()))
+        })
+    }
+
+    /// Drill into the selected row
+    fn enter(&mut self) -> anyhow::Result<()> {
+        let selected = self.selected();
+        self.status.clear();
+
+        let next = match &self.level().view {
+            View::Databases => {
+                let Some((source, dataset)) = self.database_parts(selected) else {
+                    return Ok(());
+                };
+                let versions = self.manager.list_database_versions(&source, &dataset)?;
+                View::Versions {
+                    source,
+                    dataset,
+                    versions,
+                }
+            }
+            View::Versions {
+                source,
+                dataset,
+                versions,
+            } => {
+                let Some(version) = versions.get(selected) else {
+                    return Ok(());
+                };
+                let (source, dataset, timestamp) =
+                    (source.clone(), dataset.clone(), version.timestamp.clone());
+                return self.open_version(source, dataset, timestamp);
+            }
+            View::Taxonomy { tree } => {
+                let Some((_, taxon)) = tree.visible().get(selected).copied() else {
+                    return Ok(());
+                };
+                let version = self.version.as_ref().expect("taxonomy view has a version");
+                let name = tree
+                    .get(taxon)
+                    .map(|node| node.name.clone())
+                    .unwrap_or_default();
+                View::Chunks {
+                    title: format!("{} (taxid:{})", name, taxon.0),
+                    chunks: tree.chunks_within(&version.manifest.chunk_index, taxon),
+                }
+            }
+            View::Chunks { chunks, .. } => {
+                let Some(chunk) = chunks.get(selected).cloned() else {
+                    return Ok(());
+                };
+                let (rows, total) = self.load_sequences(&chunk)?;
+                View::Sequences { chunk, rows, total }
+            }
+            View::Sequences { rows, .. } => {
+                let Some(row) = rows.get(selected) else {
+                    return Ok(());
+                };
+                let storage = &self.manager.get_repository().storage.sequence_storage;
+                View::Sequence {
+                    id: row.id.clone(),
+                    canonical: storage.load_canonical(&row.hash).ok(),
+                    representations: storage
+                        .load_representations(&row.hash)
+                        .map(|r| r.representations)
+                        .unwrap_or_default(),
+                    scroll: 0,
+                }
+            }
+            View::Sequence { .. } => return Ok(()),
+        };
+        self.push(next);
+        Ok(())
+    }
+
+    /// Load a version's manifest and show its taxonomy tree
+    fn open_version(
+        &mut self,
+        source: String,
+        dataset: String,
+        timestamp: String,
+    ) -> anyhow::Result<()> {
+        let manifest = self
+            .manager
+            .get_version_manifest(&source, &dataset, &timestamp)?;
+        // The NCBI tree is large, so it is only loaded the first time it is needed
+        let taxonomy = self.taxonomy.get_or_insert_with(|| load_taxonomy_db().ok());
+        let tree = TaxonTree::build(&manifest.chunk_index, |taxon| {
+            lineage(taxonomy.as_ref(), taxon)
+        });
+        if taxonomy.is_none() {
+            self.status = "No taxonomy downloaded; taxa are shown without lineages".into();
+        }
+        self.version = Some(OpenVersion {
+            source,
+            dataset,
+            timestamp,
+            manifest,
+        });
+        self.push(View::Taxonomy { tree });
+        Ok(())
+    }
+
+    /// Expand, collapse or toggle the selected taxon; `None` toggles
+    fn fold(&mut self, expand: Option<bool>) {
+        let selected = self.selected();
+        let level = self.level_mut();
+        let View::Taxonomy { tree } = &mut level.view else {
+            return;
+        };
+        let rows = tree.visible();
+        let Some((_, taxon)) = rows.get(selected).copied() else {
+            return;
+        };
+        let has_children = tree.get(taxon).is_some_and(|n| !n.children.is_empty());
+        match expand {
+            Some(true) => tree.expand(taxon),
+            Some(false) if has_children && tree.is_expanded(taxon) => tree.collapse(taxon),
+            Some(false) => {
+                // Already folded: jump to the parent like most tree views
+                let parent = tree.get(taxon).and_then(|n| n.parent);
+                if let Some(index) = rows.iter().position(|(_, t)| Some(*t) == parent) {
+                    level.state.select(Some(index));
+                }
+            }
+            None => tree.toggle(taxon),
+        }
+    }
+
+    fn load_sequences(
+        &self,
+        chunk: &ManifestMetadata,
+    ) -> anyhow::Result<(Vec<SequenceRow>, usize)> {
+        let manifest = self.manager.load_manifest(&chunk.hash)?;
+        let storage = &self.manager.get_repository().storage.sequence_storage;
+        let mut rows = Vec::new();
+        for hash in manifest.sequence_refs.iter().take(SEQUENCE_LIMIT) {
+            let header = storage
+                .load_representations(hash)
+                .ok()
+                .and_then(|r| r.representations.first().map(|r| r.header.clone()))
+                .unwrap_or_default();
+            let header = header.trim_start_matches('>').to_string();
+            let id = header
+                .split_whitespace()
+                .next()
+                .unwrap_or("unknown")
+                .to_string();
+            let length = storage.load_canonical(hash).map(|c| c.length).unwrap_or(0);
+            rows.push(SequenceRow {
+                hash: *hash,
+                id,
+                length,
+                header,
+            });
+        }
+        Ok((rows, manifest.sequence_refs.len()))
+    }
+
+    /// Diff of the selected version against the one before it
+    fn refresh_diff(&mut self) {
+        let selected = self.selected();
+        let View::Versions {
+            source,
+            dataset,
+            versions,
+        } = &self.level().view
+        else {
+            return;
+        };
+        let (Some(newer), older) = (versions.get(selected), versions.get(selected + 1)) else {
+            return;
+        };
+        let key = (source.clone(), dataset.clone(), newer.timestamp.clone());
+        if self.diffs.contains_key(&key) {
+            return;
+        }
+        let diff = older.and_then(|older| {
+            let new = self
+                .manager
+                .get_version_manifest(source, dataset, &newer.timestamp)
+                .ok()?;
+            let old = self
+                .manager
+                .get_version_manifest(source, dataset, &older.timestamp)
+                .ok()?;
+            Some(VersionDiff::between(&old.chunk_index, &new.chunk_index))
+        });
+        self.diffs.insert(key, diff);
+    }
+
+    /// What `e` exports from the current view
+    fn export_request(&self) -> Option<ExportRequest> {
+        let selected = self.selected();
+        match &self.level().view {
+            View::Databases => {
+                let (source, dataset) = self.database_parts(selected)?;
+                let db = self.databases.get(selected)?;
+                Some(ExportRequest::Version {
+                    reference: format!("{}/{}@{}", source, dataset, db.version),
+                    taxon: None,
+                    output: None,
+                })
+            }
+            View::Versions {
+                source,
+                dataset,
+                versions,
+            } => versions.get(selected).map(|v| ExportRequest::Version {
+                reference: format!("{}/{}@{}", source, dataset, v.timestamp),
+                taxon: None,
+                output: None,
+            }),
+            View::Taxonomy { tree } => {
+                let (_, taxon) = tree.visible().get(selected).copied()?;
+                Some(taxon_export(self.version.as_ref()?, taxon))
+            }
+            View::Chunks { chunks, .. } => chunks.get(selected).cloned().map(ExportRequest::Chunk),
+            View::Sequences { rows, .. } => rows
+                .get(selected)
+                .map(|row| ExportRequest::Sequence(row.hash, row.id.clone())),
+            View::Sequence { .. } => {
+                let parent = &self.levels[self.levels.len() - 2];
+                let View::Sequences { rows, .. } = &parent.view else {
+                    return None;
+                };
+                rows.get(parent.state.selected().unwrap_or(0))
+                    .map(|row| ExportRequest::Sequence(row.hash, row.id.clone()))
+            }
+        }
+    }
+
+    fn write_chunk(&self, chunk: &ManifestMetadata) -> anyhow::Result<PathBuf> {
+        let manifest = self.manager.load_manifest(&chunk.hash)?;
+        let sequences = self
+            .manager
+            .load_sequences_from_manifest(&manifest, None, usize::MAX)?;
+        let path = PathBuf::from(format!("chunk-{}.fasta", chunk.hash.truncated(12)));
+        let mut content = String::new();
+        for (_, fasta) in sequences {
+            content.push_str(&fasta);
+            content.push('\n');
+        }
+        std::fs::write(&path, content)?;
+        Ok(path)
+    }
+
+    fn write_sequence(&self, hash: SHA256Hash, id: &str) -> anyhow::Result<PathBuf> {
+        let storage = &self.manager.get_repository().storage.sequence_storage;
+        let fasta = storage.get_sequence_as_fasta(&hash, None)?;
+        let name: String = id
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() || c == '.' || c == '_' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        let path = PathBuf::from(format!("{}.fasta", name));
+        std::fs::write(&path, format!("{}\n", fasta))?;
+        Ok(path)
+    }
+}
+
+fn taxon_export(version: &OpenVersion, taxon: TaxonId) -> ExportRequest {
+    ExportRequest::Version {
+        reference: format!(
+            "{}/{}@{}",
+            version.source, version.dataset, version.timestamp
+        ),
+        taxon: Some(taxon),
+        output: Some(PathBuf::from(format!(
+            "{}-{}-{}-taxid{}.fasta",
+            version.source, version.dataset, version.timestamp, taxon.0
+        ))),
+    }
+}
+
+fn lineage(taxonomy: Option<&TaxonomyDB>, taxon: TaxonId) -> Vec<(TaxonId, String, String)> {
+    match taxonomy {
+        Some(db) if db.get_taxon(taxon.0).is_some() => db
+            .get_lineage(taxon.0)
+            .into_iter()
+            .filter_map(|id| db.get_taxon(id))
+            .map(|info| {
+                (
+                    TaxonId(info.taxon_id),
+                    info.scientific_name.clone(),
+                    info.rank.clone(),
+                )
+            })
+            .collect(),
+        _ if taxon.0 == 0 => vec![(taxon, "unclassified".into(), "no rank".into())],
+        _ => vec![(
+            taxon,
+            get_taxonomy_name(taxon, &None),
+            "no rank".to_string(),
+        )],
+    }
+}
+
+pub fn run_repository_browser<B: Backend>(terminal: &mut Terminal<B>) -> anyhow::Result<()> {
+    let mut browser = RepositoryBrowser::new()?;
+
+    loop {
+        browser.refresh_diff();
+        terminal.draw(|f| draw_browser(f, &mut browser))?;
+
+        if let Event::Key(key) = event::read()? {
+            let result = match key.code {
+                KeyCode::Char('q') => break,
+                KeyCode::Esc | KeyCode::Backspace => {
+                    if !browser.back() {
+                        break;
+                    }
+                    Ok(())
+                }
+                KeyCode::Down | KeyCode::Char('j') => {
+                    browser.move_by(1);
+                    Ok(())
+                }
+                KeyCode::Up | KeyCode::Char('k') => {
+                    browser.move_by(-1);
+                    Ok(())
+                }
+                KeyCode::PageDown => {
+                    browser.move_by(PAGE as isize);
+                    Ok(())
+                }
+                KeyCode::PageUp => {
+                    browser.move_by(-(PAGE as isize));
+                    Ok(())
+                }
+                KeyCode::Home => {
+                    browser.move_by(isize::MIN / 2);
+                    Ok(())
+                }
+                KeyCode::End => {
+                    browser.move_by(isize::MAX / 2);
+                    Ok(())
+                }
+                KeyCode::Right | KeyCode::Char('l') => {
+                    browser.fold(Some(true));
+                    Ok(())
+                }
+                KeyCode::Left | KeyCode::Char('h') => {
+                    browser.fold(Some(false));
+                    Ok(())
+                }
+                KeyCode::Char(' ') => {
+                    browser.fold(None);
+                    Ok(())
+                }
+                KeyCode::Enter => browser.enter(),
+                KeyCode::Char('e') => match browser.export_request() {
+                    Some(ExportRequest::Chunk(chunk)) => browser.write_chunk(&chunk).map(|path| {
+                        browser.status =
+                            format!("Wrote chunk {} to {}", chunk.hash, path.display());
+                    }),
+                    Some(ExportRequest::Sequence(hash, id)) => {
+                        browser.write_sequence(hash, &id).map(|path| {
+                            browser.status = format!("Wrote {} to {}", id, path.display());
+                        })
+                    }
+                    Some(request) => run_export(terminal, request).map(|message| {
+                        browser.status = message;
+                    }),
+                    None => Ok(()),
+                },
+                _ => Ok(()),
+            };
+            if let Err(e) = result {
+                browser.status = format!("Error: {}", e);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Run `database export` on the normal screen, then return to the browser
+fn run_export<B: Backend>(
+    terminal: &mut Terminal<B>,
+    request: ExportRequest,
+) -> anyhow::Result<String> {
+    use crate::cli::commands::database::export::{self, ExportArgs, ExportFormat};
+
+    let ExportRequest::Version {
+        reference,
+        taxon,
+        output,
+    } = request
+    else {
+        return Ok(String::new());
+    };
+    let args = ExportArgs {
+        database: reference.clone(),
+        output,
+        force: taxon.is_some(),
+        format: ExportFormat::Fasta,
+        compress: false,
+        no_cache: false,
+        cached_only: false,
+        with_taxonomy: false,
+        quiet: false,
+        stream: false,
+        sequence_date: None,
+        taxonomy_date: None,
+        taxonomy_filter: taxon.map(|taxon| format!("descendants_of({})", taxon.0)),
+        redundancy: None,
+        max_sequences: None,
+        sample: None,
+    };
+
+    disable_raw_mode()?;
+    execute!(io::stdout(), LeaveAlternateScreen, cursor::Show)?;
+
+    let result = export::run(args);
+    if let Err(e) = &result {
+        eprintln!("Error: {:#}", e);
+    }
+    println!("\nPress Enter to return to the browser");
+    let mut line = String::new();
+    let _ = io::stdin().read_line(&mut line);
+
+    execute!(io::stdout(), EnterAlternateScreen, cursor::Hide)?;
+    enable_raw_mode()?;
+    terminal.clear()?;
+
+    result.map(|_| format!("Exported {}", reference))
+}
+
+fn draw_browser(f: &mut Frame, browser: &mut RepositoryBrowser) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(10),
+            Constraint::Length(3),
+        ])
+        .split(f.size());
+
+    let breadcrumb = Paragraph::new(Line::from(breadcrumb(browser))).block(
+        Block::default()
+            .title(" Repository Browser ")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Cyan)),
+    );
+    f.render_widget(breadcrumb, chunks[0]);
+
+    let body = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(55), Constraint::Percentage(45)])
+        .split(chunks[1]);
+
+    if matches!(browser.level().view, View::Sequence { .. }) {
+        draw_sequence(f, chunks[1], browser);
+    } else {
+        draw_list(f, body[0], browser);
+        draw_details(f, body[1], browser);
+    }
+
+    let keys = match browser.level().view {
+        View::Taxonomy { .. } => "↑↓ move  →/← expand/collapse  Space toggle  Enter chunks  e export taxon  Esc back  q quit",
+        View::Sequence { .. } => "↑↓ scroll  e export sequence  Esc back  q quit",
+        View::Sequences { .. } => "↑↓ move  Enter details  e export sequence  Esc back  q quit",
+        View::Chunks { .. } => "↑↓ move  Enter sequences  e export chunk  Esc back  q quit",
+        _ => "↑↓ move  Enter open  e export version  Esc back  q quit",
+    };
+    let footer = if browser.status.is_empty() {
+        Line::from(Span::styled(keys, Style::default().fg(Color::DarkGray)))
+    } else {
+        Line::from(vec![
+            Span::styled(browser.status.clone(), Style::default().fg(Color::Yellow)),
+            Span::styled(
+                format!("  │  {}", keys),
+                Style::default().fg(Color::DarkGray),
+            ),
+        ])
+    };
+    let footer = Paragraph::new(footer).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::DarkGray)),
+    );
+    f.render_widget(footer, chunks[2]);
+}
+
+fn breadcrumb(browser: &RepositoryBrowser) -> Vec<Span<'static>> {
+    let mut parts = vec!["Databases".to_string()];
+    for level in &browser.levels[1..] {
+        match &level.view {
+            View::Versions {
+                source, dataset, ..
+            } => parts.push(format!("{}/{}", source, dataset)),
+            View::Taxonomy { .. } => {
+                if let Some(version) = &browser.version {
+                    parts.push(version.timestamp.clone());
+                }
+            }
+            View::Chunks { title, .. } => parts.push(title.clone()),
+            View::Sequences { chunk, .. } => parts.push(format!("chunk {}", chunk.hash)),
+            View::Sequence { id, .. } => parts.push(id.clone()),
+            View::Databases => {}
+        }
+    }
+
+    let last = parts.len() - 1;
+    let mut spans = Vec::new();
+    for (index, part) in parts.into_iter().enumerate() {
+        if index > 0 {
+            spans.push(Span::styled(" › ", Style::default().fg(Color::DarkGray)));
+        }
+        let style = if index == last {
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Color::White)
+        };
+        spans.push(Span::styled(part, style));
+    }
+    spans
+}
+
+fn draw_list(f: &mut Frame, area: Rect, browser: &mut RepositoryBrowser) {
+    let (title, items): (String, Vec<ListItem>) = match &browser.level().view {
+        View::Databases => (
+            " Databases ".into(),
+            browser
+                .databases
+                .iter()
+                .map(|db| {
+                    ListItem::new(Line::from(vec![
+                        Span::styled(
+                            format!("{:<32}", db.name),
+                            Style::default().add_modifier(Modifier::BOLD),
+                        ),
+                        Span::styled(
+                            format!("{} sequences", format_number(db.sequence_count)),
+                            Style::default().fg(Color::Gray),
+                        ),
+                    ]))
+                })
+                .collect(),
+        ),
+        View::Versions { versions, .. } => (
+            " Versions ".into(),
+            versions
+                .iter()
+                .map(|v| {
+                    let mut spans = vec![Span::raw(format!(
+                        "{}  {}",
+                        v.created_at.format("%Y-%m-%d"),
+                        v.timestamp
+                    ))];
+                    if !v.aliases.is_empty() {
+                        spans.push(Span::styled(
+                            format!("  [{}]", v.aliases.join(", ")),
+                            Style::default().fg(Color::Green),
+                        ));
+                    }
+                    ListItem::new(Line::from(spans))
+                })
+                .collect(),
+        ),
+        View::Taxonomy { tree } => (
+            " Taxonomy ".into(),
+            tree.visible()
+                .into_iter()
+                .filter_map(|(depth, taxon)| {
+                    let node = tree.get(taxon)?;
+                    let marker = if node.children.is_empty() {
+                        "  "
+                    } else if tree.is_expanded(taxon) {
+                        "▼ "
+                    } else {
+                        "▶ "
+                    };
+                    Some(ListItem::new(Line::from(vec![
+                        Span::raw("  ".repeat(depth)),
+                        Span::styled(marker, Style::default().fg(Color::Cyan)),
+                        Span::raw(node.name.clone()),
+                        Span::styled(
+                            format!(
+                                "  {} seqs, {} chunks",
+                                format_number(node.sequences),
+                                format_number(node.chunks)
+                            ),
+                            Style::default().fg(Color::DarkGray),
+                        ),
+                    ])))
+                })
+                .collect(),
+        ),
+        View::Chunks { chunks, .. } => (
+            format!(" Chunks ({}) ", format_number(chunks.len())),
+            chunks
+                .iter()
+                .map(|chunk| {
+                    ListItem::new(Line::from(vec![
+                        Span::styled(chunk.hash.truncated(16), Style::default().fg(Color::Cyan)),
+                        Span::raw(format!(
+                            "  {:>8} seqs  {:>10}",
+                            format_number(chunk.sequence_count),
+                            format_bytes(chunk.size as u64)
+                        )),
+                    ]))
+                })
+                .collect(),
+        ),
+        View::Sequences { rows, total, .. } => (
+            if *total > rows.len() {
+                format!(
+                    " Sequences (first {} of {}) ",
+                    format_number(rows.len()),
+                    format_number(*total)
+                )
+            } else {
+                format!(" Sequences ({}) ", format_number(*total))
+            },
+            rows.iter()
+                .map(|row| {
+                    ListItem::new(Line::from(vec![
+                        Span::styled(
+                            format!("{:<20}", row.id),
+                            Style::default().add_modifier(Modifier::BOLD),
+                        ),
+                        Span::styled(
+                            format!("{:>7}  ", row.length),
+                            Style::default().fg(Color::DarkGray),
+                        ),
+                        Span::raw(description(&row.header).to_string()),
+                    ]))
+                })
+                .collect(),
+        ),
+        View::Sequence { .. } => return,
+    };
+
+    let list = List::new(items)
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .highlight_style(
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("▶ ");
+    f.render_stateful_widget(list, area, &mut browser.level_mut().state);
+}
+
+fn draw_details(f: &mut Frame, area: Rect, browser: &RepositoryBrowser) {
+    let selected = browser.selected();
+    let label =
+        |name: &str| Span::styled(format!("{:<14}", name), Style::default().fg(Color::Gray));
+    let mut lines: Vec<Line> = Vec::new();
+
+    match &browser.level().view {
+        View::Databases => {
+            if let Some(db) = browser.databases.get(selected) {
+                lines.push(Line::from(vec![
+                    label("Current"),
+                    Span::raw(db.version.clone()),
+                ]));
+                lines.push(Line::from(vec![
+                    label("Created"),
+                    Span::raw(db.created_at.format("%Y-%m-%d %H:%M UTC").to_string()),
+                ]));
+                lines.push(Line::from(vec![
+                    label("Sequences"),
+                    Span::raw(format_number(db.sequence_count)),
+                ]));
+                lines.push(Line::from(vec![
+                    label("Chunks"),
+                    Span::raw(format_number(db.chunk_count)),
+                ]));
+                lines.push(Line::from(vec![
+                    label("Size"),
+                    Span::raw(format_bytes(db.total_size as u64)),
+                ]));
+                if !db.reduction_profiles.is_empty() {
+                    lines.push(Line::from(vec![
+                        label("Profiles"),
+                        Span::raw(db.reduction_profiles.join(", ")),
+                    ]));
+                }
+            }
+        }
+        View::Versions {
+            source,
+            dataset,
+            versions,
+        } => {
+            if let Some(v) = versions.get(selected) {
+                if let Some(upstream) = &v.upstream_version {
+                    lines.push(Line::from(vec![
+                        label("Upstream"),
+                        Span::raw(upstream.clone()),
+                    ]));
+                }
+                lines.push(Line::from(vec![
+                    label("Created"),
+                    Span::raw(v.created_at.format("%Y-%m-%d %H:%M UTC").to_string()),
+                ]));
+                lines.push(Line::from(vec![
+                    label("Aliases"),
+                    Span::raw(if v.aliases.is_empty() {
+                        "-".to_string()
+                    } else {
+                        v.aliases.join(", ")
+                    }),
+                ]));
+                lines.push(Line::from(vec![
+                    label("Sequences"),
+                    Span::raw(format_number(v.sequence_count)),
+                ]));
+                lines.push(Line::from(vec![
+                    label("Chunks"),
+                    Span::raw(format_number(v.chunk_count)),
+                ]));
+                lines.push(Line::from(vec![
+                    label("Size"),
+                    Span::raw(format_bytes(v.total_size)),
+                ]));
+                lines.push(Line::from(""));
+
+                let key = (source.clone(), dataset.clone(), v.timestamp.clone());
+                match (browser.diffs.get(&key), versions.get(selected + 1)) {
+                    (Some(Some(diff)), Some(older)) => {
+                        lines.extend(diff_lines(diff, &older.timestamp));
+                    }
+                    (_, None) => lines.push(Line::from(Span::styled(
+                        "First version",
+                        Style::default().fg(Color::DarkGray),
+                    ))),
+                    _ => lines.push(Line::from(Span::styled(
+                        "Could not compare with the previous version",
+                        Style::default().fg(Color::DarkGray),
+                    ))),
+                }
+                lines.push(Line::from(""));
+                lines.push(Line::from(Span::styled(
+                    "Timeline",
+                    Style::default().add_modifier(Modifier::BOLD),
+                )));
+                for (index, version) in versions.iter().enumerate() {
+                    let marker = if index == selected { "●" } else { "○" };
+                    lines.push(Line::from(vec![
+                        Span::styled(format!(" {} ", marker), Style::default().fg(Color::Cyan)),
+                        Span::raw(version.created_at.format("%Y-%m-%d").to_string()),
+                        Span::styled(
+                            format!("  {} seqs", format_number(version.sequence_count)),
+                            Style::default().fg(Color::DarkGray),
+                        ),
+                    ]));
+                }
+            }
+        }
+        View::Taxonomy { tree } => {
+            if let Some((_, taxon)) = tree.visible().get(selected) {
+                if let Some(node) = tree.get(*taxon) {
+                    lines.push(Line::from(Span::styled(
+                        node.name.clone(),
+                        Style::default().add_modifier(Modifier::BOLD),
+                    )));
+                    lines.push(Line::from(vec![
+                        label("Taxon ID"),
+                        Span::raw(taxon.0.to_string()),
+                    ]));
+                    lines.push(Line::from(vec![
+                        label("Rank"),
+                        Span::raw(node.rank.clone()),
+                    ]));
+                    lines.push(Line::from(vec![
+                        label("Sequences"),
+                        Span::raw(format_number(node.sequences)),
+                    ]));
+                    lines.push(Line::from(vec![
+                        label("Chunks"),
+                        Span::raw(format_number(node.chunks)),
+                    ]));
+                    lines.push(Line::from(vec![
+                        label("Children"),
+                        Span::raw(format_number(node.children.len())),
+                    ]));
+                }
+            }
+        }
+        View::Chunks { chunks, .. } => {
+            if let Some(chunk) = chunks.get(selected) {
+                lines.push(Line::from(vec![
+                    label("Hash"),
+                    Span::raw(chunk.hash.to_hex()),
+                ]));
+                lines.push(Line::from(vec![
+                    label("Sequences"),
+                    Span::raw(format_number(chunk.sequence_count)),
+                ]));
+                lines.push(Line::from(vec![
+                    label("Size"),
+                    Span::raw(format_bytes(chunk.size as u64)),
+                ]));
+                if let Some(compressed) = chunk.compressed_size {
+                    lines.push(Line::from(vec![
+                        label("Compressed"),
+                        Span::raw(format_bytes(compressed as u64)),
+                    ]));
+                }
+                let taxa: Vec<String> = chunk
+                    .taxon_ids
+                    .iter()
+                    .take(10)
+                    .map(|taxon| taxon.0.to_string())
+                    .collect();
+                let more = chunk.taxon_ids.len().saturating_sub(taxa.len());
+                lines.push(Line::from(vec![
+                    label("Taxa"),
+                    Span::raw(if more > 0 {
+                        format!("{} (+{} more)", taxa.join(", "), more)
+                    } else {
+                        taxa.join(", ")
+                    }),
+                ]));
+            }
+        }
+        View::Sequences { rows, chunk, .. } => {
+            lines.push(Line::from(vec![
+                label("Chunk"),
+                Span::raw(chunk.hash.to_hex()),
+            ]));
+            if let Some(row) = rows.get(selected) {
+                lines.push(Line::from(""));
+                lines.push(Line::from(vec![
+                    label("Hash"),
+                    Span::raw(row.hash.to_hex()),
+                ]));
+                lines.push(Line::from(vec![
+                    label("Length"),
+                    Span::raw(format_number(row.length)),
+                ]));
+                lines.push(Line::from(""));
+                lines.push(Line::from(Span::raw(row.header.clone())));
+            }
+        }
+        View::Sequence { .. } => {}
+    }
+
+    let details = Paragraph::new(lines)
+        .block(Block::default().title(" Details ").borders(Borders::ALL))
+        .wrap(Wrap { trim: false });
+    f.render_widget(details, area);
+}
+
+fn diff_lines(diff: &VersionDiff, older: &str) -> Vec<Line<'static>> {
+    let change = diff.sequences_after as i64 - diff.sequences_before as i64;
+    let taxa = |taxa: &[TaxonId]| {
+        let shown: Vec<String> = taxa.iter().take(5).map(|t| t.0.to_string()).collect();
+        if taxa.len() > shown.len() {
+            format!("{} (+{} more)", shown.join(", "), taxa.len() - shown.len())
+        } else {
+            shown.join(", ")
+        }
+    };
+
+    let mut lines = vec![
+        Line::from(Span::styled(
+            format!("Changes since {}", older),
+            Style::default().add_modifier(Modifier::BOLD),
+        )),
+        Line::from(vec![
+            Span::styled(
+                format!(" +{} chunks", format_number(diff.chunks_added)),
+                Style::default().fg(Color::Green),
+            ),
+            Span::styled(
+                format!("  -{} chunks", format_number(diff.chunks_removed)),
+                Style::default().fg(Color::Red),
+            ),
+            Span::raw(format!("  {} shared", format_number(diff.chunks_shared))),
+        ]),
+        Line::from(Span::styled(
+            format!(
+                " {}{} sequences",
+                if change >= 0 { "+" } else { "-" },
+                format_number(change.unsigned_abs() as usize)
+            ),
+            Style::default().fg(if change >= 0 {
+                Color::Green
+            } else {
+                Color::Red
+            }),
+        )),
+    ];
+    if !diff.taxa_added.is_empty() {
+        lines.push(Line::from(Span::styled(
+            format!(" + taxa {}", taxa(&diff.taxa_added)),
+            Style::default().fg(Color::Green),
+        )));
+    }
+    if !diff.taxa_removed.is_empty() {
+        lines.push(Line::from(Span::styled(
+            format!(" - taxa {}", taxa(&diff.taxa_removed)),
+            Style::default().fg(Color::Red),
+        )));
+    }
+    lines
+}
+
+fn draw_sequence(f: &mut Frame, area: Rect, browser: &RepositoryBrowser) {
+    let View::Sequence {
+        id,
+        canonical,
+        representations,
+        scroll,
+    } = &browser.level().view
+    else {
+        return;
+    };
+
+    let mut lines = vec![Line::from(Span::styled(
+        id.clone(),
+        Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::BOLD),
+    ))];
+    if let Some(canonical) = canonical {
+        lines.push(Line::from(format!(
+            "{} {} · {} residues · first seen {} · last seen {}",
+            canonical.sequence_type,
+            canonical.sequence_hash.to_hex(),
+            format_number(canonical.length),
+            canonical.first_seen.format("%Y-%m-%d"),
+            canonical.last_seen.format("%Y-%m-%d"),
+        )));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        format!("Representations ({})", representations.len()),
+        Style::default().add_modifier(Modifier::BOLD),
+    )));
+    for repr in representations {
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!(" {} ", repr.source),
+                Style::default().fg(Color::Cyan),
+            ),
+            Span::raw(repr.header.trim_start_matches('>').to_string()),
+        ]));
+        let mut facts = Vec::new();
+        if !repr.accessions.is_empty() {
+            facts.push(format!("accessions {}", repr.accessions.join(", ")));
+        }
+        if let Some(taxon) = repr.taxon_id {
+            facts.push(format!("taxid {}", taxon.0));
+        }
+        facts.push(format!("seen {}", repr.last_seen.format("%Y-%m-%d")));
+        lines.push(Line::from(Span::styled(
+            format!("   {}", facts.join(" · ")),
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+    if let Some(canonical) = canonical {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            "Sequence",
+            Style::default().add_modifier(Modifier::BOLD),
+        )));
+        let shown = &canonical.sequence[..canonical.sequence.len().min(PREVIEW_LENGTH)];
+        for line in shown.chunks(60) {
+            lines.push(Line::from(String::from_utf8_lossy(line).into_owned()));
+        }
+        if canonical.sequence.len() > PREVIEW_LENGTH {
+            lines.push(Line::from(Span::styled(
+                format!(
+                    "… {} more residues",
+                    format_number(canonical.sequence.len() - PREVIEW_LENGTH)
+                ),
+                Style::default().fg(Color::DarkGray),
+            )));
+        }
+    }
+
+    let paragraph = Paragraph::new(lines)
+        .block(Block::default().title(" Sequence ").borders(Borders::ALL))
+        .wrap(Wrap { trim: false })
+        .scroll((*scroll, 0));
+    f.render_widget(paragraph, area);
+}
+
+/// Header text after the ID
+fn description(header: &str) -> &str {
+    header
+        .split_once(char::is_whitespace)
+        .map(|(_, rest)| rest)
+        .unwrap_or("")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(byte: u8, taxa: &[u32], sequences: usize) -> ManifestMetadata {
+        ManifestMetadata {
+            hash: SHA256Hash([byte; 32]),
+            taxon_ids: taxa.iter().map(|t| TaxonId(*t)).collect(),
+            sequence_count: sequences,
+            size: sequences * 100,
+            compressed_size: None,
+        }
+    }
+
+    // root(1) > Bacteria(2) > E. coli(562), root > Eukaryota(2759) > human(9606)
+    fn lineage(taxon: TaxonId) -> Vec<(TaxonId, String, String)> {
+        let path: &[(u32, &str)] = match taxon.0 {
+            562 => &[(1, "root"), (2, "Bacteria"), (562, "Escherichia coli")],
+            9606 => &[(1, "root"), (2759, "Eukaryota"), (9606, "Homo sapiens")],
+            2 => &[(1, "root"), (2, "Bacteria")],
+            _ => &[],
+        };
+        path.iter()
+            .map(|(id, name)| (TaxonId(*id), name.to_string(), "no rank".to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_tree_counts_roll_up_lineages() {
+        let chunks = vec![
+            chunk(1, &[562], 10),
+            chunk(2, &[562, 2], 5),
+            chunk(3, &[9606], 3),
+            chunk(4, &[2], 1),
+        ];
+        let tree = TaxonTree::build(&chunks, lineage);
+
+        let root = tree.get(TaxonId(1)).unwrap();
+        assert_eq!(root.sequences, 19);
+        assert_eq!(root.chunks, 4);
+        assert_eq!(root.children, vec![TaxonId(2), TaxonId(2759)]);
+
+        let bacteria = tree.get(TaxonId(2)).unwrap();
+        assert_eq!(bacteria.sequences, 16);
+        assert_eq!(bacteria.chunks, 3);
+        assert_eq!(tree.get(TaxonId(562)).unwrap().sequences, 15);
+
+        assert_eq!(tree.chunks_within(&chunks, TaxonId(2)).len(), 3);
+        assert_eq!(tree.chunks_within(&chunks, TaxonId(9606)).len(), 1);
+    }
+
+    #[test]
+    fn test_tree_folding() {
+        let chunks = vec![chunk(1, &[562], 10), chunk(2, &[9606], 3)];
+        let mut tree = TaxonTree::build(&chunks, lineage);
+
+        // Only the roots start expanded
+        let rows: Vec<TaxonId> = tree.visible().into_iter().map(|(_, t)| t).collect();
+        assert_eq!(rows, vec![TaxonId(1), TaxonId(2), TaxonId(2759)]);
+
+        tree.expand(TaxonId(2));
+        let rows = tree.visible();
+        assert_eq!(rows[2], (2, TaxonId(562)));
+        assert_eq!(rows.len(), 4);
+
+        tree.toggle(TaxonId(1));
+        assert_eq!(tree.visible(), vec![(0, TaxonId(1))]);
+    }
+
+    #[test]
+    fn test_tree_without_lineages_is_flat() {
+        let chunks = vec![chunk(1, &[42], 2), chunk(2, &[], 1)];
+        let tree = TaxonTree::build(&chunks, |_| Vec::new());
+        let rows = tree.visible();
+        assert_eq!(rows.len(), 2);
+        assert!(rows.iter().all(|(depth, _)| *depth == 0));
+        assert!(tree.get(TaxonId(0)).is_some());
+    }
+
+    #[test]
+    fn test_version_diff() {
+        let old = vec![chunk(1, &[562], 10), chunk(2, &[9606], 3)];
+        let new = vec![chunk(1, &[562], 10), chunk(3, &[7227], 4)];
+        let diff = VersionDiff::between(&old, &new);
+
+        assert_eq!(diff.chunks_added, 1);
+        assert_eq!(diff.chunks_removed, 1);
+        assert_eq!(diff.chunks_shared, 1);
+        assert_eq!(diff.sequences_before, 13);
+        assert_eq!(diff.sequences_after, 14);
+        assert_eq!(diff.taxa_added, vec![TaxonId(7227)]);
+        assert_eq!(diff.taxa_removed, vec![TaxonId(9606)]);
+    }
+}
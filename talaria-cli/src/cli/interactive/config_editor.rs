@@ -1,5 +1,4 @@
 use crossterm::event::{self, Event, KeyCode};
-use dirs;
 use ratatui::{
     backend::Backend,
     layout::{Alignment, Constraint, Direction, Layout, Rect},
@@ -9,7 +8,7 @@ use ratatui::{
     Frame, Terminal,
 };
 use std::{io, path::PathBuf};
-use talaria_core::config::{default_config, load_config, save_config, Config};
+use talaria_core::config::{default_config, load_config, save_config, user_config_path, Config};
 
 pub struct ConfigEditor {
     config: Config,
@@ -27,7 +26,7 @@ impl ConfigEditor {
         list_state.select(Some(0));
 
         // Try to load existing config or use default
-        let default_path = dirs::config_dir().map(|p| p.join("talaria").join("config.toml"));
+        let default_path = user_config_path();
 
         let (config, path) = if let Some(ref p) = default_path {
             if p.exists() {
@@ -290,11 +289,9 @@ pub fn run_config_editor<B: Backend>(terminal: &mut Terminal<B>) -> io::Result<(
                             editor.message = "Enter new value (Esc to cancel)".to_string();
                         }
                     }
-                    KeyCode::Char(' ') => {
-                        if editor.is_boolean_field(editor.selected_field) {
-                            editor.toggle_boolean();
-                            editor.message = "Value toggled".to_string();
-                        }
+                    KeyCode::Char(' ') if editor.is_boolean_field(editor.selected_field) => {
+                        editor.toggle_boolean();
+                        editor.message = "Value toggled".to_string();
                     }
                     KeyCode::Char('s') => {
                         editor.save_config();
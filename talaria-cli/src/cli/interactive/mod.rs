@@ -1,3 +1,4 @@
+pub mod browser;
 pub mod config_editor;
 pub mod docs_viewer;
 pub mod download;
@@ -124,11 +124,7 @@ pub fn format_info(text: &str) -> String {
 }
 
 pub fn format_progress(current: usize, total: usize) -> String {
-    let percentage = if total > 0 {
-        (current * 100) / total
-    } else {
-        0
-    };
+    let percentage = (current * 100).checked_div(total).unwrap_or(0);
 
     let filled = percentage / 5;
     let empty = 20 - filled;
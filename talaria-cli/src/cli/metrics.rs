@@ -0,0 +1,50 @@
+/// Opt-in Prometheus metrics for long-running commands
+///
+/// `--metrics-listen` and `--metrics-textfile` are global flags, but only
+/// commands that call `start` (download, add, reduce, mirror sync and serve)
+/// publish anything. Other commands ignore them.
+use anyhow::Result;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::time::Duration;
+use talaria_herald::performance::{metrics, ExporterConfig, MetricsExporter};
+
+static CONFIG: OnceLock<ExporterConfig> = OnceLock::new();
+
+/// Remember where to publish metrics; a no-op when neither target is given
+pub fn configure(listen: Option<SocketAddr>, textfile: Option<PathBuf>, interval_secs: u64) {
+    if listen.is_none() && textfile.is_none() {
+        return;
+    }
+
+    let _ = CONFIG.set(ExporterConfig {
+        listen,
+        textfile,
+        interval: Duration::from_secs(interval_secs.max(1)),
+    });
+}
+
+/// Start publishing metrics for `operation` if configured
+///
+/// Call this before opening the repository: storage opened while metrics are
+/// enabled turns on RocksDB statistics for the cache and compaction counters.
+/// Keep the returned exporter alive for the duration of the command; dropping
+/// it writes the textfile one last time.
+pub fn start(operation: &str) -> Result<Option<MetricsExporter>> {
+    let Some(config) = CONFIG.get() else {
+        return Ok(None);
+    };
+
+    let registry = metrics::enable(operation);
+    let exporter = MetricsExporter::start(registry, config.clone())?;
+
+    if let Some(addr) = config.listen {
+        tracing::info!("Serving metrics on http://{}/metrics", addr);
+    }
+    if let Some(path) = &config.textfile {
+        tracing::info!("Writing metrics to {}", path.display());
+    }
+
+    Ok(Some(exporter))
+}
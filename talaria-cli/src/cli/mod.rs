@@ -3,6 +3,7 @@ pub mod commands;
 pub mod formatting;
 pub mod global_config;
 pub mod interactive;
+pub mod metrics;
 pub mod progress;
 pub mod visualize;
 
@@ -53,6 +54,28 @@ pub struct Cli {
         help = "Include trace-level information in audit log"
     )]
     pub audit_trace: bool,
+
+    /// Serve Prometheus metrics at http://ADDR/metrics during long-running operations
+    #[arg(
+        long,
+        global = true,
+        value_name = "ADDR",
+        env = "TALARIA_METRICS_LISTEN"
+    )]
+    pub metrics_listen: Option<std::net::SocketAddr>,
+
+    /// Periodically write Prometheus metrics to this file (node_exporter textfile collector)
+    #[arg(
+        long,
+        global = true,
+        value_name = "PATH",
+        env = "TALARIA_METRICS_TEXTFILE"
+    )]
+    pub metrics_textfile: Option<std::path::PathBuf>,
+
+    /// Seconds between metrics textfile writes
+    #[arg(long, global = true, value_name = "SECS", default_value = "15")]
+    pub metrics_interval: u64,
 }
 
 #[derive(Subcommand)]
@@ -92,6 +115,12 @@ pub enum Commands {
 
     /// Manage HERALD repository
     Herald(commands::herald::HeraldArgs),
+
+    /// Pin databases, taxonomy and tools for a reproducible analysis
+    Lock(commands::lock::LockArgs),
+
+    /// Serve a read-only HTTP query API over the repository
+    Serve(commands::serve::ServeArgs),
 }
 
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
@@ -239,6 +239,12 @@ fn run(cli: Cli) -> anyhow::Result<()> {
         eprintln!("Using {} threads", num_threads);
     }
 
+    crate::cli::metrics::configure(
+        cli.metrics_listen,
+        cli.metrics_textfile.clone(),
+        cli.metrics_interval,
+    );
+
     match cli.command {
         Commands::Reduce(args) => crate::cli::commands::reduce::run(args),
         Commands::Reconstruct(args) => crate::cli::commands::reconstruct::run(args),
@@ -251,5 +257,7 @@ fn run(cli: Cli) -> anyhow::Result<()> {
         Commands::Temporal(args) => crate::cli::commands::temporal::run(args),
         Commands::Chunk { command } => crate::cli::commands::chunk::run(command),
         Commands::Herald(args) => crate::cli::commands::herald::run(args),
+        Commands::Lock(args) => crate::cli::commands::lock::run(args),
+        Commands::Serve(args) => crate::cli::commands::serve::run(args),
     }
 }
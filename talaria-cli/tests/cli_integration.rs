@@ -182,6 +182,50 @@ fn test_reduce_reconstruct_roundtrip() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_reconstruct_by_accession_from_current_version() -> Result<()> {
+    let env = TestEnvironment::new()?;
+
+    let original = env.create_input_file("original.fasta", &create_diverse_fasta(20))?;
+    let reduced = env.output_path("reduced.fasta");
+    let reconstructed = env.output_path("reconstructed.fasta");
+
+    add_test_database(&original, "test_by_accession", env.temp_dir.path())?;
+
+    let mut cmd = talaria_cmd();
+    cmd.arg("reduce")
+        .arg("local/test_by_accession")
+        .arg("-o")
+        .arg(&reduced)
+        .arg("--target-aligner")
+        .arg("generic")
+        .arg("--reduction-ratio")
+        .arg("0.5")
+        .arg("--profile")
+        .arg("by-accession")
+        .env("TALARIA_HOME", env.temp_dir.path());
+    cmd.assert().success();
+
+    // No version given, so the reference resolves through the "current" alias
+    let mut cmd = talaria_cmd();
+    cmd.arg("reconstruct")
+        .arg("local/test_by_accession:by-accession")
+        .arg("--sequences")
+        .arg("seq_3")
+        .arg("-o")
+        .arg(&reconstructed)
+        .env("TALARIA_HOME", env.temp_dir.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("no accession index").not())
+        .stderr(predicate::str::contains("no accession index").not());
+
+    assert_eq!(count_sequences(&reconstructed)?, 1);
+    assert!(contains_sequence(&reconstructed, "seq_3")?);
+
+    Ok(())
+}
+
 #[test]
 fn test_validate_command() -> Result<()> {
     let env = TestEnvironment::new()?;
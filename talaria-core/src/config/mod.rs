@@ -2,7 +2,8 @@
 
 use crate::TalariaError;
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct Config {
@@ -16,6 +17,10 @@ pub struct Config {
     pub performance: PerformanceConfig,
     #[serde(default)]
     pub database: DatabaseConfig,
+    #[serde(default)]
+    pub chunking: ChunkingConfig,
+    #[serde(default)]
+    pub backup: BackupConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -78,6 +83,64 @@ pub struct DatabaseConfig {
     pub preferred_mirror: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChunkingConfig {
+    /// Preferred chunk size in bytes
+    #[serde(default = "default_target_chunk_size")]
+    pub target_chunk_size: usize,
+    /// Hard cap on chunk size in bytes
+    #[serde(default = "default_max_chunk_size")]
+    pub max_chunk_size: usize,
+    /// Chunks smaller than this are merged with related taxa
+    #[serde(default = "default_min_sequences_per_chunk")]
+    pub min_sequences_per_chunk: usize,
+    /// Fraction of a chunk that must share a taxonomic group
+    #[serde(default = "default_taxonomic_coherence")]
+    pub taxonomic_coherence: f64,
+    /// How chunk boundaries are placed ("packed" or "content-defined");
+    /// unset falls back to `TALARIA_CHUNK_BOUNDARIES`
+    #[serde(default)]
+    pub boundaries: Option<String>,
+    /// Taxa that get their own chunking rule
+    #[serde(default)]
+    pub special_taxa: Vec<SpecialTaxonConfig>,
+    /// Minimum chunk size in bytes per taxonomic rank (e.g. species = 1048576)
+    #[serde(default)]
+    pub rank_min_sizes: BTreeMap<String, usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpecialTaxonConfig {
+    pub taxon_id: u32,
+    #[serde(default)]
+    pub name: String,
+    /// "own-chunks", "group-with-siblings" or "group-at-<rank>"
+    #[serde(default = "default_special_taxon_strategy")]
+    pub strategy: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupConfig {
+    /// Off-site target for backups (e.g. "s3://bucket/talaria-backups")
+    #[serde(default)]
+    pub offsite: Option<String>,
+    /// Region of the off-site bucket; unset falls back to `AWS_DEFAULT_REGION`
+    #[serde(default)]
+    pub region: Option<String>,
+    /// Endpoint of an S3-compatible service
+    #[serde(default)]
+    pub endpoint: Option<String>,
+    /// Number of most recent days that keep their newest off-site backup
+    #[serde(default = "default_keep_daily")]
+    pub keep_daily: usize,
+    /// Number of most recent weeks that keep their newest off-site backup
+    #[serde(default = "default_keep_weekly")]
+    pub keep_weekly: usize,
+    /// Number of most recent months that keep their newest off-site backup
+    #[serde(default = "default_keep_monthly")]
+    pub keep_monthly: usize,
+}
+
 // Default value functions
 fn default_target_ratio() -> f64 {
     0.3
@@ -130,6 +193,30 @@ fn default_auto_update_check() -> bool {
 fn default_preferred_mirror() -> Option<String> {
     Some("ebi".to_string())
 }
+fn default_target_chunk_size() -> usize {
+    10 * 1024 * 1024
+}
+fn default_max_chunk_size() -> usize {
+    50 * 1024 * 1024
+}
+fn default_min_sequences_per_chunk() -> usize {
+    10
+}
+fn default_taxonomic_coherence() -> f64 {
+    0.8
+}
+fn default_special_taxon_strategy() -> String {
+    "own-chunks".to_string()
+}
+fn default_keep_daily() -> usize {
+    7
+}
+fn default_keep_weekly() -> usize {
+    4
+}
+fn default_keep_monthly() -> usize {
+    12
+}
 
 impl Default for ReductionConfig {
     fn default() -> Self {
@@ -184,6 +271,33 @@ impl Default for DatabaseConfig {
     }
 }
 
+impl Default for ChunkingConfig {
+    fn default() -> Self {
+        Self {
+            target_chunk_size: default_target_chunk_size(),
+            max_chunk_size: default_max_chunk_size(),
+            min_sequences_per_chunk: default_min_sequences_per_chunk(),
+            taxonomic_coherence: default_taxonomic_coherence(),
+            boundaries: None,
+            special_taxa: Vec::new(),
+            rank_min_sizes: BTreeMap::new(),
+        }
+    }
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self {
+            offsite: None,
+            region: None,
+            endpoint: None,
+            keep_daily: default_keep_daily(),
+            keep_weekly: default_keep_weekly(),
+            keep_monthly: default_keep_monthly(),
+        }
+    }
+}
+
 pub fn default_config() -> Config {
     Config::default()
 }
@@ -195,6 +309,32 @@ pub fn load_config<P: AsRef<Path>>(path: P) -> Result<Config, TalariaError> {
     Ok(config)
 }
 
+/// Path of the user configuration file
+///
+/// `TALARIA_CONFIG` wins, then `~/.config/talaria/config.toml`.
+pub fn user_config_path() -> Option<PathBuf> {
+    if let Ok(path) = std::env::var("TALARIA_CONFIG") {
+        return Some(PathBuf::from(path));
+    }
+    let home = std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .ok()?;
+    Some(
+        PathBuf::from(home)
+            .join(".config")
+            .join("talaria")
+            .join("config.toml"),
+    )
+}
+
+/// Load the user configuration, or the defaults if there is none
+pub fn load_user_config() -> Result<Config, TalariaError> {
+    match user_config_path() {
+        Some(path) if path.exists() => load_config(path),
+        _ => Ok(Config::default()),
+    }
+}
+
 pub fn save_config<P: AsRef<Path>>(path: P, config: &Config) -> Result<(), TalariaError> {
     let contents = toml::to_string_pretty(config)
         .map_err(|e| TalariaError::Configuration(format!("Failed to serialize config: {}", e)))?;
@@ -239,6 +379,25 @@ mod tests {
         assert_eq!(config.database.retention_count, 3);
         assert!(!config.database.auto_update_check);
         assert_eq!(config.database.preferred_mirror, Some("ebi".to_string()));
+
+        // Test chunking defaults
+        assert_eq!(config.chunking.target_chunk_size, 10 * 1024 * 1024);
+        assert_eq!(config.chunking.max_chunk_size, 50 * 1024 * 1024);
+        assert_eq!(config.chunking.min_sequences_per_chunk, 10);
+        assert_eq!(config.chunking.boundaries, None);
+        assert!(config.chunking.special_taxa.is_empty());
+        assert!(config.chunking.rank_min_sizes.is_empty());
+
+        // Test backup defaults
+        assert_eq!(config.backup.offsite, None);
+        assert_eq!(
+            (
+                config.backup.keep_daily,
+                config.backup.keep_weekly,
+                config.backup.keep_monthly
+            ),
+            (7, 4, 12)
+        );
     }
 
     #[test]
@@ -345,6 +504,64 @@ algorithm = "custom"
         assert_eq!(config.performance.chunk_size, 10000);
     }
 
+    #[test]
+    fn test_load_chunking_config() {
+        let toml_content = r#"
+[chunking]
+max_chunk_size = 67108864
+boundaries = "content-defined"
+
+[chunking.rank_min_sizes]
+species = 2097152
+
+[[chunking.special_taxa]]
+taxon_id = 9606
+name = "Homo sapiens"
+
+[[chunking.special_taxa]]
+taxon_id = 10239
+name = "Viruses"
+strategy = "group-at-family"
+"#;
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        write!(temp_file, "{}", toml_content).unwrap();
+
+        let config = load_config(temp_file.path()).unwrap();
+        let chunking = &config.chunking;
+
+        assert_eq!(chunking.max_chunk_size, 64 * 1024 * 1024);
+        assert_eq!(chunking.target_chunk_size, 10 * 1024 * 1024);
+        assert_eq!(chunking.boundaries.as_deref(), Some("content-defined"));
+        assert_eq!(chunking.rank_min_sizes.get("species"), Some(&2097152));
+        assert_eq!(chunking.special_taxa.len(), 2);
+        assert_eq!(chunking.special_taxa[0].strategy, "own-chunks");
+        assert_eq!(chunking.special_taxa[1].taxon_id, 10239);
+        assert_eq!(chunking.special_taxa[1].strategy, "group-at-family");
+    }
+
+    #[test]
+    fn test_load_backup_config() {
+        let toml_content = r#"
+[backup]
+offsite = "s3://lab-backups/talaria"
+region = "eu-west-1"
+keep_weekly = 8
+"#;
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        write!(temp_file, "{}", toml_content).unwrap();
+
+        let config = load_config(temp_file.path()).unwrap();
+        let backup = &config.backup;
+
+        assert_eq!(backup.offsite.as_deref(), Some("s3://lab-backups/talaria"));
+        assert_eq!(backup.region.as_deref(), Some("eu-west-1"));
+        assert_eq!(backup.endpoint, None);
+        assert_eq!(backup.keep_daily, 7);
+        assert_eq!(backup.keep_weekly, 8);
+    }
+
     #[test]
     fn test_load_invalid_config() {
         let toml_content = "this is not valid TOML {{";
@@ -3,10 +3,11 @@
 use serde::{Deserialize, Serialize};
 
 /// Output format for command results
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
 pub enum OutputFormat {
     /// Human-readable text output
+    #[default]
     Text,
     /// JSON output
     Json,
@@ -26,12 +27,6 @@ pub enum OutputFormat {
     HashOnly,
 }
 
-impl Default for OutputFormat {
-    fn default() -> Self {
-        Self::Text
-    }
-}
-
 impl OutputFormat {
     /// Check if format is machine-readable
     pub fn is_machine_readable(&self) -> bool {
@@ -3,7 +3,7 @@
 use serde::{Deserialize, Serialize};
 
 /// Type of biological sequence
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
 pub enum SequenceType {
     /// Protein/amino acid sequence
     Protein,
@@ -14,6 +14,7 @@ pub enum SequenceType {
     /// Generic nucleotide (DNA or RNA)
     Nucleotide,
     /// Unknown sequence type
+    #[default]
     Unknown,
 }
 
@@ -56,12 +57,6 @@ impl SequenceType {
     }
 }
 
-impl Default for SequenceType {
-    fn default() -> Self {
-        Self::Unknown
-    }
-}
-
 impl std::fmt::Display for SequenceType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
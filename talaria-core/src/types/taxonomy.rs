@@ -62,7 +62,7 @@ impl TaxonId {
 }
 
 /// Source of taxonomy data (how it was obtained)
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
 pub enum TaxonomyDataSource {
     /// Provided by API (e.g., UniProt API when querying by taxid)
     Api,
@@ -75,6 +75,7 @@ pub enum TaxonomyDataSource {
     /// Inherited from parent sequence during processing
     Inherited,
     /// Unknown source
+    #[default]
     Unknown,
 }
 
@@ -97,12 +98,6 @@ impl TaxonomyDataSource {
     }
 }
 
-impl Default for TaxonomyDataSource {
-    fn default() -> Self {
-        Self::Unknown
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,93 @@
+/// Update throughput on simulated database releases
+///
+/// Applies the second release of a simulated series to a database holding the
+/// first, once by re-chunking the whole release and once incrementally, the
+/// way `talaria database update` does. The series comes from the release
+/// simulator in `talaria-test`, so the benchmark needs no downloaded data.
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use std::path::{Path, PathBuf};
+use talaria_bio::parse_fasta;
+use talaria_herald::database::DatabaseManager;
+use talaria_test::fixtures::{create_test_fasta, test_database_source};
+use talaria_test::simulator::{Release, Simulator, SimulatorConfig};
+use tempfile::TempDir;
+
+fn write_release(release: &Release, dir: &Path) -> PathBuf {
+    let sequences: Vec<_> = release.proteins().map(|r| r.to_test_sequence()).collect();
+    let path = dir.join(format!("{}.fasta", release.name));
+    std::fs::write(&path, create_test_fasta(&sequences)).unwrap();
+    path
+}
+
+/// A fresh database holding `fasta` as its only version
+fn load(fasta: &Path, dir: &Path) -> DatabaseManager {
+    let mut manager = DatabaseManager::new(Some(dir.to_string_lossy().to_string())).unwrap();
+    manager
+        .chunk_sequences_direct_with_progress_final(
+            parse_fasta(fasta).unwrap(),
+            &test_database_source("sim_bench"),
+            None,
+            true,
+        )
+        .unwrap();
+    manager
+}
+
+fn bench_release_update(c: &mut Criterion) {
+    let mut group = c.benchmark_group("simulated_release_update");
+    group.sample_size(10);
+
+    let temp_dir = TempDir::new().unwrap();
+    std::env::set_var("TALARIA_HOME", temp_dir.path());
+
+    for (size, config) in [
+        ("default", SimulatorConfig::default()),
+        ("large", SimulatorConfig::large()),
+    ] {
+        let releases: Vec<Release> = Simulator::new(config).take(2).collect();
+        let dir = temp_dir.path().join(size);
+        std::fs::create_dir_all(&dir).unwrap();
+        let previous = write_release(&releases[0], &dir);
+        let next = write_release(&releases[1], &dir);
+        group.throughput(Throughput::Elements(releases[1].proteins().count() as u64));
+
+        group.bench_function(BenchmarkId::new("full_rechunk", size), |b| {
+            b.iter_with_setup(
+                || TempDir::new_in(&dir).unwrap(),
+                // Returned so that closing and deleting the database is not timed
+                |db_dir| {
+                    let manager = load(&next, db_dir.path());
+                    (manager, db_dir)
+                },
+            );
+        });
+
+        group.bench_function(BenchmarkId::new("incremental", size), |b| {
+            b.iter_with_setup(
+                || {
+                    let db_dir = TempDir::new_in(&dir).unwrap();
+                    let manager = load(&previous, db_dir.path());
+                    // Versions are second-resolution timestamps
+                    std::thread::sleep(std::time::Duration::from_secs(1));
+                    (manager, db_dir)
+                },
+                |(mut manager, db_dir)| {
+                    let report = manager
+                        .apply_incremental_release(
+                            &next,
+                            &test_database_source("sim_bench"),
+                            &|_: &str| {},
+                        )
+                        .unwrap();
+                    (report, manager, db_dir)
+                },
+            );
+        });
+    }
+
+    group.finish();
+    std::env::remove_var("TALARIA_HOME");
+}
+
+criterion_group!(benches, bench_release_update);
+criterion_main!(benches);
@@ -1,11 +1,25 @@
+use crate::storage::HeraldStorage;
+use crate::types::SHA256Hash;
+use crate::TemporalManifest;
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use talaria_core::system::paths;
 use talaria_storage::backend::RocksDBBackend;
 
+pub mod offsite;
+pub mod retention;
+
+pub use offsite::{OffsiteBackup, OffsiteCatalogEntry, OffsitePruneReport, OffsitePushReport};
+pub use retention::RetentionPolicy;
+
+/// Backup engine directory of the sequence store (manifests, sequences, indices)
+const SEQUENCES_ENGINE: &str = "rocksdb";
+/// Backup engine directory of the chunk store
+const CHUNKS_ENGINE: &str = "chunks";
+
 /// Manages database backups using RocksDB BackupEngine
 pub struct BackupManager {
     backups_dir: PathBuf,
@@ -20,12 +34,40 @@ pub struct BackupMetadata {
     pub description: Option<String>,
     pub created_at: DateTime<Utc>,
     pub size_bytes: u64,
+    /// Backup of the chunk store; `None` for backups made before chunks were
+    /// included
+    #[serde(default)]
+    pub chunk_backup_id: Option<u32>,
+}
+
+/// Outcome of restoring a backup into a scratch directory and checking it
+#[derive(Debug, Clone)]
+pub struct RestoreDrillReport {
+    pub name: String,
+    pub restored_to: PathBuf,
+    /// Chunks read back and checked against their hash
+    pub chunks_verified: usize,
+    /// Database version manifests found in the restored store
+    pub manifests_checked: usize,
+    /// (manifest key, chunk) pairs whose chunk is missing from the restore
+    pub missing_chunks: Vec<(String, String)>,
+}
+
+impl RestoreDrillReport {
+    pub fn passed(&self) -> bool {
+        self.missing_chunks.is_empty()
+    }
 }
 
 impl BackupManager {
     /// Create a new backup manager
     pub fn new() -> Result<Self> {
-        let backups_dir = paths::talaria_backups_dir();
+        Self::with_dir(paths::talaria_backups_dir())
+    }
+
+    /// Backup manager over another backups directory, e.g. one pulled from
+    /// an off-site target
+    pub fn with_dir(backups_dir: PathBuf) -> Result<Self> {
         let databases_dir = paths::talaria_databases_dir();
 
         // Ensure backups directory exists
@@ -37,9 +79,19 @@ impl BackupManager {
         })
     }
 
+    /// Directory holding the backup engines and metadata
+    pub fn backups_dir(&self) -> &Path {
+        &self.backups_dir
+    }
+
     /// Get the RocksDB backup directory path
     fn rocksdb_backup_dir(&self) -> PathBuf {
-        self.backups_dir.join("rocksdb")
+        self.backups_dir.join(SEQUENCES_ENGINE)
+    }
+
+    /// Get the chunk store backup directory path
+    fn chunk_backup_dir(&self) -> PathBuf {
+        self.backups_dir.join(CHUNKS_ENGINE)
     }
 
     /// Get the metadata directory path
@@ -55,14 +107,20 @@ impl BackupManager {
     /// Create a new backup with a given name
     ///
     /// This creates:
-    /// 1. A RocksDB backup using BackupEngine
-    /// 2. Metadata JSON with name, description, and creation time
+    /// 1. A RocksDB backup of the sequence store using BackupEngine
+    /// 2. A RocksDB backup of the chunk store
+    /// 3. Metadata JSON with name, description, and creation time
+    ///
+    /// Both engines share unchanged SST files with earlier backups, so each
+    /// backup only adds the files written since.
     pub fn create_backup(
         &self,
-        rocksdb: &RocksDBBackend,
+        storage: &HeraldStorage,
         name: &str,
         description: Option<String>,
     ) -> Result<BackupMetadata> {
+        let rocksdb = storage.sequence_storage.get_rocksdb();
+
         // Ensure backup directories exist
         let backup_dir = self.rocksdb_backup_dir();
         let metadata_dir = self.metadata_dir();
@@ -92,13 +150,27 @@ impl BackupManager {
 
         let created_at = DateTime::from_timestamp(*timestamp, 0).unwrap_or_else(|| Utc::now());
 
+        // Back up the chunk store alongside
+        let chunk_backup_dir = self.chunk_backup_dir();
+        let chunk_backup_id = storage
+            .chunk_storage()
+            .create_backup(&chunk_backup_dir, true)
+            .context("Failed to create chunk store backup")?;
+        let chunk_size_bytes = RocksDBBackend::list_backups(&chunk_backup_dir)?
+            .iter()
+            .find(|(id, _, _)| *id == chunk_backup_id)
+            .map(|(_, _, size)| *size)
+            .unwrap_or(0);
+        let size_bytes = size_bytes + chunk_size_bytes;
+
         // Create metadata
         let metadata = BackupMetadata {
             id: backup_id,
             name: name.to_string(),
             description: description.clone(),
             created_at,
-            size_bytes: *size_bytes,
+            size_bytes,
+            chunk_backup_id: Some(chunk_backup_id),
         };
 
         // Save metadata
@@ -106,7 +178,7 @@ impl BackupManager {
 
         tracing::info!("✓ Backup '{}' created successfully", name);
         tracing::info!("  Backup ID: {}", backup_id);
-        tracing::info!("  Size: {:.2} MB", size_bytes / 1_048_576);
+        tracing::info!("  Size: {:.2} MB", size_bytes as f64 / 1_048_576.0);
 
         Ok(metadata)
     }
@@ -133,7 +205,7 @@ impl BackupManager {
         }
 
         // Sort by creation date (newest first)
-        backups.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        backups.sort_by_key(|b| std::cmp::Reverse(b.created_at));
 
         Ok(backups)
     }
@@ -196,8 +268,16 @@ impl BackupManager {
             fs::remove_dir_all(&temp_restore)?;
         }
 
-        RocksDBBackend::restore_from_latest_backup(&backup_dir, &temp_restore)
+        RocksDBBackend::restore_from_backup(&backup_dir, &temp_restore, metadata.id)
             .context("Failed to restore backup")?;
+        if let Some(chunk_backup_id) = metadata.chunk_backup_id {
+            RocksDBBackend::restore_from_backup(
+                &self.chunk_backup_dir(),
+                &temp_restore.join("chunk_storage"),
+                chunk_backup_id,
+            )
+            .context("Failed to restore chunk store backup")?;
+        }
 
         tracing::info!(
             "✓ Backup restored to temporary location: {}",
@@ -221,11 +301,112 @@ impl BackupManager {
 
         RocksDBBackend::verify_backup(&backup_dir, metadata.id)
             .context("Backup verification failed")?;
+        if let Some(chunk_backup_id) = metadata.chunk_backup_id {
+            RocksDBBackend::verify_backup(self.chunk_backup_dir(), chunk_backup_id)
+                .context("Chunk store backup verification failed")?;
+        }
 
         tracing::info!("✓ Backup '{}' verification passed", name);
         Ok(())
     }
 
+    /// Restore a backup into an empty directory and check what comes back
+    ///
+    /// The restore is laid out like a repository and opened on its own, so
+    /// the live stores are never touched. Every restored chunk is read and
+    /// hashed (`HeraldStorage::verify_integrity`), and every database version
+    /// manifest must find all of its chunks.
+    pub fn restore_drill(&self, name: &str, restore_to: &Path) -> Result<RestoreDrillReport> {
+        let metadata = self.get_backup_metadata(name)?;
+        if restore_to.exists() && fs::read_dir(restore_to)?.next().is_some() {
+            anyhow::bail!(
+                "{} is not empty; restore drills need an empty directory",
+                restore_to.display()
+            );
+        }
+
+        self.verify_backup(name)?;
+
+        let sequences_dir = restore_to.join("sequences");
+        RocksDBBackend::restore_from_backup(
+            &self.rocksdb_backup_dir(),
+            &sequences_dir.join("rocksdb"),
+            metadata.id,
+        )
+        .context("Failed to restore sequence store")?;
+        match metadata.chunk_backup_id {
+            Some(chunk_backup_id) => RocksDBBackend::restore_from_backup(
+                &self.chunk_backup_dir(),
+                &restore_to.join("chunk_storage"),
+                chunk_backup_id,
+            )
+            .context("Failed to restore chunk store")?,
+            None => tracing::warn!(
+                "Backup '{}' predates chunk store backups; only manifests and sequences are restored",
+                name
+            ),
+        }
+
+        let storage = HeraldStorage::with_sequences_dir(restore_to, &sequences_dir)?;
+        storage
+            .verify_integrity()
+            .context("Restored chunk store failed verification")?;
+        let chunks_verified = storage.chunk_storage().list_all_chunks()?.len();
+
+        let mut manifests_checked = 0;
+        let mut missing_chunks = Vec::new();
+        let rocksdb = storage.sequence_storage.get_rocksdb();
+        for (key, data) in rocksdb.iterate_manifest_prefix("manifest:")? {
+            // Database versions are `manifest:{source}:{dataset}:{version}`
+            if key.split(':').count() != 4 {
+                continue;
+            }
+            let manifest: TemporalManifest = bincode::deserialize(&data)
+                .with_context(|| format!("Restored manifest {} is unreadable", key))?;
+            manifests_checked += 1;
+            let indexed: Vec<SHA256Hash> = manifest.chunk_index.iter().map(|c| c.hash).collect();
+            for hash in storage.missing_chunks(&indexed) {
+                missing_chunks.push((key.clone(), hash.to_hex()));
+            }
+        }
+
+        Ok(RestoreDrillReport {
+            name: name.to_string(),
+            restored_to: restore_to.to_path_buf(),
+            chunks_verified,
+            manifests_checked,
+            missing_chunks,
+        })
+    }
+
+    /// Files of a backup, relative to the backups directory
+    ///
+    /// Lists each engine's meta file and every file it references. Shared
+    /// SST files are named by content, so they never change once written.
+    pub fn backup_files(&self, metadata: &BackupMetadata) -> Result<Vec<String>> {
+        let mut files = engine_files(&self.backups_dir, SEQUENCES_ENGINE, metadata.id)?;
+        if let Some(chunk_backup_id) = metadata.chunk_backup_id {
+            files.extend(engine_files(
+                &self.backups_dir,
+                CHUNKS_ENGINE,
+                chunk_backup_id,
+            )?);
+        }
+        files.push(format!("metadata/{}.json", metadata.name));
+        Ok(files)
+    }
+
+    /// Record the metadata of a backup brought in from elsewhere
+    pub fn import_metadata(&self, metadata: &BackupMetadata) -> Result<()> {
+        let metadata_dir = self.metadata_dir();
+        fs::create_dir_all(&metadata_dir)?;
+        fs::write(
+            metadata_dir.join(format!("{}.json", metadata.name)),
+            serde_json::to_string_pretty(metadata)?,
+        )?;
+        Ok(())
+    }
+
     /// Delete a backup by name
     pub fn delete_backup(&self, name: &str) -> Result<()> {
         let metadata = self.get_backup_metadata(name)?;
@@ -255,6 +436,11 @@ impl BackupManager {
 
         RocksDBBackend::purge_old_backups(&backup_dir, num_to_keep)
             .context("Failed to purge old backups")?;
+        let chunk_backup_dir = self.chunk_backup_dir();
+        if chunk_backup_dir.exists() {
+            RocksDBBackend::purge_old_backups(&chunk_backup_dir, num_to_keep)
+                .context("Failed to purge old chunk store backups")?;
+        }
 
         tracing::info!("✓ Old backups purged successfully");
 
@@ -304,8 +490,114 @@ impl BackupManager {
     }
 }
 
+/// Meta file of one engine backup plus the files it lists
+///
+/// Meta files name one file per line (`private/…`, `shared/…` or
+/// `shared_checksum/…`) followed by checksum and size fields.
+fn engine_files(backups_dir: &Path, engine: &str, backup_id: u32) -> Result<Vec<String>> {
+    let meta = format!("meta/{}", backup_id);
+    let content = fs::read_to_string(backups_dir.join(engine).join(&meta))
+        .with_context(|| format!("Missing {} backup meta file {}", engine, meta))?;
+
+    let mut files = vec![format!("{}/{}", engine, meta)];
+    files.extend(parse_meta_files(&content).map(|file| format!("{}/{}", engine, file)));
+    Ok(files)
+}
+
+fn parse_meta_files(content: &str) -> impl Iterator<Item = &str> {
+    content
+        .lines()
+        .filter_map(|line| line.split_whitespace().next())
+        .filter(|file| {
+            file.starts_with("private/")
+                || file.starts_with("shared/")
+                || file.starts_with("shared_checksum/")
+        })
+}
+
 impl Default for BackupManager {
     fn default() -> Self {
         Self::new().expect("Failed to create BackupManager")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_meta_files() {
+        let meta = "schema_version 2\n\
+                    1729286400\n\
+                    1042\n\
+                    3\n\
+                    private/4/MANIFEST-000012 crc32 2818275041 size 1123\n\
+                    private/4/CURRENT crc32 1472935912 size 16\n\
+                    shared_checksum/000009_3204827315_58213.sst crc32 3204827315 size 58213\n\
+                    // FOOTER\n";
+
+        let files: Vec<_> = parse_meta_files(meta).collect();
+        assert_eq!(
+            files,
+            [
+                "private/4/MANIFEST-000012",
+                "private/4/CURRENT",
+                "shared_checksum/000009_3204827315_58213.sst"
+            ]
+        );
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_restore_drill_passes_on_stored_chunks() {
+        use crate::types::{ChunkClassification, SHA256Hash, SHA256HashExt, TemporalDeltaChunk};
+
+        let repo = tempfile::TempDir::new().unwrap();
+        let storage =
+            HeraldStorage::with_sequences_dir(repo.path(), &repo.path().join("sequences")).unwrap();
+        let reference = storage
+            .store_chunk(&b"ACGTACGTTGCA".repeat(200), true)
+            .unwrap();
+        storage.store_chunk(b"stored as is", false).unwrap();
+        storage
+            .store_delta_chunk(&TemporalDeltaChunk {
+                // Delta chunks are keyed by their reference and operations
+                content_hash: TemporalDeltaChunk::compute_content_hash(&reference, &[]).unwrap(),
+                reference_hash: reference,
+                chunk_type: ChunkClassification::Delta {
+                    reference_hash: reference,
+                    compression_ratio: 0.5,
+                },
+                taxonomy_version: SHA256Hash::zero(),
+                taxon_ids: Vec::new(),
+                deltas: Vec::new(),
+                sequences: Vec::new(),
+                created_at: Utc::now(),
+                valid_from: Utc::now(),
+                valid_until: None,
+                original_size: 1000,
+                compressed_size: 500,
+                compression_ratio: 0.5,
+            })
+            .unwrap();
+
+        let backups = tempfile::TempDir::new().unwrap();
+        let manager = BackupManager::with_dir(backups.path().to_path_buf()).unwrap();
+        manager.create_backup(&storage, "drill", None).unwrap();
+
+        let restore = tempfile::TempDir::new().unwrap();
+        let report = manager
+            .restore_drill("drill", &restore.path().join("restored"))
+            .unwrap();
+        assert!(report.passed());
+        assert_eq!(report.chunks_verified, 3);
+    }
+
+    #[test]
+    fn test_metadata_without_chunk_backup() {
+        let json = r#"{"id":3,"name":"nightly","description":null,
+            "created_at":"2024-04-01T02:00:00Z","size_bytes":1024}"#;
+        let metadata: BackupMetadata = serde_json::from_str(json).unwrap();
+        assert_eq!(metadata.chunk_backup_id, None);
+    }
+}
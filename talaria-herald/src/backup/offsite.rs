@@ -0,0 +1,684 @@
+/// Off-site copies of backups through any `CloudStorage` provider
+///
+/// The remote mirrors the local backups directory: each engine's `meta/`,
+/// `private/` and `shared_checksum/` files under the same relative paths,
+/// plus one catalog entry per pushed backup. Shared SST files are named by
+/// content, so a push skips those the remote already has and uploads every
+/// other file of the backup. The catalog entry is written last; a backup is
+/// only listed once all of its files are in place.
+use super::{BackupManager, BackupMetadata, RetentionPolicy};
+use crate::cloud::{create_storage, CloudConfig, CloudStorage};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+
+const CATALOG_DIR: &str = "catalog";
+
+/// A backup as recorded on the off-site target
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OffsiteCatalogEntry {
+    pub metadata: BackupMetadata,
+    /// Files of the backup, relative to the backups directory
+    pub files: Vec<String>,
+    pub pushed_at: DateTime<Utc>,
+}
+
+/// Outcome of pushing one backup
+#[derive(Debug, Clone, Default)]
+pub struct OffsitePushReport {
+    pub uploaded: usize,
+    /// Files the target already had from earlier pushes
+    pub skipped: usize,
+    pub bytes_uploaded: u64,
+}
+
+/// Outcome of applying the retention policy off-site
+#[derive(Debug, Clone, Default)]
+pub struct OffsitePruneReport {
+    pub kept: Vec<String>,
+    pub removed: Vec<String>,
+    /// Objects no retained backup references any more
+    pub deleted_files: usize,
+}
+
+pub struct OffsiteBackup {
+    storage: Box<dyn CloudStorage>,
+    prefix: String,
+    staging_dir: PathBuf,
+}
+
+impl OffsiteBackup {
+    /// `staging_dir` holds catalog entries while they are transferred
+    pub fn new(storage: Box<dyn CloudStorage>, prefix: String, staging_dir: PathBuf) -> Self {
+        Self {
+            storage,
+            prefix: prefix.trim_matches('/').to_string(),
+            staging_dir,
+        }
+    }
+
+    /// Connect to a target such as `s3://bucket/talaria-backups`
+    ///
+    /// Must be called inside a Tokio runtime context.
+    pub fn from_url(
+        url: &str,
+        region: Option<&str>,
+        endpoint: Option<&str>,
+        staging_dir: PathBuf,
+    ) -> Result<Self> {
+        let (config, prefix) = parse_target(url, region, endpoint)?;
+        Ok(Self::new(create_storage(&config)?, prefix, staging_dir))
+    }
+
+    /// Upload a local backup, skipping files the target already holds
+    pub async fn push(&self, manager: &BackupManager, name: &str) -> Result<OffsitePushReport> {
+        let metadata = manager.get_backup_metadata(name)?;
+        let files = manager.backup_files(&metadata)?;
+
+        self.storage
+            .verify_access()
+            .await
+            .context("Off-site target is not accessible")?;
+        let remote: HashMap<String, usize> = self
+            .storage
+            .list_objects(self.list_prefix().as_deref())
+            .await?
+            .into_iter()
+            .map(|object| (object.key, object.size))
+            .collect();
+
+        let mut report = OffsitePushReport::default();
+        for file in &files {
+            let local = manager.backups_dir().join(file);
+            let size = fs::metadata(&local)
+                .with_context(|| format!("Backup file {} is missing", local.display()))?
+                .len();
+            let key = self.key(file);
+            // Only content-named files can be trusted by name and size; engine
+            // IDs and backup names are reused after a backup is deleted
+            if is_content_named(file) && remote.get(&key) == Some(&(size as usize)) {
+                report.skipped += 1;
+                continue;
+            }
+            self.storage
+                .upload(&local, &key, None)
+                .await
+                .with_context(|| format!("Failed to upload {}", file))?;
+            report.uploaded += 1;
+            report.bytes_uploaded += size;
+        }
+
+        let entry = OffsiteCatalogEntry {
+            metadata,
+            files,
+            pushed_at: Utc::now(),
+        };
+        let staged = self.stage(&format!("{}.json", name))?;
+        fs::write(&staged, serde_json::to_vec_pretty(&entry)?)?;
+        self.storage
+            .upload(&staged, &self.catalog_key(name), None)
+            .await?;
+        fs::remove_file(&staged)?;
+
+        Ok(report)
+    }
+
+    /// Backups on the target, newest first
+    pub async fn list(&self) -> Result<Vec<OffsiteCatalogEntry>> {
+        let catalog_prefix = self.key(CATALOG_DIR);
+        let objects = self.storage.list_objects(Some(&catalog_prefix)).await?;
+
+        let mut entries = Vec::new();
+        for object in objects.iter().filter(|o| o.key.ends_with(".json")) {
+            let staged = self.stage("catalog-entry.json")?;
+            self.storage.download(&object.key, &staged, None).await?;
+            let entry: OffsiteCatalogEntry = serde_json::from_slice(&fs::read(&staged)?)
+                .with_context(|| format!("Invalid catalog entry {}", object.key))?;
+            fs::remove_file(&staged)?;
+            entries.push(entry);
+        }
+
+        entries.sort_by_key(|e| std::cmp::Reverse(e.metadata.created_at));
+        Ok(entries)
+    }
+
+    /// Download a backup into the backups directory of `manager`
+    ///
+    /// Files land in a staging directory first. Shared SST files already
+    /// present locally with the same size are kept; engine-specific files
+    /// (`meta/<id>`, `private/<id>/`) are only moved in if no local file of
+    /// that name exists, or it is identical, so a local backup that happens
+    /// to carry the same engine ID is never overwritten.
+    pub async fn pull(&self, name: &str, manager: &BackupManager) -> Result<BackupMetadata> {
+        let entry = self
+            .list()
+            .await?
+            .into_iter()
+            .find(|e| e.metadata.name == name)
+            .ok_or_else(|| anyhow::anyhow!("Backup '{}' not found off-site", name))?;
+        if let Ok(local) = manager.get_backup_metadata(name) {
+            if local.id != entry.metadata.id
+                || local.chunk_backup_id != entry.metadata.chunk_backup_id
+            {
+                anyhow::bail!("A different local backup is already named '{}'", name);
+            }
+        }
+        let remote: HashMap<String, usize> = self
+            .storage
+            .list_objects(self.list_prefix().as_deref())
+            .await?
+            .into_iter()
+            .map(|object| (object.key, object.size))
+            .collect();
+
+        let staging = self.stage(&format!("pull-{}", name))?;
+        if staging.exists() {
+            fs::remove_dir_all(&staging)?;
+        }
+        let mut staged = Vec::new();
+        for file in &entry.files {
+            let key = self.key(file);
+            let size = remote
+                .get(&key)
+                .ok_or_else(|| anyhow::anyhow!("Off-site backup '{}' lacks {}", name, file))?;
+            let local = manager.backups_dir().join(file);
+            if is_shared_file(file) && fs::metadata(&local).is_ok_and(|m| m.len() as usize == *size)
+            {
+                continue;
+            }
+            let target = staging.join(file);
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            self.storage
+                .download(&key, &target, None)
+                .await
+                .with_context(|| format!("Failed to download {}", file))?;
+            staged.push(file);
+        }
+
+        // Check every collision before moving anything into place
+        let mut moves = Vec::new();
+        for file in staged {
+            let source = staging.join(file);
+            let local = manager.backups_dir().join(file);
+            if local.exists() && !is_shared_file(file) {
+                if fs::read(&local)? != fs::read(&source)? {
+                    fs::remove_dir_all(&staging)?;
+                    anyhow::bail!(
+                        "Backup '{}' collides with local backup file {}; pull it into an empty \
+                         backups directory instead",
+                        name,
+                        file
+                    );
+                }
+                continue;
+            }
+            moves.push((source, local));
+        }
+        for (source, local) in moves {
+            if let Some(parent) = local.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            if fs::rename(&source, &local).is_err() {
+                fs::copy(&source, &local)?;
+            }
+        }
+        fs::remove_dir_all(&staging)?;
+        manager.import_metadata(&entry.metadata)?;
+
+        Ok(entry.metadata)
+    }
+
+    /// Drop backups outside the retention policy and the files only they used
+    ///
+    /// Run it after pushes rather than during one: files of a push whose
+    /// catalog entry is not written yet count as unreferenced.
+    pub async fn prune(
+        &self,
+        policy: &RetentionPolicy,
+        dry_run: bool,
+    ) -> Result<OffsitePruneReport> {
+        let entries = self.list().await?;
+        let metadata: Vec<BackupMetadata> = entries.iter().map(|e| e.metadata.clone()).collect();
+        let keep = policy.retained(&metadata);
+
+        let (kept, removed): (Vec<_>, Vec<_>) = entries
+            .iter()
+            .partition(|e| keep.contains(&e.metadata.name));
+        let referenced: HashSet<String> = kept
+            .iter()
+            .flat_map(|e| e.files.iter().map(|file| self.key(file)))
+            .collect();
+
+        let catalog_prefix = format!("{}/", self.key(CATALOG_DIR));
+        let unreferenced: Vec<String> = self
+            .storage
+            .list_objects(self.list_prefix().as_deref())
+            .await?
+            .into_iter()
+            .map(|object| object.key)
+            .filter(|key| !key.starts_with(&catalog_prefix) && !referenced.contains(key))
+            .collect();
+
+        if !dry_run {
+            // Catalog entries first, so no listed backup ever lacks files
+            for entry in &removed {
+                self.storage
+                    .delete(&self.catalog_key(&entry.metadata.name))
+                    .await?;
+            }
+            for result in self.storage.delete_batch(&unreferenced).await? {
+                result?;
+            }
+        }
+
+        Ok(OffsitePruneReport {
+            kept: kept.iter().map(|e| e.metadata.name.clone()).collect(),
+            removed: removed.iter().map(|e| e.metadata.name.clone()).collect(),
+            deleted_files: unreferenced.len(),
+        })
+    }
+
+    fn key(&self, relative: &str) -> String {
+        if self.prefix.is_empty() {
+            relative.to_string()
+        } else {
+            format!("{}/{}", self.prefix, relative)
+        }
+    }
+
+    fn catalog_key(&self, name: &str) -> String {
+        self.key(&format!("{}/{}.json", CATALOG_DIR, name))
+    }
+
+    fn list_prefix(&self) -> Option<String> {
+        (!self.prefix.is_empty()).then(|| format!("{}/", self.prefix))
+    }
+
+    fn stage(&self, file_name: &str) -> Result<PathBuf> {
+        fs::create_dir_all(&self.staging_dir)?;
+        Ok(self.staging_dir.join(file_name))
+    }
+}
+
+/// Whether a backup file is a content-named SST shared between backups
+/// Shared SST file whose name includes its checksum and size
+fn is_content_named(file: &str) -> bool {
+    file.split('/').any(|part| part == "shared_checksum")
+}
+
+fn is_shared_file(file: &str) -> bool {
+    file.split('/')
+        .any(|part| part == "shared" || part == "shared_checksum")
+}
+
+/// Split an off-site URL into a provider config and a key prefix
+pub fn parse_target(
+    url: &str,
+    region: Option<&str>,
+    endpoint: Option<&str>,
+) -> Result<(CloudConfig, String)> {
+    let Some(location) = url.strip_prefix("s3://") else {
+        anyhow::bail!(
+            "Unsupported off-site target '{}'; expected s3://bucket[/prefix]",
+            url
+        );
+    };
+    let (bucket, prefix) = location.split_once('/').unwrap_or((location, ""));
+    if bucket.is_empty() {
+        anyhow::bail!("Off-site target '{}' has no bucket", url);
+    }
+
+    let region = region.map(str::to_string).unwrap_or_else(|| {
+        std::env::var("AWS_DEFAULT_REGION").unwrap_or_else(|_| "us-east-1".to_string())
+    });
+    let config = CloudConfig::S3 {
+        bucket: bucket.to_string(),
+        region,
+        prefix: None,
+        endpoint: endpoint.map(str::to_string),
+    };
+    Ok((config, prefix.trim_matches('/').to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cloud::CloudObject;
+    use async_trait::async_trait;
+    use chrono::TimeZone;
+    use indicatif::ProgressBar;
+    use std::collections::BTreeMap;
+    use std::path::Path;
+    use std::sync::{Arc, Mutex};
+
+    /// Bucket held in memory; clones share the same objects
+    #[derive(Clone, Default)]
+    struct MemoryStorage {
+        objects: Arc<Mutex<BTreeMap<String, Vec<u8>>>>,
+    }
+
+    impl MemoryStorage {
+        fn keys(&self) -> Vec<String> {
+            self.objects.lock().unwrap().keys().cloned().collect()
+        }
+
+        fn contains(&self, key: &str) -> bool {
+            self.objects.lock().unwrap().contains_key(key)
+        }
+    }
+
+    #[async_trait]
+    impl CloudStorage for MemoryStorage {
+        async fn list_objects(&self, prefix: Option<&str>) -> Result<Vec<CloudObject>> {
+            Ok(self
+                .objects
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|(key, _)| key.starts_with(prefix.unwrap_or("")))
+                .map(|(key, data)| CloudObject {
+                    key: key.clone(),
+                    size: data.len(),
+                    etag: None,
+                    last_modified: Utc::now(),
+                    storage_class: None,
+                })
+                .collect())
+        }
+
+        async fn exists(&self, key: &str) -> Result<bool> {
+            Ok(self.contains(key))
+        }
+
+        async fn get_metadata(&self, key: &str) -> Result<CloudObject> {
+            self.list_objects(Some(key))
+                .await?
+                .into_iter()
+                .find(|object| object.key == key)
+                .ok_or_else(|| anyhow::anyhow!("No object {}", key))
+        }
+
+        async fn upload(
+            &self,
+            local_path: &Path,
+            key: &str,
+            _progress: Option<&ProgressBar>,
+        ) -> Result<()> {
+            let data = fs::read(local_path)?;
+            self.objects.lock().unwrap().insert(key.to_string(), data);
+            Ok(())
+        }
+
+        async fn download(
+            &self,
+            key: &str,
+            local_path: &Path,
+            _progress: Option<&ProgressBar>,
+        ) -> Result<()> {
+            let data = self
+                .objects
+                .lock()
+                .unwrap()
+                .get(key)
+                .cloned()
+                .ok_or_else(|| anyhow::anyhow!("No object {}", key))?;
+            fs::write(local_path, data)?;
+            Ok(())
+        }
+
+        async fn delete(&self, key: &str) -> Result<()> {
+            self.objects.lock().unwrap().remove(key);
+            Ok(())
+        }
+
+        async fn delete_batch(&self, keys: &[String]) -> Result<Vec<Result<()>>> {
+            let mut objects = self.objects.lock().unwrap();
+            Ok(keys
+                .iter()
+                .map(|key| {
+                    objects.remove(key);
+                    Ok(())
+                })
+                .collect())
+        }
+
+        async fn get_presigned_url(
+            &self,
+            key: &str,
+            _expires_in: std::time::Duration,
+        ) -> Result<String> {
+            Ok(format!("memory://{}", key))
+        }
+
+        async fn verify_access(&self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Lay out a sequence store backup the way BackupEngine does: a meta
+    /// file, one private file and the given shared SST files
+    fn write_backup(
+        manager: &BackupManager,
+        name: &str,
+        id: u32,
+        created_at: DateTime<Utc>,
+        shared: &[&str],
+    ) -> BackupMetadata {
+        let engine = manager.backups_dir().join("rocksdb");
+        let private = format!("private/{}/MANIFEST-000001", id);
+        let mut meta = format!("{}\n{} crc32 0 size 0\n", id, private);
+        for file in shared {
+            let path = engine.join("shared_checksum").join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, format!("sst {}", file)).unwrap();
+            meta.push_str(&format!("shared_checksum/{} crc32 0 size 0\n", file));
+        }
+        fs::create_dir_all(engine.join("meta")).unwrap();
+        fs::write(engine.join("meta").join(id.to_string()), meta).unwrap();
+        fs::create_dir_all(engine.join(format!("private/{}", id))).unwrap();
+        fs::write(engine.join(&private), format!("manifest of {}", name)).unwrap();
+
+        let metadata = BackupMetadata {
+            id,
+            name: name.to_string(),
+            description: None,
+            created_at,
+            size_bytes: 0,
+            chunk_backup_id: None,
+        };
+        manager.import_metadata(&metadata).unwrap();
+        metadata
+    }
+
+    fn day(day: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 6, day, 2, 0, 0).unwrap()
+    }
+
+    fn offsite(storage: &MemoryStorage, staging: &Path) -> OffsiteBackup {
+        OffsiteBackup::new(
+            Box::new(storage.clone()),
+            "lab/nightly/".to_string(),
+            staging.to_path_buf(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_push_skips_files_already_offsite() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let manager = BackupManager::with_dir(dir.path().join("backups")).unwrap();
+        let storage = MemoryStorage::default();
+        let offsite = offsite(&storage, &dir.path().join("staging"));
+
+        write_backup(&manager, "mon", 1, day(24), &["000010.sst", "000011.sst"]);
+        let report = offsite.push(&manager, "mon").await.unwrap();
+        // meta, private, two shared files and the metadata file
+        assert_eq!(report.uploaded, 5);
+        assert_eq!(report.skipped, 0);
+
+        write_backup(&manager, "tue", 2, day(25), &["000011.sst", "000012.sst"]);
+        let report = offsite.push(&manager, "tue").await.unwrap();
+        assert_eq!(report.uploaded, 4);
+        assert_eq!(report.skipped, 1);
+
+        // Pushing again only skips the shared files
+        let report = offsite.push(&manager, "tue").await.unwrap();
+        assert_eq!(report.uploaded, 3);
+        assert_eq!(report.skipped, 2);
+
+        let names: Vec<_> = offsite
+            .list()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|e| e.metadata.name)
+            .collect();
+        assert_eq!(names, ["tue", "mon"]);
+        assert!(storage.contains("lab/nightly/catalog/tue.json"));
+        assert!(storage
+            .keys()
+            .iter()
+            .all(|key| key.starts_with("lab/nightly/")));
+    }
+
+    #[tokio::test]
+    async fn test_prune_deletes_only_unreferenced_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let manager = BackupManager::with_dir(dir.path().join("backups")).unwrap();
+        let storage = MemoryStorage::default();
+        let offsite = offsite(&storage, &dir.path().join("staging"));
+
+        write_backup(&manager, "old", 1, day(10), &["a.sst", "b.sst"]);
+        write_backup(&manager, "mid", 2, day(29), &["b.sst", "c.sst"]);
+        write_backup(&manager, "new", 3, day(30), &["c.sst", "d.sst"]);
+        for name in ["old", "mid", "new"] {
+            offsite.push(&manager, name).await.unwrap();
+        }
+        let before = storage.keys();
+
+        let policy = RetentionPolicy {
+            daily: 2,
+            weekly: 0,
+            monthly: 0,
+        };
+        let report = offsite.prune(&policy, true).await.unwrap();
+        assert_eq!(report.removed, ["old"]);
+        // meta, private, a.sst and the metadata file of "old"
+        assert_eq!(report.deleted_files, 4);
+        assert_eq!(storage.keys(), before, "dry run deleted objects");
+
+        let report = offsite.prune(&policy, false).await.unwrap();
+        assert_eq!(report.kept, ["new", "mid"]);
+        assert_eq!(report.removed, ["old"]);
+        assert_eq!(report.deleted_files, 4);
+
+        let engine = "lab/nightly/rocksdb";
+        for gone in [
+            format!("{}/shared_checksum/a.sst", engine),
+            format!("{}/meta/1", engine),
+            format!("{}/private/1/MANIFEST-000001", engine),
+            "lab/nightly/metadata/old.json".to_string(),
+            "lab/nightly/catalog/old.json".to_string(),
+        ] {
+            assert!(!storage.contains(&gone), "{} not deleted", gone);
+        }
+        // b.sst is still used by "mid"
+        for kept in ["b.sst", "c.sst", "d.sst"] {
+            assert!(storage.contains(&format!("{}/shared_checksum/{}", engine, kept)));
+        }
+        assert_eq!(storage.keys().len(), before.len() - 5);
+
+        let report = offsite.prune(&policy, false).await.unwrap();
+        assert!(report.removed.is_empty());
+        assert_eq!(report.deleted_files, 0);
+    }
+
+    #[tokio::test]
+    async fn test_pull_refuses_name_and_file_collisions() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let source = BackupManager::with_dir(dir.path().join("source")).unwrap();
+        let storage = MemoryStorage::default();
+        let offsite = offsite(&storage, &dir.path().join("staging"));
+        write_backup(&source, "mon", 1, day(24), &["a.sst", "b.sst"]);
+        offsite.push(&source, "mon").await.unwrap();
+
+        // A different local backup carries the same name
+        let named = BackupManager::with_dir(dir.path().join("named")).unwrap();
+        write_backup(&named, "mon", 7, day(20), &["a.sst"]);
+        let err = offsite.pull("mon", &named).await.unwrap_err();
+        assert!(err.to_string().contains("already named 'mon'"), "{}", err);
+        assert_eq!(named.get_backup_metadata("mon").unwrap().id, 7);
+
+        // A local backup under another name holds the same engine ID
+        let clashing = BackupManager::with_dir(dir.path().join("clashing")).unwrap();
+        write_backup(&clashing, "other", 1, day(20), &["a.sst"]);
+        let meta = clashing.backups_dir().join("rocksdb/meta/1");
+        let local_meta = fs::read(&meta).unwrap();
+        let err = offsite.pull("mon", &clashing).await.unwrap_err();
+        assert!(err.to_string().contains("collides"), "{}", err);
+        assert_eq!(fs::read(&meta).unwrap(), local_meta);
+        assert!(clashing.get_backup_metadata("mon").is_err());
+        assert!(!clashing
+            .backups_dir()
+            .join("rocksdb/shared_checksum/b.sst")
+            .exists());
+
+        // An empty backups directory takes the backup as pushed
+        let empty = BackupManager::with_dir(dir.path().join("empty")).unwrap();
+        let metadata = offsite.pull("mon", &empty).await.unwrap();
+        assert_eq!(metadata.id, 1);
+        assert_eq!(empty.get_backup_metadata("mon").unwrap().id, 1);
+        let source_meta = source.get_backup_metadata("mon").unwrap();
+        for file in source.backup_files(&source_meta).unwrap() {
+            assert_eq!(
+                fs::read(empty.backups_dir().join(&file)).unwrap(),
+                fs::read(source.backups_dir().join(&file)).unwrap(),
+                "{} differs",
+                file
+            );
+        }
+
+        // Pulling the same backup again is a no-op
+        offsite.pull("mon", &empty).await.unwrap();
+    }
+
+    #[test]
+    fn test_parse_target() {
+        let (config, prefix) =
+            parse_target("s3://lab-backups/talaria/nightly/", Some("eu-west-1"), None).unwrap();
+        match config {
+            CloudConfig::S3 {
+                bucket,
+                region,
+                prefix: storage_prefix,
+                ..
+            } => {
+                assert_eq!(bucket, "lab-backups");
+                assert_eq!(region, "eu-west-1");
+                assert_eq!(storage_prefix, None);
+            }
+            other => panic!("unexpected config {:?}", other),
+        }
+        assert_eq!(prefix, "talaria/nightly");
+
+        let (_, prefix) = parse_target("s3://lab-backups", Some("us-east-1"), None).unwrap();
+        assert_eq!(prefix, "");
+
+        assert!(parse_target("gs://bucket/x", None, None).is_err());
+        assert!(parse_target("s3:///x", None, None).is_err());
+    }
+
+    #[test]
+    fn test_shared_files() {
+        assert!(is_shared_file(
+            "rocksdb/shared_checksum/000009_3204827315_58213.sst"
+        ));
+        assert!(!is_shared_file("rocksdb/meta/4"));
+        assert!(!is_shared_file("chunks/private/4/MANIFEST-000012"));
+        assert!(!is_shared_file("metadata/nightly.json"));
+    }
+}
@@ -0,0 +1,126 @@
+/// Daily/weekly/monthly retention for off-site backups
+///
+/// The newest backup of each of the last `daily` days, `weekly` ISO weeks and
+/// `monthly` months is kept; a backup may satisfy several slots at once. The
+/// newest backup overall is always kept.
+use super::BackupMetadata;
+use chrono::Datelike;
+use std::collections::HashSet;
+use talaria_core::config::BackupConfig;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    pub daily: usize,
+    pub weekly: usize,
+    pub monthly: usize,
+}
+
+impl RetentionPolicy {
+    pub fn from_config(config: &BackupConfig) -> Self {
+        Self {
+            daily: config.keep_daily,
+            weekly: config.keep_weekly,
+            monthly: config.keep_monthly,
+        }
+    }
+
+    /// Names of the backups the policy keeps
+    pub fn retained(&self, backups: &[BackupMetadata]) -> HashSet<String> {
+        let mut newest_first: Vec<&BackupMetadata> = backups.iter().collect();
+        newest_first.sort_by_key(|b| std::cmp::Reverse(b.created_at));
+
+        let mut keep = HashSet::new();
+        if let Some(newest) = newest_first.first() {
+            keep.insert(newest.name.clone());
+        }
+
+        let date = |b: &BackupMetadata| b.created_at.date_naive();
+        keep_newest_per_period(&newest_first, self.daily, &mut keep, |b| {
+            let day = date(b);
+            (day.year(), day.ordinal())
+        });
+        keep_newest_per_period(&newest_first, self.weekly, &mut keep, |b| {
+            let week = date(b).iso_week();
+            (week.year(), week.week())
+        });
+        keep_newest_per_period(&newest_first, self.monthly, &mut keep, |b| {
+            let day = date(b);
+            (day.year(), day.month())
+        });
+        keep
+    }
+}
+
+/// Keep the first (newest) backup of each of the first `slots` periods
+fn keep_newest_per_period(
+    newest_first: &[&BackupMetadata],
+    slots: usize,
+    keep: &mut HashSet<String>,
+    period: impl Fn(&BackupMetadata) -> (i32, u32),
+) {
+    let mut seen = HashSet::new();
+    for backup in newest_first {
+        if seen.len() == slots && !seen.contains(&period(backup)) {
+            break;
+        }
+        if seen.insert(period(backup)) {
+            keep.insert(backup.name.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone, Utc};
+
+    fn backup(name: &str, days_ago: i64) -> BackupMetadata {
+        let now = Utc.with_ymd_and_hms(2024, 6, 30, 2, 0, 0).unwrap();
+        BackupMetadata {
+            id: 0,
+            name: name.to_string(),
+            description: None,
+            created_at: now - Duration::days(days_ago),
+            size_bytes: 0,
+            chunk_backup_id: None,
+        }
+    }
+
+    #[test]
+    fn test_retention_keeps_newest_per_period() {
+        // One nightly backup for 90 days, plus a second one today
+        let mut backups: Vec<_> = (0..90).map(|d| backup(&format!("d{}", d), d)).collect();
+        backups.push(backup("today-early", 0));
+        backups[0].created_at += Duration::hours(1);
+
+        let policy = RetentionPolicy {
+            daily: 3,
+            weekly: 2,
+            monthly: 3,
+        };
+        let keep = policy.retained(&backups);
+
+        // Days: 2024-06-30, 06-29, 06-28 (the later of today's two)
+        for name in ["d0", "d1", "d2"] {
+            assert!(keep.contains(name), "{} not kept", name);
+        }
+        assert!(!keep.contains("today-early"));
+        // Weeks: d0 for the current week; last week ends on Sunday 06-23 (d7)
+        assert!(keep.contains("d7"));
+        // Months: June (d0), May (d30 = 05-31), April (d61 = 04-30)
+        assert!(keep.contains("d30"));
+        assert!(keep.contains("d61"));
+        assert_eq!(keep.len(), 6);
+    }
+
+    #[test]
+    fn test_retention_always_keeps_newest() {
+        let policy = RetentionPolicy {
+            daily: 0,
+            weekly: 0,
+            monthly: 0,
+        };
+        let keep = policy.retained(&[backup("old", 10), backup("new", 1)]);
+        assert_eq!(keep, HashSet::from(["new".to_string()]));
+    }
+}
@@ -0,0 +1,160 @@
+/// Content-defined chunk boundaries over canonical sequence hashes
+///
+/// Packing sequences into chunks by size means one insertion shifts every
+/// later boundary in the taxon, so every later chunk changes hash between
+/// releases. Here the hashes are sorted and cut points are chosen from a
+/// rolling hash over a small window of neighbouring hashes. An insertion or
+/// removal only moves the boundaries next to it, and the chunks around it
+/// keep their hashes.
+use crate::types::SHA256Hash;
+
+/// Number of neighbouring sequence hashes the rolling hash covers
+const WINDOW: usize = 8;
+
+/// Sequence counts per chunk for content-defined cutting
+#[derive(Debug, Clone, Copy)]
+pub struct BoundaryParams {
+    /// Expected sequences per chunk; boundaries hit with probability 1/avg
+    pub avg: usize,
+    /// No boundary before this many sequences
+    pub min: usize,
+    /// Forced boundary at this many sequences
+    pub max: usize,
+}
+
+impl BoundaryParams {
+    /// Derive sequence counts from byte sizes and an estimated sequence size
+    pub fn from_sizes(
+        target_bytes: usize,
+        max_bytes: usize,
+        min_sequences: usize,
+        avg_sequence_bytes: usize,
+    ) -> Self {
+        let avg_sequence_bytes = avg_sequence_bytes.max(1);
+        let avg = (target_bytes / avg_sequence_bytes).max(1);
+        let max = (max_bytes / avg_sequence_bytes).max(avg);
+        let min = (avg / 4).max(min_sequences).min(max);
+        Self { avg, min, max }
+    }
+}
+
+/// Sort and deduplicate hashes so boundaries do not depend on input order
+pub fn canonical_order(mut hashes: Vec<SHA256Hash>) -> Vec<SHA256Hash> {
+    hashes.sort();
+    hashes.dedup();
+    hashes
+}
+
+/// Split sorted hashes into chunks at content-defined cut points
+///
+/// Returns the exclusive end index of each chunk.
+pub fn content_defined_cuts(hashes: &[SHA256Hash], params: BoundaryParams) -> Vec<usize> {
+    let mut cuts = Vec::new();
+    let mut rolling: u64 = 0;
+    let mut start = 0;
+
+    for (i, hash) in hashes.iter().enumerate() {
+        // Buzhash-style window: rotate, mix in the new hash, drop the old one
+        rolling = rolling.rotate_left(1) ^ fingerprint(hash);
+        if i >= WINDOW {
+            rolling ^= fingerprint(&hashes[i - WINDOW]).rotate_left(WINDOW as u32);
+        }
+
+        let len = i + 1 - start;
+        if len >= params.max || (len >= params.min && rolling.is_multiple_of(params.avg as u64)) {
+            cuts.push(i + 1);
+            start = i + 1;
+        }
+    }
+
+    if start < hashes.len() {
+        cuts.push(hashes.len());
+    }
+    cuts
+}
+
+fn fingerprint(hash: &SHA256Hash) -> u64 {
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&hash.as_bytes()[..8]);
+    u64::from_le_bytes(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn hashes(range: std::ops::Range<u32>) -> Vec<SHA256Hash> {
+        range
+            .map(|i| SHA256Hash::compute(format!("seq{}", i).as_bytes()))
+            .collect()
+    }
+
+    fn chunks(hashes: &[SHA256Hash], params: BoundaryParams) -> HashSet<Vec<SHA256Hash>> {
+        let mut start = 0;
+        content_defined_cuts(hashes, params)
+            .into_iter()
+            .map(|end| {
+                let chunk = hashes[start..end].to_vec();
+                start = end;
+                chunk
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_cuts_respect_min_and_max() {
+        let sorted = canonical_order(hashes(0..5000));
+        let params = BoundaryParams {
+            avg: 50,
+            min: 10,
+            max: 200,
+        };
+
+        let cuts = content_defined_cuts(&sorted, params);
+        assert_eq!(*cuts.last().unwrap(), sorted.len());
+
+        let mut start = 0;
+        for (i, end) in cuts.iter().enumerate() {
+            let len = end - start;
+            assert!(len <= params.max);
+            if i + 1 < cuts.len() {
+                assert!(len >= params.min);
+            }
+            start = *end;
+        }
+
+        // Boundaries come from the content, not from a fixed stride
+        let avg = sorted.len() as f64 / cuts.len() as f64;
+        assert!(avg > 20.0 && avg < 120.0, "average chunk length {}", avg);
+    }
+
+    #[test]
+    fn test_insertion_only_changes_neighbouring_chunks() {
+        let params = BoundaryParams {
+            avg: 50,
+            min: 10,
+            max: 500,
+        };
+        let before = canonical_order(hashes(0..5000));
+        let mut edited = before.clone();
+        edited.push(SHA256Hash::compute(b"inserted"));
+        let after = canonical_order(edited);
+
+        let old = chunks(&before, params);
+        let new = chunks(&after, params);
+        let changed = new.difference(&old).count();
+
+        assert!(old.len() > 20);
+        assert!(changed <= 2, "{} of {} chunks changed", changed, new.len());
+    }
+
+    #[test]
+    fn test_params_from_sizes() {
+        let params = BoundaryParams::from_sizes(10_000, 50_000, 3, 1000);
+        assert_eq!((params.avg, params.min, params.max), (10, 3, 50));
+
+        let tiny = BoundaryParams::from_sizes(100, 1000, 1, 1000);
+        assert_eq!((tiny.avg, tiny.min, tiny.max), (1, 1, 1));
+    }
+}
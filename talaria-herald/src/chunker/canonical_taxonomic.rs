@@ -1,18 +1,29 @@
 /// Taxonomic chunker that creates manifests referencing canonical sequences
 use crate::storage::sequence::SequenceStorage;
-use crate::types::{ChunkClassification, ChunkManifest, DatabaseSource, SHA256Hash, TaxonId};
+use crate::taxonomy::TaxonomyManager;
+use crate::types::{
+    ChunkClassification, ChunkManifest, ChunkStrategy, DatabaseSource, SHA256Hash, TaxonId,
+};
 use anyhow::Result;
 use chrono::Utc;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 use talaria_bio::sequence::Sequence;
 
+use super::boundaries::{self, BoundaryParams};
+use super::{ChunkBoundaries, TaxonomicRank};
+
+/// Estimate size based on average sequence length (1000 bytes typical)
+const AVG_SEQUENCE_SIZE: usize = 1000;
+
 /// Taxonomic chunker that works with canonical sequences
 pub struct TaxonomicChunker {
     strategy: super::ChunkingStrategy,
     pub sequence_storage: Arc<SequenceStorage>,
     database_source: DatabaseSource,
     quiet_mode: bool,
+    /// Index prefix holding the taxa and hashes of deferred content-defined cuts
+    spool: Option<String>,
 }
 
 impl TaxonomicChunker {
@@ -26,6 +37,7 @@ impl TaxonomicChunker {
             sequence_storage,
             database_source,
             quiet_mode: false,
+            spool: None,
         }
     }
 
@@ -34,6 +46,82 @@ impl TaxonomicChunker {
         self.quiet_mode = quiet;
     }
 
+    /// Defer content-defined cuts for a file chunked batch by batch
+    ///
+    /// Content-defined cuts depend on every sequence of a taxon, so with
+    /// content-defined boundaries each batch only stores its sequences and
+    /// records their taxa and hashes under `prefix` in the index column
+    /// family, and returns no manifests. `finish_spooled` then cuts each
+    /// taxon once all batches are in. Packed boundaries are cut per batch as
+    /// before. Chunkers sharing a prefix share the spool.
+    pub fn spool_content_defined(&mut self, prefix: impl Into<String>) {
+        if self.strategy.boundaries == ChunkBoundaries::ContentDefined {
+            self.spool = Some(prefix.into());
+        }
+    }
+
+    /// Drop whatever an interrupted run left in the spool
+    pub fn clear_spool(&self) -> Result<()> {
+        if let Some(prefix) = &self.spool {
+            self.sequence_storage
+                .get_rocksdb()
+                .delete_index_prefix(prefix)?;
+        }
+        Ok(())
+    }
+
+    /// Cut the spooled taxa and hand each taxon's manifests to `emit`
+    ///
+    /// Spool keys sort by taxon and then by hash, so each taxon's hashes
+    /// arrive complete and in canonical order and only one taxon is held in
+    /// memory. Special taxa rules apply within each taxon. The spool is
+    /// emptied afterwards.
+    pub fn finish_spooled(
+        &self,
+        mut emit: impl FnMut(Vec<ChunkManifest>) -> Result<()>,
+    ) -> Result<()> {
+        let Some(prefix) = &self.spool else {
+            return Ok(());
+        };
+        let taxonomy_version = self.get_taxonomy_version();
+        let sequence_version = self.get_sequence_version();
+        let mut cut = |taxon_id: TaxonId, hashes: Vec<SHA256Hash>| -> Result<()> {
+            let manifests = self.create_content_defined_manifests(
+                vec![taxon_id],
+                hashes,
+                taxonomy_version.clone(),
+                sequence_version.clone(),
+            )?;
+            emit(self.apply_special_taxa_rules(
+                manifests,
+                taxonomy_version.clone(),
+                sequence_version.clone(),
+            )?)
+        };
+
+        let rocksdb = self.sequence_storage.get_rocksdb();
+        let mut current: Option<TaxonId> = None;
+        let mut hashes = Vec::new();
+        rocksdb.for_each_index_prefix(prefix, |key, _| {
+            let (taxon, hex) = key[prefix.len()..]
+                .split_once(':')
+                .ok_or_else(|| anyhow::anyhow!("Malformed chunking spool key {}", key))?;
+            let taxon_id = TaxonId(taxon.parse()?);
+            if let Some(previous) = current.filter(|previous| *previous != taxon_id) {
+                cut(previous, std::mem::take(&mut hashes))?;
+            }
+            current = Some(taxon_id);
+            hashes.push(SHA256Hash::from_hex(hex)?);
+            Ok(())
+        })?;
+        if let Some(taxon_id) = current {
+            cut(taxon_id, hashes)?;
+        }
+
+        rocksdb.delete_index_prefix(prefix)?;
+        Ok(())
+    }
+
     /// Chunk sequences by storing them canonically and creating manifests (quiet version)
     pub fn chunk_sequences_canonical_quiet(
         &mut self,
@@ -149,19 +237,24 @@ impl TaxonomicChunker {
             // Process in smaller mini-batches for more frequent progress updates
             for mini_chunk in chunk.chunks(MINI_BATCH_SIZE) {
                 // Prepare mini-batch for parallel storage
-                let batch_data: Vec<(&str, &str, crate::types::DatabaseSource)> = mini_chunk
+                // Assigned taxa (e.g. from a taxid map) reach the representations
+                // even when the header carries none
+                let batch_data: Vec<_> = mini_chunk
                     .iter()
-                    .map(|(_, header, sequence_str, _)| {
+                    .map(|(_, header, sequence_str, taxon_id)| {
                         (
                             sequence_str.as_str(),
                             header.as_str(),
                             self.database_source.clone(),
+                            (taxon_id.0 != 0).then_some(*taxon_id),
                         )
                     })
                     .collect();
 
                 // Store mini-batch in parallel
-                let batch_results = self.sequence_storage.store_sequences_batch(batch_data)?;
+                let batch_results = self
+                    .sequence_storage
+                    .store_sequences_batch_with_taxa(batch_data)?;
 
                 // Track results
                 for ((id, _, _, taxon_id), (hash, is_new)) in
@@ -229,6 +322,26 @@ impl TaxonomicChunker {
                 &format!("Grouped into {} taxa", taxon_groups.len()),
             );
         }
+
+        // Content-defined cuts wait for the whole file; see `spool_content_defined`
+        if let Some(prefix) = &self.spool {
+            let entries: Vec<(String, Vec<u8>)> = taxon_groups
+                .iter()
+                .flat_map(|(taxon_id, hashes)| {
+                    hashes.iter().map(move |hash| {
+                        // Fixed-width taxa keep each taxon's keys together
+                        (
+                            format!("{}{:010}:{}", prefix, taxon_id.0, hash.to_hex()),
+                            Vec::new(),
+                        )
+                    })
+                })
+                .collect();
+            self.sequence_storage
+                .get_rocksdb()
+                .put_indices_batch(&entries)?;
+            return Ok(Vec::new());
+        }
         if !self.quiet_mode {
             // Show sample of taxon IDs for debugging
             let sample_taxids: Vec<String> = taxon_groups
@@ -317,7 +430,7 @@ impl TaxonomicChunker {
                 .par_iter()
                 .map(|(taxon_id, sequence_hashes)| {
                     self.create_manifests_for_group(
-                        *taxon_id,
+                        vec![*taxon_id],
                         sequence_hashes.clone(),
                         taxonomy_version.clone(),
                         sequence_version.clone(),
@@ -354,18 +467,24 @@ impl TaxonomicChunker {
     /// Create manifests for a taxonomic group
     fn create_manifests_for_group(
         &self,
-        taxon_id: TaxonId,
+        taxon_ids: Vec<TaxonId>,
         sequence_hashes: Vec<SHA256Hash>,
         taxonomy_version: SHA256Hash,
         sequence_version: SHA256Hash,
     ) -> Result<Vec<ChunkManifest>> {
+        if self.strategy.boundaries == ChunkBoundaries::ContentDefined {
+            return self.create_content_defined_manifests(
+                taxon_ids,
+                sequence_hashes,
+                taxonomy_version,
+                sequence_version,
+            );
+        }
+
         let mut manifests = Vec::new();
         let mut current_refs = Vec::new();
         let mut current_size = 0;
 
-        // Estimate size based on average sequence length (1000 bytes typical)
-        const AVG_SEQUENCE_SIZE: usize = 1000;
-
         for hash in sequence_hashes {
             let estimated_size = AVG_SEQUENCE_SIZE; // In production, load and check actual size
 
@@ -377,7 +496,7 @@ impl TaxonomicChunker {
                 // Create manifest
                 if !current_refs.is_empty() {
                     manifests.push(self.create_manifest(
-                        vec![taxon_id],
+                        taxon_ids.clone(),
                         current_refs,
                         taxonomy_version.clone(),
                         sequence_version.clone(),
@@ -396,7 +515,7 @@ impl TaxonomicChunker {
         // Create final manifest
         if !current_refs.is_empty() {
             manifests.push(self.create_manifest(
-                vec![taxon_id],
+                taxon_ids,
                 current_refs,
                 taxonomy_version,
                 sequence_version,
@@ -406,6 +525,37 @@ impl TaxonomicChunker {
         Ok(manifests)
     }
 
+    /// Create manifests for a taxonomic group with content-defined boundaries
+    fn create_content_defined_manifests(
+        &self,
+        taxon_ids: Vec<TaxonId>,
+        sequence_hashes: Vec<SHA256Hash>,
+        taxonomy_version: SHA256Hash,
+        sequence_version: SHA256Hash,
+    ) -> Result<Vec<ChunkManifest>> {
+        let sorted = boundaries::canonical_order(sequence_hashes);
+        let params = BoundaryParams::from_sizes(
+            self.strategy.target_chunk_size,
+            self.strategy.max_chunk_size,
+            self.strategy.min_sequences_per_chunk,
+            AVG_SEQUENCE_SIZE,
+        );
+
+        let mut manifests = Vec::new();
+        let mut start = 0;
+        for end in boundaries::content_defined_cuts(&sorted, params) {
+            manifests.push(self.create_manifest(
+                taxon_ids.clone(),
+                sorted[start..end].to_vec(),
+                taxonomy_version.clone(),
+                sequence_version.clone(),
+            )?);
+            start = end;
+        }
+
+        Ok(manifests)
+    }
+
     /// Create a chunk manifest
     fn create_manifest(
         &self,
@@ -447,6 +597,10 @@ impl TaxonomicChunker {
         taxonomy_version: SHA256Hash,
         sequence_version: SHA256Hash,
     ) -> Result<Vec<ChunkManifest>> {
+        if !self.strategy.special_taxa.is_empty() {
+            return self.apply_configured_taxa_rules(manifests, taxonomy_version, sequence_version);
+        }
+
         // Skip special taxa rules for very large datasets to avoid performance issues
         if manifests.len() > 1000 {
             return Ok(manifests);
@@ -541,6 +695,65 @@ impl TaxonomicChunker {
         Ok(final_manifests)
     }
 
+    /// Apply the `special_taxa` rules from the chunking strategy
+    ///
+    /// Manifests whose taxon falls under a grouping rule are merged per group
+    /// and re-split with the usual size and boundary settings. Manifests under
+    /// an `OwnChunks` rule, or under no rule, are kept as they are.
+    fn apply_configured_taxa_rules(
+        &self,
+        manifests: Vec<ChunkManifest>,
+        taxonomy_version: SHA256Hash,
+        sequence_version: SHA256Hash,
+    ) -> Result<Vec<ChunkManifest>> {
+        use talaria_core::system::paths;
+
+        let taxonomy = TaxonomyManager::load(&paths::talaria_databases_dir())
+            .ok()
+            .filter(|t| t.has_taxonomy());
+        if taxonomy.is_none() {
+            tracing::debug!("No taxonomy loaded; special taxa rules only match exact taxa");
+        }
+
+        let mut final_manifests = Vec::new();
+        let mut groups: BTreeMap<TaxonId, Vec<ChunkManifest>> = BTreeMap::new();
+
+        for manifest in manifests {
+            let key = manifest
+                .taxon_ids
+                .first()
+                .and_then(|taxon| special_group_key(&self.strategy, *taxon, taxonomy.as_ref()));
+            match key {
+                Some(key) => groups.entry(key).or_default().push(manifest),
+                None => final_manifests.push(manifest),
+            }
+        }
+
+        for (_, group) in groups {
+            if group.len() == 1 {
+                final_manifests.extend(group);
+                continue;
+            }
+
+            let mut taxon_ids: Vec<TaxonId> = group
+                .iter()
+                .flat_map(|m| m.taxon_ids.iter().copied())
+                .collect();
+            taxon_ids.sort();
+            taxon_ids.dedup();
+            let refs: Vec<SHA256Hash> = group.into_iter().flat_map(|m| m.sequence_refs).collect();
+
+            final_manifests.extend(self.create_manifests_for_group(
+                taxon_ids,
+                refs,
+                taxonomy_version.clone(),
+                sequence_version.clone(),
+            )?);
+        }
+
+        Ok(final_manifests)
+    }
+
     fn get_taxonomy_version(&self) -> SHA256Hash {
         // Get actual taxonomy version from taxonomy manager
         use talaria_core::system::paths;
@@ -565,9 +778,202 @@ impl TaxonomicChunker {
     }
 }
 
+/// Group a taxon's manifests are merged into under the configured rules
+///
+/// The rule on the closest ancestor (or the taxon itself) wins, so a model
+/// organism can keep its own chunks inside a clade that is grouped by family.
+/// Returns `None` when the manifest should be left alone.
+fn special_group_key(
+    strategy: &super::ChunkingStrategy,
+    taxon: TaxonId,
+    taxonomy: Option<&TaxonomyManager>,
+) -> Option<TaxonId> {
+    let lineage: Vec<TaxonId> = taxonomy
+        .and_then(|t| t.get_lineage(&taxon).ok())
+        .map(|nodes| nodes.into_iter().rev().map(|n| n.taxon_id).collect())
+        .filter(|l: &Vec<TaxonId>| !l.is_empty())
+        .unwrap_or_else(|| vec![taxon]);
+
+    let rule = lineage.iter().find_map(|t| strategy.special_taxon(*t))?;
+
+    match &rule.strategy {
+        ChunkStrategy::OwnChunks => None,
+        ChunkStrategy::GroupWithSiblings => Some(
+            taxonomy
+                .and_then(|t| t.get_parent(taxon))
+                .unwrap_or(rule.taxon_id),
+        ),
+        ChunkStrategy::GroupAtLevel(_) => {
+            let ancestor = match (taxonomy, rule.strategy.rank()) {
+                (Some(t), Some(TaxonomicRank::Domain)) => t
+                    .get_ancestor_at_rank(taxon, "domain")
+                    .or_else(|| t.get_ancestor_at_rank(taxon, "superkingdom")),
+                (Some(t), Some(rank)) => t.get_ancestor_at_rank(taxon, rank.as_str()),
+                _ => None,
+            };
+            // Taxa above the grouping rank share the rule taxon's group
+            Some(ancestor.unwrap_or(rule.taxon_id))
+        }
+    }
+}
+
 // Benefits of this approach:
 // 1. True deduplication - sequences stored once across all databases
 // 2. Manifests are lightweight - just references to sequences
 // 3. Cross-database efficiency - same sequence in UniProt and NCBI stored once
 // 4. Maintains taxonomic organization for efficient access
 // 5. Special taxa handling ensures important organisms are easily accessible
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::SpecialTaxon;
+    use std::collections::HashSet;
+
+    fn strategy_with(rules: Vec<(u32, ChunkStrategy)>) -> super::super::ChunkingStrategy {
+        let mut strategy = super::super::ChunkingStrategy::default();
+        for (taxon_id, rule) in rules {
+            strategy.set_special_taxon(SpecialTaxon {
+                taxon_id: TaxonId(taxon_id),
+                name: String::new(),
+                strategy: rule,
+            });
+        }
+        strategy
+    }
+
+    #[test]
+    fn test_special_group_key_without_taxonomy() {
+        let strategy = strategy_with(vec![
+            (9606, ChunkStrategy::OwnChunks),
+            (10239, ChunkStrategy::group_at(TaxonomicRank::Family)),
+        ]);
+
+        assert_eq!(special_group_key(&strategy, TaxonId(9606), None), None);
+        assert_eq!(
+            special_group_key(&strategy, TaxonId(10239), None),
+            Some(TaxonId(10239))
+        );
+        // Descendants need a taxonomy to be matched
+        assert_eq!(special_group_key(&strategy, TaxonId(11676), None), None);
+    }
+
+    #[test]
+    fn test_strategy_from_config() {
+        let mut config = talaria_core::config::ChunkingConfig {
+            max_chunk_size: 64 * 1024 * 1024,
+            boundaries: Some("content-defined".to_string()),
+            ..Default::default()
+        };
+        config
+            .special_taxa
+            .push(talaria_core::config::SpecialTaxonConfig {
+                taxon_id: 10239,
+                name: "Viruses".to_string(),
+                strategy: "group-at-family".to_string(),
+            });
+
+        let strategy = super::super::ChunkingStrategy::from_config(&config).unwrap();
+        assert_eq!(strategy.max_chunk_size, 64 * 1024 * 1024);
+        assert_eq!(strategy.boundaries, ChunkBoundaries::ContentDefined);
+        let rule = strategy.special_taxon(TaxonId(10239)).unwrap();
+        assert_eq!(rule.strategy.rank(), Some(TaxonomicRank::Family));
+        assert_eq!(rule.strategy.to_string(), "group-at-family");
+
+        config.special_taxa[0].strategy = "group-at-clade".to_string();
+        assert!(super::super::ChunkingStrategy::from_config(&config).is_err());
+
+        config.special_taxa.clear();
+        config.max_chunk_size = 1024;
+        assert!(super::super::ChunkingStrategy::from_config(&config).is_err());
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_boundaries_from_env() {
+        use super::super::ChunkingStrategy;
+        let config = talaria_core::config::ChunkingConfig::default();
+
+        std::env::set_var("TALARIA_CHUNK_BOUNDARIES", "content-defined");
+        assert_eq!(
+            ChunkingStrategy::default().boundaries,
+            ChunkBoundaries::Packed
+        );
+        assert_eq!(
+            ChunkingStrategy::from_config(&config).unwrap().boundaries,
+            ChunkBoundaries::ContentDefined
+        );
+
+        std::env::set_var("TALARIA_CHUNK_BOUNDARIES", "contentdefined");
+        assert!(ChunkingStrategy::from_config(&config).is_err());
+
+        std::env::remove_var("TALARIA_CHUNK_BOUNDARIES");
+        assert_eq!(
+            ChunkingStrategy::from_config(&config).unwrap().boundaries,
+            ChunkBoundaries::Packed
+        );
+    }
+
+    /// Distinct E. coli sequences numbered by `ids`
+    fn sequences(ids: impl Iterator<Item = u32>) -> Vec<Sequence> {
+        const RESIDUES: &[u8] = b"ACDEFGHIKLMNPQRSTVWY";
+        ids.map(|i| {
+            let mut sequence = b"MKV".to_vec();
+            sequence.extend((0..4).map(|k| RESIDUES[(i / 20u32.pow(k)) as usize % 20]));
+            Sequence {
+                id: format!("SEQ_{:05}", i),
+                description: None,
+                sequence,
+                taxon_id: Some(562),
+                taxonomy_sources: Default::default(),
+            }
+        })
+        .collect()
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_content_defined_chunks_survive_an_insertion() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        std::env::set_var("TALARIA_HOME", temp_dir.path());
+        let storage = Arc::new(SequenceStorage::new(&temp_dir.path().join("sequences")).unwrap());
+
+        // About 50 sequences per chunk at the estimated sequence size
+        let mut strategy = super::super::ChunkingStrategy::default();
+        strategy.boundaries = ChunkBoundaries::ContentDefined;
+        strategy.target_chunk_size = 50 * AVG_SEQUENCE_SIZE;
+        strategy.max_chunk_size = 500 * AVG_SEQUENCE_SIZE;
+
+        let chunk = |sequences: Vec<Sequence>| -> HashSet<Vec<SHA256Hash>> {
+            let mut chunker = TaxonomicChunker::new(
+                strategy.clone(),
+                storage.clone(),
+                DatabaseSource::Custom("test_cdc".to_string()),
+            );
+            chunker
+                .chunk_sequences_canonical_quiet_final(sequences, true)
+                .unwrap()
+                .into_iter()
+                .map(|manifest| {
+                    let mut refs = manifest.sequence_refs;
+                    refs.sort();
+                    refs
+                })
+                .collect()
+        };
+
+        let before = chunk(sequences(0..2000));
+        assert!(before.len() > 10, "only {} chunks", before.len());
+
+        // Input order does not matter
+        assert_eq!(chunk(sequences((0..2000).rev())), before);
+
+        // One new sequence only changes the chunk it lands in and its neighbour
+        let after = chunk(sequences(0..2001));
+        let changed = after.difference(&before).count();
+        assert!((1..=2).contains(&changed), "{} chunks changed", changed);
+        assert!(before.difference(&after).count() <= 2);
+
+        std::env::remove_var("TALARIA_HOME");
+    }
+}
@@ -84,24 +84,29 @@ impl TaxonomicRank {
 
     /// Create from string rank name
     pub fn from_string(s: &str) -> Self {
+        Self::parse(s).unwrap_or(Self::Species) // Default to species if unknown
+    }
+
+    /// Parse a rank name, returning `None` for unknown ranks
+    pub fn parse(s: &str) -> Option<Self> {
         match s.to_lowercase().as_str() {
-            "domain" => Self::Domain,
-            "kingdom" => Self::Kingdom,
-            "phylum" => Self::Phylum,
-            "class" => Self::Class,
-            "order" => Self::Order,
-            "family" => Self::Family,
-            "genus" => Self::Genus,
-            "species" => Self::Species,
-            "subspecies" | "strain" => Self::Subspecies,
-            _ => Self::Species, // Default to species if unknown
+            "domain" | "superkingdom" => Some(Self::Domain),
+            "kingdom" => Some(Self::Kingdom),
+            "phylum" => Some(Self::Phylum),
+            "class" => Some(Self::Class),
+            "order" => Some(Self::Order),
+            "family" => Some(Self::Family),
+            "genus" => Some(Self::Genus),
+            "species" => Some(Self::Species),
+            "subspecies" | "strain" => Some(Self::Subspecies),
+            _ => None,
         }
     }
 }
 
 /// Hierarchical taxonomic chunker
 pub struct HierarchicalTaxonomicChunker {
-    _strategy: super::ChunkingStrategy,
+    strategy: super::ChunkingStrategy,
     sequence_storage: SequenceStorage,
     database_source: DatabaseSource,
     taxonomy_manager: Option<TaxonomyManager>,
@@ -115,7 +120,7 @@ impl HierarchicalTaxonomicChunker {
         taxonomy_manager: Option<TaxonomyManager>,
     ) -> Self {
         Self {
-            _strategy: strategy,
+            strategy,
             sequence_storage,
             database_source,
             taxonomy_manager,
@@ -139,7 +144,7 @@ impl HierarchicalTaxonomicChunker {
         // Process in batches for performance
         const BATCH_SIZE: usize = 10000;
         for chunk in sequences.chunks(BATCH_SIZE) {
-            let batch_data: Vec<(String, String, DatabaseSource)> = chunk
+            let batch_data: Vec<(String, String, DatabaseSource, Option<TaxonId>)> = chunk
                 .iter()
                 .map(|seq| {
                     let header = format!(
@@ -151,16 +156,25 @@ impl HierarchicalTaxonomicChunker {
                             .unwrap_or_default()
                     );
                     let sequence_str = String::from_utf8_lossy(&seq.sequence).to_string();
-                    (sequence_str, header, self.database_source.clone())
+                    (
+                        sequence_str,
+                        header,
+                        self.database_source.clone(),
+                        seq.taxon_id.map(TaxonId),
+                    )
                 })
                 .collect();
 
             // Store batch
             let batch_results: Vec<SHA256Hash> = batch_data
                 .iter()
-                .map(|(seq, header, source)| {
-                    self.sequence_storage
-                        .store_sequence(&seq, &header, source.clone())
+                .map(|(seq, header, source, taxon_id)| {
+                    self.sequence_storage.store_sequence_with_taxon(
+                        &seq,
+                        &header,
+                        source.clone(),
+                        *taxon_id,
+                    )
                 })
                 .collect::<Result<Vec<_>>>()?;
 
@@ -277,9 +291,10 @@ impl HierarchicalTaxonomicChunker {
         let mut current_refs = Vec::new();
         let mut current_size = 0;
 
-        let min_size = rank.min_chunk_size();
-        let target_size = rank.target_chunk_size();
-        let max_size = rank.max_chunk_size();
+        // Rank thresholds keep the built-in 1:2:5 ratio around any override
+        let min_size = self.strategy.rank_min_chunk_size(rank);
+        let target_size = min_size * 2;
+        let max_size = min_size * 5;
 
         // Estimate size based on average sequence length
         const AVG_SEQUENCE_SIZE: usize = 1000;
@@ -469,6 +484,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_rank_min_size_overrides() {
+        let mut strategy = super::super::ChunkingStrategy::default();
+        strategy
+            .rank_min_sizes
+            .insert("species".to_string(), 2_000_000);
+
+        assert_eq!(
+            strategy.rank_min_chunk_size(TaxonomicRank::Species),
+            2_000_000
+        );
+        assert_eq!(
+            strategy.rank_min_chunk_size(TaxonomicRank::Genus),
+            TaxonomicRank::Genus.min_chunk_size()
+        );
+        assert_eq!(
+            TaxonomicRank::parse("superkingdom"),
+            Some(TaxonomicRank::Domain)
+        );
+        assert_eq!(TaxonomicRank::parse("clade"), None);
+    }
+
     #[test]
     #[serial_test::serial]
     #[ignore] // Requires taxonomy data: talaria database download ncbi/taxonomy
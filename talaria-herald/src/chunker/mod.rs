@@ -1,4 +1,5 @@
 // Chunker implementations
+pub mod boundaries;
 pub mod canonical_taxonomic;
 pub mod hierarchical_taxonomic;
 
@@ -7,4 +8,4 @@ pub use canonical_taxonomic::TaxonomicChunker;
 pub use hierarchical_taxonomic::{HierarchicalTaxonomicChunker, TaxonomicRank};
 
 // Re-export ChunkingStrategy from types
-pub use crate::types::ChunkingStrategy;
+pub use crate::types::{ChunkBoundaries, ChunkingStrategy};
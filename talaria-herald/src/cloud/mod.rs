@@ -175,8 +175,9 @@ impl CloudSyncManager {
         chunk_hash: &SHA256Hash,
         direction: SyncDirection,
     ) -> Result<()> {
-        let chunk_key = format!("{}/chunks/{}", self.remote_prefix, chunk_hash.to_hex());
-        let local_chunk_path = self.local_path.join("chunks").join(chunk_hash.to_hex());
+        let relative = crate::remote::remote_chunk_key(chunk_hash);
+        let chunk_key = format!("{}/{}", self.remote_prefix, relative);
+        let local_chunk_path = self.local_path.join(relative);
 
         match direction {
             SyncDirection::Upload => {
@@ -233,16 +234,15 @@ impl CloudSyncManager {
 
     /// Get sync status
     pub async fn get_status(&self) -> Result<SyncStatus> {
-        let local_chunks = self.count_local_chunks()?;
         let cloud_objects = self.storage.list_objects(Some(&self.remote_prefix)).await?;
 
-        // Extract cloud chunk hashes
+        // Chunks are stored as "prefix/chunks/ab/cdef..." (see `remote_chunk_key`)
         let cloud_chunk_hashes: std::collections::HashSet<String> = cloud_objects
             .iter()
-            .filter(|o| o.key.contains("/chunks/"))
             .filter_map(|o| {
-                // Extract hash from path like "prefix/chunks/ab/cd/abcd..."
-                o.key.split('/').next_back().map(|s| s.to_string())
+                let (_, relative) = o.key.split_once("chunks/")?;
+                let (shard, rest) = relative.split_once('/')?;
+                Some(format!("{}{}", shard, rest))
             })
             .collect();
 
@@ -250,6 +250,7 @@ impl CloudSyncManager {
 
         // Get local chunk hashes
         let local_chunk_hashes = self.get_local_chunk_hashes()?;
+        let local_chunks = local_chunk_hashes.len();
 
         // Calculate pending uploads (local chunks not in cloud)
         let pending_uploads = local_chunk_hashes
@@ -280,24 +281,17 @@ impl CloudSyncManager {
             return Ok(hashes);
         }
 
-        // Walk through chunk directories
+        // Prefix directories like "ab" hold files named by the rest of the hash
         for entry in std::fs::read_dir(&chunks_dir)? {
             let entry = entry?;
-            if entry.path().is_dir() {
-                // This is a prefix directory like "ab"
-                for chunk_entry in std::fs::read_dir(entry.path())? {
-                    let chunk_entry = chunk_entry?;
-                    if chunk_entry.path().is_dir() {
-                        // This is a hash directory like "cd"
-                        for file_entry in std::fs::read_dir(chunk_entry.path())? {
-                            let file_entry = file_entry?;
-                            if let Some(name) = file_entry.file_name().to_str() {
-                                // Remove any extension to get the hash
-                                let hash = name.split('.').next().unwrap_or(name);
-                                hashes.insert(hash.to_string());
-                            }
-                        }
-                    }
+            if !entry.path().is_dir() {
+                continue;
+            }
+            let shard = entry.file_name().to_string_lossy().to_string();
+            for chunk_entry in std::fs::read_dir(entry.path())? {
+                let name = chunk_entry?.file_name().to_string_lossy().to_string();
+                if !name.ends_with(".tmp") {
+                    hashes.insert(format!("{}{}", shard, name));
                 }
             }
         }
@@ -305,20 +299,6 @@ impl CloudSyncManager {
         Ok(hashes)
     }
 
-    fn count_local_chunks(&self) -> Result<usize> {
-        let chunks_dir = self.local_path.join("chunks");
-        if !chunks_dir.exists() {
-            return Ok(0);
-        }
-
-        let count = std::fs::read_dir(chunks_dir)?
-            .filter_map(|entry| entry.ok())
-            .filter(|entry| entry.path().is_file())
-            .count();
-
-        Ok(count)
-    }
-
     fn get_last_sync_time(&self) -> Result<Option<chrono::DateTime<chrono::Utc>>> {
         let sync_file = self.local_path.join(".last_sync");
         if !sync_file.exists() {
@@ -0,0 +1,1097 @@
+/// Portable, verifiable database bundles
+///
+/// A bundle carries one database version to another repository as a single
+/// file: the version manifest, only the chunk manifests and canonical
+/// sequences that version references, the installed taxonomy tree, an
+/// optional reduction profile and a Merkle proof for every chunk. It is a
+/// zstd-compressed tar whose first entry, `bundle.json`, describes the rest,
+/// so it can be inspected with standard tools before it is imported.
+///
+/// Everything is verified before anything is written to the target: chunks
+/// against their content hash and, through their proof, the manifest's chunk
+/// root; all other entries, including the sequences and headers bundled with
+/// each chunk, against the hashes listed in `bundle.json`. Verified entries are
+/// staged next to the target repository and only stored once the whole bundle
+/// has been checked. Chunks and sequences the target already holds are
+/// skipped.
+use crate::database::DatabaseManager;
+use crate::operations::{AccessionIndex, ReductionManifest};
+use crate::types::{
+    ChunkManifest, MerkleProof, Position, ProofStep, SHA256Hash, TemporalDeltaChunk,
+    TemporalManifest,
+};
+use crate::verification::MerkleDAG;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use talaria_core::system::paths;
+use talaria_core::types::DatabaseReference;
+use talaria_storage::types::{CanonicalSequence, SequenceRepresentations};
+
+/// Bundle format version, bumped on incompatible changes
+pub const BUNDLE_FORMAT_VERSION: u32 = 1;
+
+/// File extension of bundles
+pub const BUNDLE_EXTENSION: &str = "tar.zst";
+
+const HEADER_ENTRY: &str = "bundle.json";
+const MANIFEST_ENTRY: &str = "manifest.bin";
+const PROOFS_ENTRY: &str = "proofs.msgpack";
+const CHUNKS_DIR: &str = "chunks/";
+const SEQUENCES_DIR: &str = "sequences/";
+const TAXONOMY_DIR: &str = "taxonomy/";
+const DELTAS_DIR: &str = "deltas/";
+const PROFILE_ENTRY: &str = "profile/reduction.msgpack";
+const ACCESSION_INDEX_ENTRY: &str = "profile/accessions.msgpack";
+
+/// Description of a bundle, stored as its first entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleHeader {
+    pub format_version: u32,
+    pub created_at: DateTime<Utc>,
+    pub talaria_version: String,
+    pub source: String,
+    pub dataset: String,
+    /// Resolved version timestamp
+    pub version: String,
+    pub profile: Option<String>,
+    /// Taxonomy version whose tree is included, if any
+    pub taxonomy_version: Option<String>,
+    /// SHA-256 of the manifest entry
+    pub manifest_hash: String,
+    /// Root of the manifest's chunk Merkle tree; compare with the source
+    pub chunk_root: String,
+    pub chunk_count: usize,
+    pub sequence_count: usize,
+    /// SHA-256 of every entry besides the manifest and the chunks, by entry
+    /// path; chunks are named by their hash and proven against `chunk_root`
+    pub files: BTreeMap<String, String>,
+}
+
+impl BundleHeader {
+    /// `source/dataset@version[:profile]` of the bundled state
+    pub fn label(&self) -> String {
+        let mut label = format!("{}/{}@{}", self.source, self.dataset, self.version);
+        if let Some(profile) = &self.profile {
+            label.push(':');
+            label.push_str(profile);
+        }
+        label
+    }
+}
+
+/// Settings for `create_bundle`
+#[derive(Debug, Clone)]
+pub struct BundleOptions {
+    /// Include the installed taxonomy tree
+    pub include_taxonomy: bool,
+}
+
+impl Default for BundleOptions {
+    fn default() -> Self {
+        Self {
+            include_taxonomy: true,
+        }
+    }
+}
+
+/// Outcome of verifying or importing a bundle
+#[derive(Debug, Clone)]
+pub struct BundleImportReport {
+    pub header: BundleHeader,
+    /// Chunks written (or, when only verifying, checked)
+    pub chunks_imported: usize,
+    /// Chunks the target already held
+    pub chunks_skipped: usize,
+    pub sequences_imported: usize,
+    pub sequences_skipped: usize,
+    pub delta_chunks: usize,
+    /// Taxonomy tree installed because the target lacked that version
+    pub taxonomy_installed: bool,
+    /// Version became the target's `current` (it had none for this database)
+    pub became_current: bool,
+}
+
+/// Canonical sequence with its headers, as bundled
+#[derive(Serialize, Deserialize)]
+struct BundledSequence {
+    canonical: CanonicalSequence,
+    representations: SequenceRepresentations,
+}
+
+/// Entry that is listed by hash in the header
+enum FileSource {
+    Bytes(Vec<u8>),
+    Path(PathBuf),
+    DeltaChunk(SHA256Hash),
+}
+
+/// Write a bundle of `reference` (`source/dataset[@version][:profile]`)
+pub fn create_bundle(
+    manager: &DatabaseManager,
+    reference: &str,
+    output: &Path,
+    options: &BundleOptions,
+) -> Result<BundleHeader> {
+    let db_ref = DatabaseReference::parse(reference)?;
+    let (source, dataset) = (db_ref.source.as_str(), db_ref.dataset.as_str());
+    let version = manager
+        .resolve_version_reference(source, dataset, db_ref.version_or_default())
+        .with_context(|| format!("Failed to resolve {}", reference))?;
+    let manifest = manager.get_version_manifest(source, dataset, &version)?;
+    let storage = &manager.get_repository().storage;
+
+    let manifest_bytes = bincode::serialize(&manifest)?;
+    let proofs = chunk_proofs(&manifest.chunk_index);
+    let chunk_root = proofs
+        .first()
+        .map(|proof| proof.root_hash)
+        .ok_or_else(|| anyhow::anyhow!("{} has no chunks to bundle", reference))?;
+
+    // Entries listed by hash, in the order they are written
+    let mut files: Vec<(String, FileSource)> = Vec::new();
+    if let Some(profile) = &db_ref.profile {
+        let reduction = storage
+            .get_database_reduction_by_profile(source, dataset, &version, profile)?
+            .with_context(|| format!("Reduction profile '{}' not found", profile))?;
+        files.push((
+            PROFILE_ENTRY.to_string(),
+            FileSource::Bytes(rmp_serde::to_vec(&reduction)?),
+        ));
+        if let Some(index) = storage.get_accession_index(source, dataset, &version, profile)? {
+            files.push((
+                ACCESSION_INDEX_ENTRY.to_string(),
+                FileSource::Bytes(rmp_serde::to_vec(&index.load()?)?),
+            ));
+        }
+        for delta in &reduction.delta_chunks {
+            files.push((
+                format!("{}{}", DELTAS_DIR, delta.chunk_hash.to_hex()),
+                FileSource::DeltaChunk(delta.chunk_hash),
+            ));
+        }
+    }
+
+    let taxonomy_version = if options.include_taxonomy {
+        installed_taxonomy_tree(&mut files)?
+    } else {
+        None
+    };
+
+    let proofs_bytes = rmp_serde::to_vec(&proofs)?;
+    let mut file_hashes = BTreeMap::new();
+    file_hashes.insert(
+        PROOFS_ENTRY.to_string(),
+        SHA256Hash::compute(&proofs_bytes).to_hex(),
+    );
+    // The header listing the sequences entries' hashes comes first, so chunks
+    // and their sequences are hashed as they are spooled and copied after it
+    let spool_dir = output
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let mut spool = tar::Builder::new(BufWriter::new(tempfile::tempfile_in(spool_dir)?));
+    for_each_chunk_entry(storage, &manifest, |hash, data, sequences| {
+        append_entry(
+            &mut spool,
+            &format!("{}{}", CHUNKS_DIR, hash.to_hex()),
+            data,
+        )?;
+        if let Some((path, data)) = sequences {
+            file_hashes.insert(path.clone(), SHA256Hash::compute(&data).to_hex());
+            append_entry(&mut spool, &path, &data)?;
+        }
+        Ok(())
+    })?;
+    let mut spooled = spool
+        .into_inner()?
+        .into_inner()
+        .map_err(|e| e.into_error())?;
+    spooled.seek(SeekFrom::Start(0))?;
+    for (path, file) in &files {
+        let hash = match file {
+            FileSource::Bytes(data) => SHA256Hash::compute(data),
+            FileSource::Path(path) => hash_file(path)?,
+            FileSource::DeltaChunk(hash) => SHA256Hash::compute(&storage.get_chunk(hash)?),
+        };
+        file_hashes.insert(path.clone(), hash.to_hex());
+    }
+
+    let header = BundleHeader {
+        format_version: BUNDLE_FORMAT_VERSION,
+        created_at: Utc::now(),
+        talaria_version: env!("CARGO_PKG_VERSION").to_string(),
+        source: source.to_string(),
+        dataset: dataset.to_string(),
+        version: version.clone(),
+        profile: db_ref.profile.clone(),
+        taxonomy_version,
+        manifest_hash: SHA256Hash::compute(&manifest_bytes).to_hex(),
+        chunk_root: chunk_root.to_hex(),
+        chunk_count: manifest.chunk_index.len(),
+        sequence_count: manifest.chunk_index.iter().map(|c| c.sequence_count).sum(),
+        files: file_hashes,
+    };
+
+    let file = File::create(output)
+        .with_context(|| format!("Failed to create bundle {}", output.display()))?;
+    let encoder = zstd::stream::write::Encoder::new(BufWriter::new(file), 3)?;
+    let mut builder = tar::Builder::new(encoder);
+
+    append_entry(
+        &mut builder,
+        HEADER_ENTRY,
+        &serde_json::to_vec_pretty(&header)?,
+    )?;
+    append_entry(&mut builder, MANIFEST_ENTRY, &manifest_bytes)?;
+    append_entry(&mut builder, PROOFS_ENTRY, &proofs_bytes)?;
+
+    for (path, file) in &files {
+        match file {
+            FileSource::Bytes(data) => append_entry(&mut builder, path, data)?,
+            FileSource::Path(file_path) => {
+                builder.append_file(path, &mut File::open(file_path)?)?;
+            }
+            FileSource::DeltaChunk(hash) => {
+                append_entry(&mut builder, path, &storage.get_chunk(hash)?)?;
+            }
+        }
+    }
+
+    for entry in tar::Archive::new(BufReader::new(spooled)).entries()? {
+        let mut entry = entry?;
+        let header = entry.header().clone();
+        builder.append(&header, &mut entry)?;
+    }
+
+    builder.into_inner()?.finish()?.flush()?;
+    Ok(header)
+}
+
+/// Visit each chunk with the sequences entry it introduces, if any
+fn for_each_chunk_entry(
+    storage: &crate::HeraldStorage,
+    manifest: &TemporalManifest,
+    mut visit: impl FnMut(&SHA256Hash, &[u8], Option<(String, Vec<u8>)>) -> Result<()>,
+) -> Result<()> {
+    let mut written = HashSet::new();
+    for entry in &manifest.chunk_index {
+        let data = storage.get_chunk(&entry.hash)?;
+        let chunk = parse_chunk_manifest(&data)
+            .with_context(|| format!("Chunk {} is not a chunk manifest", entry.hash))?;
+
+        let mut sequences = Vec::new();
+        for hash in &chunk.sequence_refs {
+            if written.insert(*hash) {
+                sequences.push(BundledSequence {
+                    canonical: storage.sequence_storage.load_canonical(hash)?,
+                    representations: storage.sequence_storage.load_representations(hash)?,
+                });
+            }
+        }
+        let sequences = match sequences.is_empty() {
+            true => None,
+            false => Some((
+                format!("{}{}", SEQUENCES_DIR, entry.hash.to_hex()),
+                rmp_serde::to_vec(&sequences)?,
+            )),
+        };
+        visit(&entry.hash, &data, sequences)?;
+    }
+    Ok(())
+}
+
+/// Read the header of a bundle without reading the rest
+pub fn read_bundle_header(path: &Path) -> Result<BundleHeader> {
+    let mut archive = open_archive(path)?;
+    let mut entries = archive.entries()?;
+    let mut entry = entries
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("{} is empty", path.display()))??;
+    if entry.path()?.to_string_lossy() != HEADER_ENTRY {
+        anyhow::bail!("{} is not a Talaria bundle", path.display());
+    }
+    let mut data = Vec::new();
+    entry.read_to_end(&mut data)?;
+    parse_header(&data)
+}
+
+/// Verify every entry of a bundle without touching any repository
+///
+/// `expected_root` pins the chunk root obtained from the source out of band.
+pub fn verify_bundle(
+    path: &Path,
+    expected_root: Option<&SHA256Hash>,
+) -> Result<BundleImportReport> {
+    process_bundle(path, None, expected_root)
+}
+
+/// Verify a bundle and merge it into the repository of `manager`
+///
+/// Nothing is written to the repository until every entry has been
+/// verified; a bundle that fails a check leaves the target untouched.
+pub fn import_bundle(
+    manager: &DatabaseManager,
+    path: &Path,
+    expected_root: Option<&SHA256Hash>,
+) -> Result<BundleImportReport> {
+    process_bundle(path, Some(manager), expected_root)
+}
+
+fn process_bundle(
+    path: &Path,
+    target: Option<&DatabaseManager>,
+    expected_root: Option<&SHA256Hash>,
+) -> Result<BundleImportReport> {
+    let storage = target.map(|manager| &manager.get_repository().storage);
+    let mut archive = open_archive(path)?;
+
+    let mut header: Option<BundleHeader> = None;
+    let mut manifest: Option<TemporalManifest> = None;
+    let mut manifest_bytes = Vec::new();
+    let mut chunk_root = SHA256Hash::default();
+    let mut proofs: HashMap<SHA256Hash, MerkleProof> = HashMap::new();
+    let mut seen_chunks = HashSet::new();
+    let mut required_sequences = HashSet::new();
+    let mut seen_sequences = HashSet::new();
+    let mut seen_files = HashSet::new();
+    let mut seen_deltas = HashSet::new();
+    let mut reduction: Option<ReductionManifest> = None;
+    let mut accession_index: Option<AccessionIndex> = None;
+    let mut taxonomy_staging: Option<PathBuf> = None;
+    // Verified entries to store once the whole bundle has been checked
+    let staging = storage
+        .map(|storage| StagingDir::create(&storage.base_path))
+        .transpose()?;
+    let mut staged: Vec<(String, PathBuf)> = Vec::new();
+    let mut stage = |name: &str, data: &[u8]| -> Result<()> {
+        if let Some(staging) = &staging {
+            let path = staging.0.join(staged.len().to_string());
+            fs::write(&path, data)?;
+            staged.push((name.to_string(), path));
+        }
+        Ok(())
+    };
+
+    let (mut chunks_imported, mut chunks_skipped) = (0, 0);
+    let (mut sequences_imported, mut sequences_skipped) = (0, 0);
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let name = entry.path()?.to_string_lossy().into_owned();
+        let mut data = Vec::new();
+        entry.read_to_end(&mut data)?;
+
+        let Some(head) = &header else {
+            if name != HEADER_ENTRY {
+                anyhow::bail!("{} is not a Talaria bundle", path.display());
+            }
+            let parsed = parse_header(&data)?;
+            if let (Some(_), Some(version)) = (target, &parsed.taxonomy_version) {
+                if !paths::talaria_taxonomy_version_dir(version)
+                    .join("tree")
+                    .exists()
+                {
+                    let staging = paths::talaria_taxonomy_versions_dir()
+                        .join(format!(".{}.importing", version));
+                    if staging.exists() {
+                        fs::remove_dir_all(&staging)?;
+                    }
+                    taxonomy_staging = Some(staging);
+                }
+            }
+            header = Some(parsed);
+            continue;
+        };
+
+        if name == MANIFEST_ENTRY {
+            if SHA256Hash::compute(&data).to_hex() != head.manifest_hash {
+                anyhow::bail!("Manifest does not match the bundle header");
+            }
+            let parsed: TemporalManifest = bincode::deserialize(&data)?;
+            chunk_root = verify_chunk_root(&parsed, head, expected_root)?;
+            manifest = Some(parsed);
+            manifest_bytes = data;
+            continue;
+        }
+
+        if manifest.is_none() {
+            anyhow::bail!("Entry {} comes before the manifest", name);
+        }
+
+        // Everything but chunks is listed by hash in the header
+        if !name.starts_with(CHUNKS_DIR) {
+            let expected = head
+                .files
+                .get(&name)
+                .ok_or_else(|| anyhow::anyhow!("Unexpected entry {} in bundle", name))?;
+            if &SHA256Hash::compute(&data).to_hex() != expected {
+                anyhow::bail!("Entry {} does not match the bundle header", name);
+            }
+            seen_files.insert(name.clone());
+        }
+
+        if name == PROOFS_ENTRY {
+            let list: Vec<MerkleProof> = rmp_serde::from_slice(&data)?;
+            proofs = list.into_iter().map(|p| (p.leaf_hash, p)).collect();
+        } else if let Some(hex) = name.strip_prefix(CHUNKS_DIR) {
+            let hash = parse_hash(hex)?;
+            if SHA256Hash::compute(&data) != hash {
+                anyhow::bail!("Chunk {} does not match its hash", hex);
+            }
+            let proof = proofs
+                .get(&hash)
+                .ok_or_else(|| anyhow::anyhow!("No Merkle proof for chunk {}", hex))?;
+            if proof.root_hash != chunk_root || !MerkleDAG::verify_proof(proof, &data) {
+                anyhow::bail!(
+                    "Merkle proof of chunk {} does not lead to the chunk root",
+                    hex
+                );
+            }
+            let chunk = parse_chunk_manifest(&data)?;
+            required_sequences.extend(chunk.sequence_refs.iter().copied());
+            seen_chunks.insert(hash);
+
+            match storage {
+                Some(storage) if storage.has_chunk(&hash) => chunks_skipped += 1,
+                _ => {
+                    stage(&name, &data)?;
+                    chunks_imported += 1;
+                }
+            }
+        } else if name.starts_with(SEQUENCES_DIR) {
+            let sequences: Vec<BundledSequence> = rmp_serde::from_slice(&data)?;
+            for bundled in &sequences {
+                let hash = bundled.canonical.sequence_hash;
+                if SHA256Hash::compute(&bundled.canonical.sequence) != hash {
+                    anyhow::bail!("Sequence {} does not match its hash", hash);
+                }
+                if !required_sequences.contains(&hash) {
+                    anyhow::bail!("Sequence {} is not referenced by any bundled chunk", hash);
+                }
+                if bundled.representations.canonical_hash != hash {
+                    anyhow::bail!("Headers bundled with sequence {} belong to another", hash);
+                }
+                seen_sequences.insert(hash);
+            }
+            match storage {
+                Some(_) => stage(&name, &data)?,
+                None => sequences_imported += sequences.len(),
+            }
+        } else {
+            if name == PROFILE_ENTRY {
+                let parsed: ReductionManifest = rmp_serde::from_slice(&data)?;
+                if !parsed.verify_integrity()? {
+                    anyhow::bail!("Reduction profile fails its Merkle integrity check");
+                }
+                reduction = Some(parsed);
+            } else if name == ACCESSION_INDEX_ENTRY {
+                accession_index = Some(rmp_serde::from_slice(&data)?);
+            } else if let Some(hex) = name.strip_prefix(DELTAS_DIR) {
+                let chunk: TemporalDeltaChunk = serde_json::from_slice(&data)?;
+                if chunk.content_hash.to_hex() != hex {
+                    anyhow::bail!("Delta chunk {} has a different content hash", hex);
+                }
+                if storage.is_some_and(|storage| !storage.has_chunk(&chunk.content_hash)) {
+                    stage(&name, &data)?;
+                }
+                seen_deltas.insert(chunk.content_hash);
+            } else if let Some(relative) = name.strip_prefix(TAXONOMY_DIR) {
+                let relative = Path::new(relative);
+                if !relative
+                    .components()
+                    .all(|c| matches!(c, std::path::Component::Normal(_)))
+                {
+                    anyhow::bail!("Invalid taxonomy path {} in bundle", name);
+                }
+                if let Some(staging) = &taxonomy_staging {
+                    let file_path = staging.join(relative);
+                    if let Some(parent) = file_path.parent() {
+                        fs::create_dir_all(parent)?;
+                    }
+                    fs::write(file_path, &data)?;
+                }
+            }
+        }
+    }
+
+    let header = header.ok_or_else(|| anyhow::anyhow!("{} is empty", path.display()))?;
+    let manifest = manifest.ok_or_else(|| anyhow::anyhow!("Bundle has no manifest"))?;
+
+    // Everything the header and manifests promise must have arrived
+    if let Some(missing) = manifest
+        .chunk_index
+        .iter()
+        .find(|c| !seen_chunks.contains(&c.hash))
+    {
+        anyhow::bail!("Bundle is missing chunk {}", missing.hash);
+    }
+    if let Some(missing) = required_sequences
+        .iter()
+        .find(|hash| !seen_sequences.contains(*hash))
+    {
+        anyhow::bail!("Bundle is missing sequence {}", missing);
+    }
+    if let Some(missing) = header.files.keys().find(|name| !seen_files.contains(*name)) {
+        anyhow::bail!("Bundle is missing {}", missing);
+    }
+    if let Some(reduction) = &reduction {
+        if let Some(missing) = reduction
+            .delta_chunks
+            .iter()
+            .find(|d| !seen_deltas.contains(&d.chunk_hash))
+        {
+            anyhow::bail!("Bundle is missing delta chunk {}", missing.chunk_hash);
+        }
+    }
+
+    let mut became_current = false;
+    let mut taxonomy_installed = false;
+    if let Some(manager) = target {
+        let storage = &manager.get_repository().storage;
+        for (name, path) in &staged {
+            let data = fs::read(path)?;
+            if name.starts_with(CHUNKS_DIR) {
+                storage.store_chunk(&data, true)?;
+            } else if name.starts_with(SEQUENCES_DIR) {
+                let sequences: Vec<BundledSequence> = rmp_serde::from_slice(&data)?;
+                for bundled in sequences {
+                    if import_sequence(storage, bundled)? {
+                        sequences_imported += 1;
+                    } else {
+                        sequences_skipped += 1;
+                    }
+                }
+            } else if name.starts_with(DELTAS_DIR) {
+                storage.store_delta_chunk(&serde_json::from_slice(&data)?)?;
+            }
+        }
+
+        let (source, dataset, version) = (&header.source, &header.dataset, &header.version);
+        became_current = manager.import_version_manifest(
+            source,
+            dataset,
+            version,
+            &manifest,
+            &manifest_bytes,
+        )?;
+
+        if let Some(reduction) = &reduction {
+            storage.store_database_reduction_manifest(reduction, source, dataset, version)?;
+            if let Some(index) = &accession_index {
+                storage.store_accession_index(index, source, dataset, version)?;
+            }
+            manager.add_reduction_profile_to_metadata(source, dataset, &reduction.profile)?;
+        }
+
+        let staged = taxonomy_staging.filter(|staging| staging.exists());
+        if let (Some(staging), Some(taxonomy)) = (&staged, &header.taxonomy_version) {
+            // The version directory may exist with mappings but no tree
+            let version_dir = paths::talaria_taxonomy_version_dir(taxonomy);
+            fs::create_dir_all(&version_dir)?;
+            fs::rename(staging.join("tree"), version_dir.join("tree"))?;
+            fs::remove_dir_all(staging)?;
+            let current = paths::talaria_taxonomy_current_dir();
+            if !current.exists() && !current.is_symlink() {
+                #[cfg(unix)]
+                std::os::unix::fs::symlink(taxonomy, &current)?;
+                #[cfg(windows)]
+                fs::write(&current, taxonomy)?;
+            }
+            taxonomy_installed = true;
+        }
+    }
+
+    Ok(BundleImportReport {
+        header,
+        chunks_imported,
+        chunks_skipped,
+        sequences_imported,
+        sequences_skipped,
+        delta_chunks: seen_deltas.len(),
+        taxonomy_installed,
+        became_current,
+    })
+}
+
+/// Directory of verified bundle entries awaiting import, removed when dropped
+struct StagingDir(PathBuf);
+
+impl StagingDir {
+    fn create(base_path: &Path) -> Result<Self> {
+        let path = base_path.join(format!(".bundle-import-{}", std::process::id()));
+        if path.exists() {
+            fs::remove_dir_all(&path)?;
+        }
+        fs::create_dir_all(&path)?;
+        Ok(Self(path))
+    }
+}
+
+impl Drop for StagingDir {
+    fn drop(&mut self) {
+        if let Err(e) = fs::remove_dir_all(&self.0) {
+            tracing::warn!("Failed to remove {}: {}", self.0.display(), e);
+        }
+    }
+}
+
+/// Merkle inclusion proofs for every chunk, in `chunk_index` order
+///
+/// Builds the same tree as `MerkleDAG::build_from_items` (pairs hashed left
+/// to right, an odd node promoted unchanged) one level at a time, so all
+/// proofs cost O(n log n) instead of a tree search per chunk.
+pub fn chunk_proofs(chunk_index: &[crate::types::ManifestMetadata]) -> Vec<MerkleProof> {
+    let mut paths: Vec<Vec<ProofStep>> = vec![Vec::new(); chunk_index.len()];
+    let mut positions: Vec<usize> = (0..chunk_index.len()).collect();
+    let mut level: Vec<SHA256Hash> = chunk_index.iter().map(|c| c.hash).collect();
+
+    while level.len() > 1 {
+        for (path, position) in paths.iter_mut().zip(positions.iter_mut()) {
+            let i = *position;
+            if i % 2 == 1 {
+                path.push(ProofStep {
+                    hash: level[i - 1],
+                    position: Position::Left,
+                });
+            } else if i + 1 < level.len() {
+                path.push(ProofStep {
+                    hash: level[i + 1],
+                    position: Position::Right,
+                });
+            }
+            *position = i / 2;
+        }
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => {
+                    let mut hasher = Sha256::new();
+                    hasher.update(left.as_bytes());
+                    hasher.update(right.as_bytes());
+                    SHA256Hash(hasher.finalize().into())
+                }
+                [single] => *single,
+                _ => unreachable!(),
+            })
+            .collect();
+    }
+
+    let root = level.first().copied().unwrap_or_default();
+    chunk_index
+        .iter()
+        .zip(paths)
+        .map(|(chunk, path)| MerkleProof {
+            leaf_hash: chunk.hash,
+            root_hash: root,
+            path,
+        })
+        .collect()
+}
+
+/// Check the manifest's chunk index against its stored root and the header
+fn verify_chunk_root(
+    manifest: &TemporalManifest,
+    header: &BundleHeader,
+    expected_root: Option<&SHA256Hash>,
+) -> Result<SHA256Hash> {
+    let root = chunk_proofs(&manifest.chunk_index)
+        .first()
+        .map(|proof| proof.root_hash)
+        .ok_or_else(|| anyhow::anyhow!("Bundled manifest has no chunks"))?;
+
+    if let Some(tree) = &manifest.chunk_merkle_tree {
+        if tree.root_hash != root {
+            anyhow::bail!("Manifest chunk index does not match its Merkle root");
+        }
+    }
+    if root.to_hex() != header.chunk_root {
+        anyhow::bail!("Chunk root does not match the bundle header");
+    }
+    if let Some(expected) = expected_root {
+        if &root != expected {
+            anyhow::bail!("Chunk root {} is not the expected {}", root, expected);
+        }
+    }
+    Ok(root)
+}
+
+/// Store a bundled sequence; false if the target already had all of it
+fn import_sequence(storage: &crate::HeraldStorage, bundled: BundledSequence) -> Result<bool> {
+    let sequence_storage = &storage.sequence_storage;
+    let hash = bundled.canonical.sequence_hash;
+
+    let existing = if sequence_storage.canonical_exists(&hash)? {
+        Some(sequence_storage.load_representations(&hash)?)
+    } else {
+        None
+    };
+    let sequence = std::str::from_utf8(&bundled.canonical.sequence)
+        .with_context(|| format!("Sequence {} is not ASCII", hash))?;
+
+    let mut imported = existing.is_none();
+    for representation in bundled.representations.representations {
+        let known = existing.as_ref().is_some_and(|reps| {
+            reps.representations()
+                .iter()
+                .any(|r| r.source == representation.source && r.header == representation.header)
+        });
+        if !known {
+            sequence_storage.store_sequence_with_taxon(
+                sequence,
+                &representation.header,
+                representation.source,
+                representation.taxon_id,
+            )?;
+            imported = true;
+        }
+    }
+    Ok(imported)
+}
+
+/// Add the installed taxonomy tree to `files`; returns its version
+fn installed_taxonomy_tree(files: &mut Vec<(String, FileSource)>) -> Result<Option<String>> {
+    if !paths::talaria_taxonomy_current_dir().exists() {
+        return Ok(None);
+    }
+    let version = DatabaseManager::current_taxonomy_version();
+    let tree_dir = paths::talaria_taxonomy_version_dir(&version).join("tree");
+    if !tree_dir.exists() {
+        return Ok(None);
+    }
+
+    let mut names: Vec<_> = fs::read_dir(&tree_dir)?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_file())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    for name in names {
+        files.push((
+            format!("{}tree/{}", TAXONOMY_DIR, name),
+            FileSource::Path(tree_dir.join(&name)),
+        ));
+    }
+    Ok(Some(version))
+}
+
+fn append_entry<W: Write>(builder: &mut tar::Builder<W>, path: &str, data: &[u8]) -> Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(Utc::now().timestamp() as u64);
+    header.set_cksum();
+    builder.append_data(&mut header, path, data)?;
+    Ok(())
+}
+
+fn open_archive(path: &Path) -> Result<tar::Archive<impl Read>> {
+    let file =
+        File::open(path).with_context(|| format!("Failed to open bundle {}", path.display()))?;
+    let decoder = zstd::stream::read::Decoder::new(file)?;
+    Ok(tar::Archive::new(decoder))
+}
+
+fn parse_header(data: &[u8]) -> Result<BundleHeader> {
+    let header: BundleHeader = serde_json::from_slice(data).context("Invalid bundle header")?;
+    if header.format_version > BUNDLE_FORMAT_VERSION {
+        anyhow::bail!(
+            "Bundle format {} is newer than supported ({}); upgrade talaria",
+            header.format_version,
+            BUNDLE_FORMAT_VERSION
+        );
+    }
+    Ok(header)
+}
+
+fn parse_chunk_manifest(data: &[u8]) -> Result<ChunkManifest> {
+    rmp_serde::from_slice(data)
+        .or_else(|_| serde_json::from_slice(data))
+        .map_err(|_| anyhow::anyhow!("Chunk is not a chunk manifest"))
+}
+
+fn parse_hash(hex: &str) -> Result<SHA256Hash> {
+    SHA256Hash::from_hex(hex).map_err(|_| anyhow::anyhow!("Invalid hash '{}' in bundle", hex))
+}
+
+fn hash_file(path: &Path) -> Result<SHA256Hash> {
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut BufReader::new(File::open(path)?), &mut hasher)?;
+    Ok(SHA256Hash(hasher.finalize().into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{ManifestMetadata, TaxonId};
+
+    fn chunk_index(count: usize) -> Vec<ManifestMetadata> {
+        (0..count)
+            .map(|i| ManifestMetadata {
+                hash: SHA256Hash::compute(format!("chunk{}", i).as_bytes()),
+                taxon_ids: Vec::new(),
+                sequence_count: 1,
+                size: 0,
+                compressed_size: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_chunk_proofs_match_merkle_dag() {
+        for count in 1..10 {
+            let index = chunk_index(count);
+            let root = MerkleDAG::build_from_items(index.clone())
+                .unwrap()
+                .root_hash()
+                .unwrap();
+
+            let proofs = chunk_proofs(&index);
+            assert_eq!(proofs.len(), count);
+            for (proof, chunk) in proofs.iter().zip(&index) {
+                assert_eq!(proof.leaf_hash, chunk.hash);
+                assert_eq!(proof.root_hash, root, "{} chunks", count);
+                assert!(MerkleDAG::verify_proof(proof, &[]), "{} chunks", count);
+            }
+        }
+    }
+
+    #[test]
+    fn test_archive_without_header_is_rejected() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join(format!("test.{}", BUNDLE_EXTENSION));
+
+        let encoder = zstd::stream::write::Encoder::new(File::create(&path).unwrap(), 3).unwrap();
+        let mut builder = tar::Builder::new(encoder);
+        append_entry(&mut builder, MANIFEST_ENTRY, b"not a bundle").unwrap();
+        builder.into_inner().unwrap().finish().unwrap();
+
+        assert!(read_bundle_header(&path).is_err());
+        assert!(verify_bundle(&path, None).is_err());
+    }
+
+    /// Repository under `dir` holding one version of custom/test_bundle
+    fn source_repository(dir: &Path) -> DatabaseManager {
+        use talaria_bio::sequence::Sequence;
+        use talaria_test::fixtures::test_database_source;
+
+        let mut manager =
+            DatabaseManager::new(Some(dir.join("source").to_string_lossy().into_owned())).unwrap();
+        let sequences = [
+            ("SEQ_001", "ACGTACGTACGTAAAA"),
+            ("SEQ_002", "GGGGCCCCGGGGCCCC"),
+            ("SEQ_003", "ACGTACGTACGTAAAA"),
+        ]
+        .iter()
+        .map(|(id, seq)| Sequence {
+            id: id.to_string(),
+            description: Some("bundle".to_string()),
+            sequence: seq.as_bytes().to_vec(),
+            taxon_id: Some(562),
+            taxonomy_sources: Default::default(),
+        })
+        .collect();
+        manager
+            .chunk_sequences_direct_with_progress_final(
+                sequences,
+                &test_database_source("bundle"),
+                None,
+                true,
+            )
+            .unwrap();
+        manager
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_bundle_round_trip() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::env::set_var("TALARIA_HOME", dir.path());
+
+        let source = source_repository(dir.path());
+        let path = dir.path().join(format!("test.{}", BUNDLE_EXTENSION));
+        let header = create_bundle(
+            &source,
+            "custom/test_bundle",
+            &path,
+            &BundleOptions::default(),
+        )
+        .unwrap();
+        let expected = source
+            .get_version_manifest("custom", "test_bundle", "current")
+            .unwrap();
+        assert_eq!(header.version, expected.version);
+        assert_eq!(header.sequence_count, 3);
+        assert_eq!(read_bundle_header(&path).unwrap().label(), header.label());
+
+        let root = SHA256Hash::from_hex(&header.chunk_root).unwrap();
+        let verified = verify_bundle(&path, Some(&root)).unwrap();
+        assert_eq!(verified.chunks_imported, header.chunk_count);
+        assert_eq!(verified.sequences_imported, 2);
+        assert!(verify_bundle(&path, Some(&SHA256Hash::compute(b"other root"))).is_err());
+
+        // Repositories in one process share the canonical sequence store, which
+        // also holds the version manifests. Drop the version and release the
+        // store so the target starts without it; its sequences stay behind.
+        source
+            .delete_entire_database("custom", "test_bundle")
+            .unwrap();
+        drop(source);
+        let target = DatabaseManager::new(Some(
+            dir.path().join("target").to_string_lossy().into_owned(),
+        ))
+        .unwrap();
+        let report = import_bundle(&target, &path, Some(&root)).unwrap();
+        assert!(report.became_current);
+        assert_eq!(report.chunks_imported, header.chunk_count);
+        assert_eq!(report.sequences_imported + report.sequences_skipped, 2);
+
+        let imported = target
+            .get_version_manifest("custom", "test_bundle", "current")
+            .unwrap();
+        assert_eq!(imported.version, expected.version);
+        let hashes = |manifest: &TemporalManifest| -> Vec<SHA256Hash> {
+            manifest.chunk_index.iter().map(|c| c.hash).collect()
+        };
+        assert_eq!(hashes(&imported), hashes(&expected));
+        let sequences = crate::operations::FastaAssembler::new(&target.get_repository().storage)
+            .assemble_from_chunks(&hashes(&imported))
+            .unwrap();
+        let residues: HashSet<Vec<u8>> = sequences.into_iter().map(|seq| seq.sequence).collect();
+        assert_eq!(
+            residues,
+            HashSet::from([b"ACGTACGTACGTAAAA".to_vec(), b"GGGGCCCCGGGGCCCC".to_vec()])
+        );
+        // Taxa assigned outside the headers come along with the representations
+        for residues in &residues {
+            let representations = target
+                .get_repository()
+                .storage
+                .sequence_storage
+                .load_representations(&SHA256Hash::compute(residues))
+                .unwrap();
+            for representation in representations.representations() {
+                assert_eq!(representation.taxon_id, Some(TaxonId(562)));
+            }
+        }
+
+        // A second import finds everything in place
+        let again = import_bundle(&target, &path, Some(&root)).unwrap();
+        assert_eq!(again.chunks_imported, 0);
+        assert_eq!(again.chunks_skipped, header.chunk_count);
+        assert_eq!(again.sequences_imported, 0);
+
+        std::env::remove_var("TALARIA_HOME");
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_tampered_bundle_is_rejected() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::env::set_var("TALARIA_HOME", dir.path());
+
+        let source = source_repository(dir.path());
+        let path = dir.path().join(format!("test.{}", BUNDLE_EXTENSION));
+        create_bundle(
+            &source,
+            "custom/test_bundle",
+            &path,
+            &BundleOptions::default(),
+        )
+        .unwrap();
+
+        // Flip one byte of the first chunk; with the sequences entries'
+        // headers rewritten instead, the chunks are intact
+        let tampered_chunk = rewrite_bundle(&path, "chunk", |name, data| {
+            if !name.starts_with(CHUNKS_DIR) {
+                return false;
+            }
+            let last = data.len() - 1;
+            data[last] ^= 0xff;
+            true
+        });
+        let tampered_headers = rewrite_bundle(&path, "headers", |name, data| {
+            if !name.starts_with(SEQUENCES_DIR) {
+                return false;
+            }
+            let mut sequences: Vec<BundledSequence> = rmp_serde::from_slice(data).unwrap();
+            sequences[0].representations.representations[0].header = ">SEQ_666".to_string();
+            *data = rmp_serde::to_vec(&sequences).unwrap();
+            true
+        });
+
+        let error = verify_bundle(&tampered_chunk, None).unwrap_err();
+        assert!(
+            error.to_string().contains("does not match its hash"),
+            "{}",
+            error
+        );
+        let error = verify_bundle(&tampered_headers, None).unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("does not match the bundle header"),
+            "{}",
+            error
+        );
+
+        let chunks: Vec<SHA256Hash> = source
+            .get_version_manifest("custom", "test_bundle", "current")
+            .unwrap()
+            .chunk_index
+            .iter()
+            .map(|c| c.hash)
+            .collect();
+        source
+            .delete_entire_database("custom", "test_bundle")
+            .unwrap();
+        drop(source);
+        let target = DatabaseManager::new(Some(
+            dir.path().join("target").to_string_lossy().into_owned(),
+        ))
+        .unwrap();
+        for tampered in [&tampered_chunk, &tampered_headers] {
+            assert!(import_bundle(&target, tampered, None).is_err());
+        }
+
+        // Chunks verified before the failure were staged, not stored
+        let storage = &target.get_repository().storage;
+        assert!(chunks.iter().all(|hash| !storage.has_chunk(hash)));
+        assert!(target
+            .get_version_manifest("custom", "test_bundle", "current")
+            .is_err());
+
+        std::env::remove_var("TALARIA_HOME");
+    }
+
+    /// Copy of the bundle at `path` with the first entry `tamper` changes
+    fn rewrite_bundle(
+        path: &Path,
+        label: &str,
+        mut tamper: impl FnMut(&str, &mut Vec<u8>) -> bool,
+    ) -> PathBuf {
+        let output = path.with_file_name(format!("tampered_{}.{}", label, BUNDLE_EXTENSION));
+        let encoder = zstd::stream::write::Encoder::new(File::create(&output).unwrap(), 3).unwrap();
+        let mut builder = tar::Builder::new(encoder);
+        let mut tampered = false;
+        let mut archive = open_archive(path).unwrap();
+        for entry in archive.entries().unwrap() {
+            let mut entry = entry.unwrap();
+            let name = entry.path().unwrap().to_string_lossy().into_owned();
+            let mut data = Vec::new();
+            entry.read_to_end(&mut data).unwrap();
+            if !tampered {
+                tampered = tamper(&name, &mut data);
+            }
+            append_entry(&mut builder, &name, &data).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+        assert!(tampered);
+        output
+    }
+}
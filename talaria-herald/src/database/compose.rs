@@ -0,0 +1,1049 @@
+/// Virtual databases defined by set algebra over existing ones
+///
+/// An expression such as
+/// `uniprot/swissprot ∪ custom/lab-proteins − ncbi/refseq-protein ∩ taxon:Bacteria`
+/// is evaluated over the canonical sequence hashes of each operand version and
+/// the result is stored as an ordinary version manifest. Chunks whose sequences
+/// all survive are shared as they are and the remainder is listed in new chunk
+/// manifests, so no sequence data is copied and the virtual database exports,
+/// reduces and diffs like any other.
+///
+/// Grammar:
+///
+/// - operands are `source/dataset[@version]` or `taxon:NAME`, `taxon:ID`,
+///   `taxon:"Multi word name"` (the taxon and all of its descendants)
+/// - `∪` or `|` is union, `∩` or `&` intersection and `−`, `\` or a `-`
+///   surrounded by whitespace difference
+/// - `∩` binds tighter than `∪` and `−`, which are left-associative, so the
+///   example above reads `(swissprot ∪ lab-proteins) − (refseq-protein ∩ Bacteria)`
+/// - parentheses group as usual
+///
+/// Taxon operands can only narrow a set of sequences: they may appear on
+/// either side of `∩` or on the right of `−`, never on their own or in a
+/// union with a database. A sequence belongs to a taxon when any of its
+/// recorded taxon assignments is the taxon or one of its descendants.
+use crate::database::DatabaseManager;
+use crate::taxonomy::filter::{FilterContext, TaxonRef, TaxonomyFilter, TaxonomyResolver};
+use crate::types::{
+    BiTemporalCoordinate, ChunkManifest, ManifestMetadata, SHA256Hash, SHA256HashExt,
+    SerializedMerkleTree, TaxonId, TemporalManifest,
+};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use talaria_core::types::DatabaseReference;
+
+/// Parsed composition expression
+#[derive(Debug, Clone, PartialEq)]
+pub enum ComposeExpr {
+    /// A database version, `current` when none is given
+    Database(DatabaseReference),
+    /// A taxon and all of its descendants
+    Taxon(TaxonRef),
+    Union(Box<ComposeExpr>, Box<ComposeExpr>),
+    Intersection(Box<ComposeExpr>, Box<ComposeExpr>),
+    Difference(Box<ComposeExpr>, Box<ComposeExpr>),
+}
+
+/// What a subexpression denotes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    /// A finite set of sequences
+    Set,
+    /// A taxonomic predicate that can only narrow a set
+    Predicate,
+}
+
+impl ComposeExpr {
+    /// Parse and validate an expression
+    pub fn parse(expression: &str) -> Result<Self> {
+        let tokens = tokenize(expression)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let expr = parser.parse_union()?;
+        if let Some((_, position)) = parser.tokens.get(parser.pos) {
+            anyhow::bail!(
+                "Unexpected token at position {} in '{}'",
+                position,
+                expression
+            );
+        }
+        if expr.kind()? != Kind::Set {
+            anyhow::bail!(
+                "'{}' only names taxa; intersect it with a database to select sequences",
+                expr
+            );
+        }
+        Ok(expr)
+    }
+
+    /// Database operands in the order they appear
+    pub fn databases(&self) -> Vec<&DatabaseReference> {
+        let mut out = Vec::new();
+        self.collect_databases(&mut out);
+        out
+    }
+
+    /// Whether the expression contains taxon operands
+    pub fn uses_taxonomy(&self) -> bool {
+        match self {
+            ComposeExpr::Database(_) => false,
+            ComposeExpr::Taxon(_) => true,
+            ComposeExpr::Union(l, r)
+            | ComposeExpr::Intersection(l, r)
+            | ComposeExpr::Difference(l, r) => l.uses_taxonomy() || r.uses_taxonomy(),
+        }
+    }
+
+    fn collect_databases<'a>(&'a self, out: &mut Vec<&'a DatabaseReference>) {
+        match self {
+            ComposeExpr::Database(reference) => out.push(reference),
+            ComposeExpr::Taxon(_) => {}
+            ComposeExpr::Union(l, r)
+            | ComposeExpr::Intersection(l, r)
+            | ComposeExpr::Difference(l, r) => {
+                l.collect_databases(out);
+                r.collect_databases(out);
+            }
+        }
+    }
+
+    fn kind(&self) -> Result<Kind> {
+        use Kind::*;
+        Ok(match self {
+            ComposeExpr::Database(_) => Set,
+            ComposeExpr::Taxon(_) => Predicate,
+            ComposeExpr::Union(l, r) => match (l.kind()?, r.kind()?) {
+                (Set, Set) => Set,
+                (Predicate, Predicate) => Predicate,
+                _ => anyhow::bail!(
+                    "'{}' unites a database with taxa; taxa can only narrow a database with ∩ or −",
+                    self
+                ),
+            },
+            ComposeExpr::Intersection(l, r) => match (l.kind()?, r.kind()?) {
+                (Predicate, Predicate) => Predicate,
+                _ => Set,
+            },
+            ComposeExpr::Difference(l, r) => match (l.kind()?, r.kind()?) {
+                (Predicate, Set) => anyhow::bail!(
+                    "'{}' subtracts a database from taxa; put the database on the left",
+                    self
+                ),
+                (left, _) => left,
+            },
+        })
+    }
+
+    /// Taxonomy filter equivalent to a predicate subexpression
+    fn to_filter(&self) -> TaxonomyFilter {
+        match self {
+            ComposeExpr::Taxon(taxon) => TaxonomyFilter::DescendantsOf(taxon.clone()),
+            ComposeExpr::Union(l, r) => {
+                TaxonomyFilter::Or(Box::new(l.to_filter()), Box::new(r.to_filter()))
+            }
+            ComposeExpr::Intersection(l, r) => {
+                TaxonomyFilter::And(Box::new(l.to_filter()), Box::new(r.to_filter()))
+            }
+            ComposeExpr::Difference(l, r) => TaxonomyFilter::And(
+                Box::new(l.to_filter()),
+                Box::new(TaxonomyFilter::Not(Box::new(r.to_filter()))),
+            ),
+            ComposeExpr::Database(_) => unreachable!("databases are never part of a predicate"),
+        }
+    }
+
+    fn taxa(&self) -> Vec<&TaxonRef> {
+        match self {
+            ComposeExpr::Database(_) => Vec::new(),
+            ComposeExpr::Taxon(taxon) => vec![taxon],
+            ComposeExpr::Union(l, r)
+            | ComposeExpr::Intersection(l, r)
+            | ComposeExpr::Difference(l, r) => {
+                let mut taxa = l.taxa();
+                taxa.extend(r.taxa());
+                taxa
+            }
+        }
+    }
+
+    fn precedence(&self) -> u8 {
+        match self {
+            ComposeExpr::Union(..) | ComposeExpr::Difference(..) => 1,
+            ComposeExpr::Intersection(..) => 2,
+            ComposeExpr::Database(_) | ComposeExpr::Taxon(_) => 3,
+        }
+    }
+}
+
+impl fmt::Display for ComposeExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (l, op, r) = match self {
+            ComposeExpr::Database(reference) => return write!(f, "{}", reference),
+            ComposeExpr::Taxon(TaxonRef::Id(id)) => return write!(f, "taxon:{}", id.0),
+            ComposeExpr::Taxon(TaxonRef::Name(name))
+                if name.contains(|c: char| c.is_whitespace() || OPERATOR_CHARS.contains(&c)) =>
+            {
+                return write!(f, "taxon:\"{}\"", name)
+            }
+            ComposeExpr::Taxon(TaxonRef::Name(name)) => return write!(f, "taxon:{}", name),
+            ComposeExpr::Union(l, r) => (l, "∪", r),
+            ComposeExpr::Intersection(l, r) => (l, "∩", r),
+            ComposeExpr::Difference(l, r) => (l, "−", r),
+        };
+        // Operators are left-associative, so only the right operand needs
+        // parentheses at equal precedence
+        let precedence = self.precedence();
+        if l.precedence() < precedence {
+            write!(f, "({})", l)?;
+        } else {
+            write!(f, "{}", l)?;
+        }
+        write!(f, " {} ", op)?;
+        if r.precedence() <= precedence {
+            write!(f, "({})", r)
+        } else {
+            write!(f, "{}", r)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    LParen,
+    RParen,
+    Union,
+    Intersection,
+    Difference,
+    Operand(String),
+}
+
+const OPERATOR_CHARS: &[char] = &['(', ')', '∪', '|', '∩', '&', '−', '\\'];
+
+/// Split an expression into tokens paired with their character offset
+fn tokenize(expression: &str) -> Result<Vec<(Token, usize)>> {
+    let mut tokens = Vec::new();
+    let mut chars = expression.chars().enumerate().peekable();
+
+    while let Some(&(position, c)) = chars.peek() {
+        let token = match c {
+            c if c.is_whitespace() => {
+                chars.next();
+                continue;
+            }
+            '(' => Token::LParen,
+            ')' => Token::RParen,
+            '∪' | '|' => Token::Union,
+            '∩' | '&' => Token::Intersection,
+            '−' | '\\' => Token::Difference,
+            _ => {
+                let mut word = String::new();
+                let mut quoted = false;
+                while let Some(&(_, c)) = chars.peek() {
+                    if !quoted && (c.is_whitespace() || OPERATOR_CHARS.contains(&c)) {
+                        break;
+                    }
+                    if c == '"' {
+                        quoted = !quoted;
+                    }
+                    word.push(c);
+                    chars.next();
+                }
+                if quoted {
+                    anyhow::bail!("Unterminated quote at position {}", position);
+                }
+                // A lone hyphen is subtraction; hyphens inside names are kept
+                tokens.push((
+                    if word == "-" {
+                        Token::Difference
+                    } else {
+                        Token::Operand(word)
+                    },
+                    position,
+                ));
+                continue;
+            }
+        };
+        chars.next();
+        tokens.push((token, position));
+    }
+
+    if tokens.is_empty() {
+        anyhow::bail!("Empty composition expression");
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(token, _)| token)
+    }
+
+    fn position(&self) -> String {
+        match self.tokens.get(self.pos) {
+            Some((_, position)) => format!("position {}", position),
+            None => "end of expression".to_string(),
+        }
+    }
+
+    fn parse_union(&mut self) -> Result<ComposeExpr> {
+        let mut left = self.parse_intersection()?;
+        loop {
+            let union = match self.peek() {
+                Some(Token::Union) => true,
+                Some(Token::Difference) => false,
+                _ => return Ok(left),
+            };
+            self.pos += 1;
+            let right = Box::new(self.parse_intersection()?);
+            left = if union {
+                ComposeExpr::Union(Box::new(left), right)
+            } else {
+                ComposeExpr::Difference(Box::new(left), right)
+            };
+        }
+    }
+
+    fn parse_intersection(&mut self) -> Result<ComposeExpr> {
+        let mut left = self.parse_primary()?;
+        while self.peek() == Some(&Token::Intersection) {
+            self.pos += 1;
+            let right = self.parse_primary()?;
+            left = ComposeExpr::Intersection(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_primary(&mut self) -> Result<ComposeExpr> {
+        let position = self.position();
+        match self.tokens.get(self.pos).map(|(token, _)| token.clone()) {
+            Some(Token::LParen) => {
+                self.pos += 1;
+                let expr = self.parse_union()?;
+                if self.peek() != Some(&Token::RParen) {
+                    anyhow::bail!("Expected ')' at {}", self.position());
+                }
+                self.pos += 1;
+                Ok(expr)
+            }
+            Some(Token::Operand(word)) => {
+                self.pos += 1;
+                parse_operand(&word).with_context(|| format!("Invalid operand at {}", position))
+            }
+            _ => anyhow::bail!("Expected a database or taxon at {}", position),
+        }
+    }
+}
+
+fn parse_operand(word: &str) -> Result<ComposeExpr> {
+    if let Some(taxon) = word.strip_prefix("taxon:") {
+        let taxon = taxon.trim_matches('"').trim();
+        if taxon.is_empty() {
+            anyhow::bail!("'{}' names no taxon", word);
+        }
+        return Ok(ComposeExpr::Taxon(match taxon.parse::<u32>() {
+            Ok(id) => TaxonRef::Id(TaxonId(id)),
+            Err(_) => TaxonRef::Name(taxon.to_string()),
+        }));
+    }
+    if word.contains('"') {
+        anyhow::bail!("Quotes are only allowed in taxon operands: '{}'", word);
+    }
+    let reference = DatabaseReference::parse(word)?;
+    if reference.profile.is_some() {
+        anyhow::bail!(
+            "'{}' names a reduction profile; compose full databases only",
+            word
+        );
+    }
+    if reference.source.is_empty() || reference.dataset.is_empty() {
+        anyhow::bail!("'{}' is not a 'source/dataset' reference", word);
+    }
+    Ok(ComposeExpr::Database(reference))
+}
+
+/// A database operand pinned to the version it was evaluated against
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResolvedOperand {
+    /// `source/dataset`
+    pub database: String,
+    /// Version as written in the expression (`current` when omitted)
+    pub requested: String,
+    /// Resolved version timestamp
+    pub version: String,
+    pub sequences: usize,
+}
+
+/// How a virtual database version was composed, stored with the version
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompositionRecord {
+    /// Normalised expression
+    pub expression: String,
+    pub operands: Vec<ResolvedOperand>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Outcome of composing a virtual database version
+#[derive(Debug, Clone)]
+pub struct CompositionReport {
+    pub record: CompositionRecord,
+    pub version: String,
+    pub sequences: usize,
+    pub chunks: usize,
+    /// Chunks taken unchanged from an operand
+    pub chunks_shared: usize,
+    /// Chunk manifests written for partially selected chunks
+    pub chunks_created: usize,
+    pub total_size: usize,
+}
+
+/// Evaluation state: operand chunks in the order they were first seen
+struct Evaluator<'a> {
+    manager: &'a DatabaseManager,
+    ctx: FilterContext<'a>,
+    catalog: Vec<ManifestMetadata>,
+    catalog_hashes: HashSet<SHA256Hash>,
+    operands: Vec<ResolvedOperand>,
+    /// Manifests of the operand versions, to carry their taxonomy over
+    manifests: Vec<TemporalManifest>,
+}
+
+impl<'a> Evaluator<'a> {
+    fn eval(&mut self, expr: &ComposeExpr) -> Result<HashSet<SHA256Hash>> {
+        match expr {
+            ComposeExpr::Database(reference) => self.load(reference),
+            ComposeExpr::Union(l, r) => {
+                let mut left = self.eval(l)?;
+                left.extend(self.eval(r)?);
+                Ok(left)
+            }
+            ComposeExpr::Intersection(l, r) => match (l.kind()?, r.kind()?) {
+                (Kind::Set, Kind::Predicate) => {
+                    let set = self.eval(l)?;
+                    self.select(set, &r.to_filter(), true)
+                }
+                (Kind::Predicate, Kind::Set) => {
+                    let set = self.eval(r)?;
+                    self.select(set, &l.to_filter(), true)
+                }
+                _ => {
+                    let left = self.eval(l)?;
+                    let right = self.eval(r)?;
+                    Ok(left.intersection(&right).copied().collect())
+                }
+            },
+            ComposeExpr::Difference(l, r) => {
+                let left = self.eval(l)?;
+                if r.kind()? == Kind::Predicate {
+                    return self.select(left, &r.to_filter(), false);
+                }
+                let right = self.eval(r)?;
+                Ok(left.difference(&right).copied().collect())
+            }
+            ComposeExpr::Taxon(_) => unreachable!("predicates are applied to a set"),
+        }
+    }
+
+    /// Sequences of one database version
+    fn load(&mut self, reference: &DatabaseReference) -> Result<HashSet<SHA256Hash>> {
+        let (source, dataset) = (reference.source.as_str(), reference.dataset.as_str());
+        let requested = reference.version_or_default();
+        let version = self
+            .manager
+            .resolve_version_reference(source, dataset, requested)
+            .with_context(|| format!("Failed to resolve {}", reference))?;
+        let manifest = self
+            .manager
+            .get_version_manifest(source, dataset, &version)?;
+
+        let mut sequences = HashSet::new();
+        for chunk in &manifest.chunk_index {
+            let chunk_manifest = self
+                .manager
+                .load_manifest(&chunk.hash)
+                .with_context(|| format!("Failed to load chunk {} of {}", chunk.hash, reference))?;
+            sequences.extend(chunk_manifest.sequence_refs);
+            if self.catalog_hashes.insert(chunk.hash) {
+                self.catalog.push(chunk.clone());
+            }
+        }
+
+        self.operands.push(ResolvedOperand {
+            database: format!("{}/{}", source, dataset),
+            requested: requested.to_string(),
+            version,
+            sequences: sequences.len(),
+        });
+        self.manifests.push(manifest);
+        Ok(sequences)
+    }
+
+    /// Keep the sequences that do (`keep_matching`) or do not match a filter
+    ///
+    /// Each sequence is decided from every taxon recorded on its
+    /// representations, whichever database they came from. A chunk's taxon
+    /// list only covers the sequences as that chunk saw them, so it cannot
+    /// stand in for them; verdicts are cached per taxon instead.
+    fn select(
+        &self,
+        set: HashSet<SHA256Hash>,
+        filter: &TaxonomyFilter,
+        keep_matching: bool,
+    ) -> Result<HashSet<SHA256Hash>> {
+        let mut taxon_cache: HashMap<TaxonId, bool> = HashMap::new();
+        let mut matches = |taxa: &[TaxonId]| -> Result<bool> {
+            let mut any = false;
+            for taxon in taxa {
+                let matched = match taxon_cache.get(taxon) {
+                    Some(matched) => *matched,
+                    None => {
+                        let matched = filter.evaluate_with(&[*taxon], &self.ctx).ok_or_else(|| {
+                            anyhow::anyhow!("Taxon operands need a taxonomy; run 'talaria database update-taxonomy'")
+                        })?;
+                        taxon_cache.insert(*taxon, matched);
+                        matched
+                    }
+                };
+                any |= matched;
+            }
+            Ok(any)
+        };
+
+        let storage = &self.manager.get_repository().storage.sequence_storage;
+        let mut kept = HashSet::with_capacity(set.len());
+        for hash in set {
+            let taxa: Vec<TaxonId> = storage
+                .load_representations(&hash)
+                .map(|reps| {
+                    reps.representations()
+                        .iter()
+                        .filter_map(|rep| rep.taxon_id)
+                        .collect()
+                })
+                .unwrap_or_default();
+            if matches(&taxa)? == keep_matching {
+                kept.insert(hash);
+            }
+        }
+        Ok(kept)
+    }
+}
+
+/// Evaluate `expr` and register the result as a version of `source/dataset`
+///
+/// `resolver` is required when the expression has taxon operands. The
+/// composition is recorded with the version so it can be re-evaluated
+/// against newer operand versions later.
+pub fn compose_database(
+    manager: &DatabaseManager,
+    source: &str,
+    dataset: &str,
+    version: &str,
+    expr: &ComposeExpr,
+    resolver: Option<&dyn TaxonomyResolver>,
+) -> Result<CompositionReport> {
+    let rocksdb = manager
+        .get_repository()
+        .storage
+        .sequence_storage
+        .get_rocksdb();
+    if rocksdb
+        .get_manifest(&format!("manifest:{}:{}:{}", source, dataset, version))?
+        .is_some()
+    {
+        anyhow::bail!("{}/{} already has a version {}", source, dataset, version);
+    }
+
+    let mut ctx = FilterContext::new();
+    if expr.uses_taxonomy() {
+        let resolver = resolver.ok_or_else(|| {
+            anyhow::anyhow!(
+                "Taxon operands need a taxonomy; run 'talaria database update-taxonomy'"
+            )
+        })?;
+        ctx = ctx.with_resolver(resolver);
+        for taxon in expr.taxa() {
+            if let TaxonRef::Name(name) = taxon {
+                let ids = TaxonomyFilter::Name(name.clone())
+                    .identity_taxa(&ctx)
+                    .unwrap_or_default();
+                if ids.is_empty() {
+                    anyhow::bail!("Unknown taxon '{}'", name);
+                }
+            }
+        }
+    }
+
+    let mut evaluator = Evaluator {
+        manager,
+        ctx,
+        catalog: Vec::new(),
+        catalog_hashes: HashSet::new(),
+        operands: Vec::new(),
+        manifests: Vec::new(),
+    };
+    let selected = evaluator.eval(expr)?;
+    if selected.is_empty() {
+        anyhow::bail!("'{}' selects no sequences", expr);
+    }
+
+    let storage = manager.get_storage();
+    let mut emitted: HashSet<SHA256Hash> = HashSet::with_capacity(selected.len());
+    let mut chunk_index = Vec::new();
+    let (mut chunks_shared, mut chunks_created) = (0, 0);
+    for chunk in &evaluator.catalog {
+        let manifest = manager.load_manifest(&chunk.hash)?;
+        let kept: Vec<SHA256Hash> = manifest
+            .sequence_refs
+            .iter()
+            .filter(|hash| selected.contains(hash) && !emitted.contains(hash))
+            .copied()
+            .collect();
+        if kept.is_empty() {
+            continue;
+        }
+        emitted.extend(kept.iter().copied());
+
+        if kept.len() == manifest.sequence_refs.len() {
+            chunk_index.push(chunk.clone());
+            chunks_shared += 1;
+            continue;
+        }
+
+        let partial = partial_chunk(manager, &manifest, kept)?;
+        let data = rmp_serde::to_vec(&partial)?;
+        let hash = storage.store_chunk(&data, true)?;
+        chunk_index.push(ManifestMetadata {
+            hash,
+            taxon_ids: partial.taxon_ids.clone(),
+            sequence_count: partial.sequence_count,
+            size: partial.total_size,
+            compressed_size: Some(data.len()),
+        });
+        chunks_created += 1;
+    }
+
+    let chunk_merkle_tree = SerializedMerkleTree::from_chunk_index(&chunk_index)?;
+
+    // Carry the taxonomy over only when every operand was built against the same one
+    let first = &evaluator.manifests[0];
+    let same_taxonomy = evaluator
+        .manifests
+        .iter()
+        .all(|m| m.taxonomy_version == first.taxonomy_version);
+    let previous_version = manager
+        .resolve_version_reference(source, dataset, "current")
+        .ok();
+    let now = Utc::now();
+    let manifest = TemporalManifest {
+        version: version.to_string(),
+        created_at: now,
+        sequence_version: version.to_string(),
+        taxonomy_version: if same_taxonomy {
+            first.taxonomy_version.clone()
+        } else {
+            "none".to_string()
+        },
+        temporal_coordinate: Some(BiTemporalCoordinate {
+            sequence_time: now,
+            taxonomy_time: now,
+        }),
+        taxonomy_root: if same_taxonomy {
+            first.taxonomy_root
+        } else {
+            SHA256Hash::zero()
+        },
+        sequence_root: SHA256Hash::zero(),
+        chunk_merkle_tree,
+        taxonomy_manifest_hash: if same_taxonomy {
+            first.taxonomy_manifest_hash
+        } else {
+            SHA256Hash::zero()
+        },
+        taxonomy_dump_version: if same_taxonomy {
+            first.taxonomy_dump_version.clone()
+        } else {
+            "none".to_string()
+        },
+        source_database: Some(format!("{}/{}", source, dataset)),
+        chunk_index,
+        discrepancies: Vec::new(),
+        etag: format!("virtual-{}-{}", dataset, version),
+        previous_version,
+    };
+
+    let total_size = manifest.chunk_index.iter().map(|c| c.size).sum();
+    manager.save_manifest_to_repository(
+        source,
+        dataset,
+        version,
+        &manifest,
+        manifest.chunk_index.len(),
+        emitted.len(),
+        total_size,
+    )?;
+
+    let record = CompositionRecord {
+        expression: expr.to_string(),
+        operands: evaluator.operands,
+        created_at: now,
+    };
+    manager.set_version_composition(source, dataset, version, &record)?;
+
+    Ok(CompositionReport {
+        record,
+        version: version.to_string(),
+        sequences: emitted.len(),
+        chunks: manifest.chunk_index.len(),
+        chunks_shared,
+        chunks_created,
+        total_size,
+    })
+}
+
+/// Chunk manifest listing the selected part of an operand chunk
+///
+/// Keeps the source chunk's taxon list, which may name taxa no longer present.
+fn partial_chunk(
+    manager: &DatabaseManager,
+    source: &ChunkManifest,
+    sequence_refs: Vec<SHA256Hash>,
+) -> Result<ChunkManifest> {
+    let storage = &manager.get_repository().storage.sequence_storage;
+    let mut total_size = 0;
+    for hash in &sequence_refs {
+        total_size += storage.get_size(hash)?;
+    }
+
+    let mut sorted_refs = sequence_refs.clone();
+    sorted_refs.sort();
+    let manifest_data: Vec<u8> = sorted_refs
+        .iter()
+        .flat_map(|h| h.as_bytes().iter())
+        .copied()
+        .collect();
+
+    Ok(ChunkManifest {
+        chunk_hash: SHA256Hash::compute(&manifest_data),
+        sequence_count: sequence_refs.len(),
+        sequence_refs,
+        taxon_ids: source.taxon_ids.clone(),
+        chunk_type: source.chunk_type.clone(),
+        total_size,
+        created_at: Utc::now(),
+        taxonomy_version: source.taxonomy_version,
+        sequence_version: source.sequence_version,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn db(source: &str, dataset: &str) -> ComposeExpr {
+        ComposeExpr::Database(DatabaseReference {
+            source: source.to_string(),
+            dataset: dataset.to_string(),
+            version: None,
+            profile: None,
+        })
+    }
+
+    fn taxon(name: &str) -> ComposeExpr {
+        ComposeExpr::Taxon(TaxonRef::Name(name.to_string()))
+    }
+
+    #[test]
+    fn test_intersection_binds_tighter() {
+        let expr = ComposeExpr::parse(
+            "uniprot/swissprot ∪ custom/lab-proteins − ncbi/refseq-protein ∩ taxon:Bacteria",
+        )
+        .unwrap();
+        assert_eq!(
+            expr,
+            ComposeExpr::Difference(
+                Box::new(ComposeExpr::Union(
+                    Box::new(db("uniprot", "swissprot")),
+                    Box::new(db("custom", "lab-proteins")),
+                )),
+                Box::new(ComposeExpr::Intersection(
+                    Box::new(db("ncbi", "refseq-protein")),
+                    Box::new(taxon("Bacteria")),
+                )),
+            )
+        );
+    }
+
+    #[test]
+    fn test_ascii_operators_match_unicode() {
+        let unicode = ComposeExpr::parse(
+            "(uniprot/swissprot ∪ custom/lab-proteins) − ncbi/refseq-protein ∩ taxon:Bacteria",
+        )
+        .unwrap();
+        let ascii = ComposeExpr::parse(
+            "(uniprot/swissprot|custom/lab-proteins) - ncbi/refseq-protein&taxon:Bacteria",
+        )
+        .unwrap();
+        assert_eq!(unicode, ascii);
+        let backslash = ComposeExpr::parse(
+            "uniprot/swissprot|custom/lab-proteins\\ncbi/refseq-protein&taxon:Bacteria",
+        )
+        .unwrap();
+        assert_eq!(unicode, backslash);
+    }
+
+    #[test]
+    fn test_operands() {
+        let expr = ComposeExpr::parse(
+            "uniprot/swissprot@2024_04 ∩ taxon:\"Escherichia coli\" − taxon:562",
+        )
+        .unwrap();
+        let ComposeExpr::Difference(left, right) = &expr else {
+            panic!("expected a difference: {:?}", expr);
+        };
+        assert_eq!(**right, ComposeExpr::Taxon(TaxonRef::Id(TaxonId(562))));
+        let ComposeExpr::Intersection(database, name) = &**left else {
+            panic!("expected an intersection: {:?}", left);
+        };
+        assert_eq!(**name, taxon("Escherichia coli"));
+        assert_eq!(expr.databases()[0].version.as_deref(), Some("2024_04"));
+        assert_eq!(
+            **database,
+            ComposeExpr::Database(DatabaseReference::parse("uniprot/swissprot@2024_04").unwrap())
+        );
+    }
+
+    #[test]
+    fn test_display_round_trips() {
+        for text in [
+            "uniprot/swissprot ∪ custom/lab-proteins − ncbi/refseq-protein ∩ taxon:Bacteria",
+            "uniprot/swissprot − (custom/a − custom/b)",
+            "(uniprot/swissprot ∪ custom/a) ∩ (taxon:Bacteria ∪ taxon:\"Homo sapiens\")",
+            "uniprot/swissprot@2024_04 ∩ taxon:2",
+        ] {
+            let expr = ComposeExpr::parse(text).unwrap();
+            assert_eq!(expr.to_string(), text);
+            assert_eq!(ComposeExpr::parse(&expr.to_string()).unwrap(), expr);
+        }
+    }
+
+    #[test]
+    fn test_taxa_only_narrow() {
+        for text in [
+            "taxon:Bacteria",
+            "uniprot/swissprot ∪ taxon:Bacteria",
+            "taxon:Bacteria − uniprot/swissprot",
+            "taxon:Bacteria ∩ taxon:Archaea",
+        ] {
+            assert!(
+                ComposeExpr::parse(text).is_err(),
+                "{} should be rejected",
+                text
+            );
+        }
+        for text in [
+            "taxon:Bacteria ∩ uniprot/swissprot",
+            "uniprot/swissprot − taxon:Bacteria",
+            "uniprot/swissprot ∩ (taxon:Bacteria − taxon:562)",
+        ] {
+            assert!(ComposeExpr::parse(text).is_ok(), "{} should parse", text);
+        }
+    }
+
+    #[test]
+    fn test_malformed_expressions() {
+        for text in [
+            "",
+            "uniprot/swissprot ∪",
+            "(uniprot/swissprot",
+            "uniprot/swissprot custom/a",
+            "swissprot",
+            "uniprot/swissprot:blast-30",
+            "uniprot/swissprot ∩ taxon:\"Escherichia coli",
+            "uniprot/swissprot ∩ taxon:",
+        ] {
+            assert!(
+                ComposeExpr::parse(text).is_err(),
+                "{:?} should be rejected",
+                text
+            );
+        }
+    }
+
+    /// root(1) > Bacteria(2) > E. coli(562), root(1) > Archaea(2157)
+    struct TestTaxonomy;
+
+    impl TaxonomyResolver for TestTaxonomy {
+        fn resolve_name(&self, name: &str) -> Vec<TaxonId> {
+            match name {
+                "Bacteria" => vec![TaxonId(2)],
+                "Archaea" => vec![TaxonId(2157)],
+                _ => Vec::new(),
+            }
+        }
+
+        fn lineage(&self, taxon_id: TaxonId) -> Vec<crate::taxonomy::filter::LineageEntry> {
+            let entry = |id: u32, name: &str| (TaxonId(id), name.to_string(), String::new());
+            match taxon_id.0 {
+                1 => vec![entry(1, "root")],
+                2 => vec![entry(1, "root"), entry(2, "Bacteria")],
+                562 => vec![
+                    entry(1, "root"),
+                    entry(2, "Bacteria"),
+                    entry(562, "Escherichia coli"),
+                ],
+                2157 => vec![entry(1, "root"), entry(2157, "Archaea")],
+                _ => Vec::new(),
+            }
+        }
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_compose_repository_databases() {
+        use crate::operations::{DatabaseDiffer, FastaAssembler};
+        use talaria_bio::sequence::Sequence;
+        use talaria_test::fixtures::test_database_source;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        std::env::set_var("TALARIA_HOME", temp_dir.path());
+
+        let mut manager = DatabaseManager::new(None).unwrap();
+        // Paths are resolved once per process, so the store may outlive the
+        // TempDir; clear the composed versions left by an earlier run
+        for dataset in [
+            "test_compose_union",
+            "test_compose_narrowed",
+            "test_compose_difference",
+        ] {
+            manager.delete_entire_database("custom", dataset).unwrap();
+        }
+        let mut add = |name: &str, entries: &[(&str, &str, u32)]| {
+            let sequences = entries
+                .iter()
+                .map(|(id, seq, taxon)| Sequence {
+                    id: id.to_string(),
+                    // Stored representations take their taxon from the header
+                    description: Some(format!("{} OX={}", name, taxon)),
+                    sequence: seq.as_bytes().to_vec(),
+                    taxon_id: Some(*taxon),
+                    taxonomy_sources: Default::default(),
+                })
+                .collect();
+            manager
+                .chunk_sequences_direct_with_progress_final(
+                    sequences,
+                    &test_database_source(name),
+                    None,
+                    true,
+                )
+                .unwrap();
+        };
+        // SHARED is E. coli in one database and Archaea in the other
+        add(
+            "compose_a",
+            &[
+                ("A1", "ACGTACGTACGTAAAA", 562),
+                ("A2", "GGGGCCCCGGGGCCCC", 2157),
+                ("SHARED", "TTTTAAAACCCCGGGG", 562),
+            ],
+        );
+        add(
+            "compose_b",
+            &[
+                ("SHARED", "TTTTAAAACCCCGGGG", 2157),
+                ("B1", "CATGCATGCATGCATG", 562),
+            ],
+        );
+
+        let compose = |dataset: &str, expression: &str| {
+            let expr = ComposeExpr::parse(expression).unwrap();
+            let report = compose_database(
+                &manager,
+                "custom",
+                dataset,
+                "v1",
+                &expr,
+                Some(&TestTaxonomy),
+            )
+            .unwrap();
+            let manifest = manager
+                .get_version_manifest("custom", dataset, "v1")
+                .unwrap();
+            assert_eq!(
+                report.sequences,
+                manifest
+                    .chunk_index
+                    .iter()
+                    .map(|c| c.sequence_count)
+                    .sum::<usize>()
+            );
+            manifest
+        };
+        let exported = |manifest: &TemporalManifest| -> HashSet<String> {
+            let hashes: Vec<SHA256Hash> = manifest.chunk_index.iter().map(|c| c.hash).collect();
+            FastaAssembler::new(&manager.get_repository().storage)
+                .assemble_from_chunks(&hashes)
+                .unwrap()
+                .into_iter()
+                .map(|seq| String::from_utf8(seq.sequence).unwrap())
+                .collect()
+        };
+        let set = |sequences: &[&str]| -> HashSet<String> {
+            sequences.iter().map(|s| s.to_string()).collect()
+        };
+
+        // Any recorded assignment counts, so SHARED is Archaea through compose_b
+        let union = compose(
+            "test_compose_union",
+            "custom/test_compose_a ∪ custom/test_compose_b − taxon:Archaea",
+        );
+        assert_eq!(
+            exported(&union),
+            set(&["ACGTACGTACGTAAAA", "CATGCATGCATGCATG"])
+        );
+
+        let narrowed = compose(
+            "test_compose_narrowed",
+            "custom/test_compose_a ∩ taxon:Bacteria",
+        );
+        assert_eq!(
+            exported(&narrowed),
+            set(&["ACGTACGTACGTAAAA", "TTTTAAAACCCCGGGG"])
+        );
+
+        let difference = compose(
+            "test_compose_difference",
+            "custom/test_compose_a − custom/test_compose_b",
+        );
+        assert_eq!(
+            exported(&difference),
+            set(&["ACGTACGTACGTAAAA", "GGGGCCCCGGGGCCCC"])
+        );
+
+        // The composed version diffs against its operand like any other version
+        let operand = manager
+            .get_version_manifest("custom", "test_compose_a", "current")
+            .unwrap();
+        let comparison = DatabaseDiffer::compare_manifests(
+            &operand,
+            &difference,
+            Some(&manager.get_repository().storage),
+            None,
+        )
+        .unwrap();
+        assert_eq!(comparison.sequence_analysis.total_sequences_a, 3);
+        assert_eq!(comparison.sequence_analysis.total_sequences_b, 2);
+        assert_eq!(comparison.sequence_analysis.shared_sequences, 2);
+        assert_eq!(comparison.sequence_analysis.unique_to_a, 1);
+        assert_eq!(comparison.sequence_analysis.unique_to_b, 0);
+
+        std::env::remove_var("TALARIA_HOME");
+    }
+}
@@ -0,0 +1,681 @@
+/// Reproducibility lockfiles
+///
+/// A lockfile pins everything an analysis depended on: the resolved database
+/// versions behind references such as `uniprot/swissprot@stable:blast-30`,
+/// their sequence and taxonomy coordinates and Merkle roots, the reduction
+/// profile parameters, the installed taxonomy and the current tool versions.
+/// Aliases move over time; a lockfile does not.
+use crate::database::DatabaseManager;
+use crate::operations::ReductionParameters;
+use crate::types::SHA256Hash;
+use crate::verification::{MerkleDAG, Verifier};
+use crate::TemporalManifest;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use talaria_core::types::DatabaseReference;
+use talaria_tools::{Tool, ToolManager};
+
+/// Default lockfile name in the analysis directory
+pub const LOCKFILE_NAME: &str = "talaria.lock";
+
+/// Lockfile format version, bumped on incompatible changes
+pub const LOCKFILE_FORMAT_VERSION: u32 = 1;
+
+/// Pinned state of an analysis
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnalysisLock {
+    pub format_version: u32,
+    pub created_at: DateTime<Utc>,
+    pub talaria_version: String,
+    /// Installed taxonomy version, if any
+    pub taxonomy_version: Option<String>,
+    #[serde(default, rename = "database")]
+    pub databases: Vec<LockedDatabase>,
+    #[serde(default, rename = "tool")]
+    pub tools: Vec<LockedTool>,
+}
+
+/// A database reference resolved to a concrete version
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockedDatabase {
+    /// Reference as given when locking (may contain aliases)
+    pub reference: String,
+    pub source: String,
+    pub dataset: String,
+    /// Resolved version timestamp
+    pub version: String,
+    pub sequence_version: String,
+    pub taxonomy_version: String,
+    pub sequence_time: Option<DateTime<Utc>>,
+    pub taxonomy_time: Option<DateTime<Utc>>,
+    pub sequence_root: String,
+    pub taxonomy_root: String,
+    /// Merkle root over the version's chunk hashes, recomputed on verify
+    pub chunk_root: Option<String>,
+    pub chunk_count: usize,
+    pub sequence_count: usize,
+    pub profile: Option<LockedProfile>,
+}
+
+/// Reduction profile used with a locked database
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockedProfile {
+    pub name: String,
+    pub reduction_id: String,
+    pub reduction_root: String,
+    pub parameters: ReductionParameters,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockedTool {
+    pub name: String,
+    pub version: String,
+}
+
+/// Difference between a lockfile and the local state
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LockMismatch {
+    /// What differs, e.g. `uniprot/swissprot@20240101_000000 sequence root`
+    pub subject: String,
+    pub expected: String,
+    pub found: String,
+}
+
+impl fmt::Display for LockMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: expected {}, found {}",
+            self.subject, self.expected, self.found
+        )
+    }
+}
+
+/// Outcome of restoring a lockfile
+#[derive(Debug, Clone, Default)]
+pub struct LockRestoreReport {
+    /// State that was switched to the locked version
+    pub restored: Vec<String>,
+    /// Locked state that is not available locally
+    pub missing: Vec<String>,
+    /// Chunks of locked versions downloaded from the remote
+    pub chunks_fetched: usize,
+}
+
+impl AnalysisLock {
+    /// Resolve `references` against the local repository and pin them
+    pub fn resolve(
+        manager: &DatabaseManager,
+        references: &[String],
+        tools: &ToolManager,
+    ) -> Result<Self> {
+        let databases = references
+            .iter()
+            .map(|reference| LockedDatabase::resolve(manager, reference))
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut locked_tools = Vec::new();
+        for (tool, _) in tools.list_all_tools()? {
+            if let Some(version) = tools.get_current_version(tool)? {
+                locked_tools.push(LockedTool {
+                    name: tool.name().to_string(),
+                    version,
+                });
+            }
+        }
+
+        Ok(Self {
+            format_version: LOCKFILE_FORMAT_VERSION,
+            created_at: Utc::now(),
+            talaria_version: env!("CARGO_PKG_VERSION").to_string(),
+            taxonomy_version: installed_taxonomy_version(),
+            databases,
+            tools: locked_tools,
+        })
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read lockfile {}", path.display()))?;
+        let lock: Self = toml::from_str(&contents)
+            .with_context(|| format!("Failed to parse lockfile {}", path.display()))?;
+        if lock.format_version > LOCKFILE_FORMAT_VERSION {
+            anyhow::bail!(
+                "Lockfile {} uses format version {}, this talaria supports up to {}",
+                path.display(),
+                lock.format_version,
+                LOCKFILE_FORMAT_VERSION
+            );
+        }
+        Ok(lock)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let contents = toml::to_string_pretty(self)?;
+        fs::write(path, contents)
+            .with_context(|| format!("Failed to write lockfile {}", path.display()))
+    }
+
+    /// Compare the lockfile against the local repository, taxonomy and tools
+    pub fn verify(&self, manager: &DatabaseManager, tools: &ToolManager) -> Vec<LockMismatch> {
+        let mut mismatches = Vec::new();
+
+        for locked in &self.databases {
+            locked.verify(manager, &mut mismatches);
+        }
+
+        if let Some(expected) = &self.taxonomy_version {
+            let found = installed_taxonomy_version();
+            if found.as_ref() != Some(expected) {
+                mismatches.push(LockMismatch {
+                    subject: "taxonomy version".to_string(),
+                    expected: expected.clone(),
+                    found: found.unwrap_or_else(|| "none".to_string()),
+                });
+            }
+        }
+
+        for locked in &self.tools {
+            let found = locked
+                .name
+                .parse::<Tool>()
+                .ok()
+                .and_then(|tool| tools.get_current_version(tool).ok().flatten());
+            if found.as_ref() != Some(&locked.version) {
+                mismatches.push(LockMismatch {
+                    subject: format!("tool {}", locked.name),
+                    expected: locked.version.clone(),
+                    found: found.unwrap_or_else(|| "not installed".to_string()),
+                });
+            }
+        }
+
+        mismatches
+    }
+
+    /// Switch the local state back to the locked versions
+    ///
+    /// Database `current` aliases, the current taxonomy and current tool
+    /// versions are repointed. Chunks of a locked version that are missing
+    /// locally are downloaded from the configured remote first (see
+    /// [`HeraldStorage::fetch_missing_chunks`]); versions, taxonomies and
+    /// tools are not downloaded, and locked state that is still not available
+    /// is reported as missing. A database version whose content no longer
+    /// matches the lockfile is an error.
+    ///
+    /// [`HeraldStorage::fetch_missing_chunks`]: crate::storage::HeraldStorage::fetch_missing_chunks
+    pub fn restore(
+        &self,
+        manager: &DatabaseManager,
+        tools: &ToolManager,
+    ) -> Result<LockRestoreReport> {
+        let mut report = LockRestoreReport::default();
+
+        for locked in &self.databases {
+            let label = locked.label();
+            if let Ok(manifest) =
+                manager.get_version_manifest(&locked.source, &locked.dataset, &locked.version)
+            {
+                let chunks: Vec<SHA256Hash> = manifest.chunk_index.iter().map(|c| c.hash).collect();
+                match manager
+                    .get_repository()
+                    .storage
+                    .fetch_missing_chunks(&chunks)
+                {
+                    Ok(fetched) => report.chunks_fetched += fetched,
+                    Err(e) => tracing::warn!("Could not fetch chunks of {}: {}", label, e),
+                }
+            }
+
+            let mut mismatches = Vec::new();
+            locked.verify(manager, &mut mismatches);
+
+            if mismatches.iter().any(|m| m.found == "missing") {
+                report.missing.push(label);
+                continue;
+            }
+            if let Some(mismatch) = mismatches.first() {
+                anyhow::bail!("Cannot restore {}: {}", label, mismatch);
+            }
+            manager.set_version_alias(
+                &locked.source,
+                &locked.dataset,
+                &locked.version,
+                "current",
+            )?;
+            report.restored.push(label);
+        }
+
+        if let Some(version) = &self.taxonomy_version {
+            let label = format!("taxonomy@{}", version);
+            if installed_taxonomy_version().as_ref() == Some(version) {
+                report.restored.push(label);
+            } else if talaria_core::system::paths::talaria_taxonomy_version_dir(version).exists() {
+                set_current_taxonomy_version(version)?;
+                report.restored.push(label);
+            } else {
+                report.missing.push(label);
+            }
+        }
+
+        for locked in &self.tools {
+            let label = format!("{}@{}", locked.name, locked.version);
+            let tool: Tool = locked.name.parse()?;
+            let installed = tools
+                .list_versions(tool)?
+                .iter()
+                .any(|info| info.version == locked.version);
+            if installed {
+                tools.set_current_version(tool, &locked.version)?;
+                report.restored.push(label);
+            } else {
+                report.missing.push(label);
+            }
+        }
+
+        Ok(report)
+    }
+}
+
+impl LockedDatabase {
+    /// Resolve a `source/dataset[@version][:profile]` reference
+    pub fn resolve(manager: &DatabaseManager, reference: &str) -> Result<Self> {
+        let db_ref = DatabaseReference::parse(reference)?;
+        let version = manager
+            .resolve_version_reference(&db_ref.source, &db_ref.dataset, db_ref.version_or_default())
+            .with_context(|| format!("Failed to resolve {}", reference))?;
+        let manifest = manager.get_version_manifest(&db_ref.source, &db_ref.dataset, &version)?;
+
+        let profile = match &db_ref.profile {
+            Some(name) => {
+                let reduction = manager
+                    .get_repository()
+                    .storage
+                    .get_database_reduction_by_profile(
+                        &db_ref.source,
+                        &db_ref.dataset,
+                        &version,
+                        name,
+                    )?
+                    .with_context(|| {
+                        format!("Reduction profile '{}' not found for {}", name, reference)
+                    })?;
+                Some(LockedProfile {
+                    name: name.clone(),
+                    reduction_id: reduction.reduction_id.to_hex(),
+                    reduction_root: reduction.reduction_merkle_root.to_hex(),
+                    parameters: reduction.parameters,
+                })
+            }
+            None => None,
+        };
+
+        let (sequence_time, taxonomy_time) = match &manifest.temporal_coordinate {
+            Some(coordinate) => (
+                Some(coordinate.sequence_time),
+                Some(coordinate.taxonomy_time),
+            ),
+            None => (None, None),
+        };
+
+        Ok(Self {
+            reference: reference.to_string(),
+            source: db_ref.source,
+            dataset: db_ref.dataset,
+            version,
+            sequence_version: manifest.sequence_version.clone(),
+            taxonomy_version: manifest.taxonomy_version.clone(),
+            sequence_time,
+            taxonomy_time,
+            sequence_root: manifest.sequence_root.to_hex(),
+            taxonomy_root: manifest.taxonomy_root.to_hex(),
+            chunk_root: chunk_root(&manifest)?,
+            chunk_count: manifest.chunk_index.len(),
+            sequence_count: manifest.chunk_index.iter().map(|c| c.sequence_count).sum(),
+            profile,
+        })
+    }
+
+    /// `source/dataset@version[:profile]` of the pinned state
+    pub fn label(&self) -> String {
+        let mut label = format!("{}/{}@{}", self.source, self.dataset, self.version);
+        if let Some(profile) = &self.profile {
+            label.push(':');
+            label.push_str(&profile.name);
+        }
+        label
+    }
+
+    fn verify(&self, manager: &DatabaseManager, mismatches: &mut Vec<LockMismatch>) {
+        let label = self.label();
+        let mut check = |what: &str, expected: String, found: String| {
+            if expected != found {
+                mismatches.push(LockMismatch {
+                    subject: format!("{} {}", label, what),
+                    expected,
+                    found,
+                });
+            }
+        };
+
+        let manifest =
+            match manager.get_version_manifest(&self.source, &self.dataset, &self.version) {
+                Ok(manifest) => manifest,
+                Err(_) => {
+                    check("version", self.version.clone(), "missing".to_string());
+                    return;
+                }
+            };
+
+        check(
+            "sequence root",
+            self.sequence_root.clone(),
+            manifest.sequence_root.to_hex(),
+        );
+        check(
+            "taxonomy root",
+            self.taxonomy_root.clone(),
+            manifest.taxonomy_root.to_hex(),
+        );
+        let found_root = match chunk_root(&manifest) {
+            Ok(root) => root.unwrap_or_default(),
+            Err(e) => format!("invalid ({})", e),
+        };
+        check(
+            "chunk root",
+            self.chunk_root.clone().unwrap_or_default(),
+            found_root,
+        );
+        check(
+            "chunk count",
+            self.chunk_count.to_string(),
+            manifest.chunk_index.len().to_string(),
+        );
+
+        // The root only pins the chunk hashes; the chunks must still match them
+        let storage = &manager.get_repository().storage;
+        let verifier = Verifier::new(storage, &manifest);
+        let indexed: Vec<SHA256Hash> = manifest.chunk_index.iter().map(|c| c.hash).collect();
+        let missing: HashSet<SHA256Hash> = storage.missing_chunks(&indexed).into_iter().collect();
+        for chunk in &manifest.chunk_index {
+            let found = if missing.contains(&chunk.hash) {
+                "missing"
+            } else if verifier.verify_chunk(&chunk.hash).is_err() {
+                "corrupted"
+            } else {
+                continue;
+            };
+            check(
+                &format!("chunk {}", chunk.hash),
+                "present".to_string(),
+                found.to_string(),
+            );
+        }
+
+        let Some(profile) = &self.profile else {
+            return;
+        };
+        let reduction = manager
+            .get_repository()
+            .storage
+            .get_database_reduction_by_profile(
+                &self.source,
+                &self.dataset,
+                &self.version,
+                &profile.name,
+            )
+            .ok()
+            .flatten();
+        match reduction {
+            Some(reduction) => {
+                check(
+                    "reduction root",
+                    profile.reduction_root.clone(),
+                    reduction.reduction_merkle_root.to_hex(),
+                );
+                let expected = serde_json::to_string(&profile.parameters).unwrap_or_default();
+                let found = serde_json::to_string(&reduction.parameters).unwrap_or_default();
+                check("reduction parameters", expected, found);
+            }
+            None => check("profile", profile.name.clone(), "missing".to_string()),
+        }
+    }
+}
+
+/// Merkle root over the chunk index of `manifest`
+///
+/// Computed rather than read from the manifest, which not every ingest path
+/// fills in.
+fn chunk_root(manifest: &TemporalManifest) -> Result<Option<String>> {
+    let dag = MerkleDAG::build_from_items(manifest.chunk_index.clone())?;
+    Ok(dag.root_hash().map(|root| root.to_hex()))
+}
+
+fn installed_taxonomy_version() -> Option<String> {
+    let current = talaria_core::system::paths::talaria_taxonomy_current_dir();
+    current
+        .exists()
+        .then(DatabaseManager::current_taxonomy_version)
+}
+
+/// Repoint the taxonomy `current` link at an installed version
+fn set_current_taxonomy_version(version: &str) -> Result<()> {
+    let current_link = talaria_core::system::paths::talaria_taxonomy_current_dir();
+    if current_link.exists() || current_link.is_symlink() {
+        fs::remove_file(&current_link)?;
+    }
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(version, &current_link)?;
+    #[cfg(windows)]
+    fs::write(&current_link, version)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lockfile_roundtrip() {
+        let lock = AnalysisLock {
+            format_version: LOCKFILE_FORMAT_VERSION,
+            created_at: Utc::now(),
+            talaria_version: "0.1.0".to_string(),
+            taxonomy_version: Some("20240101_000000".to_string()),
+            databases: vec![LockedDatabase {
+                reference: "uniprot/swissprot@stable:blast-30".to_string(),
+                source: "uniprot".to_string(),
+                dataset: "swissprot".to_string(),
+                version: "20240101_000000".to_string(),
+                sequence_version: "20240101_000000".to_string(),
+                taxonomy_version: "20240101_000000".to_string(),
+                sequence_time: Some(Utc::now()),
+                taxonomy_time: None,
+                sequence_root: "ab".repeat(32),
+                taxonomy_root: "cd".repeat(32),
+                chunk_root: None,
+                chunk_count: 12,
+                sequence_count: 3400,
+                profile: Some(LockedProfile {
+                    name: "blast-30".to_string(),
+                    reduction_id: "ef".repeat(32),
+                    reduction_root: "01".repeat(32),
+                    parameters: ReductionParameters::default(),
+                }),
+            }],
+            tools: vec![LockedTool {
+                name: "lambda".to_string(),
+                version: "3.0.0".to_string(),
+            }],
+        };
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join(LOCKFILE_NAME);
+        lock.save(&path).unwrap();
+        let loaded = AnalysisLock::load(&path).unwrap();
+
+        assert_eq!(loaded.databases.len(), 1);
+        assert_eq!(
+            loaded.databases[0].label(),
+            "uniprot/swissprot@20240101_000000:blast-30"
+        );
+        assert_eq!(
+            loaded.databases[0].sequence_root,
+            lock.databases[0].sequence_root
+        );
+        assert_eq!(loaded.tools, lock.tools);
+        assert_eq!(loaded.taxonomy_version, lock.taxonomy_version);
+    }
+
+    /// Repository under `dir` whose current version of custom/test_lock holds `sequences`
+    fn repository_with(dir: &Path, sequences: &[(&str, &str)]) -> DatabaseManager {
+        use talaria_bio::sequence::Sequence;
+        use talaria_test::fixtures::test_database_source;
+
+        let mut manager =
+            DatabaseManager::new(Some(dir.join("repo").to_string_lossy().into_owned())).unwrap();
+        let sequences = sequences
+            .iter()
+            .map(|(id, seq)| Sequence {
+                id: id.to_string(),
+                description: Some("lock".to_string()),
+                sequence: seq.as_bytes().to_vec(),
+                taxon_id: Some(562),
+                taxonomy_sources: Default::default(),
+            })
+            .collect();
+        manager
+            .chunk_sequences_direct_with_progress_final(
+                sequences,
+                &test_database_source("lock"),
+                None,
+                true,
+            )
+            .unwrap();
+        manager
+    }
+
+    fn database_lock(manager: &DatabaseManager) -> AnalysisLock {
+        AnalysisLock {
+            format_version: LOCKFILE_FORMAT_VERSION,
+            created_at: Utc::now(),
+            talaria_version: "0.1.0".to_string(),
+            taxonomy_version: None,
+            databases: vec![LockedDatabase::resolve(manager, "custom/test_lock").unwrap()],
+            tools: Vec::new(),
+        }
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_verify_detects_missing_and_tampered_chunks() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::env::set_var("TALARIA_HOME", dir.path());
+        let tools = ToolManager::with_directory(dir.path().join("tools"));
+
+        let manager = repository_with(
+            dir.path(),
+            &[
+                ("SEQ_001", "ACGTACGTACGTAAAA"),
+                ("SEQ_002", "GGGGCCCCGGGGCCCC"),
+            ],
+        );
+        let lock = database_lock(&manager);
+        let manifest = manager
+            .get_version_manifest("custom", "test_lock", "current")
+            .unwrap();
+        assert_eq!(lock.databases[0].chunk_root, chunk_root(&manifest).unwrap());
+        assert!(lock.databases[0].chunk_root.is_some());
+        assert!(lock.verify(&manager, &tools).is_empty());
+
+        let chunks = manager.get_repository().storage.chunk_storage();
+        let hash = manifest.chunk_index[0].hash;
+        let original = manager.get_repository().storage.get_chunk(&hash).unwrap();
+
+        chunks.store_chunk(&hash, b"tampered").unwrap();
+        let mismatches = lock.verify(&manager, &tools);
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(
+            mismatches[0].subject,
+            format!("{} chunk {}", lock.databases[0].label(), hash)
+        );
+        assert_eq!(mismatches[0].found, "corrupted");
+        assert!(lock.restore(&manager, &tools).is_err());
+
+        chunks.delete_chunk(&hash).unwrap();
+        let mismatches = lock.verify(&manager, &tools);
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].found, "missing");
+        let report = lock.restore(&manager, &tools).unwrap();
+        assert_eq!(report.missing, vec![lock.databases[0].label()]);
+
+        // Restoring fetches the missing chunk from the configured remote
+        let remote = dir.path().join("remote");
+        let remote_path = remote.join(crate::remote::remote_chunk_key(&hash));
+        fs::create_dir_all(remote_path.parent().unwrap()).unwrap();
+        fs::write(&remote_path, &original).unwrap();
+        std::env::set_var(
+            "TALARIA_REMOTE_REPO",
+            format!("file://{}", remote.display()),
+        );
+        let report = lock.restore(&manager, &tools).unwrap();
+        std::env::remove_var("TALARIA_REMOTE_REPO");
+        assert_eq!(report.chunks_fetched, 1);
+        assert_eq!(report.restored, vec![lock.databases[0].label()]);
+        assert!(lock.verify(&manager, &tools).is_empty());
+
+        std::env::remove_var("TALARIA_HOME");
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_restore_repoints_current_version() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::env::set_var("TALARIA_HOME", dir.path());
+        let tools = ToolManager::with_directory(dir.path().join("tools"));
+
+        let mut manager = repository_with(dir.path(), &[("SEQ_001", "ACGTACGTACGTAAAA")]);
+        let lock = database_lock(&manager);
+        let locked = lock.databases[0].version.clone();
+
+        // Versions are second-resolution timestamps
+        std::thread::sleep(std::time::Duration::from_secs(1));
+        manager
+            .chunk_sequences_direct_with_progress_final(
+                vec![talaria_bio::sequence::Sequence {
+                    id: "SEQ_002".to_string(),
+                    description: Some("lock".to_string()),
+                    sequence: b"GGGGCCCCGGGGCCCC".to_vec(),
+                    taxon_id: Some(562),
+                    taxonomy_sources: Default::default(),
+                }],
+                &talaria_test::fixtures::test_database_source("lock"),
+                None,
+                true,
+            )
+            .unwrap();
+        let current = |manager: &DatabaseManager| {
+            manager
+                .resolve_version_reference("custom", "test_lock", "current")
+                .unwrap()
+        };
+        assert_ne!(current(&manager), locked);
+
+        // The newer version pins different chunks
+        let newer = database_lock(&manager);
+        assert_ne!(newer.databases[0].chunk_root, lock.databases[0].chunk_root);
+
+        let report = lock.restore(&manager, &tools).unwrap();
+        assert_eq!(report.restored, vec![lock.databases[0].label()]);
+        assert!(report.missing.is_empty());
+        assert_eq!(current(&manager), locked);
+        assert!(lock.verify(&manager, &tools).is_empty());
+
+        std::env::remove_var("TALARIA_HOME");
+    }
+}
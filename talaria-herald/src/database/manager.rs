@@ -1,7 +1,9 @@
 use super::{DownloadResult, TaxonomyUpdateResult};
+use crate::database::compose::CompositionRecord;
 use crate::download::manager::{DownloadManager, DownloadOptions};
 use crate::download::workspace::{find_existing_workspace_for_source, DownloadState, Stage};
 use crate::download::{parse_database_source, DownloadProgress};
+use crate::processing::{ContaminationReport, PipelineRecord};
 use crate::taxonomy::{TaxonomyManager, VersionDecision};
 /// Database manager using content-addressed storage
 ///
@@ -34,7 +36,7 @@ pub struct DatabaseStats {
 
 pub struct DatabaseManager {
     repository: HeraldRepository,
-    base_path: PathBuf,
+    pub(crate) base_path: PathBuf,
     #[allow(dead_code)]
     use_json_manifest: bool,
     _taxonomy_manager: TaxonomyManager,
@@ -43,7 +45,7 @@ pub struct DatabaseManager {
     /// Current version being processed (set once at start)
     current_version: Option<String>,
     /// Metadata cache for expensive queries
-    cache: Option<std::sync::Arc<crate::database::cache::MetadataCache>>,
+    pub(crate) cache: Option<std::sync::Arc<crate::database::cache::MetadataCache>>,
 }
 
 /// Structure for storing partial manifests in RocksDB
@@ -162,6 +164,166 @@ impl DatabaseManager {
         Ok(rocksdb.get_manifest(&current_alias_key)?.is_some())
     }
 
+    /// Chunking strategy for a database
+    ///
+    /// A per-database override saved with `set_chunking_strategy` wins, then
+    /// the `[chunking]` section of the user config, then the built-in defaults.
+    pub fn chunking_strategy(&self, source: &DatabaseSource) -> Result<ChunkingStrategy> {
+        let rocksdb = self.get_repository().storage.sequence_storage.get_rocksdb();
+        if let Some(data) = rocksdb.get_manifest(&self.chunking_key(source))? {
+            return serde_json::from_slice(&data)
+                .context("Invalid chunking override stored for database");
+        }
+        ChunkingStrategy::from_user_config().context("Invalid [chunking] configuration")
+    }
+
+    /// Save a chunking override used by every later import and update of a database
+    pub fn set_chunking_strategy(
+        &self,
+        source: &DatabaseSource,
+        strategy: &ChunkingStrategy,
+    ) -> Result<()> {
+        strategy.validate()?;
+        let rocksdb = self.get_repository().storage.sequence_storage.get_rocksdb();
+        rocksdb.put_manifest(&self.chunking_key(source), &serde_json::to_vec(strategy)?)?;
+        Ok(())
+    }
+
+    /// Chunker for a file chunked batch by batch
+    ///
+    /// Content-defined cuts are deferred to a spool shared by every chunker of
+    /// the database (see `TaxonomicChunker::spool_content_defined`), so they
+    /// come out the same as when the whole file is chunked at once.
+    pub(crate) fn streaming_chunker(
+        &self,
+        strategy: ChunkingStrategy,
+        source: &DatabaseSource,
+    ) -> TaxonomicChunker {
+        let (source_name, dataset) = self.get_source_dataset_names(source);
+        let mut chunker = TaxonomicChunker::new(
+            strategy,
+            Arc::clone(&self.get_repository().storage.sequence_storage),
+            source.clone(),
+        );
+        chunker.set_quiet_mode(true);
+        chunker.spool_content_defined(format!("chunking_spool:{}:{}:", source_name, dataset));
+        chunker
+    }
+
+    fn chunking_key(&self, source: &DatabaseSource) -> String {
+        let (source_name, dataset) = self.get_source_dataset_names(source);
+        format!("chunking:{}:{}", source_name, dataset)
+    }
+
+    /// Record the processing pipeline a database version was built with
+    pub fn set_version_pipeline(
+        &self,
+        source: &str,
+        dataset: &str,
+        version: &str,
+        record: &PipelineRecord,
+    ) -> Result<()> {
+        let rocksdb = self.get_repository().storage.sequence_storage.get_rocksdb();
+        rocksdb.put_manifest(
+            &Self::pipeline_key(source, dataset, version),
+            &serde_json::to_vec(record)?,
+        )?;
+        Ok(())
+    }
+
+    /// Processing pipeline recorded for a database version, if any
+    pub fn version_pipeline(
+        &self,
+        source: &str,
+        dataset: &str,
+        version: &str,
+    ) -> Result<Option<PipelineRecord>> {
+        let rocksdb = self.get_repository().storage.sequence_storage.get_rocksdb();
+        rocksdb
+            .get_manifest(&Self::pipeline_key(source, dataset, version))?
+            .map(|data| {
+                serde_json::from_slice(&data).context("Invalid pipeline record stored for version")
+            })
+            .transpose()
+    }
+
+    fn pipeline_key(source: &str, dataset: &str, version: &str) -> String {
+        format!("pipeline:{}:{}:{}", source, dataset, version)
+    }
+
+    /// Record the contamination findings of a version's screening steps
+    pub fn set_version_screening(
+        &self,
+        source: &str,
+        dataset: &str,
+        version: &str,
+        report: &ContaminationReport,
+    ) -> Result<()> {
+        let rocksdb = self.get_repository().storage.sequence_storage.get_rocksdb();
+        rocksdb.put_manifest(
+            &Self::screening_key(source, dataset, version),
+            &serde_json::to_vec(report)?,
+        )?;
+        Ok(())
+    }
+
+    /// Contamination findings recorded for a database version, if screened
+    pub fn version_screening(
+        &self,
+        source: &str,
+        dataset: &str,
+        version: &str,
+    ) -> Result<Option<ContaminationReport>> {
+        let rocksdb = self.get_repository().storage.sequence_storage.get_rocksdb();
+        rocksdb
+            .get_manifest(&Self::screening_key(source, dataset, version))?
+            .map(|data| {
+                serde_json::from_slice(&data).context("Invalid screening report stored for version")
+            })
+            .transpose()
+    }
+
+    fn screening_key(source: &str, dataset: &str, version: &str) -> String {
+        format!("screen:{}:{}:{}", source, dataset, version)
+    }
+
+    /// Record how a virtual database version was composed
+    pub fn set_version_composition(
+        &self,
+        source: &str,
+        dataset: &str,
+        version: &str,
+        record: &CompositionRecord,
+    ) -> Result<()> {
+        let rocksdb = self.get_repository().storage.sequence_storage.get_rocksdb();
+        rocksdb.put_manifest(
+            &Self::composition_key(source, dataset, version),
+            &serde_json::to_vec(record)?,
+        )?;
+        Ok(())
+    }
+
+    /// Composition of a virtual database version, `None` for ordinary versions
+    pub fn version_composition(
+        &self,
+        source: &str,
+        dataset: &str,
+        version: &str,
+    ) -> Result<Option<CompositionRecord>> {
+        let rocksdb = self.get_repository().storage.sequence_storage.get_rocksdb();
+        rocksdb
+            .get_manifest(&Self::composition_key(source, dataset, version))?
+            .map(|data| {
+                serde_json::from_slice(&data)
+                    .context("Invalid composition record stored for version")
+            })
+            .transpose()
+    }
+
+    fn composition_key(source: &str, dataset: &str, version: &str) -> String {
+        format!("compose:{}:{}:{}", source, dataset, version)
+    }
+
     /// Check for updates without downloading (dry-run mode)
     pub async fn check_for_updates(
         &mut self,
@@ -691,7 +853,7 @@ impl DatabaseManager {
 
     /// Handle initial download when no local manifest exists
     /// Check if the database being downloaded is taxonomy data itself
-    fn is_taxonomy_database(source: &DatabaseSource) -> bool {
+    pub fn is_taxonomy_database(source: &DatabaseSource) -> bool {
         matches!(
             source,
             DatabaseSource::UniProt(UniProtDatabase::IdMapping)
@@ -1444,7 +1606,7 @@ impl DatabaseManager {
 
         // Create chunker with canonical storage
         let mut chunker = TaxonomicChunker::new(
-            ChunkingStrategy::default(),
+            self.chunking_strategy(source)?,
             sequence_storage,
             source.clone(),
         );
@@ -1916,7 +2078,7 @@ impl DatabaseManager {
             version: version.to_string(),
             created_at: now,
             sequence_version: version.to_string(),
-            taxonomy_version: "current".to_string(), // Will be updated when taxonomy is loaded
+            taxonomy_version: Self::current_taxonomy_version(),
             temporal_coordinate: Some(BiTemporalCoordinate {
                 sequence_time: now,
                 taxonomy_time: now,
@@ -2123,14 +2285,16 @@ impl DatabaseManager {
             version: version.to_string(),
             created_at: Utc::now(),
             sequence_version: version.to_string(),
-            taxonomy_version: "current".to_string(),
+            taxonomy_version: Self::current_taxonomy_version(),
             temporal_coordinate: Some(BiTemporalCoordinate {
                 sequence_time: Utc::now(),
                 taxonomy_time: Utc::now(),
             }),
             taxonomy_root: crate::SHA256Hash::zero(),
             sequence_root: crate::SHA256Hash::zero(),
-            chunk_merkle_tree: None,
+            chunk_merkle_tree: crate::types::SerializedMerkleTree::from_chunk_index(
+                &chunk_metadata,
+            )?,
             taxonomy_manifest_hash: crate::SHA256Hash::zero(),
             taxonomy_dump_version: "current".to_string(),
             source_database: Some(format!("{}/{}", source_name, dataset_name)),
@@ -2248,7 +2412,7 @@ impl DatabaseManager {
     }
 
     /// Internal implementation of store_chunk_manifests
-    fn store_chunk_manifests_internal(
+    pub(crate) fn store_chunk_manifests_internal(
         &mut self,
         manifests: Vec<crate::ChunkManifest>,
         _source: &DatabaseSource,
@@ -2616,7 +2780,7 @@ impl DatabaseManager {
     /// Original chunking logic (kept for reference but not used)
 
     /// Create version metadata file with upstream version detection
-    fn create_version_metadata(
+    pub(crate) fn create_version_metadata(
         &self,
         source: &DatabaseSource,
         timestamp: &str,
@@ -2822,7 +2986,7 @@ impl DatabaseManager {
     /// Check if a database manifest exists in RocksDB (by DatabaseSource)
     ///
     /// Also performs automatic repair if database versions exist but current alias is missing
-    fn has_database_by_source(&self, source: &DatabaseSource) -> Result<bool> {
+    pub(crate) fn has_database_by_source(&self, source: &DatabaseSource) -> Result<bool> {
         let (source_name, dataset) = self.get_source_dataset_names(source);
         let rocksdb = self.get_repository().storage.sequence_storage.get_rocksdb();
 
@@ -2894,7 +3058,7 @@ impl DatabaseManager {
     }
 
     /// Get source and dataset names for directory structure
-    fn get_source_dataset_names(&self, source: &DatabaseSource) -> (String, String) {
+    pub(crate) fn get_source_dataset_names(&self, source: &DatabaseSource) -> (String, String) {
         use talaria_core::{NCBIDatabase, UniProtDatabase};
 
         match source {
@@ -3791,6 +3955,47 @@ impl DatabaseManager {
         Ok(())
     }
 
+    /// Register a version manifest brought in from another repository
+    ///
+    /// `manifest_bytes` are stored as received so the manifest hash matches
+    /// the source. The version only becomes `current` if the database has
+    /// none yet; returns whether it did.
+    pub fn import_version_manifest(
+        &self,
+        source_name: &str,
+        dataset_name: &str,
+        version: &str,
+        manifest: &TemporalManifest,
+        manifest_bytes: &[u8],
+    ) -> Result<bool> {
+        let rocksdb = self.get_repository().storage.sequence_storage.get_rocksdb();
+        let current_alias_key = format!("alias:{}:{}:current", source_name, dataset_name);
+        let has_current = rocksdb.get_manifest(&current_alias_key)?.is_some();
+
+        if !has_current {
+            let chunk_count = manifest.chunk_index.len();
+            let sequence_count = manifest.chunk_index.iter().map(|c| c.sequence_count).sum();
+            let total_size = manifest.chunk_index.iter().map(|c| c.size).sum();
+            self.save_manifest_to_repository(
+                source_name,
+                dataset_name,
+                version,
+                manifest,
+                chunk_count,
+                sequence_count,
+                total_size,
+            )?;
+        }
+        let manifest_key = format!("manifest:{}:{}:{}", source_name, dataset_name, version);
+        rocksdb.put_manifest(&manifest_key, manifest_bytes)?;
+
+        if let Some(cache) = &self.cache {
+            cache.invalidate_database(source_name, dataset_name);
+        }
+
+        Ok(!has_current)
+    }
+
     /// Update database metadata cache with a new reduction profile
     /// This ensures the profile shows up in database list/info immediately
     pub fn add_reduction_profile_to_metadata(
@@ -4433,7 +4638,7 @@ impl DatabaseManager {
     }
 
     /// Stream-process FASTA file with true parallel pipeline
-    fn chunk_database_streaming(
+    pub(crate) fn chunk_database_streaming(
         &mut self,
         file_path: &Path,
         source: &DatabaseSource,
@@ -4451,6 +4656,13 @@ impl DatabaseManager {
         use talaria_bio::sequence::Sequence;
         use talaria_utils::display::{format_bytes, format_number};
 
+        let strategy = self.chunking_strategy(source)?;
+        // Finishes the content-defined cuts deferred by the workers' chunkers
+        let spool = self.streaming_chunker(strategy.clone(), source);
+        if download_state.sequences_processed == 0 {
+            spool.clear_spool()?;
+        }
+
         let file = File::open(file_path)?;
         let file_size = file.metadata()?.len();
 
@@ -4603,8 +4815,6 @@ impl DatabaseManager {
 
         // Clone things for the worker threads
         let num_workers = num_cpus::get();
-        let storage_for_workers = Arc::clone(&sequence_storage);
-        let source_for_workers = source.clone();
         let _version_for_workers = version.clone();
         let _base_path_for_workers = self.base_path.clone();
         let processed_for_workers = Arc::clone(&processed_counter);
@@ -4612,26 +4822,22 @@ impl DatabaseManager {
         let sequences_processed_for_workers = Arc::clone(&sequences_processed);
         let processing_progress_for_workers = Arc::clone(&processing_progress);
         let chunking_progress_for_workers = Arc::clone(&chunking_progress);
+        let strategy_for_workers = strategy;
 
         // Spawn worker threads pool
         let mut workers = vec![];
         for _worker_id in 0..num_workers {
             let receiver = Arc::clone(&batch_receiver);
-            let storage = Arc::clone(&storage_for_workers);
-            let source = source_for_workers.clone();
             let result_tx = result_sender.clone();
             let processed = Arc::clone(&processed_for_workers);
             let total_seq = Arc::clone(&total_for_progress);
             let seq_processed = Arc::clone(&sequences_processed_for_workers);
             let proc_progress = Arc::clone(&processing_progress_for_workers);
             let chunk_progress = Arc::clone(&chunking_progress_for_workers);
+            // Each worker gets its own chunker
+            let mut chunker = self.streaming_chunker(strategy_for_workers.clone(), source);
 
             let worker = thread::spawn(move || {
-                // Each worker gets its own chunker
-                let mut chunker =
-                    TaxonomicChunker::new(ChunkingStrategy::default(), storage, source.clone());
-                chunker.set_quiet_mode(true); // Quiet for parallel processing
-
                 loop {
                     // Get next batch to process
                     let batch = {
@@ -4914,10 +5120,43 @@ impl DatabaseManager {
 
         // Get collector results (batch count and total chunks)
         // Propagate any errors that occurred during manifest saving
-        let (batch_count, total_chunks) = collector
+        let (mut batch_count, mut total_chunks) = collector
             .join()
             .map_err(|e| anyhow::anyhow!("Collector thread panicked: {:?}", e))??;
 
+        // Content-defined cuts, now that every taxon is complete
+        const SPOOLED_PARTIAL_CHUNKS: usize = 10_000;
+        let rocksdb = sequence_storage.get_rocksdb();
+        let chunk_storage = self.repository.storage.chunk_storage();
+        let mut next_batch = processed_counter.load(Ordering::Relaxed);
+        let mut buffered = Vec::new();
+        let mut save_buffered = |buffered: &mut Vec<(crate::ChunkManifest, SHA256Hash)>| {
+            next_batch += 1;
+            batch_count += 1;
+            total_chunks += buffered.len();
+            Self::save_partial_manifest_static(
+                &rocksdb,
+                Some(&chunk_storage),
+                next_batch,
+                std::mem::take(buffered),
+                source,
+                &version,
+            )
+        };
+        spool.finish_spooled(|manifests| {
+            for manifest in manifests {
+                let hash = SHA256Hash::compute(&rmp_serde::to_vec(&manifest)?);
+                buffered.push((manifest, hash));
+            }
+            if buffered.len() >= SPOOLED_PARTIAL_CHUNKS {
+                save_buffered(&mut buffered)?;
+            }
+            Ok(())
+        })?;
+        if !buffered.is_empty() {
+            save_buffered(&mut buffered)?;
+        }
+
         // CRITICAL VALIDATION: Ensure work was actually done
         // This catches the "skip all sequences → 0 chunks" resume bug
         if batch_count == 0 && total_chunks == 0 && final_total > 0 {
@@ -5160,96 +5399,67 @@ impl DatabaseManager {
     /// - Historical sequence versions
     /// - Taxonomy updates over time
     /// - Reproducible analyses at specific time points
+    ///
+    /// All matching sequences are loaded into memory; use `chunks_at_time`
+    /// and load the chunks in batches for large results.
     pub fn query_at_time(
         &self,
         sequence_time: chrono::DateTime<chrono::Utc>,
         taxonomy_time: chrono::DateTime<chrono::Utc>,
         taxon_ids: Option<Vec<u32>>,
     ) -> Result<Vec<talaria_bio::sequence::Sequence>> {
-        // Use bi-temporal index to query at specific time
-        let bi_temporal =
-            crate::temporal::BiTemporalDatabase::new(Arc::new(self.repository.storage.clone()))?;
-
-        // Query at the specified temporal coordinate
-        let coordinate = crate::types::BiTemporalCoordinate {
-            sequence_time: sequence_time,
-            taxonomy_time: taxonomy_time,
-        };
-
-        // Note: bi_temporal needs to be mutable for query_at
-        let mut bi_temporal = bi_temporal;
-        let snapshot = bi_temporal.query_at(sequence_time, taxonomy_time)?;
-
-        // Get the manifest from the snapshot
-        let manifest = &snapshot.manifest;
-
-        // Store bi-temporal index in RocksDB for future queries
-        let backend = self.repository.storage.chunk_storage();
-        let index_key = format!(
-            "bitemporal:{}:{}",
-            sequence_time.timestamp(),
-            taxonomy_time.timestamp()
-        );
-
-        if let Some(cf) = backend.db.cf_handle("temporal") {
-            // Serialize and store the manifest data for fast retrieval
-            if let Some(temporal_manifest) = snapshot.manifest.data() {
-                let manifest_data = rmp_serde::to_vec(temporal_manifest)?;
-                backend
-                    .db
-                    .put_cf(&cf, index_key.as_bytes(), &manifest_data)?;
-            }
-
-            // Also store in a sorted index for range queries
-            let time_index_key = format!(
-                "bitemporal_index:{:020}:{:020}",
-                sequence_time.timestamp(),
-                taxonomy_time.timestamp()
-            );
-            let index_value = rmp_serde::to_vec(&coordinate)?;
-            backend
-                .db
-                .put_cf(&cf, time_index_key.as_bytes(), &index_value)?;
-        }
-
-        // Filter chunks by taxon IDs if specified
-        let chunks = if let Some(taxa) = taxon_ids {
-            manifest
-                .chunk_index()
-                .map(|index| {
-                    index
-                        .iter()
-                        .filter(|chunk| chunk.taxon_ids.iter().any(|tid| taxa.contains(&tid.0)))
-                        .cloned()
-                        .collect()
-                })
-                .unwrap_or_else(Vec::new)
-        } else {
-            manifest.chunk_index().cloned().unwrap_or_else(Vec::new)
-        };
-
-        // Load sequences from chunks
-        let chunk_hashes: Vec<_> = chunks.iter().map(|c| c.hash).collect();
+        let chunk_hashes = self.chunks_at_time(sequence_time, taxonomy_time, taxon_ids)?;
         self.repository.load_sequences_from_chunks(&chunk_hashes)
     }
 
-    /// Find manifest at a specific temporal coordinate
+    /// Chunks holding the repository state at a bi-temporal coordinate
     ///
-    /// Internal helper for bi-temporal queries.
-    /// Will search through historical manifests when full temporal support is implemented.
-    #[allow(dead_code)] // Used by query_at_time
-    fn find_manifest_at_time(
+    /// Every database contributes its newest version created at or before
+    /// `sequence_time`. Taxa reclassified by the taxonomy version in effect at
+    /// `taxonomy_time` are remapped before the chunks are restricted to
+    /// `taxon_ids`, when given.
+    pub fn chunks_at_time(
         &self,
-        _sequence_time: &chrono::DateTime<chrono::Utc>,
-        _taxonomy_time: &chrono::DateTime<chrono::Utc>,
-    ) -> Result<crate::TemporalManifest> {
-        // For now, return the current manifest
-        // In a full implementation, this would search historical manifests
-        self.repository
-            .manifest
-            .get_data()
-            .cloned()
-            .ok_or_else(|| anyhow::anyhow!("No manifest available"))
+        sequence_time: chrono::DateTime<chrono::Utc>,
+        taxonomy_time: chrono::DateTime<chrono::Utc>,
+        taxon_ids: Option<Vec<u32>>,
+    ) -> Result<Vec<SHA256Hash>> {
+        let reclassifications = self
+            .repository
+            .temporal
+            .get_taxonomy_version_at(taxonomy_time)?
+            .map(|version| version.reclassifications)
+            .unwrap_or_default();
+
+        let mut seen = std::collections::HashSet::new();
+        let mut chunks = Vec::new();
+        for database in self.list_databases()? {
+            let Some((source, dataset)) = database.name.split_once('/') else {
+                continue;
+            };
+            // Versions are listed newest first
+            let Some(version) = self
+                .list_database_versions(source, dataset)?
+                .into_iter()
+                .find(|version| version.created_at <= sequence_time)
+            else {
+                continue;
+            };
+            let manifest = self.get_version_manifest(source, dataset, &version.timestamp)?;
+            for chunk in manifest.chunk_index {
+                let wanted = match &taxon_ids {
+                    Some(taxa) => chunk.taxon_ids.iter().any(|tid| {
+                        let tid = reclassifications.get(tid).unwrap_or(tid);
+                        taxa.contains(&tid.0)
+                    }),
+                    None => true,
+                };
+                if wanted && seen.insert(chunk.hash) {
+                    chunks.push(chunk.hash);
+                }
+            }
+        }
+        Ok(chunks)
     }
 
     /// Get temporal history of a sequence
@@ -5767,7 +5977,7 @@ impl DatabaseManager {
         }
 
         // Sort by timestamp (newest first)
-        versions.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        versions.sort_by_key(|b| std::cmp::Reverse(b.created_at));
 
         // Update cache
         if let Some(cache) = &self.cache {
@@ -5956,6 +6166,9 @@ impl DatabaseManager {
         // Delete the manifest
         let manifest_key = format!("manifest:{}:{}:{}", source, dataset, timestamp);
         rocksdb.delete_manifest(&manifest_key)?;
+        rocksdb.delete_manifest(&Self::pipeline_key(source, dataset, &timestamp))?;
+        rocksdb.delete_manifest(&Self::composition_key(source, dataset, &timestamp))?;
+        rocksdb.delete_manifest(&Self::screening_key(source, dataset, &timestamp))?;
 
         // Remove all aliases pointing to this version
         self.cleanup_version_aliases(source, dataset, &timestamp)?;
@@ -5989,6 +6202,9 @@ impl DatabaseManager {
             // Delete manifest
             let manifest_key = format!("manifest:{}:{}:{}", source, dataset, version.timestamp);
             rocksdb.delete_manifest(&manifest_key)?;
+            rocksdb.delete_manifest(&Self::pipeline_key(source, dataset, &version.timestamp))?;
+            rocksdb.delete_manifest(&Self::composition_key(source, dataset, &version.timestamp))?;
+            rocksdb.delete_manifest(&Self::screening_key(source, dataset, &version.timestamp))?;
 
             // Remove aliases
             self.cleanup_version_aliases(source, dataset, &version.timestamp)?;
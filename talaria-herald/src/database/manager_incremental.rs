@@ -0,0 +1,906 @@
+/// Incremental release updates for DatabaseManager
+///
+/// Instead of re-chunking a whole upstream release, the new FASTA is streamed
+/// and compared against the current version by sequence ID. Canonical
+/// sequences that are already part of the current version are never chunked
+/// again, chunks whose members all survive are carried over by hash, and only
+/// added, removed and modified representations are recorded in the new
+/// temporal version. The current version is indexed in RocksDB while the
+/// update runs, so memory does not grow with the size of the database.
+use crate::database::manager::TemporalSequenceRecord;
+use crate::database::DatabaseManager;
+use crate::delta::{DeltaGeneratorConfig, SequenceDeltaGenerator};
+use crate::download::manager::{DownloadManager, DownloadOptions};
+use crate::download::workspace::{find_existing_workspace_for_source, Stage};
+use crate::download::DownloadProgress;
+use crate::{
+    BiTemporalCoordinate, ManifestMetadata, SHA256Hash, SHA256HashExt, TaxonomicChunker,
+    TemporalManifest,
+};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::sync::Arc;
+use talaria_bio::sequence::Sequence;
+use talaria_core::DatabaseSource;
+use talaria_storage::backend::RocksDBBackend;
+
+/// Number of release sequences checked against canonical storage at once
+const COMPARE_BATCH_SIZE: usize = 10_000;
+
+/// Number of changed sequences buffered before they are chunked
+const CHUNK_BATCH_SIZE: usize = 100_000;
+
+/// Outcome of an incremental release update
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct IncrementalUpdateReport {
+    /// Version created by the update (the previous version if nothing changed)
+    pub version: String,
+    pub previous_version: String,
+    /// Representations in the release that the current version lacks
+    pub sequences_added: usize,
+    /// Representations of the current version missing from the release
+    pub sequences_removed: usize,
+    /// Representations whose sequence or header changed
+    pub sequences_modified: usize,
+    pub sequences_unchanged: usize,
+    /// Changed sequences whose canonical form had to be stored
+    pub canonical_stored: usize,
+    /// Changed sequences whose canonical form already existed
+    pub canonical_skipped: usize,
+    /// Chunks carried over from the previous version by hash
+    pub chunks_reused: usize,
+    pub chunks_created: usize,
+    pub chunks_retired: usize,
+    /// Delta chunks recorded for sequences modified in place
+    pub delta_chunks: usize,
+}
+
+impl IncrementalUpdateReport {
+    /// Whether the release differed from the current version at all
+    pub fn has_changes(&self) -> bool {
+        self.sequences_added + self.sequences_removed + self.sequences_modified > 0
+    }
+}
+
+/// A representation of the current version, keyed by sequence ID
+#[derive(serde::Serialize, serde::Deserialize)]
+struct CurrentEntry {
+    canonical: SHA256Hash,
+    header: String,
+    chunk: usize,
+    last_seen: DateTime<Utc>,
+    /// Whether other representations of this source compete for the ID or canonical
+    ambiguous: bool,
+    seen: bool,
+}
+
+impl CurrentEntry {
+    /// Keep whichever of two representations for the same ID was stored last
+    fn supersede(&mut self, other: CurrentEntry) {
+        if other.last_seen >= self.last_seen {
+            *self = other;
+        }
+        self.ambiguous = true;
+    }
+}
+
+/// A changed representation, recorded in the temporal history
+#[derive(serde::Serialize, serde::Deserialize)]
+struct Change {
+    sequence_id: String,
+    canonical: SHA256Hash,
+    taxon_id: Option<u32>,
+    removed: bool,
+}
+
+/// A sequence whose content changed under the same ID
+struct Modification {
+    sequence_id: String,
+    old: SHA256Hash,
+    new: SHA256Hash,
+}
+
+/// The version an update starts from, indexed for lookups by sequence ID
+///
+/// Entries live in the index column family under a per-dataset prefix, so
+/// memory stays bounded by the batch size instead of growing with the
+/// database. The index only exists while an update runs.
+struct VersionIndex {
+    rocksdb: Arc<RocksDBBackend>,
+    prefix: String,
+}
+
+impl VersionIndex {
+    fn new(rocksdb: Arc<RocksDBBackend>, source_name: &str, dataset_name: &str) -> Result<Self> {
+        let index = Self {
+            rocksdb,
+            prefix: format!("incremental:{}:{}:", source_name, dataset_name),
+        };
+        // Left behind by an interrupted update
+        index.clear()?;
+        Ok(index)
+    }
+
+    fn clear(&self) -> Result<()> {
+        self.rocksdb.delete_index_prefix(&self.prefix)?;
+        Ok(())
+    }
+
+    fn id_key(&self, id: &str) -> String {
+        format!("{}id:{}", self.prefix, id)
+    }
+
+    fn chunk_key(&self, canonical: &SHA256Hash) -> String {
+        format!("{}chunk:{}", self.prefix, canonical.to_hex())
+    }
+
+    fn present_key(&self, canonical: &SHA256Hash) -> String {
+        format!("{}present:{}", self.prefix, canonical.to_hex())
+    }
+
+    fn change_key(&self, position: u64) -> String {
+        // Fixed width keeps the log in the order it was written
+        format!("{}change:{:016x}", self.prefix, position)
+    }
+
+    fn entry(&self, id: &str) -> Result<Option<CurrentEntry>> {
+        let data = self.rocksdb.get_index(&self.id_key(id))?;
+        Ok(data.map(|data| rmp_serde::from_slice(&data)).transpose()?)
+    }
+
+    fn entries<S: AsRef<str>>(&self, ids: &[S]) -> Result<Vec<Option<CurrentEntry>>> {
+        let keys: Vec<String> = ids.iter().map(|id| self.id_key(id.as_ref())).collect();
+        let mut entries = Vec::with_capacity(keys.len());
+        for data in self.rocksdb.get_indices_batch(&keys)? {
+            entries.push(data.map(|data| rmp_serde::from_slice(&data)).transpose()?);
+        }
+        Ok(entries)
+    }
+
+    fn put_entries<'a>(
+        &self,
+        entries: impl IntoIterator<Item = (&'a String, &'a CurrentEntry)>,
+    ) -> Result<()> {
+        let mut batch = Vec::new();
+        for (id, entry) in entries {
+            batch.push((self.id_key(id), rmp_serde::to_vec(entry)?));
+        }
+        self.rocksdb.put_indices_batch(&batch)
+    }
+
+    fn remove_entry(&self, id: &str) -> Result<()> {
+        self.rocksdb.delete_index(&self.id_key(id))
+    }
+
+    /// Visit every entry without loading them all
+    fn for_each_entry(&self, mut f: impl FnMut(&str, CurrentEntry) -> Result<()>) -> Result<()> {
+        let prefix = format!("{}id:", self.prefix);
+        self.rocksdb.for_each_index_prefix(&prefix, |key, data| {
+            f(&key[prefix.len()..], rmp_serde::from_slice(data)?)
+        })
+    }
+
+    fn put_chunk(&self, members: &[SHA256Hash], chunk_idx: usize) -> Result<()> {
+        let value = rmp_serde::to_vec(&(chunk_idx as u64))?;
+        let batch: Vec<_> = members
+            .iter()
+            .map(|canonical| (self.chunk_key(canonical), value.clone()))
+            .collect();
+        self.rocksdb.put_indices_batch(&batch)
+    }
+
+    /// Chunk of the current version holding each canonical, if any
+    fn chunks_of(&self, canonicals: &[SHA256Hash]) -> Result<Vec<Option<usize>>> {
+        let keys: Vec<String> = canonicals.iter().map(|c| self.chunk_key(c)).collect();
+        let mut chunks = Vec::with_capacity(keys.len());
+        for data in self.rocksdb.get_indices_batch(&keys)? {
+            let chunk_idx: Option<u64> =
+                data.map(|data| rmp_serde::from_slice(&data)).transpose()?;
+            chunks.push(chunk_idx.map(|idx| idx as usize));
+        }
+        Ok(chunks)
+    }
+
+    /// Record canonicals as referenced by the release
+    fn mark_present<'a>(&self, canonicals: impl IntoIterator<Item = &'a SHA256Hash>) -> Result<()> {
+        let batch: Vec<_> = canonicals
+            .into_iter()
+            .map(|canonical| (self.present_key(canonical), Vec::new()))
+            .collect();
+        self.rocksdb.put_indices_batch(&batch)
+    }
+
+    /// Append `changes` to the log of this update, leaving `changes` empty
+    fn log_changes(&self, logged: &mut u64, changes: &mut Vec<Change>) -> Result<()> {
+        let mut batch = Vec::with_capacity(changes.len());
+        for change in changes.drain(..) {
+            batch.push((self.change_key(*logged), rmp_serde::to_vec(&change)?));
+            *logged += 1;
+        }
+        self.rocksdb.put_indices_batch(&batch)
+    }
+
+    /// Visit the logged changes in order, `size` at a time
+    fn for_each_change_batch(
+        &self,
+        size: usize,
+        mut f: impl FnMut(&[Change]) -> Result<()>,
+    ) -> Result<()> {
+        let prefix = format!("{}change:", self.prefix);
+        let mut batch = Vec::with_capacity(size);
+        self.rocksdb.for_each_index_prefix(&prefix, |_, data| {
+            batch.push(rmp_serde::from_slice(data)?);
+            if batch.len() == size {
+                f(&batch)?;
+                batch.clear();
+            }
+            Ok(())
+        })?;
+        if !batch.is_empty() {
+            f(&batch)?;
+        }
+        Ok(())
+    }
+
+    fn present(&self, canonicals: &[SHA256Hash]) -> Result<Vec<bool>> {
+        let keys: Vec<String> = canonicals.iter().map(|c| self.present_key(c)).collect();
+        Ok(self
+            .rocksdb
+            .get_indices_batch(&keys)?
+            .into_iter()
+            .map(|data| data.is_some())
+            .collect())
+    }
+}
+
+impl DatabaseManager {
+    /// Download the latest release of `source` and apply it incrementally
+    ///
+    /// Returns `None` when no local copy existed and a full download was done instead.
+    pub async fn update_incremental(
+        &mut self,
+        source: &DatabaseSource,
+        progress_callback: impl Fn(&str) + Send + Sync,
+    ) -> Result<Option<IncrementalUpdateReport>> {
+        if !self.has_database_by_source(source)? {
+            progress_callback("No local database found - performing initial download");
+            self.download(source, progress_callback).await?;
+            return Ok(None);
+        }
+
+        // Reuse a complete download left behind by an interrupted update
+        let existing_download = match find_existing_workspace_for_source(source)? {
+            Some((_workspace, state)) if state.stage == Stage::Complete => state
+                .files
+                .decompressed
+                .filter(|decompressed| decompressed.exists()),
+            _ => None,
+        };
+
+        let release_path = if let Some(path) = existing_download {
+            progress_callback(&format!(
+                "✓ Found complete download: {}",
+                path.file_name().unwrap_or_default().to_string_lossy()
+            ));
+            path
+        } else {
+            progress_callback("Downloading latest release...");
+            let mut download_manager = DownloadManager::new()?;
+            let options = DownloadOptions {
+                skip_verify: false,
+                resume: true,
+                preserve_on_failure: true,
+                preserve_always: std::env::var("TALARIA_PRESERVE_DOWNLOADS").is_ok(),
+                force: false,
+            };
+            let mut progress = DownloadProgress::new();
+            download_manager
+                .download_with_state(source.clone(), options, &mut progress)
+                .await?
+        };
+
+        let report = match self.apply_incremental_release(&release_path, source, &progress_callback)
+        {
+            Ok(report) => report,
+            Err(e) => {
+                progress_callback(&format!(
+                    "Update failed: {}. Downloaded file preserved in workspace for retry.",
+                    e
+                ));
+                return Err(e);
+            }
+        };
+
+        if let Err(e) = DownloadManager::cleanup_download_workspace(source) {
+            progress_callback(&format!("Warning: Failed to clean up workspace: {}", e));
+        }
+
+        Ok(Some(report))
+    }
+
+    /// Apply a release FASTA file on top of the current version of `source`
+    ///
+    /// Sequences are matched by ID. Unchanged sequences are skipped, changed
+    /// sequences whose canonical form is already part of the current version
+    /// only gain a representation, and everything else is chunked. Chunks that
+    /// lost members are rebuilt from their survivors; all others are reused.
+    pub fn apply_incremental_release(
+        &mut self,
+        file_path: &Path,
+        source: &DatabaseSource,
+        progress_callback: &dyn Fn(&str),
+    ) -> Result<IncrementalUpdateReport> {
+        let _span = tracing::info_span!("apply_incremental_release", source = %source).entered();
+
+        let (source_name, dataset_name) = self.get_source_dataset_names(source);
+        let index = VersionIndex::new(
+            self.get_repository().storage.sequence_storage.get_rocksdb(),
+            &source_name,
+            &dataset_name,
+        )?;
+        let result = self.apply_release_with_index(file_path, source, &index, progress_callback);
+        index.clear()?;
+        result
+    }
+
+    fn apply_release_with_index(
+        &mut self,
+        file_path: &Path,
+        source: &DatabaseSource,
+        index: &VersionIndex,
+        progress_callback: &dyn Fn(&str),
+    ) -> Result<IncrementalUpdateReport> {
+        let started = Utc::now();
+        let (source_name, dataset_name) = self.get_source_dataset_names(source);
+        let previous_version = self.get_current_version_info(source)?.timestamp;
+        let previous = self.get_manifest(&format!(
+            "{}/{}@{}",
+            source_name, dataset_name, previous_version
+        ))?;
+
+        progress_callback(&format!(
+            "Indexing version {} ({} chunks)...",
+            previous_version,
+            previous.chunk_index.len()
+        ));
+        let sequence_storage = Arc::clone(&self.get_repository().storage.sequence_storage);
+
+        for (chunk_idx, meta) in previous.chunk_index.iter().enumerate() {
+            let chunk = self
+                .load_manifest(&meta.hash)
+                .with_context(|| format!("Failed to load chunk manifest {}", meta.hash))?;
+            let mut entries: HashMap<String, CurrentEntry> = HashMap::new();
+            for canonical in &chunk.sequence_refs {
+                let representations = sequence_storage.load_representations(canonical)?;
+                let own: Vec<_> = representations
+                    .representations()
+                    .iter()
+                    .filter(|r| &r.source == source)
+                    .collect();
+                let shared = own.len() > 1;
+                for repr in own {
+                    let entry = CurrentEntry {
+                        canonical: *canonical,
+                        header: repr.header.clone(),
+                        chunk: chunk_idx,
+                        last_seen: repr.last_seen,
+                        ambiguous: shared,
+                        seen: false,
+                    };
+                    // Representations are never dropped, so an ID may appear on several
+                    // canonicals; the most recently stored one is its current form
+                    match entries.entry(header_id(&repr.header).to_string()) {
+                        Entry::Occupied(mut existing) => existing.get_mut().supersede(entry),
+                        Entry::Vacant(slot) => {
+                            slot.insert(entry);
+                        }
+                    }
+                }
+            }
+
+            // Merge with the same IDs seen in earlier chunks
+            let ids: Vec<String> = entries.keys().cloned().collect();
+            for (id, earlier) in ids.iter().zip(index.entries(&ids)?) {
+                if let Some(mut earlier) = earlier {
+                    let later = entries.remove(id).expect("ID taken from the map");
+                    earlier.supersede(later);
+                    entries.insert(id.clone(), earlier);
+                }
+            }
+            index.put_entries(&entries)?;
+            index.put_chunk(&chunk.sequence_refs, chunk_idx)?;
+        }
+
+        // A removed ID keeps its representation while its canonical stays in
+        // the version through another ID; drop those removed since last stored
+        let versions: HashSet<String> = self
+            .list_database_versions(&source_name, &dataset_name)?
+            .into_iter()
+            .map(|v| v.timestamp)
+            .collect();
+        let mut removed_ids = Vec::new();
+        index.for_each_entry(|id, entry| {
+            if entry.ambiguous {
+                if let Some(removed_at) = self.latest_removal(id, &versions)? {
+                    if removed_at >= entry.last_seen {
+                        removed_ids.push(id.to_string());
+                    }
+                }
+            }
+            Ok(())
+        })?;
+        for id in &removed_ids {
+            index.remove_entry(id)?;
+        }
+
+        progress_callback("Comparing release against current version...");
+        let mut report = IncrementalUpdateReport {
+            previous_version: previous_version.clone(),
+            ..Default::default()
+        };
+        let mut chunker = TaxonomicChunker::new(
+            self.chunking_strategy(source)?,
+            Arc::clone(&sequence_storage),
+            source.clone(),
+        );
+        let mut new_chunks = Vec::new();
+        let mut pending: Vec<Sequence> = Vec::new();
+        // Changes are logged in the index as each batch is compared
+        let mut changes: Vec<Change> = Vec::new();
+        let mut logged = 0u64;
+        let mut modifications: BTreeMap<usize, Vec<Modification>> = BTreeMap::new();
+
+        let mut records = ReleaseReader::open(file_path)?;
+        loop {
+            let batch = records.next_batch(COMPARE_BATCH_SIZE)?;
+            if batch.is_empty() {
+                break;
+            }
+
+            let hashes: Vec<SHA256Hash> = batch
+                .iter()
+                .map(|seq| SHA256Hash::compute(&seq.sequence))
+                .collect();
+            let exists = sequence_storage.canonical_exists_batch(&hashes)?;
+            let in_version = index.chunks_of(&hashes)?;
+            // Canonical sequences referenced by earlier batches of the release
+            let was_present = index.present(&hashes)?;
+            let ids: Vec<&str> = batch.iter().map(|seq| seq.id.as_str()).collect();
+            let mut entries: HashMap<String, CurrentEntry> = ids
+                .iter()
+                .zip(index.entries(&ids)?)
+                .filter_map(|(id, entry)| Some((id.to_string(), entry?)))
+                .collect();
+            let mut present: HashSet<SHA256Hash> = HashSet::new();
+            // Sequences that only gain a representation, stored together
+            let mut representations: Vec<(String, String)> = Vec::new();
+
+            for (i, seq) in batch.into_iter().enumerate() {
+                let canonical = hashes[i];
+                let header = format!(
+                    ">{}{}",
+                    seq.id,
+                    seq.description
+                        .as_ref()
+                        .map(|d| format!(" {}", d))
+                        .unwrap_or_default()
+                );
+
+                match entries.get_mut(&seq.id) {
+                    Some(entry) if entry.canonical == canonical && entry.header == header => {
+                        entry.seen = true;
+                        report.sequences_unchanged += 1;
+                        present.insert(canonical);
+                        continue;
+                    }
+                    Some(entry) => {
+                        entry.seen = true;
+                        report.sequences_modified += 1;
+                        if entry.canonical != canonical {
+                            modifications
+                                .entry(entry.chunk)
+                                .or_default()
+                                .push(Modification {
+                                    sequence_id: seq.id.clone(),
+                                    old: entry.canonical,
+                                    new: canonical,
+                                });
+                        }
+                    }
+                    None => report.sequences_added += 1,
+                }
+                changes.push(Change {
+                    sequence_id: seq.id.clone(),
+                    canonical,
+                    taxon_id: seq.taxon_id,
+                    removed: false,
+                });
+
+                if exists[i] {
+                    report.canonical_skipped += 1;
+                } else {
+                    report.canonical_stored += 1;
+                }
+
+                // Sequences already in this version (or already queued for chunking)
+                // only need their new representation
+                let first_seen = !was_present[i] && present.insert(canonical);
+                if in_version[i].is_some() || !first_seen {
+                    representations
+                        .push((String::from_utf8_lossy(&seq.sequence).into_owned(), header));
+                } else {
+                    pending.push(seq);
+                }
+            }
+
+            sequence_storage.store_sequences_batch(
+                representations
+                    .iter()
+                    .map(|(sequence, header)| (sequence.as_str(), header.as_str(), source.clone()))
+                    .collect(),
+            )?;
+            // Every entry looked up by this batch was matched by a release record
+            index.put_entries(&entries)?;
+            index.mark_present(&present)?;
+            index.log_changes(&mut logged, &mut changes)?;
+
+            if pending.len() >= CHUNK_BATCH_SIZE {
+                let sequences = std::mem::take(&mut pending);
+                new_chunks.extend(chunker.chunk_sequences_canonical_quiet_final(sequences, false)?);
+                progress_callback(&format!(
+                    "Compared {} sequences ({} added, {} modified)",
+                    report.sequences_added + report.sequences_modified + report.sequences_unchanged,
+                    report.sequences_added,
+                    report.sequences_modified
+                ));
+            }
+        }
+
+        index.for_each_entry(|id, entry| {
+            if !entry.seen {
+                report.sequences_removed += 1;
+                changes.push(Change {
+                    sequence_id: id.to_string(),
+                    canonical: entry.canonical,
+                    taxon_id: None,
+                    removed: true,
+                });
+                if changes.len() >= COMPARE_BATCH_SIZE {
+                    index.log_changes(&mut logged, &mut changes)?;
+                }
+            }
+            Ok(())
+        })?;
+        index.log_changes(&mut logged, &mut changes)?;
+
+        if !report.has_changes() {
+            progress_callback("Release is identical to the current version");
+            report.version = previous_version;
+            report.chunks_reused = previous.chunk_index.len();
+            return Ok(report);
+        }
+
+        // Chunks that lost a member are rebuilt from the members that survive
+        let mut touched: HashSet<usize> = HashSet::new();
+        for (chunk_idx, meta) in previous.chunk_index.iter().enumerate() {
+            let chunk = self
+                .load_manifest(&meta.hash)
+                .with_context(|| format!("Failed to load chunk manifest {}", meta.hash))?;
+            let present = index.present(&chunk.sequence_refs)?;
+            if present.iter().all(|&p| p) {
+                continue;
+            }
+            touched.insert(chunk_idx);
+
+            for (canonical, _) in chunk
+                .sequence_refs
+                .iter()
+                .zip(present)
+                .filter(|(_, present)| *present)
+            {
+                // Only representations that are part of the new version qualify:
+                // unchanged ones from the release and those stored by this update
+                let representations = sequence_storage.load_representations(canonical)?;
+                let mut candidates = Vec::new();
+                for r in representations
+                    .representations()
+                    .iter()
+                    .filter(|r| &r.source == source)
+                {
+                    let current = r.last_seen >= started
+                        || index.entry(header_id(&r.header))?.is_some_and(|entry| {
+                            entry.seen && entry.canonical == *canonical && entry.header == r.header
+                        });
+                    if current {
+                        candidates.push(r);
+                    }
+                }
+                let Some(repr) = candidates.into_iter().max_by_key(|r| r.last_seen) else {
+                    continue;
+                };
+                let header = repr.header.trim_start_matches('>');
+                let (id, description) = match header.split_once(' ') {
+                    Some((id, description)) => (id.to_string(), Some(description.to_string())),
+                    None => (header.to_string(), None),
+                };
+                pending.push(Sequence {
+                    id,
+                    description,
+                    sequence: sequence_storage.load_canonical(canonical)?.sequence,
+                    taxon_id: repr.taxon_id.map(|t| t.0),
+                    taxonomy_sources: Default::default(),
+                });
+            }
+
+            if pending.len() >= CHUNK_BATCH_SIZE {
+                let sequences = std::mem::take(&mut pending);
+                new_chunks.extend(chunker.chunk_sequences_canonical_quiet_final(sequences, false)?);
+            }
+        }
+        if !pending.is_empty() {
+            new_chunks.extend(chunker.chunk_sequences_canonical_quiet_final(pending, true)?);
+        }
+        sequence_storage.save_indices()?;
+
+        // Sequences modified in place are also recorded as deltas against their old chunk
+        let mut delta_generator = SequenceDeltaGenerator::new(DeltaGeneratorConfig::default());
+        for (chunk_idx, group) in &modifications {
+            let mut old_sequences = Vec::with_capacity(group.len());
+            let mut new_sequences = Vec::with_capacity(group.len());
+            for modification in group {
+                for (hash, sequences) in [
+                    (&modification.old, &mut old_sequences),
+                    (&modification.new, &mut new_sequences),
+                ] {
+                    sequences.push(Sequence {
+                        id: modification.sequence_id.clone(),
+                        description: None,
+                        sequence: sequence_storage.load_canonical(hash)?.sequence,
+                        taxon_id: None,
+                        taxonomy_sources: Default::default(),
+                    });
+                }
+            }
+            let delta_chunks = delta_generator.generate_incremental_update(
+                &old_sequences,
+                &new_sequences,
+                previous.chunk_index[*chunk_idx].hash,
+            )?;
+            for delta_chunk in &delta_chunks {
+                self.get_repository()
+                    .storage
+                    .store_delta_chunk(delta_chunk)?;
+            }
+            report.delta_chunks += delta_chunks.len();
+        }
+
+        let stored = self.store_chunk_manifests_internal(new_chunks, source, None)?;
+        report.chunks_created = stored.len();
+        report.chunks_retired = touched.len();
+
+        let mut chunk_index: Vec<ManifestMetadata> = previous
+            .chunk_index
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| !touched.contains(idx))
+            .map(|(_, meta)| meta.clone())
+            .collect();
+        report.chunks_reused = chunk_index.len();
+        // Sequences placed in a new chunk; the rest stay where they were
+        let mut relocated: HashMap<SHA256Hash, SHA256Hash> = HashMap::new();
+        for (manifest, hash) in &stored {
+            for canonical in &manifest.sequence_refs {
+                relocated.insert(*canonical, *hash);
+            }
+            chunk_index.push(ManifestMetadata {
+                hash: *hash,
+                taxon_ids: manifest.taxon_ids.clone(),
+                sequence_count: manifest.sequence_count,
+                size: manifest.total_size,
+                compressed_size: Some(manifest.total_size / 10), // Estimate
+            });
+        }
+
+        let version = talaria_core::system::paths::generate_utc_timestamp();
+        let now = Utc::now();
+        let taxonomy_time = previous
+            .temporal_coordinate
+            .as_ref()
+            .map(|c| c.taxonomy_time)
+            .unwrap_or(now);
+        let previous_chunks: Vec<SHA256Hash> =
+            previous.chunk_index.iter().map(|meta| meta.hash).collect();
+        let manifest = TemporalManifest {
+            version: version.clone(),
+            created_at: now,
+            sequence_version: version.clone(),
+            temporal_coordinate: Some(BiTemporalCoordinate {
+                sequence_time: now,
+                taxonomy_time,
+            }),
+            chunk_merkle_tree: crate::types::SerializedMerkleTree::from_chunk_index(&chunk_index)?,
+            chunk_index,
+            etag: format!("{}-{}", source_name, version),
+            previous_version: Some(previous_version.clone()),
+            ..previous
+        };
+
+        let chunk_count = manifest.chunk_index.len();
+        let sequence_count = manifest.chunk_index.iter().map(|c| c.sequence_count).sum();
+        let total_size = manifest.chunk_index.iter().map(|c| c.size).sum();
+        self.save_manifest_to_repository(
+            &source_name,
+            &dataset_name,
+            &version,
+            &manifest,
+            chunk_count,
+            sequence_count,
+            total_size,
+        )?;
+        self.create_version_metadata(source, &version, Path::new(""))?;
+
+        let rocksdb = self.get_repository().storage.sequence_storage.get_rocksdb();
+        let mut temporal_index = crate::TemporalIndex::load(&self.base_path, rocksdb)?;
+        temporal_index.add_sequence_version(
+            version.clone(),
+            manifest.sequence_root,
+            chunk_count,
+            sequence_count,
+        )?;
+        temporal_index.save()?;
+
+        // Only changed representations enter the temporal history
+        index.for_each_change_batch(COMPARE_BATCH_SIZE, |batch| {
+            let canonicals: Vec<SHA256Hash> = batch.iter().map(|c| c.canonical).collect();
+            let chunks = index.chunks_of(&canonicals)?;
+            for (change, chunk_idx) in batch.iter().zip(chunks) {
+                let chunk_hash = if change.removed {
+                    SHA256Hash::zero()
+                } else if let Some(hash) = relocated.get(&change.canonical) {
+                    *hash
+                } else {
+                    chunk_idx
+                        .filter(|idx| !touched.contains(idx))
+                        .map(|idx| previous_chunks[idx])
+                        .unwrap_or_else(SHA256Hash::zero)
+                };
+                self.store_temporal_history(&TemporalSequenceRecord {
+                    sequence_id: change.sequence_id.clone(),
+                    version: version.clone(),
+                    sequence_time: now,
+                    taxonomy_time,
+                    taxon_id: change.taxon_id,
+                    chunk_hash,
+                })?;
+            }
+            Ok(())
+        })?;
+
+        if let Some(cache) = &self.cache {
+            cache.invalidate_database(&source_name, &dataset_name);
+        }
+
+        progress_callback(&format!(
+            "✓ Created version {} ({} added, {} removed, {} modified)",
+            version, report.sequences_added, report.sequences_removed, report.sequences_modified
+        ));
+        report.version = version;
+
+        Ok(report)
+    }
+
+    /// When `sequence_id` was last removed by one of `versions`, if ever
+    fn latest_removal(
+        &self,
+        sequence_id: &str,
+        versions: &HashSet<String>,
+    ) -> Result<Option<DateTime<Utc>>> {
+        Ok(self
+            .get_temporal_history(sequence_id)?
+            .into_iter()
+            .filter(|record| record.chunk_hash == SHA256Hash::zero())
+            .filter(|record| versions.contains(&record.version))
+            .map(|record| record.sequence_time)
+            .max())
+    }
+}
+
+/// Sequence ID of a stored FASTA header
+pub(crate) fn header_id(header: &str) -> &str {
+    header
+        .trim_start_matches('>')
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+}
+
+/// Reads a release file (plain or gzip-compressed) a batch of records at a time
+///
+/// Each batch is parsed by the talaria_bio FASTA parser, so headers, taxa and
+/// multi-line records are read exactly as a full import reads them.
+struct ReleaseReader {
+    reader: Box<dyn BufRead>,
+    /// Header line that starts the next batch
+    carry: Vec<u8>,
+}
+
+impl ReleaseReader {
+    fn open(path: &Path) -> Result<Self> {
+        let file = File::open(path)
+            .with_context(|| format!("Failed to open release file {}", path.display()))?;
+        let reader: Box<dyn BufRead> = if path.extension().and_then(|s| s.to_str()) == Some("gz") {
+            Box::new(BufReader::new(flate2::read::GzDecoder::new(file)))
+        } else {
+            Box::new(BufReader::new(file))
+        };
+        Ok(Self {
+            reader,
+            carry: Vec::new(),
+        })
+    }
+
+    /// Up to `size` records; empty once the file is exhausted
+    fn next_batch(&mut self, size: usize) -> Result<Vec<Sequence>> {
+        let mut buffer = std::mem::take(&mut self.carry);
+        let mut records = usize::from(!buffer.is_empty());
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            if self.reader.read_until(b'\n', &mut line)? == 0 {
+                break;
+            }
+            if line.starts_with(b">") {
+                if records == size {
+                    self.carry = std::mem::take(&mut line);
+                    break;
+                }
+                records += 1;
+            } else if records == 0 {
+                // Nothing before the first header belongs to a record
+                continue;
+            }
+            buffer.extend_from_slice(&line);
+        }
+        // The parser expects every header line to be terminated
+        if !buffer.is_empty() && !buffer.ends_with(b"\n") {
+            buffer.push(b'\n');
+        }
+        Ok(talaria_bio::parse_fasta_from_bytes(&buffer)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_release_reader_batches_records() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("release.fasta");
+        std::fs::write(
+            &path,
+            "\n>sp|P1|A_HUMAN First OX=9606\nMKT\nAYI\n>sp|P2|B_HUMAN Second\nGGG\n\
+             >sp|P3|C_MOUSE Third OX=10090\nLLL\n",
+        )
+        .unwrap();
+
+        let mut reader = ReleaseReader::open(&path).unwrap();
+        let first = reader.next_batch(2).unwrap();
+        assert_eq!(first.len(), 2);
+        assert_eq!(first[0].id, "sp|P1|A_HUMAN");
+        assert_eq!(first[0].sequence, b"MKTAYI");
+        assert_eq!(first[0].taxon_id, Some(9606));
+        assert_eq!(first[1].description.as_deref(), Some("Second"));
+
+        let second = reader.next_batch(2).unwrap();
+        assert_eq!(second.len(), 1);
+        assert_eq!(second[0].id, "sp|P3|C_MOUSE");
+        assert_eq!(second[0].taxon_id, Some(10090));
+        assert!(reader.next_batch(2).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,307 @@
+/// Taxonomy-only reclassification for DatabaseManager
+///
+/// When only the taxonomy changes, sequence data and chunks stay as they are.
+/// The taxa referenced by the current version are compared between the
+/// previous and the current taxdump, sequences of merged, deleted or moved
+/// taxa are re-indexed, and a new version is written that shares the sequence
+/// state of the previous one under a new taxonomy time.
+use crate::database::manager::TemporalSequenceRecord;
+use crate::database::manager_incremental::header_id;
+use crate::database::DatabaseManager;
+use crate::taxonomy::reclassify::{diff_taxa, TaxonChange, TaxonomySnapshot};
+use crate::types::TaxonId;
+use crate::{
+    BiTemporalCoordinate, ManifestMetadata, MassReclassification, ReclassificationLog, SHA256Hash,
+    TemporalManifest,
+};
+use anyhow::{Context, Result};
+use chrono::Utc;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use talaria_core::system::paths::{talaria_taxonomy_current_dir, talaria_taxonomy_version_dir};
+use talaria_core::DatabaseSource;
+
+/// Outcome of applying a new taxonomy to an existing database version
+#[derive(Debug, Clone, Default)]
+pub struct ReclassificationReport {
+    /// Version created by the reclassification (the previous version if nothing changed)
+    pub version: String,
+    pub previous_version: String,
+    /// Taxonomy version the database was reclassified against
+    pub taxonomy_version: String,
+    /// Distinct taxa referenced by the previous version
+    pub taxa_checked: usize,
+    pub taxa_merged: usize,
+    pub taxa_deleted: usize,
+    pub taxa_moved: usize,
+    pub sequences_reclassified: usize,
+    /// Chunks whose taxon index entries were rewritten
+    pub chunks_reindexed: usize,
+    pub mass_reclassifications: Vec<MassReclassification>,
+}
+
+impl ReclassificationReport {
+    /// Whether the new taxonomy affected the database at all
+    pub fn has_changes(&self) -> bool {
+        self.taxa_merged + self.taxa_deleted + self.taxa_moved > 0
+    }
+}
+
+impl DatabaseManager {
+    /// Version name of the current taxonomy (the target of the `current` link)
+    pub fn current_taxonomy_version() -> String {
+        fs::read_link(talaria_taxonomy_current_dir())
+            .ok()
+            .and_then(|target| {
+                target
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+            })
+            .unwrap_or_else(|| "current".to_string())
+    }
+
+    /// Taxdump directory of an installed taxonomy version
+    pub fn taxonomy_tree(version: &str) -> Option<PathBuf> {
+        if version == "current" {
+            return None;
+        }
+        let tree = talaria_taxonomy_version_dir(version).join("tree");
+        tree.join("nodes.dmp").exists().then_some(tree)
+    }
+
+    /// Apply the current taxonomy to the current version of `source`
+    ///
+    /// The taxonomy recorded in the version's manifest is the base of the
+    /// diff; `previous_tree` overrides its taxdump directory. A version that
+    /// is already classified against the current taxonomy is kept as is.
+    /// Only chunks that reference a changed taxon are inspected; chunk hashes
+    /// and sequence data are carried over unchanged.
+    pub fn reclassify_with_taxonomy(
+        &mut self,
+        source: &DatabaseSource,
+        previous_tree: Option<&Path>,
+        mass_threshold: usize,
+        progress_callback: &dyn Fn(&str),
+    ) -> Result<ReclassificationReport> {
+        let _span = tracing::info_span!("reclassify_with_taxonomy", source = %source).entered();
+
+        let (source_name, dataset_name) = self.get_source_dataset_names(source);
+        let previous_version = self.get_current_version_info(source)?.timestamp;
+        let previous = self.get_manifest(&format!(
+            "{}/{}@{}",
+            source_name, dataset_name, previous_version
+        ))?;
+        let taxonomy_version = Self::current_taxonomy_version();
+
+        if previous.taxonomy_version == taxonomy_version {
+            progress_callback(&format!(
+                "✓ Version {} is already classified against taxonomy {}",
+                previous_version, taxonomy_version
+            ));
+            return Ok(ReclassificationReport {
+                version: previous_version.clone(),
+                previous_version,
+                taxonomy_version,
+                ..Default::default()
+            });
+        }
+        let previous_tree = match previous_tree {
+            Some(tree) => tree.to_path_buf(),
+            None => Self::taxonomy_tree(&previous.taxonomy_version).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Version {} was classified against taxonomy '{}', which is not installed; \
+                     pass the taxonomy version to diff against explicitly",
+                    previous_version,
+                    previous.taxonomy_version
+                )
+            })?,
+        };
+
+        progress_callback("Loading taxonomy releases...");
+        let old_taxonomy = TaxonomySnapshot::from_dmp(&previous_tree)
+            .context("Failed to load previous taxonomy")?;
+        let new_taxonomy = TaxonomySnapshot::from_dmp(&talaria_taxonomy_current_dir().join("tree"))
+            .context("Failed to load current taxonomy")?;
+
+        let taxa: BTreeSet<TaxonId> = previous
+            .chunk_index
+            .iter()
+            .flat_map(|meta| meta.taxon_ids.iter().copied())
+            .collect();
+        let changes = diff_taxa(&old_taxonomy, &new_taxonomy, taxa.iter().copied());
+
+        let mut report = ReclassificationReport {
+            version: previous_version.clone(),
+            previous_version: previous_version.clone(),
+            taxonomy_version: taxonomy_version.clone(),
+            taxa_checked: taxa.len(),
+            ..Default::default()
+        };
+        for change in changes.values() {
+            match change {
+                TaxonChange::Merged(_) => report.taxa_merged += 1,
+                TaxonChange::Deleted => report.taxa_deleted += 1,
+                TaxonChange::Moved => report.taxa_moved += 1,
+            }
+        }
+
+        if changes.is_empty() {
+            progress_callback(&format!(
+                "✓ {} taxa unchanged, version {} kept",
+                report.taxa_checked, previous_version
+            ));
+            return Ok(report);
+        }
+
+        progress_callback(&format!(
+            "Reclassifying sequences of {} changed taxa...",
+            changes.len()
+        ));
+        let now = Utc::now();
+        let sequence_time = previous
+            .temporal_coordinate
+            .as_ref()
+            .map(|c| c.sequence_time)
+            .unwrap_or(previous.created_at);
+        let sequence_storage = Arc::clone(&self.get_repository().storage.sequence_storage);
+        let rocksdb = sequence_storage.get_rocksdb();
+
+        let mut log = ReclassificationLog::new();
+        let mut records: Vec<(String, Option<TaxonId>, SHA256Hash)> = Vec::new();
+        let mut chunk_index: Vec<ManifestMetadata> = Vec::with_capacity(previous.chunk_index.len());
+
+        for meta in &previous.chunk_index {
+            if !meta.taxon_ids.iter().any(|t| changes.contains_key(t)) {
+                chunk_index.push(meta.clone());
+                continue;
+            }
+            report.chunks_reindexed += 1;
+
+            let chunk = self
+                .load_manifest(&meta.hash)
+                .with_context(|| format!("Failed to load chunk manifest {}", meta.hash))?;
+            // Representations without their own taxon inherit a single-taxon chunk's
+            let chunk_taxon = match meta.taxon_ids.as_slice() {
+                [only] => Some(*only),
+                _ => None,
+            };
+
+            for canonical in &chunk.sequence_refs {
+                let representations = sequence_storage.load_representations(canonical)?;
+                for repr in representations.representations() {
+                    if &repr.source != source {
+                        continue;
+                    }
+                    let Some(old_taxon) = repr.taxon_id.or(chunk_taxon) else {
+                        continue;
+                    };
+                    let Some(change) = changes.get(&old_taxon) else {
+                        continue;
+                    };
+                    let new_taxon = change.new_taxon(old_taxon);
+                    let sequence_id = header_id(&repr.header);
+
+                    log.record(
+                        now,
+                        sequence_id,
+                        Some(old_taxon),
+                        new_taxon,
+                        change.reason(),
+                    );
+                    // Entries under the old taxon stay for queries against earlier versions
+                    if let (TaxonChange::Merged(_), Some(taxon)) = (change, new_taxon) {
+                        rocksdb.append_to_index_list(&format!("tax:{}", taxon.0), canonical)?;
+                    }
+                    records.push((sequence_id.to_string(), new_taxon, meta.hash));
+                }
+            }
+
+            let mut taxon_ids: Vec<TaxonId> = meta
+                .taxon_ids
+                .iter()
+                .filter_map(|t| match changes.get(t) {
+                    Some(change) => change.new_taxon(*t),
+                    None => Some(*t),
+                })
+                .collect();
+            taxon_ids.sort();
+            taxon_ids.dedup();
+            chunk_index.push(ManifestMetadata {
+                taxon_ids,
+                ..meta.clone()
+            });
+        }
+        report.sequences_reclassified = log.len();
+
+        let version = talaria_core::system::paths::generate_utc_timestamp();
+        let taxonomy_root = self
+            .get_repository()
+            .taxonomy
+            .get_taxonomy_root()
+            .unwrap_or(previous.taxonomy_root);
+        let manifest = TemporalManifest {
+            version: version.clone(),
+            created_at: now,
+            taxonomy_version: taxonomy_version.clone(),
+            taxonomy_dump_version: taxonomy_version.clone(),
+            temporal_coordinate: Some(BiTemporalCoordinate {
+                sequence_time,
+                taxonomy_time: now,
+            }),
+            taxonomy_root,
+            chunk_index,
+            etag: format!("{}-{}", source_name, version),
+            previous_version: Some(previous_version.clone()),
+            ..previous
+        };
+
+        let chunk_count = manifest.chunk_index.len();
+        let sequence_count = manifest.chunk_index.iter().map(|c| c.sequence_count).sum();
+        let total_size = manifest.chunk_index.iter().map(|c| c.size).sum();
+        self.save_manifest_to_repository(
+            &source_name,
+            &dataset_name,
+            &version,
+            &manifest,
+            chunk_count,
+            sequence_count,
+            total_size,
+        )?;
+        self.create_version_metadata(source, &version, Path::new(""))?;
+
+        let mut temporal_index = crate::TemporalIndex::load(&self.base_path, rocksdb)?;
+        temporal_index.add_taxonomy_version(
+            taxonomy_version,
+            taxonomy_root,
+            new_taxonomy.len(),
+            "ncbi".to_string(),
+        )?;
+        temporal_index.save()?;
+
+        for (sequence_id, taxon_id, chunk_hash) in records {
+            self.store_temporal_history(&TemporalSequenceRecord {
+                sequence_id,
+                version: version.clone(),
+                sequence_time,
+                taxonomy_time: now,
+                taxon_id: taxon_id.map(|t| t.0),
+                chunk_hash,
+            })?;
+        }
+
+        if let Some(cache) = &self.cache {
+            cache.invalidate_database(&source_name, &dataset_name);
+        }
+
+        report.mass_reclassifications = log.find_mass_reclassifications(mass_threshold, now, now);
+        progress_callback(&format!(
+            "✓ Created version {} ({} sequences reclassified in {} chunks)",
+            version, report.sequences_reclassified, report.chunks_reindexed
+        ));
+        report.version = version;
+
+        Ok(report)
+    }
+}
@@ -146,4 +146,335 @@ mod tests {
 
         std::env::remove_var("TALARIA_HOME");
     }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_incremental_release_update() {
+        let temp_dir = TempDir::new().unwrap();
+        std::env::set_var("TALARIA_HOME", temp_dir.path());
+
+        let mut manager = DatabaseManager::new(None).unwrap();
+        let source = test_database_source("incremental");
+
+        let initial = ["ACGTACGTACGT", "GCTAGCTAGCTA", "TTTTAAAACCCC"]
+            .iter()
+            .enumerate()
+            .map(|(i, seq)| Sequence {
+                id: format!("SEQ_{:03}", i + 1),
+                description: Some("initial release".to_string()),
+                sequence: seq.as_bytes().to_vec(),
+                taxon_id: Some(9606),
+                taxonomy_sources: Default::default(),
+            })
+            .collect();
+        manager
+            .chunk_sequences_direct_with_progress_final(initial, &source, None, true)
+            .unwrap();
+        let previous = manager.get_current_version_info(&source).unwrap().timestamp;
+
+        // Versions are second-resolution timestamps
+        std::thread::sleep(std::time::Duration::from_secs(1));
+
+        // SEQ_001 unchanged, SEQ_002 modified, SEQ_003 removed, SEQ_004 added
+        let release = temp_dir.path().join("release.fasta");
+        std::fs::write(
+            &release,
+            ">SEQ_001 initial release\nACGTACGTACGT\n\
+             >SEQ_002 initial release\nGCTAGCTAGGGG\n\
+             >SEQ_004 next release\nCCCCGGGGTTTT\n",
+        )
+        .unwrap();
+
+        let report = manager
+            .apply_incremental_release(&release, &source, &|_: &str| {})
+            .unwrap();
+
+        assert_eq!(report.sequences_added, 1);
+        assert_eq!(report.sequences_removed, 1);
+        assert_eq!(report.sequences_modified, 1);
+        assert_eq!(report.sequences_unchanged, 1);
+        assert_eq!(report.previous_version, previous);
+        assert_ne!(report.version, previous);
+
+        let manifest = manager
+            .get_manifest(&format!("custom/test_incremental@{}", report.version))
+            .unwrap();
+        assert_eq!(manifest.previous_version, Some(previous));
+        let total_sequences: usize = manifest.chunk_index.iter().map(|c| c.sequence_count).sum();
+        assert_eq!(total_sequences, 3);
+
+        // The lookup index of the previous version only lives during the update
+        let rocksdb = manager
+            .get_repository()
+            .storage
+            .sequence_storage
+            .get_rocksdb();
+        assert!(rocksdb
+            .iterate_index_prefix("incremental:")
+            .unwrap()
+            .is_empty());
+
+        std::env::remove_var("TALARIA_HOME");
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_incremental_release_removed_and_readded_id() {
+        let temp_dir = TempDir::new().unwrap();
+        std::env::set_var("TALARIA_HOME", temp_dir.path());
+
+        let mut manager = DatabaseManager::new(None).unwrap();
+        let source = test_database_source("incremental_readd");
+
+        // SEQ_A and SEQ_B share a sequence, so it stays after SEQ_A is removed
+        let initial = [
+            ("SEQ_A", "ACGTACGTACGT"),
+            ("SEQ_B", "ACGTACGTACGT"),
+            ("SEQ_C", "TTTTAAAACCCC"),
+        ]
+        .iter()
+        .map(|(id, seq)| Sequence {
+            id: id.to_string(),
+            description: Some("release".to_string()),
+            sequence: seq.as_bytes().to_vec(),
+            taxon_id: Some(9606),
+            taxonomy_sources: Default::default(),
+        })
+        .collect();
+        manager
+            .chunk_sequences_direct_with_progress_final(initial, &source, None, true)
+            .unwrap();
+
+        let without_a = temp_dir.path().join("without_a.fasta");
+        std::fs::write(
+            &without_a,
+            ">SEQ_B release\nACGTACGTACGT\n>SEQ_C release\nTTTTAAAACCCC\n",
+        )
+        .unwrap();
+        let with_a = temp_dir.path().join("with_a.fasta");
+        std::fs::write(
+            &with_a,
+            ">SEQ_A release\nACGTACGTACGT\n\
+             >SEQ_B release\nACGTACGTACGT\n\
+             >SEQ_C release\nTTTTAAAACCCC\n",
+        )
+        .unwrap();
+
+        let mut apply = |release: &std::path::Path| {
+            // Versions are second-resolution timestamps
+            std::thread::sleep(std::time::Duration::from_secs(1));
+            manager
+                .apply_incremental_release(release, &source, &|_: &str| {})
+                .unwrap()
+        };
+
+        let removed = apply(&without_a);
+        assert_eq!(removed.sequences_removed, 1);
+        assert_eq!(removed.sequences_unchanged, 2);
+
+        // The removed ID must not be counted as removed a second time
+        let repeated = apply(&without_a);
+        assert!(!repeated.has_changes());
+        assert_eq!(repeated.sequences_unchanged, 2);
+        assert_eq!(repeated.version, removed.version);
+
+        let readded = apply(&with_a);
+        assert_eq!(readded.sequences_added, 1);
+        assert_eq!(readded.sequences_removed, 0);
+        assert_eq!(readded.sequences_unchanged, 2);
+
+        let stable = apply(&with_a);
+        assert!(!stable.has_changes());
+        assert_eq!(stable.sequences_unchanged, 3);
+
+        std::env::remove_var("TALARIA_HOME");
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_reclassify_keeps_version_classified_against_current_taxonomy() {
+        let temp_dir = TempDir::new().unwrap();
+        std::env::set_var("TALARIA_HOME", temp_dir.path());
+
+        let mut manager = DatabaseManager::new(None).unwrap();
+        let source = test_database_source("reclassify_current");
+        let sequences = vec![Sequence {
+            id: "SEQ_001".to_string(),
+            description: Some("reclassify".to_string()),
+            sequence: b"ACGTACGTACGT".to_vec(),
+            taxon_id: Some(9606),
+            taxonomy_sources: Default::default(),
+        }];
+        manager
+            .chunk_sequences_direct_with_progress_final(sequences, &source, None, true)
+            .unwrap();
+        let version = manager.get_current_version_info(&source).unwrap().timestamp;
+
+        // Re-running (or forcing) must not mint identical versions
+        for _ in 0..2 {
+            let report = manager
+                .reclassify_with_taxonomy(&source, None, 100, &|_: &str| {})
+                .unwrap();
+            assert!(!report.has_changes());
+            assert_eq!(report.version, version);
+            assert_eq!(
+                report.taxonomy_version,
+                DatabaseManager::current_taxonomy_version()
+            );
+        }
+        assert_eq!(
+            manager.get_current_version_info(&source).unwrap().timestamp,
+            version
+        );
+
+        std::env::remove_var("TALARIA_HOME");
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_chunks_at_time_follows_database_versions() {
+        use crate::{SHA256Hash, TemporalManifest};
+        use std::collections::HashSet;
+
+        let temp_dir = TempDir::new().unwrap();
+        std::env::set_var("TALARIA_HOME", temp_dir.path());
+
+        let mut manager = DatabaseManager::new(None).unwrap();
+        let source = test_database_source("time_travel");
+        let before = chrono::Utc::now() - chrono::Duration::hours(1);
+        let sequences = vec![Sequence {
+            id: "SEQ_001".to_string(),
+            description: Some("first release".to_string()),
+            sequence: b"ACGTACGTACGT".to_vec(),
+            taxon_id: Some(9606),
+            taxonomy_sources: Default::default(),
+        }];
+        manager
+            .chunk_sequences_direct_with_progress_final(sequences, &source, None, true)
+            .unwrap();
+        let first = manager.get_current_version_info(&source).unwrap().timestamp;
+
+        // Versions are second-resolution timestamps
+        std::thread::sleep(std::time::Duration::from_secs(1));
+        let between = chrono::Utc::now();
+        std::thread::sleep(std::time::Duration::from_secs(1));
+
+        let release = temp_dir.path().join("release.fasta");
+        std::fs::write(
+            &release,
+            ">SEQ_001 first release OX=9606\nACGTACGTACGT\n\
+             >SEQ_002 second release OX=10090\nGGGGCCCCTTTT\n",
+        )
+        .unwrap();
+        let report = manager
+            .apply_incremental_release(&release, &source, &|_: &str| {})
+            .unwrap();
+
+        let chunks = |manifest: TemporalManifest| -> HashSet<SHA256Hash> {
+            manifest.chunk_index.iter().map(|c| c.hash).collect()
+        };
+        let at = |time, taxa| -> HashSet<SHA256Hash> {
+            manager
+                .chunks_at_time(time, time, taxa)
+                .unwrap()
+                .into_iter()
+                .collect()
+        };
+
+        assert!(at(before, None).is_empty());
+        let first_chunks = chunks(
+            manager
+                .get_manifest(&format!("custom/test_time_travel@{}", first))
+                .unwrap(),
+        );
+        assert_eq!(at(between, None), first_chunks);
+        let second_chunks = chunks(
+            manager
+                .get_manifest(&format!("custom/test_time_travel@{}", report.version))
+                .unwrap(),
+        );
+        assert_eq!(at(chrono::Utc::now(), None), second_chunks);
+
+        // The mouse sequence only exists from the second version on
+        assert!(at(between, Some(vec![10090])).is_empty());
+        assert!(!at(chrono::Utc::now(), Some(vec![10090])).is_empty());
+
+        std::env::remove_var("TALARIA_HOME");
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_streaming_content_defined_matches_single_pass() {
+        use crate::download::workspace::DownloadState;
+        use crate::SHA256Hash;
+
+        let temp_dir = TempDir::new().unwrap();
+        std::env::set_var("TALARIA_HOME", temp_dir.path());
+
+        let mut manager = DatabaseManager::new(None).unwrap();
+        let streamed = test_database_source("cdc_streamed");
+        let single = test_database_source("cdc_single");
+        let mut strategy = manager.chunking_strategy(&streamed).unwrap();
+        strategy.boundaries = crate::ChunkBoundaries::ContentDefined;
+        strategy.target_chunk_size = 500_000;
+        strategy.max_chunk_size = 2_000_000;
+        for source in [&streamed, &single] {
+            manager.set_chunking_strategy(source, &strategy).unwrap();
+        }
+
+        // More than one streaming batch of 10,000 sequences, over two taxa
+        let residues = b"ACDEFGHIKL";
+        let sequences: Vec<Sequence> = (0..12_500usize)
+            .map(|i| {
+                let taxon = if i % 3 == 0 { 562 } else { 4932 };
+                let mut sequence = b"MKTAYIAKQR".to_vec();
+                sequence.extend(
+                    format!("{:05}", i)
+                        .bytes()
+                        .map(|d| residues[(d - b'0') as usize]),
+                );
+                Sequence {
+                    id: format!("SEQ_{:05}", i),
+                    description: Some(format!("Protein {} OX={}", i, taxon)),
+                    sequence,
+                    taxon_id: Some(taxon),
+                    taxonomy_sources: Default::default(),
+                }
+            })
+            .collect();
+        let fasta = temp_dir.path().join("release.fasta");
+        talaria_bio::write_fasta(&fasta, &sequences).unwrap();
+
+        let mut state = DownloadState::new(streamed.clone(), temp_dir.path().join("workspace"));
+        manager
+            .chunk_database_streaming(&fasta, &streamed, &mut state, None, None)
+            .unwrap();
+        manager
+            .chunk_sequences_direct_with_progress_final(sequences, &single, None, true)
+            .unwrap();
+
+        let chunk_members = |name: &str| {
+            let manifest = manager
+                .get_manifest(&format!("custom/test_{}", name))
+                .unwrap();
+            let mut members: Vec<Vec<SHA256Hash>> = manifest
+                .chunk_index
+                .iter()
+                .map(|meta| {
+                    let mut refs = manager.load_manifest(&meta.hash).unwrap().sequence_refs;
+                    refs.sort();
+                    refs
+                })
+                .collect();
+            members.sort();
+            members
+        };
+        let streamed_chunks = chunk_members("cdc_streamed");
+        assert!(streamed_chunks.len() > 2);
+        assert_eq!(streamed_chunks.iter().map(Vec::len).sum::<usize>(), 12_500);
+        // Cuts do not depend on where the batches ended
+        assert_eq!(streamed_chunks, chunk_members("cdc_single"));
+
+        std::env::remove_var("TALARIA_HOME");
+    }
 }
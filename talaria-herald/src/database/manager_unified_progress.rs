@@ -196,6 +196,10 @@ impl DatabaseManager {
         let mut total_dedup = 0usize;
 
         let mut sequences_batch = Vec::with_capacity(BATCH_SIZE);
+        let chunking_strategy = self.chunking_strategy(source)?;
+        // Finishes the content-defined cuts deferred by the batch chunkers
+        let spool = self.streaming_chunker(chunking_strategy.clone(), source);
+        spool.clear_spool()?;
         let mut current_id = String::new();
         let mut current_desc = None;
         let mut current_seq = Vec::new();
@@ -228,17 +232,7 @@ impl DatabaseManager {
                         )?;
 
                         // Process batch using the quiet chunker
-                        // Create chunker for this batch
-                        let strategy = crate::chunker::ChunkingStrategy::default();
-                        // Use the existing SequenceStorage from the repository
-                        let sequence_storage =
-                            Arc::clone(&self.get_repository().storage.sequence_storage);
-                        let mut chunker = crate::chunker::TaxonomicChunker::new(
-                            strategy,
-                            sequence_storage,
-                            source.clone(),
-                        );
-                        chunker.set_quiet_mode(true);
+                        let mut chunker = self.streaming_chunker(chunking_strategy.clone(), source);
 
                         let manifests = chunker.chunk_sequences_canonical(sequences_batch)?;
 
@@ -292,14 +286,10 @@ impl DatabaseManager {
             ));
 
             // Create chunker for final batch
-            let strategy = crate::chunker::ChunkingStrategy::default();
-            // Use the existing SequenceStorage from the repository
-            let sequence_storage = Arc::clone(&self.get_repository().storage.sequence_storage);
-            let mut chunker =
-                crate::chunker::TaxonomicChunker::new(strategy, sequence_storage, source.clone());
-            chunker.set_quiet_mode(true);
+            let mut chunker = self.streaming_chunker(chunking_strategy.clone(), source);
             let _ = chunker.chunk_sequences_canonical(sequences_batch)?;
         }
+        spool.finish_spooled(|_| Ok(()))?;
 
         // Final update
         tracker.update_storing(total_sequences, total_new, total_dedup)?;
@@ -1,16 +1,26 @@
 //! Database management functionality for HERALD
 
+pub mod bundle;
 pub mod cache;
+pub mod compose;
 pub mod diff;
+pub mod lockfile;
 pub mod manager;
+pub mod manager_incremental;
+pub mod manager_reclassify;
 pub mod manager_resume;
 pub mod manager_unified_progress;
 
 #[cfg(test)]
 mod manager_test;
 
+pub use bundle::{BundleHeader, BundleImportReport, BundleOptions};
+pub use compose::{ComposeExpr, CompositionRecord, CompositionReport};
 pub use diff::DatabaseDiffer;
+pub use lockfile::{AnalysisLock, LockMismatch, LockRestoreReport, LockedDatabase};
 pub use manager::DatabaseManager;
+pub use manager_incremental::IncrementalUpdateReport;
+pub use manager_reclassify::ReclassificationReport;
 
 // Re-export comparison types from talaria-utils for convenience
 pub use talaria_utils::report::{
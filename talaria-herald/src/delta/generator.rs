@@ -68,6 +68,18 @@ impl DeltaGenerator {
         Ok(chunks)
     }
 
+    /// Chunk delta records that were already encoded against their references
+    ///
+    /// Unlike [`Self::generate_delta_chunks`], the reference each child was
+    /// encoded against is kept as given, so callers can record it.
+    pub fn chunk_delta_records(
+        &self,
+        delta_records: Vec<DeltaRecord>,
+        reference_chunk_hash: SHA256Hash,
+    ) -> Result<Vec<TemporalDeltaChunk>> {
+        self.batch_into_chunks(delta_records, reference_chunk_hash)
+    }
+
     /// Batch delta records into appropriately sized chunks
     fn batch_into_chunks(
         &self,
@@ -183,7 +195,8 @@ impl DeltaGenerator {
             .collect();
 
         // Compute content hash
-        let content_hash = SHA256Hash::compute(&serialized);
+        let content_hash =
+            TemporalDeltaChunk::compute_content_hash(&reference_chunk_hash, &delta_operations)?;
 
         // Use the HERALD TemporalDeltaChunk type that's defined in types.rs
         let delta_chunk = TemporalDeltaChunk {
@@ -330,7 +343,7 @@ impl DeltaGenerator {
         reference_hash: SHA256Hash,
     ) -> Result<TemporalDeltaChunk> {
         let serialized = serde_json::to_vec(&operations)?;
-        let content_hash = SHA256Hash::compute(&serialized);
+        let content_hash = TemporalDeltaChunk::compute_content_hash(&reference_hash, &operations)?;
 
         let delta_chunk = TemporalDeltaChunk {
             content_hash,
@@ -490,3 +503,33 @@ fn test_incremental_update_detection() {
     // Should have chunks for modifications and insertions
     assert!(!chunks.is_empty());
 }
+
+#[test]
+fn test_operation_chunks_keyed_by_reference() {
+    let generator = DeltaGenerator::new(DeltaGeneratorConfig::default());
+    let operations = vec![DeltaOperation::Insert {
+        sequence_id: "seq1".to_string(),
+        data: b"ACGT".to_vec(),
+    }];
+
+    let first_reference = SHA256Hash::compute(b"first");
+    let second_reference = SHA256Hash::compute(b"second");
+    let first = generator
+        .create_operation_chunk(operations.clone(), first_reference)
+        .unwrap();
+    let second = generator
+        .create_operation_chunk(operations, second_reference)
+        .unwrap();
+
+    // Identical operations against different references must not share a key
+    assert_ne!(first.content_hash, second.content_hash);
+    for chunk in [&first, &second] {
+        let data = serde_json::to_vec(chunk).unwrap();
+        assert!(crate::remote::verify_chunk_data(&chunk.content_hash, &data));
+    }
+
+    // Repointing re-keys the chunk to match one built against the new reference
+    let mut repointed = first.clone();
+    repointed.set_reference(second_reference);
+    assert_eq!(repointed.content_hash, second.content_hash);
+}
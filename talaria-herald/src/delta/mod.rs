@@ -1,5 +1,6 @@
 pub mod canonical;
 pub mod generator;
+pub mod rebase;
 pub mod reconstructor;
 /// Delta encoding and reconstruction module
 pub mod traits;
@@ -7,5 +8,6 @@ pub mod traits;
 // Re-export main types
 pub use canonical::{CanonicalDelta, CanonicalDeltaManager, Delta, DeltaOp};
 pub use generator::DeltaGenerator as SequenceDeltaGenerator;
+pub use rebase::{DeltaRebaseReport, DeltaRebaser};
 pub use reconstructor::{DeltaReconstructor as SequenceDeltaReconstructor, ReconstructorConfig};
 pub use traits::{DeltaGenerator, DeltaGeneratorConfig, DeltaReconstructor};
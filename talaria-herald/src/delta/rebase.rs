@@ -0,0 +1,440 @@
+/// Delta chain rebasing
+///
+/// Every delta chunk built on top of another delta chunk adds one more
+/// reconstruction step. Chunks whose chain is deeper than the configured
+/// limit are re-encoded directly against the materialized chunk at the end of
+/// their chain, so reading them needs a single delta application again.
+use crate::delta::canonical::{Delta, DeltaCompressor, DeltaOp, MyersDeltaCompressor};
+use crate::delta::reconstructor::{DeltaChainManager, DeltaReconstructor, ReconstructorConfig};
+use crate::operations::FastaAssembler;
+use crate::storage::HeraldStorage;
+use crate::types::*;
+use anyhow::{bail, Result};
+use lru::LruCache;
+use std::collections::{BTreeMap, HashMap};
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+use talaria_bio::sequence::Sequence;
+
+/// Reconstructed chunks kept in memory during a run
+const MATERIALIZED_CACHE_CHUNKS: usize = 64;
+
+/// Outcome of a rebase run
+#[derive(Debug, Clone, Default)]
+pub struct DeltaRebaseReport {
+    /// Number of delta chunks at each chain depth before rebasing
+    pub depth_before: BTreeMap<usize, usize>,
+    /// Number of delta chunks at each chain depth after rebasing
+    pub depth_after: BTreeMap<usize, usize>,
+    pub chunks_rebased: usize,
+    /// Chunks that could not be re-encoded and were left untouched
+    pub chunks_skipped: usize,
+    /// Sequences stored as edits against the new reference
+    pub sequences_reencoded: usize,
+    /// Replaced chunks deleted from storage
+    pub chunks_removed: usize,
+}
+
+impl DeltaRebaseReport {
+    pub fn max_depth_before(&self) -> usize {
+        self.depth_before.keys().next_back().copied().unwrap_or(0)
+    }
+
+    pub fn max_depth_after(&self) -> usize {
+        self.depth_after.keys().next_back().copied().unwrap_or(0)
+    }
+}
+
+/// Re-encodes delta chunks whose chain exceeds a maximum depth
+pub struct DeltaRebaser<'a> {
+    storage: &'a HeraldStorage,
+    max_depth: usize,
+    dry_run: bool,
+    compressor: MyersDeltaCompressor,
+    reconstructor: DeltaReconstructor,
+}
+
+/// Delta chunks and reconstructed sequences of one rebase run
+struct RebaseState {
+    chunks: HashMap<SHA256Hash, TemporalDeltaChunk>,
+    chains: DeltaChainManager,
+    materialized: LruCache<SHA256Hash, Arc<Vec<Sequence>>>,
+    /// New hash of each chunk replaced during the run
+    renamed: HashMap<SHA256Hash, SHA256Hash>,
+    /// Stored chunks replaced during the run, deleted once it is done
+    superseded: Vec<SHA256Hash>,
+}
+
+impl<'a> DeltaRebaser<'a> {
+    pub fn new(storage: &'a HeraldStorage, max_depth: usize) -> Self {
+        Self {
+            storage,
+            max_depth: max_depth.max(1),
+            dry_run: false,
+            compressor: MyersDeltaCompressor::new(1000, true),
+            reconstructor: DeltaReconstructor::new(ReconstructorConfig {
+                parallel: false,
+                ..Default::default()
+            }),
+        }
+    }
+
+    /// Only report what would be rebased, without writing anything
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Rebase all delta chunks deeper than the maximum depth
+    ///
+    /// Candidates are processed shallowest first: once a chunk sits directly
+    /// on a materialized chunk, the chains of its descendants shrink with it
+    /// and they often no longer need rebasing themselves.
+    pub fn run(&self) -> Result<DeltaRebaseReport> {
+        let mut state = RebaseState {
+            chunks: HashMap::new(),
+            chains: DeltaChainManager::new(self.max_depth),
+            materialized: LruCache::new(
+                NonZeroUsize::new(MATERIALIZED_CACHE_CHUNKS).expect("cache size is not zero"),
+            ),
+            renamed: HashMap::new(),
+            superseded: Vec::new(),
+        };
+        for hash in self.storage.list_delta_chunks()? {
+            match self.storage.get_delta_chunk(&hash) {
+                Ok(chunk) => {
+                    state.chains.add_chunk(&chunk);
+                    state.chunks.insert(hash, chunk);
+                }
+                Err(e) => tracing::warn!("Skipping unreadable delta chunk {}: {}", hash, e),
+            }
+        }
+
+        let mut report = DeltaRebaseReport {
+            depth_before: state.chains.depth_histogram(),
+            ..Default::default()
+        };
+
+        for candidate in state.chains.get_rebase_candidates() {
+            // Rebasing an ancestor re-keys its descendants
+            let candidate = state.current_hash(candidate);
+            if !state.chains.needs_rebase(&candidate) {
+                continue;
+            }
+            let Some(root) = state.chains.materialized_root(&candidate) else {
+                continue;
+            };
+
+            let rebased = if self.dry_run {
+                let mut chunk = state.chunks[&candidate].clone();
+                chunk.set_reference(root);
+                Some(chunk)
+            } else {
+                match self.reencode(&mut state, &candidate, root) {
+                    Ok(Some((chunk, reencoded))) => {
+                        let replaced =
+                            self.storage
+                                .replace_delta_chunk(&candidate, &chunk, &state.chains)?;
+                        state.superseded.extend(replaced);
+                        report.sequences_reencoded += reencoded;
+                        Some(chunk)
+                    }
+                    Ok(None) => None,
+                    Err(e) => {
+                        tracing::warn!("Failed to rebase delta chunk {}: {}", candidate, e);
+                        None
+                    }
+                }
+            };
+
+            match rebased {
+                Some(chunk) => {
+                    state.replace(&candidate, chunk);
+                    report.chunks_rebased += 1;
+                }
+                None => report.chunks_skipped += 1,
+            }
+        }
+
+        report.depth_after = state.chains.depth_histogram();
+        report.chunks_removed = self
+            .storage
+            .remove_superseded_delta_chunks(&state.superseded)?;
+        Ok(report)
+    }
+
+    /// Encode the sequences of `hash` as a single delta against `root`
+    ///
+    /// Returns `None` if the new chunk does not reproduce the original
+    /// sequences, in which case the chunk must be left as it is.
+    fn reencode(
+        &self,
+        state: &mut RebaseState,
+        hash: &SHA256Hash,
+        root: SHA256Hash,
+    ) -> Result<Option<(TemporalDeltaChunk, usize)>> {
+        let target = self.materialize(state, hash, state.chunks.len())?;
+        let reference = self.materialize(state, &root, 0)?;
+        let reference_by_id: HashMap<&str, &Sequence> =
+            reference.iter().map(|s| (s.id.as_str(), s)).collect();
+
+        let mut deltas = Vec::with_capacity(target.len());
+        let mut reencoded = 0;
+        for sequence in target.iter() {
+            let edits = match reference_by_id.get(sequence.id.as_str()) {
+                Some(base) => {
+                    let delta = self
+                        .compressor
+                        .compute_delta(&base.sequence, &sequence.sequence)?;
+                    edits_from_delta(base.sequence.len(), &delta)
+                }
+                None => None,
+            };
+            deltas.push(match edits {
+                Some(operations) => {
+                    reencoded += 1;
+                    DeltaOperation::Modify {
+                        sequence_id: sequence.id.clone(),
+                        reference_offset: 0,
+                        operations,
+                    }
+                }
+                None => DeltaOperation::Insert {
+                    sequence_id: sequence.id.clone(),
+                    data: sequence.sequence.clone(),
+                },
+            });
+        }
+
+        let original = &state.chunks[hash];
+        deltas.extend(
+            original
+                .deltas
+                .iter()
+                .filter(|op| matches!(op, DeltaOperation::Delete { .. }))
+                .cloned(),
+        );
+
+        let serialized = serde_json::to_vec(&deltas)?;
+        let original_size: usize = target.iter().map(|s| s.sequence.len()).sum();
+        let compression_ratio = if original_size > 0 {
+            serialized.len() as f32 / original_size as f32
+        } else {
+            1.0
+        };
+        let chunk = TemporalDeltaChunk {
+            content_hash: TemporalDeltaChunk::compute_content_hash(&root, &deltas)?,
+            reference_hash: root,
+            chunk_type: ChunkClassification::Delta {
+                reference_hash: root,
+                compression_ratio,
+            },
+            deltas,
+            original_size,
+            compressed_size: serialized.len(),
+            compression_ratio,
+            ..original.clone()
+        };
+
+        let rebuilt = self
+            .reconstructor
+            .reconstruct_uncached(&chunk, &reference)?;
+        let identical = rebuilt.len() == target.len()
+            && rebuilt
+                .iter()
+                .zip(target.iter())
+                .all(|(a, b)| a.id == b.id && a.sequence == b.sequence);
+        if !identical {
+            tracing::warn!(
+                "Re-encoded delta chunk {} does not reproduce its sequences, keeping it",
+                hash
+            );
+            return Ok(None);
+        }
+
+        Ok(Some((chunk, reencoded)))
+    }
+
+    /// Sequences of a chunk, applying its delta chain if it is a delta chunk
+    fn materialize(
+        &self,
+        state: &mut RebaseState,
+        hash: &SHA256Hash,
+        budget: usize,
+    ) -> Result<Arc<Vec<Sequence>>> {
+        if let Some(sequences) = state.materialized.get(hash) {
+            return Ok(sequences.clone());
+        }
+
+        let sequences = match state.chunks.get(hash).cloned() {
+            Some(chunk) => {
+                if budget == 0 {
+                    bail!("Delta chain of {} does not end in a full chunk", hash);
+                }
+                let reference = self.materialize(state, &chunk.reference_hash, budget - 1)?;
+                self.reconstructor
+                    .reconstruct_uncached(&chunk, &reference)?
+            }
+            None => FastaAssembler::new(self.storage)
+                .with_verification(false)
+                .assemble_from_chunks(&[*hash])?,
+        };
+
+        let sequences = Arc::new(sequences);
+        state.materialized.put(*hash, sequences.clone());
+        Ok(sequences)
+    }
+}
+
+impl RebaseState {
+    /// Swap `old` for its rebased version and repoint its children
+    ///
+    /// Delta chunks are keyed by their reference as well, so every repointed
+    /// descendant is replaced under a new hash in turn, as the storage does.
+    fn replace(&mut self, old: &SHA256Hash, chunk: TemporalDeltaChunk) {
+        let new_hash = chunk.content_hash;
+        let children = self.chains.children(old).to_vec();
+        self.chains.remove_chunk(old);
+        self.chunks.remove(old);
+        if let Some(sequences) = self.materialized.pop(old) {
+            self.materialized.put(new_hash, sequences);
+        }
+        self.renamed.insert(*old, new_hash);
+
+        self.chains.add_chunk(&chunk);
+        self.chunks.insert(new_hash, chunk);
+
+        for child_hash in children {
+            if let Some(mut child) = self.chunks.get(&child_hash).cloned() {
+                child.set_reference(new_hash);
+                self.replace(&child_hash, child);
+            }
+        }
+    }
+
+    /// Hash `hash` is stored under after the replacements so far
+    fn current_hash(&self, mut hash: SHA256Hash) -> SHA256Hash {
+        while let Some(renamed) = self.renamed.get(&hash) {
+            if *renamed == hash {
+                break;
+            }
+            hash = *renamed;
+        }
+        hash
+    }
+}
+
+/// Convert a byte-level delta into edits applied in place to the reference
+///
+/// Edits are positioned against the sequence as it is being rewritten, which
+/// is how the reconstructor applies them. Returns `None` if the delta copies
+/// reference bytes out of order, or if it shares nothing with the reference
+/// and a plain insert is cheaper.
+pub fn edits_from_delta(reference_len: usize, delta: &Delta) -> Option<Vec<SeqEdit>> {
+    let mut edits = Vec::new();
+    // Position in the rewritten sequence and in the original reference
+    let mut pos = 0;
+    let mut ref_pos = 0;
+    let mut copied = false;
+
+    for op in &delta.ops {
+        match op {
+            DeltaOp::Insert { data } => {
+                if data.is_empty() {
+                    continue;
+                }
+                edits.push(SeqEdit::Insert {
+                    pos,
+                    bases: data.clone(),
+                });
+                pos += data.len();
+            }
+            DeltaOp::Skip { length } => {
+                if *length > 0 {
+                    edits.push(SeqEdit::Delete {
+                        pos,
+                        count: *length,
+                    });
+                }
+                ref_pos += length;
+            }
+            DeltaOp::Copy { offset, length } => {
+                if *offset < ref_pos {
+                    return None;
+                }
+                if *offset > ref_pos {
+                    edits.push(SeqEdit::Delete {
+                        pos,
+                        count: offset - ref_pos,
+                    });
+                }
+                pos += length;
+                ref_pos = offset + length;
+                copied |= *length > 0;
+            }
+        }
+    }
+
+    if !copied || ref_pos > reference_len {
+        return None;
+    }
+    if ref_pos < reference_len {
+        edits.push(SeqEdit::Delete {
+            pos,
+            count: reference_len - ref_pos,
+        });
+    }
+
+    Some(edits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(reference: &[u8], edits: &[SeqEdit]) -> Vec<u8> {
+        let mut sequence = reference.to_vec();
+        for edit in edits {
+            match edit {
+                SeqEdit::Substitute { pos, new_base } => sequence[*pos] = *new_base,
+                SeqEdit::Insert { pos, bases } => {
+                    sequence.splice(*pos..*pos, bases.iter().copied());
+                }
+                SeqEdit::Delete { pos, count } => {
+                    sequence.drain(*pos..*pos + *count);
+                }
+            }
+        }
+        sequence
+    }
+
+    #[test]
+    fn test_edits_from_delta_roundtrip() {
+        let compressor = MyersDeltaCompressor::new(1000, true);
+        let cases: [(&[u8], &[u8]); 4] = [
+            (b"ACGTACGTACGTACGT", b"ACGTACGTACGTACGT"),
+            (b"ACGTACGTACGTACGT", b"ACGTTTACGTACGAACGTA"),
+            (b"MKVLAAGIVGLLLAQ", b"KVLAAGIVGLL"),
+            (b"MKVLAAGIVGLLLAQ", b"WWMKVLAGIVGLLLAQWW"),
+        ];
+
+        for (reference, target) in cases {
+            let delta = compressor.compute_delta(reference, target).unwrap();
+            let edits = edits_from_delta(reference.len(), &delta).unwrap();
+            assert_eq!(apply(reference, &edits), target);
+        }
+    }
+
+    #[test]
+    fn test_edits_from_delta_rejects_unrelated() {
+        let delta = Delta {
+            ops: vec![DeltaOp::Insert {
+                data: b"TTTT".to_vec(),
+            }],
+            original_size: 4,
+            delta_size: 4,
+            compression_ratio: 1.0,
+        };
+        assert!(edits_from_delta(8, &delta).is_none());
+    }
+}
@@ -3,7 +3,7 @@ use crate::types::*;
 use anyhow::Result;
 use dashmap::DashMap;
 use rayon::prelude::*;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 /// Delta reconstruction for HERALD delta chunks
 ///
@@ -62,7 +62,15 @@ impl DeltaReconstructor {
     ) -> Result<Vec<Sequence>> {
         // Cache the reference sequences
         self.cache_references(&delta_chunk.reference_hash, reference_sequences.clone())?;
+        self.reconstruct_uncached(delta_chunk, &reference_sequences)
+    }
 
+    /// Reconstruct sequences from a delta chunk without caching its reference
+    pub fn reconstruct_uncached(
+        &self,
+        delta_chunk: &TemporalDeltaChunk,
+        reference_sequences: &[Sequence],
+    ) -> Result<Vec<Sequence>> {
         // Build reference map by ID
         let ref_map: HashMap<String, &Sequence> = reference_sequences
             .iter()
@@ -79,6 +87,18 @@ impl DeltaReconstructor {
         Ok(sequences)
     }
 
+    /// Rebuild one sequence from its delta operation and its own reference
+    ///
+    /// Returns `None` for a deleted sequence.
+    pub fn reconstruct_sequence(
+        &self,
+        operation: &DeltaOperation,
+        reference: &Sequence,
+    ) -> Result<Option<Sequence>> {
+        let ref_map = HashMap::from([(reference.id.clone(), reference)]);
+        self.apply_delta_operation(operation, &ref_map)
+    }
+
     /// Sequential reconstruction
     fn reconstruct_sequential(
         &self,
@@ -132,11 +152,18 @@ impl DeltaReconstructor {
                         anyhow::anyhow!("No suitable reference found for {}", sequence_id)
                     })?;
 
+                // A zero length means the rest of the reference from the offset
+                let length = if *length == 0 {
+                    ref_seq.sequence.len().saturating_sub(*reference_offset)
+                } else {
+                    *length
+                };
+
                 // Extract the subsequence if needed
-                let sequence = if *reference_offset == 0 && *length == ref_seq.sequence.len() {
+                let sequence = if *reference_offset == 0 && length == ref_seq.sequence.len() {
                     ref_seq.sequence.clone()
                 } else {
-                    ref_seq.sequence[*reference_offset..*reference_offset + *length].to_vec()
+                    ref_seq.sequence[*reference_offset..*reference_offset + length].to_vec()
                 };
 
                 Ok(Some(Sequence {
@@ -326,17 +353,16 @@ pub struct CacheStats {
 }
 
 /// Delta chain manager for preventing long chains
+///
+/// Chunks may be added in any order; depths are resolved by walking the
+/// reference chain. A chunk whose reference is not a tracked delta chunk
+/// sits directly on a materialized chunk and has depth 1.
 pub struct DeltaChainManager {
     max_depth: usize,
-    chain_map: HashMap<SHA256Hash, ChainInfo>,
-}
-
-#[derive(Debug, Clone)]
-struct ChainInfo {
-    depth: usize,
-    #[allow(dead_code)]
-    reference: SHA256Hash,
-    children: Vec<SHA256Hash>,
+    /// Reference of each tracked delta chunk
+    chain_map: HashMap<SHA256Hash, SHA256Hash>,
+    /// Delta chunks referencing each chunk, tracked or not
+    children: HashMap<SHA256Hash, Vec<SHA256Hash>>,
 }
 
 impl DeltaChainManager {
@@ -344,48 +370,110 @@ impl DeltaChainManager {
         Self {
             max_depth,
             chain_map: HashMap::new(),
+            children: HashMap::new(),
         }
     }
 
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
     /// Add a delta chunk to the chain tracker
     pub fn add_chunk(&mut self, chunk: &TemporalDeltaChunk) {
         if let ChunkClassification::Delta { reference_hash, .. } = &chunk.chunk_type {
-            let depth = self
+            if self
                 .chain_map
-                .get(reference_hash)
-                .map(|info| info.depth + 1)
-                .unwrap_or(1);
-
-            let info = ChainInfo {
-                depth,
-                reference: reference_hash.clone(),
-                children: Vec::new(),
-            };
+                .insert(chunk.content_hash, *reference_hash)
+                .is_none()
+            {
+                self.children
+                    .entry(*reference_hash)
+                    .or_default()
+                    .push(chunk.content_hash);
+            }
+        }
+    }
+
+    /// Remove a chunk from the tracker, e.g. after it was replaced
+    pub fn remove_chunk(&mut self, chunk_hash: &SHA256Hash) {
+        if let Some(reference) = self.chain_map.remove(chunk_hash) {
+            if let Some(siblings) = self.children.get_mut(&reference) {
+                siblings.retain(|child| child != chunk_hash);
+                if siblings.is_empty() {
+                    self.children.remove(&reference);
+                }
+            }
+        }
+    }
 
-            self.chain_map.insert(chunk.content_hash, info);
+    /// Number of tracked delta chunks
+    pub fn len(&self) -> usize {
+        self.chain_map.len()
+    }
 
-            // Update parent's children list
-            if let Some(parent) = self.chain_map.get_mut(reference_hash) {
-                parent.children.push(chunk.content_hash);
+    pub fn is_empty(&self) -> bool {
+        self.chain_map.is_empty()
+    }
+
+    /// Delta chunks referencing `chunk_hash` directly
+    pub fn children(&self, chunk_hash: &SHA256Hash) -> &[SHA256Hash] {
+        self.children
+            .get(chunk_hash)
+            .map(|children| children.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// References from `chunk_hash` up to and including the materialized chunk
+    ///
+    /// Empty for chunks that are not tracked delta chunks.
+    pub fn chain(&self, chunk_hash: &SHA256Hash) -> Vec<SHA256Hash> {
+        let mut chain = Vec::new();
+        let mut current = chunk_hash;
+        while let Some(reference) = self.chain_map.get(current) {
+            // A cycle would never reach a materialized chunk
+            if chain.len() > self.chain_map.len() {
+                break;
             }
+            chain.push(*reference);
+            current = reference;
         }
+        chain
+    }
+
+    /// Number of deltas that must be applied to reconstruct `chunk_hash`
+    pub fn depth(&self, chunk_hash: &SHA256Hash) -> usize {
+        self.chain(chunk_hash).len()
+    }
+
+    /// Materialized chunk at the end of the chain of `chunk_hash`
+    pub fn materialized_root(&self, chunk_hash: &SHA256Hash) -> Option<SHA256Hash> {
+        self.chain(chunk_hash).last().copied()
     }
 
     /// Check if a chunk needs rebasing
     pub fn needs_rebase(&self, chunk_hash: &SHA256Hash) -> bool {
-        self.chain_map
-            .get(chunk_hash)
-            .map(|info| info.depth > self.max_depth)
-            .unwrap_or(false)
+        self.depth(chunk_hash) > self.max_depth
     }
 
-    /// Get all chunks that need rebasing
+    /// Get all chunks that need rebasing, shallowest first
     pub fn get_rebase_candidates(&self) -> Vec<SHA256Hash> {
-        self.chain_map
-            .iter()
-            .filter(|(_, info)| info.depth > self.max_depth)
-            .map(|(hash, _)| hash.clone())
-            .collect()
+        let mut candidates: Vec<(usize, SHA256Hash)> = self
+            .chain_map
+            .keys()
+            .map(|hash| (self.depth(hash), *hash))
+            .filter(|(depth, _)| *depth > self.max_depth)
+            .collect();
+        candidates.sort();
+        candidates.into_iter().map(|(_, hash)| hash).collect()
+    }
+
+    /// Number of tracked delta chunks at each chain depth
+    pub fn depth_histogram(&self) -> BTreeMap<usize, usize> {
+        let mut histogram = BTreeMap::new();
+        for hash in self.chain_map.keys() {
+            *histogram.entry(self.depth(hash)).or_insert(0) += 1;
+        }
+        histogram
     }
 }
 
@@ -480,6 +568,55 @@ mod tests {
         assert_eq!(sequence, b"ACGT");
     }
 
+    #[test]
+    fn test_reconstruct_sequence_against_own_reference() {
+        let reconstructor = DeltaReconstructor::default();
+        let reference = Sequence {
+            id: "REF1".to_string(),
+            description: Some("reference protein".to_string()),
+            sequence: b"MKVLAT".to_vec(),
+            taxon_id: Some(9606),
+            taxonomy_sources: Default::default(),
+        };
+
+        // Zero length covers the whole reference
+        let identical = DeltaOperation::UseReference {
+            sequence_id: "CHILD1".to_string(),
+            reference_offset: 0,
+            length: 0,
+        };
+        let seq = reconstructor
+            .reconstruct_sequence(&identical, &reference)
+            .unwrap()
+            .unwrap();
+        assert_eq!(seq.id, "CHILD1");
+        assert_eq!(seq.sequence, b"MKVLAT");
+
+        let modified = DeltaOperation::Modify {
+            sequence_id: "CHILD2".to_string(),
+            reference_offset: 0,
+            operations: vec![SeqEdit::Substitute {
+                pos: 2,
+                new_base: b'I',
+            }],
+        };
+        let seq = reconstructor
+            .reconstruct_sequence(&modified, &reference)
+            .unwrap()
+            .unwrap();
+        assert_eq!(seq.id, "CHILD2");
+        assert_eq!(seq.sequence, b"MKILAT");
+        assert_eq!(seq.taxon_id, Some(9606));
+
+        let deleted = DeltaOperation::Delete {
+            sequence_id: "CHILD3".to_string(),
+        };
+        assert!(reconstructor
+            .reconstruct_sequence(&deleted, &reference)
+            .unwrap()
+            .is_none());
+    }
+
     #[test]
     fn test_chain_manager() {
         let mut manager = DeltaChainManager::new(2);
@@ -506,4 +643,62 @@ mod tests {
         manager.add_chunk(&chunk);
         assert!(!manager.needs_rebase(&chunk.content_hash));
     }
+
+    fn delta_on(name: &[u8], reference: SHA256Hash) -> TemporalDeltaChunk {
+        TemporalDeltaChunk {
+            content_hash: SHA256Hash::compute(name),
+            reference_hash: reference,
+            chunk_type: ChunkClassification::Delta {
+                reference_hash: reference,
+                compression_ratio: 0.5,
+            },
+            taxonomy_version: SHA256Hash::zero(),
+            taxon_ids: Vec::new(),
+            deltas: Vec::new(),
+            sequences: Vec::new(),
+            created_at: chrono::Utc::now(),
+            valid_from: chrono::Utc::now(),
+            valid_until: None,
+            original_size: 1000,
+            compressed_size: 500,
+            compression_ratio: 0.5,
+        }
+    }
+
+    #[test]
+    fn test_chain_manager_depths_out_of_order() {
+        let root = SHA256Hash::compute(b"root");
+        let first = delta_on(b"first", root);
+        let second = delta_on(b"second", first.content_hash);
+        let third = delta_on(b"third", second.content_hash);
+
+        let mut manager = DeltaChainManager::new(2);
+        manager.add_chunk(&third);
+        manager.add_chunk(&first);
+        manager.add_chunk(&second);
+
+        assert_eq!(manager.depth(&third.content_hash), 3);
+        assert_eq!(manager.materialized_root(&third.content_hash), Some(root));
+        assert_eq!(
+            manager.children(&first.content_hash),
+            &[second.content_hash]
+        );
+        assert_eq!(manager.children(&root), &[first.content_hash]);
+        assert_eq!(manager.get_rebase_candidates(), vec![third.content_hash]);
+        assert_eq!(
+            manager.depth_histogram().into_iter().collect::<Vec<_>>(),
+            vec![(1, 1), (2, 1), (3, 1)]
+        );
+
+        manager.remove_chunk(&third.content_hash);
+        assert!(manager.get_rebase_candidates().is_empty());
+        assert!(manager.children(&second.content_hash).is_empty());
+
+        // Re-adding a chunk does not list it twice
+        manager.add_chunk(&second);
+        assert_eq!(
+            manager.children(&first.content_hash),
+            &[second.content_hash]
+        );
+    }
 }
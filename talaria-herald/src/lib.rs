@@ -103,7 +103,7 @@ pub mod cloud;
 pub mod download;
 
 // Re-export commonly used types
-pub use chunker::{ChunkingStrategy, TaxonomicChunker};
+pub use chunker::{ChunkBoundaries, ChunkingStrategy, TaxonomicChunker};
 pub use manifest::Manifest;
 pub use storage::{
     ChunkAccessTracker, ChunkCompressor, ChunkIndexBuilder, ChunkMetadata, ChunkQuery,
@@ -131,7 +131,7 @@ pub use storage::indices;
 pub use storage::sequence::SequenceStorage as sequence_storage;
 pub use talaria_storage::format;
 pub use taxonomy::evolution::{
-    MassReclassification, TaxonEvolutionReport, TaxonomyEvolutionTracker,
+    MassReclassification, ReclassificationLog, TaxonEvolutionReport, TaxonomyEvolutionTracker,
 };
 pub use taxonomy::filter as taxonomy_filter;
 pub use temporal::{
@@ -172,6 +172,9 @@ impl HeraldRepository {
     }
 
     /// Open an existing HERALD repository
+    ///
+    /// Fails if the repository uses an on-disk format that must be migrated
+    /// with `talaria herald migrate` first, or one newer than this build.
     pub fn open(base_path: &Path) -> Result<Self> {
         let storage = HeraldStorage::open(base_path)?;
         let manifest =
@@ -0,0 +1,488 @@
+/// Random access to the sequences of a reduction profile by accession
+///
+/// Reconstructing a reduced database walks every reference and delta chunk.
+/// When only a few full-length sequences are needed, e.g. the hits of a
+/// search against the reduced database, the per-profile accession index maps
+/// each child to the delta chunk holding its edits and the canonical hash of
+/// its own reference, so only those are loaded. The index is stored one key
+/// per accession, so a lookup reads only the entries it asks for.
+use crate::delta::SequenceDeltaReconstructor;
+use crate::storage::HeraldStorage;
+use crate::types::{DeltaOperation, SHA256Hash, TemporalDeltaChunk};
+use anyhow::{Context, Result};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use talaria_bio::sequence::Sequence;
+use talaria_storage::backend::RocksDBBackend;
+
+/// Where a sequence of a reduction profile is rebuilt from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccessionLocation {
+    /// Canonical hash of the reference (of the sequence itself for references)
+    pub reference_hash: SHA256Hash,
+    /// Delta chunk holding the child's edits; `None` for references
+    pub delta_chunk: Option<SHA256Hash>,
+}
+
+/// Accession index for one reduction profile, built at reduce time
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AccessionIndex {
+    pub profile: String,
+    entries: HashMap<String, AccessionLocation>,
+}
+
+impl AccessionIndex {
+    pub fn new(profile: impl Into<String>) -> Self {
+        Self {
+            profile: profile.into(),
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Index a reference sequence kept by the reduction
+    pub fn add_reference(&mut self, id: String, hash: SHA256Hash) {
+        self.entries.insert(
+            id,
+            AccessionLocation {
+                reference_hash: hash,
+                delta_chunk: None,
+            },
+        );
+    }
+
+    /// Index the children of a stored delta chunk
+    ///
+    /// `child_references` maps child IDs to the canonical hash of their
+    /// reference. Children without one are skipped. Returns how many were
+    /// indexed.
+    pub fn add_delta_chunk(
+        &mut self,
+        chunk: &TemporalDeltaChunk,
+        child_references: &HashMap<String, SHA256Hash>,
+    ) -> usize {
+        let mut indexed = 0;
+        for seq_ref in &chunk.sequences {
+            if let Some(reference_hash) = child_references.get(&seq_ref.sequence_id) {
+                self.entries.insert(
+                    seq_ref.sequence_id.clone(),
+                    AccessionLocation {
+                        reference_hash: *reference_hash,
+                        delta_chunk: Some(chunk.content_hash),
+                    },
+                );
+                indexed += 1;
+            }
+        }
+        indexed
+    }
+
+    pub fn get(&self, accession: &str) -> Option<&AccessionLocation> {
+        self.entries.get(accession)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Number of indexed children (sequences stored as deltas)
+    pub fn child_count(&self) -> usize {
+        self.entries
+            .values()
+            .filter(|l| l.delta_chunk.is_some())
+            .count()
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = (&String, &AccessionLocation)> {
+        self.entries.iter()
+    }
+}
+
+/// Point lookups into the accession index of a reduction profile
+pub trait AccessionLookup: Sync {
+    fn profile(&self) -> &str;
+
+    fn locate(&self, accession: &str) -> Result<Option<AccessionLocation>>;
+}
+
+impl AccessionLookup for AccessionIndex {
+    fn profile(&self) -> &str {
+        &self.profile
+    }
+
+    fn locate(&self, accession: &str) -> Result<Option<AccessionLocation>> {
+        Ok(self.get(accession).cloned())
+    }
+}
+
+/// Accession index of a stored reduction profile
+///
+/// Returned by `HeraldStorage::get_accession_index`. Entries are read from
+/// storage on demand.
+pub struct StoredAccessionIndex {
+    backend: Arc<RocksDBBackend>,
+    profile: String,
+    key: String,
+    len: usize,
+}
+
+impl StoredAccessionIndex {
+    pub(crate) fn new(
+        backend: Arc<RocksDBBackend>,
+        profile: String,
+        key: String,
+        len: usize,
+    ) -> Self {
+        Self {
+            backend,
+            profile,
+            key,
+            len,
+        }
+    }
+
+    pub fn profile(&self) -> &str {
+        &self.profile
+    }
+
+    pub fn get(&self, accession: &str) -> Result<Option<AccessionLocation>> {
+        self.backend
+            .get_index(&format!("{}:{}", self.key, accession))?
+            .map(|data| rmp_serde::from_slice(&data).map_err(Into::into))
+            .transpose()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Read every entry, e.g. to export the profile
+    pub fn load(&self) -> Result<AccessionIndex> {
+        let prefix = format!("{}:", self.key);
+        let mut index = AccessionIndex::new(self.profile.clone());
+        for (key, data) in self.backend.iterate_index_prefix(&prefix)? {
+            index.entries.insert(
+                key[prefix.len()..].to_string(),
+                rmp_serde::from_slice(&data)?,
+            );
+        }
+        Ok(index)
+    }
+}
+
+impl AccessionLookup for StoredAccessionIndex {
+    fn profile(&self) -> &str {
+        &self.profile
+    }
+
+    fn locate(&self, accession: &str) -> Result<Option<AccessionLocation>> {
+        self.get(accession)
+    }
+}
+
+/// Sequences materialized for a set of accessions
+#[derive(Debug, Default)]
+pub struct AccessionReconstruction {
+    /// Rebuilt sequences, in request order
+    pub sequences: Vec<Sequence>,
+    /// Requested accessions the profile does not contain, including
+    /// children whose delta deletes them
+    pub missing: Vec<String>,
+}
+
+/// Rebuilds individual sequences of a reduction profile
+pub struct AccessionReconstructor<'a> {
+    storage: &'a HeraldStorage,
+    index: &'a dyn AccessionLookup,
+    reconstructor: SequenceDeltaReconstructor,
+}
+
+impl<'a> AccessionReconstructor<'a> {
+    pub fn new(storage: &'a HeraldStorage, index: &'a dyn AccessionLookup) -> Self {
+        Self {
+            storage,
+            index,
+            reconstructor: SequenceDeltaReconstructor::default(),
+        }
+    }
+
+    /// Rebuild the requested accessions
+    ///
+    /// Requests are grouped by delta chunk so each chunk is read once, and the
+    /// groups are rebuilt in parallel.
+    pub fn reconstruct(&self, accessions: &[String]) -> Result<AccessionReconstruction> {
+        let mut missing = Vec::new();
+        let mut seen = HashSet::new();
+        let mut indexed = HashSet::new();
+        let mut groups: HashMap<Option<SHA256Hash>, Vec<(&str, AccessionLocation)>> =
+            HashMap::new();
+
+        for accession in accessions {
+            if !seen.insert(accession.as_str()) {
+                continue;
+            }
+            match self.index.locate(accession)? {
+                Some(location) => {
+                    indexed.insert(accession.as_str());
+                    groups
+                        .entry(location.delta_chunk)
+                        .or_default()
+                        .push((accession, location));
+                }
+                None => missing.push(accession.clone()),
+            }
+        }
+
+        let groups: Vec<_> = groups.into_iter().collect();
+        let rebuilt = groups
+            .par_iter()
+            .map(|(delta_chunk, members)| self.reconstruct_group(delta_chunk.as_ref(), members))
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut by_id: HashMap<String, Sequence> = rebuilt
+            .into_iter()
+            .flatten()
+            .map(|seq| (seq.id.clone(), seq))
+            .collect();
+        // Indexed accessions that were not rebuilt were deleted by their delta
+        let mut deleted = HashSet::new();
+        for accession in accessions {
+            if indexed.contains(accession.as_str())
+                && !by_id.contains_key(accession)
+                && deleted.insert(accession.as_str())
+            {
+                missing.push(accession.clone());
+            }
+        }
+
+        let sequences = accessions
+            .iter()
+            .filter_map(|accession| by_id.remove(accession))
+            .collect();
+
+        Ok(AccessionReconstruction { sequences, missing })
+    }
+
+    fn reconstruct_group(
+        &self,
+        delta_chunk: Option<&SHA256Hash>,
+        members: &[(&str, AccessionLocation)],
+    ) -> Result<Vec<Sequence>> {
+        let Some(chunk_hash) = delta_chunk else {
+            return members
+                .iter()
+                .map(|(accession, location)| {
+                    self.load_reference(&location.reference_hash, accession)
+                })
+                .collect();
+        };
+
+        let chunk = self
+            .storage
+            .get_delta_chunk(chunk_hash)
+            .with_context(|| format!("Failed to load delta chunk {}", chunk_hash))?;
+        let operations: HashMap<&str, &DeltaOperation> = chunk
+            .deltas
+            .iter()
+            .map(|op| (op.sequence_id(), op))
+            .collect();
+
+        let mut sequences = Vec::with_capacity(members.len());
+        for (accession, location) in members {
+            let operation = operations.get(accession).ok_or_else(|| {
+                anyhow::anyhow!("Delta chunk {} has no entry for {}", chunk_hash, accession)
+            })?;
+            let reference = self.load_reference(&location.reference_hash, accession)?;
+            if let Some(seq) = self
+                .reconstructor
+                .reconstruct_sequence(operation, &reference)?
+            {
+                sequences.push(seq);
+            }
+        }
+        Ok(sequences)
+    }
+
+    /// Load a canonical sequence with the header recorded for `id`
+    fn load_reference(&self, hash: &SHA256Hash, id: &str) -> Result<Sequence> {
+        let sequence_storage = &self.storage.sequence_storage;
+        let canonical = sequence_storage
+            .load_canonical(hash)
+            .with_context(|| format!("Reference sequence {} not in storage", hash))?;
+        let representations = sequence_storage.load_representations(hash)?;
+
+        let representation = representations
+            .representations()
+            .iter()
+            .find(|r| {
+                r.accessions.iter().any(|a| a == id)
+                    || r.header.trim_start_matches('>').split_whitespace().next() == Some(id)
+            })
+            .or_else(|| representations.representations().first());
+
+        Ok(Sequence {
+            id: id.to_string(),
+            description: representation.and_then(|r| r.description.clone()),
+            sequence: canonical.sequence,
+            taxon_id: representation.and_then(|r| r.taxon_id).map(|t| t.0),
+            taxonomy_sources: Default::default(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{ChunkClassification, SequenceRef};
+    use chrono::Utc;
+
+    fn delta_chunk(children: &[&str]) -> TemporalDeltaChunk {
+        let reference_hash = SHA256Hash::compute(b"reference chunk");
+        TemporalDeltaChunk {
+            content_hash: SHA256Hash::compute(children.join(",").as_bytes()),
+            reference_hash,
+            chunk_type: ChunkClassification::Delta {
+                reference_hash,
+                compression_ratio: 1.0,
+            },
+            taxonomy_version: SHA256Hash::default(),
+            taxon_ids: Vec::new(),
+            deltas: Vec::new(),
+            sequences: children
+                .iter()
+                .map(|id| SequenceRef {
+                    chunk_hash: SHA256Hash::default(),
+                    offset: 0,
+                    length: 0,
+                    sequence_id: id.to_string(),
+                })
+                .collect(),
+            created_at: Utc::now(),
+            valid_from: Utc::now(),
+            valid_until: None,
+            original_size: 0,
+            compressed_size: 0,
+            compression_ratio: 1.0,
+        }
+    }
+
+    #[test]
+    fn test_index_references_and_children() {
+        let ref_a = SHA256Hash::compute(b"MKVLAT");
+        let ref_b = SHA256Hash::compute(b"MSTNPK");
+        let mut index = AccessionIndex::new("blast-30");
+        index.add_reference("A".to_string(), ref_a);
+        index.add_reference("B".to_string(), ref_b);
+
+        let chunk = delta_chunk(&["A1", "B1", "ORPHAN"]);
+        let child_references =
+            HashMap::from([("A1".to_string(), ref_a), ("B1".to_string(), ref_b)]);
+        assert_eq!(index.add_delta_chunk(&chunk, &child_references), 2);
+
+        assert_eq!(index.len(), 4);
+        assert_eq!(index.child_count(), 2);
+        assert_eq!(index.get("A").unwrap().delta_chunk, None);
+        assert_eq!(
+            index.get("B1").unwrap(),
+            &AccessionLocation {
+                reference_hash: ref_b,
+                delta_chunk: Some(chunk.content_hash),
+            }
+        );
+        assert!(index.get("ORPHAN").is_none());
+    }
+
+    #[test]
+    fn test_deleted_children_are_reported_missing() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let storage = HeraldStorage::new(dir.path()).unwrap();
+        let reference = storage
+            .sequence_storage
+            .store_sequence(
+                "MKVLAT",
+                ">A reference",
+                crate::types::DatabaseSource::Custom("test".to_string()),
+            )
+            .unwrap();
+
+        let mut chunk = delta_chunk(&["A1", "A2"]);
+        chunk.deltas = vec![
+            DeltaOperation::UseReference {
+                sequence_id: "A1".to_string(),
+                reference_offset: 0,
+                length: 0,
+            },
+            DeltaOperation::Delete {
+                sequence_id: "A2".to_string(),
+            },
+        ];
+        storage.store_delta_chunk(&chunk).unwrap();
+
+        let mut index = AccessionIndex::new("blast-30");
+        index.add_reference("A".to_string(), reference);
+        let child_references =
+            HashMap::from([("A1".to_string(), reference), ("A2".to_string(), reference)]);
+        index.add_delta_chunk(&chunk, &child_references);
+
+        let requested: Vec<String> = ["A2", "NOPE", "A1", "A"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let result = AccessionReconstructor::new(&storage, &index)
+            .reconstruct(&requested)
+            .unwrap();
+
+        let ids: Vec<&str> = result.sequences.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, ["A1", "A"]);
+        assert_eq!(result.missing, ["NOPE", "A2"]);
+    }
+
+    #[test]
+    fn test_stored_index_reads_single_entries() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let storage = HeraldStorage::new(dir.path()).unwrap();
+        let ref_a = SHA256Hash::compute(b"MKVLAT");
+
+        let mut index = AccessionIndex::new("blast-30");
+        index.add_reference("A".to_string(), ref_a);
+        index.add_reference("B".to_string(), SHA256Hash::compute(b"MSTNPK"));
+        storage
+            .store_accession_index(&index, "uniprot", "swissprot", "v1")
+            .unwrap();
+
+        let stored = storage
+            .get_accession_index("uniprot", "swissprot", "v1", "blast-30")
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.len(), 2);
+        assert_eq!(stored.get("A").unwrap().unwrap().reference_hash, ref_a);
+        assert!(stored.get("NOPE").unwrap().is_none());
+        assert!(storage
+            .get_accession_index("uniprot", "swissprot", "v1", "blast-50")
+            .unwrap()
+            .is_none());
+
+        // Reducing again replaces the previous entries
+        let mut index = AccessionIndex::new("blast-30");
+        index.add_reference("A".to_string(), ref_a);
+        storage
+            .store_accession_index(&index, "uniprot", "swissprot", "v1")
+            .unwrap();
+        let stored = storage
+            .get_accession_index("uniprot", "swissprot", "v1", "blast-30")
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.len(), 1);
+        assert!(stored.get("B").unwrap().is_none());
+        assert_eq!(stored.load().unwrap().get("A"), index.get("A"));
+    }
+}
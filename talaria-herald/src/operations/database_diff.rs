@@ -39,6 +39,20 @@ pub struct ChunkAnalysis {
     pub shared_percentage_a: f64,
     /// Percentage of chunks from B that are shared
     pub shared_percentage_b: f64,
+    /// Bytes of B's chunks already present in A (0 when sizes are unknown)
+    #[serde(default)]
+    pub reused_bytes_b: u64,
+    /// Bytes of B's chunks not present in A (0 when sizes are unknown)
+    #[serde(default)]
+    pub new_bytes_b: u64,
+}
+
+impl ChunkAnalysis {
+    /// Share of B's chunk data that A already has, as a percentage
+    pub fn reuse_percentage_b(&self) -> Option<f64> {
+        let total = self.reused_bytes_b + self.new_bytes_b;
+        (total > 0).then(|| self.reused_bytes_b as f64 / total as f64 * 100.0)
+    }
 }
 
 /// Sequence-level comparison results
@@ -201,6 +215,8 @@ impl DatabaseDiffer {
             } else {
                 0.0
             },
+            reused_bytes_b: 0,
+            new_bytes_b: 0,
         })
     }
 
@@ -576,6 +592,8 @@ impl DatabaseDiffer {
                 } else {
                     0.0
                 },
+                reused_bytes_b: 0,
+                new_bytes_b: 0,
             };
         }
 
@@ -592,6 +610,19 @@ impl DatabaseDiffer {
 
         let shared_count = shared.len();
 
+        // Chunk sizes come from B's index; a chunk A already has is reused
+        let (reused_bytes_b, new_bytes_b) =
+            manifest_b
+                .chunk_index
+                .iter()
+                .fold((0u64, 0u64), |(reused, new), chunk| {
+                    if set_a.contains(&chunk.hash) {
+                        (reused + chunk.size as u64, new)
+                    } else {
+                        (reused, new + chunk.size as u64)
+                    }
+                });
+
         ChunkAnalysis {
             total_chunks_a: total_a,
             total_chunks_b: total_b,
@@ -608,6 +639,8 @@ impl DatabaseDiffer {
             } else {
                 0.0
             },
+            reused_bytes_b,
+            new_bytes_b,
         }
     }
 
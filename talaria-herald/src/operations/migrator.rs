@@ -1,13 +1,18 @@
-#![allow(dead_code)]
-
 /// Trait for managing version migrations
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
 use talaria_core::system::paths;
 
-use crate::types::TemporalManifest;
+use crate::storage::format_version::{
+    FormatStatus, RepositoryFormat, CURRENT_FORMAT_VERSION, FORMAT_FILE, LEGACY_FORMAT_VERSION,
+};
+use crate::storage::HeraldStorage;
+use crate::types::{SHA256Hash, TemporalDeltaChunk, TemporalManifest};
 // DatabaseSource will be defined locally
 
 /// Database source for downloading
@@ -69,6 +74,8 @@ pub enum StepType {
     CreateBackup,
     /// Update symlinks
     UpdateSymlinks,
+    /// Record a new repository format version
+    UpdateFormatVersion(u32),
     /// Custom step
     Custom(String),
 }
@@ -186,17 +193,14 @@ pub trait VersionMigrator: Send + Sync {
 }
 
 /// Standard implementation of VersionMigrator
+#[derive(Default)]
 pub struct StandardVersionMigrator {
-    base_path: PathBuf,
     migration_history: Vec<MigrationResult>,
 }
 
 impl StandardVersionMigrator {
-    pub fn new(base_path: PathBuf) -> Self {
-        Self {
-            base_path,
-            migration_history: Vec::new(),
-        }
+    pub fn new() -> Self {
+        Self::default()
     }
 
     fn create_migration_steps(&self, strategy: MigrationStrategy) -> Vec<MigrationStep> {
@@ -345,6 +349,11 @@ impl StandardVersionMigrator {
                 // Implementation would verify integrity
                 Ok(())
             }
+            StepType::UpdateFormatVersion(version) => {
+                tracing::info!("Recording format version {}...", version);
+                // Repository formats are migrated by FormatMigrator
+                Ok(())
+            }
             StepType::Custom(ref name) => {
                 tracing::info!("Executing custom step: {}", name);
                 // Implementation would handle custom steps
@@ -544,3 +553,517 @@ impl VersionMigrator for StandardVersionMigrator {
         Ok(true)
     }
 }
+
+/// File at the repository root tracking a running format migration
+pub const MIGRATION_CHECKPOINT_FILE: &str = "migration_checkpoint.json";
+
+/// Files backed up before a format migration rewrites them
+const BACKED_UP_FILES: &[&str] = &[FORMAT_FILE, "delta_index_v2.json"];
+
+const REKEY_DELTA_CHUNKS: &str = "rekey_delta_chunks";
+
+/// Re-keyed delta chunks written per batch
+const REKEY_BATCH_SIZE: usize = 1000;
+
+/// Progress of a format migration, saved after every step
+///
+/// An interrupted migration resumes from its checkpoint; rolling back uses it
+/// to remove everything the migration added.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigrationCheckpoint {
+    pub plan: MigrationPlan,
+    pub completed_steps: Vec<String>,
+    /// Chunk keys added by the migration
+    pub written_chunks: Vec<SHA256Hash>,
+    pub started_at: DateTime<Utc>,
+}
+
+/// Migrates a repository's on-disk layout to `CURRENT_FORMAT_VERSION`
+///
+/// Migrations only add data or rewrite small index files that are backed up
+/// first, so a failed or interrupted migration can be rolled back without
+/// copying the chunk store.
+pub struct FormatMigrator<'a> {
+    storage: &'a HeraldStorage,
+    base_path: PathBuf,
+}
+
+impl<'a> FormatMigrator<'a> {
+    pub fn new(storage: &'a HeraldStorage) -> Self {
+        Self {
+            storage,
+            base_path: storage.base_path.clone(),
+        }
+    }
+
+    /// Format version of the repository, treating unversioned repositories as legacy
+    pub fn current_version(&self) -> Result<u32> {
+        Ok(RepositoryFormat::load(&self.base_path)?
+            .map(|format| format.version)
+            .unwrap_or(LEGACY_FORMAT_VERSION))
+    }
+
+    /// Steps to bring the repository to the current format, if it is older
+    pub fn plan(&self) -> Result<Option<MigrationPlan>> {
+        let from = self.current_version()?;
+        if from >= CURRENT_FORMAT_VERSION {
+            return Ok(None);
+        }
+
+        let backup_path = paths::talaria_backups_dir().join(format!(
+            "format-v{}-{}",
+            from,
+            Utc::now().format("%Y%m%d_%H%M%S")
+        ));
+        let mut steps = vec![MigrationStep {
+            name: "Back up index files".to_string(),
+            step_type: StepType::CreateBackup,
+            description: format!(
+                "Copy format record and delta index to {}",
+                backup_path.display()
+            ),
+            reversible: true,
+            duration: 1,
+        }];
+        for version in from..CURRENT_FORMAT_VERSION {
+            steps.extend(Self::steps_for(version));
+            steps.push(MigrationStep {
+                name: format!("Record format {}", version + 1),
+                step_type: StepType::UpdateFormatVersion(version + 1),
+                description: format!("Mark the repository as format {}", version + 1),
+                reversible: true,
+                duration: 1,
+            });
+        }
+
+        Ok(Some(MigrationPlan {
+            from_version: from.to_string(),
+            to_version: CURRENT_FORMAT_VERSION.to_string(),
+            estimated_time: steps.iter().map(|s| s.duration).sum(),
+            steps,
+            download_size: 0,
+            is_major: true,
+            rollback_plan: Some(RollbackPlan {
+                steps: vec![
+                    RollbackStep {
+                        name: "Remove added chunks".to_string(),
+                        action: "delete_written_chunks".to_string(),
+                    },
+                    RollbackStep {
+                        name: "Restore index files".to_string(),
+                        action: "restore_from_backup".to_string(),
+                    },
+                ],
+                backup_path: Some(backup_path),
+            }),
+        }))
+    }
+
+    /// Steps migrating format `version` to `version + 1`
+    fn steps_for(version: u32) -> Vec<MigrationStep> {
+        match version {
+            1 => vec![
+                MigrationStep {
+                    name: "Re-key delta chunks".to_string(),
+                    step_type: StepType::Custom(REKEY_DELTA_CHUNKS.to_string()),
+                    description: "Store delta chunks under their content hash".to_string(),
+                    reversible: true,
+                    duration: 600,
+                },
+                MigrationStep {
+                    name: "Verify delta chunks".to_string(),
+                    step_type: StepType::VerifyIntegrity,
+                    description: "Check every indexed delta chunk loads by its hash".to_string(),
+                    reversible: false,
+                    duration: 60,
+                },
+            ],
+            _ => Vec::new(),
+        }
+    }
+
+    /// Whether migrating would rewrite data, as opposed to only recording the new format
+    pub fn requires_rewrite(&self) -> Result<bool> {
+        // Re-keying delta chunks (format 1 -> 2) is the only rewriting step so far
+        Ok(self.current_version()? < 2 && !self.missing_delta_chunks()?.is_empty())
+    }
+
+    /// Checkpoint of an interrupted migration
+    pub fn pending_checkpoint(&self) -> Result<Option<MigrationCheckpoint>> {
+        Self::load_checkpoint(&self.base_path)
+    }
+
+    fn load_checkpoint(base_path: &Path) -> Result<Option<MigrationCheckpoint>> {
+        let path = base_path.join(MIGRATION_CHECKPOINT_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let checkpoint = serde_json::from_str(&fs::read_to_string(&path)?)
+            .with_context(|| format!("Invalid migration checkpoint {}", path.display()))?;
+        Ok(Some(checkpoint))
+    }
+
+    /// Checks that need no storage, run before a repository's stores are opened
+    ///
+    /// Refuses repositories written by a newer build and ones with an
+    /// interrupted migration, so neither is touched by RocksDB.
+    pub fn ensure_openable(base_path: &Path, status: FormatStatus) -> Result<()> {
+        if Self::load_checkpoint(base_path)?.is_some() {
+            bail!(
+                "A format migration of {} was interrupted. Run 'talaria herald migrate' to resume it \
+                 or 'talaria herald migrate --rollback' to undo it",
+                base_path.display()
+            );
+        }
+        if let FormatStatus::Unsupported(version) = status {
+            bail!(
+                "Repository at {} uses format {}, but this version of talaria only supports \
+                 formats up to {}. Upgrade talaria to use it",
+                base_path.display(),
+                version,
+                CURRENT_FORMAT_VERSION
+            );
+        }
+        Ok(())
+    }
+
+    /// Check that a repository being opened can be used as it is
+    ///
+    /// `status` must be detected before storage was opened. Nothing is
+    /// written: an outdated repository that needs no rewrite stays readable
+    /// and is only recorded as current by `talaria herald migrate`.
+    pub fn ensure_supported(&self, status: FormatStatus) -> Result<()> {
+        Self::ensure_openable(&self.base_path, status)?;
+
+        match status {
+            FormatStatus::Fresh | FormatStatus::Current | FormatStatus::Unsupported(_) => Ok(()),
+            FormatStatus::Outdated(version) => {
+                if self.requires_rewrite()? {
+                    bail!(
+                        "Repository at {} uses format {} and must be migrated to format {}. \
+                         Run 'talaria herald migrate' (use --dry-run to see the steps)",
+                        self.base_path.display(),
+                        version,
+                        CURRENT_FORMAT_VERSION
+                    );
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Run a migration plan, resuming from a matching checkpoint
+    ///
+    /// A failing step rolls back everything the migration did. A migration
+    /// that is interrupted keeps its checkpoint and can be resumed or rolled
+    /// back later.
+    pub fn execute(
+        &self,
+        plan: &MigrationPlan,
+        options: &MigrationOptions,
+        progress: &dyn Fn(&str),
+    ) -> Result<MigrationResult> {
+        let start_time = std::time::Instant::now();
+
+        if options.dry_run {
+            return Ok(MigrationResult {
+                success: true,
+                completed_steps: plan.steps.iter().map(|s| s.name.clone()).collect(),
+                failed_steps: Vec::new(),
+                duration_seconds: 0,
+                bytes_downloaded: 0,
+                bytes_removed: 0,
+                error: None,
+            });
+        }
+
+        let mut checkpoint = match self.pending_checkpoint()? {
+            Some(checkpoint) if checkpoint.plan.to_version == plan.to_version => checkpoint,
+            Some(checkpoint) => bail!(
+                "A migration to format {} is in progress; roll it back first",
+                checkpoint.plan.to_version
+            ),
+            None => MigrationCheckpoint {
+                plan: plan.clone(),
+                completed_steps: Vec::new(),
+                written_chunks: Vec::new(),
+                started_at: Utc::now(),
+            },
+        };
+        self.save_checkpoint(&checkpoint)?;
+
+        // Steps come from the checkpoint so a resumed run uses the original backup location
+        let steps = checkpoint.plan.steps.clone();
+        for step in &steps {
+            if checkpoint.completed_steps.contains(&step.name) {
+                progress(&format!("✓ {} (already done)", step.name));
+                continue;
+            }
+            progress(&format!("{}...", step.description));
+
+            if let Err(e) = self.execute_step(step, &mut checkpoint, progress) {
+                tracing::error!("Migration step '{}' failed: {:#}", step.name, e);
+                let completed_steps = checkpoint.completed_steps.clone();
+                self.rollback()
+                    .context("Rollback after failed migration step also failed")?;
+                return Ok(MigrationResult {
+                    success: false,
+                    completed_steps,
+                    failed_steps: vec![step.name.clone()],
+                    duration_seconds: start_time.elapsed().as_secs(),
+                    bytes_downloaded: 0,
+                    bytes_removed: 0,
+                    error: Some(format!("{:#}", e)),
+                });
+            }
+
+            checkpoint.completed_steps.push(step.name.clone());
+            self.save_checkpoint(&checkpoint)?;
+        }
+
+        fs::remove_file(self.base_path.join(MIGRATION_CHECKPOINT_FILE))?;
+        Ok(MigrationResult {
+            success: true,
+            completed_steps: checkpoint.completed_steps,
+            failed_steps: Vec::new(),
+            duration_seconds: start_time.elapsed().as_secs(),
+            bytes_downloaded: 0,
+            bytes_removed: 0,
+            error: None,
+        })
+    }
+
+    /// Undo the migration recorded in the checkpoint
+    pub fn rollback(&self) -> Result<()> {
+        let Some(checkpoint) = self.pending_checkpoint()? else {
+            bail!("No migration in progress at {}", self.base_path.display());
+        };
+
+        let backend = self.storage.chunk_storage();
+        backend.delete_chunks_batch(&checkpoint.written_chunks)?;
+
+        let backup_path = checkpoint
+            .plan
+            .rollback_plan
+            .as_ref()
+            .and_then(|plan| plan.backup_path.clone());
+        let backup_done = checkpoint.plan.steps.iter().any(|s| {
+            s.step_type == StepType::CreateBackup && checkpoint.completed_steps.contains(&s.name)
+        });
+        if let (Some(backup_path), true) = (backup_path, backup_done) {
+            for name in BACKED_UP_FILES {
+                let target = self.base_path.join(name);
+                let backup = backup_path.join(name);
+                if backup.exists() {
+                    fs::copy(&backup, &target)?;
+                } else if target.exists() {
+                    fs::remove_file(&target)?;
+                }
+            }
+        }
+
+        fs::remove_file(self.base_path.join(MIGRATION_CHECKPOINT_FILE))?;
+        Ok(())
+    }
+
+    fn save_checkpoint(&self, checkpoint: &MigrationCheckpoint) -> Result<()> {
+        let path = self.base_path.join(MIGRATION_CHECKPOINT_FILE);
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, serde_json::to_string_pretty(checkpoint)?)?;
+        fs::rename(&tmp_path, &path)?;
+        Ok(())
+    }
+
+    fn execute_step(
+        &self,
+        step: &MigrationStep,
+        checkpoint: &mut MigrationCheckpoint,
+        progress: &dyn Fn(&str),
+    ) -> Result<()> {
+        match &step.step_type {
+            StepType::CreateBackup => {
+                let Some(backup_path) = checkpoint
+                    .plan
+                    .rollback_plan
+                    .as_ref()
+                    .and_then(|plan| plan.backup_path.as_ref())
+                else {
+                    return Ok(());
+                };
+                fs::create_dir_all(backup_path)?;
+                for name in BACKED_UP_FILES {
+                    let source = self.base_path.join(name);
+                    if source.exists() {
+                        fs::copy(&source, backup_path.join(name))?;
+                    }
+                }
+                Ok(())
+            }
+            StepType::Custom(name) if name == REKEY_DELTA_CHUNKS => {
+                self.rekey_delta_chunks(checkpoint, progress)
+            }
+            StepType::VerifyIntegrity => {
+                let missing = self.missing_delta_chunks()?;
+                if !missing.is_empty() {
+                    bail!(
+                        "{} delta chunks listed in the delta index are not in the chunk store",
+                        missing.len()
+                    );
+                }
+                for hash in self.storage.list_delta_chunks()? {
+                    self.storage
+                        .get_delta_chunk(&hash)
+                        .with_context(|| format!("Delta chunk {} is unreadable", hash))?;
+                }
+                Ok(())
+            }
+            StepType::UpdateFormatVersion(version) => {
+                let mut format = RepositoryFormat::load(&self.base_path)?
+                    .unwrap_or_else(|| RepositoryFormat::new(LEGACY_FORMAT_VERSION));
+                format.record_migration(format.version, *version);
+                format.save(&self.base_path)
+            }
+            other => bail!("Step type {:?} is not part of format migrations", other),
+        }
+    }
+
+    /// Delta chunks in the delta index that cannot be loaded by their hash
+    fn missing_delta_chunks(&self) -> Result<HashSet<SHA256Hash>> {
+        Ok(self
+            .storage
+            .list_delta_chunks()?
+            .into_iter()
+            .filter(|hash| !self.storage.has_chunk(hash))
+            .collect())
+    }
+
+    /// Format 1 stored delta chunks under the hash of their serialized form
+    fn rekey_delta_chunks(
+        &self,
+        checkpoint: &mut MigrationCheckpoint,
+        progress: &dyn Fn(&str),
+    ) -> Result<()> {
+        let mut missing = self.missing_delta_chunks()?;
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        let backend = self.storage.chunk_storage();
+        let all_chunks = backend.list_all_chunks()?;
+        let total = missing.len();
+        let mut batch = Vec::new();
+        for (scanned, hash) in all_chunks.iter().enumerate() {
+            if missing.is_empty() {
+                break;
+            }
+            if scanned % 100_000 == 0 && scanned > 0 {
+                progress(&format!(
+                    "  Scanned {}/{} chunks, {}/{} delta chunks re-keyed",
+                    scanned,
+                    all_chunks.len(),
+                    total - missing.len(),
+                    total
+                ));
+            }
+
+            let Ok(data) = self.storage.get_chunk(hash) else {
+                continue;
+            };
+            if data.first() != Some(&b'{') {
+                continue;
+            }
+            let Ok(chunk) = serde_json::from_slice::<TemporalDeltaChunk>(&data) else {
+                continue;
+            };
+            if !missing.remove(&chunk.content_hash) {
+                continue;
+            }
+
+            // The stored bytes are reused as-is; only the key changes
+            batch.push((chunk.content_hash, backend.load_chunk(hash)?));
+            if batch.len() >= REKEY_BATCH_SIZE {
+                self.commit_rekeyed(checkpoint, &mut batch)?;
+            }
+        }
+        self.commit_rekeyed(checkpoint, &mut batch)?;
+
+        progress(&format!(
+            "  Re-keyed {} delta chunks",
+            total - missing.len()
+        ));
+        Ok(())
+    }
+
+    /// Write a batch of re-keyed chunks
+    ///
+    /// The keys are checkpointed before the batch is written, so a rollback
+    /// after any interruption finds every chunk the migration added.
+    fn commit_rekeyed(
+        &self,
+        checkpoint: &mut MigrationCheckpoint,
+        batch: &mut Vec<(SHA256Hash, Vec<u8>)>,
+    ) -> Result<()> {
+        if batch.is_empty() {
+            return Ok(());
+        }
+        checkpoint
+            .written_chunks
+            .extend(batch.iter().map(|(hash, _)| *hash));
+        self.save_checkpoint(checkpoint)?;
+        self.storage.chunk_storage().store_chunks_batch(batch)?;
+        batch.clear();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    #[serial_test::serial]
+    fn test_opening_does_not_record_format() {
+        let temp_dir = TempDir::new().unwrap();
+        std::env::set_var("TALARIA_HOME", temp_dir.path());
+        let base_path = temp_dir.path().join("databases");
+
+        // A chunk store without a format record is a legacy repository
+        drop(HeraldStorage::open_unchecked(&base_path).unwrap());
+        let storage = HeraldStorage::new(&base_path).unwrap();
+        let status = RepositoryFormat::detect(&base_path).unwrap();
+        assert_eq!(status, FormatStatus::Outdated(LEGACY_FORMAT_VERSION));
+
+        let migrator = FormatMigrator::new(&storage);
+        assert!(!migrator.requires_rewrite().unwrap());
+        migrator.ensure_supported(status).unwrap();
+        assert!(!base_path.join(FORMAT_FILE).exists());
+
+        let plan = migrator.plan().unwrap().unwrap();
+        let result = migrator
+            .execute(&plan, &MigrationOptions::default(), &|_: &str| {})
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(migrator.current_version().unwrap(), CURRENT_FORMAT_VERSION);
+        assert!(migrator.pending_checkpoint().unwrap().is_none());
+
+        std::env::remove_var("TALARIA_HOME");
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_newer_format_is_refused_before_opening_stores() {
+        let temp_dir = TempDir::new().unwrap();
+        std::env::set_var("TALARIA_HOME", temp_dir.path());
+        let base_path = temp_dir.path().join("databases");
+        RepositoryFormat::new(CURRENT_FORMAT_VERSION + 1)
+            .save(&base_path)
+            .unwrap();
+
+        let error = HeraldStorage::open(&base_path).err().unwrap();
+        assert!(error.to_string().contains("Upgrade talaria"), "{}", error);
+        assert!(!base_path.join("chunk_storage").exists());
+
+        std::env::remove_var("TALARIA_HOME");
+    }
+}
@@ -4,8 +4,10 @@
 //! - **Database comparison**: Compare chunks, sequences, and taxonomies between databases
 //! - **Reduction**: Compress sequence databases using delta encoding
 //! - **Assembly**: Reconstruct original sequences from reduced format
+//! - **Accession index**: Rebuild single sequences of a reduction profile by accession
 //! - **Migration**: Convert between database formats
 //! - **Validation**: Verify database integrity and consistency
+//! - **Recall**: Measure what a reduction loses for real searches
 //!
 //! # Key Components
 //!
@@ -52,10 +54,12 @@
 //! # }
 //! ```
 
+pub mod accession_index;
 pub mod assembler;
 pub mod database_diff;
 pub mod differ;
 pub mod migrator;
+pub mod recall;
 pub mod reducer;
 pub mod reduction;
 pub mod reference_selector;
@@ -66,6 +70,10 @@ pub mod state;
 pub mod validator;
 
 // Re-export main types
+pub use accession_index::{
+    AccessionIndex, AccessionLocation, AccessionLookup, AccessionReconstruction,
+    AccessionReconstructor, StoredAccessionIndex,
+};
 pub use assembler::{AssemblyBuilder, AssemblyResult, FastaAssembler};
 pub use database_diff::{
     format_bytes, ChunkAnalysis, DatabaseComparison, DatabaseDiffer, SequenceAnalysis,
@@ -75,6 +83,10 @@ pub use differ::{
     ChangeType, ChunkChange, DiffOptions, DiffResult, DiffStats, StandardTemporalManifestDiffer,
     TemporalManifestDiffer,
 };
+pub use migrator::{
+    FormatMigrator, MigrationCheckpoint, MigrationOptions, MigrationPlan, MigrationResult,
+};
+pub use recall::RecallValidator;
 pub use reducer::Reducer;
 pub use reduction::{
     DeltaChunkRef, ReductionManager, ReductionManifest, ReductionParameters, ReductionStatistics,
@@ -83,8 +95,8 @@ pub use reduction::{
 pub use reference_selector::{ReferenceSelectorImpl, SelectionAlgorithm, SelectionResult};
 pub use results::{
     CompositionStats, DatabaseInfoResult, DiscrepancyResult, GarbageCollectionResult,
-    HistoryResult, MirrorResult, OptimizationResult, ReconstructionResult, ReductionResult,
-    StatsResult, TaxonomyComparison, TaxonomyCoverageInfo, TaxonomyCoverageResult,
+    HistoryResult, MirrorResult, OptimizationResult, RecallValidationResult, ReconstructionResult,
+    ReductionResult, StatsResult, TaxonomyComparison, TaxonomyCoverageInfo, TaxonomyCoverageResult,
     UpdateCheckResult, UpdateResult, ValidationResult, VerificationResult, VersionHistoryEntry,
 };
 pub use selection::traits::{
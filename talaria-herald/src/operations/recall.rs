@@ -0,0 +1,593 @@
+/// Search-recall validation of reductions
+///
+/// Runs the same query set against the original database and against the
+/// reduced references, then maps reduced hits back to the sequences they
+/// stand for through the delta `ref2children` relation. The resulting
+/// numbers answer "what would this pipeline have lost" rather than "how
+/// much of the input is covered".
+///
+/// Queries are usually sampled from the original database, so every one of
+/// them finds itself there and, through its reference, in the reduced
+/// database. Such self-hits are dropped on both sides before comparing.
+use super::results::{EValueShift, RankAgreement, RecallValidationResult};
+use super::FastaAssembler;
+use crate::database::DatabaseManager;
+use crate::types::SHA256Hash;
+use anyhow::{Context, Result};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use std::collections::HashMap;
+use std::time::Instant;
+use talaria_bio::compression::DeltaRecord;
+use talaria_bio::sequence::Sequence;
+use talaria_bio::taxonomy::{TaxonomicRank, TaxonomyDB};
+use talaria_core::types::DatabaseReference;
+use talaria_tools::{Aligner, AlignmentSummary};
+
+/// E-values below this are clamped before taking logarithms
+const MIN_EVALUE: f64 = 1e-300;
+
+/// Ranks compared when none are configured
+pub const DEFAULT_RANKS: [TaxonomicRank; 7] = [
+    TaxonomicRank::Superkingdom,
+    TaxonomicRank::Phylum,
+    TaxonomicRank::Class,
+    TaxonomicRank::Order,
+    TaxonomicRank::Family,
+    TaxonomicRank::Genus,
+    TaxonomicRank::Species,
+];
+
+/// Pick `count` queries from `sequences`, reproducibly for a given seed
+///
+/// The queries stay in the database they were drawn from; `compare_hits`
+/// discards the self-hits this produces.
+pub fn sample_queries(sequences: &[Sequence], count: usize, seed: u64) -> Vec<Sequence> {
+    if count >= sequences.len() {
+        return sequences.to_vec();
+    }
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut picked: Vec<&Sequence> = sequences.choose_multiple(&mut rng, count).collect();
+    // Sort by ID so the query order does not depend on the sampling order
+    picked.sort_by_key(|s| s.id.as_str());
+    picked.into_iter().cloned().collect()
+}
+
+/// What a recall validation searches, loaded from a stored reduction profile
+pub struct ProfileSequences {
+    /// Every sequence of the reduced database version
+    pub original: Vec<Sequence>,
+    pub references: Vec<Sequence>,
+    /// One record per child, naming the reference it was encoded against
+    pub deltas: Vec<DeltaRecord>,
+}
+
+/// Load the inputs of a recall validation for `source/dataset[@version]:profile`
+///
+/// Children are tied to their references through the profile's accession
+/// index, so profiles reduced before the index existed must be reduced again.
+pub fn load_profile_sequences(
+    manager: &DatabaseManager,
+    reference: &DatabaseReference,
+) -> Result<ProfileSequences> {
+    let (source, dataset) = (reference.source.as_str(), reference.dataset.as_str());
+    let profile = reference
+        .profile
+        .as_deref()
+        .ok_or_else(|| anyhow::anyhow!("{} names no reduction profile", reference))?;
+    let version = manager
+        .resolve_version_reference(source, dataset, reference.version_or_default())
+        .with_context(|| format!("Failed to resolve {}", reference))?;
+    let manifest = manager.get_version_manifest(source, dataset, &version)?;
+
+    let storage = &manager.get_repository().storage;
+    let reduction = storage
+        .get_database_reduction_by_profile(source, dataset, &version, profile)?
+        .ok_or_else(|| anyhow::anyhow!("Reduction profile '{}' not found", profile))?;
+    let index = storage
+        .get_accession_index(source, dataset, &version, profile)?
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "Profile '{}' has no accession index; reduce {}/{} again",
+                profile,
+                source,
+                dataset
+            )
+        })?;
+
+    let assembler = FastaAssembler::new(storage);
+    let chunk_hashes: Vec<SHA256Hash> = manifest.chunk_index.iter().map(|c| c.hash).collect();
+    let original = assembler.assemble_from_chunks(&chunk_hashes)?;
+    let reference_hashes: Vec<SHA256Hash> = reduction
+        .reference_chunks
+        .iter()
+        .map(|c| c.chunk_hash)
+        .collect();
+    let references = assembler.assemble_from_chunks(&reference_hashes)?;
+
+    // The index names references by canonical hash
+    let mut reference_ids: HashMap<SHA256Hash, &str> = HashMap::new();
+    for seq in &references {
+        reference_ids
+            .entry(SHA256Hash::compute(&seq.sequence))
+            .or_insert(seq.id.as_str());
+    }
+
+    let mut deltas = Vec::new();
+    let mut unresolved = 0;
+    for chunk in &reduction.delta_chunks {
+        for child in &chunk.child_ids {
+            let reference_id = index
+                .get(child)?
+                .and_then(|location| reference_ids.get(&location.reference_hash));
+            match reference_id {
+                Some(reference_id) => deltas.push(DeltaRecord {
+                    child_id: child.clone(),
+                    reference_id: reference_id.to_string(),
+                    taxon_id: None,
+                    deltas: Vec::new(),
+                    header_change: None,
+                }),
+                None => unresolved += 1,
+            }
+        }
+    }
+    if unresolved > 0 {
+        anyhow::bail!(
+            "{} children of profile '{}' have no reference in its accession index",
+            unresolved,
+            profile
+        );
+    }
+
+    Ok(ProfileSequences {
+        original,
+        references,
+        deltas,
+    })
+}
+
+/// Build the reference -> children relation from delta records
+pub fn ref2children(deltas: &[DeltaRecord]) -> HashMap<String, Vec<String>> {
+    let mut map: HashMap<String, Vec<String>> = HashMap::new();
+    for delta in deltas {
+        map.entry(delta.reference_id.clone())
+            .or_default()
+            .push(delta.child_id.clone());
+    }
+    map
+}
+
+/// Runs a configured aligner against an original and a reduced database
+pub struct RecallValidator<'a> {
+    aligner: &'a mut dyn Aligner,
+    taxonomy: Option<&'a TaxonomyDB>,
+    ranks: Vec<TaxonomicRank>,
+}
+
+impl<'a> RecallValidator<'a> {
+    pub fn new(aligner: &'a mut dyn Aligner) -> Self {
+        Self {
+            aligner,
+            taxonomy: None,
+            ranks: DEFAULT_RANKS.to_vec(),
+        }
+    }
+
+    /// Compare taxonomic assignments at each rank using this taxonomy
+    pub fn with_taxonomy(mut self, taxonomy: &'a TaxonomyDB) -> Self {
+        self.taxonomy = Some(taxonomy);
+        self
+    }
+
+    pub fn with_ranks(mut self, ranks: Vec<TaxonomicRank>) -> Self {
+        self.ranks = ranks;
+        self
+    }
+
+    /// Search `queries` against both databases and compare the results
+    pub fn run(
+        &mut self,
+        queries: &[Sequence],
+        original: &[Sequence],
+        references: &[Sequence],
+        deltas: &[DeltaRecord],
+    ) -> Result<RecallValidationResult> {
+        let start = Instant::now();
+
+        tracing::info!(
+            "Searching {} queries against {} original sequences",
+            queries.len(),
+            original.len()
+        );
+        let original_hits = self.aligner.search(queries, original)?;
+
+        tracing::info!(
+            "Searching {} queries against {} reference sequences",
+            queries.len(),
+            references.len()
+        );
+        let reduced_hits = self.aligner.search(queries, references)?;
+
+        // Children inherit the reference's taxon unless the delta says otherwise
+        let mut taxa: HashMap<String, u32> = HashMap::new();
+        for seq in original.iter().chain(references) {
+            if let Some(taxon) = seq.taxon_id {
+                taxa.insert(seq.id.clone(), taxon);
+            }
+        }
+        for delta in deltas {
+            if let Some(taxon) = delta.taxon_id {
+                taxa.entry(delta.child_id.clone()).or_insert(taxon);
+            }
+        }
+
+        let mut result = compare_hits(
+            queries.len(),
+            &original_hits,
+            &reduced_hits,
+            &ref2children(deltas),
+            &taxa,
+            self.taxonomy,
+            &self.ranks,
+        );
+        result.aligner = self.aligner.version().unwrap_or_else(|_| "unknown".into());
+        result.duration = start.elapsed();
+        Ok(result)
+    }
+}
+
+/// Compare precomputed hits from the original and reduced databases
+///
+/// A reduced hit to a reference counts as a hit to each of its children
+/// with the same e-value. Top-hit taxonomy uses the taxon of the sequence
+/// actually hit, which is what a classifier running on the reduced
+/// database would report. A query's hit to itself is ignored, but when the
+/// query is a reference its self-hit still stands for its children.
+pub fn compare_hits(
+    query_count: usize,
+    original_hits: &[AlignmentSummary],
+    reduced_hits: &[AlignmentSummary],
+    ref2children: &HashMap<String, Vec<String>>,
+    taxa: &HashMap<String, u32>,
+    taxonomy: Option<&TaxonomyDB>,
+    ranks: &[TaxonomicRank],
+) -> RecallValidationResult {
+    let original_hits: Vec<&AlignmentSummary> = original_hits
+        .iter()
+        .filter(|hit| hit.query_id != hit.reference_id)
+        .collect();
+    let reduced_hits: Vec<&AlignmentSummary> = reduced_hits
+        .iter()
+        .filter(|hit| {
+            hit.query_id != hit.reference_id
+                || ref2children
+                    .get(&hit.reference_id)
+                    .is_some_and(|children| children.iter().any(|c| *c != hit.query_id))
+        })
+        .collect();
+    let original = best_per_subject(&original_hits);
+    let original_top = top_hits(&original_hits);
+    let reduced_top = top_hits(&reduced_hits);
+
+    // Expand reduced hits through the delta relation, then drop the query's
+    // hit to itself; a direct hit to a sequence wins over one inherited from
+    // its reference
+    let mut expanded: HashMap<(&str, &str), f64> = HashMap::new();
+    for hit in &reduced_hits {
+        let key = (hit.query_id.as_str(), hit.reference_id.as_str());
+        let e = expanded.entry(key).or_insert(hit.e_value);
+        *e = e.min(hit.e_value);
+    }
+    for hit in &reduced_hits {
+        for child in ref2children.get(&hit.reference_id).into_iter().flatten() {
+            if *child == hit.query_id {
+                continue;
+            }
+            let key = (hit.query_id.as_str(), child.as_str());
+            let e = expanded.entry(key).or_insert(hit.e_value);
+            *e = e.min(hit.e_value);
+        }
+    }
+    expanded.retain(|(query, subject), _| query != subject);
+
+    let mut recovered = 0;
+    let mut shifts = Vec::new();
+    for (key, orig_e) in &original {
+        if let Some(red_e) = expanded.get(key) {
+            recovered += 1;
+            shifts.push(red_e.max(MIN_EVALUE).log10() - orig_e.max(MIN_EVALUE).log10());
+        }
+    }
+
+    let mut concordant = 0;
+    for (query, orig_top) in &original_top {
+        let Some(red_top) = reduced_top.get(query) else {
+            continue;
+        };
+        if orig_top.reference_id == red_top.reference_id
+            || ref2children
+                .get(&red_top.reference_id)
+                .is_some_and(|c| c.contains(&orig_top.reference_id))
+        {
+            concordant += 1;
+        }
+    }
+
+    // Exact taxon first, then each configured rank
+    let mut rank_agreement = vec![RankAgreement::new("taxon")];
+    if taxonomy.is_some() {
+        rank_agreement.extend(
+            ranks
+                .iter()
+                .map(|r| RankAgreement::new(&format!("{:?}", r).to_lowercase())),
+        );
+    }
+    for (query, orig_top) in &original_top {
+        let Some(&orig_taxon) = taxa.get(&orig_top.reference_id) else {
+            continue;
+        };
+        // A query the reduced database missed disagrees at every rank
+        let red_taxon = reduced_top
+            .get(query)
+            .and_then(|hit| taxa.get(&hit.reference_id))
+            .copied();
+
+        rank_agreement[0].record(red_taxon == Some(orig_taxon));
+
+        let Some(db) = taxonomy else {
+            continue;
+        };
+        for (row, rank) in rank_agreement[1..].iter_mut().zip(ranks) {
+            let Some(expected) = db.find_ancestor_at_rank(orig_taxon, *rank) else {
+                continue;
+            };
+            let actual = red_taxon.and_then(|t| db.find_ancestor_at_rank(t, *rank));
+            row.record(actual == Some(expected));
+        }
+    }
+    for row in &mut rank_agreement {
+        row.agreement = ratio(row.agreed, row.compared);
+    }
+
+    let original_count = original.len();
+    let compared = original_top.len();
+    RecallValidationResult {
+        aligner: String::new(),
+        queries: query_count,
+        queries_with_hits: compared,
+        queries_with_reduced_hits: reduced_top.len(),
+        original_hits: original_count,
+        recovered_hits: recovered,
+        sensitivity: ratio(recovered, original_count),
+        top_hits_concordant: concordant,
+        top_hit_concordance: ratio(concordant, compared),
+        rank_agreement,
+        evalue_shift: EValueShift::from_log10_shifts(shifts),
+        duration: Default::default(),
+    }
+}
+
+fn ratio(part: usize, whole: usize) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f64 / whole as f64
+    }
+}
+
+/// Best e-value for every (query, subject) pair
+fn best_per_subject<'a>(hits: &[&'a AlignmentSummary]) -> HashMap<(&'a str, &'a str), f64> {
+    let mut best: HashMap<(&str, &str), f64> = HashMap::new();
+    for hit in hits {
+        let key = (hit.query_id.as_str(), hit.reference_id.as_str());
+        let e = best.entry(key).or_insert(hit.e_value);
+        *e = e.min(hit.e_value);
+    }
+    best
+}
+
+/// Lowest e-value hit per query, ties broken by bit score
+fn top_hits<'a>(hits: &[&'a AlignmentSummary]) -> HashMap<&'a str, &'a AlignmentSummary> {
+    let mut top: HashMap<&str, &AlignmentSummary> = HashMap::new();
+    for &hit in hits {
+        top.entry(hit.query_id.as_str())
+            .and_modify(|current| {
+                let better = hit
+                    .e_value
+                    .total_cmp(&current.e_value)
+                    .then(current.bit_score.total_cmp(&hit.bit_score))
+                    .is_lt();
+                if better {
+                    *current = hit;
+                }
+            })
+            .or_insert(hit);
+    }
+    top
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use talaria_bio::taxonomy::TaxonomyInfo;
+
+    fn hit(query: &str, subject: &str, e_value: f64) -> AlignmentSummary {
+        AlignmentSummary {
+            query_id: query.to_string(),
+            reference_id: subject.to_string(),
+            identity: 90.0,
+            alignment_length: 100,
+            mismatches: 10,
+            gap_opens: 0,
+            query_start: 1,
+            query_end: 100,
+            ref_start: 1,
+            ref_end: 100,
+            e_value,
+            bit_score: 100.0,
+        }
+    }
+
+    fn taxonomy() -> TaxonomyDB {
+        let mut db = TaxonomyDB::new();
+        for (id, rank, parent) in [
+            (1, "no rank", None),
+            (10, "genus", Some(1)),
+            (11, "species", Some(10)),
+            (12, "species", Some(10)),
+            (20, "genus", Some(1)),
+            (21, "species", Some(20)),
+        ] {
+            db.add_taxon(TaxonomyInfo {
+                taxon_id: id,
+                scientific_name: format!("taxon {}", id),
+                rank: rank.to_string(),
+                parent_id: parent,
+            });
+        }
+        db
+    }
+
+    #[test]
+    fn test_compare_hits_maps_children_through_references() {
+        let original = vec![
+            hit("q1", "a", 1e-50),
+            hit("q1", "b", 1e-40),
+            hit("q2", "c", 1e-30),
+            hit("q3", "d", 1e-10),
+        ];
+        // b is a child of a, c is a child of x; d was lost entirely and q3
+        // now lands on an unrelated reference in another genus
+        let reduced = vec![
+            hit("q1", "a", 1e-50),
+            hit("q2", "x", 1e-20),
+            hit("q3", "y", 1e-5),
+        ];
+        let relation = HashMap::from([
+            ("a".to_string(), vec!["b".to_string()]),
+            ("x".to_string(), vec!["c".to_string()]),
+        ]);
+        let taxa = HashMap::from([
+            ("a".to_string(), 11),
+            ("b".to_string(), 11),
+            ("c".to_string(), 12),
+            ("x".to_string(), 11),
+            ("d".to_string(), 11),
+            ("y".to_string(), 21),
+        ]);
+        let db = taxonomy();
+
+        let result = compare_hits(
+            3,
+            &original,
+            &reduced,
+            &relation,
+            &taxa,
+            Some(&db),
+            &[TaxonomicRank::Genus, TaxonomicRank::Species],
+        );
+
+        assert_eq!(result.original_hits, 4);
+        assert_eq!(result.recovered_hits, 3);
+        assert!((result.sensitivity - 0.75).abs() < 1e-9);
+        assert_eq!(result.top_hits_concordant, 2);
+
+        let rank = |name: &str| {
+            result
+                .rank_agreement
+                .iter()
+                .find(|r| r.rank == name)
+                .unwrap()
+                .clone()
+        };
+        // q2's top hit moved from species 12 to 11, same genus
+        assert_eq!((rank("taxon").agreed, rank("taxon").compared), (1, 3));
+        assert_eq!((rank("genus").agreed, rank("genus").compared), (2, 3));
+        assert_eq!((rank("species").agreed, rank("species").compared), (1, 3));
+
+        // b: 1e-50 vs 1e-40, c: 1e-20 vs 1e-30
+        assert_eq!(result.evalue_shift.compared, 3);
+        assert_eq!(result.evalue_shift.worse, 1);
+        assert!((result.evalue_shift.max_log10 - 10.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_compare_hits_without_taxonomy_reports_exact_taxon_only() {
+        let original = vec![hit("q1", "a", 1e-5)];
+        let result = compare_hits(
+            2,
+            &original,
+            &[],
+            &HashMap::new(),
+            &HashMap::from([("a".to_string(), 11)]),
+            None,
+            &DEFAULT_RANKS,
+        );
+
+        assert_eq!(result.queries, 2);
+        assert_eq!(result.queries_with_reduced_hits, 0);
+        assert_eq!(result.sensitivity, 0.0);
+        assert_eq!(result.rank_agreement.len(), 1);
+        assert_eq!(result.rank_agreement[0].compared, 1);
+        assert_eq!(result.rank_agreement[0].agreed, 0);
+    }
+
+    #[test]
+    fn test_compare_hits_expands_self_hits_before_dropping_them() {
+        // q1 is in the database and a child of reference a; q2 is reference
+        // b with child d; q3 is a reference without children
+        let original = vec![
+            hit("q1", "q1", 1e-90),
+            hit("q1", "c", 1e-30),
+            hit("q2", "q2", 1e-90),
+            hit("q2", "d", 1e-40),
+            hit("q3", "q3", 1e-90),
+        ];
+        let reduced = vec![
+            hit("q1", "a", 1e-80),
+            hit("q2", "q2", 1e-90),
+            hit("q3", "q3", 1e-90),
+        ];
+        let relation = HashMap::from([
+            ("a".to_string(), vec!["q1".to_string(), "c".to_string()]),
+            ("q2".to_string(), vec!["d".to_string()]),
+        ]);
+
+        let result = compare_hits(
+            3,
+            &original,
+            &reduced,
+            &relation,
+            &HashMap::new(),
+            None,
+            &DEFAULT_RANKS,
+        );
+
+        // q1 -> c and q2 -> d are real hits; q2's self-hit recovers d, while
+        // q3 found nothing but itself
+        assert_eq!(result.original_hits, 2);
+        assert_eq!(result.queries_with_hits, 2);
+        assert_eq!(result.queries_with_reduced_hits, 2);
+        assert_eq!(result.recovered_hits, 2);
+        assert_eq!(result.top_hits_concordant, 2);
+    }
+
+    #[test]
+    fn test_sample_queries_is_reproducible() {
+        let sequences: Vec<Sequence> = (0..50)
+            .map(|i| Sequence::new(format!("seq{:02}", i), b"MKV".to_vec()))
+            .collect();
+
+        let a = sample_queries(&sequences, 10, 7);
+        let b = sample_queries(&sequences, 10, 7);
+        assert_eq!(a.len(), 10);
+        assert_eq!(
+            a.iter().map(|s| &s.id).collect::<Vec<_>>(),
+            b.iter().map(|s| &s.id).collect::<Vec<_>>()
+        );
+        assert_eq!(sample_queries(&sequences, 100, 7).len(), 50);
+    }
+}
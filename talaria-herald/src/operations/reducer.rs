@@ -1,4 +1,5 @@
 use super::reference_selector::{ReferenceSelectorImpl, SelectionAlgorithm};
+use crate::performance::metrics;
 use crate::TargetAligner;
 use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
 use std::collections::HashMap;
@@ -163,6 +164,13 @@ impl Reducer {
     ) -> Result<(Vec<Sequence>, Vec<DeltaRecord>, usize), talaria_core::error::TalariaError> {
         let multi_progress = MultiProgress::new();
 
+        // Each call is reported to the metrics exporter as one batch
+        let metrics_input = metrics::registry().map(|metrics| {
+            metrics.set_batch_size(sequences.len());
+            let bytes: usize = sequences.iter().map(|s| s.sequence.len()).sum();
+            (metrics, sequences.len(), bytes)
+        });
+
         // Step 0: Sanitize sequences by removing those with ambiguous residues
         let (sanitized_sequences, removed_count) = if !self.silent {
             action("Sanitizing sequences (removing ambiguous residues)...");
@@ -383,6 +391,10 @@ impl Reducer {
 
         // Don't print statistics here - let the command do it after writing files
 
+        if let Some((metrics, count, bytes)) = metrics_input {
+            metrics.record_processed(count, bytes);
+        }
+
         Ok((selection_result.references, deltas, original_count))
     }
 
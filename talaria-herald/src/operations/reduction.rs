@@ -58,6 +58,14 @@ pub struct ReductionManifest {
 
     /// Optional previous version of this reduction profile
     pub previous_version: Option<SHA256Hash>,
+
+    /// Processing pipeline applied to the input before reduction
+    #[serde(default)]
+    pub pipeline: Option<crate::processing::PipelineRecord>,
+
+    /// Contamination findings of the pipeline's screening steps
+    #[serde(default)]
+    pub screening: Option<crate::processing::ContaminationReport>,
 }
 
 /// Parameters used for reduction
@@ -196,6 +204,8 @@ impl ReductionManifest {
             created_at: Utc::now(),
             version: "1.0.0".to_string(),
             previous_version: None,
+            pipeline: None,
+            screening: None,
         }
     }
 
@@ -10,7 +10,8 @@ use serde::{Deserialize, Serialize};
 use std::time::Duration;
 use talaria_bio::taxonomy::TaxonomyDiscrepancy;
 use talaria_utils::report::{
-    Cell, CellStyle, Metric, MetricSeverity, Report, Reportable, Section, Table,
+    Cell, CellStyle, ChartData, ChartType, Metric, MetricSeverity, Report, Reportable, Section,
+    Table,
 };
 
 /// Result of a database reduction operation
@@ -64,6 +65,90 @@ pub enum ErrorSeverity {
     Low,
 }
 
+/// Result of a search-recall validation of a reduction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecallValidationResult {
+    pub aligner: String,
+    pub queries: usize,
+    /// Queries with at least one hit against the original database
+    pub queries_with_hits: usize,
+    pub queries_with_reduced_hits: usize,
+    /// Distinct (query, subject) pairs found in the original database
+    pub original_hits: usize,
+    /// Original pairs still found after mapping reduced hits to children
+    pub recovered_hits: usize,
+    pub sensitivity: f64,
+    pub top_hits_concordant: usize,
+    pub top_hit_concordance: f64,
+    pub rank_agreement: Vec<RankAgreement>,
+    pub evalue_shift: EValueShift,
+    pub duration: Duration,
+}
+
+/// Agreement of top-hit taxonomic assignments at one rank
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RankAgreement {
+    pub rank: String,
+    pub compared: usize,
+    pub agreed: usize,
+    pub agreement: f64,
+}
+
+impl RankAgreement {
+    pub fn new(rank: &str) -> Self {
+        Self {
+            rank: rank.to_string(),
+            compared: 0,
+            agreed: 0,
+            agreement: 0.0,
+        }
+    }
+
+    pub fn record(&mut self, agreed: bool) {
+        self.compared += 1;
+        if agreed {
+            self.agreed += 1;
+        }
+    }
+}
+
+/// Change in log10 e-value for hits found in both databases
+///
+/// Positive shifts mean the reduced database reports a weaker hit.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EValueShift {
+    pub compared: usize,
+    pub mean_log10: f64,
+    pub median_log10: f64,
+    pub max_log10: f64,
+    /// Hits whose e-value got worse
+    pub worse: usize,
+}
+
+impl EValueShift {
+    pub fn from_log10_shifts(mut shifts: Vec<f64>) -> Self {
+        if shifts.is_empty() {
+            return Self::default();
+        }
+
+        shifts.sort_by(f64::total_cmp);
+        let n = shifts.len();
+        let median_log10 = if n.is_multiple_of(2) {
+            (shifts[n / 2 - 1] + shifts[n / 2]) / 2.0
+        } else {
+            shifts[n / 2]
+        };
+
+        Self {
+            compared: n,
+            mean_log10: shifts.iter().sum::<f64>() / n as f64,
+            median_log10,
+            max_log10: shifts[n - 1],
+            worse: shifts.iter().filter(|s| **s > 0.0).count(),
+        }
+    }
+}
+
 /// Result of a garbage collection operation
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GarbageCollectionResult {
@@ -264,6 +349,9 @@ pub struct TaxonomyCoverageResult {
     pub coverage_by_rank: Vec<(String, usize)>, // (rank, count)
     pub most_common_taxa: Vec<(String, usize)>, // (taxon_name, sequence_count)
     pub rare_taxa: Vec<(String, usize)>,
+    /// Sequence counts per lineage (names root first), for the sunburst
+    #[serde(default)]
+    pub lineage_counts: Vec<(Vec<String>, usize)>,
     pub comparison: Option<TaxonomyComparison>,
     pub duration: Duration,
 }
@@ -1305,6 +1393,27 @@ impl Reportable for StatsResult {
         ]);
         report = report.section(Section::table("Base Composition", comp_table));
 
+        let composition = ChartData::new(
+            ChartType::Pie,
+            "Base composition",
+            ["A", "C", "G", "T", "N", "Other"]
+                .iter()
+                .map(|base| base.to_string())
+                .collect(),
+        )
+        .with_dataset(
+            "Bases",
+            vec![
+                self.composition.a_count as f64,
+                self.composition.c_count as f64,
+                self.composition.g_count as f64,
+                self.composition.t_count as f64,
+                self.composition.n_count as f64,
+                self.composition.other_count as f64,
+            ],
+        );
+        report = report.section(Section::chart("Composition Chart", composition));
+
         // Length distribution (show top 10 bins)
         if !self.length_distribution.is_empty() {
             let mut dist_table = Table::new(vec!["Length Range".to_string(), "Count".to_string()]);
@@ -1325,6 +1434,15 @@ impl Reportable for StatsResult {
                 ]);
             }
             report = report.section(Section::table("Length Distribution", dist_table));
+
+            let (labels, counts) = self
+                .length_distribution
+                .iter()
+                .map(|(bin, count)| (format!("{} bp", bin), *count as f64))
+                .unzip();
+            let histogram = ChartData::new(ChartType::Histogram, "Sequence lengths", labels)
+                .with_dataset("Sequences", counts);
+            report = report.section(Section::chart("Length Histogram", histogram));
         }
 
         report.build()
@@ -1362,6 +1480,18 @@ impl Reportable for TaxonomyCoverageResult {
             report = report.section(Section::table("Coverage by Rank", rank_table));
         }
 
+        if !self.lineage_counts.is_empty() {
+            let lineages: Vec<(Vec<String>, f64)> = self
+                .lineage_counts
+                .iter()
+                .map(|(lineage, count)| (lineage.clone(), *count as f64))
+                .collect();
+            report = report.section(Section::chart(
+                "Taxonomic Composition",
+                ChartData::sunburst("Sequences by lineage", &lineages),
+            ));
+        }
+
         // Most common taxa
         if !self.most_common_taxa.is_empty() {
             let mut common_table = Table::new(vec![
@@ -1508,6 +1638,98 @@ impl Reportable for HistoryResult {
     }
 }
 
+impl Reportable for RecallValidationResult {
+    fn to_report(&self) -> Report {
+        let mut report = Report::builder("Search Recall Validation", "validate")
+            .metadata("duration", format!("{:.2?}", self.duration))
+            .metadata("aligner", &self.aligner);
+
+        let severity = |value: f64, good: f64, fair: f64| {
+            if value >= good {
+                MetricSeverity::Success
+            } else if value >= fair {
+                MetricSeverity::Warning
+            } else {
+                MetricSeverity::Error
+            }
+        };
+
+        let summary_metrics = vec![
+            Metric::new("Queries", self.queries).with_severity(MetricSeverity::Info),
+            Metric::new("Queries With Hits", self.queries_with_hits)
+                .with_severity(MetricSeverity::Info),
+            Metric::new("Sensitivity", format!("{:.2}%", self.sensitivity * 100.0))
+                .with_severity(severity(self.sensitivity, 0.99, 0.95)),
+            Metric::new(
+                "Top-Hit Concordance",
+                format!("{:.2}%", self.top_hit_concordance * 100.0),
+            )
+            .with_severity(severity(self.top_hit_concordance, 0.99, 0.95)),
+            Metric::new(
+                "Median E-value Shift",
+                format!("{:+.2} log10", self.evalue_shift.median_log10),
+            )
+            .with_severity(MetricSeverity::Info),
+        ];
+        report = report.section(Section::summary("Summary", summary_metrics));
+
+        let mut hits_table = Table::new(vec!["Metric".to_string(), "Value".to_string()]);
+        for (label, value) in [
+            ("Original hits", self.original_hits.to_string()),
+            ("Recovered hits", self.recovered_hits.to_string()),
+            (
+                "Queries with reduced hits",
+                self.queries_with_reduced_hits.to_string(),
+            ),
+            (
+                "Concordant top hits",
+                format!("{} / {}", self.top_hits_concordant, self.queries_with_hits),
+            ),
+        ] {
+            hits_table.add_row(vec![Cell::new(label), Cell::new(value)]);
+        }
+        report = report.section(Section::table("Hits", hits_table));
+
+        if !self.rank_agreement.is_empty() {
+            let mut rank_table = Table::new(vec![
+                "Rank".to_string(),
+                "Compared".to_string(),
+                "Agreed".to_string(),
+                "Agreement".to_string(),
+            ]);
+            for row in &self.rank_agreement {
+                let style = match severity(row.agreement, 0.99, 0.95) {
+                    MetricSeverity::Success => CellStyle::Success,
+                    MetricSeverity::Warning => CellStyle::Warning,
+                    _ => CellStyle::Error,
+                };
+                rank_table.add_row(vec![
+                    Cell::new(&row.rank),
+                    Cell::new(row.compared.to_string()),
+                    Cell::new(row.agreed.to_string()),
+                    Cell::new(format!("{:.2}%", row.agreement * 100.0)).with_style(style),
+                ]);
+            }
+            report = report.section(Section::table("Taxonomic Agreement", rank_table));
+        }
+
+        let shift = &self.evalue_shift;
+        let mut shift_table = Table::new(vec!["Statistic".to_string(), "log10 Shift".to_string()]);
+        for (label, value) in [
+            ("Hits compared", shift.compared.to_string()),
+            ("Mean", format!("{:+.2}", shift.mean_log10)),
+            ("Median", format!("{:+.2}", shift.median_log10)),
+            ("Largest increase", format!("{:+.2}", shift.max_log10)),
+            ("Hits with worse e-value", shift.worse.to_string()),
+        ] {
+            shift_table.add_row(vec![Cell::new(label), Cell::new(value)]);
+        }
+        report = report.section(Section::table("E-value Shift", shift_table));
+
+        report.build()
+    }
+}
+
 fn format_bytes(bytes: u64) -> String {
     const KB: u64 = 1024;
     const MB: u64 = KB * 1024;
@@ -133,6 +133,20 @@ impl LockFreeThroughputMonitor {
         (seq_per_sec, mb_per_sec)
     }
 
+    /// Get total sequences, bytes and chunks recorded so far - lock-free
+    pub fn totals(&self) -> (u64, u64, u64) {
+        (
+            self.metrics.total_sequences.load(Ordering::Relaxed),
+            self.metrics.total_bytes.load(Ordering::Relaxed),
+            self.metrics.total_chunks.load(Ordering::Relaxed),
+        )
+    }
+
+    /// Get the last recorded batch size - lock-free
+    pub fn batch_size(&self) -> usize {
+        self.metrics.current_batch_size.load(Ordering::Relaxed)
+    }
+
     /// Detect bottlenecks - lock-free
     pub fn detect_bottlenecks(&self) -> Vec<Bottleneck> {
         let mut bottlenecks = Vec::new();
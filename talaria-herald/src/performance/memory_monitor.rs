@@ -273,11 +273,9 @@ impl MemoryMonitor {
         let overhead_factor = 2; // Account for processing overhead
         let bytes_per_sequence = (avg_sequence_size * overhead_factor) as u64;
 
-        let optimal_size = if bytes_per_sequence > 0 {
-            (usable_bytes / bytes_per_sequence) as usize
-        } else {
-            max_batch
-        };
+        let optimal_size = usable_bytes
+            .checked_div(bytes_per_sequence)
+            .map_or(max_batch, |n| n as usize);
 
         // Clamp to min/max bounds
         optimal_size.max(min_batch).min(max_batch)
@@ -0,0 +1,656 @@
+/// Prometheus metrics export for long-running operations
+///
+/// Nothing is collected until `enable` installs the process-wide registry.
+/// Storage code reports sequences and chunks through `registry()`, which is a
+/// single atomic load when metrics are off. The registry is rendered in the
+/// Prometheus text exposition format, served over HTTP at `/metrics` and/or
+/// written periodically to a textfile for node_exporter's textfile collector.
+use super::adaptive::AdaptiveConfig;
+use super::lock_free_monitor::LockFreeThroughputMonitor;
+use super::memory_monitor::MemoryMonitor;
+use crate::storage::RocksDBBackend;
+use anyhow::{Context, Result};
+use parking_lot::Mutex;
+use std::fmt::Write as _;
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock, Weak};
+use std::thread;
+use std::time::{Duration, Instant};
+use talaria_storage::backend::RocksDBMetrics;
+
+/// Content type of the Prometheus text exposition format
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Poll interval of the exporter threads while waiting for work or shutdown
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+static REGISTRY: OnceLock<MetricsRegistry> = OnceLock::new();
+
+/// Name, type, help and accessor of an exported RocksDB figure
+type RocksDBSeries = (
+    &'static str,
+    &'static str,
+    &'static str,
+    fn(&RocksDBMetrics) -> u64,
+);
+
+const ROCKSDB_METRICS: [RocksDBSeries; 13] = [
+    (
+        "talaria_rocksdb_keys",
+        "gauge",
+        "Estimated number of keys",
+        |m| m.total_keys,
+    ),
+    (
+        "talaria_rocksdb_live_data_bytes",
+        "gauge",
+        "Estimated live data size",
+        |m| m.total_size_bytes,
+    ),
+    (
+        "talaria_rocksdb_sst_files",
+        "gauge",
+        "SST files across all levels",
+        |m| m.num_files,
+    ),
+    (
+        "talaria_rocksdb_pending_compaction_bytes",
+        "gauge",
+        "Estimated bytes compaction still has to rewrite",
+        |m| m.pending_compaction_bytes,
+    ),
+    (
+        "talaria_rocksdb_running_compactions",
+        "gauge",
+        "Compactions currently running",
+        |m| m.running_compactions,
+    ),
+    (
+        "talaria_rocksdb_compaction_read_bytes_total",
+        "counter",
+        "Bytes read by compaction (requires statistics)",
+        |m| m.compaction_read_bytes,
+    ),
+    (
+        "talaria_rocksdb_compaction_write_bytes_total",
+        "counter",
+        "Bytes written by compaction (requires statistics)",
+        |m| m.compaction_write_bytes,
+    ),
+    (
+        "talaria_rocksdb_memtable_bytes",
+        "gauge",
+        "Size of all memtables",
+        |m| m.memtable_bytes,
+    ),
+    (
+        "talaria_rocksdb_block_cache_usage_bytes",
+        "gauge",
+        "Block cache memory in use",
+        |m| m.block_cache_usage_bytes,
+    ),
+    (
+        "talaria_rocksdb_block_cache_hits_total",
+        "counter",
+        "Block cache hits (requires statistics)",
+        |m| m.cache_hits,
+    ),
+    (
+        "talaria_rocksdb_block_cache_misses_total",
+        "counter",
+        "Block cache misses (requires statistics)",
+        |m| m.cache_misses,
+    ),
+    (
+        "talaria_rocksdb_bloom_filter_positive_total",
+        "counter",
+        "Point lookups the bloom filter let through (requires statistics)",
+        |m| m.bloom_filter_hits,
+    ),
+    (
+        "talaria_rocksdb_bloom_filter_useful_total",
+        "counter",
+        "Point lookups the bloom filter answered without a read (requires statistics)",
+        |m| m.bloom_filter_misses,
+    ),
+];
+
+/// Install the process-wide registry, labelled with the running operation
+///
+/// Later calls return the registry installed first.
+pub fn enable(operation: &str) -> &'static MetricsRegistry {
+    REGISTRY.get_or_init(|| MetricsRegistry::new(operation))
+}
+
+/// The process-wide registry, if metrics are enabled
+pub fn registry() -> Option<&'static MetricsRegistry> {
+    REGISTRY.get()
+}
+
+/// Counters and sampled gauges of one operation
+pub struct MetricsRegistry {
+    operation: String,
+    started: Instant,
+    throughput: LockFreeThroughputMonitor,
+    memory: MemoryMonitor,
+    memory_pressure_threshold: f64,
+    /// Set once the operation reports its own input with `record_processed`
+    reports_processed: AtomicBool,
+    sequences_new: AtomicU64,
+    sequences_deduplicated: AtomicU64,
+    chunks_new: AtomicU64,
+    chunks_deduplicated: AtomicU64,
+    chunk_bytes_new: AtomicU64,
+    chunk_bytes_deduplicated: AtomicU64,
+    /// RocksDB instances to sample, by name; weak so the registry never keeps a database open
+    rocksdb: Mutex<Vec<(&'static str, Weak<RocksDBBackend>)>>,
+}
+
+impl MetricsRegistry {
+    pub fn new(operation: &str) -> Self {
+        let mut memory = MemoryMonitor::new();
+        memory.start(Duration::from_secs(1));
+
+        Self {
+            operation: operation.to_string(),
+            started: Instant::now(),
+            throughput: LockFreeThroughputMonitor::new(),
+            memory,
+            memory_pressure_threshold: AdaptiveConfig::default().target_memory_usage as f64,
+            reports_processed: AtomicBool::new(false),
+            sequences_new: AtomicU64::new(0),
+            sequences_deduplicated: AtomicU64::new(0),
+            chunks_new: AtomicU64::new(0),
+            chunks_deduplicated: AtomicU64::new(0),
+            chunk_bytes_new: AtomicU64::new(0),
+            chunk_bytes_deduplicated: AtomicU64::new(0),
+            rocksdb: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn operation(&self) -> &str {
+        &self.operation
+    }
+
+    /// Record input sequences of an operation that does more than store them
+    ///
+    /// From then on stored sequences only count as new or deduplicated, so an
+    /// operation that stores part of its input (reduce) counts it once.
+    pub fn record_processed(&self, count: usize, bytes: usize) {
+        self.reports_processed.store(true, Ordering::Relaxed);
+        self.throughput.record_sequences(count, bytes);
+    }
+
+    /// Record stored sequences, `new` of which were not stored before
+    pub fn record_sequences(&self, count: usize, bytes: usize, new: usize) {
+        if !self.reports_processed.load(Ordering::Relaxed) {
+            self.throughput.record_sequences(count, bytes);
+        }
+        self.sequences_new.fetch_add(new as u64, Ordering::Relaxed);
+        self.sequences_deduplicated
+            .fetch_add(count.saturating_sub(new) as u64, Ordering::Relaxed);
+    }
+
+    /// Record a chunk write; `deduplicated` chunks were already stored
+    pub fn record_chunk(&self, bytes: usize, deduplicated: bool) {
+        self.throughput.record_chunks(1);
+        let (chunks, chunk_bytes) = if deduplicated {
+            (&self.chunks_deduplicated, &self.chunk_bytes_deduplicated)
+        } else {
+            (&self.chunks_new, &self.chunk_bytes_new)
+        };
+        chunks.fetch_add(1, Ordering::Relaxed);
+        chunk_bytes.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    pub fn set_batch_size(&self, size: usize) {
+        self.throughput.update_batch_size(size);
+    }
+
+    /// Sample a RocksDB instance under `name`, replacing any earlier one
+    pub fn attach_rocksdb(&self, name: &'static str, backend: &Arc<RocksDBBackend>) {
+        let mut backends = self.rocksdb.lock();
+        backends.retain(|(existing, db)| *existing != name && db.strong_count() > 0);
+        backends.push((name, Arc::downgrade(backend)));
+    }
+
+    /// Render all metrics in the Prometheus text format
+    pub fn render(&self) -> String {
+        let mut out = MetricsWriter::new(&self.operation);
+
+        let elapsed = self.started.elapsed().as_secs_f64();
+        let (sequences, bytes, chunks) = self.throughput.totals();
+        let (seq_per_sec, mb_per_sec) = self.throughput.current_throughput();
+
+        out.metric(
+            "talaria_operation_uptime_seconds",
+            "gauge",
+            "Seconds since the operation started",
+            &[(&[], elapsed)],
+        );
+        out.metric(
+            "talaria_sequences_processed_total",
+            "counter",
+            "Sequences processed",
+            &[(&[], sequences as f64)],
+        );
+        out.metric(
+            "talaria_bytes_processed_total",
+            "counter",
+            "Sequence bytes processed",
+            &[(&[], bytes as f64)],
+        );
+        out.metric(
+            "talaria_sequences_per_second",
+            "gauge",
+            "Average sequence throughput",
+            &[(&[], seq_per_sec)],
+        );
+        out.metric(
+            "talaria_bytes_per_second",
+            "gauge",
+            "Average byte throughput",
+            &[(&[], mb_per_sec * 1_000_000.0)],
+        );
+        out.metric(
+            "talaria_batch_size",
+            "gauge",
+            "Sequences in the most recent batch",
+            &[(&[], self.throughput.batch_size() as f64)],
+        );
+
+        let sequences_new = self.sequences_new.load(Ordering::Relaxed);
+        let sequences_dedup = self.sequences_deduplicated.load(Ordering::Relaxed);
+        out.metric(
+            "talaria_sequences_stored_total",
+            "counter",
+            "Sequences by whether they were new or already stored",
+            &[
+                (&[("result", "new")], sequences_new as f64),
+                (&[("result", "deduplicated")], sequences_dedup as f64),
+            ],
+        );
+
+        let chunks_new = self.chunks_new.load(Ordering::Relaxed);
+        let chunks_dedup = self.chunks_deduplicated.load(Ordering::Relaxed);
+        out.metric(
+            "talaria_chunks_stored_total",
+            "counter",
+            "Chunk writes by whether the chunk was new or already stored",
+            &[
+                (&[("result", "new")], chunks_new as f64),
+                (&[("result", "deduplicated")], chunks_dedup as f64),
+            ],
+        );
+        out.metric(
+            "talaria_chunk_bytes_stored_total",
+            "counter",
+            "Uncompressed chunk bytes by whether the chunk was new or already stored",
+            &[
+                (
+                    &[("result", "new")],
+                    self.chunk_bytes_new.load(Ordering::Relaxed) as f64,
+                ),
+                (
+                    &[("result", "deduplicated")],
+                    self.chunk_bytes_deduplicated.load(Ordering::Relaxed) as f64,
+                ),
+            ],
+        );
+        out.metric(
+            "talaria_chunks_created_total",
+            "counter",
+            "Chunks written or deduplicated",
+            &[(&[], chunks as f64)],
+        );
+        out.metric(
+            "talaria_chunk_dedup_ratio",
+            "gauge",
+            "Fraction of chunk writes that were already stored",
+            &[(&[], ratio(chunks_dedup, chunks_new + chunks_dedup))],
+        );
+        out.metric(
+            "talaria_sequence_dedup_ratio",
+            "gauge",
+            "Fraction of sequences that were already stored",
+            &[(&[], ratio(sequences_dedup, sequences_new + sequences_dedup))],
+        );
+
+        let memory = self.memory.get_stats();
+        out.metric(
+            "talaria_memory_total_bytes",
+            "gauge",
+            "Total system memory",
+            &[(&[], memory.total as f64)],
+        );
+        out.metric(
+            "talaria_memory_available_bytes",
+            "gauge",
+            "Available system memory",
+            &[(&[], memory.available as f64)],
+        );
+        out.metric(
+            "talaria_process_resident_bytes",
+            "gauge",
+            "Resident set size of this process",
+            &[(&[], memory.process_rss as f64)],
+        );
+        out.metric(
+            "talaria_memory_usage_ratio",
+            "gauge",
+            "Fraction of system memory in use",
+            &[(&[], memory.usage_ratio)],
+        );
+        out.metric(
+            "talaria_memory_pressure",
+            "gauge",
+            "1 while memory use exceeds the adaptive target",
+            &[(
+                &[],
+                memory.has_pressure(self.memory_pressure_threshold) as u8 as f64,
+            )],
+        );
+
+        self.render_rocksdb(&mut out);
+        out.finish()
+    }
+
+    fn render_rocksdb(&self, out: &mut MetricsWriter) {
+        let samples: Vec<_> = self
+            .rocksdb
+            .lock()
+            .iter()
+            .filter_map(|(name, db)| db.upgrade().map(|db| (*name, db)))
+            .collect();
+        if samples.is_empty() {
+            return;
+        }
+        let samples: Vec<_> = samples
+            .into_iter()
+            .map(|(name, db)| (name, db.collect_metrics()))
+            .collect();
+
+        let labels: Vec<[(&str, &str); 1]> = samples.iter().map(|(db, _)| [("db", *db)]).collect();
+        for (name, kind, help, value) in ROCKSDB_METRICS {
+            let values: Vec<(&[(&str, &str)], f64)> = labels
+                .iter()
+                .zip(&samples)
+                .map(|(labels, (_, metrics))| (labels.as_slice(), value(metrics) as f64))
+                .collect();
+            out.metric(name, kind, help, &values);
+        }
+    }
+}
+
+fn ratio(part: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        part as f64 / total as f64
+    }
+}
+
+/// Builds the text exposition, adding the operation label to every sample
+struct MetricsWriter<'a> {
+    operation: &'a str,
+    out: String,
+}
+
+impl<'a> MetricsWriter<'a> {
+    fn new(operation: &'a str) -> Self {
+        Self {
+            operation,
+            out: String::new(),
+        }
+    }
+
+    fn metric(&mut self, name: &str, kind: &str, help: &str, samples: &[(&[(&str, &str)], f64)]) {
+        let _ = writeln!(self.out, "# HELP {} {}", name, help);
+        let _ = writeln!(self.out, "# TYPE {} {}", name, kind);
+        for (labels, value) in samples {
+            let _ = write!(
+                self.out,
+                "{}{{operation=\"{}\"",
+                name,
+                escape_label(self.operation)
+            );
+            for (key, label) in labels.iter() {
+                let _ = write!(self.out, ",{}=\"{}\"", key, escape_label(label));
+            }
+            let _ = writeln!(self.out, "}} {}", value);
+        }
+    }
+
+    fn finish(self) -> String {
+        self.out
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Where to publish the registry
+#[derive(Debug, Clone, Default)]
+pub struct ExporterConfig {
+    /// Address to serve `GET /metrics` on
+    pub listen: Option<SocketAddr>,
+    /// File rewritten atomically for node_exporter's textfile collector
+    pub textfile: Option<PathBuf>,
+    /// Interval between textfile writes
+    pub interval: Duration,
+}
+
+/// Background threads publishing a registry until dropped
+///
+/// Dropping the exporter writes the textfile one last time so the final
+/// counters of a finished run remain visible.
+pub struct MetricsExporter {
+    registry: &'static MetricsRegistry,
+    textfile: Option<PathBuf>,
+    stop: Arc<AtomicBool>,
+    threads: Vec<thread::JoinHandle<()>>,
+}
+
+impl MetricsExporter {
+    pub fn start(registry: &'static MetricsRegistry, config: ExporterConfig) -> Result<Self> {
+        let stop = Arc::new(AtomicBool::new(false));
+        let mut threads = Vec::new();
+
+        if let Some(addr) = config.listen {
+            let listener = TcpListener::bind(addr)
+                .with_context(|| format!("Failed to listen for metrics on {}", addr))?;
+            listener.set_nonblocking(true)?;
+            let stop = Arc::clone(&stop);
+            threads.push(thread::spawn(move || serve(listener, registry, &stop)));
+        }
+
+        if let Some(path) = config.textfile.clone() {
+            // Fail early on an unwritable path rather than in the background
+            write_textfile(&path, registry)?;
+            let stop = Arc::clone(&stop);
+            let interval = config.interval.max(POLL_INTERVAL);
+            threads.push(thread::spawn(move || {
+                let mut last_write = Instant::now();
+                while !stop.load(Ordering::Relaxed) {
+                    thread::sleep(POLL_INTERVAL);
+                    if last_write.elapsed() >= interval {
+                        if let Err(e) = write_textfile(&path, registry) {
+                            tracing::warn!("Failed to write metrics to {}: {}", path.display(), e);
+                        }
+                        last_write = Instant::now();
+                    }
+                }
+            }));
+        }
+
+        Ok(Self {
+            registry,
+            textfile: config.textfile,
+            stop,
+            threads,
+        })
+    }
+}
+
+impl Drop for MetricsExporter {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        for handle in self.threads.drain(..) {
+            let _ = handle.join();
+        }
+        if let Some(path) = &self.textfile {
+            if let Err(e) = write_textfile(path, self.registry) {
+                tracing::warn!("Failed to write metrics to {}: {}", path.display(), e);
+            }
+        }
+    }
+}
+
+/// Write the registry next to `path` and rename it into place
+pub fn write_textfile(path: &Path, registry: &MetricsRegistry) -> Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    let tmp_path = path.with_extension("prom.tmp");
+    std::fs::write(&tmp_path, registry.render())?;
+    std::fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+fn serve(listener: TcpListener, registry: &MetricsRegistry, stop: &AtomicBool) {
+    while !stop.load(Ordering::Relaxed) {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                if let Err(e) = respond(stream, registry) {
+                    tracing::debug!("Metrics request failed: {}", e);
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => thread::sleep(POLL_INTERVAL),
+            Err(e) => {
+                tracing::warn!("Metrics listener failed: {}", e);
+                thread::sleep(POLL_INTERVAL);
+            }
+        }
+    }
+}
+
+/// Answer one HTTP/1.x request; only `GET /metrics` is served
+fn respond(mut stream: TcpStream, registry: &MetricsRegistry) -> std::io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+
+    let mut request = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !request.windows(4).any(|w| w == b"\r\n\r\n") && request.len() < 8192 {
+        let n = stream.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        request.extend_from_slice(&buf[..n]);
+    }
+
+    let request_line = String::from_utf8_lossy(&request);
+    let mut parts = request_line.split_whitespace();
+    let (status, content_type, body) = match (parts.next(), parts.next()) {
+        (Some("GET"), Some("/metrics")) => ("200 OK", CONTENT_TYPE, registry.render()),
+        (Some("GET"), _) => ("404 Not Found", "text/plain", "Not found\n".to_string()),
+        _ => (
+            "405 Method Not Allowed",
+            "text/plain",
+            "Method not allowed\n".to_string(),
+        ),
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_counts_and_dedup_ratio() {
+        let registry = MetricsRegistry::new("add");
+        registry.record_sequences(10, 1_000, 4);
+        registry.record_chunk(500, false);
+        registry.record_chunk(500, true);
+        registry.record_chunk(500, true);
+        registry.record_chunk(500, true);
+        registry.set_batch_size(10);
+
+        let text = registry.render();
+        assert!(text.contains("# TYPE talaria_sequences_processed_total counter"));
+        assert!(text.contains("talaria_sequences_processed_total{operation=\"add\"} 10\n"));
+        assert!(text.contains(
+            "talaria_sequences_stored_total{operation=\"add\",result=\"deduplicated\"} 6\n"
+        ));
+        assert!(text.contains("talaria_chunk_dedup_ratio{operation=\"add\"} 0.75\n"));
+        assert!(text.contains("talaria_batch_size{operation=\"add\"} 10\n"));
+        // No RocksDB attached, so no RocksDB series
+        assert!(!text.contains("talaria_rocksdb_"));
+    }
+
+    #[test]
+    fn test_stored_input_is_not_processed_twice() {
+        let registry = MetricsRegistry::new("reduce");
+        registry.record_processed(10, 1_000);
+        registry.record_sequences(4, 400, 4);
+
+        let text = registry.render();
+        assert!(text.contains("talaria_sequences_processed_total{operation=\"reduce\"} 10\n"));
+        assert!(text.contains("talaria_bytes_processed_total{operation=\"reduce\"} 1000\n"));
+        assert!(text
+            .contains("talaria_sequences_stored_total{operation=\"reduce\",result=\"new\"} 4\n"));
+    }
+
+    #[test]
+    fn test_exporter_serves_and_writes_textfile() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let textfile = temp_dir.path().join("talaria.prom");
+        let registry: &'static MetricsRegistry = Box::leak(Box::new(MetricsRegistry::new("serve")));
+
+        let probe = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = probe.local_addr().unwrap();
+        drop(probe);
+
+        let exporter = MetricsExporter::start(
+            registry,
+            ExporterConfig {
+                listen: Some(addr),
+                textfile: Some(textfile.clone()),
+                interval: Duration::from_secs(60),
+            },
+        )
+        .unwrap();
+        assert!(textfile.exists());
+
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream
+            .write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains("talaria_operation_uptime_seconds{operation=\"serve\"}"));
+
+        registry.record_sequences(3, 30, 3);
+        drop(exporter);
+        let written = std::fs::read_to_string(&textfile).unwrap();
+        assert!(written.contains("talaria_sequences_processed_total{operation=\"serve\"} 3\n"));
+    }
+}
@@ -3,11 +3,13 @@ pub mod deadlock_detector;
 pub mod lock_free_monitor;
 /// Performance monitoring and optimization module
 pub mod memory_monitor;
+pub mod metrics;
 pub mod throughput;
 
 pub use adaptive::{AdaptiveConfig, AdaptiveConfigBuilder, AdaptiveManager, PerformanceMetrics};
 pub use lock_free_monitor::LockFreeThroughputMonitor;
 pub use memory_monitor::{MemoryMonitor, MemoryStats};
+pub use metrics::{ExporterConfig, MetricsExporter, MetricsRegistry};
 pub use throughput::{Bottleneck, PerformanceReport, PerformanceSnapshot, ThroughputMonitor};
 
 /// Get system information for performance tuning
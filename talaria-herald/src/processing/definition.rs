@@ -0,0 +1,618 @@
+/// Declarative processing pipelines loaded from TOML or YAML
+///
+/// A definition names an ordered list of steps, each selected by `type`:
+///
+/// ```toml
+/// name = "protein-qc"
+///
+/// [[steps]]
+/// type = "stop-codons"
+/// internal = "mask"
+///
+/// [[steps]]
+/// type = "seg"
+///
+/// [[steps]]
+/// type = "dedup"
+///
+/// [[steps]]
+/// type = "screen"
+/// panel = "UniVec.fasta"
+/// action = "trim"
+/// ```
+///
+/// Omitted step parameters take their defaults. `record` captures the
+/// definition with all defaults filled in, so a stored version says exactly
+/// what ran.
+use super::pipeline::{CaseTransformer, LowComplexityFilter, StandardProcessingPipeline};
+use super::processors::{
+    Alphabet, AmbiguityFilter, DustMasker, ExactDeduplicator, HeaderRewriter, HeaderTarget,
+    LengthAction, LengthFilter, MaskStyle, ReverseComplementer, SegMasker, SelenocysteineHandler,
+    StopCodonAction, StopCodonHandler,
+};
+use super::screening::{ContaminantPanel, ContaminantScreener, ScreenAction, ScreeningLog};
+use crate::types::SHA256Hash;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Arc;
+use talaria_bio::sequence::Sequence;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PipelineDefinition {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub steps: Vec<StepDefinition>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum StepDefinition {
+    /// SEG masking of low-complexity protein regions
+    Seg(SegStep),
+    /// DUST masking of low-complexity nucleotide regions
+    Dust(DustStep),
+    /// Drop sequences below an entropy threshold
+    LowComplexity(LowComplexityStep),
+    Case(CaseStep),
+    ReverseComplement(ReverseComplementStep),
+    AmbiguityFilter(AmbiguityStep),
+    Length(LengthStep),
+    StopCodons(StopCodonStep),
+    Selenocysteine(SelenocysteineStep),
+    RewriteHeader(RewriteHeaderStep),
+    Dedup(DedupStep),
+    /// Flag, trim or drop matches to a contaminant panel
+    Screen(ScreenStep),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SegStep {
+    pub window: usize,
+    pub locut: f64,
+    pub hicut: f64,
+    pub mask: MaskStyle,
+    pub alphabet: Alphabet,
+}
+
+impl Default for SegStep {
+    fn default() -> Self {
+        Self {
+            window: 12,
+            locut: 2.2,
+            hicut: 2.5,
+            mask: MaskStyle::Hard,
+            alphabet: Alphabet::Auto,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DustStep {
+    pub window: usize,
+    pub threshold: f64,
+    pub mask: MaskStyle,
+    pub alphabet: Alphabet,
+}
+
+impl Default for DustStep {
+    fn default() -> Self {
+        Self {
+            window: 64,
+            threshold: 20.0,
+            mask: MaskStyle::Hard,
+            alphabet: Alphabet::Auto,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LowComplexityStep {
+    /// Normalized entropy below which a sequence is dropped (0.0-1.0)
+    pub threshold: f64,
+    pub min_length: usize,
+}
+
+impl Default for LowComplexityStep {
+    fn default() -> Self {
+        Self {
+            threshold: 0.3,
+            min_length: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CaseStep {
+    pub upper: bool,
+}
+
+impl Default for CaseStep {
+    fn default() -> Self {
+        Self { upper: true }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ReverseComplementStep {
+    pub alphabet: Alphabet,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AmbiguityStep {
+    pub max_fraction: f64,
+    pub alphabet: Alphabet,
+}
+
+impl Default for AmbiguityStep {
+    fn default() -> Self {
+        Self {
+            max_fraction: 0.1,
+            alphabet: Alphabet::Auto,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LengthStep {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max: Option<usize>,
+    /// Also treat lengths this many robust deviations from the median as outliers
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mad_factor: Option<f64>,
+    pub action: LengthAction,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StopCodonStep {
+    pub internal: StopCodonAction,
+    pub strip_trailing: bool,
+    pub alphabet: Alphabet,
+}
+
+impl Default for StopCodonStep {
+    fn default() -> Self {
+        Self {
+            internal: StopCodonAction::Keep,
+            strip_trailing: true,
+            alphabet: Alphabet::Auto,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SelenocysteineStep {
+    pub replacement: char,
+    pub alphabet: Alphabet,
+}
+
+impl Default for SelenocysteineStep {
+    fn default() -> Self {
+        Self {
+            replacement: 'C',
+            alphabet: Alphabet::Auto,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RewriteHeaderStep {
+    pub pattern: String,
+    pub replacement: String,
+    #[serde(default)]
+    pub target: HeaderTarget,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DedupStep {
+    pub ignore_case: bool,
+}
+
+impl Default for DedupStep {
+    fn default() -> Self {
+        Self { ignore_case: true }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ScreenStep {
+    /// FASTA file, or a HERALD database such as "contaminants/univec"
+    pub panel: String,
+    /// Nucleotide k-mer length
+    pub k: usize,
+    /// Protein k-mer length
+    pub protein_k: usize,
+    /// Shared k-mers needed to call a match
+    pub min_kmers: usize,
+    pub action: ScreenAction,
+    /// Trimmed sequences shorter than this are dropped
+    pub min_length: usize,
+    pub alphabet: Alphabet,
+}
+
+impl Default for ScreenStep {
+    fn default() -> Self {
+        Self {
+            panel: String::new(),
+            k: 25,
+            protein_k: 8,
+            min_kmers: 3,
+            action: ScreenAction::Exclude,
+            min_length: 30,
+            alphabet: Alphabet::Auto,
+        }
+    }
+}
+
+/// A pipeline definition as stored with a database version or reduction
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PipelineRecord {
+    pub name: String,
+    /// SHA-256 of `definition`
+    pub sha256: String,
+    /// The definition as canonical TOML, defaults filled in
+    pub definition: String,
+}
+
+impl PipelineRecord {
+    pub fn definition(&self) -> Result<PipelineDefinition> {
+        PipelineDefinition::from_toml_str(&self.definition)
+    }
+}
+
+impl PipelineDefinition {
+    /// Load a definition; `.yaml`/`.yml` files are read as YAML, anything else as TOML
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read pipeline {}", path.display()))?;
+        let yaml = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| e.eq_ignore_ascii_case("yaml") || e.eq_ignore_ascii_case("yml"));
+        let definition = if yaml {
+            Self::from_yaml_str(&content)
+        } else {
+            Self::from_toml_str(&content)
+        };
+        definition.with_context(|| format!("Invalid pipeline {}", path.display()))
+    }
+
+    pub fn from_toml_str(content: &str) -> Result<Self> {
+        Ok(toml::from_str(content)?)
+    }
+
+    pub fn from_yaml_str(content: &str) -> Result<Self> {
+        Ok(serde_yaml::from_str(content)?)
+    }
+
+    /// Validate the steps and assemble the processors
+    ///
+    /// Screening panels must be FASTA files; see `build_with_panels`.
+    pub fn build(&self) -> Result<StandardProcessingPipeline> {
+        self.build_with_panels(&load_panel_file)
+    }
+
+    /// Like `build`, with `load_panel` turning each `screen` step's panel
+    /// into sequences (e.g. to read panels stored as HERALD databases)
+    pub fn build_with_panels(
+        &self,
+        load_panel: &dyn Fn(&str) -> Result<Vec<Sequence>>,
+    ) -> Result<StandardProcessingPipeline> {
+        if self.steps.is_empty() {
+            anyhow::bail!("Pipeline '{}' has no steps", self.name);
+        }
+
+        let mut pipeline = StandardProcessingPipeline::new();
+        let log = pipeline.screening_log();
+        for (index, step) in self.steps.iter().enumerate() {
+            let processor = step
+                .build(&log, load_panel)
+                .with_context(|| format!("Pipeline '{}' step {}", self.name, index + 1))?;
+            pipeline.add_processor(processor);
+        }
+        Ok(pipeline)
+    }
+
+    /// Canonical form for version metadata
+    pub fn record(&self) -> Result<PipelineRecord> {
+        let definition = toml::to_string(self)?;
+        Ok(PipelineRecord {
+            name: self.name.clone(),
+            sha256: SHA256Hash::compute(definition.as_bytes()).to_hex(),
+            definition,
+        })
+    }
+}
+
+/// Panels given as FASTA paths
+fn load_panel_file(panel: &str) -> Result<Vec<Sequence>> {
+    if !Path::new(panel).is_file() {
+        anyhow::bail!("Contaminant panel '{}' is not a FASTA file", panel);
+    }
+    Ok(talaria_bio::parse_fasta(panel)?)
+}
+
+impl StepDefinition {
+    fn build(
+        &self,
+        log: &ScreeningLog,
+        load_panel: &dyn Fn(&str) -> Result<Vec<Sequence>>,
+    ) -> Result<Box<dyn super::traits::SequenceProcessor>> {
+        Ok(match self {
+            StepDefinition::Seg(step) => {
+                if step.window == 0 || step.locut > step.hicut {
+                    anyhow::bail!("seg needs window > 0 and locut <= hicut");
+                }
+                Box::new(SegMasker::new(
+                    step.window,
+                    step.locut,
+                    step.hicut,
+                    step.mask,
+                    step.alphabet,
+                ))
+            }
+            StepDefinition::Dust(step) => {
+                if step.window < 4 {
+                    anyhow::bail!("dust needs a window of at least 4");
+                }
+                Box::new(DustMasker::new(
+                    step.window,
+                    step.threshold,
+                    step.mask,
+                    step.alphabet,
+                ))
+            }
+            StepDefinition::LowComplexity(step) => {
+                Box::new(LowComplexityFilter::new(step.threshold, step.min_length))
+            }
+            StepDefinition::Case(step) => Box::new(CaseTransformer::new(step.upper)),
+            StepDefinition::ReverseComplement(step) => {
+                Box::new(ReverseComplementer::new(step.alphabet))
+            }
+            StepDefinition::AmbiguityFilter(step) => {
+                if !(0.0..=1.0).contains(&step.max_fraction) {
+                    anyhow::bail!("ambiguity-filter max_fraction must be within 0.0-1.0");
+                }
+                Box::new(AmbiguityFilter::new(step.max_fraction, step.alphabet))
+            }
+            StepDefinition::Length(step) => {
+                if let (Some(min), Some(max)) = (step.min, step.max) {
+                    if min > max {
+                        anyhow::bail!("length min {} exceeds max {}", min, max);
+                    }
+                }
+                if step.mad_factor.is_some_and(|f| f <= 0.0) {
+                    anyhow::bail!("length mad_factor must be positive");
+                }
+                if step.min.is_none() && step.max.is_none() && step.mad_factor.is_none() {
+                    anyhow::bail!("length needs min, max or mad_factor");
+                }
+                Box::new(LengthFilter::new(
+                    step.min,
+                    step.max,
+                    step.mad_factor,
+                    step.action,
+                ))
+            }
+            StepDefinition::StopCodons(step) => Box::new(StopCodonHandler::new(
+                step.internal,
+                step.strip_trailing,
+                step.alphabet,
+            )),
+            StepDefinition::Selenocysteine(step) => {
+                if !step.replacement.is_ascii_alphabetic() {
+                    anyhow::bail!(
+                        "selenocysteine replacement '{}' is not a residue letter",
+                        step.replacement
+                    );
+                }
+                Box::new(SelenocysteineHandler::new(
+                    step.replacement as u8,
+                    step.alphabet,
+                ))
+            }
+            StepDefinition::RewriteHeader(step) => {
+                let pattern = regex::Regex::new(&step.pattern)
+                    .with_context(|| format!("Invalid header pattern '{}'", step.pattern))?;
+                Box::new(HeaderRewriter::new(
+                    pattern,
+                    step.replacement.clone(),
+                    step.target,
+                ))
+            }
+            StepDefinition::Dedup(step) => Box::new(ExactDeduplicator::new(step.ignore_case)),
+            StepDefinition::Screen(step) => {
+                if step.panel.is_empty() {
+                    anyhow::bail!("screen needs a panel");
+                }
+                let sequences = load_panel(&step.panel)
+                    .with_context(|| format!("Failed to load panel '{}'", step.panel))?;
+                let panel = ContaminantPanel::build(
+                    &step.panel,
+                    &sequences,
+                    step.k,
+                    step.protein_k,
+                    step.alphabet,
+                )?;
+                Box::new(ContaminantScreener::new(
+                    panel,
+                    step.min_kmers,
+                    step.action,
+                    step.min_length,
+                    Arc::clone(log),
+                ))
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOML_PIPELINE: &str = r#"
+name = "protein-qc"
+
+[[steps]]
+type = "stop-codons"
+internal = "drop"
+
+[[steps]]
+type = "selenocysteine"
+
+[[steps]]
+type = "rewrite-header"
+pattern = '^sp\|(\w+)\|\S+'
+replacement = "$1"
+
+[[steps]]
+type = "dedup"
+"#;
+
+    const YAML_PIPELINE: &str = r#"
+name: protein-qc
+steps:
+  - type: stop-codons
+    internal: drop
+  - type: selenocysteine
+  - type: rewrite-header
+    pattern: '^sp\|(\w+)\|\S+'
+    replacement: "$1"
+  - type: dedup
+"#;
+
+    #[test]
+    fn test_toml_and_yaml_agree() {
+        let toml = PipelineDefinition::from_toml_str(TOML_PIPELINE).unwrap();
+        let yaml = PipelineDefinition::from_yaml_str(YAML_PIPELINE).unwrap();
+        assert_eq!(toml, yaml);
+        assert_eq!(toml.steps.len(), 4);
+        assert_eq!(
+            toml.steps[3],
+            StepDefinition::Dedup(DedupStep { ignore_case: true })
+        );
+    }
+
+    #[test]
+    fn test_record_round_trips_with_defaults() {
+        let definition = PipelineDefinition::from_toml_str(TOML_PIPELINE).unwrap();
+        let record = definition.record().unwrap();
+        assert_eq!(record.name, "protein-qc");
+        assert_eq!(record.sha256.len(), 64);
+        assert!(record.definition.contains("replacement = \"C\""));
+        assert_eq!(record.definition().unwrap(), definition);
+    }
+
+    #[test]
+    fn test_alphabet_defaults_to_auto() {
+        let content = "name = \"x\"\n[[steps]]\ntype = \"reverse-complement\"\n\
+                       [[steps]]\ntype = \"selenocysteine\"\nalphabet = \"protein\"\n";
+        let definition = PipelineDefinition::from_toml_str(content).unwrap();
+        assert_eq!(
+            definition.steps[0],
+            StepDefinition::ReverseComplement(ReverseComplementStep {
+                alphabet: Alphabet::Auto
+            })
+        );
+        assert_eq!(
+            definition.steps[1],
+            StepDefinition::Selenocysteine(SelenocysteineStep {
+                replacement: 'C',
+                alphabet: Alphabet::Protein,
+            })
+        );
+        let record = definition.record().unwrap();
+        assert_eq!(record.definition().unwrap(), definition);
+    }
+
+    #[test]
+    fn test_invalid_definitions_rejected() {
+        let unknown_field = "name = \"x\"\n[[steps]]\ntype = \"seg\"\nwindw = 10\n";
+        assert!(PipelineDefinition::from_toml_str(unknown_field).is_err());
+
+        let unknown_step = "name = \"x\"\n[[steps]]\ntype = \"translate\"\n";
+        assert!(PipelineDefinition::from_toml_str(unknown_step).is_err());
+
+        let bad_regex = "name = \"x\"\n[[steps]]\ntype = \"rewrite-header\"\npattern = \"(\"\nreplacement = \"\"\n";
+        let definition = PipelineDefinition::from_toml_str(bad_regex).unwrap();
+        assert!(definition.build().is_err());
+
+        let empty_length = "name = \"x\"\n[[steps]]\ntype = \"length\"\n";
+        let definition = PipelineDefinition::from_toml_str(empty_length).unwrap();
+        assert!(definition.build().is_err());
+
+        let missing_panel =
+            "name = \"x\"\n[[steps]]\ntype = \"screen\"\npanel = \"/nonexistent.fa\"\n";
+        let definition = PipelineDefinition::from_toml_str(missing_panel).unwrap();
+        assert!(definition.build().is_err());
+    }
+
+    #[test]
+    fn test_screen_step_reports_findings() {
+        const ADAPTER: &str = "AGATCGGAAGAGCACACGTCTGAACTCCAGTCAC";
+        let definition = PipelineDefinition::from_toml_str(
+            "name = \"screen\"\n[[steps]]\ntype = \"screen\"\npanel = \"contaminants/adapters\"\nk = 21\n",
+        )
+        .unwrap();
+        let pipeline = definition
+            .build_with_panels(&|panel| {
+                assert_eq!(panel, "contaminants/adapters");
+                Ok(vec![Sequence::new("truseq".into(), ADAPTER.into())])
+            })
+            .unwrap();
+
+        let sequences = vec![
+            Sequence::new("clean".into(), b"ATGGCTAGCAAAGGAGAAGAACTTTTCACTGG".to_vec()),
+            Sequence::new("adapter".into(), format!("TTGCA{}", ADAPTER).into_bytes()),
+        ];
+        let (kept, _) = pipeline.run(sequences).unwrap();
+        assert_eq!(kept.len(), 1);
+
+        let report = pipeline.screening_report().unwrap();
+        assert_eq!(report.panels[0].name, "contaminants/adapters");
+        assert_eq!(report.findings[0].sequence_id, "adapter");
+        assert_eq!(report.count(ScreenAction::Exclude), 1);
+        assert!(PipelineDefinition::from_toml_str(TOML_PIPELINE)
+            .unwrap()
+            .build()
+            .unwrap()
+            .screening_report()
+            .is_none());
+    }
+
+    #[test]
+    fn test_pipeline_runs_steps_in_order() {
+        let definition = PipelineDefinition::from_toml_str(TOML_PIPELINE).unwrap();
+        let pipeline = definition.build().unwrap();
+        let sequences = vec![
+            Sequence::new("sp|P1|A_HUMAN".into(), b"MKVULIEF*".to_vec()),
+            Sequence::new("sp|P2|B_HUMAN".into(), b"MKVCLIEF".to_vec()),
+            Sequence::new("sp|P3|C_HUMAN".into(), b"MKV*LIEF".to_vec()),
+        ];
+
+        let (kept, result) = pipeline.run(sequences).unwrap();
+        assert_eq!(result.stages_completed, 4);
+        assert_eq!(result.stage_results[0].sequences_filtered, 1);
+        // P1 matches P2 once its selenocysteine is replaced
+        assert_eq!(result.stage_results[3].sequences_filtered, 1);
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].id, "P1");
+        assert_eq!(kept[0].sequence, b"MKVCLIEF");
+    }
+}
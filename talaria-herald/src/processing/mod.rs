@@ -2,9 +2,14 @@
 
 // Processing pipelines module
 
+pub mod definition;
 pub mod pipeline;
+pub mod processors;
+pub mod screening;
 pub mod traits;
 
 pub use traits::{BatchProcessor, ProcessingPipeline};
 
-pub use pipeline::create_reduction_pipeline;
+pub use definition::{PipelineDefinition, PipelineRecord, StepDefinition};
+pub use pipeline::{create_reduction_pipeline, StandardProcessingPipeline};
+pub use screening::{ContaminationFinding, ContaminationReport, ScreenAction};
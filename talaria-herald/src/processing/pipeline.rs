@@ -1,9 +1,12 @@
 #![allow(dead_code)]
 
 /// Processing pipeline implementation for sequence processing
+use super::processors::iupac_complement;
+use super::screening::{ContaminationReport, ScreeningLog};
 use super::traits::{
-    BatchProcessor, FilterCriteria, FilterProcessor, ProcessingPipeline, ProcessingResult,
-    ProcessorConfig, SequenceProcessor, SequenceType, TransformOperation, TransformProcessor,
+    BatchProcessor, FilterCriteria, FilterProcessor, PipelineResult, ProcessingPipeline,
+    ProcessingResult, ProcessorConfig, SequenceProcessor, SequenceType, StageResult,
+    TransformOperation, TransformProcessor,
 };
 use anyhow::Result;
 use std::time::{Duration, Instant};
@@ -14,6 +17,8 @@ pub struct StandardProcessingPipeline {
     processors: Vec<Box<dyn SequenceProcessor>>,
     batch_size: usize,
     parallel: bool,
+    /// Findings of any screening steps
+    screening: ScreeningLog,
 }
 
 impl StandardProcessingPipeline {
@@ -22,9 +27,21 @@ impl StandardProcessingPipeline {
             processors: Vec::new(),
             batch_size: 1000,
             parallel: true,
+            screening: ScreeningLog::default(),
         }
     }
 
+    /// Log that screening steps of this pipeline report into
+    pub fn screening_log(&self) -> ScreeningLog {
+        std::sync::Arc::clone(&self.screening)
+    }
+
+    /// Contamination findings so far, `None` without screening steps
+    pub fn screening_report(&self) -> Option<ContaminationReport> {
+        let report = self.screening.lock().ok()?;
+        (!report.panels.is_empty()).then(|| report.clone())
+    }
+
     pub fn with_batch_size(mut self, size: usize) -> Self {
         self.batch_size = size;
         self
@@ -39,6 +56,39 @@ impl StandardProcessingPipeline {
         self.processors.push(processor);
         self
     }
+
+    /// Run the processors in order over the whole set
+    ///
+    /// Sequences a stage filtered out (cleared) are removed before the next
+    /// stage sees them, so whole-set processors such as deduplication only
+    /// consider survivors.
+    pub fn run(&self, mut sequences: Vec<Sequence>) -> Result<(Vec<Sequence>, PipelineResult)> {
+        let start = Instant::now();
+        let mut stage_results = Vec::with_capacity(self.processors.len());
+
+        for processor in &self.processors {
+            let result = processor.process(&mut sequences)?;
+            sequences.retain(|s| !s.sequence.is_empty());
+            stage_results.push(StageResult {
+                stage_name: processor.name().to_string(),
+                success: result.errors.is_empty(),
+                time_ms: result.processing_time.as_millis() as u64,
+                sequences_processed: result.processed,
+                sequences_filtered: result.filtered,
+                sequences_modified: result.modified,
+                errors: result.errors,
+            });
+        }
+
+        Ok((
+            sequences,
+            PipelineResult {
+                stages_completed: stage_results.len(),
+                total_time_ms: start.elapsed().as_millis() as u64,
+                stage_results,
+            },
+        ))
+    }
 }
 
 impl SequenceProcessor for StandardProcessingPipeline {
@@ -348,19 +398,12 @@ impl TransformProcessor for CaseTransformer {
                 sequence.sequence.reverse();
             }
             TransformOperation::Complement => {
-                // DNA complement transformation
                 for byte in &mut sequence.sequence {
-                    *byte = match *byte {
-                        b'A' | b'a' => b'T',
-                        b'T' | b't' => b'A',
-                        b'G' | b'g' => b'C',
-                        b'C' | b'c' => b'G',
-                        _ => *byte,
-                    };
+                    *byte = iupac_complement(*byte);
                 }
             }
-            TransformOperation::Custom(_) => {
-                // Custom transformation not implemented in this basic version
+            TransformOperation::Custom(transform) => {
+                transform(sequence);
             }
         }
         Ok(())
@@ -476,3 +519,40 @@ impl ProcessingResult {
         self.processing_time += other.processing_time;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_custom_transform_is_applied() {
+        let transformer = CaseTransformer::new(true);
+        let mut seq = Sequence::new("s1".to_string(), b"ACGT".to_vec());
+        transformer
+            .transform(
+                &mut seq,
+                TransformOperation::Custom(Box::new(|s| s.sequence.truncate(2))),
+            )
+            .unwrap();
+        assert_eq!(seq.sequence, b"AC");
+
+        transformer
+            .transform(&mut seq, TransformOperation::Complement)
+            .unwrap();
+        assert_eq!(seq.sequence, b"TG");
+    }
+
+    #[test]
+    fn test_run_drops_filtered_sequences() {
+        let pipeline = create_reduction_pipeline(0.3, 5, true);
+        let sequences = vec![
+            Sequence::new("short".to_string(), b"ACG".to_vec()),
+            Sequence::new("kept".to_string(), b"acgtacgatc".to_vec()),
+        ];
+        let (kept, result) = pipeline.run(sequences).unwrap();
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].sequence, b"ACGTACGATC");
+        assert_eq!(result.stage_results[0].sequences_filtered, 1);
+        assert_eq!(result.stage_results[1].sequences_processed, 1);
+    }
+}
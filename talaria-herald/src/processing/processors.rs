@@ -0,0 +1,1074 @@
+/// Quality-control processors for declarative pipelines
+///
+/// Filters work like `LowComplexityFilter`: a dropped sequence has its
+/// residues cleared, and `StandardProcessingPipeline::run` removes it before
+/// the next stage. Processors that need the whole set (length outliers,
+/// deduplication) must therefore see all sequences in one `process` call.
+use super::traits::{ProcessingResult, ProcessorConfig, SequenceProcessor, SequenceType};
+use anyhow::Result;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+use talaria_bio::sequence::Sequence;
+
+/// How masked residues are written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MaskStyle {
+    /// Replace with `X` (protein) or `N` (nucleotide)
+    #[default]
+    Hard,
+    /// Lowercase the residues
+    Soft,
+}
+
+/// Molecule type a step treats sequences as
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Alphabet {
+    /// Detect per sequence
+    #[default]
+    Auto,
+    Nucleotide,
+    Protein,
+}
+
+/// What to do with sequences outside the length bounds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LengthAction {
+    #[default]
+    Drop,
+    /// Cut overlong sequences to the upper bound; short ones are still dropped
+    Trim,
+}
+
+/// Handling of internal stop codons (`*`) in protein sequences
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StopCodonAction {
+    #[default]
+    Keep,
+    /// Replace with `X`
+    Mask,
+    Drop,
+}
+
+/// Part of the FASTA header a rewrite applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HeaderTarget {
+    Id,
+    Description,
+    /// `id description`, split again at the first whitespace afterwards
+    #[default]
+    Header,
+}
+
+/// IUPAC complement of a nucleotide code, preserving case
+pub fn iupac_complement(base: u8) -> u8 {
+    let complement = match base.to_ascii_uppercase() {
+        b'A' => b'T',
+        b'T' | b'U' => b'A',
+        b'G' => b'C',
+        b'C' => b'G',
+        b'R' => b'Y',
+        b'Y' => b'R',
+        b'K' => b'M',
+        b'M' => b'K',
+        b'B' => b'V',
+        b'V' => b'B',
+        b'D' => b'H',
+        b'H' => b'D',
+        // S, W, N and gaps are their own complement
+        _ => return base,
+    };
+    if base.is_ascii_lowercase() {
+        complement.to_ascii_lowercase()
+    } else {
+        complement
+    }
+}
+
+fn stage_result(
+    processed: usize,
+    filtered: usize,
+    modified: usize,
+    start: Instant,
+) -> ProcessingResult {
+    ProcessingResult {
+        processed,
+        filtered,
+        modified,
+        errors: Vec::new(),
+        processing_time: start.elapsed(),
+    }
+}
+
+fn processor_config(name: &str, parameters: &[(&str, String)]) -> ProcessorConfig {
+    ProcessorConfig {
+        name: name.to_string(),
+        parameters: parameters
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.clone()))
+            .collect(),
+        ..ProcessorConfig::default()
+    }
+}
+
+impl Alphabet {
+    /// Whether `seq` is handled as a protein sequence
+    pub fn is_protein(self, seq: &Sequence) -> bool {
+        match self {
+            Alphabet::Auto => seq.detect_type() == SequenceType::Protein,
+            Alphabet::Nucleotide => false,
+            Alphabet::Protein => true,
+        }
+    }
+
+    fn name(self) -> String {
+        format!("{:?}", self).to_lowercase()
+    }
+}
+
+/// Mask `positions` in place; returns whether anything changed
+fn apply_mask(sequence: &mut [u8], masked: &[bool], style: MaskStyle, hard: u8) -> bool {
+    let mut changed = false;
+    for (residue, _) in sequence.iter_mut().zip(masked).filter(|(_, &m)| m) {
+        let new = match style {
+            MaskStyle::Hard => hard,
+            MaskStyle::Soft => residue.to_ascii_lowercase(),
+        };
+        changed |= new != *residue;
+        *residue = new;
+    }
+    changed
+}
+
+/// Shannon entropy (bits) of the residue composition of `window`
+fn composition_entropy(window: &[u8]) -> f64 {
+    let mut counts = [0u32; 256];
+    for &residue in window {
+        counts[residue.to_ascii_uppercase() as usize] += 1;
+    }
+    let len = window.len() as f64;
+    counts
+        .iter()
+        .filter(|&&c| c > 0)
+        .map(|&c| {
+            let p = c as f64 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// SEG low-complexity masking for protein sequences
+///
+/// Windows whose composition entropy is at most `locut` seed a segment,
+/// which extends over neighbouring windows at most `hicut`.
+pub struct SegMasker {
+    window: usize,
+    locut: f64,
+    hicut: f64,
+    style: MaskStyle,
+    alphabet: Alphabet,
+}
+
+impl SegMasker {
+    pub fn new(
+        window: usize,
+        locut: f64,
+        hicut: f64,
+        style: MaskStyle,
+        alphabet: Alphabet,
+    ) -> Self {
+        Self {
+            window,
+            locut,
+            hicut,
+            style,
+            alphabet,
+        }
+    }
+
+    fn low_complexity(&self, sequence: &[u8]) -> Vec<bool> {
+        let mut masked = vec![false; sequence.len()];
+        if sequence.len() < self.window {
+            return masked;
+        }
+        let entropy: Vec<f64> = sequence
+            .windows(self.window)
+            .map(composition_entropy)
+            .collect();
+
+        let mut i = 0;
+        while i < entropy.len() {
+            if entropy[i] > self.locut {
+                i += 1;
+                continue;
+            }
+            let mut left = i;
+            while left > 0 && entropy[left - 1] <= self.hicut {
+                left -= 1;
+            }
+            let mut right = i;
+            while right + 1 < entropy.len() && entropy[right + 1] <= self.hicut {
+                right += 1;
+            }
+            masked[left..right + self.window].fill(true);
+            i = right + 1;
+        }
+        masked
+    }
+}
+
+impl SequenceProcessor for SegMasker {
+    fn process(&self, sequences: &mut [Sequence]) -> Result<ProcessingResult> {
+        let start = Instant::now();
+        let mut modified = 0;
+        for seq in sequences.iter_mut().filter(|s| self.alphabet.is_protein(s)) {
+            let masked = self.low_complexity(&seq.sequence);
+            if apply_mask(&mut seq.sequence, &masked, self.style, b'X') {
+                modified += 1;
+            }
+        }
+        Ok(stage_result(sequences.len(), 0, modified, start))
+    }
+
+    fn name(&self) -> &str {
+        "SegMasker"
+    }
+
+    fn supports_type(&self, seq_type: SequenceType) -> bool {
+        seq_type == SequenceType::Protein
+    }
+
+    fn config(&self) -> ProcessorConfig {
+        processor_config(
+            self.name(),
+            &[
+                ("window", self.window.to_string()),
+                ("locut", self.locut.to_string()),
+                ("hicut", self.hicut.to_string()),
+                ("alphabet", self.alphabet.name()),
+            ],
+        )
+    }
+
+    fn estimate_time(&self, num_sequences: usize) -> Duration {
+        Duration::from_micros(num_sequences as u64 * 20)
+    }
+}
+
+/// DUST low-complexity masking for nucleotide sequences
+///
+/// Scores each window by its repeated triplets,
+/// `sum(c * (c - 1) / 2) / (l - 1)` over the `l` triplets it holds, and
+/// masks every window scoring above `threshold`.
+pub struct DustMasker {
+    window: usize,
+    threshold: f64,
+    style: MaskStyle,
+    alphabet: Alphabet,
+}
+
+impl DustMasker {
+    pub fn new(window: usize, threshold: f64, style: MaskStyle, alphabet: Alphabet) -> Self {
+        Self {
+            window,
+            threshold,
+            style,
+            alphabet,
+        }
+    }
+
+    fn triplet(codon: &[u8]) -> Option<usize> {
+        codon.iter().try_fold(0, |acc, &base| {
+            let code = match base.to_ascii_uppercase() {
+                b'A' => 0,
+                b'C' => 1,
+                b'G' => 2,
+                b'T' | b'U' => 3,
+                _ => return None,
+            };
+            Some(acc * 4 + code)
+        })
+    }
+
+    fn low_complexity(&self, sequence: &[u8]) -> Vec<bool> {
+        let mut masked = vec![false; sequence.len()];
+        if sequence.len() < self.window || self.window < 4 {
+            return masked;
+        }
+        let triplets: Vec<Option<usize>> = sequence.windows(3).map(Self::triplet).collect();
+        let per_window = self.window - 2;
+
+        // Running triplet counts and sum of c * (c - 1) / 2
+        let mut counts = [0u32; 64];
+        let mut pairs = 0u64;
+        let mut total = 0u32;
+        for (i, triplet) in triplets.iter().enumerate() {
+            if let Some(t) = *triplet {
+                pairs += counts[t] as u64;
+                counts[t] += 1;
+                total += 1;
+            }
+            if i >= per_window {
+                if let Some(t) = triplets[i - per_window] {
+                    counts[t] -= 1;
+                    pairs -= counts[t] as u64;
+                    total -= 1;
+                }
+            }
+            if i + 1 >= per_window && total > 1 {
+                let score = pairs as f64 / (total - 1) as f64;
+                if score > self.threshold {
+                    let window_start = i + 1 - per_window;
+                    masked[window_start..window_start + self.window].fill(true);
+                }
+            }
+        }
+        masked
+    }
+}
+
+impl SequenceProcessor for DustMasker {
+    fn process(&self, sequences: &mut [Sequence]) -> Result<ProcessingResult> {
+        let start = Instant::now();
+        let mut modified = 0;
+        for seq in sequences
+            .iter_mut()
+            .filter(|s| !self.alphabet.is_protein(s))
+        {
+            let masked = self.low_complexity(&seq.sequence);
+            if apply_mask(&mut seq.sequence, &masked, self.style, b'N') {
+                modified += 1;
+            }
+        }
+        Ok(stage_result(sequences.len(), 0, modified, start))
+    }
+
+    fn name(&self) -> &str {
+        "DustMasker"
+    }
+
+    fn supports_type(&self, seq_type: SequenceType) -> bool {
+        matches!(
+            seq_type,
+            SequenceType::DNA | SequenceType::RNA | SequenceType::Nucleotide
+        )
+    }
+
+    fn config(&self) -> ProcessorConfig {
+        processor_config(
+            self.name(),
+            &[
+                ("window", self.window.to_string()),
+                ("threshold", self.threshold.to_string()),
+                ("alphabet", self.alphabet.name()),
+            ],
+        )
+    }
+
+    fn estimate_time(&self, num_sequences: usize) -> Duration {
+        Duration::from_micros(num_sequences as u64 * 5)
+    }
+}
+
+/// Reverse-complement nucleotide sequences, IUPAC codes included
+///
+/// Protein sequences are left untouched.
+pub struct ReverseComplementer {
+    alphabet: Alphabet,
+}
+
+impl ReverseComplementer {
+    pub fn new(alphabet: Alphabet) -> Self {
+        Self { alphabet }
+    }
+}
+
+impl SequenceProcessor for ReverseComplementer {
+    fn process(&self, sequences: &mut [Sequence]) -> Result<ProcessingResult> {
+        let start = Instant::now();
+        let mut modified = 0;
+        for seq in sequences
+            .iter_mut()
+            .filter(|s| !self.alphabet.is_protein(s))
+        {
+            seq.sequence.reverse();
+            for base in &mut seq.sequence {
+                *base = iupac_complement(*base);
+            }
+            modified += 1;
+        }
+        Ok(stage_result(sequences.len(), 0, modified, start))
+    }
+
+    fn name(&self) -> &str {
+        "ReverseComplementer"
+    }
+
+    fn supports_type(&self, seq_type: SequenceType) -> bool {
+        seq_type != SequenceType::Protein
+    }
+
+    fn config(&self) -> ProcessorConfig {
+        processor_config(self.name(), &[("alphabet", self.alphabet.name())])
+    }
+
+    fn estimate_time(&self, num_sequences: usize) -> Duration {
+        Duration::from_nanos(num_sequences as u64 * 200)
+    }
+}
+
+/// Drop sequences with too many ambiguous residues
+///
+/// Ambiguous means anything but `ACGTU` for nucleotides and `X`, `B`, `Z`,
+/// `J` for proteins.
+pub struct AmbiguityFilter {
+    max_fraction: f64,
+    alphabet: Alphabet,
+}
+
+impl AmbiguityFilter {
+    pub fn new(max_fraction: f64, alphabet: Alphabet) -> Self {
+        Self {
+            max_fraction,
+            alphabet,
+        }
+    }
+
+    fn ambiguous_fraction(&self, seq: &Sequence) -> f64 {
+        if seq.sequence.is_empty() {
+            return 0.0;
+        }
+        let protein = self.alphabet.is_protein(seq);
+        let ambiguous = seq
+            .sequence
+            .iter()
+            .map(u8::to_ascii_uppercase)
+            .filter(|residue| {
+                if protein {
+                    matches!(residue, b'X' | b'B' | b'Z' | b'J')
+                } else {
+                    !matches!(residue, b'A' | b'C' | b'G' | b'T' | b'U')
+                }
+            })
+            .count();
+        ambiguous as f64 / seq.sequence.len() as f64
+    }
+}
+
+impl SequenceProcessor for AmbiguityFilter {
+    fn process(&self, sequences: &mut [Sequence]) -> Result<ProcessingResult> {
+        let start = Instant::now();
+        let mut filtered = 0;
+        for seq in sequences.iter_mut() {
+            if self.ambiguous_fraction(seq) > self.max_fraction {
+                seq.sequence.clear();
+                filtered += 1;
+            }
+        }
+        Ok(stage_result(sequences.len(), filtered, 0, start))
+    }
+
+    fn name(&self) -> &str {
+        "AmbiguityFilter"
+    }
+
+    fn supports_type(&self, _seq_type: SequenceType) -> bool {
+        true
+    }
+
+    fn config(&self) -> ProcessorConfig {
+        processor_config(
+            self.name(),
+            &[
+                ("max_fraction", self.max_fraction.to_string()),
+                ("alphabet", self.alphabet.name()),
+            ],
+        )
+    }
+
+    fn estimate_time(&self, num_sequences: usize) -> Duration {
+        Duration::from_micros(num_sequences as u64)
+    }
+}
+
+/// Enforce length bounds, optionally derived from the length distribution
+///
+/// With `mad_factor`, sequences further than `mad_factor` robust standard
+/// deviations (1.4826 x MAD) from the median length are outliers. Fixed
+/// `min`/`max` bounds apply on top.
+pub struct LengthFilter {
+    min: Option<usize>,
+    max: Option<usize>,
+    mad_factor: Option<f64>,
+    action: LengthAction,
+}
+
+impl LengthFilter {
+    pub fn new(
+        min: Option<usize>,
+        max: Option<usize>,
+        mad_factor: Option<f64>,
+        action: LengthAction,
+    ) -> Self {
+        Self {
+            min,
+            max,
+            mad_factor,
+            action,
+        }
+    }
+
+    /// Inclusive (lower, upper) bounds for the current set
+    fn bounds(&self, sequences: &[Sequence]) -> (usize, usize) {
+        let mut lower = self.min.unwrap_or(0);
+        let mut upper = self.max.unwrap_or(usize::MAX);
+
+        if let Some(factor) = self.mad_factor {
+            let mut lengths: Vec<f64> = sequences
+                .iter()
+                .filter(|s| !s.sequence.is_empty())
+                .map(|s| s.sequence.len() as f64)
+                .collect();
+            if let Some(median) = median(&mut lengths) {
+                let mut deviations: Vec<f64> =
+                    lengths.iter().map(|len| (len - median).abs()).collect();
+                let mad = median_of(&mut deviations);
+                // All lengths (nearly) equal: nothing is an outlier
+                if mad > 0.0 {
+                    let spread = factor * 1.4826 * mad;
+                    lower = lower.max((median - spread).max(0.0).ceil() as usize);
+                    upper = upper.min((median + spread).floor() as usize);
+                }
+            }
+        }
+        (lower, upper)
+    }
+}
+
+fn median(values: &mut [f64]) -> Option<f64> {
+    (!values.is_empty()).then(|| median_of(values))
+}
+
+fn median_of(values: &mut [f64]) -> f64 {
+    values.sort_by(|a, b| a.total_cmp(b));
+    let mid = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    }
+}
+
+impl SequenceProcessor for LengthFilter {
+    fn process(&self, sequences: &mut [Sequence]) -> Result<ProcessingResult> {
+        let start = Instant::now();
+        let (lower, upper) = self.bounds(sequences);
+        let mut filtered = 0;
+        let mut modified = 0;
+
+        for seq in sequences.iter_mut().filter(|s| !s.sequence.is_empty()) {
+            let len = seq.sequence.len();
+            if len < lower || (len > upper && self.action == LengthAction::Drop) {
+                seq.sequence.clear();
+                filtered += 1;
+            } else if len > upper {
+                seq.sequence.truncate(upper);
+                modified += 1;
+            }
+        }
+        Ok(stage_result(sequences.len(), filtered, modified, start))
+    }
+
+    fn name(&self) -> &str {
+        "LengthFilter"
+    }
+
+    fn supports_type(&self, _seq_type: SequenceType) -> bool {
+        true
+    }
+
+    fn config(&self) -> ProcessorConfig {
+        let mut parameters = vec![("action", format!("{:?}", self.action).to_lowercase())];
+        if let Some(min) = self.min {
+            parameters.push(("min", min.to_string()));
+        }
+        if let Some(max) = self.max {
+            parameters.push(("max", max.to_string()));
+        }
+        if let Some(factor) = self.mad_factor {
+            parameters.push(("mad_factor", factor.to_string()));
+        }
+        processor_config(self.name(), &parameters)
+    }
+
+    fn estimate_time(&self, num_sequences: usize) -> Duration {
+        Duration::from_nanos(num_sequences as u64 * 100)
+    }
+}
+
+/// Handle `*` in protein sequences
+///
+/// Trailing stops are stripped when `strip_trailing` is set; the rest are
+/// internal and follow `internal`.
+pub struct StopCodonHandler {
+    internal: StopCodonAction,
+    strip_trailing: bool,
+    alphabet: Alphabet,
+}
+
+impl StopCodonHandler {
+    pub fn new(internal: StopCodonAction, strip_trailing: bool, alphabet: Alphabet) -> Self {
+        Self {
+            internal,
+            strip_trailing,
+            alphabet,
+        }
+    }
+}
+
+impl SequenceProcessor for StopCodonHandler {
+    fn process(&self, sequences: &mut [Sequence]) -> Result<ProcessingResult> {
+        let start = Instant::now();
+        let mut filtered = 0;
+        let mut modified = 0;
+
+        for seq in sequences.iter_mut().filter(|s| self.alphabet.is_protein(s)) {
+            let original_len = seq.sequence.len();
+            if self.strip_trailing {
+                while seq.sequence.last() == Some(&b'*') {
+                    seq.sequence.pop();
+                }
+            }
+            let trailing = seq
+                .sequence
+                .iter()
+                .rev()
+                .take_while(|&&r| r == b'*')
+                .count();
+            let internal_end = seq.sequence.len() - trailing;
+            let has_internal = seq.sequence[..internal_end].contains(&b'*');
+
+            match self.internal {
+                StopCodonAction::Drop if has_internal => {
+                    seq.sequence.clear();
+                    filtered += 1;
+                    continue;
+                }
+                StopCodonAction::Mask if has_internal => {
+                    for residue in seq.sequence[..internal_end]
+                        .iter_mut()
+                        .filter(|r| **r == b'*')
+                    {
+                        *residue = b'X';
+                    }
+                    modified += 1;
+                    continue;
+                }
+                _ => {}
+            }
+            if seq.sequence.len() != original_len {
+                modified += 1;
+            }
+        }
+        Ok(stage_result(sequences.len(), filtered, modified, start))
+    }
+
+    fn name(&self) -> &str {
+        "StopCodonHandler"
+    }
+
+    fn supports_type(&self, seq_type: SequenceType) -> bool {
+        seq_type == SequenceType::Protein
+    }
+
+    fn config(&self) -> ProcessorConfig {
+        processor_config(
+            self.name(),
+            &[
+                ("internal", format!("{:?}", self.internal).to_lowercase()),
+                ("strip_trailing", self.strip_trailing.to_string()),
+                ("alphabet", self.alphabet.name()),
+            ],
+        )
+    }
+
+    fn estimate_time(&self, num_sequences: usize) -> Duration {
+        Duration::from_nanos(num_sequences as u64 * 100)
+    }
+}
+
+/// Replace selenocysteine (`U`) in protein sequences
+///
+/// Many aligners reject `U`; `C` is the usual stand-in.
+pub struct SelenocysteineHandler {
+    replacement: u8,
+    alphabet: Alphabet,
+}
+
+impl SelenocysteineHandler {
+    pub fn new(replacement: u8, alphabet: Alphabet) -> Self {
+        Self {
+            replacement,
+            alphabet,
+        }
+    }
+}
+
+impl SequenceProcessor for SelenocysteineHandler {
+    fn process(&self, sequences: &mut [Sequence]) -> Result<ProcessingResult> {
+        let start = Instant::now();
+        let mut modified = 0;
+        for seq in sequences.iter_mut().filter(|s| self.alphabet.is_protein(s)) {
+            let mut changed = false;
+            for residue in seq.sequence.iter_mut() {
+                match *residue {
+                    b'U' => *residue = self.replacement.to_ascii_uppercase(),
+                    b'u' => *residue = self.replacement.to_ascii_lowercase(),
+                    _ => continue,
+                }
+                changed = true;
+            }
+            if changed {
+                modified += 1;
+            }
+        }
+        Ok(stage_result(sequences.len(), 0, modified, start))
+    }
+
+    fn name(&self) -> &str {
+        "SelenocysteineHandler"
+    }
+
+    fn supports_type(&self, seq_type: SequenceType) -> bool {
+        seq_type == SequenceType::Protein
+    }
+
+    fn config(&self) -> ProcessorConfig {
+        processor_config(
+            self.name(),
+            &[
+                ("replacement", (self.replacement as char).to_string()),
+                ("alphabet", self.alphabet.name()),
+            ],
+        )
+    }
+
+    fn estimate_time(&self, num_sequences: usize) -> Duration {
+        Duration::from_nanos(num_sequences as u64 * 100)
+    }
+}
+
+/// Rewrite FASTA headers with a regular expression
+///
+/// `replacement` uses `regex` syntax (`$1`, `${name}`); every match is
+/// replaced.
+pub struct HeaderRewriter {
+    pattern: Regex,
+    replacement: String,
+    target: HeaderTarget,
+}
+
+impl HeaderRewriter {
+    pub fn new(pattern: Regex, replacement: String, target: HeaderTarget) -> Self {
+        Self {
+            pattern,
+            replacement,
+            target,
+        }
+    }
+
+    fn rewrite(&self, text: &str) -> String {
+        self.pattern
+            .replace_all(text, self.replacement.as_str())
+            .into_owned()
+    }
+}
+
+impl SequenceProcessor for HeaderRewriter {
+    fn process(&self, sequences: &mut [Sequence]) -> Result<ProcessingResult> {
+        let start = Instant::now();
+        let mut modified = 0;
+
+        for seq in sequences.iter_mut() {
+            let (id, description) = match self.target {
+                HeaderTarget::Id => (self.rewrite(&seq.id), seq.description.clone()),
+                HeaderTarget::Description => (
+                    seq.id.clone(),
+                    seq.description.as_deref().map(|d| self.rewrite(d)),
+                ),
+                HeaderTarget::Header => {
+                    let header = match &seq.description {
+                        Some(description) => format!("{} {}", seq.id, description),
+                        None => seq.id.clone(),
+                    };
+                    let rewritten = self.rewrite(&header);
+                    let rewritten = rewritten.trim();
+                    match rewritten.split_once(char::is_whitespace) {
+                        Some((id, description)) => {
+                            (id.to_string(), Some(description.trim_start().to_string()))
+                        }
+                        None => (rewritten.to_string(), None),
+                    }
+                }
+            };
+            if id.is_empty() {
+                anyhow::bail!("Header rewrite left sequence '{}' without an id", seq.id);
+            }
+            let description = description.filter(|d| !d.is_empty());
+            if id != seq.id || description != seq.description {
+                seq.id = id;
+                seq.description = description;
+                modified += 1;
+            }
+        }
+        Ok(stage_result(sequences.len(), 0, modified, start))
+    }
+
+    fn name(&self) -> &str {
+        "HeaderRewriter"
+    }
+
+    fn supports_type(&self, _seq_type: SequenceType) -> bool {
+        true
+    }
+
+    fn config(&self) -> ProcessorConfig {
+        processor_config(
+            self.name(),
+            &[
+                ("pattern", self.pattern.as_str().to_string()),
+                ("replacement", self.replacement.clone()),
+                ("target", format!("{:?}", self.target).to_lowercase()),
+            ],
+        )
+    }
+
+    fn estimate_time(&self, num_sequences: usize) -> Duration {
+        Duration::from_micros(num_sequences as u64)
+    }
+}
+
+/// Keep only the first of each set of identical sequences
+pub struct ExactDeduplicator {
+    ignore_case: bool,
+}
+
+impl ExactDeduplicator {
+    pub fn new(ignore_case: bool) -> Self {
+        Self { ignore_case }
+    }
+}
+
+impl SequenceProcessor for ExactDeduplicator {
+    fn process(&self, sequences: &mut [Sequence]) -> Result<ProcessingResult> {
+        let start = Instant::now();
+        let mut seen: HashSet<[u8; 32]> = HashSet::with_capacity(sequences.len());
+        let mut filtered = 0;
+
+        for seq in sequences.iter_mut().filter(|s| !s.sequence.is_empty()) {
+            let mut hasher = Sha256::new();
+            if self.ignore_case {
+                hasher.update(seq.sequence.to_ascii_uppercase());
+            } else {
+                hasher.update(&seq.sequence);
+            }
+            if !seen.insert(hasher.finalize().into()) {
+                seq.sequence.clear();
+                filtered += 1;
+            }
+        }
+        Ok(stage_result(sequences.len(), filtered, 0, start))
+    }
+
+    fn name(&self) -> &str {
+        "ExactDeduplicator"
+    }
+
+    fn supports_type(&self, _seq_type: SequenceType) -> bool {
+        true
+    }
+
+    fn config(&self) -> ProcessorConfig {
+        let parameters: HashMap<String, String> =
+            HashMap::from([("ignore_case".to_string(), self.ignore_case.to_string())]);
+        ProcessorConfig {
+            name: self.name().to_string(),
+            parallel: false,
+            parameters,
+            ..ProcessorConfig::default()
+        }
+    }
+
+    fn estimate_time(&self, num_sequences: usize) -> Duration {
+        Duration::from_micros(num_sequences as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seq(id: &str, residues: &str) -> Sequence {
+        Sequence::new(id.to_string(), residues.as_bytes().to_vec())
+    }
+
+    #[test]
+    fn test_iupac_reverse_complement() {
+        let mut sequences = vec![seq("n1", "ACGTRYKMBVDHNSWacgtn")];
+        let result = ReverseComplementer::new(Alphabet::Auto)
+            .process(&mut sequences)
+            .unwrap();
+        assert_eq!(result.modified, 1);
+        assert_eq!(sequences[0].sequence, b"nacgtWSNDHBVKMRYACGT");
+    }
+
+    #[test]
+    fn test_seg_masks_low_complexity_protein() {
+        let protein = "MKTAYIAKQRQISFVKSHFSRQQQQQQQQQQQQQQQQQQLEERLGLIEVQAPILSRVGDGTQDNLSGAEKAVQVKVKALPDAQFEVVHSLAKWKRQTLGQHDFSAGEGLYTHMKALRPDEDRLSPLHSVYVDQWDWERVMGDGERQFSTLKSTVEAIWAGIKATEAAVSEEFGLAPFLPDQIHFVHSQELLSRYPDLDAKGRERAIAKDLGAVFLVGIGGKLSDGHRHDVRAPDYDDWUAQGSTK";
+        let mut sequences = vec![seq("p1", protein)];
+        let seg = SegMasker::new(12, 2.2, 2.5, MaskStyle::Hard, Alphabet::Auto);
+        seg.process(&mut sequences).unwrap();
+
+        let masked = String::from_utf8(sequences[0].sequence.clone()).unwrap();
+        let start = protein.find("QQQQ").unwrap();
+        assert!(masked[start..start + 18].bytes().all(|r| r == b'X'));
+        assert!(masked.starts_with("MKTAYIAK"));
+        assert!(masked.ends_with("QGSTK"));
+    }
+
+    #[test]
+    fn test_dust_masks_simple_repeats() {
+        let random = "ACGTTGCAAGCTTCGATCGGATCCTAGCTAGGCTAACGTTCAGTCAGGCATGCAATCGTAGCTGACTG";
+        let repeat = "A".repeat(80);
+        let mut sequences = vec![seq("d1", &format!("{}{}{}", random, repeat, random))];
+        DustMasker::new(64, 20.0, MaskStyle::Soft, Alphabet::Auto)
+            .process(&mut sequences)
+            .unwrap();
+
+        let masked = &sequences[0].sequence;
+        let repeat_start = random.len();
+        assert!(masked[repeat_start..repeat_start + 80]
+            .iter()
+            .all(|&b| b == b'a'));
+        assert_eq!(&masked[..10], &random.as_bytes()[..10]);
+    }
+
+    #[test]
+    fn test_length_outliers_trimmed() {
+        let mut sequences: Vec<Sequence> = (0..9)
+            .map(|i| seq(&format!("s{}", i), &"MKV".repeat(30 + i)))
+            .collect();
+        sequences.push(seq("long", &"MKV".repeat(300)));
+        sequences.push(seq("short", "MKV"));
+
+        let filter = LengthFilter::new(None, None, Some(3.0), LengthAction::Trim);
+        let (lower, upper) = filter.bounds(&sequences);
+        let result = filter.process(&mut sequences).unwrap();
+
+        assert_eq!(result.filtered, 1);
+        assert_eq!(result.modified, 1);
+        assert!(sequences[10].sequence.is_empty());
+        assert_eq!(sequences[9].sequence.len(), upper);
+        assert!(lower > 3 && upper < 900);
+    }
+
+    #[test]
+    fn test_stop_codons_and_selenocysteine() {
+        let mut sequences = vec![
+            seq("trailing", "MKVLIEF**"),
+            seq("internal", "MKV*LIEF*"),
+            seq("seleno", "MKVULIEF"),
+        ];
+        StopCodonHandler::new(StopCodonAction::Mask, true, Alphabet::Auto)
+            .process(&mut sequences)
+            .unwrap();
+        assert_eq!(sequences[0].sequence, b"MKVLIEF");
+        assert_eq!(sequences[1].sequence, b"MKVXLIEF");
+
+        let result = SelenocysteineHandler::new(b'C', Alphabet::Auto)
+            .process(&mut sequences)
+            .unwrap();
+        assert_eq!(result.modified, 1);
+        assert_eq!(sequences[2].sequence, b"MKVCLIEF");
+
+        let mut sequences = vec![seq("internal", "MKV*LIEF")];
+        let result = StopCodonHandler::new(StopCodonAction::Drop, false, Alphabet::Auto)
+            .process(&mut sequences)
+            .unwrap();
+        assert_eq!(result.filtered, 1);
+    }
+
+    #[test]
+    fn test_forced_alphabet() {
+        // A peptide made only of nucleotide letters is detected as RNA
+        let peptide = || vec![seq("pep", "GACUAGCU")];
+
+        let mut sequences = peptide();
+        SelenocysteineHandler::new(b'C', Alphabet::Auto)
+            .process(&mut sequences)
+            .unwrap();
+        assert_eq!(sequences[0].sequence, b"GACUAGCU");
+
+        let mut sequences = peptide();
+        SelenocysteineHandler::new(b'C', Alphabet::Protein)
+            .process(&mut sequences)
+            .unwrap();
+        assert_eq!(sequences[0].sequence, b"GACCAGCC");
+
+        let mut sequences = peptide();
+        let result = ReverseComplementer::new(Alphabet::Protein)
+            .process(&mut sequences)
+            .unwrap();
+        assert_eq!(result.modified, 0);
+        assert_eq!(sequences[0].sequence, b"GACUAGCU");
+    }
+
+    #[test]
+    fn test_ambiguity_filter_and_dedup() {
+        let mut sequences = vec![
+            seq("a", "ACGTNNNNAC"),
+            seq("b", "ACGTACGTAC"),
+            seq("c", "acgtacgtac"),
+            seq("p", "MKXXLIEFQP"),
+        ];
+        let result = AmbiguityFilter::new(0.1, Alphabet::Auto)
+            .process(&mut sequences)
+            .unwrap();
+        assert_eq!(result.filtered, 2);
+        assert!(sequences[0].sequence.is_empty());
+        assert!(sequences[3].sequence.is_empty());
+
+        let result = ExactDeduplicator::new(true)
+            .process(&mut sequences)
+            .unwrap();
+        assert_eq!(result.filtered, 1);
+        assert!(!sequences[1].sequence.is_empty());
+        assert!(sequences[2].sequence.is_empty());
+    }
+
+    #[test]
+    fn test_header_rewrite() {
+        let mut sequences =
+            vec![seq("sp|P12345|ALBU_HUMAN", "MKV")
+                .with_description("Albumin OS=Homo sapiens".into())];
+        let rewriter = HeaderRewriter::new(
+            Regex::new(r"^sp\|(\w+)\|\w+ (.*) OS=.*$").unwrap(),
+            "$1 $2".to_string(),
+            HeaderTarget::Header,
+        );
+        let result = rewriter.process(&mut sequences).unwrap();
+        assert_eq!(result.modified, 1);
+        assert_eq!(sequences[0].id, "P12345");
+        assert_eq!(sequences[0].description.as_deref(), Some("Albumin"));
+
+        let blank = HeaderRewriter::new(Regex::new(".*").unwrap(), String::new(), HeaderTarget::Id);
+        assert!(blank.process(&mut sequences).is_err());
+    }
+}
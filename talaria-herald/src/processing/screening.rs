@@ -0,0 +1,527 @@
+/// Contaminant screening against a local reference panel
+///
+/// A panel (UniVec, adapter and PhiX sequences, or any FASTA or HERALD
+/// database of known contaminants) is indexed as k-mers. Nucleotide k-mers
+/// are canonical, so hits on either strand count; protein panels are indexed
+/// separately with a shorter k. A sequence sharing at least `min_kmers`
+/// k-mers with the panel is flagged, trimmed or excluded, and every hit is
+/// written to the pipeline's `ContaminationReport`.
+use super::processors::Alphabet;
+use super::traits::{ProcessingResult, ProcessorConfig, SequenceProcessor, SequenceType};
+use crate::types::SHA256Hash;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use talaria_bio::sequence::Sequence;
+
+/// What to do with a sequence that matches the panel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScreenAction {
+    /// Keep it and add `contaminant=<panel entry>` to the description
+    Flag,
+    /// Cut matches at either end; sequences with internal matches are excluded
+    Trim,
+    #[default]
+    Exclude,
+}
+
+impl std::fmt::Display for ScreenAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(match self {
+            ScreenAction::Flag => "flagged",
+            ScreenAction::Trim => "trimmed",
+            ScreenAction::Exclude => "excluded",
+        })
+    }
+}
+
+/// One screened sequence that matched the panel
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContaminationFinding {
+    pub sequence_id: String,
+    /// Panel the match came from
+    pub panel: String,
+    /// Panel sequence sharing the most k-mers
+    pub contaminant: String,
+    pub matched_kmers: usize,
+    /// Matched regions as 0-based, end-exclusive positions in the input
+    pub regions: Vec<(usize, usize)>,
+    pub sequence_length: usize,
+    /// Fraction of the sequence covered by matches
+    pub confidence: f32,
+    /// Action actually taken (a failed trim becomes an exclusion)
+    pub action: ScreenAction,
+    pub detection_date: DateTime<Utc>,
+}
+
+/// A panel as recorded alongside its findings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PanelSummary {
+    pub name: String,
+    pub sequences: usize,
+    pub kmers: usize,
+    /// SHA-256 over the panel's residues, to tell panel revisions apart
+    pub sha256: String,
+}
+
+/// Screening results of one pipeline run
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ContaminationReport {
+    pub panels: Vec<PanelSummary>,
+    pub findings: Vec<ContaminationFinding>,
+}
+
+impl ContaminationReport {
+    /// Number of findings that ended in `action`
+    pub fn count(&self, action: ScreenAction) -> usize {
+        self.findings.iter().filter(|f| f.action == action).count()
+    }
+}
+
+/// Shared by the screeners of one pipeline
+pub type ScreeningLog = Arc<Mutex<ContaminationReport>>;
+
+/// K-mer index of contaminant sequences
+pub struct ContaminantPanel {
+    name: String,
+    k: usize,
+    protein_k: usize,
+    alphabet: Alphabet,
+    nucleotide_kmers: HashMap<u64, u32>,
+    protein_kmers: HashMap<u64, u32>,
+    entries: Vec<String>,
+    sha256: String,
+}
+
+impl ContaminantPanel {
+    /// Index `sequences` with `k` for nucleotides and `protein_k` for proteins
+    ///
+    /// `alphabet` decides the molecule type of panel and screened sequences.
+    pub fn build(
+        name: &str,
+        sequences: &[Sequence],
+        k: usize,
+        protein_k: usize,
+        alphabet: Alphabet,
+    ) -> Result<Self> {
+        if !(11..=32).contains(&k) {
+            anyhow::bail!("screen k must be within 11-32, got {}", k);
+        }
+        if !(4..=12).contains(&protein_k) {
+            anyhow::bail!("screen protein_k must be within 4-12, got {}", protein_k);
+        }
+        if sequences.is_empty() {
+            anyhow::bail!("Contaminant panel '{}' has no sequences", name);
+        }
+
+        let mut panel = Self {
+            name: name.to_string(),
+            k,
+            protein_k,
+            alphabet,
+            nucleotide_kmers: HashMap::new(),
+            protein_kmers: HashMap::new(),
+            entries: Vec::with_capacity(sequences.len()),
+            sha256: String::new(),
+        };
+        let mut hasher = Sha256::new();
+        for (index, seq) in sequences.iter().enumerate() {
+            hasher.update(&seq.sequence);
+            hasher.update(b"\n");
+            let protein = alphabet.is_protein(seq);
+            let kmers = if protein {
+                &mut panel.protein_kmers
+            } else {
+                &mut panel.nucleotide_kmers
+            };
+            for (_, code) in kmer_codes(&seq.sequence, if protein { protein_k } else { k }, protein)
+            {
+                kmers.entry(code).or_insert(index as u32);
+            }
+            panel.entries.push(seq.id.clone());
+        }
+        panel.sha256 = SHA256Hash(hasher.finalize().into()).to_hex();
+        Ok(panel)
+    }
+
+    pub fn summary(&self) -> PanelSummary {
+        PanelSummary {
+            name: self.name.clone(),
+            sequences: self.entries.len(),
+            kmers: self.nucleotide_kmers.len() + self.protein_kmers.len(),
+            sha256: self.sha256.clone(),
+        }
+    }
+
+    /// Start positions of panel k-mers in `seq`, with the panel entry each came from
+    fn hits(&self, seq: &Sequence) -> (usize, Vec<(usize, u32)>) {
+        let protein = self.alphabet.is_protein(seq);
+        let (k, kmers) = if protein {
+            (self.protein_k, &self.protein_kmers)
+        } else {
+            (self.k, &self.nucleotide_kmers)
+        };
+        let hits = kmer_codes(&seq.sequence, k, protein)
+            .into_iter()
+            .filter_map(|(position, code)| kmers.get(&code).map(|&entry| (position, entry)))
+            .collect();
+        (k, hits)
+    }
+}
+
+fn base_code(base: u8) -> Option<u64> {
+    match base.to_ascii_uppercase() {
+        b'A' => Some(0),
+        b'C' => Some(1),
+        b'G' => Some(2),
+        b'T' | b'U' => Some(3),
+        _ => None,
+    }
+}
+
+fn residue_code(residue: u8) -> Option<u64> {
+    match residue.to_ascii_uppercase() {
+        b'X' => None,
+        r @ b'A'..=b'Z' => Some((r - b'A') as u64),
+        _ => None,
+    }
+}
+
+/// Rolling k-mer codes with their start positions
+///
+/// Nucleotide k-mers take 2 bits per base and are canonical (the smaller of
+/// the k-mer and its reverse complement); protein k-mers take 5 bits per
+/// residue. Windows with ambiguous residues are skipped.
+fn kmer_codes(sequence: &[u8], k: usize, protein: bool) -> Vec<(usize, u64)> {
+    let bits = if protein { 5 } else { 2 };
+    let mask = if bits * k >= 64 {
+        u64::MAX
+    } else {
+        (1u64 << (bits * k)) - 1
+    };
+    let mut codes = Vec::with_capacity((sequence.len() + 1).saturating_sub(k));
+    let (mut forward, mut reverse, mut run) = (0u64, 0u64, 0usize);
+
+    for (position, &residue) in sequence.iter().enumerate() {
+        let code = if protein {
+            residue_code(residue)
+        } else {
+            base_code(residue)
+        };
+        let Some(code) = code else {
+            run = 0;
+            continue;
+        };
+        forward = ((forward << bits) | code) & mask;
+        if !protein {
+            reverse = (reverse >> 2) | ((3 - code) << (2 * (k - 1)));
+        }
+        run += 1;
+        if run >= k {
+            let code = if protein {
+                forward
+            } else {
+                forward.min(reverse)
+            };
+            codes.push((position + 1 - k, code));
+        }
+    }
+    codes
+}
+
+/// Part of a sequence left after cutting matches at its ends
+///
+/// Matches starting or ending within `tolerance` of an end are cut; any
+/// other match is internal and makes the sequence untrimmable.
+fn trim_bounds(
+    regions: &[(usize, usize)],
+    length: usize,
+    tolerance: usize,
+) -> Option<(usize, usize)> {
+    let (mut start, mut end) = (0, length);
+    for &(region_start, region_end) in regions {
+        if region_start <= tolerance {
+            start = start.max(region_end);
+        } else if region_end + tolerance >= length {
+            end = end.min(region_start);
+        } else {
+            return None;
+        }
+    }
+    (start < end).then_some((start, end))
+}
+
+/// Screens sequences against a contaminant panel
+pub struct ContaminantScreener {
+    panel: ContaminantPanel,
+    min_kmers: usize,
+    action: ScreenAction,
+    /// Trimmed sequences shorter than this are excluded
+    min_length: usize,
+    log: ScreeningLog,
+}
+
+impl ContaminantScreener {
+    pub fn new(
+        panel: ContaminantPanel,
+        min_kmers: usize,
+        action: ScreenAction,
+        min_length: usize,
+        log: ScreeningLog,
+    ) -> Self {
+        if let Ok(mut report) = log.lock() {
+            report.panels.push(panel.summary());
+        }
+        Self {
+            panel,
+            min_kmers: min_kmers.max(1),
+            action,
+            min_length,
+            log,
+        }
+    }
+}
+
+impl SequenceProcessor for ContaminantScreener {
+    fn process(&self, sequences: &mut [Sequence]) -> Result<ProcessingResult> {
+        let start = Instant::now();
+        let (mut filtered, mut modified) = (0, 0);
+        let mut findings = Vec::new();
+
+        for seq in sequences.iter_mut().filter(|s| !s.sequence.is_empty()) {
+            let (k, hits) = self.panel.hits(seq);
+            if hits.len() < self.min_kmers {
+                continue;
+            }
+
+            let mut regions: Vec<(usize, usize)> = Vec::new();
+            let mut counts: HashMap<u32, usize> = HashMap::new();
+            for &(position, entry) in &hits {
+                *counts.entry(entry).or_default() += 1;
+                match regions.last_mut() {
+                    Some(last) if position <= last.1 => last.1 = position + k,
+                    _ => regions.push((position, position + k)),
+                }
+            }
+            let (&best, _) = counts
+                .iter()
+                .max_by_key(|&(entry, count)| (*count, std::cmp::Reverse(*entry)))
+                .expect("at least one hit");
+            let contaminant = self.panel.entries[best as usize].clone();
+            let length = seq.sequence.len();
+            let covered: usize = regions.iter().map(|(s, e)| e - s).sum();
+
+            let trimmed = match self.action {
+                ScreenAction::Trim => trim_bounds(&regions, length, k)
+                    .filter(|(s, e)| e - s >= self.min_length.max(1)),
+                _ => None,
+            };
+            let action = match (self.action, trimmed) {
+                (ScreenAction::Flag, _) => {
+                    let tag = format!("contaminant={}", contaminant);
+                    seq.description = Some(match seq.description.take() {
+                        Some(description) => format!("{} {}", description, tag),
+                        None => tag,
+                    });
+                    modified += 1;
+                    ScreenAction::Flag
+                }
+                (ScreenAction::Trim, Some((s, e))) => {
+                    seq.sequence = seq.sequence[s..e].to_vec();
+                    modified += 1;
+                    ScreenAction::Trim
+                }
+                _ => {
+                    seq.sequence.clear();
+                    filtered += 1;
+                    ScreenAction::Exclude
+                }
+            };
+
+            findings.push(ContaminationFinding {
+                sequence_id: seq.id.clone(),
+                panel: self.panel.name.clone(),
+                contaminant,
+                matched_kmers: hits.len(),
+                regions,
+                sequence_length: length,
+                confidence: covered as f32 / length as f32,
+                action,
+                detection_date: Utc::now(),
+            });
+        }
+
+        if let Ok(mut report) = self.log.lock() {
+            report.findings.extend(findings);
+        }
+        Ok(ProcessingResult {
+            processed: sequences.len(),
+            filtered,
+            modified,
+            errors: Vec::new(),
+            processing_time: start.elapsed(),
+        })
+    }
+
+    fn name(&self) -> &str {
+        "ContaminantScreener"
+    }
+
+    fn supports_type(&self, _seq_type: SequenceType) -> bool {
+        true
+    }
+
+    fn config(&self) -> ProcessorConfig {
+        let parameters: HashMap<String, String> = HashMap::from([
+            ("panel".to_string(), self.panel.name.clone()),
+            ("k".to_string(), self.panel.k.to_string()),
+            ("protein_k".to_string(), self.panel.protein_k.to_string()),
+            ("min_kmers".to_string(), self.min_kmers.to_string()),
+            (
+                "action".to_string(),
+                format!("{:?}", self.action).to_lowercase(),
+            ),
+            (
+                "alphabet".to_string(),
+                format!("{:?}", self.panel.alphabet).to_lowercase(),
+            ),
+        ]);
+        ProcessorConfig {
+            name: self.name().to_string(),
+            parameters,
+            ..ProcessorConfig::default()
+        }
+    }
+
+    fn estimate_time(&self, num_sequences: usize) -> Duration {
+        Duration::from_micros(num_sequences as u64 * 20)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Illumina TruSeq adapter and a stretch of PhiX
+    const ADAPTER: &str = "AGATCGGAAGAGCACACGTCTGAACTCCAGTCAC";
+    const PHIX: &str = "GAGTTTTATCGCTTCCATGACGCAGAAGTTAACACTTTCGGATATTTCTGATGAGTCGAAAAATTATCTTGATAAAGCAGGAATTACTACTGCTTGTTTACGAATTAAATCGAAGTGGACTGCTGGCGG";
+    const HOST: &str = "ATGGCTAGCAAAGGAGAAGAACTTTTCACTGGAGTTGTCCCAATTCTTGTTGAATTAGATGGTGATGTTAATGGGCACAAATTTTCTGTCAGTGGAGAGGGTGAAGGTGATGC";
+
+    fn seq(id: &str, residues: &str) -> Sequence {
+        Sequence::new(id.to_string(), residues.as_bytes().to_vec())
+    }
+
+    fn reverse_complement(residues: &str) -> String {
+        residues
+            .bytes()
+            .rev()
+            .map(|b| super::super::processors::iupac_complement(b) as char)
+            .collect()
+    }
+
+    fn screener(action: ScreenAction) -> (ContaminantScreener, ScreeningLog) {
+        let panel = vec![seq("adapter", ADAPTER), seq("phix", PHIX)];
+        let panel = ContaminantPanel::build("univec", &panel, 21, 8, Alphabet::Auto).unwrap();
+        let log = ScreeningLog::default();
+        (
+            ContaminantScreener::new(panel, 3, action, 30, Arc::clone(&log)),
+            log,
+        )
+    }
+
+    #[test]
+    fn test_canonical_kmers_match_both_strands() {
+        let forward = kmer_codes(PHIX.as_bytes(), 21, false);
+        let reverse = kmer_codes(reverse_complement(PHIX).as_bytes(), 21, false);
+        assert_eq!(forward.len(), PHIX.len() - 20);
+        let mut a: Vec<u64> = forward.into_iter().map(|(_, c)| c).collect();
+        let mut b: Vec<u64> = reverse.into_iter().map(|(_, c)| c).collect();
+        a.sort();
+        b.sort();
+        assert_eq!(a, b);
+
+        // Ambiguous bases break the window
+        assert_eq!(kmer_codes(b"ACGTNACGT", 4, false).len(), 2);
+    }
+
+    #[test]
+    fn test_exclude_and_report() {
+        let (screener, log) = screener(ScreenAction::Exclude);
+        let mut sequences = vec![
+            seq("clean", HOST),
+            seq("phix_rc", &reverse_complement(PHIX)),
+        ];
+        let result = screener.process(&mut sequences).unwrap();
+        assert_eq!(result.filtered, 1);
+        assert!(sequences[1].sequence.is_empty());
+
+        let report = log.lock().unwrap();
+        assert_eq!(report.panels[0].sequences, 2);
+        assert_eq!(report.findings.len(), 1);
+        assert_eq!(report.findings[0].sequence_id, "phix_rc");
+        assert_eq!(report.findings[0].contaminant, "phix");
+        assert!(report.findings[0].confidence > 0.99);
+    }
+
+    #[test]
+    fn test_trim_terminal_adapter() {
+        let (screener, log) = screener(ScreenAction::Trim);
+        let mut sequences = vec![
+            seq("read", &format!("{}{}", HOST, ADAPTER)),
+            seq("chimera", &format!("{}{}{}", HOST, PHIX, HOST)),
+        ];
+        let result = screener.process(&mut sequences).unwrap();
+        assert_eq!((result.modified, result.filtered), (1, 1));
+        assert_eq!(sequences[0].sequence, HOST.as_bytes());
+        assert!(sequences[1].sequence.is_empty());
+
+        let report = log.lock().unwrap();
+        assert_eq!(report.count(ScreenAction::Trim), 1);
+        assert_eq!(report.count(ScreenAction::Exclude), 1);
+        assert_eq!(
+            report.findings[0].regions,
+            vec![(HOST.len(), HOST.len() + ADAPTER.len())]
+        );
+    }
+
+    #[test]
+    fn test_flag_keeps_sequence() {
+        let (screener, _) = screener(ScreenAction::Flag);
+        let mut sequences = vec![seq("read", &format!("{}{}", ADAPTER, HOST))];
+        let result = screener.process(&mut sequences).unwrap();
+        assert_eq!(result.modified, 1);
+        assert_eq!(sequences[0].len(), ADAPTER.len() + HOST.len());
+        assert_eq!(
+            sequences[0].description.as_deref(),
+            Some("contaminant=adapter")
+        );
+    }
+
+    #[test]
+    fn test_protein_panel_and_validation() {
+        let vector_protein = "MSKGEELFTGVVPILVELDGDVNGHKFSVSGEGEGDATYGKLTLKFICTTGKLPVPWPTLVTTFSYGVQCFSRYPDHMKQHDFFKSAMPEGYVQERTIFFKDDGNYK";
+        let panel =
+            ContaminantPanel::build("gfp", &[seq("gfp", vector_protein)], 21, 8, Alphabet::Auto)
+                .unwrap();
+        let log = ScreeningLog::default();
+        let screener = ContaminantScreener::new(panel, 3, ScreenAction::Exclude, 0, log);
+        let mut sequences = vec![
+            seq("fusion", &format!("MHHHHHH{}", &vector_protein[..40])),
+            seq(
+                "host",
+                "MKTAYIAKQRQISFVKSHFSRQLEERLGLIEVQAPILSRVGDGTQDNLSGAEK",
+            ),
+        ];
+        let result = screener.process(&mut sequences).unwrap();
+        assert_eq!(result.filtered, 1);
+        assert!(sequences[0].sequence.is_empty());
+
+        assert!(ContaminantPanel::build("x", &[seq("a", ADAPTER)], 40, 8, Alphabet::Auto).is_err());
+        assert!(ContaminantPanel::build("x", &[], 21, 8, Alphabet::Auto).is_err());
+    }
+}
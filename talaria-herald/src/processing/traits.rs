@@ -319,6 +319,8 @@ pub struct StageResult {
     pub success: bool,
     pub time_ms: u64,
     pub sequences_processed: usize,
+    pub sequences_filtered: usize,
+    pub sequences_modified: usize,
     pub errors: Vec<String>,
 }
 
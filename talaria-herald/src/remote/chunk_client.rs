@@ -108,8 +108,7 @@ impl ChunkClient {
             match self.download_with_protocol(&url).await {
                 Ok(data) => {
                     // Verify the downloaded data matches the expected hash
-                    let computed_hash = SHA256Hash::compute(&data);
-                    if computed_hash != *hash {
+                    if !verify_chunk_data(hash, &data) {
                         return Err(ChunkDownloadError::InvalidData.into());
                     }
                     return Ok(data);
@@ -179,12 +178,11 @@ impl ChunkClient {
 
     /// Build URL for a specific chunk
     fn build_chunk_url(&self, hash: &SHA256Hash) -> Result<String> {
-        let hash_str = hash.to_hex(); // Use full hex, not truncated Display
-
-        // Use first 2 chars as prefix for sharding (like git)
-        let prefix = &hash_str[..2];
-        let suffix = &hash_str[2..];
+        self.build_url(&remote_chunk_key(hash))
+    }
 
+    /// Build URL for a path relative to the remote root
+    fn build_url(&self, relative: &str) -> Result<String> {
         match self.protocol {
             Protocol::S3 => {
                 // s3://bucket/path -> https://bucket.s3.amazonaws.com/path
@@ -196,35 +194,73 @@ impl ChunkClient {
                 let bucket = parts[0];
                 let path = parts[1];
                 Ok(format!(
-                    "https://{}.s3.amazonaws.com/{}/chunks/{}/{}",
-                    bucket, path, prefix, suffix
+                    "https://{}.s3.amazonaws.com/{}/{}",
+                    bucket, path, relative
                 ))
             }
             Protocol::GCS => {
                 // gs://bucket/path -> https://storage.googleapis.com/bucket/path
                 let url = self.base_url.replace("gs://", "");
                 Ok(format!(
-                    "https://storage.googleapis.com/{}/chunks/{}/{}",
-                    url, prefix, suffix
+                    "https://storage.googleapis.com/{}/{}",
+                    url, relative
                 ))
             }
             Protocol::Azure => {
                 // azure://account.blob.core.windows.net/container/path
                 let url = self.base_url.replace("azure://", "https://");
-                Ok(format!("{}/chunks/{}/{}", url, prefix, suffix))
+                Ok(format!("{}/{}", url, relative))
             }
             Protocol::Http | Protocol::Https => {
                 // Direct HTTP(S) URL
-                Ok(format!("{}/chunks/{}/{}", self.base_url, prefix, suffix))
+                Ok(format!("{}/{}", self.base_url, relative))
             }
             Protocol::File => {
                 // Local filesystem (for testing)
                 let path = self.base_url.replace("file://", "");
-                Ok(format!("{}/chunks/{}/{}", path, prefix, suffix))
+                Ok(format!("{}/{}", path, relative))
+            }
+        }
+    }
+
+    /// Download a file stored next to the chunks, e.g. the published manifests
+    pub async fn download_file(&self, relative: &str) -> Result<Vec<u8>> {
+        let url = self.build_url(relative)?;
+        self.download_with_protocol(&url).await
+    }
+
+    /// Whether the remote holds a chunk, without downloading it
+    pub async fn chunk_exists(&self, hash: &SHA256Hash) -> Result<bool> {
+        let url = self.build_chunk_url(hash)?;
+        match self.protocol {
+            Protocol::File => Ok(std::path::Path::new(&url).is_file()),
+            _ => {
+                let response = self
+                    .client
+                    .head(&url)
+                    .send()
+                    .await
+                    .with_context(|| format!("Failed to reach {}", url))?;
+                if response.status() == reqwest::StatusCode::NOT_FOUND {
+                    return Ok(false);
+                }
+                response.error_for_status()?;
+                Ok(true)
             }
         }
     }
 
+    /// Whether the remote holds each chunk, checking up to `parallel` at once
+    pub async fn chunks_exist(&self, hashes: &[SHA256Hash], parallel: usize) -> Result<Vec<bool>> {
+        use futures::stream::{self, StreamExt, TryStreamExt};
+
+        stream::iter(hashes.iter())
+            .map(|hash| self.chunk_exists(hash))
+            .buffered(parallel)
+            .try_collect()
+            .await
+    }
+
     /// Download using protocol-specific method
     async fn download_with_protocol(&self, url: &str) -> Result<Vec<u8>> {
         match self.protocol {
@@ -346,6 +382,41 @@ impl ChunkClient {
     }
 }
 
+/// Key of a chunk relative to the remote root: `chunks/{first 2}/{rest}`
+///
+/// This is the layout thin clones read and `herald sync --publish` writes.
+pub fn remote_chunk_key(hash: &SHA256Hash) -> String {
+    let hex = hash.to_hex();
+    format!("chunks/{}/{}", &hex[..2], &hex[2..])
+}
+
+/// Whether `data` is the chunk stored under `hash`
+///
+/// Most chunks are keyed by the hash of their contents. Delta chunks are keyed
+/// by their content hash, which covers their reference and delta operations.
+/// Delta chunks written before the reference was hashed are keyed by their
+/// operations alone.
+pub fn verify_chunk_data(hash: &SHA256Hash, data: &[u8]) -> bool {
+    use crate::types::TemporalDeltaChunk;
+
+    if SHA256Hash::compute(data) == *hash {
+        return true;
+    }
+    if data.first() != Some(&b'{') {
+        return false;
+    }
+    let Some(chunk) = serde_json::from_slice::<TemporalDeltaChunk>(data)
+        .ok()
+        .filter(|chunk| chunk.content_hash == *hash)
+    else {
+        return false;
+    };
+    TemporalDeltaChunk::compute_content_hash(&chunk.reference_hash, &chunk.deltas)
+        .is_ok_and(|content_hash| content_hash == *hash)
+        || serde_json::to_vec(&chunk.deltas)
+            .is_ok_and(|deltas| SHA256Hash::compute(&deltas) == *hash)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
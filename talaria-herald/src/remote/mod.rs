@@ -2,4 +2,6 @@
 /// Supports S3, GCS, Azure Blob Storage, and HTTP(S)
 pub mod chunk_client;
 
-pub use chunk_client::{ChunkClient, ChunkDownloadError, Protocol};
+pub use chunk_client::{
+    remote_chunk_key, verify_chunk_data, ChunkClient, ChunkDownloadError, Protocol,
+};
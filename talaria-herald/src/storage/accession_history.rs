@@ -0,0 +1,603 @@
+/// Accession history: merged, demerged, deleted and replaced accessions
+///
+/// UniProt lists the secondary accessions left behind by merges and demerges
+/// in `sec_ac.txt` and withdrawn Swiss-Prot entries in `delac_sp.txt`. NCBI
+/// publishes dead-accession tables (`dead_prot.accession2taxid` and friends)
+/// and replaced-accession lists. Importing these files records one event per
+/// accession under `acchist:{accession}` in the index column family. An
+/// event keeps the release it was first imported from, so importing each
+/// release's files in order dates every change.
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::BufRead;
+use std::sync::Arc;
+
+use crate::types::SHA256Hash;
+use talaria_storage::backend::{RocksDBBackend, RocksDBIndexOps};
+
+const HISTORY_PREFIX: &str = "acchist:";
+const GI_PREFIX: &str = "gi:";
+
+/// Accessions read and written per RocksDB batch during an import
+const IMPORT_BATCH_SIZE: usize = 10_000;
+
+/// What happened to an accession
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AccessionEvent {
+    /// Merged into another entry and kept there as a secondary accession
+    Merged {
+        into: String,
+    },
+    /// Split into several entries that each keep it as a secondary accession
+    Demerged {
+        into: Vec<String>,
+    },
+    Deleted,
+    /// NCBI record withdrawn in favour of a newer accession
+    Replaced {
+        by: String,
+    },
+}
+
+/// One event with the release it was first seen in
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccessionHistoryEntry {
+    pub event: AccessionEvent,
+    /// Release label, e.g. `2023_02` for UniProt
+    pub release: String,
+    /// `uniprot` or `ncbi`
+    pub source: String,
+    pub recorded_at: DateTime<Utc>,
+}
+
+impl fmt::Display for AccessionHistoryEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.event {
+            AccessionEvent::Merged { into } => {
+                write!(f, "merged into {} in {}", into, self.release)
+            }
+            AccessionEvent::Demerged { into } => {
+                write!(f, "demerged into {} in {}", into.join(", "), self.release)
+            }
+            AccessionEvent::Deleted => write!(f, "deleted in {}", self.release),
+            AccessionEvent::Replaced { by } => {
+                write!(f, "replaced by {} in {}", by, self.release)
+            }
+        }
+    }
+}
+
+/// Events parsed from one history file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HistoryFile {
+    /// Release named in the file header, if any
+    pub release: Option<String>,
+    pub events: Vec<(String, AccessionEvent)>,
+    /// GI numbers and the accession they stood for
+    pub gi: Vec<(u64, String)>,
+}
+
+/// Counts from [`AccessionHistory::import`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HistoryImportStats {
+    pub recorded: usize,
+    /// Events already known from an earlier release
+    pub unchanged: usize,
+    pub gi_numbers: usize,
+}
+
+/// Accession history index on the shared RocksDB backend
+pub struct AccessionHistory {
+    backend: Arc<RocksDBBackend>,
+}
+
+impl AccessionHistory {
+    pub fn new(backend: Arc<RocksDBBackend>) -> Self {
+        Self { backend }
+    }
+
+    /// Every recorded event for an accession, oldest first
+    pub fn history(&self, accession: &str) -> Result<Vec<AccessionHistoryEntry>> {
+        let key = format!("{}{}", HISTORY_PREFIX, accession);
+        match self.backend.get_index(&key)? {
+            Some(data) => serde_json::from_slice(&data)
+                .with_context(|| format!("Corrupt accession history for {}", accession)),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// The latest event for an accession
+    pub fn latest(&self, accession: &str) -> Result<Option<AccessionHistoryEntry>> {
+        Ok(self.history(accession)?.pop())
+    }
+
+    /// Record an event unless it is already the accession's latest one
+    ///
+    /// Returns whether anything was written.
+    pub fn record(
+        &self,
+        accession: &str,
+        event: AccessionEvent,
+        release: &str,
+        source: &str,
+    ) -> Result<bool> {
+        let mut history = self.history(accession)?;
+        if !append_event(&mut history, event, release, source) {
+            return Ok(false);
+        }
+        let key = format!("{}{}", HISTORY_PREFIX, accession);
+        self.backend
+            .put_index(&key, &serde_json::to_vec(&history)?)?;
+        Ok(true)
+    }
+
+    /// Accession a GI number stood for
+    pub fn gi_accession(&self, gi: u64) -> Result<Option<String>> {
+        let key = format!("{}{}", GI_PREFIX, gi);
+        Ok(self
+            .backend
+            .get_index(&key)?
+            .map(|data| String::from_utf8_lossy(&data).into_owned()))
+    }
+
+    /// Record every event and GI number of a parsed file
+    ///
+    /// Events are grouped by accession, so each history is read and written
+    /// once however many events the file lists for it, and histories are
+    /// read and written a batch at a time.
+    pub fn import(
+        &self,
+        file: &HistoryFile,
+        release: &str,
+        source: &str,
+    ) -> Result<HistoryImportStats> {
+        let mut stats = HistoryImportStats::default();
+        let mut by_accession: BTreeMap<&str, Vec<&AccessionEvent>> = BTreeMap::new();
+        for (accession, event) in &file.events {
+            by_accession.entry(accession).or_default().push(event);
+        }
+
+        let grouped: Vec<_> = by_accession.into_iter().collect();
+        for batch in grouped.chunks(IMPORT_BATCH_SIZE) {
+            let keys: Vec<String> = batch
+                .iter()
+                .map(|(accession, _)| format!("{}{}", HISTORY_PREFIX, accession))
+                .collect();
+            let mut writes = Vec::new();
+            for ((key, (accession, events)), data) in keys
+                .into_iter()
+                .zip(batch)
+                .zip(self.backend.get_indices_batch(&keys)?)
+            {
+                let mut history: Vec<AccessionHistoryEntry> = match data {
+                    Some(data) => serde_json::from_slice(&data)
+                        .with_context(|| format!("Corrupt accession history for {}", accession))?,
+                    None => Vec::new(),
+                };
+                let mut changed = false;
+                for event in events {
+                    if append_event(&mut history, (*event).clone(), release, source) {
+                        stats.recorded += 1;
+                        changed = true;
+                    } else {
+                        stats.unchanged += 1;
+                    }
+                }
+                if changed {
+                    writes.push((key, serde_json::to_vec(&history)?));
+                }
+            }
+            self.backend.put_indices_batch(&writes)?;
+        }
+
+        for batch in file.gi.chunks(IMPORT_BATCH_SIZE) {
+            let writes: Vec<(String, Vec<u8>)> = batch
+                .iter()
+                .map(|(gi, accession)| {
+                    (
+                        format!("{}{}", GI_PREFIX, gi),
+                        accession.as_bytes().to_vec(),
+                    )
+                })
+                .collect();
+            self.backend.put_indices_batch(&writes)?;
+            stats.gi_numbers += batch.len();
+        }
+        Ok(stats)
+    }
+}
+
+/// Append an event unless it is already the latest one; returns whether it was
+fn append_event(
+    history: &mut Vec<AccessionHistoryEntry>,
+    event: AccessionEvent,
+    release: &str,
+    source: &str,
+) -> bool {
+    if history.last().is_some_and(|entry| entry.event == event) {
+        return false;
+    }
+    history.push(AccessionHistoryEntry {
+        event,
+        release: release.to_string(),
+        source: source.to_string(),
+        recorded_at: Utc::now(),
+    });
+    true
+}
+
+/// One hop taken while following an accession's history
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolutionStep {
+    pub accession: String,
+    pub entry: AccessionHistoryEntry,
+}
+
+/// Where an accession's history ends
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResolutionOutcome {
+    /// A stored sequence carries this accession
+    Current {
+        accession: String,
+        hash: SHA256Hash,
+    },
+    Deleted {
+        accession: String,
+        release: String,
+    },
+    /// Demerged: the accession now belongs to several entries
+    Ambiguous {
+        accession: String,
+        candidates: Vec<String>,
+    },
+    /// The history leads to an accession that is not stored locally
+    NotStored {
+        accession: String,
+    },
+    /// Neither stored nor in the history index
+    Unknown,
+}
+
+/// Result of following an accession through its history
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccessionResolution {
+    pub query: String,
+    pub steps: Vec<ResolutionStep>,
+    pub outcome: ResolutionOutcome,
+}
+
+impl AccessionResolution {
+    /// Canonical sequence the accession leads to
+    pub fn hash(&self) -> Option<SHA256Hash> {
+        match &self.outcome {
+            ResolutionOutcome::Current { hash, .. } => Some(*hash),
+            _ => None,
+        }
+    }
+
+    /// e.g. "Q9XYZ1 was merged into P12345 in 2023_02"
+    pub fn describe(&self) -> String {
+        let mut parts: Vec<String> = self
+            .steps
+            .iter()
+            .map(|step| format!("{} was {}", step.accession, step.entry))
+            .collect();
+        match &self.outcome {
+            ResolutionOutcome::Current { accession, .. } if self.steps.is_empty() => {
+                parts.push(format!("{} is current", accession));
+            }
+            ResolutionOutcome::NotStored { accession } => {
+                parts.push(format!("{} is not stored locally", accession));
+            }
+            ResolutionOutcome::Unknown => {
+                parts.push(format!("Accession {} not found", self.query));
+            }
+            _ => {}
+        }
+        parts.join("; ")
+    }
+}
+
+/// Longest history chain followed before giving up on a cycle
+pub(crate) const MAX_RESOLUTION_STEPS: usize = 32;
+
+/// UniProt accession format, e.g. `P12345` or `A0A023GPI8`
+pub fn is_uniprot_accession(value: &str) -> bool {
+    let bytes = value.as_bytes();
+    let digit = |i: usize| bytes[i].is_ascii_digit();
+    let upper = |i: usize| bytes[i].is_ascii_uppercase();
+    let alnum = |i: usize| upper(i) || digit(i);
+
+    // [OPQ][0-9][A-Z0-9]{3}[0-9] | [A-NR-Z][0-9]([A-Z][A-Z0-9]{2}[0-9]){1,2}
+    match bytes.len() {
+        6 if matches!(bytes[0], b'O' | b'P' | b'Q') => {
+            digit(1) && alnum(2) && alnum(3) && alnum(4) && digit(5)
+        }
+        6 | 10 if upper(0) && !matches!(bytes[0], b'O' | b'P' | b'Q') => {
+            digit(1)
+                && (2..bytes.len()).step_by(4).all(|start| {
+                    upper(start) && alnum(start + 1) && alnum(start + 2) && digit(start + 3)
+                })
+        }
+        _ => false,
+    }
+}
+
+/// Release label from a UniProt header line such as
+/// `Release:     2024_01 of 24-Jan-2024`
+fn uniprot_release(line: &str) -> Option<String> {
+    let rest = line.trim().strip_prefix("Release:")?;
+    rest.split_whitespace().next().map(str::to_string)
+}
+
+/// Parse UniProt `sec_ac.txt`
+///
+/// A secondary accession listed against one primary was merged into it;
+/// listed against several, its entry was demerged.
+pub fn parse_uniprot_sec_ac<R: BufRead>(reader: R) -> Result<HistoryFile> {
+    let mut file = HistoryFile::default();
+    let mut targets: BTreeMap<String, Vec<String>> = BTreeMap::new();
+
+    for line in reader.lines() {
+        let line = line?;
+        if file.release.is_none() {
+            file.release = uniprot_release(&line);
+        }
+        let mut words = line.split_whitespace();
+        let (Some(secondary), Some(primary), None) = (words.next(), words.next(), words.next())
+        else {
+            continue;
+        };
+        if is_uniprot_accession(secondary) && is_uniprot_accession(primary) {
+            targets
+                .entry(secondary.to_string())
+                .or_default()
+                .push(primary.to_string());
+        }
+    }
+
+    file.events = targets
+        .into_iter()
+        .map(|(secondary, mut primaries)| {
+            let event = if primaries.len() == 1 {
+                AccessionEvent::Merged {
+                    into: primaries.remove(0),
+                }
+            } else {
+                primaries.sort();
+                primaries.dedup();
+                AccessionEvent::Demerged { into: primaries }
+            };
+            (secondary, event)
+        })
+        .collect();
+    Ok(file)
+}
+
+/// Parse UniProt `delac_sp.txt` (or `delac_tr.txt`)
+pub fn parse_uniprot_delac<R: BufRead>(reader: R) -> Result<HistoryFile> {
+    let mut file = HistoryFile::default();
+    for line in reader.lines() {
+        let line = line?;
+        if file.release.is_none() {
+            file.release = uniprot_release(&line);
+        }
+        let accession = line.trim();
+        if is_uniprot_accession(accession) {
+            file.events
+                .push((accession.to_string(), AccessionEvent::Deleted));
+        }
+    }
+    Ok(file)
+}
+
+/// Parse an NCBI dead-accession table in `accession2taxid` layout
+///
+/// Columns are accession, accession.version, taxid and optionally gi. Every
+/// row is a deletion; non-zero GI numbers are kept so old `gi|…` citations
+/// still resolve.
+pub fn parse_ncbi_dead_accessions<R: BufRead>(reader: R) -> Result<HistoryFile> {
+    let mut file = HistoryFile::default();
+    for line in reader.lines() {
+        let line = line?;
+        let columns: Vec<&str> = line.split('\t').map(str::trim).collect();
+        if columns[0].is_empty() || columns[0] == "accession" || columns[0].starts_with('#') {
+            continue;
+        }
+        let accession = strip_version(columns[0]).to_string();
+        if let Some(gi) = columns.get(3).and_then(|gi| gi.parse::<u64>().ok()) {
+            if gi > 0 {
+                file.gi.push((gi, accession.clone()));
+            }
+        }
+        file.events.push((accession, AccessionEvent::Deleted));
+    }
+    Ok(file)
+}
+
+/// Parse a replaced-accession list: `old<TAB>new` per line, `#` comments
+pub fn parse_ncbi_replaced<R: BufRead>(reader: R) -> Result<HistoryFile> {
+    let mut file = HistoryFile::default();
+    for (number, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut words = line.split_whitespace();
+        match (words.next(), words.next()) {
+            (Some(old), Some(new)) => file.events.push((
+                strip_version(old).to_string(),
+                AccessionEvent::Replaced {
+                    by: strip_version(new).to_string(),
+                },
+            )),
+            _ => anyhow::bail!(
+                "Line {}: expected an old and a new accession, got '{}'",
+                number + 1,
+                line
+            ),
+        }
+    }
+    Ok(file)
+}
+
+/// `NP_001234.2` -> `NP_001234`; accessions are indexed without versions
+pub fn strip_version(accession: &str) -> &str {
+    match accession.rsplit_once('.') {
+        Some((base, version))
+            if !version.is_empty() && version.bytes().all(|b| b.is_ascii_digit()) =>
+        {
+            base
+        }
+        _ => accession,
+    }
+}
+
+/// GI number in a query such as `gi|12345` or `12345`
+pub fn parse_gi(query: &str) -> Option<u64> {
+    let number = query.strip_prefix("gi|").unwrap_or(query);
+    let number = number.split('|').next()?;
+    if number.is_empty() || !number.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    number.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    const SEC_AC: &str = "\
+UniProt - Swiss-Prot and TrEMBL Protein Knowledgebase
+Release:     2023_02 of 03-May-2023
+
+Secondary AC  Primary AC
+____________  __________
+A0A023GPJ3    Q8WZ42
+P29358        P02768
+P29358        P02769
+Q9XYZ1        P12345
+";
+
+    const DELAC: &str = "\
+Release:     2024_01 of 24-Jan-2024
+Deleted accessions
+____________
+A0A0A7DMC3
+P0DPQ9
+
+-----------------------------------------------------------------------
+Copyrighted by the UniProt Consortium
+";
+
+    #[test]
+    fn test_uniprot_accession_format() {
+        for valid in ["P12345", "Q8WZ42", "O00001", "A0A023GPJ3", "A2BC19"] {
+            assert!(is_uniprot_accession(valid), "{}", valid);
+        }
+        for invalid in ["Secondary", "P1234", "12345P", "A0A023GPJ", "NP_000001"] {
+            assert!(!is_uniprot_accession(invalid), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_parse_sec_ac() {
+        let file = parse_uniprot_sec_ac(Cursor::new(SEC_AC)).unwrap();
+        assert_eq!(file.release.as_deref(), Some("2023_02"));
+        assert_eq!(
+            file.events,
+            vec![
+                (
+                    "A0A023GPJ3".to_string(),
+                    AccessionEvent::Merged {
+                        into: "Q8WZ42".to_string()
+                    }
+                ),
+                (
+                    "P29358".to_string(),
+                    AccessionEvent::Demerged {
+                        into: vec!["P02768".to_string(), "P02769".to_string()]
+                    }
+                ),
+                (
+                    "Q9XYZ1".to_string(),
+                    AccessionEvent::Merged {
+                        into: "P12345".to_string()
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_delac() {
+        let file = parse_uniprot_delac(Cursor::new(DELAC)).unwrap();
+        assert_eq!(file.release.as_deref(), Some("2024_01"));
+        let accessions: Vec<&str> = file.events.iter().map(|(a, _)| a.as_str()).collect();
+        assert_eq!(accessions, vec!["A0A0A7DMC3", "P0DPQ9"]);
+    }
+
+    #[test]
+    fn test_parse_ncbi_files() {
+        let dead = "accession\taccession.version\ttaxid\tgi\n\
+                    XP_001234\tXP_001234.1\t9606\t123456\n\
+                    WP_000001\tWP_000001.2\t562\t0\n";
+        let file = parse_ncbi_dead_accessions(Cursor::new(dead)).unwrap();
+        assert_eq!(file.events.len(), 2);
+        assert_eq!(file.gi, vec![(123456, "XP_001234".to_string())]);
+
+        let replaced = "# old\tnew\nNP_000001.1\tNP_000002.3\n";
+        let file = parse_ncbi_replaced(Cursor::new(replaced)).unwrap();
+        assert_eq!(
+            file.events,
+            vec![(
+                "NP_000001".to_string(),
+                AccessionEvent::Replaced {
+                    by: "NP_000002".to_string()
+                }
+            )]
+        );
+        assert!(parse_ncbi_replaced(Cursor::new("NP_000001\n")).is_err());
+    }
+
+    #[test]
+    fn test_gi_and_versions() {
+        assert_eq!(parse_gi("gi|123456"), Some(123456));
+        assert_eq!(parse_gi("gi|123456|ref|NP_000001.1|"), Some(123456));
+        assert_eq!(parse_gi("P12345"), None);
+        assert_eq!(strip_version("NP_001234.2"), "NP_001234");
+        assert_eq!(strip_version("P12345"), "P12345");
+    }
+
+    #[test]
+    fn test_describe() {
+        let entry = AccessionHistoryEntry {
+            event: AccessionEvent::Merged {
+                into: "P12345".to_string(),
+            },
+            release: "2023_02".to_string(),
+            source: "uniprot".to_string(),
+            recorded_at: Utc::now(),
+        };
+        let resolution = AccessionResolution {
+            query: "Q9XYZ1".to_string(),
+            steps: vec![ResolutionStep {
+                accession: "Q9XYZ1".to_string(),
+                entry,
+            }],
+            outcome: ResolutionOutcome::NotStored {
+                accession: "P12345".to_string(),
+            },
+        };
+        assert_eq!(
+            resolution.describe(),
+            "Q9XYZ1 was merged into P12345 in 2023_02; P12345 is not stored locally"
+        );
+    }
+}
@@ -1,7 +1,12 @@
+use super::format_version::{FormatStatus, RepositoryFormat};
 use super::indices::SequenceIndices;
 use super::sequence::SequenceStorage;
+use super::thin_clone::{RemoteChunkSource, ThinCloneConfig};
 use super::traits::{ChunkStorage, DeltaStorage, ManifestStorage, StateManagement};
-use crate::operations::{OperationType, ProcessingState, ProcessingStateManager, SourceInfo};
+use crate::operations::{
+    FormatMigrator, OperationType, ProcessingState, ProcessingStateManager, SourceInfo,
+};
+use crate::performance::metrics;
 /// Content-addressed storage implementation for HERALD
 use crate::types::*;
 use anyhow::{anyhow, Result};
@@ -11,7 +16,7 @@ use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use talaria_storage::backend::RocksDBBackend;
+use talaria_storage::backend::{RocksDBBackend, RocksDBConfig};
 use talaria_storage::compression::{ChunkCompressor, CompressionConfig};
 
 // Import and re-export storage statistics and error types from talaria-core
@@ -51,6 +56,8 @@ pub struct HeraldStorage {
     state_manager: Arc<Mutex<ProcessingStateManager>>,
     current_operation_id: Arc<Mutex<Option<String>>>,
     compressor: Arc<Mutex<ChunkCompressor>>,
+    /// Chunk source for thin clones, consulted when a chunk is not local
+    remote: Option<Arc<RemoteChunkSource>>,
 }
 
 /// Internal chunk info structure with local storage details
@@ -88,21 +95,82 @@ struct ChunkMetadataExtended {
     taxon_ids: Vec<TaxonId>,
 }
 
+/// Names of the directories directly under `dir`; empty if it does not exist
+fn subdirectories(dir: &Path) -> Result<Vec<String>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut names = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            names.push(entry.file_name().to_string_lossy().into_owned());
+        }
+    }
+    Ok(names)
+}
+
+/// Index key of a profile's accession index; entries live under `{key}:{accession}`
+fn accession_index_key(source: &str, dataset: &str, version: &str, profile: &str) -> String {
+    format!("accessions:{}:{}:{}:{}", source, dataset, version, profile)
+}
+
 impl HeraldStorage {
+    /// Open (or create) the storage of the repository at `base_path`
+    ///
+    /// Fails before any store is opened if the repository was written by a
+    /// newer build or a format migration was interrupted, and once opened if
+    /// it must be migrated with `talaria herald migrate` first. A fresh
+    /// directory is stamped with the current format as it is created.
     pub fn new(base_path: &Path) -> Result<Self> {
+        let format = RepositoryFormat::detect(base_path)?;
+        FormatMigrator::ensure_openable(base_path, format)?;
+        let storage = Self::open_unchecked(base_path)?;
+        match format {
+            FormatStatus::Fresh => RepositoryFormat::current().save(base_path)?,
+            FormatStatus::Outdated(_) => FormatMigrator::new(&storage).ensure_supported(format)?,
+            _ => {}
+        }
+        Ok(storage)
+    }
+
+    /// Open storage without checking the repository format, to migrate it
+    pub fn open_unchecked(base_path: &Path) -> Result<Self> {
         // Use centralized canonical sequence storage path
         // HERALD Principle #1: Single shared location for all sequences
-        let sequences_dir = paths::canonical_sequence_storage_dir();
-        let sequence_storage = Arc::new(SequenceStorage::new(&sequences_dir)?);
+        Self::with_sequences_dir(base_path, &paths::canonical_sequence_storage_dir())
+    }
+
+    /// Open storage whose canonical sequences live outside the shared location
+    ///
+    /// Used for restored copies of a repository, which must not touch the live
+    /// sequence store.
+    pub fn with_sequences_dir(base_path: &Path, sequences_dir: &Path) -> Result<Self> {
+        // The exported cache and compaction counters need RocksDB statistics
+        let rocksdb_config = RocksDBConfig {
+            enable_statistics: metrics::registry().is_some(),
+            ..Default::default()
+        };
+        let remote = match ThinCloneConfig::load(base_path)? {
+            Some(config) => Some(Arc::new(RemoteChunkSource::open(base_path, config)?)),
+            None => None,
+        };
+        let sequence_storage = Arc::new(
+            SequenceStorage::new_with_config(sequences_dir, rocksdb_config.clone())?
+                .with_remote(remote.clone()),
+        );
 
         // Create RocksDB storage for chunks
         let chunk_storage_dir = base_path.join("chunk_storage");
-        let chunk_storage = Arc::new(RocksDBBackend::new(&chunk_storage_dir)?);
+        let chunk_storage = Arc::new(RocksDBBackend::new_with_config(
+            &chunk_storage_dir,
+            rocksdb_config,
+        )?);
 
         // Create sequence indices (shares RocksDB backend)
         let indices = Arc::new(SequenceIndices::with_backend(
             Some(sequence_storage.get_rocksdb()),
-            &sequences_dir,
+            sequences_dir,
             None, // Use default bloom filter config
         )?);
 
@@ -110,6 +178,11 @@ impl HeraldStorage {
         let compression_config = CompressionConfig::default();
         let compressor = ChunkCompressor::new(compression_config);
 
+        if let Some(metrics) = metrics::registry() {
+            metrics.attach_rocksdb("sequences", &sequence_storage.get_rocksdb());
+            metrics.attach_rocksdb("chunks", &chunk_storage);
+        }
+
         Ok(Self {
             base_path: base_path.to_path_buf(),
             sequence_storage,
@@ -118,6 +191,7 @@ impl HeraldStorage {
             state_manager: Arc::new(Mutex::new(state_manager)),
             current_operation_id: Arc::new(Mutex::new(None)),
             compressor: Arc::new(Mutex::new(compressor)),
+            remote,
         })
     }
 
@@ -143,6 +217,9 @@ impl HeraldStorage {
             if self.chunk_storage.chunk_exists(&hash)? {
                 tracing::Span::current().record("deduplicated", &true);
                 tracing::debug!("Chunk already exists (bloom filter hit), skipping storage");
+                if let Some(metrics) = metrics::registry() {
+                    metrics.record_chunk(data.len(), true);
+                }
                 return Ok(hash); // Confirmed exists
             }
             // False positive - bloom filter was wrong, continue to store
@@ -155,11 +232,23 @@ impl HeraldStorage {
             let _ = self.indices.add_sequence(hash.clone(), None, None, None);
             tracing::Span::current().record("deduplicated", &true);
             tracing::debug!("Chunk already exists (RocksDB check), updating bloom filter");
+            if let Some(metrics) = metrics::registry() {
+                metrics.record_chunk(data.len(), true);
+            }
             return Ok(hash);
         }
 
         tracing::Span::current().record("deduplicated", &false);
 
+        self.store_chunk_as(&hash, data, compress)?;
+        if let Some(metrics) = metrics::registry() {
+            metrics.record_chunk(data.len(), false);
+        }
+        Ok(hash)
+    }
+
+    /// Store chunk data under a caller-provided key
+    fn store_chunk_as(&self, hash: &SHA256Hash, data: &[u8], compress: bool) -> Result<()> {
         // Compress if requested
         let final_data = if compress {
             let format = ChunkFormat::default();
@@ -170,12 +259,12 @@ impl HeraldStorage {
         };
 
         // Store in RocksDB
-        self.chunk_storage.store_chunk(&hash, &final_data)?;
+        self.chunk_storage.store_chunk(hash, &final_data)?;
 
         // Update bloom filter for future lookups
-        let _ = self.indices.add_sequence(hash.clone(), None, None, None);
+        let _ = self.indices.add_sequence(*hash, None, None, None);
 
-        Ok(hash)
+        Ok(())
     }
 
     /// Store multiple chunks in a batch for better performance
@@ -248,6 +337,9 @@ impl HeraldStorage {
 
                 // Check if already stored (from our existence map)
                 if *exists_map.get(&hash).unwrap_or(&false) {
+                    if let Some(metrics) = metrics::registry() {
+                        metrics.record_chunk(data.len(), true);
+                    }
                     hashes.push(hash);
                     continue;
                 }
@@ -276,22 +368,182 @@ impl HeraldStorage {
             self.chunk_storage.store_chunks_batch(&batch_data)?;
         }
 
+        if let Some(metrics) = metrics::registry() {
+            let stored: HashSet<_> = batch_data.iter().map(|(hash, _)| hash).collect();
+            for (hash, (data, _)) in chunk_hashes.iter().zip(chunks) {
+                if stored.contains(&hash) {
+                    metrics.record_chunk(data.len(), false);
+                }
+            }
+        }
+
         Ok(hashes)
     }
 
     /// Retrieve a chunk from storage
     pub fn get_chunk(&self, hash: &SHA256Hash) -> Result<Vec<u8>> {
         // Load from RocksDB
-        let compressed_data = self.chunk_storage.load_chunk(hash)?;
+        let compressed_data = match (&self.remote, self.chunk_storage.load_chunk(hash)) {
+            (Some(remote), Ok(data)) => {
+                remote.touch(hash);
+                data
+            }
+            (None, result) => result?,
+            (Some(remote), Err(e)) => {
+                if self.chunk_storage.chunk_exists(hash)? {
+                    return Err(e);
+                }
+                return self.fetch_remote_chunk(remote, hash);
+            }
+        };
 
         // Decompress and return
         let compressor = self.compressor.lock();
         compressor.decompress(&compressed_data, Some(ChunkFormat::default()))
     }
 
+    /// Fetch a missing chunk for a thin clone, cache it and evict to budget
+    fn fetch_remote_chunk(&self, remote: &RemoteChunkSource, hash: &SHA256Hash) -> Result<Vec<u8>> {
+        tracing::debug!(
+            "Fetching chunk {} from {}",
+            hash,
+            remote.config().remote_url
+        );
+        let data = remote.fetch(hash)?;
+
+        let compressed = self
+            .compressor
+            .lock()
+            .compress(&data, ChunkFormat::default(), None)?;
+        self.chunk_storage.store_chunk(hash, &compressed)?;
+        let _ = self.indices.add_sequence(*hash, None, None, None);
+
+        let evicted = remote.record_fetched(hash, compressed.len() as u64);
+        if !evicted.is_empty() {
+            tracing::debug!("Evicting {} cached chunks", evicted.len());
+            self.chunk_storage.delete_chunks_batch(&evicted)?;
+        }
+
+        Ok(data)
+    }
+
+    /// Remote chunk source, if this repository is a thin clone
+    pub fn remote_source(&self) -> Option<&RemoteChunkSource> {
+        self.remote.as_deref()
+    }
+
+    /// Copy the manifests published next to a thin clone's chunks
+    ///
+    /// Existing local entries with the same keys are overwritten. Returns the
+    /// number of manifest entries copied.
+    pub fn bootstrap_from_remote(&self) -> Result<usize> {
+        let Some(remote) = &self.remote else {
+            anyhow::bail!("{} is not a thin clone", self.base_path.display());
+        };
+        let published = remote.fetch_manifests()?;
+
+        let rocksdb = self.sequence_storage.get_rocksdb();
+        for (key, value) in &published.manifests {
+            rocksdb.put_manifest(key, value)?;
+        }
+        for (key, value) in &published.database_metadata {
+            let mut parts = key.splitn(3, ':').skip(1);
+            if let (Some(source), Some(dataset)) = (parts.next(), parts.next()) {
+                rocksdb.put_database_metadata(source, dataset, value)?;
+            }
+        }
+        Ok(published.manifests.len())
+    }
+
+    /// Evict fetched chunks until the cache holds at most `budget` bytes
+    ///
+    /// Returns the number of chunks removed; a no-op for full repositories.
+    pub fn prune_remote_cache(&self, budget: u64) -> Result<usize> {
+        let Some(remote) = &self.remote else {
+            return Ok(0);
+        };
+        let evicted = remote.evict_to(budget)?;
+        if !evicted.is_empty() {
+            self.chunk_storage.delete_chunks_batch(&evicted)?;
+        }
+        Ok(evicted.len())
+    }
+
     /// Check if a chunk exists
+    ///
+    /// Thin clones also ask the remote about chunks they have not fetched.
     pub fn has_chunk(&self, hash: &SHA256Hash) -> bool {
-        self.chunk_storage.chunk_exists(hash).unwrap_or(false)
+        if self.chunk_storage.chunk_exists(hash).unwrap_or(false) {
+            return true;
+        }
+        match &self.remote {
+            Some(remote) => remote.contains(hash).unwrap_or_else(|e| {
+                tracing::warn!("Could not check remote for chunk {}: {}", hash, e);
+                false
+            }),
+            None => false,
+        }
+    }
+
+    /// The chunks in `hashes` neither stored locally nor held by the remote
+    ///
+    /// Unlike calling `has_chunk` in a loop, the remote is asked about all
+    /// local misses at once.
+    pub fn missing_chunks(&self, hashes: &[SHA256Hash]) -> Vec<SHA256Hash> {
+        let local_misses: Vec<SHA256Hash> = hashes
+            .iter()
+            .filter(|hash| !self.chunk_storage.chunk_exists(hash).unwrap_or(false))
+            .copied()
+            .collect();
+        let Some(remote) = &self.remote else {
+            return local_misses;
+        };
+        match remote.contains_many(&local_misses) {
+            Ok(found) => local_misses
+                .into_iter()
+                .zip(found)
+                .filter(|(_, found)| !found)
+                .map(|(hash, _)| hash)
+                .collect(),
+            Err(e) => {
+                tracing::warn!("Could not check remote for chunks: {}", e);
+                local_misses
+            }
+        }
+    }
+
+    /// Download the chunks in `hashes` that are missing locally
+    ///
+    /// Thin clones fetch on demand, so only chunks their remote lacks are
+    /// downloaded. The remote is `TALARIA_REMOTE_REPO`, or
+    /// `TALARIA_CHUNK_SERVER` when that is unset. Returns the number of
+    /// chunks downloaded.
+    pub fn fetch_missing_chunks(&self, hashes: &[SHA256Hash]) -> Result<usize> {
+        use crate::remote::ChunkClient;
+
+        let missing = self.missing_chunks(hashes);
+        if missing.is_empty() {
+            return Ok(0);
+        }
+
+        let remote_url = std::env::var("TALARIA_REMOTE_REPO")
+            .or_else(|_| std::env::var("TALARIA_CHUNK_SERVER"))
+            .unwrap_or_default();
+        if remote_url.is_empty() {
+            return Err(anyhow!(
+                "{} chunks are missing and no remote repository is configured. \
+                 Set TALARIA_REMOTE_REPO or TALARIA_CHUNK_SERVER",
+                missing.len()
+            ));
+        }
+
+        let client = ChunkClient::new(Some(remote_url))?;
+        let rt = tokio::runtime::Runtime::new()?;
+        let downloaded = rt.block_on(client.download_chunks(&missing, 8))?;
+        for (hash, data) in downloaded {
+            self.store_raw_chunk(&hash, data)?;
+        }
+        Ok(missing.len())
     }
 
     /// Three-tier existence check optimized for performance:
@@ -495,6 +747,16 @@ impl HeraldStorage {
 
     /// Store a delta chunk with type information
     pub fn store_delta_chunk(&self, chunk: &TemporalDeltaChunk) -> Result<SHA256Hash> {
+        let chunk_hash = self.write_delta_chunk(chunk)?;
+
+        // Update delta index
+        self.update_delta_index(chunk)?;
+
+        Ok(chunk_hash)
+    }
+
+    /// Store delta chunk data and metadata without touching the delta index
+    fn write_delta_chunk(&self, chunk: &TemporalDeltaChunk) -> Result<SHA256Hash> {
         // Serialize the delta chunk
         let chunk_data = serde_json::to_vec(chunk)?;
         let chunk_hash = chunk.content_hash;
@@ -516,16 +778,143 @@ impl HeraldStorage {
         fs::create_dir_all(metadata_path.parent().unwrap())?;
         fs::write(&metadata_path, serde_json::to_vec(&metadata)?)?;
 
-        // Store the chunk data (compressed if beneficial)
+        // Store the chunk data (compressed if beneficial) under its content hash,
+        // which is what the delta index and dependent chunks refer to
         let compress = chunk.compression_ratio < 0.9;
-        self.store_chunk(&chunk_data, compress)?;
-
-        // Update delta index
-        self.update_delta_index(chunk)?;
+        self.store_chunk_as(&chunk_hash, &chunk_data, compress)?;
 
         Ok(chunk_hash)
     }
 
+    /// All delta chunks listed in the delta index
+    pub fn list_delta_chunks(&self) -> Result<Vec<SHA256Hash>> {
+        let index = self.load_delta_index()?;
+        let chunks: HashSet<SHA256Hash> =
+            index.values().map(|entry| entry.delta_chunk_hash).collect();
+        Ok(chunks.into_iter().collect())
+    }
+
+    /// Replace a delta chunk with a re-encoded version of the same sequences
+    ///
+    /// The new chunk is stored first and the deltas `chains` lists as built on
+    /// the old chunk are repointed at it. A delta chunk's key covers its
+    /// reference, so each repointed descendant is stored under a new hash as
+    /// well. Index entries are then switched over in a single atomic write.
+    /// Returns the hashes of every chunk replaced; the old chunks stay in
+    /// storage until passed to `remove_superseded_delta_chunks`.
+    pub fn replace_delta_chunk(
+        &self,
+        old_hash: &SHA256Hash,
+        new_chunk: &TemporalDeltaChunk,
+        chains: &crate::delta::reconstructor::DeltaChainManager,
+    ) -> Result<Vec<SHA256Hash>> {
+        let mut index = self.load_delta_index()?;
+
+        let mut replaced_chunks = HashMap::new();
+        self.store_replacement(chains, old_hash, new_chunk.clone(), &mut replaced_chunks)?;
+
+        for entry in index.values_mut() {
+            if let Some(chunk) = replaced_chunks.get(&entry.delta_chunk_hash) {
+                entry.delta_chunk_hash = chunk.content_hash;
+                entry.reference_hash = chunk.reference_hash;
+                entry.chunk_type = chunk.chunk_type.clone();
+                entry.compression_ratio = chunk.compression_ratio;
+            }
+        }
+
+        let index_path = self.base_path.join("delta_index_v2.json");
+        let tmp_path = index_path.with_extension("json.tmp");
+        fs::write(&tmp_path, serde_json::to_string_pretty(&index)?)?;
+        fs::rename(&tmp_path, &index_path)?;
+
+        Ok(replaced_chunks.into_keys().collect())
+    }
+
+    /// Store `chunk` in place of `old_hash` and repoint its descendants
+    fn store_replacement(
+        &self,
+        chains: &crate::delta::reconstructor::DeltaChainManager,
+        old_hash: &SHA256Hash,
+        chunk: TemporalDeltaChunk,
+        replaced: &mut HashMap<SHA256Hash, TemporalDeltaChunk>,
+    ) -> Result<()> {
+        self.write_delta_chunk(&chunk)?;
+        let new_hash = chunk.content_hash;
+        replaced.insert(*old_hash, chunk);
+
+        for child_hash in chains.children(old_hash) {
+            if replaced.contains_key(child_hash) {
+                continue;
+            }
+            let mut child = self.get_delta_chunk(child_hash)?;
+            child.set_reference(new_hash);
+            self.store_replacement(chains, child_hash, child, replaced)?;
+        }
+
+        Ok(())
+    }
+
+    /// Delete delta chunks replaced by `replace_delta_chunk`
+    ///
+    /// Chunks the delta index lists again, or a reduction profile still
+    /// refers to, are kept. Returns the number of chunks deleted.
+    pub fn remove_superseded_delta_chunks(&self, superseded: &[SHA256Hash]) -> Result<usize> {
+        if superseded.is_empty() {
+            return Ok(0);
+        }
+        let mut kept: HashSet<SHA256Hash> = self.list_delta_chunks()?.into_iter().collect();
+        kept.extend(self.reduction_delta_chunks()?);
+
+        let removable: Vec<SHA256Hash> = superseded
+            .iter()
+            .filter(|hash| !kept.contains(hash))
+            .copied()
+            .collect();
+        self.remove_chunks_batch(&removable)?;
+        for hash in &removable {
+            let metadata_path = self
+                .base_path
+                .join("metadata")
+                .join(format!("{}.meta", hash.to_hex()));
+            if metadata_path.exists() {
+                fs::remove_file(&metadata_path)?;
+            }
+        }
+        Ok(removable.len())
+    }
+
+    /// Delta chunks referenced by the reduction profiles of any version
+    fn reduction_delta_chunks(&self) -> Result<HashSet<SHA256Hash>> {
+        let versions_dir = self.base_path.join("versions");
+        let mut referenced = HashSet::new();
+        for source in subdirectories(&versions_dir)? {
+            let source_dir = versions_dir.join(&source);
+            for dataset in subdirectories(&source_dir)? {
+                for version in subdirectories(&source_dir.join(&dataset))? {
+                    for profile in
+                        self.list_database_reduction_profiles(&source, &dataset, &version)?
+                    {
+                        if let Some(reduction) = self.get_database_reduction_by_profile(
+                            &source, &dataset, &version, &profile,
+                        )? {
+                            referenced.extend(reduction.delta_chunks.iter().map(|d| d.chunk_hash));
+                        }
+                    }
+                }
+            }
+        }
+        Ok(referenced)
+    }
+
+    fn load_delta_index(&self) -> Result<HashMap<String, DeltaIndexEntryV2>> {
+        let index_path = self.base_path.join("delta_index_v2.json");
+        if index_path.exists() {
+            Ok(serde_json::from_str(&fs::read_to_string(&index_path)?)?)
+        } else {
+            Ok(HashMap::new())
+        }
+    }
+
     /// Retrieve a delta chunk
     pub fn get_delta_chunk(&self, hash: &SHA256Hash) -> Result<TemporalDeltaChunk> {
         let data = self.get_chunk(hash)?;
@@ -536,12 +925,7 @@ impl HeraldStorage {
     /// Update delta index for a new delta chunk
     fn update_delta_index(&self, chunk: &TemporalDeltaChunk) -> Result<()> {
         let index_path = self.base_path.join("delta_index_v2.json");
-
-        let mut index: HashMap<String, DeltaIndexEntryV2> = if index_path.exists() {
-            serde_json::from_str(&fs::read_to_string(&index_path)?)?
-        } else {
-            HashMap::new()
-        };
+        let mut index = self.load_delta_index()?;
 
         // Index each sequence in the delta chunk
         for seq_ref in &chunk.sequences {
@@ -1003,6 +1387,55 @@ impl HeraldStorage {
         Ok(None)
     }
 
+    /// Store the accession index of a reduction profile
+    ///
+    /// Each accession is its own key in the chunk store's index column
+    /// family, so lookups read single entries instead of the whole index.
+    /// Re-reducing a profile replaces its previous entries.
+    pub fn store_accession_index(
+        &self,
+        index: &crate::operations::AccessionIndex,
+        source: &str,
+        dataset: &str,
+        version: &str,
+    ) -> Result<()> {
+        let key = accession_index_key(source, dataset, version, &index.profile);
+        self.chunk_storage
+            .delete_index_prefix(&format!("{}:", key))?;
+
+        let mut entries = Vec::with_capacity(index.len() + 1);
+        for (accession, location) in index.entries() {
+            entries.push((
+                format!("{}:{}", key, accession),
+                rmp_serde::to_vec(location)?,
+            ));
+        }
+        entries.push((key, rmp_serde::to_vec(&(index.len() as u64))?));
+        self.chunk_storage.put_indices_batch(&entries)?;
+
+        Ok(())
+    }
+
+    /// Get the accession index of a reduction profile, if it was built
+    pub fn get_accession_index(
+        &self,
+        source: &str,
+        dataset: &str,
+        version: &str,
+        profile: &str,
+    ) -> Result<Option<crate::operations::StoredAccessionIndex>> {
+        let key = accession_index_key(source, dataset, version, profile);
+        let Some(count) = self.chunk_storage.get_index(&key)? else {
+            return Ok(None);
+        };
+        Ok(Some(crate::operations::StoredAccessionIndex::new(
+            self.chunk_storage.clone(),
+            profile.to_string(),
+            key,
+            rmp_serde::from_slice::<u64>(&count)? as usize,
+        )))
+    }
+
     /// List reduction profiles for a specific database version
     pub fn list_database_reduction_profiles(
         &self,
@@ -1179,31 +1612,30 @@ impl HeraldStorage {
     }
 
     /// Verify the integrity of the storage
+    ///
+    /// Every local chunk is decompressed and checked against its key. Delta
+    /// chunks are keyed by their `content_hash` rather than by their stored
+    /// bytes, so those are checked against their delta operations.
     pub fn verify_integrity(&self) -> Result<()> {
-        // Verify that chunks directory exists
-        let chunks_dir = self.base_path.join("chunks");
-        if !chunks_dir.exists() {
-            anyhow::bail!("Chunks directory does not exist: {:?}", chunks_dir);
-        }
-
-        // Verify each chunk in RocksDB
         let mut errors = Vec::new();
         let all_hashes = self.chunk_storage.list_all_chunks()?;
 
         for hash in &all_hashes {
-            // Try to load the chunk to verify it's readable
-            match self.chunk_storage.load_chunk(hash) {
-                Ok(data) => {
-                    // Verify hash matches content
-                    let computed_hash = SHA256Hash::compute(&data);
-                    if &computed_hash != hash {
-                        errors.push(format!("Hash mismatch for chunk {}", hash));
-                    }
-                }
+            // Read locally only; a thin clone must not fetch while verifying
+            let data = match self.chunk_storage.load_chunk(hash).and_then(|stored| {
+                self.compressor
+                    .lock()
+                    .decompress(&stored, Some(ChunkFormat::default()))
+            }) {
+                Ok(data) => data,
                 Err(e) => {
                     errors.push(format!("Failed to read chunk {}: {}", hash, e));
                     continue;
                 }
+            };
+
+            if !crate::remote::verify_chunk_data(hash, &data) {
+                errors.push(format!("Hash mismatch for chunk {}", hash));
             }
         }
 
@@ -1768,6 +2200,100 @@ mod tests {
         }
     }
 
+    #[test]
+    #[serial_test::serial]
+    fn test_fresh_directory_opens_twice() {
+        let temp_dir = TempDir::new().unwrap();
+        std::env::set_var("TALARIA_HOME", temp_dir.path());
+        let base_path = temp_dir.path().join("repository");
+
+        let storage = HeraldStorage::new(&base_path).unwrap();
+        assert_eq!(
+            RepositoryFormat::detect(&base_path).unwrap(),
+            FormatStatus::Current
+        );
+        drop(storage);
+
+        // Without the format record the chunk store alone looks legacy
+        HeraldStorage::new(&base_path).unwrap();
+        assert_eq!(
+            RepositoryFormat::detect(&base_path).unwrap(),
+            FormatStatus::Current
+        );
+
+        std::env::remove_var("TALARIA_HOME");
+    }
+
+    fn delta_chunk(reference: SHA256Hash, sequence_id: &str) -> TemporalDeltaChunk {
+        let deltas = vec![DeltaOperation::Insert {
+            sequence_id: sequence_id.to_string(),
+            data: b"ACGT".to_vec(),
+        }];
+        TemporalDeltaChunk {
+            content_hash: TemporalDeltaChunk::compute_content_hash(&reference, &deltas).unwrap(),
+            reference_hash: reference,
+            chunk_type: ChunkClassification::Delta {
+                reference_hash: reference,
+                compression_ratio: 0.5,
+            },
+            taxonomy_version: SHA256Hash::zero(),
+            taxon_ids: Vec::new(),
+            deltas,
+            sequences: vec![SequenceRef {
+                chunk_hash: reference,
+                offset: 0,
+                length: 4,
+                sequence_id: sequence_id.to_string(),
+            }],
+            created_at: chrono::Utc::now(),
+            valid_from: chrono::Utc::now(),
+            valid_until: None,
+            original_size: 4,
+            compressed_size: 2,
+            compression_ratio: 0.5,
+        }
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_replace_delta_chunk_repoints_children_and_removes_old() {
+        use crate::delta::reconstructor::DeltaChainManager;
+
+        let (storage, _temp_dir) = create_test_storage();
+        let reference = storage.store_chunk(b"reference", false).unwrap();
+        let parent = delta_chunk(reference, "SEQ_A");
+        let child = delta_chunk(parent.content_hash, "SEQ_B");
+        storage.store_delta_chunk(&parent).unwrap();
+        storage.store_delta_chunk(&child).unwrap();
+        let mut chains = DeltaChainManager::new(1);
+        chains.add_chunk(&parent);
+        chains.add_chunk(&child);
+
+        let mut rebased = parent.clone();
+        rebased.set_reference(storage.store_chunk(b"other", false).unwrap());
+        let superseded = storage
+            .replace_delta_chunk(&parent.content_hash, &rebased, &chains)
+            .unwrap();
+        assert_eq!(superseded.len(), 2);
+
+        // The child's key covers its reference, so it is stored anew as well
+        let mut repointed = child.clone();
+        repointed.set_reference(rebased.content_hash);
+        let live: HashSet<SHA256Hash> = storage.list_delta_chunks().unwrap().into_iter().collect();
+        assert_eq!(
+            live,
+            HashSet::from([rebased.content_hash, repointed.content_hash])
+        );
+
+        assert_eq!(
+            storage.remove_superseded_delta_chunks(&superseded).unwrap(),
+            2
+        );
+        assert!(!storage.has_chunk(&parent.content_hash));
+        assert!(!storage.has_chunk(&child.content_hash));
+        assert!(storage.has_chunk(&repointed.content_hash));
+    }
+
     #[test]
     #[serial_test::serial]
     fn test_chunk_storage_and_retrieval() {
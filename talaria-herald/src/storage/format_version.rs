@@ -0,0 +1,191 @@
+/// On-disk repository format version
+///
+/// A `FORMAT` record at the repository root states which layout of chunk
+/// storage, manifests and index keys the repository uses. Repositories created
+/// before the record existed are treated as format 1. Opening a repository
+/// compares its format with `CURRENT_FORMAT_VERSION`; older formats must be
+/// migrated (see `operations::migrator::FormatMigrator`) and newer ones are
+/// refused so an old build never writes into a layout it does not understand.
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// File name of the format record at the repository root
+pub const FORMAT_FILE: &str = "FORMAT";
+
+/// Format of repositories written before the format record was introduced
+pub const LEGACY_FORMAT_VERSION: u32 = 1;
+
+/// Format written by this build
+///
+/// - 1: unversioned layout; delta chunks keyed by the hash of their encoding
+/// - 2: delta chunks keyed by their content hash
+pub const CURRENT_FORMAT_VERSION: u32 = 2;
+
+/// Contents of the format record
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RepositoryFormat {
+    pub version: u32,
+    /// Talaria version that last wrote the record
+    pub written_by: String,
+    pub updated_at: DateTime<Utc>,
+    /// Migrations applied to reach `version`, oldest first
+    #[serde(default)]
+    pub history: Vec<AppliedMigration>,
+}
+
+/// A migration recorded in the format record
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AppliedMigration {
+    pub from: u32,
+    pub to: u32,
+    pub applied_at: DateTime<Utc>,
+    pub written_by: String,
+}
+
+/// How a repository's format relates to this build
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormatStatus {
+    /// No repository data yet
+    Fresh,
+    Current,
+    /// Older layout that must be migrated before use
+    Outdated(u32),
+    /// Written by a newer build
+    Unsupported(u32),
+}
+
+impl RepositoryFormat {
+    pub fn new(version: u32) -> Self {
+        Self {
+            version,
+            written_by: env!("CARGO_PKG_VERSION").to_string(),
+            updated_at: Utc::now(),
+            history: Vec::new(),
+        }
+    }
+
+    pub fn current() -> Self {
+        Self::new(CURRENT_FORMAT_VERSION)
+    }
+
+    pub fn path(base_path: &Path) -> PathBuf {
+        base_path.join(FORMAT_FILE)
+    }
+
+    /// Read the format record, if the repository has one
+    pub fn load(base_path: &Path) -> Result<Option<Self>> {
+        let path = Self::path(base_path);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let format = serde_json::from_str(&content)
+            .with_context(|| format!("Invalid repository format record {}", path.display()))?;
+        Ok(Some(format))
+    }
+
+    /// Write the record atomically
+    pub fn save(&self, base_path: &Path) -> Result<()> {
+        fs::create_dir_all(base_path)?;
+        let path = Self::path(base_path);
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, serde_json::to_string_pretty(self)?)?;
+        fs::rename(&tmp_path, &path)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(())
+    }
+
+    /// Format of the repository at `base_path`
+    ///
+    /// Must be called before opening storage, which creates the chunk store
+    /// and would make an empty directory look like a legacy repository.
+    pub fn detect(base_path: &Path) -> Result<FormatStatus> {
+        let version = match Self::load(base_path)? {
+            Some(format) => format.version,
+            None if has_repository_data(base_path) => LEGACY_FORMAT_VERSION,
+            None => return Ok(FormatStatus::Fresh),
+        };
+
+        Ok(match version.cmp(&CURRENT_FORMAT_VERSION) {
+            std::cmp::Ordering::Equal => FormatStatus::Current,
+            std::cmp::Ordering::Less => FormatStatus::Outdated(version),
+            std::cmp::Ordering::Greater => FormatStatus::Unsupported(version),
+        })
+    }
+
+    /// Record a completed migration to `to`
+    pub fn record_migration(&mut self, from: u32, to: u32) {
+        let written_by = env!("CARGO_PKG_VERSION").to_string();
+        self.history.push(AppliedMigration {
+            from,
+            to,
+            applied_at: Utc::now(),
+            written_by: written_by.clone(),
+        });
+        self.version = to;
+        self.written_by = written_by;
+        self.updated_at = Utc::now();
+    }
+}
+
+/// Whether `base_path` already holds a repository created by any version
+fn has_repository_data(base_path: &Path) -> bool {
+    base_path.join("chunk_storage").join("CURRENT").exists()
+        || base_path.join("manifest.tal").exists()
+        || base_path.join("manifest.json").exists()
+        || base_path.join("delta_index_v2.json").exists()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_detect_fresh_and_legacy() {
+        let temp_dir = TempDir::new().unwrap();
+        assert_eq!(
+            RepositoryFormat::detect(temp_dir.path()).unwrap(),
+            FormatStatus::Fresh
+        );
+
+        fs::create_dir_all(temp_dir.path().join("chunk_storage")).unwrap();
+        fs::write(
+            temp_dir.path().join("chunk_storage/CURRENT"),
+            "MANIFEST-000001",
+        )
+        .unwrap();
+        assert_eq!(
+            RepositoryFormat::detect(temp_dir.path()).unwrap(),
+            FormatStatus::Outdated(LEGACY_FORMAT_VERSION)
+        );
+    }
+
+    #[test]
+    fn test_record_roundtrip() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut format = RepositoryFormat::new(LEGACY_FORMAT_VERSION);
+        format.record_migration(LEGACY_FORMAT_VERSION, CURRENT_FORMAT_VERSION);
+        format.save(temp_dir.path()).unwrap();
+
+        let loaded = RepositoryFormat::load(temp_dir.path()).unwrap().unwrap();
+        assert_eq!(loaded, format);
+        assert_eq!(loaded.history.len(), 1);
+        assert_eq!(
+            RepositoryFormat::detect(temp_dir.path()).unwrap(),
+            FormatStatus::Current
+        );
+
+        RepositoryFormat::new(CURRENT_FORMAT_VERSION + 1)
+            .save(temp_dir.path())
+            .unwrap();
+        assert_eq!(
+            RepositoryFormat::detect(temp_dir.path()).unwrap(),
+            FormatStatus::Unsupported(CURRENT_FORMAT_VERSION + 1)
+        );
+    }
+}
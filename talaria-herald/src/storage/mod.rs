@@ -1,9 +1,12 @@
 //! Storage and persistence layer for HERALD
 
+pub mod accession_history;
 pub mod chunk_index;
 pub mod core;
+pub mod format_version;
 pub mod indices;
 pub mod sequence;
+pub mod thin_clone;
 pub mod traits;
 
 // Import backend types from talaria-storage
@@ -20,6 +23,10 @@ pub use traits::{
 };
 
 // Re-export main types
+pub use accession_history::{
+    AccessionEvent, AccessionHistory, AccessionHistoryEntry, AccessionResolution, HistoryFile,
+    HistoryImportStats, ResolutionOutcome, ResolutionStep,
+};
 pub use chunk_index::{
     ChunkAccessTracker, ChunkIndexBuilder, ChunkQuery, ChunkRelationships, DefaultChunkIndex,
     IndexStatistics, OptimizationSuggestion,
@@ -28,5 +35,7 @@ pub use core::{
     ChunkMetadata, DetailedStorageStats, GCResult, GarbageCollectionStats, HeraldStorage,
     StorageChunkInfo, StorageStats, VerificationError, VerificationErrorType,
 };
+pub use format_version::{FormatStatus, RepositoryFormat, CURRENT_FORMAT_VERSION};
 pub use indices::{BloomFilter, IndexStats, SequenceIndices};
 pub use sequence::SequenceStorage;
+pub use thin_clone::{ThinCloneConfig, THIN_CLONE_CONFIG_FILE};
@@ -1,11 +1,17 @@
 /// Canonical sequence storage with cross-database deduplication
 use anyhow::{anyhow, Result};
 use dashmap::DashMap;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
 use std::sync::Arc;
 
-use crate::types::{DatabaseSource, SHA256Hash, SequenceType};
+use super::accession_history::{
+    parse_gi, strip_version, AccessionEvent, AccessionHistory, AccessionResolution,
+    ResolutionOutcome, ResolutionStep, MAX_RESOLUTION_STEPS,
+};
+use super::thin_clone::RemoteChunkSource;
+use crate::performance::metrics;
+use crate::types::{DatabaseSource, SHA256Hash, SequenceType, TaxonId};
 use chrono::Utc;
 use talaria_storage::types::{CanonicalSequence, SequenceRepresentation, SequenceRepresentations};
 
@@ -365,6 +371,8 @@ pub struct SequenceStorage {
     pub(crate) backend: Arc<RocksDBBackend>,
     /// Streaming mode flag - when true, skip index updates to save memory
     streaming_mode: Arc<std::sync::atomic::AtomicBool>,
+    /// Thin clone remote, consulted when a sequence is not local
+    remote: Option<Arc<RemoteChunkSource>>,
 }
 
 impl SequenceStorage {
@@ -402,15 +410,37 @@ impl SequenceStorage {
         Ok(Self {
             backend,
             streaming_mode: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+            remote: None,
         })
     }
 
+    /// Fetch sequences missing locally from a thin clone remote
+    pub fn with_remote(mut self, remote: Option<Arc<RemoteChunkSource>>) -> Self {
+        self.remote = remote;
+        self
+    }
+
     /// Store a sequence with its database-specific representation
     pub fn store_sequence(
         &self,
         sequence: &str,
         header: &str,
         source: DatabaseSource,
+    ) -> Result<SHA256Hash> {
+        self.store_sequence_with_taxon(sequence, header, source, None)
+    }
+
+    /// Store a sequence whose taxon was assigned outside its header
+    ///
+    /// `taxon_id` (e.g. from a taxid mapping table) is recorded on the
+    /// representation and in the taxonomy index; without it the taxon is read
+    /// from the header.
+    pub fn store_sequence_with_taxon(
+        &self,
+        sequence: &str,
+        header: &str,
+        source: DatabaseSource,
+        taxon_id: Option<TaxonId>,
     ) -> Result<SHA256Hash> {
         // Step 1: Compute canonical hash (sequence only)
         let canonical_hash = SHA256Hash::compute(sequence.as_bytes());
@@ -431,13 +461,17 @@ impl SequenceStorage {
             self.backend.store_canonical(&canonical)?;
         }
 
+        if let Some(metrics) = metrics::registry() {
+            metrics.record_sequences(1, sequence.len(), is_new as usize);
+        }
+
         // Step 3: Add database-specific representation
         let representation = SequenceRepresentation {
             source: source.clone(),
             header: header.to_string(),
             accessions: extract_accessions_from_header(header),
             description: extract_description(header),
-            taxon_id: extract_taxon_id(header),
+            taxon_id: taxon_id.or_else(|| extract_taxon_id(header)),
             metadata: parse_metadata(header),
             last_seen: Utc::now(),
         };
@@ -504,6 +538,21 @@ impl SequenceStorage {
     pub fn store_sequences_batch(
         &self,
         sequences: Vec<(&str, &str, DatabaseSource)>,
+    ) -> Result<Vec<(SHA256Hash, bool)>> {
+        self.store_sequences_batch_with_taxa(
+            sequences
+                .into_iter()
+                .map(|(sequence, header, source)| (sequence, header, source, None))
+                .collect(),
+        )
+    }
+
+    /// Batch storage of sequences whose taxa were assigned outside their headers
+    ///
+    /// See [`Self::store_sequence_with_taxon`].
+    pub fn store_sequences_batch_with_taxa(
+        &self,
+        sequences: Vec<(&str, &str, DatabaseSource, Option<TaxonId>)>,
     ) -> Result<Vec<(SHA256Hash, bool)>> {
         use rayon::prelude::*;
         use std::collections::HashSet;
@@ -515,9 +564,9 @@ impl SequenceStorage {
         for chunk in sequences.chunks(HASH_CHUNK_SIZE) {
             let chunk_results: Vec<_> = chunk
                 .par_iter()
-                .map(|(sequence, header, source)| {
+                .map(|(sequence, header, source, taxon_id)| {
                     let canonical_hash = SHA256Hash::compute(sequence.as_bytes());
-                    (sequence, header, source, canonical_hash)
+                    (sequence, header, source, *taxon_id, canonical_hash)
                 })
                 .collect();
             hashes_and_data.extend(chunk_results);
@@ -528,7 +577,7 @@ impl SequenceStorage {
             // Collect all hashes first
             let all_hashes: Vec<_> = hashes_and_data
                 .iter()
-                .map(|(_, _, _, hash)| hash.clone())
+                .map(|(_, _, _, _, hash)| hash.clone())
                 .collect();
 
             // Single batch existence check - no parallel overhead, no individual I/O
@@ -546,17 +595,17 @@ impl SequenceStorage {
         // Now create the final data with existence info
         let sequence_data: Vec<_> = hashes_and_data
             .into_iter()
-            .map(|(sequence, header, source, hash)| {
+            .map(|(sequence, header, source, taxon_id, hash)| {
                 let is_new = !existing_hashes.contains(&hash);
-                (sequence, header, source, hash, is_new)
+                (sequence, header, source, taxon_id, hash, is_new)
             })
             .collect();
 
         // Group new sequences for batch writing
         let new_sequences: Vec<_> = sequence_data
             .iter()
-            .filter(|(_, _, _, _, is_new)| *is_new)
-            .map(|(sequence, _, _, hash, _)| CanonicalSequence {
+            .filter(|(_, _, _, _, _, is_new)| *is_new)
+            .map(|(sequence, _, _, _, hash, _)| CanonicalSequence {
                 sequence_hash: hash.clone(),
                 sequence: sequence.as_bytes().to_vec(),
                 length: sequence.len(),
@@ -570,6 +619,15 @@ impl SequenceStorage {
         // Store all new canonical sequences in batch for improved I/O performance
         self.backend.store_canonical_batch(&new_sequences)?;
 
+        if let Some(metrics) = metrics::registry() {
+            let bytes = sequence_data
+                .iter()
+                .map(|(seq, _, _, _, _, _)| seq.len())
+                .sum();
+            metrics.set_batch_size(sequence_data.len());
+            metrics.record_sequences(sequence_data.len(), bytes, new_sequences.len());
+        }
+
         // Group representations by hash
         let representations_map: Arc<DashMap<SHA256Hash, Vec<SequenceRepresentation>>> =
             Arc::new(DashMap::new());
@@ -578,21 +636,23 @@ impl SequenceStorage {
         const REP_CHUNK_SIZE: usize = 1000;
 
         for chunk in sequence_data.chunks(REP_CHUNK_SIZE) {
-            chunk.par_iter().for_each(|(_, header, source, hash, _)| {
-                let representation = SequenceRepresentation {
-                    source: (*source).clone(),
-                    header: header.to_string(),
-                    accessions: extract_accessions_from_header(header),
-                    description: extract_description(header),
-                    taxon_id: extract_taxon_id(header),
-                    metadata: parse_metadata(header),
-                    last_seen: Utc::now(),
-                };
-                representations_map
-                    .entry(hash.clone())
-                    .or_default()
-                    .push(representation);
-            });
+            chunk
+                .par_iter()
+                .for_each(|(_, header, source, taxon_id, hash, _)| {
+                    let representation = SequenceRepresentation {
+                        source: (*source).clone(),
+                        header: header.to_string(),
+                        accessions: extract_accessions_from_header(header),
+                        description: extract_description(header),
+                        taxon_id: taxon_id.or_else(|| extract_taxon_id(header)),
+                        metadata: parse_metadata(header),
+                        last_seen: Utc::now(),
+                    };
+                    representations_map
+                        .entry(hash.clone())
+                        .or_default()
+                        .push(representation);
+                });
         }
 
         // Load existing representations and merge
@@ -653,7 +713,7 @@ impl SequenceStorage {
         // Return results
         Ok(sequence_data
             .into_iter()
-            .map(|(_, _, _, hash, is_new)| (hash, is_new))
+            .map(|(_, _, _, _, hash, is_new)| (hash, is_new))
             .collect())
     }
 
@@ -713,15 +773,130 @@ impl SequenceStorage {
         Ok(format!("{}\n{}", repr.header, sequence_str))
     }
 
+    /// Get a sequence as FASTA under the header that carries `accession`
+    ///
+    /// Identical sequences share one canonical entry, so the first stored
+    /// header may name another accession. Falls back to that header when no
+    /// representation lists `accession`.
+    pub fn get_sequence_as_fasta_for_accession(
+        &self,
+        hash: &SHA256Hash,
+        accession: &str,
+    ) -> Result<String> {
+        let canonical = self.backend.load_canonical(hash)?;
+        let representations = self.backend.load_representations(hash)?;
+
+        let wanted = strip_version(accession);
+        let repr = representations
+            .representations()
+            .iter()
+            .find(|r| r.accessions.iter().any(|a| strip_version(a) == wanted))
+            .or_else(|| representations.representations().first())
+            .ok_or_else(|| anyhow!("No representation found"))?;
+
+        let sequence_str = String::from_utf8(canonical.sequence.clone())?;
+        Ok(format!("{}\n{}", repr.header, sequence_str))
+    }
+
     /// Find sequence by accession
+    ///
+    /// Accessions that are no longer stored are followed through the
+    /// accession history to the sequence they were merged into or replaced by.
     pub fn find_by_accession(&self, accession: &str) -> Result<Option<SHA256Hash>> {
+        if let Some(hash) = self.lookup_accession(accession) {
+            return Ok(Some(hash));
+        }
+        Ok(self.resolve_accession(accession)?.hash())
+    }
+
+    /// Accession history index (merged, deleted and replaced accessions)
+    pub fn accession_history(&self) -> AccessionHistory {
+        AccessionHistory::new(self.get_rocksdb())
+    }
+
+    /// Follow an accession through its history to the current sequence
+    ///
+    /// Accepts versioned accessions (`NP_000001.2`) and GI numbers
+    /// (`gi|123456`). The steps record each merge or replacement taken.
+    pub fn resolve_accession(&self, query: &str) -> Result<AccessionResolution> {
+        let history = self.accession_history();
+        let mut steps = Vec::new();
+
+        let mut accession = strip_version(query).to_string();
+        if self.lookup_accession(query).is_none() && self.lookup_accession(&accession).is_none() {
+            if let Some(gi) = parse_gi(query) {
+                match history.gi_accession(gi)? {
+                    Some(found) => accession = found,
+                    None => {
+                        return Ok(AccessionResolution {
+                            query: query.to_string(),
+                            steps,
+                            outcome: ResolutionOutcome::Unknown,
+                        })
+                    }
+                }
+            }
+        }
+
+        let mut seen = HashSet::new();
+        let outcome = loop {
+            let hash = if steps.is_empty() {
+                self.lookup_accession(query)
+                    .or_else(|| self.lookup_accession(&accession))
+            } else {
+                self.lookup_accession(&accession)
+            };
+            if let Some(hash) = hash {
+                break ResolutionOutcome::Current { accession, hash };
+            }
+            if !seen.insert(accession.clone()) || steps.len() >= MAX_RESOLUTION_STEPS {
+                break ResolutionOutcome::NotStored { accession };
+            }
+            let Some(entry) = history.latest(&accession)? else {
+                break if steps.is_empty() {
+                    ResolutionOutcome::Unknown
+                } else {
+                    ResolutionOutcome::NotStored { accession }
+                };
+            };
+
+            let event = entry.event.clone();
+            let release = entry.release.clone();
+            steps.push(ResolutionStep {
+                accession: accession.clone(),
+                entry,
+            });
+            match event {
+                AccessionEvent::Merged { into } => accession = into,
+                AccessionEvent::Replaced { by } => accession = by,
+                AccessionEvent::Deleted => {
+                    break ResolutionOutcome::Deleted { accession, release };
+                }
+                AccessionEvent::Demerged { into } => {
+                    break ResolutionOutcome::Ambiguous {
+                        accession,
+                        candidates: into,
+                    };
+                }
+            }
+        };
+
+        Ok(AccessionResolution {
+            query: query.to_string(),
+            steps,
+            outcome,
+        })
+    }
+
+    /// Exact lookup in the accession index
+    fn lookup_accession(&self, accession: &str) -> Option<SHA256Hash> {
         let key = format!("acc:{}", accession);
         if let Ok(Some(data)) = self.backend.get_index(&key) {
             if let Ok(entry) = bincode::deserialize::<AccessionEntry>(&data) {
-                return Ok(Some(entry.sequence_hash));
+                return Some(entry.sequence_hash);
             }
         }
-        Ok(None)
+        None
     }
 
     /// Find sequences by taxonomy
@@ -736,8 +911,29 @@ impl SequenceStorage {
     }
 
     /// Load a canonical sequence by hash
+    ///
+    /// Thin clones fetch sequences they do not hold, together with their
+    /// representations, and keep them locally.
     pub fn load_canonical(&self, hash: &SHA256Hash) -> Result<CanonicalSequence> {
-        self.backend.load_canonical(hash)
+        match &self.remote {
+            Some(remote) if !self.backend.sequence_exists(hash)? => {
+                self.fetch_remote_sequence(remote, hash)
+            }
+            _ => self.backend.load_canonical(hash),
+        }
+    }
+
+    fn fetch_remote_sequence(
+        &self,
+        remote: &RemoteChunkSource,
+        hash: &SHA256Hash,
+    ) -> Result<CanonicalSequence> {
+        let canonical = remote.fetch_canonical(hash)?;
+        let representations = remote.fetch_representations(hash)?;
+        // Representations first, so a stored sequence always has them
+        self.backend.store_representations(&representations)?;
+        self.backend.store_canonical(&canonical)?;
+        Ok(canonical)
     }
 
     /// Check if a canonical sequence exists
@@ -752,7 +948,13 @@ impl SequenceStorage {
 
     /// Load representations for a sequence
     pub fn load_representations(&self, hash: &SHA256Hash) -> Result<SequenceRepresentations> {
-        self.backend.load_representations(hash)
+        match &self.remote {
+            Some(remote) if !self.backend.sequence_exists(hash)? => {
+                self.fetch_remote_sequence(remote, hash)?;
+                self.backend.load_representations(hash)
+            }
+            _ => self.backend.load_representations(hash),
+        }
     }
 
     /// List all sequence hashes
@@ -970,6 +1172,147 @@ mod tests {
         assert!(seq_storage.find_by_accession("seq2").unwrap().is_some());
     }
 
+    #[test]
+    fn test_resolve_retired_accessions() {
+        use crate::storage::accession_history::parse_uniprot_sec_ac;
+
+        let temp_dir = TempDir::new().unwrap();
+        let seq_storage = SequenceStorage::new(temp_dir.path()).unwrap();
+        let source = DatabaseSource::UniProt(talaria_core::UniProtDatabase::SwissProt);
+        let hash = seq_storage
+            .store_sequence("MVALPRWFDK", ">sp|P12345|PROT_HUMAN Protein", source)
+            .unwrap();
+
+        let sec_ac = "Release:     2023_02 of 03-May-2023\nQ9XYZ1        P12345\n";
+        let history = seq_storage.accession_history();
+        let file = parse_uniprot_sec_ac(std::io::Cursor::new(sec_ac)).unwrap();
+        history.import(&file, "2023_02", "uniprot").unwrap();
+        history
+            .record("P99999", AccessionEvent::Deleted, "2024_01", "uniprot")
+            .unwrap();
+        // Re-importing a later release keeps the original date
+        history.import(&file, "2023_03", "uniprot").unwrap();
+
+        let resolution = seq_storage.resolve_accession("Q9XYZ1").unwrap();
+        assert_eq!(resolution.hash(), Some(hash));
+        assert_eq!(
+            resolution.describe(),
+            "Q9XYZ1 was merged into P12345 in 2023_02"
+        );
+        assert_eq!(seq_storage.find_by_accession("Q9XYZ1").unwrap(), Some(hash));
+
+        let deleted = seq_storage.resolve_accession("P99999").unwrap();
+        assert!(matches!(deleted.outcome, ResolutionOutcome::Deleted { .. }));
+        assert_eq!(deleted.describe(), "P99999 was deleted in 2024_01");
+        assert_eq!(seq_storage.find_by_accession("P99999").unwrap(), None);
+
+        let unknown = seq_storage.resolve_accession("P00000").unwrap();
+        assert_eq!(unknown.outcome, ResolutionOutcome::Unknown);
+    }
+
+    #[test]
+    fn test_import_groups_events_by_accession() {
+        use crate::storage::accession_history::HistoryFile;
+
+        let temp_dir = TempDir::new().unwrap();
+        let seq_storage = SequenceStorage::new(temp_dir.path()).unwrap();
+        let history = seq_storage.accession_history();
+        let file = HistoryFile {
+            release: None,
+            events: vec![
+                ("NP_000001.1".into(), AccessionEvent::Deleted),
+                (
+                    "NP_000002.1".into(),
+                    AccessionEvent::Replaced {
+                        by: "NP_000003.1".into(),
+                    },
+                ),
+                ("NP_000001.1".into(), AccessionEvent::Deleted),
+                (
+                    "NP_000001.1".into(),
+                    AccessionEvent::Replaced {
+                        by: "NP_000004.1".into(),
+                    },
+                ),
+            ],
+            gi: vec![(42, "NP_000002.1".into())],
+        };
+
+        let stats = history.import(&file, "2024_01", "ncbi").unwrap();
+        assert_eq!(
+            (stats.recorded, stats.unchanged, stats.gi_numbers),
+            (3, 1, 1)
+        );
+        let events: Vec<_> = history
+            .history("NP_000001.1")
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.event)
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                AccessionEvent::Deleted,
+                AccessionEvent::Replaced {
+                    by: "NP_000004.1".into()
+                }
+            ]
+        );
+        assert_eq!(
+            history.gi_accession(42).unwrap().as_deref(),
+            Some("NP_000002.1")
+        );
+    }
+
+    #[test]
+    fn test_assigned_taxon_reaches_representation_and_index() {
+        let temp_dir = TempDir::new().unwrap();
+        let seq_storage = SequenceStorage::new(temp_dir.path()).unwrap();
+        let source = DatabaseSource::Custom("custom/test".to_string());
+
+        let results = seq_storage
+            .store_sequences_batch_with_taxa(vec![
+                (
+                    "MVALPRWFDK",
+                    ">seq1 no taxon in header",
+                    source.clone(),
+                    Some(TaxonId(562)),
+                ),
+                ("MKTAYIAKQR", ">seq2 Protein OX=9606", source.clone(), None),
+            ])
+            .unwrap();
+        let (mapped, from_header) = (results[0].0, results[1].0);
+
+        let taxon = |hash: &SHA256Hash| {
+            seq_storage
+                .load_representations(hash)
+                .unwrap()
+                .representations[0]
+                .taxon_id
+        };
+        assert_eq!(taxon(&mapped), Some(TaxonId(562)));
+        assert_eq!(taxon(&from_header), Some(TaxonId(9606)));
+        assert_eq!(
+            seq_storage.find_by_taxon(TaxonId(562)).unwrap(),
+            vec![mapped]
+        );
+
+        // Assigning a taxon later updates the existing representation
+        seq_storage
+            .store_sequence_with_taxon(
+                "MVALPRWFDK",
+                ">seq1 no taxon in header",
+                source,
+                Some(TaxonId(511145)),
+            )
+            .unwrap();
+        assert_eq!(taxon(&mapped), Some(TaxonId(511145)));
+        assert_eq!(
+            seq_storage.find_by_taxon(TaxonId(511145)).unwrap(),
+            vec![mapped]
+        );
+    }
+
     #[test]
     fn test_write_avoidance_optimization() {
         let temp_dir = TempDir::new().unwrap();
@@ -1040,6 +1383,29 @@ mod tests {
             .is_some());
     }
 
+    #[test]
+    fn test_fasta_header_follows_requested_accession() {
+        let temp_dir = TempDir::new().unwrap();
+        let seq_storage = SequenceStorage::new(temp_dir.path()).unwrap();
+        let source = DatabaseSource::Custom("custom/test".to_string());
+
+        let hash = seq_storage
+            .store_sequence("MVALPRWFDK", ">first first copy", source.clone())
+            .unwrap();
+        seq_storage
+            .store_sequence("MVALPRWFDK", ">second second copy", source)
+            .unwrap();
+
+        let fasta = seq_storage
+            .get_sequence_as_fasta_for_accession(&hash, "second")
+            .unwrap();
+        assert_eq!(fasta, ">second second copy\nMVALPRWFDK");
+        let fasta = seq_storage
+            .get_sequence_as_fasta_for_accession(&hash, "first")
+            .unwrap();
+        assert_eq!(fasta, ">first first copy\nMVALPRWFDK");
+    }
+
     #[test]
     fn test_batch_index_saving() {
         let temp_dir = TempDir::new().unwrap();
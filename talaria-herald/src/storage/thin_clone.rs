@@ -0,0 +1,708 @@
+/// Lazy remote-backed chunk storage ("thin clone")
+///
+/// A thin clone keeps manifests and indices locally but not necessarily the
+/// chunk data. The remote holds the layout written by `publish` (and uploaded
+/// by `herald sync --publish`): every chunk, uncompressed, at
+/// `chunks/{first 2 hex}/{remaining 62 hex}`, the canonical sequences and
+/// representations those chunks reference at `sequences/...` and
+/// `representations/...` under the same split, and `manifests.bin` with the
+/// version manifests, aliases and listing metadata. The manifests are copied
+/// in when the clone is created (`HeraldStorage::bootstrap_from_remote`).
+///
+/// When a chunk is missing, `HeraldStorage::get_chunk` fetches it from the
+/// remote, verifies it against its hash and caches it in the local chunk
+/// store. Chunks fetched this way are tracked in least-recently-used order and
+/// evicted once the cache exceeds its size budget; chunks written locally are
+/// never evicted. Sequences are fetched the same way by `SequenceStorage` and
+/// kept, like any other sequence in the shared store.
+use super::HeraldStorage;
+use crate::remote::{remote_chunk_key, ChunkClient};
+use crate::types::SHA256Hash;
+use crate::ChunkManifest;
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use talaria_storage::types::{CanonicalSequence, SequenceRepresentations};
+
+/// Thin clone settings at the repository root
+pub const THIN_CLONE_CONFIG_FILE: &str = "thin_clone.json";
+
+/// Fetched chunks and their recency, kept next to the config
+const CACHE_STATE_FILE: &str = "thin_clone_cache.json";
+
+/// Fetches between writes of the cache state
+const SAVE_INTERVAL: usize = 32;
+
+/// Existence checks in flight at once when checking many chunks
+const EXISTS_PARALLELISM: usize = 16;
+
+/// Published manifests, next to `chunks/` on the remote
+pub const MANIFESTS_FILE: &str = "manifests.bin";
+
+/// Everything besides chunks a thin clone needs to list and resolve versions
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct PublishedManifests {
+    /// Entries of the manifests column: version manifests, aliases and the
+    /// records kept with each version
+    pub manifests: Vec<(String, Vec<u8>)>,
+    /// `db_meta:{source}:{dataset}` listing entries
+    pub database_metadata: Vec<(String, Vec<u8>)>,
+}
+
+/// Remote key of a canonical sequence
+fn remote_sequence_key(hash: &SHA256Hash) -> String {
+    let hex = hash.to_hex();
+    format!("sequences/{}/{}", &hex[..2], &hex[2..])
+}
+
+/// Remote key of the representations of a canonical sequence
+fn remote_representations_key(hash: &SHA256Hash) -> String {
+    let hex = hash.to_hex();
+    format!("representations/{}/{}", &hex[..2], &hex[2..])
+}
+
+/// Outcome of writing the thin clone layout
+#[derive(Debug, Clone, Default)]
+pub struct PublishReport {
+    pub chunks_written: usize,
+    /// Chunks already present from an earlier publish
+    pub chunks_unchanged: usize,
+    pub sequences_written: usize,
+    /// References to sequences already present, from an earlier publish or
+    /// another chunk
+    pub sequences_unchanged: usize,
+    /// Representation sets written because they are new or were seen again
+    /// since they were last written
+    pub representations_written: usize,
+    pub manifest_entries: usize,
+}
+
+/// Write `data` to `path` through a temporary file
+fn write_atomically(path: &Path, data: &[u8]) -> Result<()> {
+    fs::create_dir_all(path.parent().expect("remote keys have a directory"))?;
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, data)?;
+    fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+/// Write the representations of a sequence unless `path` already holds them
+///
+/// Every representation added or seen again moves its `last_seen`, so a file
+/// written after the newest of them is current and is not read back. Only
+/// when both fall in the same second (`last_seen` has second resolution) are
+/// the contents compared.
+fn write_representations(path: &Path, representations: &SequenceRepresentations) -> Result<bool> {
+    let data = rmp_serde::to_vec(representations)?;
+    if let Ok(written) = fs::metadata(path).and_then(|meta| meta.modified()) {
+        let written = DateTime::<Utc>::from(written).timestamp();
+        let newest = representations
+            .representations
+            .iter()
+            .map(|representation| representation.last_seen.timestamp())
+            .max()
+            .unwrap_or(i64::MIN);
+        if newest < written || (newest == written && fs::read(path)? == data) {
+            return Ok(false);
+        }
+    }
+    write_atomically(path, &data)?;
+    Ok(true)
+}
+
+/// Write the layout thin clones read into `dest`
+///
+/// Chunks are walked one at a time and the sequences each references are
+/// written as they come, so memory does not grow with the repository.
+/// Chunks and canonical sequences are content-addressed, so those already
+/// present are left alone and republishing only writes what is new.
+/// Representations are rewritten only when one of them was added or seen
+/// since the published file was written. `manifests.bin` is always rewritten.
+pub fn publish(storage: &HeraldStorage, dest: &Path) -> Result<PublishReport> {
+    let mut report = PublishReport::default();
+    let sequences = &storage.sequence_storage;
+    for hash in storage.list_all_chunks()? {
+        let data = storage.get_chunk(&hash)?;
+        let path = dest.join(remote_chunk_key(&hash));
+        if path.exists() {
+            report.chunks_unchanged += 1;
+        } else {
+            write_atomically(&path, &data)?;
+            report.chunks_written += 1;
+        }
+
+        let Ok(manifest) = rmp_serde::from_slice::<ChunkManifest>(&data) else {
+            continue;
+        };
+        for sequence in &manifest.sequence_refs {
+            let path = dest.join(remote_sequence_key(sequence));
+            if path.exists() {
+                report.sequences_unchanged += 1;
+            } else {
+                write_atomically(
+                    &path,
+                    &rmp_serde::to_vec(&sequences.load_canonical(sequence)?)?,
+                )?;
+                report.sequences_written += 1;
+            }
+
+            if write_representations(
+                &dest.join(remote_representations_key(sequence)),
+                &sequences.load_representations(sequence)?,
+            )? {
+                report.representations_written += 1;
+            }
+        }
+    }
+
+    let rocksdb = storage.sequence_storage.get_rocksdb();
+    let published = PublishedManifests {
+        manifests: rocksdb.list_manifests()?,
+        database_metadata: rocksdb.list_database_metadata()?,
+    };
+    report.manifest_entries = published.manifests.len();
+    fs::create_dir_all(dest)?;
+    write_atomically(&dest.join(MANIFESTS_FILE), &bincode::serialize(&published)?)?;
+
+    Ok(report)
+}
+
+/// Remote and cache budget of a thin clone
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThinCloneConfig {
+    /// Remote serving `chunks/{prefix}/{rest}` (s3://, gs://, https://, file://)
+    pub remote_url: String,
+    /// Maximum bytes of fetched chunks kept locally
+    pub cache_budget: u64,
+}
+
+impl ThinCloneConfig {
+    pub fn new(remote_url: impl Into<String>, cache_budget: u64) -> Self {
+        Self {
+            remote_url: remote_url.into(),
+            cache_budget,
+        }
+    }
+
+    pub fn path(base_path: &Path) -> PathBuf {
+        base_path.join(THIN_CLONE_CONFIG_FILE)
+    }
+
+    /// Thin clone settings, if the repository is a thin clone
+    pub fn load(base_path: &Path) -> Result<Option<Self>> {
+        let path = Self::path(base_path);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let config = serde_json::from_str(&fs::read_to_string(&path)?)
+            .with_context(|| format!("Invalid thin clone config {}", path.display()))?;
+        Ok(Some(config))
+    }
+
+    pub fn save(&self, base_path: &Path) -> Result<()> {
+        fs::create_dir_all(base_path)?;
+        fs::write(Self::path(base_path), serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Turn a thin clone into a regular repository
+    ///
+    /// Chunks fetched so far stay local and are no longer evicted.
+    pub fn remove(base_path: &Path) -> Result<()> {
+        for path in [Self::path(base_path), base_path.join(CACHE_STATE_FILE)] {
+            if path.exists() {
+                fs::remove_file(path)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Least-recently-used accounting of fetched chunks
+#[derive(Debug, Default)]
+pub struct ChunkLru {
+    entries: HashMap<SHA256Hash, LruEntry>,
+    clock: u64,
+    total_bytes: u64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct LruEntry {
+    size: u64,
+    last_used: u64,
+}
+
+/// Serialized form of `ChunkLru`; JSON maps cannot use hashes as keys
+#[derive(Serialize, Deserialize)]
+struct CacheState {
+    clock: u64,
+    entries: Vec<(SHA256Hash, LruEntry)>,
+}
+
+impl ChunkLru {
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn total_bytes(&self) -> u64 {
+        self.total_bytes
+    }
+
+    pub fn contains(&self, hash: &SHA256Hash) -> bool {
+        self.entries.contains_key(hash)
+    }
+
+    /// Mark a tracked chunk as used; untracked chunks are ignored
+    pub fn touch(&mut self, hash: &SHA256Hash) {
+        self.clock += 1;
+        if let Some(entry) = self.entries.get_mut(hash) {
+            entry.last_used = self.clock;
+        }
+    }
+
+    pub fn insert(&mut self, hash: SHA256Hash, size: u64) {
+        self.clock += 1;
+        let entry = LruEntry {
+            size,
+            last_used: self.clock,
+        };
+        if let Some(previous) = self.entries.insert(hash, entry) {
+            self.total_bytes -= previous.size;
+        }
+        self.total_bytes += size;
+    }
+
+    pub fn remove(&mut self, hash: &SHA256Hash) {
+        if let Some(entry) = self.entries.remove(hash) {
+            self.total_bytes -= entry.size;
+        }
+    }
+
+    /// Drop least recently used chunks until at most `budget` bytes remain
+    ///
+    /// `keep` is never evicted, so a chunk larger than the budget can still be
+    /// served once. Returns the evicted hashes, oldest first.
+    pub fn evict_to(&mut self, budget: u64, keep: Option<&SHA256Hash>) -> Vec<SHA256Hash> {
+        if self.total_bytes <= budget {
+            return Vec::new();
+        }
+
+        let mut by_age: Vec<(u64, SHA256Hash)> = self
+            .entries
+            .iter()
+            .filter(|(hash, _)| Some(*hash) != keep)
+            .map(|(hash, entry)| (entry.last_used, *hash))
+            .collect();
+        by_age.sort();
+
+        let mut evicted = Vec::new();
+        for (_, hash) in by_age {
+            if self.total_bytes <= budget {
+                break;
+            }
+            self.remove(&hash);
+            evicted.push(hash);
+        }
+        evicted
+    }
+
+    fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let state: CacheState = serde_json::from_str(&fs::read_to_string(path)?)?;
+        let total_bytes = state.entries.iter().map(|(_, entry)| entry.size).sum();
+        Ok(Self {
+            entries: state.entries.into_iter().collect(),
+            clock: state.clock,
+            total_bytes,
+        })
+    }
+
+    fn save(&self, path: &Path) -> Result<()> {
+        let state = CacheState {
+            clock: self.clock,
+            entries: self.entries.iter().map(|(h, e)| (*h, *e)).collect(),
+        };
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, serde_json::to_string(&state)?)?;
+        fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+}
+
+/// Remote side of a thin clone
+pub struct RemoteChunkSource {
+    config: ThinCloneConfig,
+    client: ChunkClient,
+    runtime: Option<tokio::runtime::Runtime>,
+    lru: Mutex<(ChunkLru, usize)>,
+    state_path: PathBuf,
+    /// Chunks the remote is known to hold; the remote only ever gains chunks,
+    /// so a hit never needs asking again
+    present: Mutex<HashSet<SHA256Hash>>,
+}
+
+impl RemoteChunkSource {
+    pub fn open(base_path: &Path, config: ThinCloneConfig) -> Result<Self> {
+        let client = ChunkClient::new(Some(config.remote_url.clone()))?;
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(2)
+            .enable_all()
+            .build()?;
+        let state_path = base_path.join(CACHE_STATE_FILE);
+        let lru = ChunkLru::load(&state_path).unwrap_or_else(|e| {
+            tracing::warn!("Ignoring unreadable thin clone cache state: {}", e);
+            ChunkLru::default()
+        });
+
+        Ok(Self {
+            config,
+            client,
+            runtime: Some(runtime),
+            lru: Mutex::new((lru, 0)),
+            state_path,
+            present: Mutex::new(HashSet::new()),
+        })
+    }
+
+    pub fn config(&self) -> &ThinCloneConfig {
+        &self.config
+    }
+
+    /// Number and total stored size of fetched chunks held locally
+    pub fn cached(&self) -> (usize, u64) {
+        let lru = self.lru.lock();
+        (lru.0.len(), lru.0.total_bytes())
+    }
+
+    /// Run a client request to completion from synchronous code
+    fn block_on<T: Send>(
+        &self,
+        request: impl std::future::Future<Output = Result<T>> + Send,
+    ) -> Result<T> {
+        let runtime = self
+            .runtime
+            .as_ref()
+            .expect("runtime is only taken on drop");
+
+        // Blocking on our runtime from inside another runtime's thread panics
+        if tokio::runtime::Handle::try_current().is_ok() {
+            std::thread::scope(|scope| {
+                scope
+                    .spawn(|| runtime.block_on(request))
+                    .join()
+                    .unwrap_or_else(|_| Err(anyhow::anyhow!("Remote request thread panicked")))
+            })
+        } else {
+            runtime.block_on(request)
+        }
+    }
+
+    /// Download and verify a chunk
+    pub fn fetch(&self, hash: &SHA256Hash) -> Result<Vec<u8>> {
+        self.block_on(self.client.download_chunk(hash))
+            .with_context(|| {
+                format!(
+                    "Chunk {} is not local and could not be fetched from {}",
+                    hash, self.config.remote_url
+                )
+            })
+    }
+
+    /// Download and verify a canonical sequence
+    pub fn fetch_canonical(&self, hash: &SHA256Hash) -> Result<CanonicalSequence> {
+        let data = self
+            .block_on(self.client.download_file(&remote_sequence_key(hash)))
+            .with_context(|| {
+                format!(
+                    "Sequence {} is not local and could not be fetched from {}",
+                    hash, self.config.remote_url
+                )
+            })?;
+        let canonical: CanonicalSequence = rmp_serde::from_slice(&data)
+            .with_context(|| format!("Published sequence {} is unreadable", hash))?;
+        if SHA256Hash::compute(&canonical.sequence) != *hash {
+            return Err(anyhow!(
+                "Published sequence {} does not match its hash",
+                hash
+            ));
+        }
+        Ok(canonical)
+    }
+
+    /// Download the representations of a canonical sequence
+    pub fn fetch_representations(&self, hash: &SHA256Hash) -> Result<SequenceRepresentations> {
+        let data = self
+            .block_on(self.client.download_file(&remote_representations_key(hash)))
+            .with_context(|| {
+                format!(
+                    "Representations of {} could not be fetched from {}",
+                    hash, self.config.remote_url
+                )
+            })?;
+        let representations: SequenceRepresentations = rmp_serde::from_slice(&data)
+            .with_context(|| format!("Published representations of {} are unreadable", hash))?;
+        if representations.canonical_hash != *hash {
+            return Err(anyhow!(
+                "Published representations of {} belong to {}",
+                hash,
+                representations.canonical_hash
+            ));
+        }
+        Ok(representations)
+    }
+
+    /// Whether the remote holds a chunk
+    pub fn contains(&self, hash: &SHA256Hash) -> Result<bool> {
+        if self.present.lock().contains(hash) {
+            return Ok(true);
+        }
+        let found = self.block_on(self.client.chunk_exists(hash))?;
+        if found {
+            self.present.lock().insert(*hash);
+        }
+        Ok(found)
+    }
+
+    /// Whether the remote holds each chunk, asking about the unknown ones
+    /// concurrently
+    pub fn contains_many(&self, hashes: &[SHA256Hash]) -> Result<Vec<bool>> {
+        let unknown: Vec<SHA256Hash> = {
+            let present = self.present.lock();
+            hashes
+                .iter()
+                .filter(|hash| !present.contains(*hash))
+                .copied()
+                .collect()
+        };
+        let found = self.block_on(self.client.chunks_exist(&unknown, EXISTS_PARALLELISM))?;
+
+        let mut present = self.present.lock();
+        present.extend(
+            unknown
+                .iter()
+                .zip(found)
+                .filter(|(_, found)| *found)
+                .map(|(hash, _)| *hash),
+        );
+        Ok(hashes.iter().map(|hash| present.contains(hash)).collect())
+    }
+
+    /// Download the published manifests
+    pub fn fetch_manifests(&self) -> Result<PublishedManifests> {
+        let data = self
+            .block_on(self.client.download_file(MANIFESTS_FILE))
+            .with_context(|| {
+                format!(
+                    "{} has no published manifests; publish it with `herald sync --publish`",
+                    self.config.remote_url
+                )
+            })?;
+        bincode::deserialize(&data).context("Published manifests are unreadable")
+    }
+
+    /// Track a fetched chunk and return the chunks to evict
+    pub fn record_fetched(&self, hash: &SHA256Hash, size: u64) -> Vec<SHA256Hash> {
+        let mut guard = self.lru.lock();
+        let (lru, unsaved) = &mut *guard;
+        lru.insert(*hash, size);
+        let evicted = lru.evict_to(self.config.cache_budget, Some(hash));
+
+        *unsaved += 1;
+        if *unsaved >= SAVE_INTERVAL || !evicted.is_empty() {
+            if let Err(e) = lru.save(&self.state_path) {
+                tracing::warn!("Failed to save thin clone cache state: {}", e);
+            }
+            *unsaved = 0;
+        }
+        evicted
+    }
+
+    pub fn touch(&self, hash: &SHA256Hash) {
+        self.lru.lock().0.touch(hash);
+    }
+
+    /// Evict fetched chunks until at most `budget` bytes remain
+    pub fn evict_to(&self, budget: u64) -> Result<Vec<SHA256Hash>> {
+        let mut guard = self.lru.lock();
+        let evicted = guard.0.evict_to(budget, None);
+        guard.0.save(&self.state_path)?;
+        guard.1 = 0;
+        Ok(evicted)
+    }
+}
+
+impl Drop for RemoteChunkSource {
+    fn drop(&mut self) {
+        let guard = self.lru.lock();
+        if guard.1 > 0 {
+            if let Err(e) = guard.0.save(&self.state_path) {
+                tracing::warn!("Failed to save thin clone cache state: {}", e);
+            }
+        }
+        drop(guard);
+
+        // Dropping a runtime inside an async context panics
+        if let Some(runtime) = self.runtime.take() {
+            runtime.shutdown_background();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operations::FastaAssembler;
+    use crate::types::{ChunkClassification, DatabaseSource, TaxonId};
+
+    #[test]
+    fn test_lru_evicts_least_recently_used() {
+        let a = SHA256Hash::compute(b"a");
+        let b = SHA256Hash::compute(b"b");
+        let c = SHA256Hash::compute(b"c");
+
+        let mut lru = ChunkLru::default();
+        lru.insert(a, 40);
+        lru.insert(b, 40);
+        lru.touch(&a);
+        lru.insert(c, 40);
+        assert_eq!(lru.total_bytes(), 120);
+
+        assert_eq!(lru.evict_to(100, Some(&c)), vec![b]);
+        assert!(lru.contains(&a) && lru.contains(&c));
+        assert_eq!(lru.total_bytes(), 80);
+
+        // The chunk being served survives even when it alone exceeds the budget
+        assert_eq!(lru.evict_to(10, Some(&c)), vec![a]);
+        assert_eq!(lru.len(), 1);
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_publish_and_thin_clone_round_trip() {
+        let origin_dir = tempfile::TempDir::new().unwrap();
+        let origin = HeraldStorage::with_sequences_dir(
+            &origin_dir.path().join("repo"),
+            &origin_dir.path().join("sequences"),
+        )
+        .unwrap();
+        let residues = "MKTAYIAKQRQISFVKSHFSRQ".repeat(20);
+        let header = "sp|P12345|TEST_HUMAN Test protein OX=9606";
+        let sequence = origin
+            .sequence_storage
+            .store_sequence(&residues, header, DatabaseSource::Custom("test".into()))
+            .unwrap();
+        let manifest = ChunkManifest {
+            chunk_hash: SHA256Hash::compute(sequence.as_bytes()),
+            sequence_refs: vec![sequence],
+            taxon_ids: vec![TaxonId(9606)],
+            chunk_type: ChunkClassification::Full,
+            total_size: residues.len(),
+            sequence_count: 1,
+            created_at: chrono::Utc::now(),
+            taxonomy_version: SHA256Hash::compute(b""),
+            sequence_version: SHA256Hash::compute(b""),
+        };
+        let data = rmp_serde::to_vec(&manifest).unwrap();
+        let chunk = origin.store_chunk(&data, true).unwrap();
+        let rocksdb = origin.sequence_storage.get_rocksdb();
+        rocksdb
+            .put_manifest("manifest:custom:test:20240101_000000", b"manifest")
+            .unwrap();
+        rocksdb
+            .put_manifest("alias:custom:test:current", b"20240101_000000")
+            .unwrap();
+        rocksdb
+            .put_database_metadata("custom", "test", b"listing")
+            .unwrap();
+        let manifest_entries = rocksdb.list_manifests().unwrap().len();
+
+        let remote_dir = tempfile::TempDir::new().unwrap();
+        let report = publish(&origin, remote_dir.path()).unwrap();
+        assert_eq!(report.chunks_written, 1);
+        assert_eq!(report.sequences_written, 1);
+        assert_eq!(report.representations_written, 1);
+        assert_eq!(report.manifest_entries, manifest_entries);
+        assert!(remote_dir.path().join(remote_chunk_key(&chunk)).is_file());
+        let republished = publish(&origin, remote_dir.path()).unwrap();
+        assert_eq!(republished.chunks_unchanged, 1);
+        assert_eq!(republished.sequences_unchanged, 1);
+        assert_eq!(republished.representations_written, 0);
+
+        // Another database with the same sequence adds a representation
+        origin
+            .sequence_storage
+            .store_sequence(
+                &residues,
+                "tr|Q67890|OTHER_HUMAN Same protein OX=9606",
+                DatabaseSource::Custom("other".into()),
+            )
+            .unwrap();
+        let republished = publish(&origin, remote_dir.path()).unwrap();
+        assert_eq!(republished.sequences_written, 0);
+        assert_eq!(republished.representations_written, 1);
+        drop(origin);
+
+        let clone_dir = tempfile::TempDir::new().unwrap();
+        ThinCloneConfig::new(format!("file://{}", remote_dir.path().display()), 1 << 20)
+            .save(clone_dir.path())
+            .unwrap();
+        let clone = HeraldStorage::with_sequences_dir(
+            clone_dir.path(),
+            &clone_dir.path().join("sequences"),
+        )
+        .unwrap();
+        assert_eq!(clone.bootstrap_from_remote().unwrap(), manifest_entries);
+        let rocksdb = clone.sequence_storage.get_rocksdb();
+        assert_eq!(
+            rocksdb.get_manifest("alias:custom:test:current").unwrap(),
+            Some(b"20240101_000000".to_vec())
+        );
+        assert!(rocksdb
+            .get_database_metadata("custom", "test")
+            .unwrap()
+            .is_some());
+
+        // Remote-only chunks exist before they are fetched
+        assert!(clone.has_chunk(&chunk));
+        let missing = SHA256Hash::compute(b"missing");
+        assert!(!clone.has_chunk(&missing));
+        assert_eq!(clone.missing_chunks(&[chunk, missing]), vec![missing]);
+        assert!(!clone.sequence_storage.canonical_exists(&sequence).unwrap());
+
+        // Sequences behind a fetched chunk are fetched as it is assembled
+        let assembled = FastaAssembler::new(&clone)
+            .assemble_from_chunks(&[chunk])
+            .unwrap();
+        assert_eq!(assembled.len(), 1);
+        assert_eq!(assembled[0].id, "P12345");
+        assert_eq!(assembled[0].sequence, residues.as_bytes());
+        assert_eq!(clone.remote_source().unwrap().cached().0, 1);
+        assert!(clone.sequence_storage.canonical_exists(&sequence).unwrap());
+    }
+
+    #[test]
+    fn test_lru_state_roundtrip() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join(CACHE_STATE_FILE);
+
+        let mut lru = ChunkLru::default();
+        lru.insert(SHA256Hash::compute(b"a"), 10);
+        lru.insert(SHA256Hash::compute(b"b"), 20);
+        lru.save(&path).unwrap();
+
+        let loaded = ChunkLru::load(&path).unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded.total_bytes(), 30);
+        assert_eq!(loaded.clock, lru.clock);
+    }
+}
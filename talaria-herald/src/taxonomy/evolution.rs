@@ -15,7 +15,7 @@ pub struct TaxonomyEvolutionTracker {
     /// Cache of evolution histories by entity
     evolution_cache: HashMap<String, EvolutionHistory>,
     /// Index of reclassification events by date
-    reclassification_index: BTreeMap<DateTime<Utc>, Vec<ReclassificationEvent>>,
+    reclassifications: ReclassificationLog,
 }
 
 #[derive(Debug, Clone)]
@@ -33,7 +33,7 @@ impl TaxonomyEvolutionTracker {
         Self {
             repository,
             evolution_cache: HashMap::new(),
-            reclassification_index: BTreeMap::new(),
+            reclassifications: ReclassificationLog::new(),
         }
     }
 
@@ -98,16 +98,13 @@ impl TaxonomyEvolutionTracker {
                     });
 
                     // Track reclassification event
-                    self.reclassification_index
-                        .entry(snapshot_date)
-                        .or_default()
-                        .push(ReclassificationEvent {
-                            date: snapshot_date,
-                            sequence_id: entity_id.to_string(),
-                            old_taxon: current_taxon.map(TaxonId),
-                            new_taxon: sequence.taxon_id.map(TaxonId),
-                            reason: "Taxonomy update".to_string(),
-                        });
+                    self.reclassifications.record(
+                        snapshot_date,
+                        entity_id,
+                        current_taxon.map(TaxonId),
+                        sequence.taxon_id.map(TaxonId),
+                        "Taxonomy update",
+                    );
 
                     current_taxon = sequence.taxon_id;
                 }
@@ -181,36 +178,9 @@ impl TaxonomyEvolutionTracker {
         from: DateTime<Utc>,
         to: DateTime<Utc>,
     ) -> Result<Vec<MassReclassification>> {
-        let mut mass_events = Vec::new();
-
-        for (date, events) in self.reclassification_index.range(from..=to) {
-            // Group by old -> new taxon transitions
-            let mut transitions: HashMap<(Option<TaxonId>, Option<TaxonId>), Vec<String>> =
-                HashMap::new();
-
-            for event in events {
-                let key = (event.old_taxon, event.new_taxon);
-                transitions
-                    .entry(key)
-                    .or_default()
-                    .push(event.sequence_id.clone());
-            }
-
-            // Find transitions affecting many sequences
-            for ((old, new), sequences) in transitions {
-                if sequences.len() >= threshold {
-                    mass_events.push(MassReclassification {
-                        date: *date,
-                        old_taxon: old,
-                        new_taxon: new,
-                        affected_sequences: sequences,
-                        reason: "Taxonomic revision".to_string(),
-                    });
-                }
-            }
-        }
-
-        Ok(mass_events)
+        Ok(self
+            .reclassifications
+            .find_mass_reclassifications(threshold, from, to))
     }
 
     /// Generate evolution report for a taxonomic group
@@ -399,6 +369,91 @@ struct ChangeSet {
     reclassified_sequences: Vec<String>,
 }
 
+/// Reclassification events indexed by date
+///
+/// Kept separate from the tracker so reclassifications computed outside of
+/// snapshot replay (e.g. when a new taxonomy is applied) can be summarized
+/// the same way.
+#[derive(Debug, Default)]
+pub struct ReclassificationLog {
+    events: BTreeMap<DateTime<Utc>, Vec<ReclassificationEvent>>,
+}
+
+impl ReclassificationLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record that a sequence moved from one taxon to another
+    pub fn record(
+        &mut self,
+        date: DateTime<Utc>,
+        sequence_id: &str,
+        old_taxon: Option<TaxonId>,
+        new_taxon: Option<TaxonId>,
+        reason: &str,
+    ) {
+        self.events
+            .entry(date)
+            .or_default()
+            .push(ReclassificationEvent {
+                date,
+                sequence_id: sequence_id.to_string(),
+                old_taxon,
+                new_taxon,
+                reason: reason.to_string(),
+            });
+    }
+
+    /// Total number of recorded events
+    pub fn len(&self) -> usize {
+        self.events.values().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Group events by taxon transition and keep those affecting at least `threshold` sequences
+    pub fn find_mass_reclassifications(
+        &self,
+        threshold: usize,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Vec<MassReclassification> {
+        let mut mass_events = Vec::new();
+
+        for (date, events) in self.events.range(from..=to) {
+            // Group by old -> new taxon transitions
+            let mut transitions: HashMap<(Option<TaxonId>, Option<TaxonId>), Vec<String>> =
+                HashMap::new();
+
+            for event in events {
+                let key = (event.old_taxon, event.new_taxon);
+                transitions
+                    .entry(key)
+                    .or_default()
+                    .push(event.sequence_id.clone());
+            }
+
+            // Find transitions affecting many sequences
+            for ((old, new), sequences) in transitions {
+                if sequences.len() >= threshold {
+                    mass_events.push(MassReclassification {
+                        date: *date,
+                        old_taxon: old,
+                        new_taxon: new,
+                        affected_sequences: sequences,
+                        reason: "Taxonomic revision".to_string(),
+                    });
+                }
+            }
+        }
+
+        mass_events
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct MassReclassification {
     pub date: DateTime<Utc>,
@@ -1,147 +1,876 @@
 use crate::types::TaxonId;
-/// Taxonomy filter with boolean expression support
+/// Taxonomy filter with an embedded query language
 ///
 /// Supports expressions like:
 /// - "Bacteria" - single taxon
+/// - "\"Escherichia coli\"" - quoted (or bare multi-word) taxon names
 /// - "Bacteria AND NOT Escherichia" - boolean AND/NOT
 /// - "9606 OR 10090" - numeric IDs with OR
 /// - "(Bacteria OR Archaea) AND NOT Escherichia" - nested expressions
+/// - "rank:genus" - taxa at a given rank
+/// - "lineage:Bacteria/Proteobacteria/*" - lineage patterns with wildcards
+/// - "descendants_of(1224)" - a taxon and everything below it
+/// - "depth<=5" / "length>=100" - lineage depth and sequence length predicates
+///
+/// Operator precedence is NOT > AND > OR. Keywords are upper-case so that
+/// lower-case words remain part of taxon names.
 use anyhow::Result;
 use std::collections::HashSet;
+use std::fmt;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum TaxonomyFilter {
     TaxonId(TaxonId),
     Name(String),
+    /// Taxon rank, e.g. `rank:genus`
+    Rank(String),
+    /// Lineage pattern, e.g. `lineage:Bacteria/Proteobacteria/*`
+    Lineage(LineagePattern),
+    /// The taxon itself or any of its descendants
+    DescendantsOf(TaxonRef),
+    /// Depth of the taxon below the root (root = 0)
+    Depth(CompareOp, u32),
+    /// Length of the sequence in residues
+    Length(CompareOp, usize),
     And(Box<TaxonomyFilter>, Box<TaxonomyFilter>),
     Or(Box<TaxonomyFilter>, Box<TaxonomyFilter>),
     Not(Box<TaxonomyFilter>),
 }
 
-impl TaxonomyFilter {
-    /// Parse a filter expression string
-    pub fn parse(expr: &str) -> Result<Self> {
-        let expr = expr.trim();
+/// A taxon referenced either by ID or by name
+#[derive(Debug, Clone, PartialEq)]
+pub enum TaxonRef {
+    Id(TaxonId),
+    Name(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Lt,
+    Le,
+    Eq,
+    Ne,
+    Ge,
+    Gt,
+}
+
+impl CompareOp {
+    fn apply<T: PartialOrd>(self, lhs: T, rhs: T) -> bool {
+        match self {
+            CompareOp::Lt => lhs < rhs,
+            CompareOp::Le => lhs <= rhs,
+            CompareOp::Eq => lhs == rhs,
+            CompareOp::Ne => lhs != rhs,
+            CompareOp::Ge => lhs >= rhs,
+            CompareOp::Gt => lhs > rhs,
+        }
+    }
+}
+
+impl fmt::Display for CompareOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            CompareOp::Lt => "<",
+            CompareOp::Le => "<=",
+            CompareOp::Eq => "=",
+            CompareOp::Ne => "!=",
+            CompareOp::Ge => ">=",
+            CompareOp::Gt => ">",
+        };
+        f.write_str(s)
+    }
+}
+
+/// One segment of a lineage pattern
+#[derive(Debug, Clone, PartialEq)]
+pub enum LineageSegment {
+    /// Taxon name, optionally containing `*` globs (case-insensitive)
+    Name(String),
+    /// `*` - exactly one lineage level
+    AnyOne,
+    /// `**` - zero or more lineage levels
+    AnyMany,
+}
+
+/// Lineage pattern such as `Bacteria/Proteobacteria/*`
+///
+/// Named segments must appear in root-to-leaf order but need not be direct
+/// parent/child, since NCBI lineages contain many unranked intermediate
+/// nodes. `*` consumes exactly one level immediately below the previous
+/// segment and `**` any number of levels. The pattern matches a taxon when
+/// it is fully consumed by the taxon's lineage, so `Bacteria/Proteobacteria`
+/// matches Proteobacteria and all of its descendants while
+/// `Bacteria/Proteobacteria/*` only matches the descendants.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineagePattern {
+    pub segments: Vec<LineageSegment>,
+}
 
-        // Try parsing as simple taxon ID
-        if let Ok(id) = expr.parse::<u32>() {
-            return Ok(TaxonomyFilter::TaxonId(TaxonId(id)));
+impl LineagePattern {
+    pub fn parse(pattern: &str) -> std::result::Result<Self, String> {
+        let mut segments = Vec::new();
+        for raw in pattern.split('/') {
+            let segment = raw.trim();
+            match segment {
+                "" => return Err("empty lineage segment".to_string()),
+                "*" => segments.push(LineageSegment::AnyOne),
+                "**" => segments.push(LineageSegment::AnyMany),
+                name => segments.push(LineageSegment::Name(name.to_string())),
+            }
         }
+        Ok(Self { segments })
+    }
+
+    /// Check the pattern against lineage names ordered root to leaf
+    pub fn matches(&self, lineage: &[&str]) -> bool {
+        Self::match_from(&self.segments, lineage)
+    }
 
-        // Parse boolean expressions
-        if let Some(filter) = Self::parse_expression(expr)? {
-            return Ok(filter);
+    fn match_from(segments: &[LineageSegment], lineage: &[&str]) -> bool {
+        let Some((first, rest)) = segments.split_first() else {
+            return true;
+        };
+
+        match first {
+            LineageSegment::AnyOne => !lineage.is_empty() && Self::match_from(rest, &lineage[1..]),
+            LineageSegment::AnyMany => {
+                (0..=lineage.len()).any(|skip| Self::match_from(rest, &lineage[skip..]))
+            }
+            LineageSegment::Name(name) => {
+                // Named segments may skip intermediate (unranked) levels
+                lineage.iter().enumerate().any(|(i, candidate)| {
+                    glob_matches(name, candidate) && Self::match_from(rest, &lineage[i + 1..])
+                })
+            }
         }
+    }
+}
+
+impl fmt::Display for LineagePattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts: Vec<&str> = self
+            .segments
+            .iter()
+            .map(|s| match s {
+                LineageSegment::Name(n) => n.as_str(),
+                LineageSegment::AnyOne => "*",
+                LineageSegment::AnyMany => "**",
+            })
+            .collect();
+        f.write_str(&parts.join("/"))
+    }
+}
+
+/// Case-insensitive glob match supporting `*` within a name
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern = pattern.to_lowercase();
+    let text = text.to_lowercase();
+
+    if !pattern.contains('*') {
+        return pattern == text;
+    }
+
+    let parts: Vec<&str> = pattern.split('*').collect();
+    let mut rest = text.as_str();
+
+    for (i, part) in parts.iter().enumerate() {
+        if part.is_empty() {
+            continue;
+        }
+        if i == 0 {
+            if !rest.starts_with(part) {
+                return false;
+            }
+            rest = &rest[part.len()..];
+        } else if i == parts.len() - 1 {
+            return rest.ends_with(part);
+        } else if let Some(pos) = rest.find(part) {
+            rest = &rest[pos + part.len()..];
+        } else {
+            return false;
+        }
+    }
+
+    true
+}
+
+/// Lineage entry as seen by the filter: (taxon, scientific name, rank)
+pub type LineageEntry = (TaxonId, String, String);
+
+/// Taxonomy lookups needed to evaluate rank, lineage and name predicates
+pub trait TaxonomyResolver {
+    /// Resolve a taxon name to all matching taxon IDs
+    fn resolve_name(&self, name: &str) -> Vec<TaxonId>;
+
+    /// Lineage of a taxon ordered root to leaf, including the taxon itself.
+    /// Returns an empty vector for unknown taxa.
+    fn lineage(&self, taxon_id: TaxonId) -> Vec<LineageEntry>;
+}
+
+/// Context a filter is evaluated in
+#[derive(Default, Clone, Copy)]
+pub struct FilterContext<'a> {
+    resolver: Option<&'a dyn TaxonomyResolver>,
+    sequence_length: Option<usize>,
+}
+
+impl<'a> FilterContext<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_resolver(mut self, resolver: &'a dyn TaxonomyResolver) -> Self {
+        self.resolver = Some(resolver);
+        self
+    }
+
+    pub fn with_sequence_length(mut self, length: usize) -> Self {
+        self.sequence_length = Some(length);
+        self
+    }
+}
 
-        // Default to name lookup
-        Ok(TaxonomyFilter::Name(expr.to_string()))
+/// Error raised for malformed filter expressions, pointing at the offending token
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub struct FilterParseError {
+    pub message: String,
+    /// Character offset of the offending token in the expression
+    pub position: usize,
+    pub expression: String,
+}
+
+impl fmt::Display for FilterParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Invalid taxonomy filter: {} at position {}",
+            self.message, self.position
+        )?;
+        writeln!(f, "  {}", self.expression)?;
+        write!(f, "  {}^", " ".repeat(self.position))
     }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Word(String),
+    Quoted(String),
+    LParen,
+    RParen,
+    Colon,
+    Compare(CompareOp),
+    And,
+    Or,
+    Not,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    position: usize,
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match &self.kind {
+            TokenKind::Word(w) => format!("'{}'", w),
+            TokenKind::Quoted(q) => format!("\"{}\"", q),
+            TokenKind::LParen => "'('".to_string(),
+            TokenKind::RParen => "')'".to_string(),
+            TokenKind::Colon => "':'".to_string(),
+            TokenKind::Compare(op) => format!("'{}'", op),
+            TokenKind::And => "AND".to_string(),
+            TokenKind::Or => "OR".to_string(),
+            TokenKind::Not => "NOT".to_string(),
+        }
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '.' | '-' | '/' | '*' | '\'' | '+' | '#')
+}
+
+fn tokenize(expr: &str) -> std::result::Result<Vec<Token>, FilterParseError> {
+    let chars: Vec<char> = expr.chars().collect();
+    let error = |message: String, position: usize| FilterParseError {
+        message,
+        position,
+        expression: expr.to_string(),
+    };
 
-    fn parse_expression(expr: &str) -> Result<Option<Self>> {
-        let expr = expr.trim();
+    let mut tokens = Vec::new();
+    let mut i = 0;
 
-        // Handle parentheses
-        if expr.starts_with('(') && expr.ends_with(')') {
-            return Self::parse_expression(&expr[1..expr.len() - 1]);
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+
+        if c.is_whitespace() {
+            i += 1;
+            continue;
         }
 
-        // Parse NOT
-        if expr.starts_with("NOT ") {
-            let rest = &expr[4..];
-            if let Some(filter) = Self::parse_expression(rest)? {
-                return Ok(Some(TaxonomyFilter::Not(Box::new(filter))));
+        let kind = match c {
+            '(' => {
+                i += 1;
+                TokenKind::LParen
+            }
+            ')' => {
+                i += 1;
+                TokenKind::RParen
+            }
+            ':' => {
+                i += 1;
+                TokenKind::Colon
+            }
+            '<' | '>' | '=' | '!' => {
+                let next = chars.get(i + 1).copied();
+                let (op, width) = match (c, next) {
+                    ('<', Some('=')) => (CompareOp::Le, 2),
+                    ('>', Some('=')) => (CompareOp::Ge, 2),
+                    ('=', Some('=')) => (CompareOp::Eq, 2),
+                    ('!', Some('=')) => (CompareOp::Ne, 2),
+                    ('<', _) => (CompareOp::Lt, 1),
+                    ('>', _) => (CompareOp::Gt, 1),
+                    ('=', _) => (CompareOp::Eq, 1),
+                    _ => return Err(error(format!("unexpected character '{}'", c), start)),
+                };
+                i += width;
+                TokenKind::Compare(op)
+            }
+            '"' => {
+                i += 1;
+                let mut value = String::new();
+                loop {
+                    match chars.get(i) {
+                        Some('"') => {
+                            i += 1;
+                            break;
+                        }
+                        Some('\\') if chars.get(i + 1) == Some(&'"') => {
+                            value.push('"');
+                            i += 2;
+                        }
+                        Some(&ch) => {
+                            value.push(ch);
+                            i += 1;
+                        }
+                        None => return Err(error("unterminated quoted name".to_string(), start)),
+                    }
+                }
+                TokenKind::Quoted(value)
             }
+            c if is_word_char(c) => {
+                while i < chars.len() && is_word_char(chars[i]) {
+                    i += 1;
+                }
+                let word: String = chars[start..i].iter().collect();
+                match word.as_str() {
+                    "AND" => TokenKind::And,
+                    "OR" => TokenKind::Or,
+                    "NOT" => TokenKind::Not,
+                    _ => TokenKind::Word(word),
+                }
+            }
+            _ => return Err(error(format!("unexpected character '{}'", c), start)),
+        };
+
+        tokens.push(Token {
+            kind,
+            position: start,
+        });
+    }
+
+    Ok(tokens)
+}
+
+const PREDICATES: &[&str] = &["rank", "lineage", "descendants_of", "depth", "length"];
+
+struct Parser<'a> {
+    expr: &'a str,
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, message: impl Into<String>, position: usize) -> FilterParseError {
+        FilterParseError {
+            message: message.into(),
+            position,
+            expression: self.expr.to_string(),
         }
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn peek_kind(&self, offset: usize) -> Option<&TokenKind> {
+        self.tokens.get(self.pos + offset).map(|t| &t.kind)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
 
-        // Parse AND (highest precedence after NOT)
-        if let Some(pos) = expr.rfind(" AND ") {
-            let left = &expr[..pos];
-            let right = &expr[pos + 5..];
+    fn end_position(&self) -> usize {
+        self.expr.chars().count()
+    }
 
-            if let (Some(l), Some(r)) = (
-                Self::parse_expression(left)?,
-                Self::parse_expression(right)?,
-            ) {
-                return Ok(Some(TaxonomyFilter::And(Box::new(l), Box::new(r))));
+    fn unexpected(&self, expected: &str) -> FilterParseError {
+        match self.peek() {
+            Some(token) => self.error(
+                format!("expected {}, found {}", expected, token.describe()),
+                token.position,
+            ),
+            None => self.error(
+                format!("expected {}, found end of expression", expected),
+                self.end_position(),
+            ),
+        }
+    }
+
+    fn parse(mut self) -> std::result::Result<TaxonomyFilter, FilterParseError> {
+        if self.tokens.is_empty() {
+            return Err(self.error("empty filter expression", 0));
+        }
+
+        let filter = self.parse_or()?;
+        if let Some(token) = self.peek() {
+            return Err(self.error(
+                format!("unexpected {} after expression", token.describe()),
+                token.position,
+            ));
+        }
+        Ok(filter)
+    }
+
+    fn parse_or(&mut self) -> std::result::Result<TaxonomyFilter, FilterParseError> {
+        let mut left = self.parse_and()?;
+        while matches!(self.peek_kind(0), Some(TokenKind::Or)) {
+            self.next();
+            let right = self.parse_and()?;
+            left = TaxonomyFilter::Or(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> std::result::Result<TaxonomyFilter, FilterParseError> {
+        let mut left = self.parse_unary()?;
+        while matches!(self.peek_kind(0), Some(TokenKind::And)) {
+            self.next();
+            let right = self.parse_unary()?;
+            left = TaxonomyFilter::And(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_unary(&mut self) -> std::result::Result<TaxonomyFilter, FilterParseError> {
+        if matches!(self.peek_kind(0), Some(TokenKind::Not)) {
+            self.next();
+            let inner = self.parse_unary()?;
+            return Ok(TaxonomyFilter::Not(Box::new(inner)));
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> std::result::Result<TaxonomyFilter, FilterParseError> {
+        let Some(token) = self.peek().cloned() else {
+            return Err(self.unexpected("a taxon, predicate or '('"));
+        };
+
+        match &token.kind {
+            TokenKind::LParen => {
+                self.next();
+                let inner = self.parse_or()?;
+                match self.peek_kind(0) {
+                    Some(TokenKind::RParen) => {
+                        self.next();
+                        Ok(inner)
+                    }
+                    _ => Err(self.unexpected("')'")),
+                }
             }
+            TokenKind::Quoted(name) => {
+                self.next();
+                Ok(TaxonomyFilter::Name(name.clone()))
+            }
+            TokenKind::Word(word) if self.starts_predicate() => {
+                let word = word.clone();
+                self.parse_predicate(&word, token.position)
+            }
+            TokenKind::Word(_) => self.parse_bare_taxon(),
+            _ => Err(self.unexpected("a taxon, predicate or '('")),
+        }
+    }
+
+    /// A word followed by `:`, a comparison or `(` starts a predicate
+    fn starts_predicate(&self) -> bool {
+        matches!(
+            self.peek_kind(1),
+            Some(TokenKind::Colon) | Some(TokenKind::Compare(_)) | Some(TokenKind::LParen)
+        )
+    }
+
+    fn parse_predicate(
+        &mut self,
+        keyword: &str,
+        position: usize,
+    ) -> std::result::Result<TaxonomyFilter, FilterParseError> {
+        let name = keyword.to_lowercase();
+        if !PREDICATES.contains(&name.as_str()) {
+            return Err(self.error(
+                format!(
+                    "unknown predicate '{}' (expected one of: {})",
+                    keyword,
+                    PREDICATES.join(", ")
+                ),
+                position,
+            ));
         }
+        self.next();
 
-        // Parse OR (lowest precedence)
-        if let Some(pos) = expr.rfind(" OR ") {
-            let left = &expr[..pos];
-            let right = &expr[pos + 4..];
+        match name.as_str() {
+            "rank" => {
+                self.expect_colon(&name)?;
+                let (value, _) = self.expect_value("a rank name")?;
+                Ok(TaxonomyFilter::Rank(value.to_lowercase()))
+            }
+            "lineage" => {
+                self.expect_colon(&name)?;
+                let (value, value_pos) = self.expect_value("a lineage pattern")?;
+                let pattern = LineagePattern::parse(&value)
+                    .map_err(|message| self.error(message, value_pos))?;
+                Ok(TaxonomyFilter::Lineage(pattern))
+            }
+            "descendants_of" => {
+                match self.peek_kind(0) {
+                    Some(TokenKind::LParen) => {
+                        self.next();
+                    }
+                    _ => return Err(self.unexpected("'(' after descendants_of")),
+                }
+                let (value, _) = self.expect_value("a taxon ID or name")?;
+                let mut name_parts = vec![value];
+                while let Some(TokenKind::Word(w)) = self.peek_kind(0) {
+                    name_parts.push(w.clone());
+                    self.next();
+                }
+                match self.peek_kind(0) {
+                    Some(TokenKind::RParen) => {
+                        self.next();
+                    }
+                    _ => return Err(self.unexpected("')'")),
+                }
+                let joined = name_parts.join(" ");
+                let taxon = match joined.parse::<u32>() {
+                    Ok(id) => TaxonRef::Id(TaxonId(id)),
+                    Err(_) => TaxonRef::Name(joined),
+                };
+                Ok(TaxonomyFilter::DescendantsOf(taxon))
+            }
+            "depth" => {
+                let (op, value) = self.expect_comparison(&name)?;
+                Ok(TaxonomyFilter::Depth(op, value))
+            }
+            "length" => {
+                let (op, value) = self.expect_comparison(&name)?;
+                Ok(TaxonomyFilter::Length(op, value))
+            }
+            _ => unreachable!("predicate list and match arms out of sync"),
+        }
+    }
 
-            if let (Some(l), Some(r)) = (
-                Self::parse_expression(left)?,
-                Self::parse_expression(right)?,
-            ) {
-                return Ok(Some(TaxonomyFilter::Or(Box::new(l), Box::new(r))));
+    fn expect_colon(&mut self, predicate: &str) -> std::result::Result<(), FilterParseError> {
+        match self.peek_kind(0) {
+            Some(TokenKind::Colon) => {
+                self.next();
+                Ok(())
             }
+            _ => Err(self.unexpected(&format!("':' after {}", predicate))),
         }
+    }
 
-        // Try as taxon ID
-        if let Ok(id) = expr.parse::<u32>() {
-            return Ok(Some(TaxonomyFilter::TaxonId(TaxonId(id))));
+    fn expect_value(
+        &mut self,
+        expected: &str,
+    ) -> std::result::Result<(String, usize), FilterParseError> {
+        match self.peek().cloned() {
+            Some(Token {
+                kind: TokenKind::Word(w) | TokenKind::Quoted(w),
+                position,
+            }) => {
+                self.next();
+                Ok((w, position))
+            }
+            _ => Err(self.unexpected(expected)),
         }
+    }
 
-        // Handle AND NOT special case
-        if let Some(pos) = expr.find(" AND NOT ") {
-            let left = &expr[..pos];
-            let right = &expr[pos + 9..];
+    fn expect_comparison<T>(
+        &mut self,
+        predicate: &str,
+    ) -> std::result::Result<(CompareOp, T), FilterParseError>
+    where
+        T: std::str::FromStr<Err = std::num::ParseIntError>,
+    {
+        let op = match self.peek_kind(0) {
+            Some(TokenKind::Compare(op)) => *op,
+            _ => {
+                return Err(self.unexpected(&format!(
+                    "a comparison (<, <=, =, !=, >=, >) after {}",
+                    predicate
+                )))
+            }
+        };
+        self.next();
+
+        match self.peek().cloned() {
+            Some(Token {
+                kind: TokenKind::Word(w),
+                position,
+            }) => {
+                let value = w.parse::<T>().map_err(|e| match e.kind() {
+                    std::num::IntErrorKind::PosOverflow => {
+                        self.error(format!("number '{}' is out of range", w), position)
+                    }
+                    _ => self.error(format!("expected a number, found '{}'", w), position),
+                })?;
+                self.next();
+                Ok((op, value))
+            }
+            _ => Err(self.unexpected("a number")),
+        }
+    }
 
-            if let (Some(l), Some(r)) = (
-                Self::parse_expression(left)?,
-                Self::parse_expression(right)?,
-            ) {
-                return Ok(Some(TaxonomyFilter::And(
-                    Box::new(l),
-                    Box::new(TaxonomyFilter::Not(Box::new(r))),
-                )));
+    /// Bare words form a taxon ID (single number) or a multi-word name
+    fn parse_bare_taxon(&mut self) -> std::result::Result<TaxonomyFilter, FilterParseError> {
+        let mut words = Vec::new();
+        while let Some(TokenKind::Word(w)) = self.peek_kind(0) {
+            if !words.is_empty() && self.starts_predicate() {
+                break;
             }
+            words.push(w.clone());
+            self.next();
         }
 
-        // Must be a taxon name
-        if !expr.is_empty() {
-            return Ok(Some(TaxonomyFilter::Name(expr.to_string())));
+        if words.len() == 1 {
+            if let Ok(id) = words[0].parse::<u32>() {
+                return Ok(TaxonomyFilter::TaxonId(TaxonId(id)));
+            }
         }
 
-        Ok(None)
+        Ok(TaxonomyFilter::Name(words.join(" ")))
+    }
+}
+
+impl TaxonomyFilter {
+    /// Parse a filter expression string
+    ///
+    /// Errors are [`FilterParseError`]s that point at the offending token.
+    pub fn parse(expr: &str) -> Result<Self> {
+        Ok(Self::parse_expression(expr)?)
+    }
+
+    /// Parse a filter expression, returning the typed parse error
+    pub fn parse_expression(expr: &str) -> std::result::Result<Self, FilterParseError> {
+        let tokens = tokenize(expr)?;
+        Parser {
+            expr,
+            tokens,
+            pos: 0,
+        }
+        .parse()
     }
 
     /// Evaluate filter against a set of taxon IDs
     pub fn matches(&self, taxon_ids: &[TaxonId]) -> bool {
+        self.matches_with(taxon_ids, &FilterContext::new())
+    }
+
+    /// Evaluate filter with taxonomy lookups and/or a sequence length
+    ///
+    /// Predicates that cannot be decided in the given context (e.g. `length`
+    /// when filtering whole chunks) are treated as satisfied, so this never
+    /// excludes data that might match.
+    pub fn matches_with(&self, taxon_ids: &[TaxonId], ctx: &FilterContext) -> bool {
+        self.evaluate_with(taxon_ids, ctx).unwrap_or(true)
+    }
+
+    /// Whether any single taxon of a group (e.g. a chunk) could satisfy the filter
+    ///
+    /// A group is only ruled out when the filter is provably false for each
+    /// of its taxa on its own, so `NOT 562` keeps a chunk holding 562 and 590.
+    /// Groups that pass must still be filtered sequence by sequence.
+    pub fn may_match_any(&self, taxon_ids: &[TaxonId], ctx: &FilterContext) -> bool {
+        taxon_ids.is_empty()
+            || taxon_ids
+                .iter()
+                .any(|id| self.matches_with(std::slice::from_ref(id), ctx))
+    }
+
+    /// Three-valued evaluation: `None` means the context cannot decide
+    pub fn evaluate_with(&self, taxon_ids: &[TaxonId], ctx: &FilterContext) -> Option<bool> {
         let taxon_set: HashSet<_> = taxon_ids.iter().cloned().collect();
-        self.evaluate(&taxon_set)
+        self.evaluate(&taxon_set, ctx)
     }
 
-    fn evaluate(&self, taxon_set: &HashSet<TaxonId>) -> bool {
+    /// Whether the filter contains a `length` predicate
+    pub fn uses_length(&self) -> bool {
         match self {
-            TaxonomyFilter::TaxonId(id) => taxon_set.contains(id),
+            TaxonomyFilter::Length(..) => true,
+            TaxonomyFilter::And(l, r) | TaxonomyFilter::Or(l, r) => {
+                l.uses_length() || r.uses_length()
+            }
+            TaxonomyFilter::Not(inner) => inner.uses_length(),
+            _ => false,
+        }
+    }
+
+    /// Taxa named by a filter made only of IDs and names joined with OR
+    ///
+    /// Returns `None` for any other filter, which must then be evaluated
+    /// taxon by taxon.
+    pub fn identity_taxa(&self, ctx: &FilterContext) -> Option<Vec<TaxonId>> {
+        match self {
+            TaxonomyFilter::TaxonId(id) => Some(vec![*id]),
+            TaxonomyFilter::Name(name) => Some(resolve_name(name, ctx)),
+            TaxonomyFilter::Or(l, r) => {
+                let mut ids = l.identity_taxa(ctx)?;
+                ids.extend(r.identity_taxa(ctx)?);
+                ids.sort();
+                ids.dedup();
+                Some(ids)
+            }
+            _ => None,
+        }
+    }
+
+    fn evaluate(&self, taxon_set: &HashSet<TaxonId>, ctx: &FilterContext) -> Option<bool> {
+        match self {
+            TaxonomyFilter::TaxonId(id) => Some(taxon_set.contains(id)),
             TaxonomyFilter::Name(name) => {
-                // Convert common names to IDs
-                let id = match name.to_lowercase().as_str() {
-                    "bacteria" => TaxonId(2),
-                    "archaea" => TaxonId(2157),
-                    "eukaryota" => TaxonId(2759),
-                    "viruses" => TaxonId(10239),
-                    "escherichia" => TaxonId(561),
-                    "escherichia coli" | "e. coli" => TaxonId(562),
-                    "homo sapiens" | "human" => TaxonId(9606),
-                    "mus musculus" | "mouse" => TaxonId(10090),
-                    "drosophila" | "drosophila melanogaster" => TaxonId(7227),
-                    "arabidopsis" | "arabidopsis thaliana" => TaxonId(3702),
-                    "saccharomyces cerevisiae" | "yeast" => TaxonId(559292),
-                    _ => return false, // Unknown name
+                let ids = resolve_name(name, ctx);
+                Some(ids.iter().any(|id| taxon_set.contains(id)))
+            }
+            TaxonomyFilter::Rank(rank) => {
+                let resolver = ctx.resolver?;
+                Some(taxon_set.iter().any(|id| {
+                    resolver
+                        .lineage(*id)
+                        .last()
+                        .is_some_and(|(_, _, r)| r.eq_ignore_ascii_case(rank))
+                }))
+            }
+            TaxonomyFilter::Lineage(pattern) => {
+                let resolver = ctx.resolver?;
+                Some(taxon_set.iter().any(|id| {
+                    let lineage = resolver.lineage(*id);
+                    let names: Vec<&str> = lineage.iter().map(|(_, n, _)| n.as_str()).collect();
+                    !names.is_empty() && pattern.matches(&names)
+                }))
+            }
+            TaxonomyFilter::DescendantsOf(taxon) => {
+                let ancestors: Vec<TaxonId> = match taxon {
+                    TaxonRef::Id(id) => vec![*id],
+                    TaxonRef::Name(name) => resolve_name(name, ctx),
                 };
-                taxon_set.contains(&id)
+                if taxon_set.iter().any(|id| ancestors.contains(id)) {
+                    return Some(true);
+                }
+                let resolver = ctx.resolver?;
+                Some(taxon_set.iter().any(|id| {
+                    resolver
+                        .lineage(*id)
+                        .iter()
+                        .any(|(ancestor, _, _)| ancestors.contains(ancestor))
+                }))
+            }
+            TaxonomyFilter::Depth(op, depth) => {
+                let resolver = ctx.resolver?;
+                Some(taxon_set.iter().any(|id| {
+                    let lineage = resolver.lineage(*id);
+                    !lineage.is_empty() && op.apply((lineage.len() - 1) as u32, *depth)
+                }))
+            }
+            TaxonomyFilter::Length(op, length) => {
+                let actual = ctx.sequence_length?;
+                Some(op.apply(actual, *length))
             }
             TaxonomyFilter::And(left, right) => {
-                left.evaluate(taxon_set) && right.evaluate(taxon_set)
+                match (
+                    left.evaluate(taxon_set, ctx),
+                    right.evaluate(taxon_set, ctx),
+                ) {
+                    (Some(false), _) | (_, Some(false)) => Some(false),
+                    (Some(true), Some(true)) => Some(true),
+                    _ => None,
+                }
             }
             TaxonomyFilter::Or(left, right) => {
-                left.evaluate(taxon_set) || right.evaluate(taxon_set)
+                match (
+                    left.evaluate(taxon_set, ctx),
+                    right.evaluate(taxon_set, ctx),
+                ) {
+                    (Some(true), _) | (_, Some(true)) => Some(true),
+                    (Some(false), Some(false)) => Some(false),
+                    _ => None,
+                }
+            }
+            TaxonomyFilter::Not(filter) => filter.evaluate(taxon_set, ctx).map(|v| !v),
+        }
+    }
+}
+
+fn resolve_name(name: &str, ctx: &FilterContext) -> Vec<TaxonId> {
+    if let Some(resolver) = ctx.resolver {
+        let ids = resolver.resolve_name(name);
+        if !ids.is_empty() {
+            return ids;
+        }
+    }
+    builtin_name(name).into_iter().collect()
+}
+
+/// Well-known names usable without a loaded taxonomy
+fn builtin_name(name: &str) -> Option<TaxonId> {
+    let id = match name.to_lowercase().as_str() {
+        "bacteria" => TaxonId(2),
+        "archaea" => TaxonId(2157),
+        "eukaryota" => TaxonId(2759),
+        "viruses" => TaxonId(10239),
+        "escherichia" => TaxonId(561),
+        "escherichia coli" | "e. coli" => TaxonId(562),
+        "homo sapiens" | "human" => TaxonId(9606),
+        "mus musculus" | "mouse" => TaxonId(10090),
+        "drosophila" | "drosophila melanogaster" => TaxonId(7227),
+        "arabidopsis" | "arabidopsis thaliana" => TaxonId(3702),
+        "saccharomyces cerevisiae" | "yeast" => TaxonId(559292),
+        _ => return None,
+    };
+    Some(id)
+}
+
+impl fmt::Display for TaxonomyFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TaxonomyFilter::TaxonId(id) => write!(f, "{}", id.0),
+            TaxonomyFilter::Name(name) => write!(f, "\"{}\"", name),
+            TaxonomyFilter::Rank(rank) => write!(f, "rank:{}", rank),
+            TaxonomyFilter::Lineage(pattern) => write!(f, "lineage:\"{}\"", pattern),
+            TaxonomyFilter::DescendantsOf(TaxonRef::Id(id)) => {
+                write!(f, "descendants_of({})", id.0)
             }
-            TaxonomyFilter::Not(filter) => !filter.evaluate(taxon_set),
+            TaxonomyFilter::DescendantsOf(TaxonRef::Name(name)) => {
+                write!(f, "descendants_of(\"{}\")", name)
+            }
+            TaxonomyFilter::Depth(op, n) => write!(f, "depth{}{}", op, n),
+            TaxonomyFilter::Length(op, n) => write!(f, "length{}{}", op, n),
+            TaxonomyFilter::And(l, r) => write!(f, "({} AND {})", l, r),
+            TaxonomyFilter::Or(l, r) => write!(f, "({} OR {})", l, r),
+            TaxonomyFilter::Not(inner) => write!(f, "NOT {}", inner),
         }
     }
 }
@@ -149,6 +878,51 @@ impl TaxonomyFilter {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::HashMap;
+
+    /// Tiny taxonomy: root(1) > Bacteria(2) > Proteobacteria(1224) >
+    /// Gammaproteobacteria(1236) > Escherichia(561) > E. coli(562)
+    struct TestTaxonomy {
+        nodes: HashMap<u32, (u32, &'static str, &'static str)>,
+    }
+
+    impl TestTaxonomy {
+        fn new() -> Self {
+            let mut nodes = HashMap::new();
+            nodes.insert(1, (1, "root", "no rank"));
+            nodes.insert(2, (1, "Bacteria", "superkingdom"));
+            nodes.insert(1224, (2, "Proteobacteria", "phylum"));
+            nodes.insert(1236, (1224, "Gammaproteobacteria", "class"));
+            nodes.insert(561, (1236, "Escherichia", "genus"));
+            nodes.insert(562, (561, "Escherichia coli", "species"));
+            nodes.insert(2157, (1, "Archaea", "superkingdom"));
+            Self { nodes }
+        }
+    }
+
+    impl TaxonomyResolver for TestTaxonomy {
+        fn resolve_name(&self, name: &str) -> Vec<TaxonId> {
+            self.nodes
+                .iter()
+                .filter(|(_, (_, n, _))| n.eq_ignore_ascii_case(name))
+                .map(|(id, _)| TaxonId(*id))
+                .collect()
+        }
+
+        fn lineage(&self, taxon_id: TaxonId) -> Vec<LineageEntry> {
+            let mut lineage = Vec::new();
+            let mut current = taxon_id.0;
+            while let Some((parent, name, rank)) = self.nodes.get(&current) {
+                lineage.push((TaxonId(current), name.to_string(), rank.to_string()));
+                if *parent == current {
+                    break;
+                }
+                current = *parent;
+            }
+            lineage.reverse();
+            lineage
+        }
+    }
 
     #[test]
     fn test_simple_filters() {
@@ -192,4 +966,162 @@ mod tests {
         assert!(!filter.matches(&[TaxonId(2), TaxonId(561)]));
         assert!(!filter.matches(&[TaxonId(9606)]));
     }
+
+    #[test]
+    fn test_precedence() {
+        // AND binds tighter than OR
+        let filter = TaxonomyFilter::parse("1 OR 2 AND 3").unwrap();
+        assert_eq!(
+            filter,
+            TaxonomyFilter::Or(
+                Box::new(TaxonomyFilter::TaxonId(TaxonId(1))),
+                Box::new(TaxonomyFilter::And(
+                    Box::new(TaxonomyFilter::TaxonId(TaxonId(2))),
+                    Box::new(TaxonomyFilter::TaxonId(TaxonId(3))),
+                )),
+            )
+        );
+    }
+
+    #[test]
+    fn test_quoted_and_multiword_names() {
+        let quoted = TaxonomyFilter::parse("\"Escherichia coli\" OR 9606").unwrap();
+        let bare = TaxonomyFilter::parse("Escherichia coli OR 9606").unwrap();
+        assert_eq!(quoted, bare);
+        assert!(quoted.matches(&[TaxonId(562)]));
+
+        // Keywords inside quotes are names
+        let filter = TaxonomyFilter::parse("\"NOT a keyword\"").unwrap();
+        assert_eq!(filter, TaxonomyFilter::Name("NOT a keyword".to_string()));
+    }
+
+    #[test]
+    fn test_predicates_parse() {
+        assert_eq!(
+            TaxonomyFilter::parse("rank:Genus").unwrap(),
+            TaxonomyFilter::Rank("genus".to_string())
+        );
+        assert_eq!(
+            TaxonomyFilter::parse("descendants_of(1224)").unwrap(),
+            TaxonomyFilter::DescendantsOf(TaxonRef::Id(TaxonId(1224)))
+        );
+        assert_eq!(
+            TaxonomyFilter::parse("descendants_of(Escherichia coli)").unwrap(),
+            TaxonomyFilter::DescendantsOf(TaxonRef::Name("Escherichia coli".to_string()))
+        );
+        assert_eq!(
+            TaxonomyFilter::parse("depth<=3").unwrap(),
+            TaxonomyFilter::Depth(CompareOp::Le, 3)
+        );
+        assert_eq!(
+            TaxonomyFilter::parse("length >= 100").unwrap(),
+            TaxonomyFilter::Length(CompareOp::Ge, 100)
+        );
+        assert!(matches!(
+            TaxonomyFilter::parse("lineage:Bacteria/Proteobacteria/*").unwrap(),
+            TaxonomyFilter::Lineage(_)
+        ));
+    }
+
+    #[test]
+    fn test_predicates_with_resolver() {
+        let taxonomy = TestTaxonomy::new();
+        let ctx = FilterContext::new().with_resolver(&taxonomy);
+
+        let genus = TaxonomyFilter::parse("rank:genus").unwrap();
+        assert!(genus.matches_with(&[TaxonId(561)], &ctx));
+        assert!(!genus.matches_with(&[TaxonId(562)], &ctx));
+
+        let below = TaxonomyFilter::parse("descendants_of(1224)").unwrap();
+        assert!(below.matches_with(&[TaxonId(1224)], &ctx));
+        assert!(below.matches_with(&[TaxonId(562)], &ctx));
+        assert!(!below.matches_with(&[TaxonId(2157)], &ctx));
+
+        let lineage = TaxonomyFilter::parse("lineage:Bacteria/Proteobacteria/*").unwrap();
+        assert!(lineage.matches_with(&[TaxonId(562)], &ctx));
+        assert!(!lineage.matches_with(&[TaxonId(1224)], &ctx));
+
+        let glob =
+            TaxonomyFilter::parse("lineage:\"Bacteria/Gamma*/**/Escherichia coli\"").unwrap();
+        assert!(glob.matches_with(&[TaxonId(562)], &ctx));
+
+        let shallow = TaxonomyFilter::parse("depth<=2").unwrap();
+        assert!(shallow.matches_with(&[TaxonId(1224)], &ctx));
+        assert!(!shallow.matches_with(&[TaxonId(562)], &ctx));
+
+        // Names resolve through the resolver before the built-in table
+        let name = TaxonomyFilter::parse("Gammaproteobacteria").unwrap();
+        assert!(name.matches_with(&[TaxonId(1236)], &ctx));
+    }
+
+    #[test]
+    fn test_length_predicate() {
+        let filter = TaxonomyFilter::parse("Bacteria AND length>=100").unwrap();
+        assert!(filter.uses_length());
+
+        let long = FilterContext::new().with_sequence_length(150);
+        let short = FilterContext::new().with_sequence_length(50);
+        assert!(filter.matches_with(&[TaxonId(2)], &long));
+        assert!(!filter.matches_with(&[TaxonId(2)], &short));
+
+        // Undecidable without a length: kept, unless the taxon already fails
+        assert!(filter.matches(&[TaxonId(2)]));
+        assert!(!filter.matches(&[TaxonId(2157)]));
+    }
+
+    #[test]
+    fn test_may_match_any_is_conservative() {
+        let ctx = FilterContext::new();
+        let mixed = [TaxonId(562), TaxonId(590)];
+
+        let not_coli = TaxonomyFilter::parse("NOT 562").unwrap();
+        assert!(!not_coli.matches_with(&mixed, &ctx));
+        assert!(not_coli.may_match_any(&mixed, &ctx));
+        assert!(!not_coli.may_match_any(&[TaxonId(562)], &ctx));
+
+        // Satisfied only by the chunk as a whole, never by one of its taxa
+        let both = TaxonomyFilter::parse("562 AND 590").unwrap();
+        assert!(both.matches_with(&mixed, &ctx));
+        assert!(!both.may_match_any(&mixed, &ctx));
+
+        let coli = TaxonomyFilter::parse("562").unwrap();
+        assert!(coli.may_match_any(&mixed, &ctx));
+        assert!(!coli.may_match_any(&[TaxonId(590)], &ctx));
+        assert!(coli.may_match_any(&[], &ctx));
+    }
+
+    #[test]
+    fn test_error_positions() {
+        let err = TaxonomyFilter::parse_expression("Bacteria AND").unwrap_err();
+        assert_eq!(err.position, 12);
+
+        let err = TaxonomyFilter::parse_expression("(Bacteria OR Archaea").unwrap_err();
+        assert_eq!(err.position, 20);
+
+        let err = TaxonomyFilter::parse_expression("Bacteria AND rnk:genus").unwrap_err();
+        assert_eq!(err.position, 13);
+        assert!(err.message.contains("unknown predicate"));
+
+        let err = TaxonomyFilter::parse_expression("depth<=deep").unwrap_err();
+        assert_eq!(err.position, 7);
+
+        let err = TaxonomyFilter::parse_expression("depth<=4294967296").unwrap_err();
+        assert_eq!(err.position, 7);
+        assert!(err.message.contains("out of range"));
+
+        let err = TaxonomyFilter::parse_expression("Bacteria )").unwrap_err();
+        assert_eq!(err.position, 9);
+
+        let rendered = err.to_string();
+        assert!(rendered.contains("Bacteria )"));
+        assert!(rendered.ends_with(&format!("{}^", " ".repeat(2 + 9))));
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        let expr = "(Bacteria OR 2157) AND NOT rank:species AND length>50";
+        let filter = TaxonomyFilter::parse(expr).unwrap();
+        let reparsed = TaxonomyFilter::parse(&filter.to_string()).unwrap();
+        assert_eq!(filter, reparsed);
+    }
 }
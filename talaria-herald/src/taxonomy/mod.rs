@@ -4,11 +4,15 @@ pub mod evolution;
 pub mod extractor;
 pub mod filter;
 pub mod manifest;
+pub mod names;
 pub mod prerequisites;
+pub mod reclassify;
 pub mod types;
 
 // Re-export commonly used types
+pub use names::{NameClass, NameMatch, NameResolution, TaxonNameIndex};
 pub use prerequisites::TaxonomyPrerequisites;
+pub use reclassify::{TaxonChange, TaxonomySnapshot};
 pub use types::{
     AuditEntry, InstalledComponent, TaxonomyManifest, TaxonomyManifestFormat,
     TaxonomyVersionPolicy, VersionDecision,
@@ -31,6 +35,9 @@ pub struct TaxonomyManager {
     taxon_to_chunks: HashMap<TaxonId, Vec<SHA256Hash>>,
     accession_to_taxon: HashMap<String, TaxonId>,
     version_history: Vec<TaxonomyVersion>,
+    name_index: Option<TaxonNameIndex>,
+    /// Lower-cased names of the loaded tree, built on first lookup without a name index
+    tree_names: std::sync::OnceLock<HashMap<String, Vec<TaxonId>>>,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -78,6 +85,8 @@ impl TaxonomyManager {
             taxon_to_chunks: HashMap::new(),
             accession_to_taxon: HashMap::new(),
             version_history: Vec::new(),
+            name_index: None,
+            tree_names: std::sync::OnceLock::new(),
         })
     }
 
@@ -238,6 +247,7 @@ impl TaxonomyManager {
             .ok_or_else(|| anyhow::anyhow!("Root taxon not found"))?
             .clone();
 
+        self.tree_names = std::sync::OnceLock::new();
         self.taxonomy_tree = Some(TaxonomyTree {
             root,
             id_to_node: nodes,
@@ -520,6 +530,7 @@ impl TaxonomyManager {
 
     fn load_taxonomy_tree(&mut self, path: &Path) -> Result<()> {
         let content = fs::read_to_string(path)?;
+        self.tree_names = std::sync::OnceLock::new();
         self.taxonomy_tree = Some(serde_json::from_str(&content)?);
         Ok(())
     }
@@ -644,7 +655,7 @@ impl TaxonomyManager {
                     }
 
                     // Sort by date
-                    versions.sort_by(|a, b| a.date.cmp(&b.date));
+                    versions.sort_by_key(|a| a.date);
                     self.version_history = versions;
 
                     return Ok(());
@@ -720,6 +731,7 @@ impl TaxonomyManager {
                 children: Vec::new(),
             };
 
+            self.tree_names = std::sync::OnceLock::new();
             self.taxonomy_tree = Some(TaxonomyTree {
                 root: root.clone(),
                 id_to_node: std::iter::once((TaxonId(1), root)).collect(),
@@ -989,6 +1001,97 @@ impl TaxonomyManager {
     }
 }
 
+impl filter::TaxonomyResolver for TaxonomyManager {
+    fn resolve_name(&self, name: &str) -> Vec<TaxonId> {
+        if let Some(index) = &self.name_index {
+            return index.resolve_ids(name);
+        }
+
+        let Some(tree) = &self.taxonomy_tree else {
+            return Vec::new();
+        };
+
+        let names = self.tree_names.get_or_init(|| {
+            let mut names: HashMap<String, Vec<TaxonId>> = HashMap::new();
+            for (id, node) in &tree.id_to_node {
+                names
+                    .entry(node.name.to_ascii_lowercase())
+                    .or_default()
+                    .push(*id);
+            }
+            for ids in names.values_mut() {
+                ids.sort();
+            }
+            names
+        });
+        names
+            .get(&name.to_ascii_lowercase())
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    fn lineage(&self, taxon_id: TaxonId) -> Vec<filter::LineageEntry> {
+        self.get_lineage(&taxon_id)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|node| (node.taxon_id, node.name, node.rank))
+            .collect()
+    }
+}
+
+impl TaxonomyManager {
+    /// Load (or build) the taxon name index for a taxonomy tree directory
+    ///
+    /// The index is tied to the taxonomy version the tree directory belongs
+    /// to and is rebuilt when `names.dmp` changes.
+    pub fn load_name_index(&mut self, tree_dir: &Path) -> Result<()> {
+        let version = tree_dir
+            .parent()
+            .and_then(|dir| fs::canonicalize(dir).ok())
+            .and_then(|dir| dir.file_name().map(|n| n.to_string_lossy().into_owned()))
+            .unwrap_or_else(|| "unknown".to_string());
+
+        self.name_index = Some(TaxonNameIndex::load_or_build(tree_dir, &version)?);
+        Ok(())
+    }
+
+    /// Get the taxon name index, if loaded
+    pub fn name_index(&self) -> Option<&TaxonNameIndex> {
+        self.name_index.as_ref()
+    }
+
+    /// Resolve a taxon name (scientific, synonym, equivalent or common name)
+    pub fn resolve_taxon_name(&self, name: &str) -> Result<NameResolution> {
+        let index = self
+            .name_index
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Taxon name index not loaded"))?;
+        Ok(index.resolve(name))
+    }
+
+    /// Select all taxa in the loaded taxonomy that satisfy a filter
+    pub fn select_taxa(&self, taxonomy_filter: &filter::TaxonomyFilter) -> Result<Vec<TaxonId>> {
+        let tree = self
+            .taxonomy_tree
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("No taxonomy loaded"))?;
+
+        let ctx = filter::FilterContext::new().with_resolver(self);
+        if let Some(ids) = taxonomy_filter.identity_taxa(&ctx) {
+            return Ok(ids);
+        }
+
+        let mut selected: Vec<TaxonId> = tree
+            .id_to_node
+            .keys()
+            .filter(|id| taxonomy_filter.matches_with(&[**id], &ctx))
+            .cloned()
+            .collect();
+        selected.sort();
+        Ok(selected)
+    }
+}
+
 // Implement serialization for TaxonomyTree
 impl serde::Serialize for TaxonomyTree {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
@@ -0,0 +1,461 @@
+//! Taxon name index built from NCBI `names.dmp`
+//!
+//! Indexes scientific names, synonyms, equivalent names and common names so
+//! users can refer to taxa as "Escherichia coli" rather than 562. Lookups are
+//! case-insensitive; misspelled names fall back to fuzzy suggestions.
+//!
+//! The index is persisted next to the taxonomy files it was built from and
+//! records the taxonomy version and a fingerprint of `names.dmp`, so a stale
+//! index is rebuilt automatically after a taxonomy update.
+
+use crate::types::TaxonId;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// File name of the persisted index inside the taxonomy tree directory
+pub const NAME_INDEX_FILE: &str = "names_index.tal";
+
+/// Bumped whenever the on-disk layout of the index changes
+const NAME_INDEX_FORMAT: u32 = 1;
+
+/// Maximum edit distance considered for fuzzy suggestions
+const MAX_FUZZY_DISTANCE: usize = 3;
+
+/// Class of a name as recorded in `names.dmp`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum NameClass {
+    ScientificName,
+    EquivalentName,
+    Synonym,
+    CommonName,
+}
+
+impl NameClass {
+    /// Map a `names.dmp` name class; classes we do not index return `None`
+    pub fn from_dmp(class: &str) -> Option<Self> {
+        match class {
+            "scientific name" => Some(Self::ScientificName),
+            "equivalent name" => Some(Self::EquivalentName),
+            "synonym" => Some(Self::Synonym),
+            "common name" | "genbank common name" => Some(Self::CommonName),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ScientificName => "scientific name",
+            Self::EquivalentName => "equivalent name",
+            Self::Synonym => "synonym",
+            Self::CommonName => "common name",
+        }
+    }
+}
+
+/// A single indexed name
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NameEntry {
+    pub taxon_id: TaxonId,
+    pub name: String,
+    /// NCBI unique name for homonyms, e.g. "Bacteria <bacteria>"
+    pub unique_name: Option<String>,
+    pub class: NameClass,
+}
+
+/// A lookup hit, with the edit distance for fuzzy matches (0 = exact)
+#[derive(Debug, Clone, PartialEq)]
+pub struct NameMatch {
+    pub entry: NameEntry,
+    pub distance: usize,
+}
+
+/// Outcome of resolving a user-supplied name to a single taxon
+#[derive(Debug, Clone, PartialEq)]
+pub enum NameResolution {
+    /// Exactly one taxon carries this name
+    Unique(TaxonId),
+    /// Several taxa share the name (homonyms, possibly across kingdoms)
+    Ambiguous(Vec<NameMatch>),
+    /// No exact match; closest names by edit distance
+    NotFound { suggestions: Vec<NameMatch> },
+}
+
+/// Case-insensitive, fuzzy-searchable index of taxon names
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaxonNameIndex {
+    format: u32,
+    /// Taxonomy version the index was built from
+    pub taxonomy_version: String,
+    /// Size and modification time of the source `names.dmp`
+    source_fingerprint: (u64, u64),
+    entries: Vec<NameEntry>,
+    /// Normalized name (and unique name) to entry positions
+    by_name: HashMap<String, Vec<usize>>,
+}
+
+impl TaxonNameIndex {
+    /// Build an index from the contents of a `names.dmp` file
+    pub fn from_dmp(content: &str, taxonomy_version: &str) -> Result<Self> {
+        let mut index = Self {
+            format: NAME_INDEX_FORMAT,
+            taxonomy_version: taxonomy_version.to_string(),
+            source_fingerprint: (0, 0),
+            entries: Vec::new(),
+            by_name: HashMap::new(),
+        };
+
+        for line in content.lines() {
+            let parts: Vec<&str> = line.split("\t|\t").collect();
+            if parts.len() < 4 {
+                continue;
+            }
+
+            let Some(class) = NameClass::from_dmp(parts[3].trim_end_matches("\t|")) else {
+                continue;
+            };
+            let taxon_id = TaxonId(
+                parts[0]
+                    .trim()
+                    .parse()
+                    .with_context(|| format!("Invalid taxon ID in names.dmp line: {}", line))?,
+            );
+            let unique_name = Some(parts[2].trim())
+                .filter(|u| !u.is_empty())
+                .map(str::to_string);
+
+            index.insert(NameEntry {
+                taxon_id,
+                name: parts[1].trim().to_string(),
+                unique_name,
+                class,
+            });
+        }
+
+        Ok(index)
+    }
+
+    /// Build an index from a `names.dmp` file on disk
+    pub fn build(names_file: &Path, taxonomy_version: &str) -> Result<Self> {
+        let content = fs::read_to_string(names_file)
+            .with_context(|| format!("Failed to read {}", names_file.display()))?;
+        let mut index = Self::from_dmp(&content, taxonomy_version)?;
+        index.source_fingerprint = fingerprint(names_file)?;
+        Ok(index)
+    }
+
+    /// Load the persisted index for a taxonomy tree directory, rebuilding it
+    /// when missing or built from a different taxonomy version
+    pub fn load_or_build(tree_dir: &Path, taxonomy_version: &str) -> Result<Self> {
+        let names_file = tree_dir.join("names.dmp");
+        let index_file = tree_dir.join(NAME_INDEX_FILE);
+
+        if let Ok(index) = Self::load(&index_file) {
+            if index.is_current(&names_file, taxonomy_version) {
+                return Ok(index);
+            }
+            tracing::info!(
+                "Taxon name index is stale (built for {}), rebuilding",
+                index.taxonomy_version
+            );
+        }
+
+        let index = Self::build(&names_file, taxonomy_version)?;
+        if let Err(e) = index.save(&index_file) {
+            // A read-only taxonomy directory only costs us a rebuild next time
+            tracing::warn!("Could not save taxon name index: {}", e);
+        }
+        Ok(index)
+    }
+
+    /// Whether this index matches the given `names.dmp` and taxonomy version
+    pub fn is_current(&self, names_file: &Path, taxonomy_version: &str) -> bool {
+        self.format == NAME_INDEX_FORMAT
+            && self.taxonomy_version == taxonomy_version
+            && fingerprint(names_file).ok() == Some(self.source_fingerprint)
+    }
+
+    /// Load a persisted index (MessagePack)
+    pub fn load(path: &Path) -> Result<Self> {
+        let bytes = fs::read(path)
+            .with_context(|| format!("Failed to read name index: {}", path.display()))?;
+        rmp_serde::from_slice(&bytes).context("Failed to deserialize taxon name index")
+    }
+
+    /// Persist the index (MessagePack)
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let bytes = rmp_serde::to_vec(self).context("Failed to serialize taxon name index")?;
+        fs::write(path, bytes)
+            .with_context(|| format!("Failed to write name index: {}", path.display()))?;
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Scientific name of a taxon, if indexed
+    pub fn scientific_name(&self, taxon_id: TaxonId) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|e| e.taxon_id == taxon_id && e.class == NameClass::ScientificName)
+            .map(|e| e.name.as_str())
+    }
+
+    /// Case-insensitive exact lookup over all name classes and unique names
+    ///
+    /// Results are ordered by name class (scientific names first), then
+    /// taxon ID, with at most one entry per taxon.
+    pub fn lookup(&self, name: &str) -> Vec<NameMatch> {
+        let mut matches: Vec<NameMatch> = self
+            .by_name
+            .get(&normalize(name))
+            .into_iter()
+            .flatten()
+            .map(|&i| NameMatch {
+                entry: self.entries[i].clone(),
+                distance: 0,
+            })
+            .collect();
+
+        matches.sort_by(|a, b| {
+            (a.entry.class, a.entry.taxon_id).cmp(&(b.entry.class, b.entry.taxon_id))
+        });
+        let mut seen = std::collections::HashSet::new();
+        matches.retain(|m| seen.insert(m.entry.taxon_id));
+        matches
+    }
+
+    /// Taxon IDs carrying this name, preferring scientific-name matches
+    pub fn resolve_ids(&self, name: &str) -> Vec<TaxonId> {
+        let matches = self.lookup(name);
+        let scientific: Vec<TaxonId> = matches
+            .iter()
+            .filter(|m| m.entry.class == NameClass::ScientificName)
+            .map(|m| m.entry.taxon_id)
+            .collect();
+
+        if scientific.is_empty() {
+            matches.iter().map(|m| m.entry.taxon_id).collect()
+        } else {
+            scientific
+        }
+    }
+
+    /// Closest names by edit distance, best first
+    pub fn fuzzy_lookup(&self, name: &str, limit: usize) -> Vec<NameMatch> {
+        let query = normalize(name);
+        let query_len = query.chars().count();
+        let max_distance = MAX_FUZZY_DISTANCE.min(query_len / 3 + 1);
+
+        let mut candidates: Vec<(usize, &str)> = self
+            .by_name
+            .keys()
+            .filter(|key| key.chars().count().abs_diff(query_len) <= max_distance)
+            .filter_map(|key| {
+                bounded_levenshtein(&query, key, max_distance).map(|d| (d, key.as_str()))
+            })
+            .collect();
+        candidates.sort();
+
+        let mut seen = std::collections::HashSet::new();
+        let mut matches = Vec::new();
+        for (distance, key) in candidates {
+            for &i in &self.by_name[key] {
+                let entry = &self.entries[i];
+                if seen.insert(entry.taxon_id) {
+                    matches.push(NameMatch {
+                        entry: entry.clone(),
+                        distance,
+                    });
+                }
+            }
+            if matches.len() >= limit {
+                break;
+            }
+        }
+        matches.truncate(limit);
+        matches
+    }
+
+    /// Resolve a name to a single taxon, reporting homonyms and suggestions
+    pub fn resolve(&self, name: &str) -> NameResolution {
+        let ids = self.resolve_ids(name);
+        match ids.as_slice() {
+            [id] => NameResolution::Unique(*id),
+            [] => NameResolution::NotFound {
+                suggestions: self.fuzzy_lookup(name, 5),
+            },
+            _ => NameResolution::Ambiguous(
+                self.lookup(name)
+                    .into_iter()
+                    .filter(|m| ids.contains(&m.entry.taxon_id))
+                    .collect(),
+            ),
+        }
+    }
+
+    fn insert(&mut self, entry: NameEntry) {
+        let position = self.entries.len();
+        let mut keys = vec![normalize(&entry.name)];
+        if let Some(unique) = &entry.unique_name {
+            keys.push(normalize(unique));
+        }
+        keys.dedup();
+
+        for key in keys {
+            self.by_name.entry(key).or_default().push(position);
+        }
+        self.entries.push(entry);
+    }
+}
+
+/// Lower-case and collapse whitespace so "escherichia  COLI" finds E. coli
+fn normalize(name: &str) -> String {
+    name.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+fn fingerprint(path: &Path) -> Result<(u64, u64)> {
+    let metadata = fs::metadata(path)?;
+    let modified = metadata
+        .modified()?
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    Ok((metadata.len(), modified))
+}
+
+/// Levenshtein distance, or `None` once it is known to exceed `max`
+fn bounded_levenshtein(a: &str, b: &str, max: usize) -> Option<usize> {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut curr = vec![0; b.len() + 1];
+
+    for (i, ca) in a.iter().enumerate() {
+        curr[0] = i + 1;
+        let mut row_min = curr[0];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != cb);
+            curr[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(curr[j] + 1);
+            row_min = row_min.min(curr[j + 1]);
+        }
+        if row_min > max {
+            return None;
+        }
+        std::mem::swap(&mut prev, &mut curr);
+    }
+
+    Some(prev[b.len()]).filter(|&d| d <= max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NAMES_DMP: &str = "\
+1\t|\troot\t|\t\t|\tscientific name\t|
+2\t|\tBacteria\t|\tBacteria <bacteria>\t|\tscientific name\t|
+2\t|\teubacteria\t|\t\t|\tgenbank common name\t|
+561\t|\tEscherichia\t|\t\t|\tscientific name\t|
+562\t|\tEscherichia coli\t|\t\t|\tscientific name\t|
+562\t|\tBacillus coli\t|\t\t|\tsynonym\t|
+562\t|\tE. coli\t|\t\t|\tcommon name\t|
+562\t|\tMigula 1895\t|\t\t|\tauthority\t|
+629395\t|\tBacteria\t|\tBacteria <walking sticks>\t|\tscientific name\t|
+9606\t|\tHomo sapiens\t|\t\t|\tscientific name\t|
+9606\t|\thuman\t|\t\t|\tgenbank common name\t|
+";
+
+    fn index() -> TaxonNameIndex {
+        TaxonNameIndex::from_dmp(NAMES_DMP, "test").unwrap()
+    }
+
+    #[test]
+    fn test_name_classes() {
+        let index = index();
+        // Authority names are not indexed
+        assert_eq!(index.len(), 10);
+        assert!(index.lookup("Migula 1895").is_empty());
+
+        assert_eq!(index.resolve_ids("Bacillus coli"), vec![TaxonId(562)]);
+        assert_eq!(index.resolve_ids("human"), vec![TaxonId(9606)]);
+        assert_eq!(
+            index.scientific_name(TaxonId(562)),
+            Some("Escherichia coli")
+        );
+    }
+
+    #[test]
+    fn test_case_insensitive_lookup() {
+        let index = index();
+        assert_eq!(
+            index.resolve("escherichia  COLI"),
+            NameResolution::Unique(TaxonId(562))
+        );
+        assert_eq!(
+            index.resolve("e. coli"),
+            NameResolution::Unique(TaxonId(562))
+        );
+    }
+
+    #[test]
+    fn test_homonyms() {
+        let index = index();
+        match index.resolve("Bacteria") {
+            NameResolution::Ambiguous(matches) => {
+                let ids: Vec<_> = matches.iter().map(|m| m.entry.taxon_id).collect();
+                assert_eq!(ids, vec![TaxonId(2), TaxonId(629395)]);
+            }
+            other => panic!("expected ambiguity, got {:?}", other),
+        }
+
+        // Unique names disambiguate
+        assert_eq!(
+            index.resolve("Bacteria <walking sticks>"),
+            NameResolution::Unique(TaxonId(629395))
+        );
+    }
+
+    #[test]
+    fn test_fuzzy_suggestions() {
+        let index = index();
+        match index.resolve("Escherichia colli") {
+            NameResolution::NotFound { suggestions } => {
+                assert_eq!(suggestions[0].entry.taxon_id, TaxonId(562));
+                assert_eq!(suggestions[0].distance, 1);
+            }
+            other => panic!("expected suggestions, got {:?}", other),
+        }
+
+        assert!(index.fuzzy_lookup("zzzzzzzz", 5).is_empty());
+    }
+
+    #[test]
+    fn test_versioned_persistence() {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::write(dir.path().join("names.dmp"), NAMES_DMP).unwrap();
+
+        let built = TaxonNameIndex::load_or_build(dir.path(), "2024-01-01").unwrap();
+        assert!(dir.path().join(NAME_INDEX_FILE).exists());
+
+        let loaded = TaxonNameIndex::load(&dir.path().join(NAME_INDEX_FILE)).unwrap();
+        assert_eq!(loaded.len(), built.len());
+        assert!(loaded.is_current(&dir.path().join("names.dmp"), "2024-01-01"));
+
+        // A different taxonomy version invalidates the index
+        assert!(!loaded.is_current(&dir.path().join("names.dmp"), "2024-02-01"));
+        let rebuilt = TaxonNameIndex::load_or_build(dir.path(), "2024-02-01").unwrap();
+        assert_eq!(rebuilt.taxonomy_version, "2024-02-01");
+    }
+}
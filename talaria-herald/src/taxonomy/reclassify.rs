@@ -0,0 +1,257 @@
+//! Taxonomy-only reclassification
+//!
+//! Compares two NCBI taxdump releases over the taxa a database actually uses.
+//! Taxa can be merged into another taxon (`merged.dmp`), deleted
+//! (`delnodes.dmp`), or keep their ID while moving to a different lineage.
+//! The result drives re-indexing of existing sequence versions under a new
+//! taxonomy without touching sequence data or chunks.
+
+use crate::types::TaxonId;
+use anyhow::{Context, Result};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+/// Upper bound on merge chains and lineage depth, guarding against cycles
+const MAX_DEPTH: usize = 256;
+
+/// Parent links of one taxdump release, plus its merged and deleted taxa
+#[derive(Debug, Default)]
+pub struct TaxonomySnapshot {
+    parents: HashMap<TaxonId, Option<TaxonId>>,
+    merged: HashMap<TaxonId, TaxonId>,
+    deleted: HashSet<TaxonId>,
+}
+
+/// How a taxon used by a database changed between two taxonomy releases
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaxonChange {
+    /// The taxon was merged into another taxon
+    Merged(TaxonId),
+    /// The taxon no longer exists
+    Deleted,
+    /// The taxon kept its ID but its lineage changed
+    Moved,
+}
+
+impl TaxonChange {
+    /// Taxon that sequences of `old` belong to after the change
+    pub fn new_taxon(&self, old: TaxonId) -> Option<TaxonId> {
+        match self {
+            TaxonChange::Merged(new) => Some(*new),
+            TaxonChange::Deleted => None,
+            TaxonChange::Moved => Some(old),
+        }
+    }
+
+    pub fn reason(&self) -> &'static str {
+        match self {
+            TaxonChange::Merged(_) => "Taxon merged",
+            TaxonChange::Deleted => "Taxon deleted",
+            TaxonChange::Moved => "Lineage changed",
+        }
+    }
+}
+
+impl TaxonomySnapshot {
+    /// Load a snapshot from a taxdump directory
+    ///
+    /// `nodes.dmp` is required; `merged.dmp` and `delnodes.dmp` are optional.
+    pub fn from_dmp(tree_dir: &Path) -> Result<Self> {
+        let read_optional = |name: &str| -> Result<Option<String>> {
+            let path = tree_dir.join(name);
+            if path.exists() {
+                Ok(Some(fs::read_to_string(&path).with_context(|| {
+                    format!("Failed to read {}", path.display())
+                })?))
+            } else {
+                Ok(None)
+            }
+        };
+
+        let nodes_path = tree_dir.join("nodes.dmp");
+        let nodes = fs::read_to_string(&nodes_path)
+            .with_context(|| format!("Failed to read {}", nodes_path.display()))?;
+
+        Self::parse(
+            &nodes,
+            read_optional("merged.dmp")?.as_deref(),
+            read_optional("delnodes.dmp")?.as_deref(),
+        )
+    }
+
+    /// Parse a snapshot from the contents of taxdump files
+    pub fn parse(nodes: &str, merged: Option<&str>, delnodes: Option<&str>) -> Result<Self> {
+        let mut snapshot = Self::default();
+
+        for line in nodes.lines() {
+            let fields = dmp_fields(line);
+            if fields.len() < 2 {
+                continue;
+            }
+            let taxon = parse_taxon(fields[0])?;
+            let parent = parse_taxon(fields[1])?;
+            snapshot
+                .parents
+                .insert(taxon, (parent != taxon).then_some(parent));
+        }
+
+        for line in merged.unwrap_or_default().lines() {
+            let fields = dmp_fields(line);
+            if fields.len() < 2 {
+                continue;
+            }
+            snapshot
+                .merged
+                .insert(parse_taxon(fields[0])?, parse_taxon(fields[1])?);
+        }
+
+        for line in delnodes.unwrap_or_default().lines() {
+            if let Some(field) = dmp_fields(line).first().filter(|f| !f.is_empty()) {
+                snapshot.deleted.insert(parse_taxon(field)?);
+            }
+        }
+
+        Ok(snapshot)
+    }
+
+    /// Number of taxa in the tree
+    pub fn len(&self) -> usize {
+        self.parents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parents.is_empty()
+    }
+
+    pub fn contains(&self, taxon: TaxonId) -> bool {
+        self.parents.contains_key(&taxon)
+    }
+
+    /// Current ID of `taxon`, following merges; `None` if it was deleted or is unknown
+    pub fn resolve(&self, taxon: TaxonId) -> Option<TaxonId> {
+        let mut current = taxon;
+        for _ in 0..MAX_DEPTH {
+            if self.contains(current) {
+                return Some(current);
+            }
+            current = *self.merged.get(&current)?;
+        }
+        None
+    }
+
+    /// Lineage of `taxon` from the root down to the taxon itself
+    pub fn lineage(&self, taxon: TaxonId) -> Vec<TaxonId> {
+        let mut lineage = Vec::new();
+        let mut current = Some(taxon);
+        while let Some(id) = current {
+            if lineage.len() >= MAX_DEPTH || !self.contains(id) {
+                break;
+            }
+            lineage.push(id);
+            current = self.parents[&id];
+        }
+        lineage.reverse();
+        lineage
+    }
+}
+
+/// Compare how `taxa` are classified in two taxonomy releases
+///
+/// Taxa unknown to both releases (e.g. the unclassified taxon 0) are ignored.
+pub fn diff_taxa(
+    old: &TaxonomySnapshot,
+    new: &TaxonomySnapshot,
+    taxa: impl IntoIterator<Item = TaxonId>,
+) -> BTreeMap<TaxonId, TaxonChange> {
+    let mut changes = BTreeMap::new();
+
+    for taxon in taxa {
+        let change = if new.contains(taxon) {
+            (old.contains(taxon) && old.lineage(taxon) != new.lineage(taxon))
+                .then_some(TaxonChange::Moved)
+        } else if let Some(merged_into) = new.resolve(taxon) {
+            Some(TaxonChange::Merged(merged_into))
+        } else if old.contains(taxon) || new.deleted.contains(&taxon) {
+            Some(TaxonChange::Deleted)
+        } else {
+            None
+        };
+
+        if let Some(change) = change {
+            changes.insert(taxon, change);
+        }
+    }
+
+    changes
+}
+
+fn dmp_fields(line: &str) -> Vec<&str> {
+    line.trim_end_matches("\t|")
+        .split("\t|\t")
+        .map(str::trim)
+        .collect()
+}
+
+fn parse_taxon(field: &str) -> Result<TaxonId> {
+    Ok(TaxonId(field.trim().parse().with_context(|| {
+        format!("Invalid taxon ID '{}'", field)
+    })?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OLD_NODES: &str = "1\t|\t1\t|\tno rank\t|\n\
+                             2\t|\t1\t|\tsuperkingdom\t|\n\
+                             1578\t|\t2\t|\tgenus\t|\n\
+                             1579\t|\t1578\t|\tspecies\t|\n\
+                             1580\t|\t1578\t|\tspecies\t|\n\
+                             1600\t|\t2\t|\tspecies\t|\n";
+
+    // 1580 merged into 1579, 1600 deleted, new genus 2759 takes over 1579
+    const NEW_NODES: &str = "1\t|\t1\t|\tno rank\t|\n\
+                             2\t|\t1\t|\tsuperkingdom\t|\n\
+                             1578\t|\t2\t|\tgenus\t|\n\
+                             2759\t|\t2\t|\tgenus\t|\n\
+                             1579\t|\t2759\t|\tspecies\t|\n";
+
+    fn snapshots() -> (TaxonomySnapshot, TaxonomySnapshot) {
+        let old = TaxonomySnapshot::parse(OLD_NODES, None, None).unwrap();
+        let new = TaxonomySnapshot::parse(NEW_NODES, Some("1580\t|\t1579\t|\n"), Some("1600\t|\n"))
+            .unwrap();
+        (old, new)
+    }
+
+    #[test]
+    fn test_lineage_and_resolve() {
+        let (old, new) = snapshots();
+        assert_eq!(
+            old.lineage(TaxonId(1579)),
+            vec![TaxonId(1), TaxonId(2), TaxonId(1578), TaxonId(1579)]
+        );
+        assert_eq!(new.resolve(TaxonId(1580)), Some(TaxonId(1579)));
+        assert_eq!(new.resolve(TaxonId(1600)), None);
+        assert_eq!(new.len(), 5);
+    }
+
+    #[test]
+    fn test_diff_taxa() {
+        let (old, new) = snapshots();
+        let changes = diff_taxa(
+            &old,
+            &new,
+            [0, 2, 1578, 1579, 1580, 1600].into_iter().map(TaxonId),
+        );
+
+        assert_eq!(changes.len(), 3);
+        assert_eq!(changes[&TaxonId(1579)], TaxonChange::Moved);
+        assert_eq!(changes[&TaxonId(1580)], TaxonChange::Merged(TaxonId(1579)));
+        assert_eq!(changes[&TaxonId(1600)], TaxonChange::Deleted);
+        assert_eq!(
+            changes[&TaxonId(1580)].new_taxon(TaxonId(1580)),
+            Some(TaxonId(1579))
+        );
+    }
+}
@@ -1,6 +1,10 @@
 /// Core types for the HERALD system
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use talaria_core::config::ChunkingConfig;
+
+use crate::chunker::TaxonomicRank;
 
 // Re-export only primitive types from talaria-core
 pub use talaria_core::types::{SHA256Hash, TaxonId};
@@ -73,6 +77,24 @@ pub struct SerializedMerkleTree {
     pub serialized_nodes: Vec<u8>, // Compact binary representation
 }
 
+impl SerializedMerkleTree {
+    /// Merkle tree over a manifest's chunk index, if it has any chunks
+    pub fn from_chunk_index(chunk_index: &[ManifestMetadata]) -> anyhow::Result<Option<Self>> {
+        if chunk_index.is_empty() {
+            return Ok(None);
+        }
+        let dag = crate::verification::MerkleDAG::build_from_items(chunk_index.to_vec())?;
+        let root_hash = dag
+            .root_hash()
+            .ok_or_else(|| anyhow::anyhow!("Failed to get Merkle root"))?;
+        Ok(Some(Self {
+            root_hash,
+            node_count: chunk_index.len(),
+            serialized_nodes: rmp_serde::to_vec(&dag)?,
+        }))
+    }
+}
+
 /// Bi-temporal coordinate for versioning
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BiTemporalCoordinate {
@@ -318,6 +340,10 @@ pub struct ChunkingStrategy {
     pub min_sequences_per_chunk: usize,  // Minimum sequences
     pub taxonomic_coherence: f32,        // 0.0 to 1.0
     pub special_taxa: Vec<SpecialTaxon>, // Special handling
+    #[serde(default)]
+    pub boundaries: ChunkBoundaries, // How chunk cut points are chosen
+    #[serde(default)]
+    pub rank_min_sizes: BTreeMap<String, usize>, // Hierarchical overrides by rank name
 }
 
 impl Default for ChunkingStrategy {
@@ -328,6 +354,144 @@ impl Default for ChunkingStrategy {
             min_sequences_per_chunk: 10,
             taxonomic_coherence: 0.8,
             special_taxa: Vec::new(),
+            boundaries: ChunkBoundaries::default(),
+            rank_min_sizes: BTreeMap::new(),
+        }
+    }
+}
+
+impl ChunkingStrategy {
+    /// Build a strategy from the `[chunking]` config section
+    pub fn from_config(config: &ChunkingConfig) -> anyhow::Result<Self> {
+        let boundaries = match &config.boundaries {
+            Some(b) => b.parse()?,
+            None => ChunkBoundaries::from_env()?.unwrap_or_default(),
+        };
+
+        let special_taxa = config
+            .special_taxa
+            .iter()
+            .map(|t| {
+                Ok(SpecialTaxon {
+                    taxon_id: TaxonId(t.taxon_id),
+                    name: t.name.clone(),
+                    strategy: t.strategy.parse()?,
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        for rank in config.rank_min_sizes.keys() {
+            if TaxonomicRank::parse(rank).is_none() {
+                anyhow::bail!("Unknown rank '{}' in chunking.rank_min_sizes", rank);
+            }
+        }
+
+        let strategy = Self {
+            target_chunk_size: config.target_chunk_size,
+            max_chunk_size: config.max_chunk_size,
+            min_sequences_per_chunk: config.min_sequences_per_chunk,
+            taxonomic_coherence: config.taxonomic_coherence as f32,
+            special_taxa,
+            boundaries,
+            rank_min_sizes: config
+                .rank_min_sizes
+                .iter()
+                .map(|(rank, size)| (rank.to_lowercase(), *size))
+                .collect(),
+        };
+        strategy.validate()?;
+        Ok(strategy)
+    }
+
+    /// Strategy from the user's `[chunking]` config, or the defaults
+    pub fn from_user_config() -> anyhow::Result<Self> {
+        let config = talaria_core::config::load_user_config()?;
+        Self::from_config(&config.chunking)
+    }
+
+    /// Reject sizes the chunkers cannot honour
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.target_chunk_size == 0 {
+            anyhow::bail!("Chunk target size must be greater than zero");
+        }
+        if self.max_chunk_size < self.target_chunk_size {
+            anyhow::bail!(
+                "Maximum chunk size ({} bytes) is smaller than the target size ({} bytes)",
+                self.max_chunk_size,
+                self.target_chunk_size
+            );
+        }
+        if !(0.0..=1.0).contains(&self.taxonomic_coherence) {
+            anyhow::bail!("Taxonomic coherence must be between 0.0 and 1.0");
+        }
+        Ok(())
+    }
+
+    /// Minimum hierarchical chunk size for a rank, honouring overrides
+    pub fn rank_min_chunk_size(&self, rank: TaxonomicRank) -> usize {
+        self.rank_min_sizes
+            .get(rank.as_str())
+            .copied()
+            .unwrap_or_else(|| rank.min_chunk_size())
+    }
+
+    /// Add or replace the rule for a taxon
+    pub fn set_special_taxon(&mut self, taxon: SpecialTaxon) {
+        self.special_taxa.retain(|t| t.taxon_id != taxon.taxon_id);
+        self.special_taxa.push(taxon);
+    }
+
+    /// Rule configured for a taxon, if any
+    pub fn special_taxon(&self, taxon_id: TaxonId) -> Option<&SpecialTaxon> {
+        self.special_taxa.iter().find(|t| t.taxon_id == taxon_id)
+    }
+}
+
+/// How sequences within a taxon are split into chunks
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ChunkBoundaries {
+    /// Fill chunks up to the target size in input order
+    #[default]
+    Packed,
+    /// Cut where a rolling hash over the sorted sequence hashes matches, so a
+    /// local edit only changes neighbouring chunks between versions
+    ContentDefined,
+}
+
+impl ChunkBoundaries {
+    /// Policy set by `TALARIA_CHUNK_BOUNDARIES`, if any
+    pub fn from_env() -> anyhow::Result<Option<Self>> {
+        match std::env::var("TALARIA_CHUNK_BOUNDARIES") {
+            Ok(value) => value
+                .parse()
+                .map(Some)
+                .map_err(|e| anyhow::anyhow!("Invalid TALARIA_CHUNK_BOUNDARIES: {}", e)),
+            Err(_) => Ok(None),
+        }
+    }
+}
+
+impl std::str::FromStr for ChunkBoundaries {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "packed" | "size" => Ok(Self::Packed),
+            "content-defined" | "content" | "cdc" => Ok(Self::ContentDefined),
+            _ => anyhow::bail!(
+                "Unknown chunk boundary policy '{}'. Use: packed, content-defined",
+                s
+            ),
+        }
+    }
+}
+
+impl std::fmt::Display for ChunkBoundaries {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Packed => write!(f, "packed"),
+            Self::ContentDefined => write!(f, "content-defined"),
         }
     }
 }
@@ -339,11 +503,67 @@ pub struct SpecialTaxon {
     pub strategy: ChunkStrategy,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ChunkStrategy {
     OwnChunks,         // Always separate chunks (e.g., E. coli)
     GroupWithSiblings, // Group with taxonomic siblings
-    GroupAtLevel(u8),  // Group at specific taxonomic level
+    GroupAtLevel(u8),  // Group at specific taxonomic level (index into TaxonomicRank::all)
+}
+
+impl ChunkStrategy {
+    /// Group everything below a taxon at the given rank
+    pub fn group_at(rank: TaxonomicRank) -> Self {
+        let level = TaxonomicRank::all()
+            .iter()
+            .position(|r| *r == rank)
+            .unwrap_or_default();
+        Self::GroupAtLevel(level as u8)
+    }
+
+    /// Rank a `GroupAtLevel` strategy groups at
+    pub fn rank(&self) -> Option<TaxonomicRank> {
+        match self {
+            Self::GroupAtLevel(level) => TaxonomicRank::all().get(*level as usize).copied(),
+            _ => None,
+        }
+    }
+}
+
+impl std::str::FromStr for ChunkStrategy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.to_lowercase();
+        match s.as_str() {
+            "own-chunks" | "own" | "isolate" => Ok(Self::OwnChunks),
+            "group-with-siblings" | "siblings" => Ok(Self::GroupWithSiblings),
+            _ => {
+                let rank = s
+                    .strip_prefix("group-at-")
+                    .and_then(TaxonomicRank::parse)
+                    .ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "Unknown chunk strategy '{}'. Use: own-chunks, group-with-siblings, group-at-<rank>",
+                            s
+                        )
+                    })?;
+                Ok(Self::group_at(rank))
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for ChunkStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::OwnChunks => write!(f, "own-chunks"),
+            Self::GroupWithSiblings => write!(f, "group-with-siblings"),
+            Self::GroupAtLevel(level) => match self.rank() {
+                Some(rank) => write!(f, "group-at-{}", rank.as_str()),
+                None => write!(f, "group-at-level-{}", level),
+            },
+        }
+    }
 }
 
 // Re-export from talaria-core
@@ -381,6 +601,37 @@ pub struct TemporalDeltaChunk {
     pub compression_ratio: f32,
 }
 
+impl TemporalDeltaChunk {
+    /// Content hash of `deltas` applied to `reference_hash`
+    ///
+    /// The reference is part of the hash, so identical operations against
+    /// different references are stored as different chunks.
+    pub fn compute_content_hash(
+        reference_hash: &SHA256Hash,
+        deltas: &[DeltaOperation],
+    ) -> Result<SHA256Hash, anyhow::Error> {
+        let mut data = reference_hash.as_bytes().to_vec();
+        data.extend(serde_json::to_vec(deltas)?);
+        Ok(SHA256Hash::compute(&data))
+    }
+
+    /// Point this delta at another chunk holding the same reference sequences
+    ///
+    /// The content hash covers the reference, so the chunk gets a new hash.
+    pub fn set_reference(&mut self, reference_hash: SHA256Hash) {
+        self.reference_hash = reference_hash;
+        if let ChunkClassification::Delta {
+            reference_hash: chunk_reference,
+            ..
+        } = &mut self.chunk_type
+        {
+            *chunk_reference = reference_hash;
+        }
+        self.content_hash = Self::compute_content_hash(&reference_hash, &self.deltas)
+            .expect("delta operations serialize to JSON");
+    }
+}
+
 /// Operations for delta reconstruction
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum DeltaOperation {
@@ -402,6 +653,18 @@ pub enum DeltaOperation {
     Delete { sequence_id: String },
 }
 
+impl DeltaOperation {
+    /// ID of the sequence this operation rebuilds
+    pub fn sequence_id(&self) -> &str {
+        match self {
+            Self::UseReference { sequence_id, .. }
+            | Self::Insert { sequence_id, .. }
+            | Self::Modify { sequence_id, .. }
+            | Self::Delete { sequence_id } => sequence_id,
+        }
+    }
+}
+
 /// Sequence edit operations
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum SeqEdit {
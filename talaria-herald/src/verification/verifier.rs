@@ -291,10 +291,14 @@ impl<'a> HeraldVerifier<'a> {
         }
 
         // Check for missing chunks (in manifest but not in storage)
-        for chunk_meta in &self.manifest.chunk_index {
-            if !self.storage.has_chunk(&chunk_meta.hash) {
-                issues.push(ConsistencyIssue::MissingChunk(chunk_meta.hash));
-            }
+        let indexed: Vec<SHA256Hash> = self
+            .manifest
+            .chunk_index
+            .iter()
+            .map(|chunk_meta| chunk_meta.hash)
+            .collect();
+        for hash in self.storage.missing_chunks(&indexed) {
+            issues.push(ConsistencyIssue::MissingChunk(hash));
         }
 
         // Check for duplicate references
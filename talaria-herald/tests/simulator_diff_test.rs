@@ -0,0 +1,114 @@
+/// Checks `database diff` against the changes recorded by the release simulator
+///
+/// Two simulated UniProt releases are added as databases and compared the way
+/// `talaria database diff` compares repository databases. Sequences only in
+/// the newer release must be exactly those of added and revised records, and
+/// sequences only in the older release those of removed and revised records.
+use serial_test::serial;
+use std::collections::HashSet;
+use talaria_bio::parse_fasta;
+use talaria_herald::database::DatabaseManager;
+use talaria_herald::operations::DatabaseDiffer;
+use talaria_herald::SHA256Hash;
+use talaria_test::fixtures::test_database_source;
+use talaria_test::simulator::{Release, ReleaseConfig, Simulator, SimulatorConfig};
+use tempfile::TempDir;
+
+/// Canonical hashes of the protein records of `release` with the given accessions
+fn protein_hashes<'a>(
+    release: &Release,
+    accessions: impl IntoIterator<Item = &'a String>,
+) -> HashSet<SHA256Hash> {
+    accessions
+        .into_iter()
+        .filter_map(|accession| release.record(accession))
+        .filter(|record| record.molecule.is_protein())
+        .map(|record| SHA256Hash::compute(record.sequence.as_bytes()))
+        .collect()
+}
+
+#[test]
+#[serial]
+fn test_diff_matches_release_changes() {
+    let temp_dir = TempDir::new().unwrap();
+    std::env::set_var("TALARIA_HOME", temp_dir.path());
+
+    let config = SimulatorConfig {
+        release: ReleaseConfig {
+            addition_rate: 0.2,
+            deletion_rate: 0.1,
+            header_edit_rate: 0.1,
+            revision_rate: 0.1,
+            ..Default::default()
+        },
+        ..SimulatorConfig::small()
+    };
+    let releases: Vec<Release> = Simulator::new(config).take(2).collect();
+    let (old, new) = (&releases[0], &releases[1]);
+
+    let mut manager = DatabaseManager::new(None).unwrap();
+    for release in &releases {
+        let files = release
+            .write_to(&temp_dir.path().join(&release.name))
+            .unwrap();
+        let sequences = parse_fasta(&files.uniprot_fasta).unwrap();
+        manager
+            .chunk_sequences_direct_with_progress_final(
+                sequences,
+                &test_database_source(&format!("sim_{}", release.number)),
+                None,
+                true,
+            )
+            .unwrap();
+    }
+
+    let manifest = |release: &Release| {
+        manager
+            .get_version_manifest("custom", &format!("test_sim_{}", release.number), "current")
+            .unwrap()
+    };
+    let comparison = DatabaseDiffer::compare_manifests(
+        &manifest(old),
+        &manifest(new),
+        Some(&manager.get_repository().storage),
+        None,
+    )
+    .unwrap();
+    let sequences = &comparison.sequence_analysis;
+
+    let all_old = protein_hashes(old, old.records.iter().map(|r| &r.accession));
+    let all_new = protein_hashes(new, new.records.iter().map(|r| &r.accession));
+    assert_eq!(sequences.total_sequences_a, all_old.len());
+    assert_eq!(sequences.total_sequences_b, all_new.len());
+
+    // A changed record may still carry a sequence stored under another accession
+    let changes = &new.changes;
+    let gained: HashSet<_> = protein_hashes(new, changes.added.iter().chain(&changes.revised))
+        .difference(&all_old)
+        .cloned()
+        .collect();
+    let lost: HashSet<_> = protein_hashes(old, changes.removed.iter().chain(&changes.revised))
+        .difference(&all_new)
+        .cloned()
+        .collect();
+    assert!(!gained.is_empty() && !lost.is_empty());
+
+    assert_eq!(sequences.unique_to_b, gained.len());
+    assert_eq!(sequences.unique_to_a, lost.len());
+    assert_eq!(sequences.shared_sequences, all_old.len() - lost.len());
+
+    // Header edits and reclassifications keep the sequence
+    let truncated = |hashes: &HashSet<SHA256Hash>| -> HashSet<String> {
+        hashes.iter().map(|hash| hash.truncated(16)).collect()
+    };
+    assert!(sequences
+        .sample_unique_b_ids
+        .iter()
+        .all(|id| truncated(&gained).contains(id)));
+    assert!(sequences
+        .sample_unique_a_ids
+        .iter()
+        .all(|id| truncated(&lost).contains(id)));
+
+    std::env::remove_var("TALARIA_HOME");
+}
@@ -0,0 +1,218 @@
+/// Checks incremental updates and their temporal history against the release simulator
+///
+/// A simulated series is loaded as one database: the first release is chunked
+/// as usual and each later one is applied with `apply_incremental_release`,
+/// the engine behind `talaria database update`. Releases are written with
+/// accession-only IDs, so a header edit, revision or reclassification is one
+/// modified record rather than a removal plus an addition.
+use serial_test::serial;
+use std::collections::HashSet;
+use std::path::Path;
+use talaria_bio::parse_fasta;
+use talaria_herald::database::{DatabaseManager, IncrementalUpdateReport};
+use talaria_herald::{SHA256Hash, SHA256HashExt};
+use talaria_test::fixtures::{create_test_fasta, test_database_source};
+use talaria_test::simulator::{Release, ReleaseConfig, Simulator, SimulatorConfig};
+use tempfile::TempDir;
+
+fn simulated_releases(count: usize) -> Vec<Release> {
+    let config = SimulatorConfig {
+        release: ReleaseConfig {
+            addition_rate: 0.2,
+            deletion_rate: 0.1,
+            header_edit_rate: 0.1,
+            revision_rate: 0.1,
+            reclassification_rate: 0.1,
+            ..Default::default()
+        },
+        ..SimulatorConfig::small()
+    };
+    Simulator::new(config).take(count).collect()
+}
+
+/// Protein records of `release` as FASTA with `>ACCESSION name OX=taxon` headers
+fn write_release(release: &Release, dir: &Path) -> std::path::PathBuf {
+    let sequences: Vec<_> = release.proteins().map(|r| r.to_test_sequence()).collect();
+    let path = dir.join(format!("{}.fasta", release.name));
+    std::fs::write(&path, create_test_fasta(&sequences)).unwrap();
+    path
+}
+
+/// Load the first release, then apply every later one as an update
+fn load_series(
+    manager: &mut DatabaseManager,
+    releases: &[Release],
+    dir: &Path,
+) -> Vec<IncrementalUpdateReport> {
+    let source = test_database_source("sim_update");
+    let sequences = parse_fasta(write_release(&releases[0], dir)).unwrap();
+    manager
+        .chunk_sequences_direct_with_progress_final(sequences, &source, None, true)
+        .unwrap();
+
+    releases[1..]
+        .iter()
+        .map(|release| {
+            // Versions are second-resolution timestamps
+            std::thread::sleep(std::time::Duration::from_secs(1));
+            manager
+                .apply_incremental_release(&write_release(release, dir), &source, &|_: &str| {})
+                .unwrap()
+        })
+        .collect()
+}
+
+/// Protein accessions of a release, split by how they changed
+struct ExpectedChanges {
+    added: HashSet<String>,
+    removed: HashSet<String>,
+    modified: HashSet<String>,
+    unchanged: HashSet<String>,
+}
+
+impl ExpectedChanges {
+    fn between(old: &Release, new: &Release) -> Self {
+        let is_protein = |release: &Release, accession: &String| {
+            release
+                .record(accession)
+                .is_some_and(|r| r.molecule.is_protein())
+        };
+        let changes = &new.changes;
+        let added: HashSet<String> = changes
+            .added
+            .iter()
+            .filter(|a| is_protein(new, a))
+            .cloned()
+            .collect();
+        let removed = changes
+            .removed
+            .iter()
+            .filter(|a| is_protein(old, a))
+            .cloned()
+            .collect();
+        let modified: HashSet<String> = changes
+            .header_edited
+            .iter()
+            .chain(&changes.revised)
+            .chain(
+                changes
+                    .reclassified
+                    .iter()
+                    .map(|(accession, _, _)| accession),
+            )
+            .filter(|a| is_protein(new, a))
+            .cloned()
+            .collect();
+        let unchanged = new
+            .proteins()
+            .map(|r| r.accession.clone())
+            .filter(|a| !added.contains(a) && !modified.contains(a))
+            .collect();
+        Self {
+            added,
+            removed,
+            modified,
+            unchanged,
+        }
+    }
+
+    fn changed(&self) -> impl Iterator<Item = &String> {
+        self.added.iter().chain(&self.removed).chain(&self.modified)
+    }
+}
+
+#[test]
+#[serial]
+fn test_incremental_update_matches_release_changes() {
+    let temp_dir = TempDir::new().unwrap();
+    std::env::set_var("TALARIA_HOME", temp_dir.path());
+
+    let releases = simulated_releases(3);
+    let mut manager = DatabaseManager::new(None).unwrap();
+    let reports = load_series(&mut manager, &releases, temp_dir.path());
+
+    let mut previous_version = None;
+    for (pair, report) in releases.windows(2).zip(&reports) {
+        let expected = ExpectedChanges::between(&pair[0], &pair[1]);
+        assert!(!expected.added.is_empty() && !expected.modified.is_empty());
+
+        assert_eq!(report.sequences_added, expected.added.len());
+        assert_eq!(report.sequences_removed, expected.removed.len());
+        assert_eq!(report.sequences_modified, expected.modified.len());
+        assert_eq!(report.sequences_unchanged, expected.unchanged.len());
+        if let Some(previous) = previous_version {
+            assert_eq!(report.previous_version, previous);
+        }
+        previous_version = Some(report.version.clone());
+    }
+
+    // Each update is a version of its own; the first is still there
+    let versions = manager
+        .list_database_versions("custom", "test_sim_update")
+        .unwrap();
+    assert_eq!(versions.len(), releases.len());
+
+    std::env::remove_var("TALARIA_HOME");
+}
+
+#[test]
+#[serial]
+fn test_temporal_history_records_release_changes() {
+    let temp_dir = TempDir::new().unwrap();
+    std::env::set_var("TALARIA_HOME", temp_dir.path());
+
+    let releases = simulated_releases(3);
+    let mut manager = DatabaseManager::new(None).unwrap();
+    let reports = load_series(&mut manager, &releases, temp_dir.path());
+
+    for (pair, report) in releases.windows(2).zip(&reports) {
+        let expected = ExpectedChanges::between(&pair[0], &pair[1]);
+        let recorded = |accession: &str| {
+            manager
+                .get_temporal_history(accession)
+                .unwrap()
+                .into_iter()
+                .find(|record| record.version == report.version)
+        };
+
+        // Only changed records enter the history of a version
+        for accession in expected.changed() {
+            assert!(
+                recorded(accession).is_some(),
+                "{} changed in {} but has no history",
+                accession,
+                pair[1].name
+            );
+        }
+        for accession in &expected.unchanged {
+            assert!(
+                recorded(accession).is_none(),
+                "{} unchanged in {} but has history",
+                accession,
+                pair[1].name
+            );
+        }
+
+        for accession in &expected.removed {
+            assert!(recorded(accession).unwrap().chunk_hash.is_zero());
+        }
+        let version_chunks: HashSet<SHA256Hash> = manager
+            .get_manifest(&format!("custom/test_sim_update@{}", report.version))
+            .unwrap()
+            .chunk_index
+            .iter()
+            .map(|c| c.hash)
+            .collect();
+        for (accession, _, to) in &pair[1].changes.reclassified {
+            if !expected.modified.contains(accession) {
+                continue;
+            }
+            // Reclassified records keep their sequence, and with it their chunk
+            let history = recorded(accession).unwrap();
+            assert_eq!(history.taxon_id, Some(to.0));
+            assert!(version_chunks.contains(&history.chunk_hash));
+        }
+    }
+
+    std::env::remove_var("TALARIA_HOME");
+}
@@ -0,0 +1,33 @@
+"""Read-only access to Talaria HERALD repositories.
+
+Example::
+
+    import talaria
+
+    repo = talaria.Repository()
+    for seq in repo.sequences("uniprot/swissprot@stable", taxa=[9606]):
+        print(seq.id, len(seq))
+
+    tax = talaria.Taxonomy()
+    print(tax.lca(9606, 10090))
+"""
+
+from ._talaria import (
+    DatabaseReference,
+    Repository,
+    Sequence,
+    SequenceIterator,
+    TalariaError,
+    Taxonomy,
+    __version__,
+)
+
+__all__ = [
+    "DatabaseReference",
+    "Repository",
+    "Sequence",
+    "SequenceIterator",
+    "TalariaError",
+    "Taxonomy",
+    "__version__",
+]
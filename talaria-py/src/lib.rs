@@ -0,0 +1,58 @@
+//! Python bindings for reading HERALD repositories
+//!
+//! Exposes read-only handles to the database manager, reference resolution,
+//! streamed FASTA assembly, sequence lookup, temporal queries and taxonomy
+//! lineage as the `talaria` Python package. Sequences are yielded lazily, a
+//! few chunks at a time, and the GIL is released while Rust code reads the
+//! repository.
+//!
+//! Build a wheel with `maturin build --release` from this directory.
+
+// `#[pymethods]` expands `PyResult` returns into a conversion clippy flags
+#![allow(clippy::useless_conversion)]
+
+mod repository;
+mod sequence;
+mod taxonomy;
+
+pub use repository::{Repository, SequenceIterator};
+pub use sequence::{DatabaseReference, Sequence};
+pub use taxonomy::Taxonomy;
+
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+use serde::Serialize;
+
+create_exception!(
+    talaria,
+    TalariaError,
+    PyException,
+    "Error raised by the Talaria repository."
+);
+
+/// Surface a Rust error as `talaria.TalariaError`
+pub(crate) fn to_py_err(e: anyhow::Error) -> PyErr {
+    TalariaError::new_err(format!("{:#}", e))
+}
+
+/// Convert a serializable value into plain Python dicts, lists and scalars
+pub(crate) fn to_py<T: Serialize>(py: Python<'_>, value: &T) -> PyResult<PyObject> {
+    let json = serde_json::to_string(value).map_err(|e| to_py_err(e.into()))?;
+    Ok(py
+        .import_bound("json")?
+        .call_method1("loads", (json,))?
+        .unbind())
+}
+
+#[pymodule]
+fn _talaria(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("__version__", env!("CARGO_PKG_VERSION"))?;
+    m.add("TalariaError", m.py().get_type_bound::<TalariaError>())?;
+    m.add_class::<Repository>()?;
+    m.add_class::<SequenceIterator>()?;
+    m.add_class::<Sequence>()?;
+    m.add_class::<DatabaseReference>()?;
+    m.add_class::<Taxonomy>()?;
+    Ok(())
+}
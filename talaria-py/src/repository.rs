@@ -0,0 +1,357 @@
+use crate::sequence::Sequence;
+use crate::{to_py, to_py_err};
+use chrono::{DateTime, Utc};
+use pyo3::prelude::*;
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::Arc;
+use talaria_herald::database::{DatabaseManager, LockedDatabase};
+use talaria_herald::operations::FastaAssembler;
+use talaria_herald::{ManifestMetadata, TaxonId, TemporalManifest};
+
+/// Chunks assembled per step while iterating a database
+const ITER_BATCH_CHUNKS: usize = 16;
+
+/// Read-only handle to a HERALD repository
+#[pyclass(module = "talaria", frozen)]
+pub struct Repository {
+    manager: Arc<DatabaseManager>,
+}
+
+/// A point in time given as a `datetime` or an RFC 3339 string
+#[derive(FromPyObject)]
+enum TimeArg {
+    DateTime(DateTime<Utc>),
+    Text(String),
+}
+
+impl TimeArg {
+    fn resolve(self) -> PyResult<DateTime<Utc>> {
+        match self {
+            TimeArg::DateTime(time) => Ok(time),
+            TimeArg::Text(text) => DateTime::parse_from_rfc3339(&text)
+                .map(|time| time.with_timezone(&Utc))
+                .map_err(|e| {
+                    pyo3::exceptions::PyValueError::new_err(format!(
+                        "Invalid time '{}': {}",
+                        text, e
+                    ))
+                }),
+        }
+    }
+}
+
+#[pymethods]
+impl Repository {
+    /// Open the repository at `path` (default: ${TALARIA_HOME}/databases)
+    #[new]
+    #[pyo3(signature = (path=None))]
+    fn new(py: Python<'_>, path: Option<PathBuf>) -> PyResult<Self> {
+        let manager = py
+            .allow_threads(|| DatabaseManager::new(path.map(|p| p.to_string_lossy().to_string())))
+            .map_err(to_py_err)?;
+        Ok(Self {
+            manager: Arc::new(manager),
+        })
+    }
+
+    /// Databases in the repository as a list of dicts
+    fn databases(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let databases = py
+            .allow_threads(|| self.manager.list_databases())
+            .map_err(to_py_err)?;
+        to_py(py, &databases)
+    }
+
+    /// Versions of `source/dataset`, newest first
+    fn versions(&self, py: Python<'_>, source: &str, dataset: &str) -> PyResult<PyObject> {
+        let versions = py
+            .allow_threads(|| self.manager.list_database_versions(source, dataset))
+            .map_err(to_py_err)?;
+        to_py(py, &versions)
+    }
+
+    /// Resolve a reference such as `uniprot/swissprot@stable` to a concrete version
+    fn resolve(&self, py: Python<'_>, reference: &str) -> PyResult<PyObject> {
+        let resolved = py
+            .allow_threads(|| LockedDatabase::resolve(&self.manager, reference))
+            .map_err(to_py_err)?;
+        to_py(py, &resolved)
+    }
+
+    /// Sequence stored under `accession`, or None
+    fn sequence(&self, py: Python<'_>, accession: &str) -> PyResult<Option<Sequence>> {
+        py.allow_threads(|| {
+            let storage = &self.manager.get_storage().sequence_storage;
+            match storage.find_by_accession(accession)? {
+                Some(hash) => {
+                    // Identical sequences share one entry; use the header of the one asked for
+                    let fasta = storage.get_sequence_as_fasta_for_accession(&hash, accession)?;
+                    Ok(parse_fasta(&fasta)?.pop_front())
+                }
+                None => Ok(None),
+            }
+        })
+        .map_err(to_py_err)
+    }
+
+    /// Iterate the canonical sequences assigned to `taxon_id`
+    fn sequences_by_taxon(&self, py: Python<'_>, taxon_id: u32) -> PyResult<SequenceIterator> {
+        let hashes = py
+            .allow_threads(|| {
+                self.manager
+                    .get_storage()
+                    .sequence_storage
+                    .find_by_taxon(TaxonId(taxon_id))
+            })
+            .map_err(to_py_err)?;
+        Ok(SequenceIterator::new(
+            self.manager.clone(),
+            Source::Canonical(hashes.into()),
+        ))
+    }
+
+    /// Iterate the sequences of a database, optionally limited to taxon IDs
+    #[pyo3(signature = (reference, taxa=None))]
+    fn sequences(
+        &self,
+        py: Python<'_>,
+        reference: &str,
+        taxa: Option<Vec<u32>>,
+    ) -> PyResult<SequenceIterator> {
+        let mut manifest = py
+            .allow_threads(|| {
+                let resolved = LockedDatabase::resolve(&self.manager, reference)?;
+                self.manager.get_manifest(&format!(
+                    "{}/{}@{}",
+                    resolved.source, resolved.dataset, resolved.version
+                ))
+            })
+            .map_err(to_py_err)?;
+
+        let taxa: Option<Vec<TaxonId>> = taxa.map(|t| t.into_iter().map(TaxonId).collect());
+        let chunks: Vec<ManifestMetadata> = std::mem::take(&mut manifest.chunk_index)
+            .into_iter()
+            .filter(|chunk| match &taxa {
+                Some(taxa) => chunk.taxon_ids.iter().any(|tid| taxa.contains(tid)),
+                None => true,
+            })
+            .collect();
+        let batches = chunks
+            .chunks(ITER_BATCH_CHUNKS)
+            .map(|batch| batch.to_vec())
+            .collect();
+
+        Ok(SequenceIterator::new(
+            self.manager.clone(),
+            Source::Chunks {
+                manifest: Box::new(manifest),
+                batches,
+                taxa,
+            },
+        ))
+    }
+
+    /// Iterate the sequences valid at a bi-temporal coordinate
+    #[pyo3(signature = (sequence_time, taxonomy_time=None, taxa=None))]
+    fn sequences_at(
+        &self,
+        py: Python<'_>,
+        sequence_time: TimeArg,
+        taxonomy_time: Option<TimeArg>,
+        taxa: Option<Vec<u32>>,
+    ) -> PyResult<SequenceIterator> {
+        let sequence_time = sequence_time.resolve()?;
+        let taxonomy_time = match taxonomy_time {
+            Some(time) => time.resolve()?,
+            None => sequence_time,
+        };
+        let hashes = py
+            .allow_threads(|| {
+                self.manager
+                    .chunks_at_time(sequence_time, taxonomy_time, taxa)
+            })
+            .map_err(to_py_err)?;
+        let batches = hashes
+            .chunks(ITER_BATCH_CHUNKS)
+            .map(|batch| batch.to_vec())
+            .collect();
+
+        Ok(SequenceIterator::new(
+            self.manager.clone(),
+            Source::Snapshot(batches),
+        ))
+    }
+
+    /// Recent versions recorded in the temporal index, newest first
+    #[pyo3(signature = (limit=20))]
+    fn history(&self, py: Python<'_>, limit: usize) -> PyResult<PyObject> {
+        let history = py
+            .allow_threads(|| {
+                self.manager
+                    .get_repository()
+                    .temporal
+                    .get_version_history(limit)
+            })
+            .map_err(to_py_err)?;
+        to_py(py, &history)
+    }
+
+    /// Sequence and taxonomy versions in effect at `time`
+    fn state_at(&self, py: Python<'_>, time: TimeArg) -> PyResult<PyObject> {
+        let time = time.resolve()?;
+        let value = py
+            .allow_threads(|| {
+                let state = self.manager.get_repository().temporal.get_state_at(time)?;
+                Ok::<_, anyhow::Error>(serde_json::json!({
+                    "timestamp": state.timestamp,
+                    "sequence_version": state.sequence_version,
+                    "taxonomy_version": state.taxonomy_version,
+                    "has_manifest": state.manifest.is_some(),
+                }))
+            })
+            .map_err(to_py_err)?;
+        to_py(py, &value)
+    }
+
+    /// Sequence and taxonomy updates between `start` and `end`
+    fn timeline(&self, py: Python<'_>, start: TimeArg, end: TimeArg) -> PyResult<PyObject> {
+        let (start, end) = (start.resolve()?, end.resolve()?);
+        let events: Vec<_> = py.allow_threads(|| {
+            self.manager
+                .get_repository()
+                .temporal
+                .get_timeline(start, end)
+                .events
+                .iter()
+                .map(|event| {
+                    serde_json::json!({
+                        "timestamp": event.timestamp,
+                        "event_type": event.event_type,
+                        "description": event.description,
+                        "details": event.details,
+                    })
+                })
+                .collect()
+        });
+        to_py(py, &events)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Repository({:?})", self.manager.get_storage().base_path)
+    }
+}
+
+/// Where a `SequenceIterator` takes its next sequences from
+enum Source {
+    /// Manifest chunks, assembled batch by batch
+    Chunks {
+        manifest: Box<TemporalManifest>,
+        batches: VecDeque<Vec<ManifestMetadata>>,
+        taxa: Option<Vec<TaxonId>>,
+    },
+    /// Canonical sequences loaded one at a time
+    Canonical(VecDeque<talaria_herald::SHA256Hash>),
+    /// Chunks of a bi-temporal snapshot, loaded batch by batch
+    Snapshot(VecDeque<Vec<talaria_herald::SHA256Hash>>),
+}
+
+/// Lazy iterator over `Sequence` records
+#[pyclass(module = "talaria")]
+pub struct SequenceIterator {
+    manager: Arc<DatabaseManager>,
+    source: Source,
+    buffer: VecDeque<Sequence>,
+}
+
+impl SequenceIterator {
+    fn new(manager: Arc<DatabaseManager>, source: Source) -> Self {
+        Self {
+            manager,
+            source,
+            buffer: VecDeque::new(),
+        }
+    }
+
+    /// Load the next non-empty batch into the buffer
+    fn refill(&mut self) -> anyhow::Result<()> {
+        while self.buffer.is_empty() {
+            match &mut self.source {
+                Source::Chunks {
+                    manifest,
+                    batches,
+                    taxa,
+                } => {
+                    let Some(batch) = batches.pop_front() else {
+                        return Ok(());
+                    };
+                    let assembler = FastaAssembler::new(self.manager.get_storage());
+                    let sequences = match taxa {
+                        Some(taxa) => {
+                            manifest.chunk_index = batch;
+                            assembler.assemble_taxonomic_subset(taxa, manifest)?
+                        }
+                        None => {
+                            let hashes: Vec<_> = batch.iter().map(|chunk| chunk.hash).collect();
+                            assembler.assemble_from_chunks(&hashes)?
+                        }
+                    };
+                    self.buffer
+                        .extend(sequences.into_iter().map(Sequence::from));
+                }
+                Source::Canonical(hashes) => {
+                    let Some(hash) = hashes.pop_front() else {
+                        return Ok(());
+                    };
+                    let fasta = self
+                        .manager
+                        .get_storage()
+                        .sequence_storage
+                        .get_sequence_as_fasta(&hash, None)?;
+                    self.buffer.extend(parse_fasta(&fasta)?);
+                }
+                Source::Snapshot(batches) => {
+                    let Some(batch) = batches.pop_front() else {
+                        return Ok(());
+                    };
+                    let sequences = self
+                        .manager
+                        .get_repository()
+                        .load_sequences_from_chunks(&batch)?;
+                    self.buffer
+                        .extend(sequences.into_iter().map(Sequence::from));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[pymethods]
+impl SequenceIterator {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(mut slf: PyRefMut<'_, Self>, py: Python<'_>) -> PyResult<Option<Sequence>> {
+        let iter = &mut *slf;
+        py.allow_threads(|| iter.refill()).map_err(to_py_err)?;
+        Ok(iter.buffer.pop_front())
+    }
+}
+
+/// Parse FASTA from sequence storage, whose headers may lack the `>`
+fn parse_fasta(text: &str) -> anyhow::Result<VecDeque<Sequence>> {
+    let mut text = if text.starts_with('>') {
+        text.to_string()
+    } else {
+        format!(">{}", text)
+    };
+    if !text.ends_with('\n') {
+        text.push('\n');
+    }
+    Ok(talaria_bio::parse_fasta_from_bytes(text.as_bytes())?
+        .into_iter()
+        .map(Sequence::from)
+        .collect())
+}
@@ -0,0 +1,111 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+/// A FASTA record
+#[pyclass(module = "talaria", frozen, get_all)]
+#[derive(Clone)]
+pub struct Sequence {
+    pub id: String,
+    pub description: Option<String>,
+    pub taxon_id: Option<u32>,
+    pub sequence: String,
+}
+
+impl From<talaria_bio::Sequence> for Sequence {
+    fn from(seq: talaria_bio::Sequence) -> Self {
+        Self {
+            sequence: String::from_utf8_lossy(&seq.sequence).into_owned(),
+            id: seq.id,
+            description: seq.description,
+            taxon_id: seq.taxon_id,
+        }
+    }
+}
+
+#[pymethods]
+impl Sequence {
+    #[new]
+    #[pyo3(signature = (id, sequence, description=None, taxon_id=None))]
+    fn new(
+        id: String,
+        sequence: String,
+        description: Option<String>,
+        taxon_id: Option<u32>,
+    ) -> Self {
+        Self {
+            id,
+            description,
+            taxon_id,
+            sequence,
+        }
+    }
+
+    /// FASTA header line without the leading `>`
+    #[getter]
+    fn header(&self) -> String {
+        match &self.description {
+            Some(description) => format!("{} {}", self.id, description),
+            None => self.id.clone(),
+        }
+    }
+
+    /// The record as FASTA text
+    fn to_fasta(&self) -> String {
+        format!(">{}\n{}\n", self.header(), self.sequence)
+    }
+
+    fn __len__(&self) -> usize {
+        self.sequence.len()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Sequence(id={:?}, taxon_id={:?}, length={})",
+            self.id,
+            self.taxon_id,
+            self.sequence.len()
+        )
+    }
+}
+
+/// A parsed `source/dataset[@version][:profile]` reference
+#[pyclass(module = "talaria", frozen, get_all)]
+#[derive(Clone)]
+pub struct DatabaseReference {
+    pub source: String,
+    pub dataset: String,
+    pub version: Option<String>,
+    pub profile: Option<String>,
+}
+
+#[pymethods]
+impl DatabaseReference {
+    #[new]
+    fn new(reference: &str) -> PyResult<Self> {
+        let parsed = talaria_utils::database::database_ref::parse_database_reference(reference)
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(Self {
+            source: parsed.source,
+            dataset: parsed.dataset,
+            version: parsed.version,
+            profile: parsed.profile,
+        })
+    }
+
+    fn __str__(&self) -> String {
+        let mut reference = format!("{}/{}", self.source, self.dataset);
+        if let Some(version) = &self.version {
+            reference.push('@');
+            reference.push_str(version);
+        }
+        if let Some(profile) = &self.profile {
+            reference.push(':');
+            reference.push_str(profile);
+        }
+        reference
+    }
+
+    fn __repr__(&self) -> String {
+        format!("DatabaseReference({:?})", self.__str__())
+    }
+}
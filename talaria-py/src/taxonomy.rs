@@ -0,0 +1,110 @@
+use crate::to_py_err;
+use pyo3::prelude::*;
+use std::path::PathBuf;
+use talaria_bio::taxonomy::core::ncbi;
+use talaria_bio::TaxonomyDB;
+
+/// NCBI taxonomy loaded from `names.dmp` and `nodes.dmp`
+#[pyclass(module = "talaria", frozen)]
+pub struct Taxonomy {
+    db: TaxonomyDB,
+}
+
+#[pymethods]
+impl Taxonomy {
+    /// Load the taxonomy dump in `path` (default: the current downloaded taxonomy)
+    #[new]
+    #[pyo3(signature = (path=None))]
+    fn new(py: Python<'_>, path: Option<PathBuf>) -> PyResult<Self> {
+        let dir = path.unwrap_or_else(talaria_utils::taxonomy::get_taxonomy_tree_path);
+        let names = dir.join("names.dmp");
+        let nodes = dir.join("nodes.dmp");
+        if !names.exists() || !nodes.exists() {
+            return Err(to_py_err(anyhow::anyhow!(
+                "No names.dmp/nodes.dmp in {}. Run 'talaria database download ncbi/taxonomy' first.",
+                dir.display()
+            )));
+        }
+
+        let db = py
+            .allow_threads(|| ncbi::build_taxonomy_db(&names, &nodes))
+            .map_err(|e| to_py_err(e.into()))?;
+        Ok(Self { db })
+    }
+
+    /// Scientific name of a taxon
+    fn name(&self, taxon_id: u32) -> Option<String> {
+        self.db
+            .get_taxon(taxon_id)
+            .map(|t| t.scientific_name.clone())
+    }
+
+    /// Rank of a taxon, e.g. "species"
+    fn rank(&self, taxon_id: u32) -> Option<String> {
+        self.db.get_taxon(taxon_id).map(|t| t.rank.clone())
+    }
+
+    fn parent(&self, taxon_id: u32) -> Option<u32> {
+        self.db.get_taxon(taxon_id).and_then(|t| t.parent_id)
+    }
+
+    /// Taxon IDs from the root down to `taxon_id`
+    fn lineage(&self, taxon_id: u32) -> PyResult<Vec<u32>> {
+        self.known(taxon_id)?;
+        Ok(self.db.get_lineage(taxon_id))
+    }
+
+    /// `(taxon_id, rank, name)` from the root down to `taxon_id`
+    fn lineage_names(&self, taxon_id: u32) -> PyResult<Vec<(u32, String, String)>> {
+        self.known(taxon_id)?;
+        Ok(self
+            .db
+            .get_lineage(taxon_id)
+            .into_iter()
+            .filter_map(|id| {
+                self.db
+                    .get_taxon(id)
+                    .map(|t| (id, t.rank.clone(), t.scientific_name.clone()))
+            })
+            .collect())
+    }
+
+    /// Lowest common ancestor of one or more taxa
+    #[pyo3(signature = (*taxon_ids))]
+    fn lca(&self, taxon_ids: Vec<u32>) -> PyResult<Option<u32>> {
+        let Some((&first, rest)) = taxon_ids.split_first() else {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "lca() needs at least one taxon ID",
+            ));
+        };
+        self.known(first)?;
+        let mut ancestor = Some(first);
+        for &taxon_id in rest {
+            self.known(taxon_id)?;
+            ancestor = ancestor.and_then(|a| self.db.common_ancestor(a, taxon_id));
+        }
+        Ok(ancestor)
+    }
+
+    /// Number of edges between two taxa through their common ancestor
+    fn distance(&self, taxon_a: u32, taxon_b: u32) -> Option<usize> {
+        self.db.distance(taxon_a, taxon_b)
+    }
+
+    fn __len__(&self) -> usize {
+        self.db.taxa_count()
+    }
+
+    fn __contains__(&self, taxon_id: u32) -> bool {
+        self.db.get_taxon(taxon_id).is_some()
+    }
+}
+
+impl Taxonomy {
+    fn known(&self, taxon_id: u32) -> PyResult<()> {
+        if self.db.get_taxon(taxon_id).is_none() {
+            return Err(pyo3::exceptions::PyKeyError::new_err(taxon_id));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,6 @@
+import os
+import tempfile
+
+# Paths are resolved once per process, so isolate TALARIA_HOME before any
+# repository is opened.
+os.environ["TALARIA_HOME"] = tempfile.mkdtemp(prefix="talaria-py-test-")
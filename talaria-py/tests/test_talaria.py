@@ -0,0 +1,181 @@
+import os
+import shutil
+import subprocess
+import time
+from datetime import datetime, timezone
+from pathlib import Path
+
+import pytest
+
+import talaria
+
+NAMES = [
+    (1, "root"),
+    (2759, "Eukaryota"),
+    (40674, "Mammalia"),
+    (9606, "Homo sapiens"),
+    (10090, "Mus musculus"),
+]
+NODES = [
+    (1, 1, "no rank"),
+    (2759, 1, "superkingdom"),
+    (40674, 2759, "class"),
+    (9606, 40674, "species"),
+    (10090, 40674, "species"),
+]
+
+
+@pytest.fixture
+def taxonomy(tmp_path):
+    with open(tmp_path / "names.dmp", "w") as f:
+        for taxon_id, name in NAMES:
+            f.write(f"{taxon_id}\t|\t{name}\t|\t\t|\tscientific name\t|\n")
+    with open(tmp_path / "nodes.dmp", "w") as f:
+        for taxon_id, parent, rank in NODES:
+            f.write(f"{taxon_id}\t|\t{parent}\t|\t{rank}\t|\t\t|\n")
+    return talaria.Taxonomy(tmp_path)
+
+
+# Two UniProt-style releases; the second adds a mouse sequence
+FIRST_RELEASE = [
+    ("sp|P69905|HBA_HUMAN", "Hemoglobin alpha OX=9606", "MVLSPADKTNVKAAWGKVGA"),
+    ("sp|P68871|HBB_HUMAN", "Hemoglobin beta OX=9606", "MVHLTPEEKSAVTALWGKVN"),
+]
+SECOND_RELEASE = [
+    ("sp|P01942|HBA_MOUSE", "Hemoglobin alpha OX=10090", "MVLSGEDKSNIKAAWGKIGG"),
+]
+
+
+def talaria_binary():
+    """The `talaria` CLI, from $TALARIA_BIN, the PATH or the workspace build"""
+    candidates = [os.environ.get("TALARIA_BIN"), shutil.which("talaria")]
+    workspace = Path(__file__).resolve().parents[2] / "target"
+    candidates += [workspace / profile / "talaria" for profile in ("release", "debug")]
+    for candidate in candidates:
+        if candidate and Path(candidate).is_file():
+            return str(candidate)
+    return None
+
+
+def write_fasta(path, records):
+    with open(path, "w") as f:
+        for seq_id, description, sequence in records:
+            f.write(f">{seq_id} {description}\n{sequence}\n")
+    return path
+
+
+def residues(records):
+    return {sequence for _, _, sequence in records}
+
+
+@pytest.fixture(scope="session")
+def populated(tmp_path_factory):
+    """custom/py_test in the default repository, in two versions
+
+    Returns the time between the versions. The repository is written by the
+    CLI, since the bindings are read-only.
+    """
+    binary = talaria_binary()
+    if binary is None:
+        pytest.skip("talaria CLI not built; build it or set TALARIA_BIN")
+    inputs = tmp_path_factory.mktemp("inputs")
+
+    def add(records, name, *flags):
+        fasta = write_fasta(inputs / f"{name}.fasta", records)
+        command = [binary, "database", "add", "-i", str(fasta), "--copy"]
+        command += ["--source", "custom", "--dataset", "py_test", *flags]
+        subprocess.run(command, check=True, capture_output=True)
+
+    add(FIRST_RELEASE, "first")
+    # Versions are second-resolution timestamps
+    time.sleep(1.1)
+    between = datetime.now(timezone.utc)
+    time.sleep(1.1)
+    add(SECOND_RELEASE, "second", "--append")
+    return between
+
+
+    ref = talaria.DatabaseReference("uniprot/swissprot@2024_04:blast-30")
+    assert (ref.source, ref.dataset) == ("uniprot", "swissprot")
+    assert ref.version == "2024_04"
+    assert ref.profile == "blast-30"
+    assert str(ref) == "uniprot/swissprot@2024_04:blast-30"
+
+    with pytest.raises(ValueError):
+        talaria.DatabaseReference("swissprot")
+
+
+def test_sequence():
+    seq = talaria.Sequence("P12345", "MKV", description="Test protein", taxon_id=9606)
+    assert len(seq) == 3
+    assert seq.header == "P12345 Test protein"
+    assert seq.to_fasta() == ">P12345 Test protein\nMKV\n"
+
+
+def test_empty_repository(tmp_path):
+    repo = talaria.Repository(tmp_path / "databases")
+    assert repo.databases() == []
+    assert repo.sequence("P12345") is None
+    assert list(repo.sequences_by_taxon(9606)) == []
+
+    with pytest.raises(talaria.TalariaError):
+        repo.resolve("uniprot/swissprot")
+
+
+def test_sequence_lookup(populated):
+    repo = talaria.Repository()
+    seq = repo.sequence("P69905")
+    assert seq is not None
+    assert seq.header == "sp|P69905|HBA_HUMAN Hemoglobin alpha OX=9606"
+    assert seq.sequence == FIRST_RELEASE[0][2]
+    assert seq.taxon_id == 9606
+    assert repo.sequence("Q99999") is None
+
+
+def test_sequences_of_a_database(populated):
+    repo = talaria.Repository()
+    assert [db["name"] for db in repo.databases()] == ["custom/py_test"]
+    assert len(repo.versions("custom", "py_test")) == 2
+
+    everything = {seq.sequence for seq in repo.sequences("custom/py_test")}
+    assert everything == residues(FIRST_RELEASE + SECOND_RELEASE)
+
+    mouse = list(repo.sequences("custom/py_test", taxa=[10090]))
+    assert [seq.sequence for seq in mouse] == [SECOND_RELEASE[0][2]]
+
+
+def test_sequences_by_taxon(populated):
+    repo = talaria.Repository()
+    human = {seq.sequence for seq in repo.sequences_by_taxon(9606)}
+    assert human == residues(FIRST_RELEASE)
+    mouse = [seq.sequence for seq in repo.sequences_by_taxon(10090)]
+    assert mouse == [SECOND_RELEASE[0][2]]
+    assert list(repo.sequences_by_taxon(562)) == []
+
+
+def test_sequences_at(populated):
+    repo = talaria.Repository()
+    between = populated
+
+    before = {seq.sequence for seq in repo.sequences_at(between)}
+    assert before == residues(FIRST_RELEASE)
+    assert list(repo.sequences_at(between.isoformat(), taxa=[10090])) == []
+
+    now = {seq.sequence for seq in repo.sequences_at(datetime.now(timezone.utc))}
+    assert now == residues(FIRST_RELEASE + SECOND_RELEASE)
+    assert list(repo.sequences_at("2000-01-01T00:00:00Z")) == []
+
+
+def test_taxonomy_lineage_and_lca(taxonomy):
+    assert len(taxonomy) == len(NAMES)
+    assert 9606 in taxonomy
+    assert taxonomy.name(9606) == "Homo sapiens"
+    assert taxonomy.rank(40674) == "class"
+    assert taxonomy.lineage(9606) == [1, 2759, 40674, 9606]
+    assert taxonomy.lineage_names(40674)[-1] == (40674, "class", "Mammalia")
+    assert taxonomy.lca(9606, 10090) == 40674
+    assert taxonomy.lca(9606) == 9606
+    assert taxonomy.distance(9606, 10090) == 2
+
+    with pytest.raises(KeyError):
+        taxonomy.lineage(12345)
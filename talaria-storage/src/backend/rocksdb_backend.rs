@@ -5,6 +5,7 @@
 use anyhow::{anyhow, Context, Result};
 use rocksdb::{
     backup::{BackupEngine, BackupEngineOptions, RestoreOptions},
+    statistics::Ticker,
     BlockBasedOptions, BoundColumnFamily, Cache, ColumnFamilyDescriptor, DBWithThreadMode,
     IteratorMode, MultiThreaded, Options, WriteBatch, WriteOptions, DB,
 };
@@ -12,6 +13,7 @@ use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
+use super::RocksDBMetrics;
 use crate::types::{CanonicalSequence, SequenceRepresentations, SequenceStorageBackend};
 use talaria_core::types::{SHA256Hash, TaxonId};
 use talaria_core::StorageStats;
@@ -27,6 +29,20 @@ pub mod cf_names {
     pub const TEMPORAL: &str = "temporal";
 }
 
+/// Every column family opened by the backend
+const ALL_COLUMN_FAMILIES: [&str; 7] = [
+    cf_names::DEFAULT,
+    cf_names::SEQUENCES,
+    cf_names::REPRESENTATIONS,
+    cf_names::MANIFESTS,
+    cf_names::INDICES,
+    cf_names::MERKLE,
+    cf_names::TEMPORAL,
+];
+
+/// LSM levels RocksDB uses by default; `num_levels` is never changed
+const NUM_LEVELS: usize = 7;
+
 /// RocksDB configuration options
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RocksDBConfig {
@@ -93,6 +109,9 @@ pub struct RocksDBBackend {
 
     /// Write options for batch operations
     write_opts: WriteOptions,
+
+    /// Database options, kept when statistics are enabled to read tickers
+    statistics: Option<Options>,
 }
 
 impl RocksDBBackend {
@@ -214,6 +233,75 @@ impl RocksDBBackend {
         Ok(())
     }
 
+    /// Store multiple index entries in one write batch
+    pub fn put_indices_batch(&self, entries: &[(String, Vec<u8>)]) -> Result<()> {
+        let cf = self.cf_handle(cf_names::INDICES)?;
+        let mut batch = WriteBatch::default();
+
+        for (key, value) in entries {
+            batch.put_cf(&cf, key.as_bytes(), value);
+        }
+
+        self.db.write_opt(batch, &self.write_opts)?;
+        Ok(())
+    }
+
+    /// Get multiple index entries in one lookup
+    pub fn get_indices_batch(&self, keys: &[String]) -> Result<Vec<Option<Vec<u8>>>> {
+        let cf = self.cf_handle(cf_names::INDICES)?;
+        self.db
+            .multi_get_cf(keys.iter().map(|k| (&cf, k.as_bytes())))
+            .into_iter()
+            .map(|r| r.map_err(Into::into))
+            .collect()
+    }
+
+    /// Delete all index entries with a given prefix
+    pub fn delete_index_prefix(&self, prefix: &str) -> Result<usize> {
+        let cf = self.cf_handle(cf_names::INDICES)?;
+        let mut batch = WriteBatch::default();
+        let mut deleted = 0;
+
+        let iter = self.db.prefix_iterator_cf(&cf, prefix.as_bytes());
+        for item in iter {
+            let (key, _) = item?;
+            if !key.starts_with(prefix.as_bytes()) {
+                break;
+            }
+            batch.delete_cf(&cf, &key);
+            deleted += 1;
+
+            // Keep large prefixes from building one huge batch
+            if batch.len() >= 10_000 {
+                self.db
+                    .write_opt(std::mem::take(&mut batch), &self.write_opts)?;
+            }
+        }
+
+        self.db.write_opt(batch, &self.write_opts)?;
+        Ok(deleted)
+    }
+
+    /// Visit all index entries with a given prefix without collecting them
+    pub fn for_each_index_prefix(
+        &self,
+        prefix: &str,
+        mut f: impl FnMut(&str, &[u8]) -> Result<()>,
+    ) -> Result<()> {
+        let cf = self.cf_handle(cf_names::INDICES)?;
+
+        let iter = self.db.prefix_iterator_cf(&cf, prefix.as_bytes());
+        for item in iter {
+            let (key, value) = item?;
+            if !key.starts_with(prefix.as_bytes()) {
+                break;
+            }
+            f(&String::from_utf8_lossy(&key), &value)?;
+        }
+
+        Ok(())
+    }
+
     /// Append to an index list (for taxonomy and database indices)
     pub fn append_to_index_list(&self, key: &str, hash: &SHA256Hash) -> Result<()> {
         let cf = self.cf_handle(cf_names::INDICES)?;
@@ -463,19 +551,8 @@ impl RocksDBBackend {
         // Create directory if it doesn't exist
         std::fs::create_dir_all(&path)?;
 
-        // Configure column families
-        let column_families = [
-            cf_names::DEFAULT,
-            cf_names::SEQUENCES,
-            cf_names::REPRESENTATIONS,
-            cf_names::MANIFESTS,
-            cf_names::INDICES,
-            cf_names::MERKLE,
-            cf_names::TEMPORAL,
-        ];
-
         // Create options for each column family
-        let cf_descriptors: Vec<ColumnFamilyDescriptor> = column_families
+        let cf_descriptors: Vec<ColumnFamilyDescriptor> = ALL_COLUMN_FAMILIES
             .iter()
             .map(|name| {
                 let cf_opts = Self::create_cf_options(&config, name);
@@ -501,13 +578,69 @@ impl RocksDBBackend {
         write_opts.set_sync(false); // Don't sync on every write for performance
         write_opts.disable_wal(false); // Keep WAL for durability
 
+        let statistics = config.enable_statistics.then_some(db_opts);
+
         Ok(Self {
             db: Arc::new(db),
             config,
             write_opts,
+            statistics,
         })
     }
 
+    /// Sample storage, compaction and cache figures for monitoring
+    ///
+    /// Size and compaction properties are summed over all column families.
+    /// Cache hit and bloom filter counts are only filled when statistics are
+    /// enabled.
+    pub fn collect_metrics(&self) -> RocksDBMetrics {
+        let mut metrics = RocksDBMetrics::new();
+
+        for name in ALL_COLUMN_FAMILIES {
+            let Some(cf) = self.db.cf_handle(name) else {
+                continue;
+            };
+            let property = |property: &str| {
+                self.db
+                    .property_int_value_cf(&cf, property)
+                    .ok()
+                    .flatten()
+                    .unwrap_or(0)
+            };
+            metrics.total_keys += property("rocksdb.estimate-num-keys");
+            metrics.total_size_bytes += property("rocksdb.estimate-live-data-size");
+            metrics.num_files += (0..NUM_LEVELS)
+                .map(|level| property(&format!("rocksdb.num-files-at-level{}", level)))
+                .sum::<u64>();
+            metrics.pending_compaction_bytes +=
+                property("rocksdb.estimate-pending-compaction-bytes");
+            metrics.memtable_bytes += property("rocksdb.cur-size-all-mem-tables");
+        }
+
+        // DB-wide properties; the block cache is shared by all column families
+        let property = |property: &str| {
+            self.db
+                .property_int_value(property)
+                .ok()
+                .flatten()
+                .unwrap_or(0)
+        };
+        metrics.running_compactions = property("rocksdb.num-running-compactions");
+        metrics.block_cache_usage_bytes = property("rocksdb.block-cache-usage");
+        metrics.block_cache_capacity_bytes = property("rocksdb.block-cache-capacity");
+
+        if let Some(opts) = &self.statistics {
+            metrics.cache_hits = opts.get_ticker_count(Ticker::BlockCacheHit);
+            metrics.cache_misses = opts.get_ticker_count(Ticker::BlockCacheMiss);
+            metrics.bloom_filter_hits = opts.get_ticker_count(Ticker::BloomFilterFullPositive);
+            metrics.bloom_filter_misses = opts.get_ticker_count(Ticker::BloomFilterUseful);
+            metrics.compaction_read_bytes = opts.get_ticker_count(Ticker::CompactReadBytes);
+            metrics.compaction_write_bytes = opts.get_ticker_count(Ticker::CompactWriteBytes);
+        }
+
+        metrics
+    }
+
     /// Create database options
     fn create_db_options(config: &RocksDBConfig) -> Result<Options> {
         let mut opts = Options::default();
@@ -807,6 +940,38 @@ impl RocksDBBackend {
         Ok(())
     }
 
+    /// Restore database from a specific backup
+    ///
+    /// # Arguments
+    /// * `backup_dir` - Directory containing backups
+    /// * `restore_dir` - Directory to restore the database to
+    /// * `backup_id` - ID of backup to restore
+    ///
+    /// # Note
+    /// This will overwrite any existing data in restore_dir
+    pub fn restore_from_backup<P: AsRef<Path>>(
+        backup_dir: P,
+        restore_dir: P,
+        backup_id: u32,
+    ) -> Result<()> {
+        let backup_path = backup_dir.as_ref();
+        let restore_path = restore_dir.as_ref();
+
+        // Create restore directory if it doesn't exist
+        std::fs::create_dir_all(restore_path).context("Failed to create restore directory")?;
+
+        // Initialize backup engine
+        let backup_opts = BackupEngineOptions::new(backup_path)?;
+        let mut backup_engine = BackupEngine::open(&backup_opts, &rocksdb::Env::new()?)?;
+
+        let restore_opts = RestoreOptions::default();
+        backup_engine
+            .restore_from_backup(restore_path, restore_path, &restore_opts, backup_id)
+            .context(format!("Failed to restore backup {}", backup_id))?;
+
+        Ok(())
+    }
+
     /// List all available backups
     ///
     /// # Arguments
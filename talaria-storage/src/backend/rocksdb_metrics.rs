@@ -32,6 +32,15 @@ pub struct RocksDBMetrics {
     pub num_files: u64,
     pub compaction_time_ms: u64,
 
+    // Compaction and memory metrics
+    pub pending_compaction_bytes: u64,
+    pub running_compactions: u64,
+    pub compaction_read_bytes: u64,
+    pub compaction_write_bytes: u64,
+    pub memtable_bytes: u64,
+    pub block_cache_usage_bytes: u64,
+    pub block_cache_capacity_bytes: u64,
+
     // Error tracking
     pub read_errors: u64,
     pub write_errors: u64,
@@ -59,6 +68,13 @@ impl Default for RocksDBMetrics {
             total_size_bytes: 0,
             num_files: 0,
             compaction_time_ms: 0,
+            pending_compaction_bytes: 0,
+            running_compactions: 0,
+            compaction_read_bytes: 0,
+            compaction_write_bytes: 0,
+            memtable_bytes: 0,
+            block_cache_usage_bytes: 0,
+            block_cache_capacity_bytes: 0,
             read_errors: 0,
             write_errors: 0,
             last_reset: Instant::now(),
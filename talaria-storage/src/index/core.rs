@@ -194,11 +194,7 @@ impl ChunkIndex for InMemoryChunkIndex {
         let total_chunks = self.chunks.len();
         let total_size: usize = self.chunks.iter().map(|entry| entry.size).sum();
         let unique_taxons = self.taxon_index.len();
-        let avg_chunk_size = if total_chunks > 0 {
-            total_size / total_chunks
-        } else {
-            0
-        };
+        let avg_chunk_size = total_size.checked_div(total_chunks).unwrap_or(0);
 
         Ok(IndexStats {
             total_chunks,
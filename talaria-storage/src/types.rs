@@ -142,8 +142,11 @@ impl SequenceRepresentations {
             .iter_mut()
             .find(|r| r.source == repr.source && r.header == repr.header)
         {
-            // Update last_seen timestamp
+            // Update last_seen timestamp and any newly assigned taxon
             existing.last_seen = repr.last_seen;
+            if repr.taxon_id.is_some() {
+                existing.taxon_id = repr.taxon_id;
+            }
         } else {
             self.representations.push(repr);
         }
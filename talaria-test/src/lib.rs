@@ -11,11 +11,13 @@
 //! - **Mock Implementations**: Mock versions of core components for testing
 //! - **Fixtures**: Common test data and FASTA sequences
 //! - **Assertions**: Custom assertions for bioinformatics data
+//! - **Simulator**: Evolving synthetic databases with UniProt/NCBI-style releases
 
 pub mod assertions;
 pub mod environment;
 pub mod fixtures;
 pub mod mock;
+pub mod simulator;
 pub mod storage;
 
 // Re-export commonly used items
@@ -25,6 +27,7 @@ pub use mock::{
     create_test_download_state, InMemoryStorageBackend, MockAligner, MockDownloadSource,
     MockTaxonomyManager,
 };
+pub use simulator::{Release, Simulator, SimulatorConfig};
 pub use storage::{StorageFixture, TestStorage};
 
 // Re-export test dependencies for convenience
@@ -0,0 +1,297 @@
+//! Sequence families evolved along the taxonomy
+
+use super::taxonomy::SimTaxonomy;
+use once_cell::sync::Lazy;
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use talaria_core::types::{SequenceType, TaxonId};
+
+const AMINO_ACIDS: &[u8] = b"ACDEFGHIKLMNPQRSTVWY";
+/// Background frequencies (per mille) of `AMINO_ACIDS` in UniProtKB
+const AMINO_ACID_WEIGHTS: [u32; 20] = [
+    83, 14, 55, 67, 39, 71, 23, 59, 58, 97, 24, 41, 47, 39, 55, 66, 54, 69, 11, 29,
+];
+static AMINO_ACID_DISTRIBUTION: Lazy<WeightedIndex<u32>> =
+    Lazy::new(|| WeightedIndex::new(AMINO_ACID_WEIGHTS).unwrap());
+const NUCLEOTIDES: &[u8] = b"ACGT";
+
+const NAME_PREFIXES: &[&str] = &[
+    "Glutamate",
+    "Serine",
+    "Phosphate",
+    "Sugar",
+    "Zinc",
+    "Iron",
+    "Heme",
+    "ATP-binding",
+    "DNA-binding",
+    "Membrane",
+];
+const NAME_KINDS: &[&str] = &[
+    "dehydrogenase",
+    "kinase",
+    "synthase",
+    "transporter",
+    "reductase",
+    "permease",
+    "protease",
+    "isomerase",
+    "ligase",
+    "regulator",
+];
+
+/// Per-branch rates of the evolution model
+///
+/// Rates are per site (substitutions, indels) or per gene copy (duplication,
+/// loss) for a branch of length 1. Branch lengths are drawn from an
+/// exponential distribution with mean 1.
+#[derive(Debug, Clone)]
+pub struct EvolutionModel {
+    pub substitution_rate: f64,
+    pub indel_rate: f64,
+    /// Mean of the geometric indel length distribution
+    pub mean_indel_length: f64,
+    pub duplication_rate: f64,
+    pub loss_rate: f64,
+    /// Transition/transversion ratio for nucleotide substitutions
+    pub kappa: f64,
+}
+
+impl Default for EvolutionModel {
+    fn default() -> Self {
+        Self {
+            substitution_rate: 0.05,
+            indel_rate: 0.002,
+            mean_indel_length: 3.0,
+            duplication_rate: 0.05,
+            loss_rate: 0.1,
+            kappa: 2.0,
+        }
+    }
+}
+
+/// A protein or gene family, rooted at the taxon it originated in
+#[derive(Debug, Clone)]
+pub struct Family {
+    pub index: usize,
+    pub molecule: SequenceType,
+    /// Gene symbol, e.g. `sdhA`
+    pub gene: String,
+    /// Product name, e.g. `Serine kinase`
+    pub name: String,
+    pub origin: TaxonId,
+    pub root_sequence: Vec<u8>,
+    /// Paralogs created so far, the original copy being 1
+    pub paralogs: u32,
+}
+
+/// One evolved gene copy in a species
+#[derive(Debug, Clone)]
+pub struct GeneCopy {
+    pub taxon_id: TaxonId,
+    pub paralog: u32,
+    pub sequence: Vec<u8>,
+}
+
+impl Family {
+    pub(crate) fn random(
+        rng: &mut StdRng,
+        index: usize,
+        molecule: SequenceType,
+        origin: TaxonId,
+        mean_length: usize,
+    ) -> Self {
+        let length = rng.gen_range(mean_length / 2..=mean_length * 3 / 2).max(10);
+        let gene = format!(
+            "{}{}",
+            (0..3)
+                .map(|_| rng.gen_range(b'a'..=b'z') as char)
+                .collect::<String>(),
+            rng.gen_range(b'A'..=b'Z') as char
+        );
+        Self {
+            index,
+            molecule,
+            gene,
+            name: format!(
+                "{} {}",
+                NAME_PREFIXES.choose(rng).unwrap(),
+                NAME_KINDS.choose(rng).unwrap()
+            ),
+            origin,
+            root_sequence: random_sequence(rng, molecule, length),
+            paralogs: 1,
+        }
+    }
+
+    /// Product name of one paralog
+    pub fn paralog_name(&self, paralog: u32) -> String {
+        if paralog == 1 {
+            self.name.clone()
+        } else {
+            format!("{} {}", self.name, paralog)
+        }
+    }
+
+    /// Evolve the family from its origin down to every species below it
+    pub(crate) fn evolve(
+        &mut self,
+        rng: &mut StdRng,
+        model: &EvolutionModel,
+        taxonomy: &SimTaxonomy,
+    ) -> Vec<GeneCopy> {
+        let mut leaves = Vec::new();
+        let root = vec![GeneCopy {
+            taxon_id: self.origin,
+            paralog: 1,
+            sequence: self.root_sequence.clone(),
+        }];
+        let mut stack = vec![(self.origin, root)];
+
+        while let Some((taxon, copies)) = stack.pop() {
+            let children = taxonomy.children(taxon);
+            if children.is_empty() {
+                if taxonomy.get(taxon).is_some_and(|t| t.rank == "species") {
+                    leaves.extend(copies.into_iter().map(|copy| GeneCopy {
+                        taxon_id: taxon,
+                        ..copy
+                    }));
+                }
+                continue;
+            }
+            for child in children {
+                let branch_length = branch_length(rng);
+                let mut evolved = Vec::new();
+                for copy in &copies {
+                    if rng.gen::<f64>() < model.loss_rate * branch_length {
+                        continue;
+                    }
+                    if rng.gen::<f64>() < model.duplication_rate * branch_length {
+                        self.paralogs += 1;
+                        evolved.push(GeneCopy {
+                            taxon_id: child,
+                            paralog: self.paralogs,
+                            sequence: model.mutate(
+                                rng,
+                                &copy.sequence,
+                                self.molecule,
+                                branch_length,
+                            ),
+                        });
+                    }
+                    evolved.push(GeneCopy {
+                        taxon_id: child,
+                        paralog: copy.paralog,
+                        sequence: model.mutate(rng, &copy.sequence, self.molecule, branch_length),
+                    });
+                }
+                if !evolved.is_empty() {
+                    stack.push((child, evolved));
+                }
+            }
+        }
+
+        leaves.sort_by_key(|copy| (copy.taxon_id, copy.paralog));
+        leaves
+    }
+}
+
+impl EvolutionModel {
+    /// Apply substitutions and indels for a branch of the given length
+    ///
+    /// The first residue is kept so proteins keep their initiator methionine.
+    pub fn mutate(
+        &self,
+        rng: &mut StdRng,
+        sequence: &[u8],
+        molecule: SequenceType,
+        branch_length: f64,
+    ) -> Vec<u8> {
+        let substitution = self.substitution_rate * branch_length;
+        let indel = self.indel_rate * branch_length;
+        let mut out = Vec::with_capacity(sequence.len() + 8);
+        if let Some(first) = sequence.first() {
+            out.push(*first);
+        }
+
+        let mut i = 1;
+        while i < sequence.len() {
+            if rng.gen::<f64>() < indel {
+                let length = self.indel_length(rng);
+                if rng.gen_bool(0.5) {
+                    out.extend((0..length).map(|_| random_residue(rng, molecule)));
+                } else {
+                    // Never delete the whole sequence
+                    i += length.min(sequence.len() - i - 1);
+                }
+            }
+            let residue = sequence[i];
+            out.push(if rng.gen::<f64>() < substitution {
+                self.substitute(rng, residue, molecule)
+            } else {
+                residue
+            });
+            i += 1;
+        }
+        out
+    }
+
+    fn indel_length(&self, rng: &mut StdRng) -> usize {
+        let p = 1.0 / self.mean_indel_length.max(1.0);
+        let mut length = 1;
+        while rng.gen::<f64>() > p && length < 50 {
+            length += 1;
+        }
+        length
+    }
+
+    fn substitute(&self, rng: &mut StdRng, residue: u8, molecule: SequenceType) -> u8 {
+        if molecule.is_protein() {
+            loop {
+                let replacement = random_residue(rng, molecule);
+                if replacement != residue {
+                    return replacement;
+                }
+            }
+        }
+        let transition = match residue {
+            b'A' => b'G',
+            b'G' => b'A',
+            b'C' => b'T',
+            _ => b'C',
+        };
+        if rng.gen::<f64>() < self.kappa / (self.kappa + 2.0) {
+            transition
+        } else {
+            let transversions: Vec<u8> = NUCLEOTIDES
+                .iter()
+                .copied()
+                .filter(|n| *n != residue && *n != transition)
+                .collect();
+            *transversions.choose(rng).unwrap()
+        }
+    }
+}
+
+pub(crate) fn random_sequence(rng: &mut StdRng, molecule: SequenceType, length: usize) -> Vec<u8> {
+    let mut sequence: Vec<u8> = (0..length).map(|_| random_residue(rng, molecule)).collect();
+    if molecule.is_protein() {
+        sequence[0] = b'M';
+    }
+    sequence
+}
+
+fn random_residue(rng: &mut StdRng, molecule: SequenceType) -> u8 {
+    if molecule.is_protein() {
+        AMINO_ACIDS[AMINO_ACID_DISTRIBUTION.sample(rng)]
+    } else {
+        NUCLEOTIDES[rng.gen_range(0..4)]
+    }
+}
+
+/// Exponentially distributed branch length with mean 1
+pub(crate) fn branch_length(rng: &mut StdRng) -> f64 {
+    -(1.0 - rng.gen::<f64>()).ln()
+}
@@ -0,0 +1,568 @@
+//! Synthetic evolving databases
+//!
+//! Builds a random taxonomy, evolves protein and nucleotide families along it
+//! with substitution, indel, duplication and loss models, then emits a series
+//! of releases with additions, deletions, header edits, sequence revisions,
+//! reclassifications and taxonomy changes. Each release can be written as
+//! UniProt- and NCBI-style FASTA, `accession2taxid` tables and an NCBI
+//! taxdump, and lists what changed so tests of `database update`, `diff` and
+//! `temporal` can check against ground truth without downloading real data.
+//!
+//! Everything derives from the seed, so a series is identical across runs.
+//!
+//! ```no_run
+//! use talaria_test::simulator::{Simulator, SimulatorConfig};
+//!
+//! let dir = tempfile::tempdir().unwrap();
+//! for release in Simulator::new(SimulatorConfig::small()).take(3) {
+//!     release.write_to(&dir.path().join(&release.name)).unwrap();
+//! }
+//! ```
+
+mod evolution;
+mod release;
+mod taxonomy;
+
+pub use evolution::{EvolutionModel, Family, GeneCopy};
+pub use release::{Release, ReleaseChanges, ReleaseConfig, ReleaseFiles, SimRecord};
+pub use taxonomy::{SimTaxon, SimTaxonomy, RANKS, ROOT};
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use std::collections::HashSet;
+use talaria_core::types::{SequenceType, TaxonId};
+
+/// Branch length used to derive a new record from a relative
+const ADDITION_BRANCH_LENGTH: f64 = 0.2;
+/// Branch length of a sequence revision
+const REVISION_BRANCH_LENGTH: f64 = 0.05;
+
+/// Size and dynamics of a simulated database series
+#[derive(Debug, Clone)]
+pub struct SimulatorConfig {
+    pub seed: u64,
+    /// Children per taxon below the root are drawn from 1..=max_children
+    pub max_children: usize,
+    pub protein_families: usize,
+    pub nucleotide_families: usize,
+    /// Mean root sequence length in residues
+    pub protein_length: usize,
+    pub nucleotide_length: usize,
+    pub evolution: EvolutionModel,
+    pub release: ReleaseConfig,
+}
+
+impl Default for SimulatorConfig {
+    fn default() -> Self {
+        Self {
+            seed: 42,
+            max_children: 3,
+            protein_families: 40,
+            nucleotide_families: 10,
+            protein_length: 300,
+            nucleotide_length: 900,
+            evolution: EvolutionModel::default(),
+            release: ReleaseConfig::default(),
+        }
+    }
+}
+
+impl SimulatorConfig {
+    /// A few dozen records, fast enough for unit tests
+    pub fn small() -> Self {
+        Self {
+            max_children: 2,
+            protein_families: 8,
+            nucleotide_families: 2,
+            protein_length: 120,
+            nucleotide_length: 360,
+            ..Self::default()
+        }
+    }
+
+    /// Around twenty thousand records, for benchmarks
+    pub fn large() -> Self {
+        Self {
+            max_children: 4,
+            protein_families: 200,
+            nucleotide_families: 50,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+}
+
+/// Generates a series of releases; also an endless iterator over them
+pub struct Simulator {
+    config: SimulatorConfig,
+    rng: StdRng,
+    taxonomy: SimTaxonomy,
+    families: Vec<Family>,
+    records: Vec<SimRecord>,
+    next_record: u64,
+    released: u32,
+}
+
+impl Simulator {
+    /// Build the taxonomy and evolve the initial records
+    pub fn new(config: SimulatorConfig) -> Self {
+        let mut rng = StdRng::seed_from_u64(config.seed);
+        let taxonomy = SimTaxonomy::generate(&mut rng, config.max_children);
+        let mut simulator = Self {
+            config,
+            rng,
+            taxonomy,
+            families: Vec::new(),
+            records: Vec::new(),
+            next_record: 1,
+            released: 0,
+        };
+
+        // Families originate high in the tree, so most are clade-specific
+        let origins: Vec<TaxonId> = ["superkingdom", "phylum", "class"]
+            .iter()
+            .flat_map(|rank| simulator.taxonomy.at_rank(rank))
+            .collect();
+        let molecules =
+            std::iter::repeat_n(SequenceType::Protein, simulator.config.protein_families).chain(
+                std::iter::repeat_n(SequenceType::DNA, simulator.config.nucleotide_families),
+            );
+        for (index, molecule) in molecules.enumerate() {
+            let origin = *origins.choose(&mut simulator.rng).unwrap();
+            let mean_length = if molecule.is_protein() {
+                simulator.config.protein_length
+            } else {
+                simulator.config.nucleotide_length
+            };
+            let mut family =
+                Family::random(&mut simulator.rng, index, molecule, origin, mean_length);
+            let copies = family.evolve(
+                &mut simulator.rng,
+                &simulator.config.evolution,
+                &simulator.taxonomy,
+            );
+            simulator.families.push(family);
+            for copy in copies {
+                let name = simulator.families[index].paralog_name(copy.paralog);
+                simulator.push_record(index, copy.taxon_id, name, copy.sequence);
+            }
+        }
+        simulator
+    }
+
+    pub fn config(&self) -> &SimulatorConfig {
+        &self.config
+    }
+
+    /// Taxonomy as of the latest release
+    pub fn taxonomy(&self) -> &SimTaxonomy {
+        &self.taxonomy
+    }
+
+    pub fn families(&self) -> &[Family] {
+        &self.families
+    }
+
+    /// Produce the next release; the first one lists every record as added
+    pub fn next_release(&mut self) -> Release {
+        let changes = if self.released == 0 {
+            ReleaseChanges {
+                added: self.records.iter().map(|r| r.accession.clone()).collect(),
+                ..Default::default()
+            }
+        } else {
+            self.evolve_release()
+        };
+        self.released += 1;
+
+        let number = self.released;
+        Release {
+            number,
+            name: format!(
+                "{:04}_{:02}",
+                2020 + (number - 1) / 12,
+                (number - 1) % 12 + 1
+            ),
+            records: self.records.clone(),
+            taxonomy: self.taxonomy.clone(),
+            changes,
+        }
+    }
+
+    fn evolve_release(&mut self) -> ReleaseChanges {
+        let config = self.config.release.clone();
+        let mut changes = ReleaseChanges::default();
+        // Records already changed in this release
+        let mut touched: HashSet<String> = HashSet::new();
+
+        // New species, in genera that already have species
+        let genera = self.taxonomy.at_rank("genus");
+        for _ in 0..config.new_species {
+            if let Some(genus) = genera.choose(&mut self.rng) {
+                let species = self.taxonomy.add_species(&mut self.rng, *genus);
+                changes.new_taxa.push(species);
+            }
+        }
+
+        // Species merged into a sibling; their records follow
+        let mut involved: HashSet<TaxonId> = changes.new_taxa.iter().copied().collect();
+        for _ in 0..config.taxon_merges {
+            let candidates: Vec<(TaxonId, TaxonId)> = self
+                .taxonomy
+                .species()
+                .into_iter()
+                .filter(|species| !involved.contains(species))
+                .filter_map(|species| {
+                    let genus = self.taxonomy.get(species)?.parent;
+                    let sibling = self
+                        .taxonomy
+                        .children(genus)
+                        .into_iter()
+                        .find(|s| *s != species && !involved.contains(s))?;
+                    Some((species, sibling))
+                })
+                .collect();
+            let Some(&(from, into)) = candidates.choose(&mut self.rng) else {
+                break;
+            };
+            self.taxonomy.merge(from, into);
+            involved.extend([from, into]);
+            changes.merged_taxa.push((from, into));
+            for record in self.records.iter_mut().filter(|r| r.taxon_id == from) {
+                record.taxon_id = into;
+                touched.insert(record.accession.clone());
+                changes
+                    .reclassified
+                    .push((record.accession.clone(), from, into));
+            }
+        }
+
+        // Genera moved to another family
+        let families = self.taxonomy.at_rank("family");
+        for _ in 0..config.lineage_moves {
+            let moved: HashSet<TaxonId> = changes.moved_taxa.iter().copied().collect();
+            let genera: Vec<TaxonId> = self
+                .taxonomy
+                .at_rank("genus")
+                .into_iter()
+                .filter(|genus| !moved.contains(genus))
+                .collect();
+            let Some(&genus) = genera.choose(&mut self.rng) else {
+                break;
+            };
+            let parent = self.taxonomy.get(genus).unwrap().parent;
+            let targets: Vec<TaxonId> = families.iter().copied().filter(|f| *f != parent).collect();
+            if let Some(&family) = targets.choose(&mut self.rng) {
+                self.taxonomy.reparent(genus, family);
+                changes.moved_taxa.push(genus);
+            }
+        }
+
+        // Record-level changes draw from disjoint shuffled slices
+        let count = |rate: f64| (rate * self.records.len() as f64).round() as usize;
+        let (deletions, edits, revisions, reclassifications) = (
+            count(config.deletion_rate),
+            count(config.header_edit_rate),
+            count(config.revision_rate),
+            count(config.reclassification_rate),
+        );
+        let additions = count(config.addition_rate);
+        let mut pool: Vec<usize> = (0..self.records.len())
+            .filter(|i| !touched.contains(&self.records[*i].accession))
+            .collect();
+        pool.shuffle(&mut self.rng);
+        let mut pool = pool.into_iter();
+
+        let removed: HashSet<usize> = pool.by_ref().take(deletions).collect();
+        for index in pool.by_ref().take(edits) {
+            let record = &mut self.records[index];
+            record.name = match record.name.strip_prefix("Probable ") {
+                Some(name) => name.to_string(),
+                None => format!("Probable {}", record.name),
+            };
+            changes.header_edited.push(record.accession.clone());
+        }
+        for index in pool.by_ref().take(revisions) {
+            let record = &mut self.records[index];
+            record.sequence = String::from_utf8(self.config.evolution.mutate(
+                &mut self.rng,
+                record.sequence.as_bytes(),
+                record.molecule,
+                REVISION_BRANCH_LENGTH,
+            ))
+            .unwrap();
+            record.version += 1;
+            changes.revised.push(record.accession.clone());
+        }
+        for index in pool.by_ref().take(reclassifications) {
+            let from = self.records[index].taxon_id;
+            let Some(genus) = self.taxonomy.get(from).map(|t| t.parent) else {
+                continue;
+            };
+            let siblings: Vec<TaxonId> = self
+                .taxonomy
+                .children(genus)
+                .into_iter()
+                .filter(|s| *s != from)
+                .collect();
+            if let Some(&to) = siblings.choose(&mut self.rng) {
+                let record = &mut self.records[index];
+                record.taxon_id = to;
+                changes
+                    .reclassified
+                    .push((record.accession.clone(), from, to));
+            }
+        }
+
+        let mut kept = Vec::with_capacity(self.records.len());
+        for (index, record) in std::mem::take(&mut self.records).into_iter().enumerate() {
+            if removed.contains(&index) {
+                changes.removed.push(record.accession);
+            } else {
+                kept.push(record);
+            }
+        }
+        self.records = kept;
+
+        // Species left without records may disappear from the tree
+        let populated: HashSet<TaxonId> = self.records.iter().map(|r| r.taxon_id).collect();
+        for _ in 0..config.taxon_deletions {
+            let empty: Vec<TaxonId> = self
+                .taxonomy
+                .species()
+                .into_iter()
+                .filter(|s| !populated.contains(s) && !involved.contains(s))
+                .collect();
+            let Some(&species) = empty.choose(&mut self.rng) else {
+                break;
+            };
+            self.taxonomy.delete(species);
+            involved.insert(species);
+            changes.deleted_taxa.push(species);
+        }
+
+        // Additions: every new species gets a record, the rest go to relatives
+        let mut targets: Vec<TaxonId> = changes.new_taxa.clone();
+        let species = self.taxonomy.species();
+        while targets.len() < additions.max(changes.new_taxa.len()) {
+            targets.push(*species.choose(&mut self.rng).unwrap());
+        }
+        for taxon in targets {
+            let accession = self.add_record(taxon);
+            changes.added.push(accession);
+        }
+
+        changes
+    }
+
+    /// Add a record to `taxon`, derived from a family member in its genus if any
+    fn add_record(&mut self, taxon: TaxonId) -> String {
+        let genus = self.taxonomy.get(taxon).map(|t| t.parent);
+        let relatives: Vec<usize> = self
+            .records
+            .iter()
+            .enumerate()
+            .filter(|(_, r)| self.taxonomy.get(r.taxon_id).map(|t| t.parent) == genus)
+            .map(|(i, _)| i)
+            .collect();
+
+        let (family, name, sequence) = match relatives.choose(&mut self.rng) {
+            Some(&index) => {
+                let relative = &self.records[index];
+                (
+                    relative.family,
+                    relative.name.clone(),
+                    relative.sequence.clone().into_bytes(),
+                )
+            }
+            None => {
+                let family = &self.families[self.rng.gen_range(0..self.families.len())];
+                (
+                    family.index,
+                    family.name.clone(),
+                    family.root_sequence.clone(),
+                )
+            }
+        };
+        let molecule = self.families[family].molecule;
+        let sequence = self.config.evolution.mutate(
+            &mut self.rng,
+            &sequence,
+            molecule,
+            ADDITION_BRANCH_LENGTH,
+        );
+        self.push_record(family, taxon, name, sequence)
+    }
+
+    fn push_record(
+        &mut self,
+        family: usize,
+        taxon_id: TaxonId,
+        name: String,
+        sequence: Vec<u8>,
+    ) -> String {
+        let number = self.next_record;
+        self.next_record += 1;
+        let family = &self.families[family];
+        let (accession, ncbi_accession) = if family.molecule.is_protein() {
+            (uniprot_accession(number), format!("WP_{:09}", number))
+        } else {
+            let accession = format!("NM_{:09}", number);
+            (accession.clone(), accession)
+        };
+        self.records.push(SimRecord {
+            accession: accession.clone(),
+            ncbi_accession,
+            version: 1,
+            molecule: family.molecule,
+            family: family.index,
+            gene: family.gene.clone(),
+            name,
+            taxon_id,
+            sequence: String::from_utf8(sequence).expect("residues are ASCII"),
+        });
+        accession
+    }
+}
+
+impl Iterator for Simulator {
+    type Item = Release;
+
+    fn next(&mut self) -> Option<Release> {
+        Some(self.next_release())
+    }
+}
+
+/// UniProt-style accession: `P00001`..`O99999`, then `A0A0000001` onwards
+fn uniprot_accession(number: u64) -> String {
+    const PREFIXES: [char; 3] = ['P', 'Q', 'O'];
+    if number < 300_000 {
+        format!(
+            "{}{:05}",
+            PREFIXES[(number / 100_000) as usize],
+            number % 100_000
+        )
+    } else {
+        format!("A0A{:07}", number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn two_releases() -> (Release, Release) {
+        let mut simulator = Simulator::new(SimulatorConfig::small());
+        (simulator.next_release(), simulator.next_release())
+    }
+
+    #[test]
+    fn test_same_seed_same_series() {
+        let a: Vec<Release> = Simulator::new(SimulatorConfig::small()).take(3).collect();
+        let b: Vec<Release> = Simulator::new(SimulatorConfig::small()).take(3).collect();
+        assert_eq!(a[2].uniprot_fasta(), b[2].uniprot_fasta());
+        assert_eq!(a[2].taxonomy.nodes_dmp(), b[2].taxonomy.nodes_dmp());
+        assert_eq!(a[2].changes, b[2].changes);
+
+        let other = Simulator::new(SimulatorConfig::small().with_seed(7)).next_release();
+        assert_ne!(a[0].uniprot_fasta(), other.uniprot_fasta());
+    }
+
+    #[test]
+    fn test_initial_release() {
+        let (first, _) = two_releases();
+        assert_eq!(first.name, "2020_01");
+        assert!(first.records.len() > 10, "{} records", first.records.len());
+        assert_eq!(first.changes.added.len(), first.records.len());
+        assert!(first.proteins().all(|r| r.sequence.starts_with('M')));
+        assert!(first
+            .nucleotides()
+            .all(|r| r.sequence.bytes().all(|b| b"ACGT".contains(&b))));
+        for record in &first.records {
+            let taxon = first.taxonomy.get(record.taxon_id).unwrap();
+            assert_eq!(taxon.rank, "species");
+            assert_eq!(first.taxonomy.lineage(record.taxon_id)[0], ROOT);
+        }
+    }
+
+    #[test]
+    fn test_changes_match_releases() {
+        let (old, new) = two_releases();
+        let changes = &new.changes;
+        assert!(!changes.added.is_empty() && !changes.removed.is_empty());
+
+        for accession in &changes.added {
+            assert!(old.record(accession).is_none() && new.record(accession).is_some());
+        }
+        for accession in &changes.removed {
+            assert!(old.record(accession).is_some() && new.record(accession).is_none());
+        }
+        for accession in &changes.header_edited {
+            assert_ne!(
+                old.record(accession).unwrap().name,
+                new.record(accession).unwrap().name
+            );
+        }
+        for accession in &changes.revised {
+            assert_eq!(
+                old.record(accession).unwrap().version + 1,
+                new.record(accession).unwrap().version
+            );
+        }
+        for (accession, from, to) in &changes.reclassified {
+            assert_eq!(old.record(accession).unwrap().taxon_id, *from);
+            assert_eq!(new.record(accession).unwrap().taxon_id, *to);
+        }
+
+        for (from, into) in &changes.merged_taxa {
+            assert!(!new.taxonomy.contains(*from));
+            assert_eq!(new.taxonomy.resolve(*from), Some(*into));
+        }
+        for taxon in &changes.deleted_taxa {
+            assert!(old.taxonomy.contains(*taxon) && !new.taxonomy.contains(*taxon));
+        }
+        for genus in &changes.moved_taxa {
+            assert_ne!(
+                old.taxonomy.get(*genus).unwrap().parent,
+                new.taxonomy.get(*genus).unwrap().parent
+            );
+        }
+        for record in &new.records {
+            assert!(new.taxonomy.contains(record.taxon_id));
+        }
+    }
+
+    #[test]
+    fn test_outputs() {
+        let (_, release) = two_releases();
+        let dir = tempfile::tempdir().unwrap();
+        let files = release.write_to(dir.path()).unwrap();
+
+        let uniprot = std::fs::read_to_string(&files.uniprot_fasta).unwrap();
+        let protein = release.proteins().next().unwrap();
+        let header = format!(">{}", protein.uniprot_header(&release.taxonomy));
+        assert!(uniprot.starts_with(&header));
+        assert!(header.contains(&format!("OX={} ", protein.taxon_id.0)));
+        assert!(uniprot
+            .lines()
+            .all(|line| line.len() <= 60 || line.starts_with('>')));
+
+        let nodes = std::fs::read_to_string(files.taxonomy_dir.join("nodes.dmp")).unwrap();
+        assert_eq!(nodes.lines().count(), release.taxonomy.len());
+        assert!(nodes
+            .lines()
+            .any(|line| line.starts_with("1\t|\t1\t|\tno rank")));
+
+        let table = std::fs::read_to_string(&files.prot_accession2taxid).unwrap();
+        assert_eq!(table.lines().count(), release.proteins().count() + 1);
+        assert!(table.contains(&format!(
+            "{}\t{}.{}\t{}\t0",
+            protein.ncbi_accession, protein.ncbi_accession, protein.version, protein.taxon_id.0
+        )));
+    }
+}
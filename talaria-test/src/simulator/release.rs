@@ -0,0 +1,334 @@
+//! Simulated releases and their UniProt/NCBI-style files
+
+use super::taxonomy::SimTaxonomy;
+use crate::fixtures::TestSequence;
+use anyhow::{Context, Result};
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+use talaria_core::types::{SequenceType, TaxonId};
+
+/// FASTA line width, as used by UniProt and NCBI
+const LINE_WIDTH: usize = 60;
+
+/// One sequence entry of a release
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimRecord {
+    /// Primary accession: UniProt-style for proteins, RefSeq-style for nucleotides
+    pub accession: String,
+    /// RefSeq-style accession, the same as `accession` for nucleotides
+    pub ncbi_accession: String,
+    /// Sequence version, bumped when the sequence is revised
+    pub version: u32,
+    pub molecule: SequenceType,
+    /// Index of the family the sequence descends from
+    pub family: usize,
+    pub gene: String,
+    /// Product name; header edits change it
+    pub name: String,
+    pub taxon_id: TaxonId,
+    pub sequence: String,
+}
+
+impl SimRecord {
+    /// UniProt entry name, e.g. `SDHA_BRACH`
+    pub fn entry_name(&self, taxonomy: &SimTaxonomy) -> String {
+        let species = self.organism(taxonomy);
+        let mut words = species.split_whitespace();
+        let genus = words.next().unwrap_or_default();
+        let epithet = words.next().unwrap_or_default();
+        let mnemonic: String = genus
+            .chars()
+            .take(3)
+            .chain(epithet.chars().take(2))
+            .collect();
+        format!("{}_{}", self.gene.to_uppercase(), mnemonic.to_uppercase())
+    }
+
+    fn organism<'a>(&self, taxonomy: &'a SimTaxonomy) -> &'a str {
+        taxonomy
+            .get(self.taxon_id)
+            .map(|taxon| taxon.name.as_str())
+            .unwrap_or("unclassified")
+    }
+
+    /// `>sp|ACC|ENTRY Name OS=Organism OX=taxid GN=gene PE=1 SV=n`
+    pub fn uniprot_header(&self, taxonomy: &SimTaxonomy) -> String {
+        format!(
+            "sp|{}|{} {} OS={} OX={} GN={} PE=1 SV={}",
+            self.accession,
+            self.entry_name(taxonomy),
+            self.name,
+            self.organism(taxonomy),
+            self.taxon_id.0,
+            self.gene,
+            self.version
+        )
+    }
+
+    /// `>ACC.version Name [Organism]` for proteins, GenBank-style for nucleotides
+    pub fn ncbi_header(&self, taxonomy: &SimTaxonomy) -> String {
+        if self.molecule.is_protein() {
+            format!(
+                "{}.{} {} [{}]",
+                self.ncbi_accession,
+                self.version,
+                self.name,
+                self.organism(taxonomy)
+            )
+        } else {
+            format!(
+                "{}.{} {} {} ({}) gene, complete cds",
+                self.ncbi_accession,
+                self.version,
+                self.organism(taxonomy),
+                self.name,
+                self.gene
+            )
+        }
+    }
+
+    /// The record as a fixture sequence, with its UniProt-style ID
+    pub fn to_test_sequence(&self) -> TestSequence {
+        TestSequence::new(&self.accession, &self.sequence)
+            .with_description(&self.name)
+            .with_taxon(self.taxon_id)
+    }
+}
+
+/// How much changes from one release to the next
+///
+/// Rates are fractions of the previous release's records; counts are per
+/// release. Every change is listed in [`ReleaseChanges`] so tests can check
+/// what `database update`, `diff` or `temporal` report against ground truth.
+#[derive(Debug, Clone)]
+pub struct ReleaseConfig {
+    pub addition_rate: f64,
+    pub deletion_rate: f64,
+    /// Product name edits that keep the sequence
+    pub header_edit_rate: f64,
+    /// Sequence revisions, which bump the sequence version
+    pub revision_rate: f64,
+    /// Records moved to another species of their genus
+    pub reclassification_rate: f64,
+    pub new_species: usize,
+    /// Species merged into a sibling (`merged.dmp`)
+    pub taxon_merges: usize,
+    /// Species without records removed from the tree (`delnodes.dmp`)
+    pub taxon_deletions: usize,
+    /// Genera moved to another family, changing the lineage of their species
+    pub lineage_moves: usize,
+}
+
+impl Default for ReleaseConfig {
+    fn default() -> Self {
+        Self {
+            addition_rate: 0.05,
+            deletion_rate: 0.02,
+            header_edit_rate: 0.03,
+            revision_rate: 0.01,
+            reclassification_rate: 0.01,
+            new_species: 1,
+            taxon_merges: 1,
+            taxon_deletions: 1,
+            lineage_moves: 1,
+        }
+    }
+}
+
+/// Ground truth of what changed since the previous release
+///
+/// Record changes are listed by primary accession. A record is in at most
+/// one of `removed`, `header_edited`, `revised` and `reclassified`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReleaseChanges {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub header_edited: Vec<String>,
+    pub revised: Vec<String>,
+    /// Accession, old taxon, new taxon; includes records of merged taxa
+    pub reclassified: Vec<(String, TaxonId, TaxonId)>,
+    pub new_taxa: Vec<TaxonId>,
+    /// Taxon merged away and the taxon it was merged into
+    pub merged_taxa: Vec<(TaxonId, TaxonId)>,
+    pub deleted_taxa: Vec<TaxonId>,
+    /// Genera given a new parent
+    pub moved_taxa: Vec<TaxonId>,
+}
+
+impl ReleaseChanges {
+    /// Whether nothing changed
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// One `kind<TAB>subject<TAB>detail` line per change
+    pub fn to_tsv(&self) -> String {
+        let mut out = String::from("change\tsubject\tdetail\n");
+        for accession in &self.added {
+            writeln!(out, "added\t{}\t", accession).unwrap();
+        }
+        for accession in &self.removed {
+            writeln!(out, "removed\t{}\t", accession).unwrap();
+        }
+        for accession in &self.header_edited {
+            writeln!(out, "header_edited\t{}\t", accession).unwrap();
+        }
+        for accession in &self.revised {
+            writeln!(out, "revised\t{}\t", accession).unwrap();
+        }
+        for (accession, from, to) in &self.reclassified {
+            writeln!(out, "reclassified\t{}\t{}->{}", accession, from.0, to.0).unwrap();
+        }
+        for taxon in &self.new_taxa {
+            writeln!(out, "new_taxon\t{}\t", taxon.0).unwrap();
+        }
+        for (from, into) in &self.merged_taxa {
+            writeln!(out, "merged_taxon\t{}\t{}", from.0, into.0).unwrap();
+        }
+        for taxon in &self.deleted_taxa {
+            writeln!(out, "deleted_taxon\t{}\t", taxon.0).unwrap();
+        }
+        for taxon in &self.moved_taxa {
+            writeln!(out, "moved_taxon\t{}\t", taxon.0).unwrap();
+        }
+        out
+    }
+}
+
+/// Paths written by [`Release::write_to`]
+#[derive(Debug, Clone)]
+pub struct ReleaseFiles {
+    pub uniprot_fasta: PathBuf,
+    pub ncbi_protein_fasta: PathBuf,
+    pub ncbi_nucleotide_fasta: PathBuf,
+    pub prot_accession2taxid: PathBuf,
+    pub nucl_accession2taxid: PathBuf,
+    /// Directory holding `nodes.dmp`, `names.dmp`, `merged.dmp` and `delnodes.dmp`
+    pub taxonomy_dir: PathBuf,
+    pub changes: PathBuf,
+}
+
+/// A complete snapshot of the simulated databases
+#[derive(Debug, Clone)]
+pub struct Release {
+    /// 1-based release number
+    pub number: u32,
+    /// UniProt-style release name, e.g. `2020_01`
+    pub name: String,
+    pub records: Vec<SimRecord>,
+    pub taxonomy: SimTaxonomy,
+    pub changes: ReleaseChanges,
+}
+
+impl Release {
+    pub fn proteins(&self) -> impl Iterator<Item = &SimRecord> {
+        self.records.iter().filter(|r| r.molecule.is_protein())
+    }
+
+    pub fn nucleotides(&self) -> impl Iterator<Item = &SimRecord> {
+        self.records.iter().filter(|r| !r.molecule.is_protein())
+    }
+
+    pub fn record(&self, accession: &str) -> Option<&SimRecord> {
+        self.records.iter().find(|r| r.accession == accession)
+    }
+
+    /// Proteins with UniProtKB headers (`OX=` carries the taxon)
+    pub fn uniprot_fasta(&self) -> String {
+        fasta(self.proteins(), |r| r.uniprot_header(&self.taxonomy))
+    }
+
+    /// Proteins with RefSeq headers; taxa are in `prot.accession2taxid`
+    pub fn ncbi_protein_fasta(&self) -> String {
+        fasta(self.proteins(), |r| r.ncbi_header(&self.taxonomy))
+    }
+
+    /// Nucleotides with GenBank headers; taxa are in `nucl_gb.accession2taxid`
+    pub fn ncbi_nucleotide_fasta(&self) -> String {
+        fasta(self.nucleotides(), |r| r.ncbi_header(&self.taxonomy))
+    }
+
+    pub fn prot_accession2taxid(&self) -> String {
+        accession2taxid(self.proteins())
+    }
+
+    pub fn nucl_accession2taxid(&self) -> String {
+        accession2taxid(self.nucleotides())
+    }
+
+    /// Write every output of the release into `dir`
+    pub fn write_to(&self, dir: &Path) -> Result<ReleaseFiles> {
+        let taxonomy_dir = dir.join("taxonomy");
+        fs::create_dir_all(&taxonomy_dir)
+            .with_context(|| format!("Failed to create {}", taxonomy_dir.display()))?;
+
+        let files = ReleaseFiles {
+            uniprot_fasta: dir.join("uniprot_sprot.fasta"),
+            ncbi_protein_fasta: dir.join("ncbi_protein.fasta"),
+            ncbi_nucleotide_fasta: dir.join("ncbi_nucleotide.fasta"),
+            prot_accession2taxid: dir.join("prot.accession2taxid"),
+            nucl_accession2taxid: dir.join("nucl_gb.accession2taxid"),
+            changes: dir.join("changes.tsv"),
+            taxonomy_dir,
+        };
+
+        let outputs = [
+            (&files.uniprot_fasta, self.uniprot_fasta()),
+            (&files.ncbi_protein_fasta, self.ncbi_protein_fasta()),
+            (&files.ncbi_nucleotide_fasta, self.ncbi_nucleotide_fasta()),
+            (&files.prot_accession2taxid, self.prot_accession2taxid()),
+            (&files.nucl_accession2taxid, self.nucl_accession2taxid()),
+            (&files.changes, self.changes.to_tsv()),
+            (
+                &files.taxonomy_dir.join("nodes.dmp"),
+                self.taxonomy.nodes_dmp(),
+            ),
+            (
+                &files.taxonomy_dir.join("names.dmp"),
+                self.taxonomy.names_dmp(),
+            ),
+            (
+                &files.taxonomy_dir.join("merged.dmp"),
+                self.taxonomy.merged_dmp(),
+            ),
+            (
+                &files.taxonomy_dir.join("delnodes.dmp"),
+                self.taxonomy.delnodes_dmp(),
+            ),
+        ];
+        for (path, content) in outputs {
+            fs::write(path, content)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+        }
+        Ok(files)
+    }
+}
+
+fn fasta<'a>(
+    records: impl Iterator<Item = &'a SimRecord>,
+    header: impl Fn(&SimRecord) -> String,
+) -> String {
+    let mut out = String::new();
+    for record in records {
+        writeln!(out, ">{}", header(record)).unwrap();
+        for line in record.sequence.as_bytes().chunks(LINE_WIDTH) {
+            out.push_str(std::str::from_utf8(line).unwrap());
+            out.push('\n');
+        }
+    }
+    out
+}
+
+fn accession2taxid<'a>(records: impl Iterator<Item = &'a SimRecord>) -> String {
+    let mut out = String::from("accession\taccession.version\ttaxid\tgi\n");
+    for record in records {
+        writeln!(
+            out,
+            "{}\t{}.{}\t{}\t0",
+            record.ncbi_accession, record.ncbi_accession, record.version, record.taxon_id.0
+        )
+        .unwrap();
+    }
+    out
+}
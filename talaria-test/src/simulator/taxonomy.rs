@@ -0,0 +1,287 @@
+//! Random taxonomy trees with NCBI ranks, Latin-looking names and taxdump output
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fmt::Write;
+use talaria_core::types::TaxonId;
+
+/// Ranks below the root, top to bottom
+pub const RANKS: [&str; 7] = [
+    "superkingdom",
+    "phylum",
+    "class",
+    "order",
+    "family",
+    "genus",
+    "species",
+];
+
+pub const ROOT: TaxonId = TaxonId(1);
+
+/// First ID handed out below the root
+const FIRST_TAXON_ID: u32 = 1000;
+
+const ONSETS: &[&str] = &[
+    "b", "c", "d", "f", "g", "l", "m", "n", "p", "r", "s", "t", "v", "x", "z", "ch", "ph", "th",
+    "br", "cr", "st", "tr",
+];
+const VOWELS: &[&str] = &["a", "e", "i", "o", "u", "ae", "ia", "io", "ou"];
+
+/// A node of the simulated taxonomy
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimTaxon {
+    pub id: TaxonId,
+    /// The root is its own parent, as in `nodes.dmp`
+    pub parent: TaxonId,
+    pub rank: &'static str,
+    pub name: String,
+}
+
+/// Taxonomy of one release, with NCBI's cumulative merged and deleted lists
+#[derive(Debug, Clone)]
+pub struct SimTaxonomy {
+    taxa: BTreeMap<TaxonId, SimTaxon>,
+    merged: BTreeMap<TaxonId, TaxonId>,
+    deleted: BTreeSet<TaxonId>,
+    next_id: u32,
+}
+
+impl SimTaxonomy {
+    /// Random tree in which every node below the root has 1..=`max_children` children
+    pub(crate) fn generate(rng: &mut StdRng, max_children: usize) -> Self {
+        let mut taxonomy = Self {
+            taxa: BTreeMap::new(),
+            merged: BTreeMap::new(),
+            deleted: BTreeSet::new(),
+            next_id: FIRST_TAXON_ID,
+        };
+        taxonomy.taxa.insert(
+            ROOT,
+            SimTaxon {
+                id: ROOT,
+                parent: ROOT,
+                rank: "no rank",
+                name: "root".to_string(),
+            },
+        );
+
+        let mut level = vec![ROOT];
+        for rank in RANKS {
+            let mut next = Vec::new();
+            for parent in level {
+                for _ in 0..rng.gen_range(1..=max_children.max(1)) {
+                    next.push(taxonomy.add(rng, parent, rank));
+                }
+            }
+            level = next;
+        }
+        taxonomy
+    }
+
+    pub fn get(&self, id: TaxonId) -> Option<&SimTaxon> {
+        self.taxa.get(&id)
+    }
+
+    pub fn contains(&self, id: TaxonId) -> bool {
+        self.taxa.contains_key(&id)
+    }
+
+    /// Number of taxa, root included
+    pub fn len(&self) -> usize {
+        self.taxa.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.taxa.is_empty()
+    }
+
+    pub fn taxa(&self) -> impl Iterator<Item = &SimTaxon> {
+        self.taxa.values()
+    }
+
+    /// Taxa of one rank in ID order
+    pub fn at_rank(&self, rank: &str) -> Vec<TaxonId> {
+        self.taxa
+            .values()
+            .filter(|taxon| taxon.rank == rank)
+            .map(|taxon| taxon.id)
+            .collect()
+    }
+
+    pub fn species(&self) -> Vec<TaxonId> {
+        self.at_rank("species")
+    }
+
+    pub fn children(&self, id: TaxonId) -> Vec<TaxonId> {
+        self.taxa
+            .values()
+            .filter(|taxon| taxon.parent == id && taxon.id != id)
+            .map(|taxon| taxon.id)
+            .collect()
+    }
+
+    /// Lineage from the root down to `id`, empty for unknown taxa
+    pub fn lineage(&self, id: TaxonId) -> Vec<TaxonId> {
+        let mut lineage = Vec::new();
+        let mut current = id;
+        while let Some(taxon) = self.taxa.get(&current) {
+            lineage.push(current);
+            if taxon.parent == current {
+                break;
+            }
+            current = taxon.parent;
+        }
+        lineage.reverse();
+        lineage
+    }
+
+    /// Ancestor of `id` at `rank`
+    pub fn ancestor_at(&self, id: TaxonId, rank: &str) -> Option<TaxonId> {
+        self.lineage(id)
+            .into_iter()
+            .find(|ancestor| self.taxa[ancestor].rank == rank)
+    }
+
+    /// Current ID of a taxon, following merges
+    pub fn resolve(&self, id: TaxonId) -> Option<TaxonId> {
+        if self.contains(id) {
+            return Some(id);
+        }
+        self.merged.get(&id).copied()
+    }
+
+    /// Every taxon ever merged away, mapped to the taxon it lives on in
+    pub fn merged(&self) -> &BTreeMap<TaxonId, TaxonId> {
+        &self.merged
+    }
+
+    /// Every taxon ever deleted
+    pub fn deleted(&self) -> &BTreeSet<TaxonId> {
+        &self.deleted
+    }
+
+    /// Species names are binomials whose genus part is the genus name
+    pub(crate) fn add_species(&mut self, rng: &mut StdRng, genus: TaxonId) -> TaxonId {
+        self.add(rng, genus, "species")
+    }
+
+    /// Merge `from` into `into`; earlier merges into `from` follow along
+    pub(crate) fn merge(&mut self, from: TaxonId, into: TaxonId) {
+        for child in self.children(from) {
+            self.taxa.get_mut(&child).expect("child exists").parent = into;
+        }
+        self.taxa.remove(&from);
+        for target in self.merged.values_mut() {
+            if *target == from {
+                *target = into;
+            }
+        }
+        self.merged.insert(from, into);
+    }
+
+    /// Delete a leaf taxon
+    pub(crate) fn delete(&mut self, id: TaxonId) {
+        debug_assert!(self.children(id).is_empty());
+        self.taxa.remove(&id);
+        self.deleted.insert(id);
+    }
+
+    pub(crate) fn reparent(&mut self, id: TaxonId, parent: TaxonId) {
+        self.taxa.get_mut(&id).expect("taxon exists").parent = parent;
+    }
+
+    fn add(&mut self, rng: &mut StdRng, parent: TaxonId, rank: &'static str) -> TaxonId {
+        let used: HashSet<&str> = self.taxa.values().map(|t| t.name.as_str()).collect();
+        let name = loop {
+            let name = match rank {
+                "species" => format!("{} {}", self.taxa[&parent].name, latin_word(rng, 2, "")),
+                "genus" => capitalize(&latin_word(rng, 3, "")),
+                "family" => capitalize(&latin_word(rng, 2, "aceae")),
+                "order" => capitalize(&latin_word(rng, 2, "ales")),
+                "class" => capitalize(&latin_word(rng, 2, "ia")),
+                "phylum" => capitalize(&latin_word(rng, 2, "ota")),
+                _ => capitalize(&latin_word(rng, 3, "a")),
+            };
+            if !used.contains(name.as_str()) {
+                break name;
+            }
+        };
+
+        let id = TaxonId(self.next_id);
+        self.next_id += 1;
+        self.taxa.insert(
+            id,
+            SimTaxon {
+                id,
+                parent,
+                rank,
+                name,
+            },
+        );
+        id
+    }
+
+    /// `nodes.dmp`: tax_id, parent tax_id, rank, EMBL code, division
+    pub fn nodes_dmp(&self) -> String {
+        let mut out = String::new();
+        for taxon in self.taxa.values() {
+            writeln!(
+                out,
+                "{}\t|\t{}\t|\t{}\t|\t\t|\t0\t|",
+                taxon.id.0, taxon.parent.0, taxon.rank
+            )
+            .unwrap();
+        }
+        out
+    }
+
+    /// `names.dmp` with one scientific name per taxon
+    pub fn names_dmp(&self) -> String {
+        let mut out = String::new();
+        for taxon in self.taxa.values() {
+            writeln!(
+                out,
+                "{}\t|\t{}\t|\t\t|\tscientific name\t|",
+                taxon.id.0, taxon.name
+            )
+            .unwrap();
+        }
+        out
+    }
+
+    pub fn merged_dmp(&self) -> String {
+        let mut out = String::new();
+        for (from, into) in &self.merged {
+            writeln!(out, "{}\t|\t{}\t|", from.0, into.0).unwrap();
+        }
+        out
+    }
+
+    pub fn delnodes_dmp(&self) -> String {
+        let mut out = String::new();
+        for id in &self.deleted {
+            writeln!(out, "{}\t|", id.0).unwrap();
+        }
+        out
+    }
+}
+
+fn latin_word(rng: &mut StdRng, syllables: usize, suffix: &str) -> String {
+    let mut word = String::new();
+    for _ in 0..syllables {
+        word.push_str(ONSETS.choose(rng).unwrap());
+        word.push_str(VOWELS.choose(rng).unwrap());
+    }
+    word.push_str(suffix);
+    word
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
@@ -5,12 +5,14 @@
 use crate::fixtures::test_database_source;
 use crate::mock::InMemoryStorageBackend;
 use crate::TestEnvironment;
-use anyhow::{Context, Result};
+use anyhow::Result;
 use chrono::Utc;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use talaria_core::types::{SHA256Hash, SequenceType};
-use talaria_storage::types::{CanonicalSequence, SequenceRepresentation, SequenceRepresentations};
+use talaria_storage::types::{
+    CanonicalSequence, SequenceRepresentation, SequenceRepresentations, SequenceStorageBackend,
+};
 
 /// Test storage wrapper with helpers
 ///
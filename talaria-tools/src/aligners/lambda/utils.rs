@@ -165,8 +165,9 @@ pub(crate) fn stream_output_with_progress<R: Read + Send + 'static>(
                                                     .trim_matches(|c: char| !c.is_ascii_digit())
                                                     .parse::<usize>(),
                                             ) {
-                                                if total > 0 {
-                                                    let pct = (current * 100) / total;
+                                                if let Some(pct) =
+                                                    (current * 100).checked_div(total)
+                                                {
                                                     progress_counter.store(pct, Ordering::Relaxed);
                                                     if let Some(ref pb) = progress_bar {
                                                         pb.set_position(pct as u64);
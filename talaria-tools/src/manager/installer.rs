@@ -116,7 +116,7 @@ impl ToolManager {
             }
         }
 
-        versions.sort_by(|a, b| b.installed_date.cmp(&a.installed_date));
+        versions.sort_by_key(|b| std::cmp::Reverse(b.installed_date));
         Ok(versions)
     }
 
@@ -557,7 +557,7 @@ impl VersionManager {
         }
 
         // Sort by creation date (newest first)
-        versions.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        versions.sort_by_key(|b| std::cmp::Reverse(b.created_at));
 
         Ok(versions)
     }
@@ -94,6 +94,13 @@ impl Section {
             content: SectionContent::Text(text.into()),
         }
     }
+
+    pub fn chart(title: impl Into<String>, chart: ChartData) -> Self {
+        Self {
+            title: title.into(),
+            content: SectionContent::Chart(chart),
+        }
+    }
 }
 
 /// Content types for report sections
@@ -268,6 +275,69 @@ pub struct ChartData {
     pub datasets: Vec<Dataset>,
 }
 
+impl ChartData {
+    pub fn new(chart_type: ChartType, title: impl Into<String>, labels: Vec<String>) -> Self {
+        Self {
+            chart_type,
+            title: title.into(),
+            labels,
+            datasets: Vec::new(),
+        }
+    }
+
+    pub fn with_dataset(mut self, label: impl Into<String>, data: Vec<f64>) -> Self {
+        self.datasets.push(Dataset {
+            label: label.into(),
+            data,
+            color: None,
+        });
+        self
+    }
+
+    /// Histogram of raw values in `bins` equal-width bins
+    pub fn histogram(title: impl Into<String>, values: &[f64], bins: usize) -> Self {
+        if values.is_empty() {
+            return Self::new(ChartType::Histogram, title, Vec::new());
+        }
+        let bins = bins.max(1);
+        let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+
+        let width = if max > min {
+            (max - min) / bins as f64
+        } else {
+            1.0
+        };
+        let mut counts = vec![0.0; bins];
+        for value in values {
+            let bin = (((value - min) / width) as usize).min(bins - 1);
+            counts[bin] += 1.0;
+        }
+        let labels = (0..bins)
+            .map(|i| {
+                let lower = min + width * i as f64;
+                format!("{:.0}–{:.0}", lower, lower + width)
+            })
+            .collect();
+
+        Self::new(ChartType::Histogram, title, labels).with_dataset("Count", counts)
+    }
+
+    /// Sunburst of lineages, each given root first with the count at its end
+    pub fn sunburst(title: impl Into<String>, lineages: &[(Vec<String>, f64)]) -> Self {
+        let labels = lineages
+            .iter()
+            .map(|(lineage, _)| lineage.join(LINEAGE_SEPARATOR))
+            .collect();
+        let values = lineages.iter().map(|(_, value)| *value).collect();
+
+        Self::new(ChartType::Sunburst, title, labels).with_dataset("Sequences", values)
+    }
+}
+
+/// Separates ranks in the labels of a `ChartType::Sunburst` chart
+pub const LINEAGE_SEPARATOR: &str = "; ";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Dataset {
     pub label: String,
@@ -281,6 +351,10 @@ pub enum ChartType {
     Line,
     Pie,
     Doughnut,
+    /// Bars of adjacent bins, first dataset only
+    Histogram,
+    /// Nested rings; labels are lineages joined by `LINEAGE_SEPARATOR`
+    Sunburst,
 }
 
 /// Trait for types that can be converted to reports
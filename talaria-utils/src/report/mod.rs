@@ -18,6 +18,7 @@ pub mod types;
 pub use core::{
     Cell, CellStyle, ChangeDirection, ChartData, ChartType, Dataset, Metric, MetricChange,
     MetricSeverity, Report, ReportBuilder, Reportable, Section, SectionContent, Table,
+    LINEAGE_SEPARATOR,
 };
 
 // Re-export specific types
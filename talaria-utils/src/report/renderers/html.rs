@@ -1,4 +1,8 @@
 /// Generic HTML renderer for Report type
+///
+/// The output is one self-contained file: styles, SVG charts and the small
+/// table-sorting script are all inline, so reports open offline.
+use super::svg::render_svg;
 use crate::report::core::{
     Cell, CellStyle, ChangeDirection, ChartData, Metric, MetricSeverity, Report, Section,
     SectionContent, Table,
@@ -194,6 +198,53 @@ pub fn render_html(report: &Report) -> Result<String> {
             margin-top: 20px;
             text-align: right;
         }}
+        details.section > summary {{
+            cursor: pointer;
+            list-style-position: outside;
+            margin-left: 18px;
+        }}
+        details.section > summary h2 {{
+            display: inline-block;
+            margin-left: -4px;
+        }}
+        table.sortable th {{
+            cursor: pointer;
+            user-select: none;
+        }}
+        table.sortable th[aria-sort="ascending"]::after {{
+            content: " ▲";
+        }}
+        table.sortable th[aria-sort="descending"]::after {{
+            content: " ▼";
+        }}
+        .chart-figure {{
+            margin: 15px 0;
+        }}
+        .chart-figure figcaption {{
+            font-weight: 600;
+            color: #34495e;
+            margin-bottom: 8px;
+        }}
+        svg.chart {{
+            width: 100%;
+            max-width: 900px;
+            height: auto;
+            font-size: 12px;
+        }}
+        svg.chart .chart-axis,
+        svg.chart .chart-legend {{
+            fill: #34495e;
+        }}
+        svg.chart .chart-total {{
+            fill: #2c3e50;
+            font-size: 16px;
+            font-weight: bold;
+        }}
+        .chart-data summary {{
+            cursor: pointer;
+            color: #7f8c8d;
+            font-size: 0.9em;
+        }}
     </style>
 </head>
 <body>
@@ -230,13 +281,68 @@ pub fn render_html(report: &Report) -> Result<String> {
         report.timestamp.format("%Y-%m-%d %H:%M:%S UTC")
     ));
 
-    html.push_str("    </div>\n</body>\n</html>");
+    html.push_str("    </div>\n");
+    html.push_str(SORT_SCRIPT);
+    html.push_str("</body>\n</html>");
 
     Ok(html)
 }
 
+/// Click a column header to sort by it; "... and N more" rows stay last
+const SORT_SCRIPT: &str = r#"<script>
+(function () {
+    function sortKey(cell) {
+        var text = cell ? cell.textContent.trim() : "";
+        var number = parseFloat(text.replace(/[,%+]/g, ""));
+        return isNaN(number) ? text : number;
+    }
+    function compare(a, b) {
+        if (typeof a === "number" && typeof b === "number") {
+            return a - b;
+        }
+        if (typeof a === "number") {
+            return -1;
+        }
+        if (typeof b === "number") {
+            return 1;
+        }
+        return a.localeCompare(b);
+    }
+    document.querySelectorAll("table.sortable th").forEach(function (th) {
+        th.title = "Sort";
+        th.addEventListener("click", function () {
+            var table = th.closest("table");
+            var body = table.tBodies[0];
+            var index = Array.prototype.indexOf.call(th.parentNode.children, th);
+            var ascending = th.getAttribute("aria-sort") !== "ascending";
+            table.querySelectorAll("th").forEach(function (other) {
+                other.removeAttribute("aria-sort");
+            });
+            th.setAttribute("aria-sort", ascending ? "ascending" : "descending");
+
+            var rows = Array.prototype.slice.call(body.rows);
+            var pinned = rows.filter(function (row) {
+                return row.cells[0] && row.cells[0].classList.contains("cell-muted");
+            });
+            rows = rows.filter(function (row) {
+                return pinned.indexOf(row) < 0;
+            });
+            rows.sort(function (a, b) {
+                var order = compare(sortKey(a.cells[index]), sortKey(b.cells[index]));
+                return ascending ? order : -order;
+            });
+            rows.concat(pinned).forEach(function (row) {
+                body.appendChild(row);
+            });
+        });
+    });
+})();
+</script>
+"#;
+
 fn render_section(html: &mut String, section: &Section) -> Result<()> {
-    html.push_str(&format!("<h2>{}</h2>\n", section.title));
+    html.push_str("<details class=\"section\" open>\n");
+    html.push_str(&format!("<summary><h2>{}</h2></summary>\n", section.title));
 
     match &section.content {
         SectionContent::Metrics(metrics) => render_metrics(html, metrics),
@@ -247,6 +353,7 @@ fn render_section(html: &mut String, section: &Section) -> Result<()> {
         SectionContent::Text(text) => render_text(html, text),
     }
 
+    html.push_str("</details>\n");
     Ok(())
 }
 
@@ -296,7 +403,7 @@ fn render_metrics(html: &mut String, metrics: &[Metric]) {
 }
 
 fn render_table(html: &mut String, table: &Table) {
-    html.push_str("<table>\n");
+    html.push_str("<table class=\"sortable\">\n");
 
     // Headers
     html.push_str("  <thead>\n    <tr>\n");
@@ -359,11 +466,13 @@ fn render_bullet_list(html: &mut String, items: &[String]) {
 }
 
 fn render_chart(html: &mut String, chart: &ChartData) {
-    // For now, render chart data as a simple table
-    // In the future, this could use Chart.js or similar for actual charts
-    html.push_str("<div class=\"chart-placeholder\">\n");
-    html.push_str(&format!("<h3>{}</h3>\n", chart.title));
-    html.push_str("<table>\n");
+    html.push_str("<figure class=\"chart-figure\">\n");
+    html.push_str(&format!("<figcaption>{}</figcaption>\n", chart.title));
+    html.push_str(&render_svg(chart));
+
+    // The numbers behind the chart, folded away
+    html.push_str("<details class=\"chart-data\">\n<summary>Data</summary>\n");
+    html.push_str("<table class=\"sortable\">\n");
 
     // Headers
     html.push_str("  <thead>\n    <tr>\n");
@@ -391,7 +500,8 @@ fn render_chart(html: &mut String, chart: &ChartData) {
     html.push_str("  </tbody>\n");
 
     html.push_str("</table>\n");
-    html.push_str("</div>\n");
+    html.push_str("</details>\n");
+    html.push_str("</figure>\n");
 }
 
 fn render_text(html: &mut String, text: &str) {
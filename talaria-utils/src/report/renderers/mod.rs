@@ -5,6 +5,7 @@ pub mod csv;
 /// reports from any command that implements `Reportable`.
 pub mod html;
 pub mod json;
+pub mod svg;
 pub mod text;
 
 pub use csv::render_csv;
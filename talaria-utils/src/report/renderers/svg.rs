@@ -0,0 +1,677 @@
+/// Inline SVG charts for the HTML renderer
+///
+/// Charts are drawn when the report is rendered, so an HTML report stays a
+/// single file that opens offline. Hovering a bar, point or slice shows its
+/// value through the SVG `<title>` element; no scripts are involved.
+use crate::report::core::{ChartData, ChartType, LINEAGE_SEPARATOR};
+use std::f64::consts::{FRAC_PI_2, TAU};
+use std::fmt::Write;
+
+const WIDTH: f64 = 720.0;
+const HEIGHT: f64 = 360.0;
+const MARGIN_LEFT: f64 = 64.0;
+const MARGIN_RIGHT: f64 = 20.0;
+const MARGIN_TOP: f64 = 20.0;
+const MARGIN_BOTTOM: f64 = 64.0;
+/// Most x-axis labels drawn before labels are thinned out
+const MAX_AXIS_LABELS: usize = 12;
+/// Slices shown in a pie before the rest are merged into "Other"
+const MAX_SLICES: usize = 9;
+/// Rings drawn in a sunburst; deeper ranks are left out
+const MAX_RINGS: usize = 6;
+
+const PALETTE: [&str; 10] = [
+    "#3498db", "#27ae60", "#e67e22", "#9b59b6", "#e74c3c", "#1abc9c", "#f39c12", "#34495e",
+    "#7f8c8d", "#c0392b",
+];
+
+/// Render a chart as an `<svg>` element
+pub fn render_svg(chart: &ChartData) -> String {
+    if chart.labels.is_empty() || chart.datasets.is_empty() {
+        return "<p class=\"chart-empty\">No data</p>\n".to_string();
+    }
+
+    match chart.chart_type {
+        ChartType::Bar => render_bars(chart, false),
+        ChartType::Histogram => render_bars(chart, true),
+        ChartType::Line => render_lines(chart),
+        ChartType::Pie => render_pie(chart, 0.0),
+        ChartType::Doughnut => render_pie(chart, 0.55),
+        ChartType::Sunburst => render_sunburst(chart),
+    }
+}
+
+fn render_bars(chart: &ChartData, histogram: bool) -> String {
+    // A histogram has one series; extra datasets would overlap its bins
+    let datasets = if histogram {
+        &chart.datasets[..1]
+    } else {
+        &chart.datasets[..]
+    };
+    let y_max = axis_max(datasets.iter().flat_map(|d| d.data.iter().copied()));
+    let mut svg = open_svg(&chart.title, WIDTH, HEIGHT);
+    draw_axes(&mut svg, &chart.labels, y_max);
+
+    let (plot_width, plot_height) = plot_size();
+    let group = plot_width / chart.labels.len() as f64;
+    let (padding, bar) = if histogram {
+        (0.5, group - 1.0)
+    } else {
+        (group * 0.1, group * 0.8 / datasets.len() as f64)
+    };
+
+    for (d, dataset) in datasets.iter().enumerate() {
+        let color = series_color(dataset.color.as_deref(), d);
+        for (i, label) in chart.labels.iter().enumerate() {
+            let value = dataset.data.get(i).copied().unwrap_or(0.0).max(0.0);
+            let height = value / y_max * plot_height;
+            let x = MARGIN_LEFT + group * i as f64 + padding + bar * d as f64;
+            let y = MARGIN_TOP + plot_height - height;
+            let _ = writeln!(
+                svg,
+                r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" fill="{}"><title>{}</title></rect>"#,
+                x,
+                y,
+                bar.max(1.0),
+                height,
+                color,
+                escape(&point_title(label, &dataset.label, value, datasets.len()))
+            );
+        }
+    }
+
+    if datasets.len() > 1 {
+        draw_series_legend(
+            &mut svg,
+            datasets.iter().map(|d| (&d.label, d.color.as_deref())),
+        );
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn render_lines(chart: &ChartData) -> String {
+    let y_max = axis_max(chart.datasets.iter().flat_map(|d| d.data.iter().copied()));
+    let mut svg = open_svg(&chart.title, WIDTH, HEIGHT);
+    draw_axes(&mut svg, &chart.labels, y_max);
+
+    let (plot_width, plot_height) = plot_size();
+    let step = plot_width / chart.labels.len() as f64;
+    for (d, dataset) in chart.datasets.iter().enumerate() {
+        let color = series_color(dataset.color.as_deref(), d);
+        let points: Vec<(f64, f64, f64)> = dataset
+            .data
+            .iter()
+            .take(chart.labels.len())
+            .enumerate()
+            .map(|(i, value)| {
+                let x = MARGIN_LEFT + step * (i as f64 + 0.5);
+                let y = MARGIN_TOP + plot_height - value.max(0.0) / y_max * plot_height;
+                (x, y, *value)
+            })
+            .collect();
+
+        let path: Vec<String> = points
+            .iter()
+            .map(|(x, y, _)| format!("{:.1},{:.1}", x, y))
+            .collect();
+        let _ = writeln!(
+            svg,
+            r#"<polyline points="{}" fill="none" stroke="{}" stroke-width="2"/>"#,
+            path.join(" "),
+            color
+        );
+        for ((x, y, value), label) in points.iter().zip(&chart.labels) {
+            let _ = writeln!(
+                svg,
+                r#"<circle cx="{:.1}" cy="{:.1}" r="3.5" fill="{}"><title>{}</title></circle>"#,
+                x,
+                y,
+                color,
+                escape(&point_title(
+                    label,
+                    &dataset.label,
+                    *value,
+                    chart.datasets.len()
+                ))
+            );
+        }
+    }
+
+    if chart.datasets.len() > 1 {
+        draw_series_legend(
+            &mut svg,
+            chart
+                .datasets
+                .iter()
+                .map(|d| (&d.label, d.color.as_deref())),
+        );
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Pie of the first dataset; `hole` is the inner radius as a fraction
+fn render_pie(chart: &ChartData, hole: f64) -> String {
+    let mut slices: Vec<(String, f64)> = chart
+        .labels
+        .iter()
+        .zip(&chart.datasets[0].data)
+        .filter(|(_, value)| **value > 0.0)
+        .map(|(label, value)| (label.clone(), *value))
+        .collect();
+    slices.sort_by(|a, b| b.1.total_cmp(&a.1));
+    if slices.len() > MAX_SLICES + 1 {
+        let other: f64 = slices.drain(MAX_SLICES..).map(|(_, value)| value).sum();
+        slices.push(("Other".to_string(), other));
+    }
+    let total: f64 = slices.iter().map(|(_, value)| value).sum();
+
+    let mut svg = open_svg(&chart.title, WIDTH, HEIGHT);
+    if total <= 0.0 {
+        svg.push_str("</svg>\n");
+        return svg;
+    }
+
+    let (cx, cy, radius) = (HEIGHT / 2.0, HEIGHT / 2.0, HEIGHT / 2.0 - MARGIN_TOP);
+    let mut angle = -FRAC_PI_2;
+    let mut legend = Vec::new();
+    for (i, (label, value)) in slices.iter().enumerate() {
+        let sweep = value / total * TAU;
+        let color = PALETTE[i % PALETTE.len()];
+        let share = value / total * 100.0;
+        let _ = writeln!(
+            svg,
+            r#"<path d="{}" fill="{}" stroke="white" stroke-width="1"><title>{}: {} ({:.1}%)</title></path>"#,
+            sector(cx, cy, radius * hole, radius, angle, angle + sweep),
+            color,
+            escape(label),
+            format_exact(*value),
+            share
+        );
+        legend.push((format!("{} ({:.1}%)", label, share), color));
+        angle += sweep;
+    }
+    if hole > 0.0 {
+        let _ = writeln!(
+            svg,
+            r#"<text x="{:.1}" y="{:.1}" text-anchor="middle" class="chart-total">{}</text>"#,
+            cx,
+            cy + 5.0,
+            format_compact(total)
+        );
+    }
+
+    draw_side_legend(&mut svg, &legend, HEIGHT + 20.0);
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// A node of the lineage tree behind a sunburst
+struct SunburstNode {
+    name: String,
+    value: f64,
+    children: Vec<usize>,
+}
+
+fn render_sunburst(chart: &ChartData) -> String {
+    // Node 0 is the root; every node's value includes its descendants
+    let mut nodes = vec![SunburstNode {
+        name: String::new(),
+        value: 0.0,
+        children: Vec::new(),
+    }];
+    for (label, value) in chart.labels.iter().zip(&chart.datasets[0].data) {
+        if *value <= 0.0 {
+            continue;
+        }
+        let mut current = 0;
+        nodes[0].value += value;
+        for name in label.split(LINEAGE_SEPARATOR).take(MAX_RINGS) {
+            let existing = nodes[current]
+                .children
+                .iter()
+                .copied()
+                .find(|&child| nodes[child].name == name);
+            current = match existing {
+                Some(child) => child,
+                None => {
+                    nodes.push(SunburstNode {
+                        name: name.to_string(),
+                        value: 0.0,
+                        children: Vec::new(),
+                    });
+                    let child = nodes.len() - 1;
+                    nodes[current].children.push(child);
+                    child
+                }
+            };
+            nodes[current].value += value;
+        }
+    }
+    for node in 0..nodes.len() {
+        let mut children = std::mem::take(&mut nodes[node].children);
+        children.sort_by(|a, b| nodes[*b].value.total_cmp(&nodes[*a].value));
+        nodes[node].children = children;
+    }
+
+    let mut svg = open_svg(&chart.title, WIDTH, HEIGHT);
+    let total = nodes[0].value;
+    if total <= 0.0 {
+        svg.push_str("</svg>\n");
+        return svg;
+    }
+
+    let depth = tree_depth(&nodes, 0).max(1);
+    let (cx, cy) = (HEIGHT / 2.0, HEIGHT / 2.0);
+    let center = 36.0;
+    let ring = (HEIGHT / 2.0 - MARGIN_TOP - center) / depth as f64;
+
+    let mut legend = Vec::new();
+    let mut angle = -FRAC_PI_2;
+    for (i, &child) in nodes[0].children.iter().enumerate() {
+        let color = PALETTE[i % PALETTE.len()];
+        let sweep = nodes[child].value / total * TAU;
+        let arcs = SunburstArcs {
+            nodes: &nodes,
+            total,
+            cx,
+            cy,
+            center,
+            ring,
+            color,
+        };
+        arcs.draw(&mut svg, child, 1, angle, sweep, &nodes[child].name);
+        if i < MAX_SLICES {
+            legend.push((nodes[child].name.clone(), color));
+        }
+        angle += sweep;
+    }
+    let _ = writeln!(
+        svg,
+        r#"<text x="{:.1}" y="{:.1}" text-anchor="middle" class="chart-total">{}</text>"#,
+        cx,
+        cy + 5.0,
+        format_compact(total)
+    );
+
+    draw_side_legend(&mut svg, &legend, HEIGHT + 20.0);
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Drawing state shared by the arcs under one top-level node
+struct SunburstArcs<'a> {
+    nodes: &'a [SunburstNode],
+    total: f64,
+    cx: f64,
+    cy: f64,
+    center: f64,
+    ring: f64,
+    color: &'a str,
+}
+
+impl SunburstArcs<'_> {
+    fn draw(
+        &self,
+        svg: &mut String,
+        node: usize,
+        depth: usize,
+        start: f64,
+        sweep: f64,
+        lineage: &str,
+    ) {
+        // Arcs too thin to see or hover are skipped along with their children
+        if sweep < 0.002 {
+            return;
+        }
+        let inner = self.center + self.ring * (depth - 1) as f64;
+        let value = self.nodes[node].value;
+        let _ = writeln!(
+            svg,
+            r#"<path d="{}" fill="{}" fill-opacity="{:.2}" stroke="white" stroke-width="1"><title>{}: {} ({:.1}%)</title></path>"#,
+            sector(
+                self.cx,
+                self.cy,
+                inner,
+                inner + self.ring,
+                start,
+                start + sweep
+            ),
+            self.color,
+            (1.0 - 0.13 * (depth - 1) as f64).max(0.3),
+            escape(lineage),
+            format_exact(value),
+            value / self.total * 100.0
+        );
+
+        let mut angle = start;
+        for &child in &self.nodes[node].children {
+            let child_sweep = self.nodes[child].value / value * sweep;
+            let child_lineage = format!("{} › {}", lineage, self.nodes[child].name);
+            self.draw(svg, child, depth + 1, angle, child_sweep, &child_lineage);
+            angle += child_sweep;
+        }
+    }
+}
+
+fn tree_depth(nodes: &[SunburstNode], node: usize) -> usize {
+    nodes[node]
+        .children
+        .iter()
+        .map(|&child| 1 + tree_depth(nodes, child))
+        .max()
+        .unwrap_or(0)
+}
+
+/// SVG path of an annular sector; `inner` of zero gives a pie slice
+fn sector(cx: f64, cy: f64, inner: f64, outer: f64, start: f64, end: f64) -> String {
+    // A single arc cannot close a full circle, so split it in two
+    if end - start >= TAU - 1e-9 {
+        let middle = start + TAU / 2.0;
+        return format!(
+            "{} {}",
+            sector(cx, cy, inner, outer, start, middle),
+            sector(cx, cy, inner, outer, middle, start + TAU)
+        );
+    }
+
+    let point = |radius: f64, angle: f64| (cx + radius * angle.cos(), cy + radius * angle.sin());
+    let large = if end - start > TAU / 2.0 { 1 } else { 0 };
+    let (x0, y0) = point(outer, start);
+    let (x1, y1) = point(outer, end);
+    let mut path = format!(
+        "M{:.2},{:.2} A{:.2},{:.2} 0 {} 1 {:.2},{:.2}",
+        x0, y0, outer, outer, large, x1, y1
+    );
+    if inner > 0.0 {
+        let (x2, y2) = point(inner, end);
+        let (x3, y3) = point(inner, start);
+        let _ = write!(
+            path,
+            " L{:.2},{:.2} A{:.2},{:.2} 0 {} 0 {:.2},{:.2} Z",
+            x2, y2, inner, inner, large, x3, y3
+        );
+    } else {
+        let _ = write!(path, " L{:.2},{:.2} Z", cx, cy);
+    }
+    path
+}
+
+fn open_svg(title: &str, width: f64, height: f64) -> String {
+    format!(
+        "<svg class=\"chart\" viewBox=\"0 0 {} {}\" xmlns=\"http://www.w3.org/2000/svg\" role=\"img\" aria-label=\"{}\">\n",
+        width,
+        height,
+        escape(title)
+    )
+}
+
+fn plot_size() -> (f64, f64) {
+    (
+        WIDTH - MARGIN_LEFT - MARGIN_RIGHT,
+        HEIGHT - MARGIN_TOP - MARGIN_BOTTOM,
+    )
+}
+
+/// Gridlines, y-axis ticks and (thinned) x-axis labels
+fn draw_axes(svg: &mut String, labels: &[String], y_max: f64) {
+    let (plot_width, plot_height) = plot_size();
+    let bottom = MARGIN_TOP + plot_height;
+
+    for tick in 0..=5 {
+        let value = y_max * tick as f64 / 5.0;
+        let y = bottom - plot_height * tick as f64 / 5.0;
+        let _ = writeln!(
+            svg,
+            r##"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="#ecf0f1"/>"##,
+            MARGIN_LEFT,
+            y,
+            MARGIN_LEFT + plot_width,
+            y
+        );
+        let _ = writeln!(
+            svg,
+            r#"<text x="{:.1}" y="{:.1}" text-anchor="end" class="chart-axis">{}</text>"#,
+            MARGIN_LEFT - 6.0,
+            y + 4.0,
+            format_compact(value)
+        );
+    }
+    let _ = writeln!(
+        svg,
+        r##"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="#95a5a6"/>"##,
+        MARGIN_LEFT,
+        bottom,
+        MARGIN_LEFT + plot_width,
+        bottom
+    );
+
+    let step = labels.len().div_ceil(MAX_AXIS_LABELS);
+    let slot = plot_width / labels.len() as f64;
+    for (i, label) in labels.iter().enumerate().step_by(step) {
+        let x = MARGIN_LEFT + slot * (i as f64 + 0.5);
+        let _ = writeln!(
+            svg,
+            r#"<text x="{:.1}" y="{:.1}" text-anchor="end" transform="rotate(-30 {:.1} {:.1})" class="chart-axis">{}</text>"#,
+            x,
+            bottom + 16.0,
+            x,
+            bottom + 16.0,
+            escape(&truncate(label, 18))
+        );
+    }
+}
+
+/// One row of series swatches along the bottom edge
+fn draw_series_legend<'a>(
+    svg: &mut String,
+    series: impl Iterator<Item = (&'a String, Option<&'a str>)>,
+) {
+    let mut x = MARGIN_LEFT;
+    let y = HEIGHT - 10.0;
+    for (i, (label, color)) in series.enumerate() {
+        let label = truncate(label, 24);
+        let _ = writeln!(
+            svg,
+            r#"<rect x="{:.1}" y="{:.1}" width="10" height="10" fill="{}"/><text x="{:.1}" y="{:.1}" class="chart-legend">{}</text>"#,
+            x,
+            y - 9.0,
+            series_color(color, i),
+            x + 14.0,
+            y,
+            escape(&label)
+        );
+        x += 24.0 + label.chars().count() as f64 * 7.0;
+    }
+}
+
+/// A column of swatches to the right of a round chart
+fn draw_side_legend(svg: &mut String, entries: &[(String, &str)], x: f64) {
+    let top = (HEIGHT - entries.len() as f64 * 22.0) / 2.0;
+    for (i, (label, color)) in entries.iter().enumerate() {
+        let y = top + i as f64 * 22.0;
+        let _ = writeln!(
+            svg,
+            r#"<rect x="{:.1}" y="{:.1}" width="12" height="12" fill="{}"/><text x="{:.1}" y="{:.1}" class="chart-legend">{}</text>"#,
+            x,
+            y,
+            color,
+            x + 18.0,
+            y + 11.0,
+            escape(&truncate(label, 40))
+        );
+    }
+}
+
+/// Colors come from report data, so they are escaped like any other text
+fn series_color(color: Option<&str>, index: usize) -> String {
+    color
+        .map(escape)
+        .unwrap_or_else(|| PALETTE[index % PALETTE.len()].to_string())
+}
+
+fn point_title(label: &str, series: &str, value: f64, series_count: usize) -> String {
+    if series_count > 1 {
+        format!("{} – {}: {}", label, series, format_exact(value))
+    } else {
+        format!("{}: {}", label, format_exact(value))
+    }
+}
+
+/// Round the largest value up to 1, 2 or 5 times a power of ten
+fn axis_max(values: impl Iterator<Item = f64>) -> f64 {
+    let max = values.fold(0.0, f64::max);
+    if max <= 0.0 {
+        return 1.0;
+    }
+    let magnitude = 10f64.powf(max.log10().floor());
+    let scaled = max / magnitude;
+    let nice = [1.0, 2.0, 5.0, 10.0]
+        .into_iter()
+        .find(|n| scaled <= *n)
+        .unwrap_or(10.0);
+    nice * magnitude
+}
+
+fn format_compact(value: f64) -> String {
+    let abs = value.abs();
+    if abs >= 1e9 {
+        format!("{:.1}G", value / 1e9)
+    } else if abs >= 1e6 {
+        format!("{:.1}M", value / 1e6)
+    } else if abs >= 1e4 {
+        format!("{:.1}k", value / 1e3)
+    } else {
+        format_exact(value)
+    }
+}
+
+fn format_exact(value: f64) -> String {
+    if value.fract() == 0.0 {
+        format!("{}", value as i64)
+    } else {
+        format!("{:.2}", value)
+    }
+}
+
+fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        text.to_string()
+    } else {
+        let kept: String = text.chars().take(max - 1).collect();
+        format!("{}…", kept)
+    }
+}
+
+/// Escape text for use in SVG/HTML content and attributes
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chart(chart_type: ChartType) -> ChartData {
+        ChartData::new(
+            chart_type,
+            "Test",
+            vec!["a".to_string(), "b<c".to_string(), "d".to_string()],
+        )
+        .with_dataset("one", vec![1.0, 2.0, 3.0])
+        .with_dataset("two", vec![3.0, 0.0, 1.5])
+    }
+
+    #[test]
+    fn test_every_chart_type_renders_svg() {
+        for chart_type in [
+            ChartType::Bar,
+            ChartType::Line,
+            ChartType::Pie,
+            ChartType::Doughnut,
+            ChartType::Histogram,
+            ChartType::Sunburst,
+        ] {
+            let svg = render_svg(&chart(chart_type));
+            assert!(svg.starts_with("<svg"), "{:?}", chart_type);
+            assert!(svg.trim_end().ends_with("</svg>"), "{:?}", chart_type);
+            assert!(
+                !svg.contains("b<c"),
+                "{:?} left a label unescaped",
+                chart_type
+            );
+            assert!(!svg.contains("NaN"), "{:?}", chart_type);
+        }
+    }
+
+    #[test]
+    fn test_bar_chart_draws_one_rect_per_value() {
+        let svg = render_svg(&chart(ChartType::Bar));
+        assert_eq!(svg.matches("<rect").count(), 6 + 2); // bars + legend swatches
+
+        let histogram = render_svg(&chart(ChartType::Histogram));
+        assert_eq!(histogram.matches("<rect").count(), 3);
+    }
+
+    #[test]
+    fn test_dataset_color_is_escaped() {
+        let mut chart = chart(ChartType::Line);
+        chart.datasets[0].color = Some("red\" onload=\"alert(1)".to_string());
+        let svg = render_svg(&chart);
+        assert!(!svg.contains("\" onload="));
+        assert!(svg.contains("red&quot; onload=&quot;alert(1)"));
+    }
+
+    #[test]
+    fn test_sunburst_nests_lineages() {
+        let chart = ChartData::sunburst(
+            "Taxa",
+            &[
+                (vec!["Bacteria".into(), "Proteobacteria".into()], 6.0),
+                (vec!["Bacteria".into(), "Firmicutes".into()], 2.0),
+                (vec!["Eukaryota".into()], 2.0),
+            ],
+        );
+        let svg = render_svg(&chart);
+        // Bacteria, Eukaryota, and the two phyla
+        assert_eq!(svg.matches("<path").count(), 4);
+        assert!(svg.contains("Bacteria: 8 (80.0%)"));
+        assert!(svg.contains("Bacteria › Proteobacteria: 6 (60.0%)"));
+    }
+
+    #[test]
+    fn test_full_circle_sector_is_closed() {
+        let chart =
+            ChartData::new(ChartType::Pie, "One", vec!["all".into()]).with_dataset("n", vec![5.0]);
+        let svg = render_svg(&chart);
+        assert_eq!(svg.matches(" A").count(), 2);
+        assert!(svg.contains("all: 5 (100.0%)"));
+    }
+
+    #[test]
+    fn test_histogram_bins_values() {
+        let chart = ChartData::histogram("Lengths", &[0.0, 1.0, 2.0, 9.0, 10.0], 2);
+        assert_eq!(chart.labels, vec!["0–5", "5–10"]);
+        assert_eq!(chart.datasets[0].data, vec![3.0, 2.0]);
+    }
+
+    #[test]
+    fn test_axis_max() {
+        assert_eq!(axis_max([0.0].into_iter()), 1.0);
+        assert_eq!(axis_max([3.0, 7.0].into_iter()), 10.0);
+        assert_eq!(axis_max([120.0].into_iter()), 200.0);
+        assert_eq!(axis_max([5.0].into_iter()), 5.0);
+    }
+}
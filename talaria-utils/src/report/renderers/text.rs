@@ -193,5 +193,7 @@ fn format_chart_type(chart_type: &crate::report::core::ChartType) -> &str {
         crate::report::core::ChartType::Line => "Line Chart",
         crate::report::core::ChartType::Pie => "Pie Chart",
         crate::report::core::ChartType::Doughnut => "Doughnut Chart",
+        crate::report::core::ChartType::Histogram => "Histogram",
+        crate::report::core::ChartType::Sunburst => "Sunburst",
     }
 }
@@ -2,7 +2,10 @@
 ///
 /// These types represent the results of comparing two sequence databases,
 /// tracking additions, removals, modifications, and statistical changes.
-use super::core::{Cell, CellStyle, Metric, MetricSeverity, Report, Reportable, Section, Table};
+use super::core::{
+    Cell, CellStyle, ChartData, ChartType, Metric, MetricSeverity, Report, Reportable, Section,
+    Table,
+};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fmt;
@@ -241,6 +244,26 @@ impl Reportable for ComparisonResult {
 
         report = report.section(Section::summary("Summary", summary_metrics));
 
+        let breakdown = ChartData::new(
+            ChartType::Doughnut,
+            "Sequences by change",
+            ["Added", "Removed", "Modified", "Renamed", "Unchanged"]
+                .iter()
+                .map(|kind| kind.to_string())
+                .collect(),
+        )
+        .with_dataset(
+            "Sequences",
+            vec![
+                self.added.len() as f64,
+                self.removed.len() as f64,
+                self.modified.len() as f64,
+                self.renamed.len() as f64,
+                self.unchanged_count as f64,
+            ],
+        );
+        report = report.section(Section::chart("Change Breakdown", breakdown));
+
         // Statistics section
         let stats_items = vec![
             (
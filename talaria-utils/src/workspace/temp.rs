@@ -360,7 +360,7 @@ pub fn list_workspaces(config: &WorkspaceConfig) -> Result<Vec<WorkspaceMetadata
     }
 
     // Sort by creation time (newest first)
-    workspaces.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    workspaces.sort_by_key(|b| std::cmp::Reverse(b.created_at));
 
     Ok(workspaces)
 }